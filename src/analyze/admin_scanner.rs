@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use anyhow::Result;
 use reqwest::Client;
 use serde::{Serialize, Deserialize};
@@ -86,7 +87,7 @@ pub async fn scan_admin_paths(client: &Client, base_url: &str) -> Result<Vec<Adm
         
         match client.get(&test_url)
             .timeout(std::time::Duration::from_secs(5))
-            .send()
+            .send_paced()
             .await 
         {
             Ok(resp) => {
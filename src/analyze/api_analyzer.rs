@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use anyhow::Result;
 use reqwest::Client;
 use std::collections::HashMap;
@@ -5,7 +6,10 @@ use serde::{Serialize, Deserialize};
 
 use super::security_headers::SecurityHeaderAnalysis;
 use super::fingerprint::TechnologyFingerprint;
-use super::cors_checker::CorsAnalysis;
+use super::cors_checker::{self, CorsAnalysis};
+
+/// Bytes of each body analyzed
+const BODY_LIMIT: usize = 2 * 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiAnalysis {
@@ -40,18 +44,17 @@ impl ApiAnalysis {
     pub async fn analyze(client: &Client, url: &str) -> Result<Self> {
         let start = std::time::Instant::now();
         
-        // Main request
-        let resp = client.get(url)
-            .timeout(std::time::Duration::from_secs(10))
-            .send()
+        // Main request; the probe's response is reused when it was read in full
+        let resp = crate::probe::response_cache::shared()
+            .fetch(client, client.get(url).timeout(std::time::Duration::from_secs(10)), BODY_LIMIT)
             .await?;
         
-        let status = resp.status().as_u16();
+        let status = resp.status;
         let response_time_ms = start.elapsed().as_millis() as u64;
         
         // Extract headers
         let mut headers = HashMap::new();
-        for (key, value) in resp.headers() {
+        for (key, value) in &resp.headers {
             if let Ok(v) = value.to_str() {
                 headers.insert(key.as_str().to_lowercase(), v.to_string());
             }
@@ -60,7 +63,7 @@ impl ApiAnalysis {
         let content_type = headers.get("content-type").cloned();
         
         // Get body
-        let body_bytes = resp.bytes().await?;
+        let body_bytes = resp.body;
         let response_size = body_bytes.len();
         let body = String::from_utf8_lossy(&body_bytes);
         
@@ -74,15 +77,17 @@ impl ApiAnalysis {
         // Analyze security headers
         let security_analysis = Some(SecurityHeaderAnalysis::analyze(&headers));
         
-        // Analyze CORS
-        let cors_analysis = Some(CorsAnalysis::analyze(&headers));
-        
         // Technology fingerprinting
         let technology = Some(TechnologyFingerprint::analyze(&headers, &body));
         
         // Test OPTIONS method
         let allowed_methods = test_options(client, url).await.unwrap_or_default();
         
+        // Analyze CORS: response headers plus real preflights
+        let state_changing = allowed_methods.iter().any(|m| matches!(m.to_ascii_uppercase().as_str(), "POST" | "PUT" | "DELETE" | "PATCH"));
+        let preflight = cors_checker::check_preflight(client, url).await;
+        let cors_analysis = Some(CorsAnalysis::analyze(&headers).with_preflight(preflight, state_changing));
+        
         // Determine accessibility
         let is_public = status >= 200 && status < 300;
         let requires_auth = status == 401 || status == 403;
@@ -134,7 +139,7 @@ impl ApiAnalysis {
 async fn test_options(client: &Client, url: &str) -> Result<Vec<String>> {
     match client.request(reqwest::Method::OPTIONS, url)
         .timeout(std::time::Duration::from_secs(5))
-        .send()
+        .send_paced()
         .await 
     {
         Ok(resp) => {
@@ -157,13 +162,14 @@ impl Serialize for SecurityHeaderAnalysis {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("SecurityHeaderAnalysis", 8)?;
+        let mut state = serializer.serialize_struct("SecurityHeaderAnalysis", 9)?;
         state.serialize_field("has_hsts", &self.has_hsts)?;
         state.serialize_field("has_csp", &self.has_csp)?;
         state.serialize_field("has_x_frame_options", &self.has_x_frame_options)?;
         state.serialize_field("has_x_content_type_options", &self.has_x_content_type_options)?;
         state.serialize_field("has_referrer_policy", &self.has_referrer_policy)?;
         state.serialize_field("has_permissions_policy", &self.has_permissions_policy)?;
+        state.serialize_field("csp", &self.csp)?;
         state.serialize_field("missing_headers", &self.missing_headers)?;
         state.serialize_field("security_score", &self.security_score)?;
         state.end()
@@ -185,7 +191,7 @@ impl Serialize for CorsAnalysis {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("CorsAnalysis", 9)?;
+        let mut state = serializer.serialize_struct("CorsAnalysis", 11)?;
         state.serialize_field("has_cors", &self.has_cors)?;
         state.serialize_field("allow_origin", &self.allow_origin)?;
         state.serialize_field("allow_credentials", &self.allow_credentials)?;
@@ -195,6 +201,8 @@ impl Serialize for CorsAnalysis {
         state.serialize_field("max_age", &self.max_age)?;
         state.serialize_field("is_misconfigured", &self.is_misconfigured)?;
         state.serialize_field("vulnerabilities", &self.vulnerabilities)?;
+        state.serialize_field("preflight", &self.preflight)?;
+        state.serialize_field("risk", &self.risk)?;
         state.end()
     }
 }
@@ -0,0 +1,256 @@
+//! Set-Cookie analysis
+//! Every cookie the probe results set is parsed once and checked centrally: the flags (`Secure`,
+//! `HttpOnly`, `SameSite`), the scope (`Domain` opening it to every subdomain, cookies set over plain
+//! HTTP), the `__Host-`/`__Secure-` prefixes (whose rules browsers enforce by dropping the cookie) and
+//! the lifetime. Flag checks that only matter for credentials (`HttpOnly`, `SameSite`, lifetime) apply to
+//! cookies whose name looks like a session or auth cookie; CSRF tokens are meant to be read by script.
+
+use crate::output::writer_jsonl::RawEvent;
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// Session cookies living longer than this are reported
+pub const MAX_SESSION_LIFETIME: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Name parts of session and auth cookies
+const SESSION_HINTS: &[&str] = &["sess", "sid", "auth", "token", "jwt", "login", "remember", "identity", "access", "refresh"];
+
+/// Name parts of CSRF tokens, which script has to read
+const CSRF_HINTS: &[&str] = &["csrf", "xsrf"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieCheck {
+    MissingSecure,
+    SetOverHttp,
+    MissingHttpOnly,
+    MissingSameSite,
+    SameSiteNone,
+    HostPrefixViolation,
+    SecurePrefixViolation,
+    BroadDomain,
+    LongLived,
+    NoPrefix,
+}
+
+impl CookieCheck {
+    pub fn label(self) -> &'static str {
+        match self {
+            CookieCheck::MissingSecure => "Missing Secure Flag",
+            CookieCheck::SetOverHttp => "Cookie Set Over HTTP",
+            CookieCheck::MissingHttpOnly => "Missing HTTPOnly Flag",
+            CookieCheck::MissingSameSite => "Missing SameSite Flag",
+            CookieCheck::SameSiteNone => "SameSite=None",
+            CookieCheck::HostPrefixViolation => "Invalid __Host- Cookie",
+            CookieCheck::SecurePrefixViolation => "Invalid __Secure- Cookie",
+            CookieCheck::BroadDomain => "Cookie Scoped To Parent Domain",
+            CookieCheck::LongLived => "Long-Lived Session Cookie",
+            CookieCheck::NoPrefix => "Session Cookie Without Prefix",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CookieIssue {
+    pub check: CookieCheck,
+    pub severity: String,
+    pub detail: String,
+}
+
+impl CookieIssue {
+    fn new(check: CookieCheck, severity: &str, detail: String) -> Self {
+        Self { check, severity: severity.to_string(), detail }
+    }
+}
+
+/// One `Set-Cookie` header; attribute names are matched case-insensitively, the last occurrence wins
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SetCookie {
+    pub name: String,
+    /// Left out of reports, it is the credential itself
+    #[serde(skip)]
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<i64>,
+    pub secure: bool,
+    pub http_only: bool,
+    /// `Strict`, `Lax` or `None` as sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+}
+
+impl SetCookie {
+    /// `None` for a header without `name=value`
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = SetCookie { name: name.to_string(), value: value.trim().to_string(), ..Default::default() };
+        for attr in parts {
+            let (key, val) = attr.split_once('=').map_or((attr.trim(), ""), |(k, v)| (k.trim(), v.trim()));
+            match key.to_ascii_lowercase().as_str() {
+                "domain" if !val.is_empty() => cookie.domain = Some(val.trim_start_matches('.').to_ascii_lowercase()),
+                "path" if val.starts_with('/') => cookie.path = Some(val.to_string()),
+                "expires" => cookie.expires = Some(val.to_string()),
+                "max-age" => cookie.max_age = val.parse().ok().or(cookie.max_age),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => cookie.same_site = Some(val.to_string()),
+                _ => {}
+            }
+        }
+        Some(cookie)
+    }
+
+    /// Cookies of a `RawEvent::headers` `set-cookie` value (one per line)
+    pub fn parse_all(joined: &str) -> Vec<Self> {
+        joined.lines().filter_map(Self::parse).collect()
+    }
+
+    /// Seconds the cookie lives from `now`; `None` for a session cookie. `Max-Age` wins over `Expires`.
+    pub fn lifetime(&self, now: SystemTime) -> Option<i64> {
+        if let Some(max_age) = self.max_age {
+            return Some(max_age);
+        }
+        // Cookie dates often use dashes (`Wed, 21-Oct-2026 07:28:00 GMT`)
+        let expires = httpdate::parse_http_date(&self.expires.as_ref()?.replace('-', " ")).ok()?;
+        Some(match expires.duration_since(now) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        })
+    }
+
+    /// A session or auth cookie by its name (CSRF tokens are not)
+    pub fn is_session(&self) -> bool {
+        let name = self.name.to_ascii_lowercase();
+        !CSRF_HINTS.iter().any(|h| name.contains(h)) && SESSION_HINTS.iter().any(|h| name.contains(h))
+    }
+}
+
+fn host_of(url: &str) -> Option<String> {
+    reqwest::Url::parse(url).ok()?.host_str().map(str::to_ascii_lowercase)
+}
+
+/// Issues of a cookie set by a response from `url`
+pub fn check_cookie(cookie: &SetCookie, url: &str, now: SystemTime) -> Vec<CookieIssue> {
+    let mut issues = Vec::new();
+    let session = cookie.is_session();
+    let https = url.starts_with("https://");
+    let name = &cookie.name;
+
+    if name.starts_with("__Host-") {
+        let mut broken = Vec::new();
+        if !cookie.secure {
+            broken.push("no Secure");
+        }
+        if cookie.domain.is_some() {
+            broken.push("a Domain");
+        }
+        if cookie.path.as_deref() != Some("/") {
+            broken.push("no Path=/");
+        }
+        if !broken.is_empty() {
+            issues.push(CookieIssue::new(CookieCheck::HostPrefixViolation, "MEDIUM", format!("{} has {}: browsers drop the cookie", name, broken.join(", "))));
+        }
+    } else if name.starts_with("__Secure-") && !cookie.secure {
+        issues.push(CookieIssue::new(CookieCheck::SecurePrefixViolation, "MEDIUM", format!("{} has no Secure: browsers drop the cookie", name)));
+    } else if session && https {
+        issues.push(CookieIssue::new(CookieCheck::NoPrefix, "INFO", format!("{} could use the __Host- prefix to be bound to this host over HTTPS", name)));
+    }
+
+    if !https {
+        if session {
+            issues.push(CookieIssue::new(CookieCheck::SetOverHttp, "MEDIUM", format!("{} is set over plain HTTP and can be read or replaced in transit", name)));
+        }
+    } else if !cookie.secure && !name.starts_with("__Host-") && !name.starts_with("__Secure-") {
+        let severity = if session { "MEDIUM" } else { "LOW" };
+        issues.push(CookieIssue::new(CookieCheck::MissingSecure, severity, format!("{} has no Secure flag and is also sent over plain HTTP", name)));
+    }
+
+    if session && !cookie.http_only {
+        issues.push(CookieIssue::new(CookieCheck::MissingHttpOnly, "MEDIUM", format!("{} has no HttpOnly flag: XSS can steal the session", name)));
+    }
+    match cookie.same_site.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        None if session => issues.push(CookieIssue::new(CookieCheck::MissingSameSite, "LOW", format!("{} has no SameSite attribute (Lax by default only in some browsers)", name))),
+        Some("none") if !cookie.secure => issues.push(CookieIssue::new(CookieCheck::SameSiteNone, "LOW", format!("{} has SameSite=None without Secure: browsers reject it", name))),
+        Some("none") if session => issues.push(CookieIssue::new(CookieCheck::SameSiteNone, "MEDIUM", format!("{} has SameSite=None: cross-site requests carry the session (CSRF)", name))),
+        _ => {}
+    }
+
+    if let (Some(domain), Some(host)) = (&cookie.domain, host_of(url)) {
+        if session && host != *domain && host.ends_with(&format!(".{}", domain)) {
+            issues.push(CookieIssue::new(CookieCheck::BroadDomain, "LOW", format!("{} is scoped to {} and sent to every subdomain of it", name, domain)));
+        }
+    }
+
+    if session {
+        if let Some(lifetime) = cookie.lifetime(now).filter(|l| *l > MAX_SESSION_LIFETIME.as_secs() as i64) {
+            issues.push(CookieIssue::new(CookieCheck::LongLived, "LOW", format!("{} lives {} days", name, lifetime / 86400)));
+        }
+    }
+    issues
+}
+
+/// Worst severity among the issues
+pub fn severity(issues: &[CookieIssue]) -> &str {
+    ["HIGH", "MEDIUM", "LOW"].into_iter().find(|s| issues.iter().any(|i| i.severity == *s)).unwrap_or("INFO")
+}
+
+/// One cookie per origin, with the responses that set it
+#[derive(Debug, Clone, Serialize)]
+pub struct CookieReport {
+    pub origin: String,
+    pub cookie: SetCookie,
+    pub session: bool,
+    /// Seconds from the scan; absent for a session cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifetime: Option<i64>,
+    pub issues: Vec<CookieIssue>,
+    pub urls: Vec<String>,
+}
+
+impl CookieReport {
+    pub fn severity(&self) -> &str {
+        severity(&self.issues)
+    }
+}
+
+/// Responses kept per report
+const MAX_URLS: usize = 5;
+
+/// Check the cookies set across the probe results, once per origin and cookie name (the first response
+/// that sets it is analyzed)
+pub fn analyze_events(results: &[RawEvent], now: SystemTime) -> Vec<CookieReport> {
+    let mut reports: Vec<CookieReport> = Vec::new();
+    for e in results {
+        let Some(header) = e.header("set-cookie") else { continue };
+        let origin = crate::probe::soft404::origin_of(&e.final_url).unwrap_or_default();
+        for cookie in SetCookie::parse_all(header) {
+            match reports.iter_mut().find(|r| r.origin == origin && r.cookie.name == cookie.name) {
+                Some(r) => {
+                    if r.urls.len() < MAX_URLS && !r.urls.contains(&e.final_url) {
+                        r.urls.push(e.final_url.clone());
+                    }
+                }
+                None => reports.push(CookieReport {
+                    origin: origin.clone(),
+                    session: cookie.is_session(),
+                    lifetime: cookie.lifetime(now),
+                    issues: check_cookie(&cookie, &e.final_url, now),
+                    cookie,
+                    urls: vec![e.final_url.clone()],
+                }),
+            }
+        }
+    }
+    reports
+}
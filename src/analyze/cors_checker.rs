@@ -1,5 +1,30 @@
+use crate::http_client::Paced;
+use reqwest::Client;
+use serde::Serialize;
 use std::collections::HashMap;
 
+use super::risk_classifier::{RiskClassification, RiskClassifier};
+
+/// Origin sent with preflights; a host nobody owns
+pub const PROBE_ORIGIN: &str = "https://apihunter-cors.example";
+
+/// Request header no application expects, to see whether requested headers are echoed back
+pub const PROBE_HEADER: &str = "x-apihunter-probe";
+
+/// Preflights sent per endpoint: method, `Access-Control-Request-Headers`, origin
+pub const PREFLIGHTS: &[(&str, &[&str], &str)] = &[
+    ("PUT", &["content-type"], PROBE_ORIGIN),
+    ("DELETE", &["authorization"], PROBE_ORIGIN),
+    ("PATCH", &[PROBE_HEADER], PROBE_ORIGIN),
+    ("PUT", &["content-type"], "null"),
+];
+
+/// Methods a browser sends without asking
+const SIMPLE_METHODS: &[&str] = &["GET", "HEAD", "POST"];
+
+/// Preflight cache lifetime (seconds) above which a permissive answer sticks around
+const LONG_MAX_AGE: u64 = 86400;
+
 #[derive(Debug, Clone)]
 pub struct CorsAnalysis {
     pub has_cors: bool,
@@ -11,6 +36,10 @@ pub struct CorsAnalysis {
     pub max_age: Option<String>,
     pub is_misconfigured: bool,
     pub vulnerabilities: Vec<String>,
+    /// Answers to real preflights, when they were sent
+    pub preflight: Option<PreflightAnalysis>,
+    /// Score of the simple-request headers and the preflights together
+    pub risk: Option<RiskClassification>,
 }
 
 impl CorsAnalysis {
@@ -82,6 +111,167 @@ impl CorsAnalysis {
             max_age,
             is_misconfigured,
             vulnerabilities,
+            preflight: None,
+            risk: None,
+        }
+    }
+
+    /// Add the preflight results: their findings join the simple-request ones and both are scored with
+    /// `RiskClassifier::classify_cors_issue`
+    pub fn with_preflight(mut self, preflight: PreflightAnalysis, has_sensitive_operations: bool) -> Self {
+        if !preflight.findings.is_empty() {
+            self.is_misconfigured = true;
+            self.vulnerabilities.extend(preflight.findings.iter().cloned());
+        }
+        let dangerous = |m: &String| matches!(m.to_ascii_uppercase().as_str(), "DELETE" | "PUT" | "PATCH");
+        let risk = RiskClassifier::classify_cors_issue(
+            self.allow_origin.as_deref() == Some("*"),
+            self.allow_credentials,
+            self.allow_methods.iter().any(dangerous),
+            self.allow_origin.as_deref() == Some("null"),
+            has_sensitive_operations,
+            Some(&preflight),
+        );
+        self.risk = Some(risk);
+        self.preflight = Some(preflight);
+        self
+    }
+}
+
+/// One preflight and its answer
+#[derive(Debug, Clone, Serialize)]
+pub struct Preflight {
+    pub method: String,
+    pub request_headers: Vec<String>,
+    pub origin: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_origin: Option<String>,
+    pub allow_credentials: bool,
+    pub allow_methods: Vec<String>,
+    pub allow_headers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+    /// A browser would go on with the actual request
+    pub accepted: bool,
+}
+
+impl Preflight {
+    /// Evaluate a preflight answer the way a browser does
+    pub fn evaluate(method: &str, request_headers: &[&str], origin: &str, status: u16, headers: &HashMap<String, String>) -> Self {
+        let list = |name: &str| headers.get(name)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let allow_origin = headers.get("access-control-allow-origin").map(|v| v.trim().to_string());
+        let allow_credentials = headers.get("access-control-allow-credentials").is_some_and(|v| v.trim().eq_ignore_ascii_case("true"));
+        let allow_methods = list("access-control-allow-methods");
+        let allow_headers = list("access-control-allow-headers");
+        let max_age = headers.get("access-control-max-age").and_then(|v| v.trim().parse().ok());
+
+        // `*` is only a wildcard for requests without credentials
+        let wildcard = |values: &[String]| !allow_credentials && values.iter().any(|v| v == "*");
+        let origin_ok = match allow_origin.as_deref() {
+            Some("*") => !allow_credentials,
+            Some(o) => o == origin,
+            None => false,
+        };
+        let method_ok = SIMPLE_METHODS.contains(&method)
+            || wildcard(&allow_methods)
+            || allow_methods.iter().any(|m| m.eq_ignore_ascii_case(method));
+        let headers_ok = request_headers.iter().all(|h| {
+            wildcard(&allow_headers) && !h.eq_ignore_ascii_case("authorization")
+                || allow_headers.iter().any(|a| a.eq_ignore_ascii_case(h))
+        });
+        Self {
+            method: method.to_string(),
+            request_headers: request_headers.iter().map(|h| h.to_string()).collect(),
+            origin: origin.to_string(),
+            status,
+            accepted: (200..300).contains(&status) && origin_ok && method_ok && headers_ok,
+            allow_origin,
+            allow_credentials,
+            allow_methods,
+            allow_headers,
+            max_age,
+        }
+    }
+}
+
+/// What the preflights of one endpoint allow
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreflightAnalysis {
+    pub preflights: Vec<Preflight>,
+    /// A foreign origin gets its preflights accepted
+    pub arbitrary_origin: bool,
+    /// `Origin: null` (sandboxed iframes, `data:` URLs) gets its preflights accepted
+    pub null_origin: bool,
+    /// An accepted preflight also allows credentials
+    pub credentials: bool,
+    /// PUT, DELETE or PATCH accepted from a foreign or `null` origin
+    pub dangerous_methods: Vec<String>,
+    /// Requested headers are echoed into `Access-Control-Allow-Headers` (or `*`)
+    pub any_header: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+    pub findings: Vec<String>,
+}
+
+impl PreflightAnalysis {
+    /// Summarize the preflights in the `SEVERITY: text` form of the simple-request findings
+    pub fn from_preflights(preflights: Vec<Preflight>) -> Self {
+        let accepted: Vec<&Preflight> = preflights.iter().filter(|p| p.accepted).collect();
+        let arbitrary_origin = accepted.iter().any(|p| p.origin == PROBE_ORIGIN);
+        let null_origin = accepted.iter().any(|p| p.origin == "null");
+        let credentials = accepted.iter().any(|p| p.allow_credentials);
+        let mut dangerous_methods: Vec<String> = Vec::new();
+        for p in &accepted {
+            if !SIMPLE_METHODS.contains(&p.method.as_str()) && !dangerous_methods.contains(&p.method) {
+                dangerous_methods.push(p.method.clone());
+            }
+        }
+        let any_header = preflights.iter().any(|p| p.allow_headers.iter().any(|h| h == "*" || h.eq_ignore_ascii_case(PROBE_HEADER)));
+        let max_age = accepted.iter().filter_map(|p| p.max_age).max();
+
+        let mut findings = Vec::new();
+        let methods = dangerous_methods.join(", ");
+        if arbitrary_origin && credentials {
+            findings.push(format!("CRITICAL: Preflight accepts any origin with credentials - cross-origin {} with the victim's session", methods));
+        } else if arbitrary_origin && !dangerous_methods.is_empty() {
+            findings.push(format!("WARNING: Preflight accepts any origin for {}", methods));
+        }
+        if null_origin {
+            findings.push(format!("WARNING: Preflight accepts Origin: null{} - exploitable from sandboxed iframes", if credentials { " with credentials" } else { "" }));
+        }
+        if any_header && (arbitrary_origin || null_origin) {
+            findings.push("INFO: Preflight allows any request header".to_string());
+        }
+        if max_age.is_some_and(|a| a > LONG_MAX_AGE) && !findings.is_empty() {
+            findings.push(format!("INFO: Permissive preflight cached for {}s", max_age.unwrap_or_default()));
+        }
+        Self { preflights, arbitrary_origin, null_origin, credentials, dangerous_methods, any_header, max_age, findings }
+    }
+}
+
+/// Send the `PREFLIGHTS` to `url` and evaluate the answers
+pub async fn check_preflight(client: &Client, url: &str) -> PreflightAnalysis {
+    let mut preflights = Vec::new();
+    for (method, request_headers, origin) in PREFLIGHTS {
+        let mut req = client.request(reqwest::Method::OPTIONS, url)
+            .timeout(std::time::Duration::from_secs(5))
+            .header("Origin", *origin)
+            .header("Access-Control-Request-Method", *method);
+        if !request_headers.is_empty() {
+            req = req.header("Access-Control-Request-Headers", request_headers.join(","));
+        }
+        let Ok(resp) = req.send_paced().await else { continue };
+        let status = resp.status().as_u16();
+        let mut headers = HashMap::new();
+        for (key, value) in resp.headers() {
+            if let Ok(v) = value.to_str() {
+                headers.insert(key.as_str().to_lowercase(), v.to_string());
+            }
         }
+        preflights.push(Preflight::evaluate(method, request_headers, origin, status, &headers));
     }
+    PreflightAnalysis::from_preflights(preflights)
 }
@@ -0,0 +1,272 @@
+//! Content-Security-Policy analysis
+//! The policy is parsed into directives and graded by what it still lets a script injection do: script
+//! sources that allow `'unsafe-inline'`, `'unsafe-eval'`, wildcards, whole schemes (`https:`, `data:`)
+//! or hosts that serve JSONP endpoints and old AngularJS builds (a known CSP bypass: any page can load a
+//! callback or a template gadget from them), plus missing `object-src`, `base-uri` and
+//! `frame-ancestors`. Nonces and hashes make browsers ignore `'unsafe-inline'`, and `'strict-dynamic'`
+//! makes them ignore host and scheme sources, so those are only reported when they still apply.
+//! Grades run from A (nothing found) over D (a script bypass is open) to F (no policy, or one that
+//! restricts no script at all).
+
+use crate::output::writer_jsonl::RawEvent;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Hosts that serve JSONP endpoints or AngularJS, usable to run script under a policy that allows them
+pub const BYPASSABLE_HOSTS: &[&str] = &[
+    "www.google.com",
+    "accounts.google.com",
+    "*.google.com",
+    "ajax.googleapis.com",
+    "*.googleapis.com",
+    "www.gstatic.com",
+    "*.gstatic.com",
+    "cdnjs.cloudflare.com",
+    "cdn.jsdelivr.net",
+    "unpkg.com",
+    "raw.githubusercontent.com",
+    "*.github.io",
+    "api.twitter.com",
+    "*.twitter.com",
+    "www.youtube.com",
+    "*.yandex.ru",
+    "*.vk.com",
+    "*.doubleclick.net",
+    "*.google-analytics.com",
+    "*.facebook.com",
+    "*.cloudfront.net",
+    "*.amazonaws.com",
+    "*.azureedge.net",
+    "*.herokuapp.com",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CspCheck {
+    Missing,
+    ReportOnly,
+    NoScriptRestriction,
+    UnsafeInline,
+    UnsafeEval,
+    WildcardSource,
+    SchemeSource,
+    InsecureSource,
+    BypassableHost,
+    MissingObjectSrc,
+    MissingBaseUri,
+    MissingFrameAncestors,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CspIssue {
+    pub check: CspCheck,
+    pub severity: String,
+    /// Directive the issue is in (the one that applies, e.g. `default-src` when `script-src` is absent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directive: Option<String>,
+    pub detail: String,
+}
+
+impl CspIssue {
+    fn new(check: CspCheck, severity: &str, directive: Option<&str>, detail: String) -> Self {
+        Self { check, severity: severity.to_string(), directive: directive.map(str::to_string), detail }
+    }
+}
+
+/// Parsed policy; directive names lower-cased, the first occurrence of a directive wins
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CspPolicy {
+    pub directives: BTreeMap<String, Vec<String>>,
+}
+
+impl CspPolicy {
+    pub fn parse(value: &str) -> Self {
+        let mut directives = BTreeMap::new();
+        for part in value.split(';') {
+            let mut tokens = part.split_ascii_whitespace();
+            let Some(name) = tokens.next() else { continue };
+            directives.entry(name.to_ascii_lowercase()).or_insert_with(|| tokens.map(str::to_string).collect());
+        }
+        Self { directives }
+    }
+
+    pub fn get(&self, directive: &str) -> Option<&[String]> {
+        self.directives.get(directive).map(Vec::as_slice)
+    }
+
+    /// The directive that governs `directive` through the fallback chain, with its sources
+    pub fn effective(&self, directive: &str) -> Option<(&str, &[String])> {
+        let chain: &[&str] = match directive {
+            "script-src-elem" | "script-src-attr" => &[directive, "script-src", "default-src"],
+            _ => &[directive, "default-src"],
+        };
+        chain.iter().find_map(|d| self.directives.get_key_value(*d)).map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+}
+
+fn has(sources: &[String], keyword: &str) -> bool {
+    sources.iter().any(|s| s.eq_ignore_ascii_case(keyword))
+}
+
+/// Host of a source expression (`https://cdn.example.com/js/` -> `cdn.example.com`)
+fn source_host(source: &str) -> Option<&str> {
+    if source.starts_with('\'') || source.ends_with(':') {
+        return None;
+    }
+    let rest = source.split_once("://").map_or(source, |(_, r)| r);
+    let host = rest.split(['/', ':']).next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+/// Whether an allowed host source covers a known bypassable host
+pub fn bypassable(host: &str) -> Option<&'static str> {
+    let host = host.to_ascii_lowercase();
+    BYPASSABLE_HOSTS.iter().copied().find(|b| {
+        match (b.strip_prefix("*."), host.strip_prefix("*.")) {
+            // The policy allows a whole domain that a bypassable host lives in, or vice versa
+            (_, Some(domain)) => b.trim_start_matches("*.") == domain || b.ends_with(&format!(".{}", domain)),
+            (Some(domain), None) => host == domain || host.ends_with(&format!(".{}", domain)),
+            (None, None) => host == *b,
+        }
+    })
+}
+
+/// Issues of a policy that is enforced (`report_only` = sent as `Content-Security-Policy-Report-Only`)
+pub fn check_policy(policy: &CspPolicy, report_only: bool) -> Vec<CspIssue> {
+    let mut issues = Vec::new();
+    if report_only {
+        issues.push(CspIssue::new(CspCheck::ReportOnly, "MEDIUM", None, "The policy is only reported, not enforced".to_string()));
+    }
+
+    match policy.effective("script-src") {
+        None => issues.push(CspIssue::new(CspCheck::NoScriptRestriction, "HIGH", None, "Neither script-src nor default-src: scripts from anywhere are allowed".to_string())),
+        Some((directive, sources)) => {
+            let hashed = sources.iter().any(|s| {
+                let s = s.to_ascii_lowercase();
+                s.starts_with("'nonce-") || s.starts_with("'sha256-") || s.starts_with("'sha384-") || s.starts_with("'sha512-")
+            });
+            let strict_dynamic = has(sources, "'strict-dynamic'");
+            if has(sources, "'unsafe-inline'") && !hashed {
+                issues.push(CspIssue::new(CspCheck::UnsafeInline, "HIGH", Some(directive), "'unsafe-inline' allows inline scripts and event handlers: injected markup runs".to_string()));
+            }
+            if has(sources, "'unsafe-eval'") {
+                issues.push(CspIssue::new(CspCheck::UnsafeEval, "MEDIUM", Some(directive), "'unsafe-eval' allows eval() and new Function() on attacker-influenced strings".to_string()));
+            }
+            // 'strict-dynamic' makes browsers ignore host and scheme sources
+            if !strict_dynamic {
+                for source in sources {
+                    let lower = source.to_ascii_lowercase();
+                    if lower == "*" {
+                        issues.push(CspIssue::new(CspCheck::WildcardSource, "HIGH", Some(directive), "'*' allows scripts from any host".to_string()));
+                    } else if matches!(lower.as_str(), "https:" | "http:" | "data:" | "blob:" | "filesystem:") {
+                        issues.push(CspIssue::new(CspCheck::SchemeSource, "HIGH", Some(directive), format!("'{}' allows scripts from any URL of that scheme", source)));
+                    } else if let Some(host) = source_host(source) {
+                        if lower.starts_with("http://") {
+                            issues.push(CspIssue::new(CspCheck::InsecureSource, "MEDIUM", Some(directive), format!("{} is loaded over plain HTTP and can be replaced in transit", source)));
+                        }
+                        if let Some(known) = bypassable(host) {
+                            issues.push(CspIssue::new(CspCheck::BypassableHost, "HIGH", Some(directive), format!("{} allows {}, which hosts JSONP endpoints or AngularJS usable to run script", source, known)));
+                        } else if host == "*" {
+                            issues.push(CspIssue::new(CspCheck::WildcardSource, "HIGH", Some(directive), format!("'{}' allows scripts from any host", source)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if policy.effective("object-src").is_none_or(|(_, s)| !has(s, "'none'")) {
+        issues.push(CspIssue::new(CspCheck::MissingObjectSrc, "MEDIUM", None, "object-src is not 'none': plugins (<object>, <embed>) can load script".to_string()));
+    }
+    if policy.get("base-uri").is_none() {
+        issues.push(CspIssue::new(CspCheck::MissingBaseUri, "LOW", None, "No base-uri: an injected <base> tag redirects relative script URLs".to_string()));
+    }
+    if policy.get("frame-ancestors").is_none() {
+        issues.push(CspIssue::new(CspCheck::MissingFrameAncestors, "LOW", None, "No frame-ancestors: the page can be framed unless X-Frame-Options forbids it".to_string()));
+    }
+    issues
+}
+
+/// A (no issues) to F (no policy, or no effective script restriction); a policy that is only reported or
+/// leaves a script bypass open gets a D
+pub fn grade(issues: &[CspIssue]) -> char {
+    let any = |checks: &[CspCheck]| issues.iter().any(|i| checks.contains(&i.check));
+    let severity = |s: &str| issues.iter().any(|i| i.severity == s);
+    if any(&[CspCheck::Missing, CspCheck::NoScriptRestriction]) {
+        'F'
+    } else if severity("HIGH") || any(&[CspCheck::ReportOnly]) {
+        'D'
+    } else if severity("MEDIUM") {
+        'C'
+    } else if !issues.is_empty() {
+        'B'
+    } else {
+        'A'
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CspAnalysis {
+    /// The header value; `None` when the response has no policy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+    pub report_only: bool,
+    pub grade: char,
+    pub issues: Vec<CspIssue>,
+}
+
+impl CspAnalysis {
+    /// Grade the policy in lower-cased response headers; an enforced policy wins over a report-only one
+    pub fn analyze(headers: &HashMap<String, String>) -> Self {
+        let enforced = headers.get("content-security-policy");
+        let (policy, report_only) = match enforced.or_else(|| headers.get("content-security-policy-report-only")) {
+            Some(p) => (p, enforced.is_none()),
+            None => {
+                let issues = vec![CspIssue::new(CspCheck::Missing, "MEDIUM", None, "No Content-Security-Policy: injected scripts run unrestricted".to_string())];
+                return Self { policy: None, report_only: false, grade: grade(&issues), issues };
+            }
+        };
+        let issues = check_policy(&CspPolicy::parse(policy), report_only);
+        Self { policy: Some(policy.clone()), report_only, grade: grade(&issues), issues }
+    }
+
+    /// Worst severity among the issues
+    pub fn severity(&self) -> &str {
+        ["HIGH", "MEDIUM", "LOW"].into_iter().find(|s| self.issues.iter().any(|i| i.severity == *s)).unwrap_or("INFO")
+    }
+}
+
+/// One distinct policy (or its absence on one origin) and the pages that send it
+#[derive(Debug, Clone, Serialize)]
+pub struct CspReport {
+    #[serde(flatten)]
+    pub analysis: CspAnalysis,
+    pub urls: Vec<String>,
+}
+
+/// Pages kept per report
+const MAX_URLS: usize = 5;
+
+/// Grade the policies of the HTML pages among the probe results. Pages sharing a policy are reported
+/// together; pages without one once per origin. API responses are left out, a policy protects documents.
+pub fn analyze_events(results: &[RawEvent]) -> Vec<CspReport> {
+    let mut reports: Vec<(String, CspReport)> = Vec::new();
+    for e in results {
+        let html = e.content_type.as_deref().is_some_and(|c| c.to_ascii_lowercase().contains("text/html"));
+        if !html || !(200..300).contains(&e.status) {
+            continue;
+        }
+        let headers: HashMap<String, String> = e.headers.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        let analysis = CspAnalysis::analyze(&headers);
+        let key = match &analysis.policy {
+            Some(p) => format!("{}|{}", analysis.report_only, p),
+            None => format!("missing|{}", crate::probe::soft404::origin_of(&e.final_url).unwrap_or_default()),
+        };
+        match reports.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, r)) if r.urls.len() < MAX_URLS => r.urls.push(e.final_url.clone()),
+            Some(_) => {}
+            None => reports.push((key, CspReport { analysis, urls: vec![e.final_url.clone()] })),
+        }
+    }
+    reports.into_iter().map(|(_, r)| r).collect()
+}
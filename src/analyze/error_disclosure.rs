@@ -0,0 +1,253 @@
+//! Verbose errors and debug pages
+//! Error responses are matched against the debug pages and stack trace formats of common frameworks:
+//! Werkzeug's interactive debugger, Django with `DEBUG = True`, Laravel Ignition/Whoops and its JSON debug
+//! errors, Symfony, Rails, ASP.NET, Spring (Whitelabel page, Boot JSON errors with `trace`), Express, and
+//! plain Java/Python/.NET/Node/Go/PHP traces. What leaked is extracted: file system paths, framework and
+//! runtime versions, and the names of credentials shown in environment or settings dumps. Severity follows
+//! the kind of page and what it gave away.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::output::writer_jsonl::RawEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framework {
+    WerkzeugDebugger,
+    Django,
+    Laravel,
+    Symfony,
+    Rails,
+    AspNet,
+    Spring,
+    Express,
+    Java,
+    Python,
+    DotNet,
+    Node,
+    Go,
+    Php,
+}
+
+impl Framework {
+    pub fn name(self) -> &'static str {
+        match self {
+            Framework::WerkzeugDebugger => "Werkzeug debugger",
+            Framework::Django => "Django",
+            Framework::Laravel => "Laravel",
+            Framework::Symfony => "Symfony",
+            Framework::Rails => "Rails",
+            Framework::AspNet => "ASP.NET",
+            Framework::Spring => "Spring",
+            Framework::Express => "Express",
+            Framework::Java => "Java",
+            Framework::Python => "Python",
+            Framework::DotNet => ".NET",
+            Framework::Node => "Node.js",
+            Framework::Go => "Go",
+            Framework::Php => "PHP",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisclosureKind {
+    /// A debugger that runs code (Werkzeug console)
+    DebugConsole,
+    /// A framework debug page: trace, source, request and settings
+    DebugPage,
+    StackTrace,
+    /// A framework error page without a trace
+    ErrorPage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorDisclosure {
+    pub framework: Framework,
+    pub kind: DisclosureKind,
+    pub severity: String,
+    /// File system paths from the trace
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Framework and runtime versions, e.g. `Django 4.2.1`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+    /// Names of credentials shown with a value (`DB_PASSWORD`, `APP_KEY`, ...)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub credentials: Vec<String>,
+    /// The text that identified the page
+    pub evidence: String,
+}
+
+/// Signatures, most specific first: the first match names the framework
+static SIGNATURES: Lazy<Vec<(Framework, DisclosureKind, Regex)>> = Lazy::new(|| {
+    use DisclosureKind::*;
+    use Framework::*;
+    [
+        (WerkzeugDebugger, DebugConsole, r"Werkzeug Debugger|The debugger caught an exception in your WSGI application|__debugger__=yes"),
+        (Django, DebugPage, r"You're seeing this error because you have <code>DEBUG = True</code>|Django Version:|Using the URLconf defined in"),
+        (Laravel, DebugPage, r"Whoops! There was an error|window\.ignite\(|_ignition/|Illuminate\\[A-Za-z\\]+Exception"),
+        (Symfony, DebugPage, r"Symfony\\Component\\[A-Za-z\\]+Exception|sf-dump|Symfony Exception"),
+        (Rails, DebugPage, r"Action Controller: Exception caught|Rails\.root:"),
+        (AspNet, DebugPage, r"Server Error in '[^']*' Application|ASP\.NET is configured to show verbose error messages"),
+        (Spring, StackTrace, r"org\.springframework\.[\w.]+Exception"),
+        (Spring, ErrorPage, r"Whitelabel Error Page"),
+        (Express, StackTrace, r"at Layer\.handle \[as handle_request\]|node_modules[/\\]express[/\\]"),
+        (Java, StackTrace, r"\bat [\w$.]+\([\w$]+\.java:\d+\)|Exception in thread "),
+        (Python, StackTrace, r"Traceback \(most recent call last\):"),
+        (DotNet, StackTrace, r"\bat [\w.`<>]+\(.*\) in .+:line \d+"),
+        (Node, StackTrace, r"\bat .+ \((?:/|[A-Z]:\\|node:)[^)]*:\d+:\d+\)"),
+        (Go, StackTrace, r"goroutine \d+ \[running\]:|panic: runtime error"),
+        (Php, StackTrace, r"(?:Fatal error|Parse error|Warning|Notice|Uncaught \w+)(?:</b>)?:.{0,300}? in (?:<b>)?[/\w\\:.\- ]+\.php(?:</b>)? on line"),
+    ]
+    .into_iter()
+    .map(|(f, k, p)| (f, k, Regex::new(p).unwrap()))
+    .collect()
+});
+
+static PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:/(?:var|home|usr|opt|srv|app|www|root|tmp|Users|data|code|src)/[\w.@\-/]+|\b[A-Z]:\\(?:[\w.\- ]+\\)+[\w.\-]+)"#).unwrap()
+});
+
+static VERSION_RES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        ("Django", r"Django Version:\s*(?:</th>\s*<td>\s*)?(\d+\.\d+(?:\.\d+)?)"),
+        ("Python", r"Python Version:\s*(?:</th>\s*<td>\s*)?(\d+\.\d+(?:\.\d+)?)"),
+        ("Werkzeug", r"Werkzeug/(\d+\.\d+(?:\.\d+)?)"),
+        ("Laravel", r"Laravel (?:v|Framework )?(\d+\.\d+(?:\.\d+)?)"),
+        ("PHP", r"PHP(?:/| Version:?\s*| )(\d+\.\d+\.\d+)"),
+        ("Rails", r"Rails (\d+\.\d+(?:\.\d+)?)"),
+        ("Ruby", r"Ruby (\d+\.\d+(?:\.\d+)?)"),
+        ("ASP.NET", r"ASP\.NET Version:\s*(\d+\.\d+(?:\.\d+)*)"),
+        (".NET Framework", r"\.NET Framework Version:\s*(\d+\.\d+(?:\.\d+)*)"),
+        ("Spring", r"spring-(?:core|webmvc|web|boot)-(\d+\.\d+\.\d+)"),
+        ("Symfony", r"Symfony (\d+\.\d+(?:\.\d+)?)"),
+        ("Node.js", r"Node\.js v(\d+\.\d+\.\d+)"),
+    ]
+    .into_iter()
+    .map(|(n, p)| (n, Regex::new(p).unwrap()))
+    .collect()
+});
+
+/// Credential names followed by a value (masked values like `********` don't count)
+static CREDENTIAL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(DB_PASSWORD|DATABASE_URL|APP_KEY|SECRET_KEY|AWS_SECRET_ACCESS_KEY|MAIL_PASSWORD|REDIS_PASSWORD|JWT_SECRET|STRIPE_SECRET)\b(?:\s*</t[dh]>\s*<t[dh][^>]*>\s*|["']?\s*[:=]\s*["']?|\s+)(?:<[^>]+>)?\s*["']?([^\s<"'*]{6,})"#).unwrap()
+});
+
+/// Paths and versions kept per page
+const MAX_ITEMS: usize = 10;
+
+fn severity(kind: DisclosureKind, leaked: bool, credentials: bool) -> &'static str {
+    match kind {
+        DisclosureKind::DebugConsole => "CRITICAL",
+        DisclosureKind::DebugPage if credentials => "CRITICAL",
+        DisclosureKind::DebugPage => "HIGH",
+        _ if credentials => "HIGH",
+        DisclosureKind::StackTrace if leaked => "MEDIUM",
+        _ => "LOW",
+    }
+}
+
+fn push_unique(out: &mut Vec<String>, value: String) {
+    if out.len() < MAX_ITEMS && !out.contains(&value) {
+        out.push(value);
+    }
+}
+
+/// Classify a response body; `None` if it is no error page or trace
+pub fn classify(body: &str) -> Option<ErrorDisclosure> {
+    let (framework, kind, m) = SIGNATURES.iter().find_map(|(f, k, re)| re.find(body).map(|m| (*f, *k, m)))?;
+    Some(with_leaks(framework, kind, m.as_str(), body))
+}
+
+/// Classify a JSON error body: Laravel (`APP_DEBUG`) and Spring Boot (`include-stacktrace`) debug errors by
+/// their fields, else the string values as text
+pub fn classify_json(v: &Value) -> Option<ErrorDisclosure> {
+    let mut text = String::new();
+    collect_strings(v, &mut text);
+    let has = |keys: &[&str]| v.as_object().is_some_and(|o| keys.iter().all(|k| o.contains_key(*k)));
+    if has(&["exception", "file", "line", "trace"]) {
+        return Some(with_leaks(Framework::Laravel, DisclosureKind::DebugPage, "\"exception\", \"file\", \"line\", \"trace\"", &text));
+    }
+    if has(&["timestamp", "status", "error", "path", "trace"]) {
+        return Some(with_leaks(Framework::Spring, DisclosureKind::StackTrace, "\"timestamp\", \"status\", \"error\", \"path\", \"trace\"", &text));
+    }
+    classify(&text)
+}
+
+fn collect_strings(v: &Value, out: &mut String) {
+    match v {
+        Value::String(s) => {
+            out.push_str(s);
+            out.push('\n');
+        }
+        Value::Array(items) => items.iter().for_each(|i| collect_strings(i, out)),
+        Value::Object(map) => {
+            for (k, val) in map {
+                // Keys too, for `KEY: value` dumps turned into objects
+                if !val.is_object() && !val.is_array() {
+                    out.push_str(k);
+                    out.push_str(": ");
+                }
+                collect_strings(val, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn with_leaks(framework: Framework, kind: DisclosureKind, evidence: &str, body: &str) -> ErrorDisclosure {
+    let mut paths = Vec::new();
+    for m in PATH_RE.find_iter(body) {
+        push_unique(&mut paths, m.as_str().trim_end_matches(['.', ':']).to_string());
+    }
+    let mut versions = Vec::new();
+    for (name, re) in VERSION_RES.iter() {
+        if let Some(c) = re.captures(body) {
+            push_unique(&mut versions, format!("{} {}", name, &c[1]));
+        }
+    }
+    let mut credentials = Vec::new();
+    for c in CREDENTIAL_RE.captures_iter(body) {
+        push_unique(&mut credentials, c[1].to_string());
+    }
+    let leaked = !paths.is_empty() || !versions.is_empty();
+    ErrorDisclosure {
+        framework,
+        kind,
+        severity: severity(kind, leaked, !credentials.is_empty()).to_string(),
+        paths,
+        versions,
+        credentials,
+        evidence: evidence.chars().take(200).collect(),
+    }
+}
+
+/// A verbose error in a probe result: the stored body when there is one, else the JSON sample or the
+/// short text sample of non-JSON responses
+pub fn classify_event(e: &RawEvent) -> Option<ErrorDisclosure> {
+    match e.body.as_ref().and_then(|b| b.text()) {
+        Some(text) => match serde_json::from_str::<Value>(&text) {
+            Ok(v) => classify_json(&v),
+            Err(_) => classify(&text),
+        },
+        None => match &e.json_sample {
+            Some(Value::Object(map)) if map.len() == 1 && map.contains_key("_sample") => map["_sample"].as_str().and_then(classify),
+            Some(v) => classify_json(v),
+            None => None,
+        },
+    }
+}
+
+/// A verbose error served by one endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    pub url: String,
+    pub status: u16,
+    #[serde(flatten)]
+    pub disclosure: ErrorDisclosure,
+}
@@ -0,0 +1,200 @@
+use crate::http_client::Paced;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Serialize, Deserialize};
+
+use super::admin_scanner::RiskLevel;
+
+static ENV_LINE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^[A-Z][A-Z0-9_]{1,63}=").unwrap());
+static HTPASSWD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^[\w.@-]+:(\$apr1\$|\$2[aby]\$|\{SHA\}|\$[56]\$)").unwrap());
+static SHA_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9a-f]{40}\s*$").unwrap());
+
+/// What a probed file is and how its content is recognized
+#[derive(Debug, Clone, Copy)]
+enum Signature {
+    GitHead,
+    GitConfig,
+    SvnEntries,
+    SvnWcDb,
+    HgRequires,
+    DotEnv,
+    PhpSource,
+    Archive,
+    SqlDump,
+    Htpasswd,
+    DsStore,
+}
+
+struct Probe {
+    path: &'static str,
+    kind: &'static str,
+    signature: Signature,
+    risk_level: RiskLevel,
+}
+
+const fn probe(path: &'static str, kind: &'static str, signature: Signature, risk_level: RiskLevel) -> Probe {
+    Probe { path, kind, signature, risk_level }
+}
+
+const PROBES: &[Probe] = &[
+    probe("/.git/HEAD", "Git repository", Signature::GitHead, RiskLevel::Critical),
+    probe("/.git/config", "Git repository", Signature::GitConfig, RiskLevel::Critical),
+    probe("/.svn/entries", "Subversion working copy", Signature::SvnEntries, RiskLevel::Critical),
+    probe("/.svn/wc.db", "Subversion working copy", Signature::SvnWcDb, RiskLevel::Critical),
+    probe("/.hg/requires", "Mercurial repository", Signature::HgRequires, RiskLevel::Critical),
+    probe("/.env", "Environment file", Signature::DotEnv, RiskLevel::Critical),
+    probe("/.env.local", "Environment file", Signature::DotEnv, RiskLevel::Critical),
+    probe("/.env.production", "Environment file", Signature::DotEnv, RiskLevel::Critical),
+    probe("/.env.backup", "Environment file", Signature::DotEnv, RiskLevel::Critical),
+    probe("/config.php.bak", "PHP source backup", Signature::PhpSource, RiskLevel::Critical),
+    probe("/config.php~", "PHP source backup", Signature::PhpSource, RiskLevel::Critical),
+    probe("/config.php.old", "PHP source backup", Signature::PhpSource, RiskLevel::Critical),
+    probe("/wp-config.php.bak", "PHP source backup", Signature::PhpSource, RiskLevel::Critical),
+    probe("/wp-config.php~", "PHP source backup", Signature::PhpSource, RiskLevel::Critical),
+    probe("/.wp-config.php.swp", "PHP source backup", Signature::PhpSource, RiskLevel::Critical),
+    probe("/index.php.bak", "PHP source backup", Signature::PhpSource, RiskLevel::High),
+    probe("/backup.zip", "Site archive", Signature::Archive, RiskLevel::Critical),
+    probe("/backup.tar.gz", "Site archive", Signature::Archive, RiskLevel::Critical),
+    probe("/site.zip", "Site archive", Signature::Archive, RiskLevel::Critical),
+    probe("/www.zip", "Site archive", Signature::Archive, RiskLevel::Critical),
+    probe("/backup.sql", "Database dump", Signature::SqlDump, RiskLevel::Critical),
+    probe("/dump.sql", "Database dump", Signature::SqlDump, RiskLevel::Critical),
+    probe("/database.sql", "Database dump", Signature::SqlDump, RiskLevel::Critical),
+    probe("/.htpasswd", "Password file", Signature::Htpasswd, RiskLevel::Critical),
+    probe("/.DS_Store", "macOS directory index", Signature::DsStore, RiskLevel::High),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposedFile {
+    pub url: String,
+    pub kind: String,
+    pub risk_level: RiskLevel,
+    /// First line of the file (secrets masked), proving the content matched
+    pub evidence: String,
+    /// How to pull the rest of the data out
+    pub extraction_hint: String,
+}
+
+/// Does `body` actually look like the file we asked for? Soft-404 pages return 200 with HTML,
+/// so every check is on the file's own format.
+fn matches_signature(signature: Signature, body: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(&body[..body.len().min(8192)]);
+    let trimmed = text.trim_start();
+    let looks_html = trimmed.starts_with('<') && !trimmed.starts_with("<?php");
+
+    match signature {
+        Signature::GitHead => trimmed.starts_with("ref: refs/") || SHA_RE.is_match(trimmed),
+        Signature::GitConfig => text.contains("[core]") && text.contains("repositoryformatversion"),
+        // Pre-1.7 format starts with the format number, then "dir" on the next lines
+        Signature::SvnEntries => {
+            let mut lines = trimmed.lines();
+            lines.next().is_some_and(|l| l.trim().parse::<u32>().is_ok()) && text.contains("\ndir\n")
+        }
+        Signature::SvnWcDb => body.starts_with(b"SQLite format 3\0"),
+        Signature::HgRequires => !looks_html && (text.contains("revlogv1") || text.contains("dotencode")),
+        Signature::DotEnv => !looks_html && ENV_LINE_RE.find_iter(&text).count() >= 2,
+        Signature::PhpSource => text.contains("<?php") || text.contains("define('DB_"),
+        Signature::Archive => body.starts_with(b"PK\x03\x04") || body.starts_with(&[0x1f, 0x8b]),
+        Signature::SqlDump => !looks_html
+            && (text.contains("CREATE TABLE") || text.contains("INSERT INTO") || text.contains("-- MySQL dump")),
+        Signature::Htpasswd => HTPASSWD_RE.is_match(&text),
+        Signature::DsStore => body.len() > 8 && &body[4..8] == b"Bud1",
+    }
+}
+
+fn extraction_hint(signature: Signature, base: &str) -> String {
+    match signature {
+        Signature::GitHead | Signature::GitConfig => format!(
+            "Dump the repository with git-dumper: git-dumper {}/.git/ ./repo && git -C ./repo log -p", base),
+        Signature::SvnEntries | Signature::SvnWcDb => format!(
+            "Download {}/.svn/wc.db and list files: sqlite3 wc.db 'select local_relpath, checksum from NODES' \
+             (pristine copies live under .svn/pristine/<sha1[0..2]>/<sha1>.svn-base)", base),
+        Signature::HgRequires => format!(
+            "Reconstruct with a Mercurial dumper starting from {}/.hg/store/00manifest.i", base),
+        Signature::DotEnv => "Review the file for database credentials, API keys and APP_KEY/secret values".to_string(),
+        Signature::PhpSource => "Backup is served as plain text - read database credentials and salts from the source".to_string(),
+        Signature::Archive => "Download and extract the archive; look for config files and source code".to_string(),
+        Signature::SqlDump => "Download the dump; look for user tables, password hashes and personal data".to_string(),
+        Signature::Htpasswd => "Crack the hashes offline (hashcat -m 1600 for $apr1$, -m 3200 for bcrypt)".to_string(),
+        Signature::DsStore => format!(
+            "Parse the file listing (e.g. ds_store_exp {}/.DS_Store) to find unlinked files", base),
+    }
+}
+
+/// First non-empty line, with anything after `=`/`:` masked so reports don't carry secrets
+fn evidence(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(&body[..body.len().min(512)]);
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    if line.chars().any(|c| c.is_control() || c == '\u{FFFD}') {
+        return format!("{} bytes (binary)", body.len());
+    }
+    let line: String = line.chars().take(80).collect();
+    match line.find(['=', ':']) {
+        Some(idx) if !line.starts_with("ref:") => format!("{}{}***", &line[..idx], &line[idx..idx + 1]),
+        _ => line,
+    }
+}
+
+/// Probe a base URL for exposed VCS metadata, environment files and backups
+pub async fn scan_exposed_files(client: &Client, base_url: &str) -> Result<Vec<ExposedFile>> {
+    let mut results = Vec::new();
+
+    let parsed = url::Url::parse(base_url)?;
+    let base = format!("{}://{}", parsed.scheme(), parsed.host_str().unwrap_or(""));
+    let mut found_kinds = std::collections::HashSet::new();
+
+    for probe in PROBES {
+        // One confirmed file per kind is enough (e.g. .git/HEAD makes .git/config redundant)
+        if found_kinds.contains(probe.kind) {
+            continue;
+        }
+        let test_url = format!("{}{}", base, probe.path);
+
+        let resp = match client.get(&test_url)
+            .timeout(std::time::Duration::from_secs(5))
+            .send_paced()
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+
+        if resp.status().is_success() {
+            let body = resp.bytes().await.unwrap_or_default();
+            if !body.is_empty() && matches_signature(probe.signature, &body) {
+                found_kinds.insert(probe.kind);
+                results.push(ExposedFile {
+                    url: test_url,
+                    kind: probe.kind.to_string(),
+                    risk_level: probe.risk_level.clone(),
+                    evidence: evidence(&body),
+                    extraction_hint: extraction_hint(probe.signature, &base),
+                });
+            }
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_reject_soft_404() {
+        let html = b"<!DOCTYPE html><html><body>Not found</body></html>";
+        assert!(!matches_signature(Signature::GitHead, html));
+        assert!(!matches_signature(Signature::DotEnv, html));
+        assert!(!matches_signature(Signature::SqlDump, b"<html>INSERT INTO</html>"));
+
+        assert!(matches_signature(Signature::GitHead, b"ref: refs/heads/main\n"));
+        assert!(matches_signature(Signature::DotEnv, b"APP_KEY=base64:abc\nDB_PASSWORD=hunter2\n"));
+        assert!(matches_signature(Signature::SvnEntries, b"10\n\ndir\n1234\n"));
+        assert_eq!(evidence(b"DB_PASSWORD=hunter2\n"), "DB_PASSWORD=***");
+    }
+}
@@ -0,0 +1,471 @@
+//! API gateway detection and gateway-specific checks
+//! Kong, Apigee, AWS API Gateway and Tyk are recognized from the probe results: their headers
+//! (`X-Kong-*`, `Via: kong/3.4`, `X-Amz-Apigw-Id`, `X-Amzn-ErrorType`, `X-Apigee-*`), host names
+//! (`*.execute-api.<region>.amazonaws.com`, `*.apigee.net`) and the error bodies they send for unknown
+//! routes or missing keys. Each detected gateway then gets the checks that matter for it, all without
+//! scan credentials:
+//! - Kong: the Admin API on ports 8001/8444 or proxied under `/admin-api`; when it answers, its routes are
+//!   listed and catch-all routes (no host, path or method) reported.
+//! - Tyk: the control API (`/tyk/apis`) on the gateway port, with the default secret from the sample
+//!   configuration, and the `/hello` health endpoint.
+//! - Apigee: sample and default proxies (`/oauth/client_credential/accesstoken`, `/helloworld`, ...) and
+//!   fault responses that name policies.
+//! - AWS API Gateway: stages besides the one in use, found by comparing each candidate to the answer for
+//!   a stage that can't exist.
+
+use crate::http_client::Paced;
+use crate::output::writer_jsonl::{collect_headers, RawEvent};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Tyk's control API secret in the sample `tyk.conf`
+pub const TYK_DEFAULT_SECRET: &str = "352d20ee67be67f6340b4c0605b044b7";
+
+/// Kong Admin API locations besides the gateway origin itself
+const KONG_ADMIN_PORTS: &[(&str, u16)] = &[("http", 8001), ("https", 8444)];
+
+/// Apigee sample proxies and the OAuth proxy every new organization gets
+const APIGEE_DEFAULT_PROXIES: &[&str] = &["/oauth/client_credential/accesstoken", "/helloworld", "/mock-target-api", "/mocktarget"];
+
+/// Stage names tried on AWS API Gateway
+pub const AWS_STAGES: &[&str] = &["prod", "production", "dev", "development", "test", "testing", "staging", "stage", "qa", "uat", "beta", "sandbox", "v1", "v2", "latest", "live", "demo", "internal"];
+
+/// Stages that shouldn't be reachable next to production
+const NON_PROD_STAGES: &[&str] = &["dev", "development", "test", "testing", "staging", "stage", "qa", "uat", "beta", "sandbox", "demo", "internal"];
+
+/// Bytes read of a gateway answer
+const BODY_LIMIT: usize = 256 * 1024;
+
+/// Routes kept per gateway
+const MAX_ROUTES: usize = 50;
+
+static VERSION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bkong/(\d+\.\d+(?:\.\d+)?)").unwrap());
+static ERRORCODE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""errorcode"\s*:\s*"([\w.\-]+)""#).unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gateway {
+    Kong,
+    Apigee,
+    AwsApiGateway,
+    Tyk,
+}
+
+impl Gateway {
+    pub fn name(self) -> &'static str {
+        match self {
+            Gateway::Kong => "Kong",
+            Gateway::Apigee => "Apigee",
+            Gateway::AwsApiGateway => "AWS API Gateway",
+            Gateway::Tyk => "Tyk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayCheck {
+    /// The admin or control API answers and accepts requests
+    AdminApiExposed,
+    /// The admin or control API is reachable but asks for a key
+    AdminApiReachable,
+    /// A route without host, path or method restriction
+    CatchAllRoute,
+    /// A sample or default proxy is deployed
+    DefaultRoute,
+    /// Health endpoint disclosing the gateway version
+    HealthEndpoint,
+    /// Error responses naming internal policies
+    FaultDisclosure,
+    /// Another stage of the same API answers
+    StageExposed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayFinding {
+    pub check: GatewayCheck,
+    pub severity: String,
+    pub url: String,
+    pub detail: String,
+}
+
+impl GatewayFinding {
+    fn new(check: GatewayCheck, severity: &str, url: &str, detail: String) -> Self {
+        Self { check, severity: severity.to_string(), url: url.to_string(), detail }
+    }
+}
+
+/// A gateway in front of one origin
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayReport {
+    pub origin: String,
+    pub gateway: Gateway,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// What identified the gateway
+    pub evidence: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin_url: Option<String>,
+    /// Routes listed by the admin API
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<String>,
+    /// Stages seen in the scan and found by enumeration (AWS)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<String>,
+    pub findings: Vec<GatewayFinding>,
+}
+
+impl GatewayReport {
+    /// Highest finding severity, `None` without findings
+    pub fn severity(&self) -> Option<&str> {
+        ["CRITICAL", "HIGH", "MEDIUM", "LOW"].into_iter().find(|s| self.findings.iter().any(|f| f.severity == *s))
+    }
+}
+
+/// Gateway signals in one response
+pub fn detect(url: &str, headers: &BTreeMap<String, String>, body: &str) -> Option<(Gateway, Option<String>, Vec<String>)> {
+    let get = |name: &str| headers.get(name).map(String::as_str);
+    let host = url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)).unwrap_or_default();
+    let names_with = |prefix: &str| headers.keys().filter(|k| k.starts_with(prefix)).map(|k| format!("header {}", k)).collect::<Vec<_>>();
+
+    // Kong
+    let mut evidence = names_with("x-kong-");
+    let mut version = None;
+    for name in ["server", "via"] {
+        if let Some(c) = get(name).and_then(|v| VERSION_RE.captures(v)) {
+            evidence.push(format!("{}: {}", name, &c[0]));
+            version = Some(c[1].to_string());
+        }
+    }
+    for marker in ["no Route matched with those values", "No API key found in request"] {
+        if body.contains(marker) {
+            evidence.push(format!("body: {}", marker));
+        }
+    }
+    if !evidence.is_empty() {
+        return Some((Gateway::Kong, version, evidence));
+    }
+
+    // AWS API Gateway; `x-amzn-requestid` alone is also sent by other AWS services
+    let mut evidence: Vec<String> = ["x-amz-apigw-id", "x-amzn-errortype"].iter().filter(|h| headers.contains_key(**h)).map(|h| format!("header {}", h)).collect();
+    if host.contains(".execute-api.") && host.ends_with(".amazonaws.com") {
+        evidence.push(format!("host {}", host));
+    }
+    if body.contains("Missing Authentication Token") && headers.contains_key("x-amzn-requestid") {
+        evidence.push("body: Missing Authentication Token".to_string());
+    }
+    if !evidence.is_empty() {
+        return Some((Gateway::AwsApiGateway, None, evidence));
+    }
+
+    // Apigee
+    let mut evidence = names_with("x-apigee-");
+    if host.ends_with(".apigee.net") || host.ends_with(".apigee.io") {
+        evidence.push(format!("host {}", host));
+    }
+    if body.contains("\"faultstring\"") && ERRORCODE_RE.is_match(body) {
+        evidence.push("body: Apigee fault (faultstring, errorcode)".to_string());
+    }
+    if !evidence.is_empty() {
+        return Some((Gateway::Apigee, None, evidence));
+    }
+
+    // Tyk
+    let mut evidence = names_with("x-tyk-");
+    for marker in ["Authorization field missing", "Key not authorised", "Attempted administrative access with invalid or missing key"] {
+        if body.contains(marker) {
+            evidence.push(format!("body: {}", marker));
+        }
+    }
+    if !evidence.is_empty() {
+        return Some((Gateway::Tyk, None, evidence));
+    }
+    None
+}
+
+/// Response text of a probe result: the stored body, else the JSON or text sample
+fn event_text(e: &RawEvent) -> String {
+    if let Some(text) = e.body.as_ref().and_then(|b| b.text()) {
+        return text;
+    }
+    match &e.json_sample {
+        Some(Value::Object(map)) if map.len() == 1 && map.contains_key("_sample") => map["_sample"].as_str().unwrap_or_default().to_string(),
+        Some(v) => v.to_string(),
+        None => String::new(),
+    }
+}
+
+/// One report per origin behind a recognized gateway, with the evidence of all its responses; Apigee
+/// fault codes naming policies are reported right away
+pub fn fingerprint(results: &[RawEvent], limit: usize) -> Vec<GatewayReport> {
+    let mut reports: Vec<GatewayReport> = Vec::new();
+    let mut fault_codes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for e in results {
+        let Some(origin) = crate::probe::soft404::origin_of(&e.final_url) else { continue };
+        let body = event_text(e);
+        let Some((gateway, version, evidence)) = detect(&e.final_url, &e.headers, &body) else { continue };
+        if gateway == Gateway::Apigee {
+            let codes = fault_codes.entry(origin.clone()).or_default();
+            for c in ERRORCODE_RE.captures_iter(&body).filter(|c| c[1].starts_with("steps.")) {
+                if !codes.contains(&c[1].to_string()) {
+                    codes.push(c[1].to_string());
+                }
+            }
+        }
+        let known = reports.len();
+        match reports.iter_mut().find(|r| r.origin == origin) {
+            Some(r) => {
+                for ev in evidence {
+                    if r.gateway == gateway && !r.evidence.contains(&ev) {
+                        r.evidence.push(ev);
+                    }
+                }
+                r.version = r.version.take().or(version);
+            }
+            None if known < limit => reports.push(GatewayReport {
+                origin,
+                gateway,
+                version,
+                evidence,
+                admin_url: None,
+                routes: Vec::new(),
+                stages: Vec::new(),
+                findings: Vec::new(),
+            }),
+            None => {}
+        }
+    }
+    for r in reports.iter_mut() {
+        if r.gateway == Gateway::AwsApiGateway {
+            r.stages = seen_stages(results, &r.origin);
+        }
+        if let Some(codes) = fault_codes.get(&r.origin).filter(|c| !c.is_empty()) {
+            r.findings.push(GatewayFinding::new(
+                GatewayCheck::FaultDisclosure,
+                "LOW",
+                &r.origin,
+                format!("fault responses name the policies that rejected the request: {}", codes.join(", ")),
+            ));
+        }
+    }
+    reports
+}
+
+/// First path segments of the scanned URLs on an origin: the stage of `execute-api` URLs
+fn seen_stages(results: &[RawEvent], origin: &str) -> Vec<String> {
+    let mut stages = Vec::new();
+    for e in results.iter().filter(|e| crate::probe::soft404::origin_of(&e.final_url).as_deref() == Some(origin)) {
+        let Ok(u) = url::Url::parse(&e.final_url) else { continue };
+        if let Some(first) = u.path_segments().and_then(|mut s| s.next()).filter(|s| !s.is_empty()) {
+            if !stages.iter().any(|s| s == first) {
+                stages.push(first.to_string());
+            }
+        }
+    }
+    stages
+}
+
+/// Route description from a Kong Admin API `/routes` entry; `None` marks a catch-all route
+pub fn kong_route(route: &Value) -> Option<String> {
+    let list = |key: &str| route.get(key).and_then(Value::as_array).map(|a| a.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(",")).filter(|s| !s.is_empty());
+    let (paths, hosts, methods) = (list("paths"), list("hosts"), list("methods"));
+    if paths.is_none() && hosts.is_none() && methods.is_none() && list("snis").is_none() && route.get("headers").and_then(Value::as_object).is_none_or(|o| o.is_empty()) {
+        return None;
+    }
+    let name = route.get("name").and_then(Value::as_str).unwrap_or("unnamed");
+    Some(format!("{} {} {}{}", name, methods.unwrap_or_else(|| "*".to_string()), hosts.map(|h| format!("{} ", h)).unwrap_or_default(), paths.unwrap_or_else(|| "/".to_string())).trim_end().to_string())
+}
+
+/// Kong Admin API root (`GET /`): its version
+pub fn kong_admin_version(v: &Value) -> Option<String> {
+    let tagline = v.get("tagline").and_then(Value::as_str)?;
+    if !tagline.to_ascii_lowercase().contains("kong") {
+        return None;
+    }
+    Some(v.get("version").and_then(Value::as_str).unwrap_or("unknown").to_string())
+}
+
+struct Answer {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+impl Answer {
+    fn json(&self) -> Option<Value> {
+        serde_json::from_str(&self.body).ok()
+    }
+}
+
+pub struct GatewayAnalyzer {
+    client: Client,
+}
+
+impl GatewayAnalyzer {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        let client = crate::http_client::builder_unauthenticated("analyze::gateway")
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .danger_accept_invalid_certs(true)
+            .build()?;
+        Ok(Self { client })
+    }
+
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Option<Answer> {
+        let mut req = self.client.get(url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let resp = req.send_paced().await.ok()?;
+        let status = resp.status().as_u16();
+        let headers = collect_headers(resp.headers());
+        let (bytes, _) = crate::probe::http_probe::read_limited(resp, BODY_LIMIT).await.ok()?;
+        Some(Answer { status, headers, body: String::from_utf8_lossy(&bytes).into_owned() })
+    }
+
+    /// Run the checks for the report's gateway
+    pub async fn check(&self, report: &mut GatewayReport) {
+        match report.gateway {
+            Gateway::Kong => self.check_kong(report).await,
+            Gateway::Tyk => self.check_tyk(report).await,
+            Gateway::Apigee => self.check_apigee(report).await,
+            Gateway::AwsApiGateway => self.check_aws(report).await,
+        }
+    }
+
+    async fn check_kong(&self, report: &mut GatewayReport) {
+        let mut candidates = vec![format!("{}/admin-api", report.origin)];
+        if let Ok(u) = url::Url::parse(&report.origin) {
+            if let Some(host) = u.host_str() {
+                candidates.extend(KONG_ADMIN_PORTS.iter().map(|(scheme, port)| format!("{}://{}:{}", scheme, host, port)));
+            }
+        }
+        for admin in candidates {
+            let Some(version) = self.get(&format!("{}/", admin), &[]).await.filter(|a| a.status == 200).and_then(|a| a.json()).and_then(|v| kong_admin_version(&v)) else { continue };
+            report.version.get_or_insert(version.clone());
+            report.findings.push(GatewayFinding::new(
+                GatewayCheck::AdminApiExposed,
+                "CRITICAL",
+                &admin,
+                format!("Kong {} Admin API answers without authentication: services, routes, plugins and consumer credentials can be read and changed", version),
+            ));
+            if let Some(routes) = self.get(&format!("{}/routes", admin), &[]).await.and_then(|a| a.json()) {
+                for route in routes.get("data").and_then(Value::as_array).into_iter().flatten() {
+                    match kong_route(route) {
+                        Some(r) => {
+                            if report.routes.len() < MAX_ROUTES {
+                                report.routes.push(r);
+                            }
+                        }
+                        None => report.findings.push(GatewayFinding::new(
+                            GatewayCheck::CatchAllRoute,
+                            "MEDIUM",
+                            &admin,
+                            format!("route '{}' has no host, path or method: every request reaches its service", route.get("name").and_then(Value::as_str).or_else(|| route.get("id").and_then(Value::as_str)).unwrap_or("unnamed")),
+                        )),
+                    }
+                }
+            }
+            report.admin_url = Some(admin);
+            break;
+        }
+    }
+
+    async fn check_tyk(&self, report: &mut GatewayReport) {
+        let hello = format!("{}/hello", report.origin);
+        if let Some(v) = self.get(&hello, &[]).await.filter(|a| a.status == 200).and_then(|a| a.json()) {
+            if let Some(version) = v.get("version").and_then(Value::as_str) {
+                report.version.get_or_insert(version.to_string());
+                report.findings.push(GatewayFinding::new(GatewayCheck::HealthEndpoint, "LOW", &hello, format!("health check shows Tyk {} and its Redis/dashboard status", version)));
+            }
+        }
+
+        let apis = format!("{}/tyk/apis", report.origin);
+        if let Some(a) = self.get(&apis, &[("x-tyk-authorization", TYK_DEFAULT_SECRET)]).await.filter(|a| a.status == 200) {
+            if let Some(list) = a.json().as_ref().and_then(Value::as_array) {
+                for api in list {
+                    let name = api.get("name").and_then(Value::as_str).unwrap_or("unnamed");
+                    let path = api.pointer("/proxy/listen_path").and_then(Value::as_str).unwrap_or("/");
+                    let target = api.pointer("/proxy/target_url").and_then(Value::as_str).unwrap_or("");
+                    if report.routes.len() < MAX_ROUTES {
+                        report.routes.push(format!("{} {} -> {}", name, path, target));
+                    }
+                }
+                report.admin_url = Some(apis.clone());
+                report.findings.push(GatewayFinding::new(
+                    GatewayCheck::AdminApiExposed,
+                    "CRITICAL",
+                    &apis,
+                    format!("Tyk control API accepts the default secret from the sample tyk.conf: {} API definitions readable, keys and APIs can be created", list.len()),
+                ));
+                return;
+            }
+        }
+        if let Some(a) = self.get(&apis, &[]).await {
+            if a.status == 403 && a.body.contains("Attempted administrative access") {
+                report.admin_url = Some(apis.clone());
+                report.findings.push(GatewayFinding::new(
+                    GatewayCheck::AdminApiReachable,
+                    "MEDIUM",
+                    &apis,
+                    "Tyk control API is served on the public gateway port; only its secret protects it".to_string(),
+                ));
+            }
+        }
+    }
+
+    async fn check_apigee(&self, report: &mut GatewayReport) {
+        for path in APIGEE_DEFAULT_PROXIES {
+            let url = format!("{}{}", report.origin, path);
+            let Some(a) = self.get(&url, &[]).await else { continue };
+            // Unknown paths get a 404 fault `ApplicationNotFound` (or `classification.*`)
+            let unknown = a.status == 404 || a.body.contains("ApplicationNotFound") || a.body.contains("classification.");
+            if !unknown && a.status < 500 {
+                report.findings.push(GatewayFinding::new(
+                    GatewayCheck::DefaultRoute,
+                    "MEDIUM",
+                    &url,
+                    format!("sample or default proxy deployed (HTTP {})", a.status),
+                ));
+            }
+        }
+    }
+
+    /// A candidate stage exists when it is answered differently from a stage that can't exist
+    async fn check_aws(&self, report: &mut GatewayReport) {
+        let fingerprint = |a: &Answer| (
+            a.status,
+            a.headers.get("x-amzn-errortype").map(|t| t.split(':').next().unwrap_or_default().to_string()),
+            a.json().and_then(|v| v.get("message").and_then(Value::as_str).map(str::to_string)),
+        );
+        let missing = format!("apihunter{:08x}", rand::random::<u32>());
+        let Some(baseline) = self.get(&format!("{}/{}/", report.origin, missing), &[]).await else { return };
+        let baseline = fingerprint(&baseline);
+        let seen = report.stages.clone();
+        for stage in AWS_STAGES.iter().filter(|s| !seen.iter().any(|seen| seen == *s)) {
+            let url = format!("{}/{}/", report.origin, stage);
+            let Some(a) = self.get(&url, &[]).await else { continue };
+            if fingerprint(&a) == baseline {
+                continue;
+            }
+            report.stages.push(stage.to_string());
+            let non_prod = NON_PROD_STAGES.contains(stage);
+            report.findings.push(GatewayFinding::new(
+                GatewayCheck::StageExposed,
+                if non_prod { "MEDIUM" } else { "LOW" },
+                &url,
+                if non_prod {
+                    format!("non-production stage '{}' is deployed on the same API (HTTP {}); it may run older code or lack the production authorizers", stage, a.status)
+                } else {
+                    format!("stage '{}' is deployed (HTTP {})", stage, a.status)
+                },
+            ));
+        }
+    }
+}
@@ -2,6 +2,16 @@ pub mod api_analyzer;
 pub mod security_headers;
 pub mod fingerprint;
 pub mod cors_checker;
+pub mod csp;
+pub mod cookies;
 pub mod admin_scanner;
+pub mod exposed_files;
+pub mod observability;
+pub mod error_disclosure;
 pub mod vulnerability_scanner;
 pub mod risk_classifier;
+pub mod open_redirect;
+pub mod oidc;
+pub mod oauth;
+pub mod gateway;
+pub mod spec_drift;
@@ -0,0 +1,238 @@
+//! OAuth flow misconfiguration tests
+//! Authorization requests come from the client code (`JsCriticalInfo::oauth_flows`) and from scanned
+//! URLs. From the code alone: an authorization URL without `state` (login CSRF) and a client using the
+//! implicit flow. Against the authorization endpoint, with the client's own `client_id`:
+//! - `redirect_uri` pointing to an external host (and the registered host as subdomain/userinfo); only a
+//!   redirect that really leaves for that host counts
+//! - `response_type=token` accepted while an invalid response type is refused (implicit flow enabled)
+//! - the registered callback page, loaded with a code in the URL, pulling in third-party resources under
+//!   a Referrer-Policy that sends the full URL (the code leaks in the Referer)
+
+use crate::analyze::open_redirect::{is_external, EVIL_HOST};
+use crate::filter::third_party::on_target;
+use crate::gather::js_deep_analyzer::OAuthFlow;
+use crate::http_client::Paced;
+use anyhow::Result;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Query parameters the tests set themselves
+const FLOW_PARAMS: &[&str] = &["response_type", "client_id", "redirect_uri", "state"];
+
+/// Marker code put on the callback URL
+pub const TEST_CODE: &str = "apihunter-test-code";
+
+/// Bytes of the callback page parsed
+const PAGE_LIMIT: usize = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthCheck {
+    OpenRedirectUri,
+    MissingState,
+    ImplicitFlow,
+    ReferrerLeak,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthFinding {
+    pub check: OAuthCheck,
+    pub severity: String,
+    pub authorization_endpoint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    pub detail: String,
+    /// Request (or code location) that shows the issue
+    pub evidence_url: String,
+}
+
+impl OAuthFinding {
+    fn new(flow: &OAuthFlow, check: OAuthCheck, severity: &str, detail: String, evidence_url: String) -> Self {
+        Self {
+            check,
+            severity: severity.to_string(),
+            authorization_endpoint: flow.authorization_endpoint.clone(),
+            client_id: flow.client_id.clone(),
+            detail,
+            evidence_url,
+        }
+    }
+}
+
+/// Authorization request for `flow` with the given response type and redirect URI (the registered one
+/// if `None`); other parameters of the original URL are kept
+pub fn authorization_url(flow: &OAuthFlow, response_type: &str, redirect_uri: Option<&str>) -> Option<String> {
+    let mut url = url::Url::parse(flow.url.as_deref().unwrap_or(&flow.authorization_endpoint)).ok()?;
+    let kept: Vec<(String, String)> = url.query_pairs().filter(|(k, _)| !FLOW_PARAMS.contains(&k.as_ref())).map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    url.set_query(None);
+    {
+        let mut query = url.query_pairs_mut();
+        query.extend_pairs(kept);
+        query.append_pair("response_type", response_type);
+        if let Some(client_id) = &flow.client_id {
+            query.append_pair("client_id", client_id);
+        }
+        if let Some(redirect) = redirect_uri.or(flow.redirect_uri.as_deref()) {
+            query.append_pair("redirect_uri", redirect);
+        }
+        query.append_pair("state", "apihunter");
+    }
+    Some(url.to_string())
+}
+
+/// External redirect URIs, plain ones first; the bypasses need the registered URI's host
+pub fn redirect_payloads(registered: Option<&str>) -> Vec<(&'static str, String)> {
+    let mut payloads = vec![("external host", format!("https://{}/callback", EVIL_HOST))];
+    if let Some(host) = registered.and_then(|r| url::Url::parse(r).ok()).and_then(|u| u.host_str().map(str::to_string)) {
+        payloads.push(("registered host as subdomain", format!("https://{}.{}/callback", host, EVIL_HOST)));
+        payloads.push(("registered host as userinfo", format!("https://{}@{}/callback", host, EVIL_HOST)));
+    }
+    payloads
+}
+
+/// Issues visible in the client code alone
+pub fn static_findings(flow: &OAuthFlow) -> Vec<OAuthFinding> {
+    let mut findings = Vec::new();
+    let evidence = flow.url.clone().unwrap_or_else(|| flow.source_file.clone());
+    if flow.has_state() == Some(false) {
+        findings.push(OAuthFinding::new(flow, OAuthCheck::MissingState, "MEDIUM",
+            format!("Authorization URL in {} has no state parameter: login CSRF", flow.source_file), evidence.clone()));
+    }
+    if flow.response_type.as_deref().is_some_and(|t| t.split(' ').any(|p| p == "token")) {
+        findings.push(OAuthFinding::new(flow, OAuthCheck::ImplicitFlow, "MEDIUM",
+            format!("Client in {} uses the implicit flow (response_type={}): tokens in the URL fragment", flow.source_file, flow.response_type.as_deref().unwrap_or_default()), evidence));
+    }
+    findings
+}
+
+/// Refused authorization request: error status or a redirect carrying `error=`
+pub fn is_error(status: u16, location: Option<&str>) -> bool {
+    status >= 400 || location.is_some_and(|l| l.contains("error="))
+}
+
+/// Referrer-Policy that sends the full URL, query included, to other sites
+pub fn leaks_full_url(policy: &str) -> bool {
+    // The last recognised value wins
+    policy.split(',').map(|p| p.trim().to_ascii_lowercase()).rfind(|p| !p.is_empty())
+        .is_some_and(|p| p == "unsafe-url" || p == "no-referrer-when-downgrade")
+}
+
+/// Resources of `html` (at `page_url`) loaded from other hosts, and the `<meta name="referrer">` policy
+pub fn page_referrers(page_url: &str, html: &str) -> (Vec<String>, Option<String>) {
+    let Ok(base) = url::Url::parse(page_url) else { return (Vec::new(), None) };
+    let doc = Html::parse_document(html);
+    let mut third_party = Vec::new();
+    for (selector, attr) in [("script[src]", "src"), ("img[src]", "src"), ("iframe[src]", "src"), ("link[href]", "href")] {
+        let Ok(sel) = Selector::parse(selector) else { continue };
+        for el in doc.select(&sel) {
+            let Some(resource) = el.value().attr(attr).and_then(|v| base.join(v).ok()) else { continue };
+            if resource.scheme().starts_with("http") && resource.host_str() != base.host_str() {
+                third_party.push(resource.to_string());
+            }
+        }
+    }
+    third_party.sort();
+    third_party.dedup();
+    let meta = Selector::parse(r#"meta[name="referrer"]"#).ok()
+        .and_then(|sel| doc.select(&sel).next().and_then(|el| el.value().attr("content").map(str::to_string)));
+    (third_party, meta)
+}
+
+pub struct OAuthTester {
+    /// Endpoints on the target, as a visitor without the scan credentials or cookies
+    target: Client,
+    /// Identity providers on other hosts; they get nothing meant for the target
+    third_party: Client,
+    domain: String,
+}
+
+impl OAuthTester {
+    /// Tester for flows of the scan target `domain`
+    pub fn new(timeout: Duration, domain: &str) -> Result<Self> {
+        // Redirects are inspected, not followed
+        let configure = |builder: reqwest::ClientBuilder| builder
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .danger_accept_invalid_certs(true)
+            .build();
+        Ok(Self {
+            target: configure(crate::http_client::builder_unauthenticated("analyze::oauth"))?,
+            third_party: configure(crate::http_client::third_party())?,
+            domain: domain.to_string(),
+        })
+    }
+
+    fn client(&self, url: &str) -> &Client {
+        if on_target(url, &self.domain) { &self.target } else { &self.third_party }
+    }
+
+    async fn request(&self, url: &str) -> Option<(u16, Option<String>)> {
+        let resp = self.client(url).get(url).send_paced().await.ok()?;
+        let location = resp.headers().get(reqwest::header::LOCATION).and_then(|l| l.to_str().ok()).map(str::to_string);
+        Some((resp.status().as_u16(), location))
+    }
+
+    async fn redirect_uri(&self, flow: &OAuthFlow) -> Option<OAuthFinding> {
+        for (technique, payload) in redirect_payloads(flow.redirect_uri.as_deref()) {
+            let url = authorization_url(flow, "code", Some(&payload))?;
+            let Some((_, Some(location))) = self.request(&url).await else { continue };
+            if is_external(&url, &location) {
+                return Some(OAuthFinding::new(flow, OAuthCheck::OpenRedirectUri, "HIGH",
+                    format!("redirect_uri not validated ({}): authorization codes are sent to {}", technique, location), url));
+            }
+        }
+        None
+    }
+
+    async fn implicit_flow(&self, flow: &OAuthFlow) -> Option<OAuthFinding> {
+        let invalid = self.request(&authorization_url(flow, "apihunter_invalid", None)?).await?;
+        // Servers that don't check the response type before login can't be judged
+        if !is_error(invalid.0, invalid.1.as_deref()) {
+            return None;
+        }
+        let url = authorization_url(flow, "token", None)?;
+        let (status, location) = self.request(&url).await?;
+        (!is_error(status, location.as_deref())).then(|| OAuthFinding::new(flow, OAuthCheck::ImplicitFlow, "MEDIUM",
+            format!("Authorization server accepts response_type=token for this client ({}), an invalid response type is refused", status), url))
+    }
+
+    async fn referrer_leak(&self, flow: &OAuthFlow) -> Option<OAuthFinding> {
+        let mut callback = url::Url::parse(flow.redirect_uri.as_deref()?).ok()?;
+        callback.query_pairs_mut().append_pair("code", TEST_CODE).append_pair("state", "apihunter");
+        let resp = self.client(callback.as_str()).get(callback.as_str()).send_paced().await.ok()?;
+        if !resp.status().is_success() {
+            return None;
+        }
+        let header = resp.headers().get("referrer-policy").and_then(|v| v.to_str().ok()).map(str::to_string);
+        let (bytes, _) = crate::probe::http_probe::read_limited(resp, PAGE_LIMIT).await.ok()?;
+        let (third_party, meta) = page_referrers(callback.as_str(), &String::from_utf8_lossy(&bytes));
+        if third_party.is_empty() {
+            return None;
+        }
+        // A meta policy overrides the header
+        let (severity, policy) = match meta.or(header) {
+            Some(p) if leaks_full_url(&p) => ("MEDIUM", format!("Referrer-Policy '{}'", p)),
+            Some(_) => return None,
+            None => ("LOW", "no Referrer-Policy (older browsers send the full URL)".to_string()),
+        };
+        Some(OAuthFinding::new(flow, OAuthCheck::ReferrerLeak, severity,
+            format!("Callback page loads {} third-party resources ({}...) with {}: the code leaks in the Referer", third_party.len(), third_party[0], policy),
+            callback.to_string()))
+    }
+
+    /// Code findings for `flow`, and with `active` the requests against its authorization endpoint
+    pub async fn test(&self, flow: &OAuthFlow, active: bool) -> Vec<OAuthFinding> {
+        let mut findings = static_findings(flow);
+        if !active || flow.client_id.is_none() {
+            return findings;
+        }
+        findings.extend(self.redirect_uri(flow).await);
+        if !findings.iter().any(|f| f.check == OAuthCheck::ImplicitFlow) {
+            findings.extend(self.implicit_flow(flow).await);
+        }
+        findings.extend(self.referrer_leak(flow).await);
+        findings
+    }
+}
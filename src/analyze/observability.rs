@@ -0,0 +1,463 @@
+//! Exposed observability endpoints
+//! Prometheus `/metrics`, Spring Boot `/actuator/*` (and the Boot 1 root paths), Go `/debug/pprof` and
+//! `/debug/vars`, Apache `server-status`/`server-info` and nginx `stub_status`. Each response is checked
+//! against the format the path should serve, so soft-404 pages don't count, and parsed for what it
+//! gives away: internal routes from metric labels, handler mappings and in-flight requests, environment
+//! property names and the sensitive ones left unmasked, and heap dumps or heap profiles. `env` and
+//! `heapdump` responding is Critical.
+
+use crate::http_client::Paced;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use super::admin_scanner::RiskLevel;
+
+/// Label values in Prometheus samples that name a route
+static PROM_ROUTE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\b(?:uri|path|route|handler|endpoint|url)="(/[^"]*)""#).unwrap());
+static PROM_TYPE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^# TYPE ([A-Za-z_:][\w:]*) (?:counter|gauge|histogram|summary|untyped)\s*$").unwrap());
+/// Request column of Apache's `server-status` table
+static STATUS_REQUEST_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:GET|POST|PUT|DELETE|PATCH|HEAD|OPTIONS) (/[^\s<]*) HTTP/\d").unwrap());
+static PPROF_HEAP_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^heap profile: (\d+): (\d+) \[\d+: \d+\]").unwrap());
+
+/// Property names whose value must not be readable
+const SENSITIVE_NAMES: &[&str] = &["password", "secret", "token", "credential", "private", "apikey", "api-key", "api_key", "access-key", "accesskey"];
+
+/// Routes, variable names and secrets kept per endpoint
+const MAX_ITEMS: usize = 50;
+
+/// Bytes of a heap dump read to recognize it
+const HEAP_PEEK: usize = 64;
+
+/// What an observability endpoint is; decides the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EndpointKind {
+    Prometheus,
+    ActuatorIndex,
+    ActuatorEnv,
+    ActuatorHeapdump,
+    ActuatorMappings,
+    ActuatorConfigprops,
+    ActuatorHttpTrace,
+    ActuatorThreaddump,
+    ActuatorBeans,
+    ActuatorLoggers,
+    ActuatorGatewayRoutes,
+    Pprof,
+    PprofHeap,
+    PprofCmdline,
+    Expvar,
+    ApacheStatus,
+    ApacheInfo,
+    NginxStatus,
+}
+
+impl EndpointKind {
+    pub fn label(self) -> &'static str {
+        use EndpointKind::*;
+        match self {
+            Prometheus => "Prometheus metrics",
+            ActuatorIndex => "Spring Boot Actuator",
+            ActuatorEnv => "Actuator env",
+            ActuatorHeapdump => "Actuator heapdump",
+            ActuatorMappings => "Actuator mappings",
+            ActuatorConfigprops => "Actuator configprops",
+            ActuatorHttpTrace => "Actuator HTTP trace",
+            ActuatorThreaddump => "Actuator threaddump",
+            ActuatorBeans => "Actuator beans",
+            ActuatorLoggers => "Actuator loggers",
+            ActuatorGatewayRoutes => "Spring Cloud Gateway routes",
+            Pprof => "Go pprof",
+            PprofHeap => "Go pprof heap",
+            PprofCmdline => "Go pprof cmdline",
+            Expvar => "Go expvar",
+            ApacheStatus => "Apache server-status",
+            ApacheInfo => "Apache server-info",
+            NginxStatus => "nginx stub_status",
+        }
+    }
+}
+
+struct Probe {
+    path: &'static str,
+    kind: EndpointKind,
+}
+
+const fn probe(path: &'static str, kind: EndpointKind) -> Probe {
+    Probe { path, kind }
+}
+
+const PROBES: &[Probe] = &[
+    probe("/metrics", EndpointKind::Prometheus),
+    probe("/actuator/prometheus", EndpointKind::Prometheus),
+    probe("/prometheus", EndpointKind::Prometheus),
+    probe("/actuator", EndpointKind::ActuatorIndex),
+    probe("/actuator/env", EndpointKind::ActuatorEnv),
+    probe("/env", EndpointKind::ActuatorEnv),
+    probe("/actuator/heapdump", EndpointKind::ActuatorHeapdump),
+    probe("/heapdump", EndpointKind::ActuatorHeapdump),
+    probe("/actuator/mappings", EndpointKind::ActuatorMappings),
+    probe("/mappings", EndpointKind::ActuatorMappings),
+    probe("/actuator/configprops", EndpointKind::ActuatorConfigprops),
+    probe("/actuator/httpexchanges", EndpointKind::ActuatorHttpTrace),
+    probe("/actuator/httptrace", EndpointKind::ActuatorHttpTrace),
+    probe("/trace", EndpointKind::ActuatorHttpTrace),
+    probe("/actuator/threaddump", EndpointKind::ActuatorThreaddump),
+    probe("/actuator/beans", EndpointKind::ActuatorBeans),
+    probe("/actuator/loggers", EndpointKind::ActuatorLoggers),
+    probe("/actuator/gateway/routes", EndpointKind::ActuatorGatewayRoutes),
+    probe("/debug/pprof/", EndpointKind::Pprof),
+    probe("/debug/pprof/heap?debug=1", EndpointKind::PprofHeap),
+    probe("/debug/pprof/cmdline", EndpointKind::PprofCmdline),
+    probe("/debug/vars", EndpointKind::Expvar),
+    probe("/server-status", EndpointKind::ApacheStatus),
+    probe("/server-info", EndpointKind::ApacheInfo),
+    probe("/nginx_status", EndpointKind::NginxStatus),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservabilityEndpoint {
+    pub url: String,
+    pub kind: EndpointKind,
+    pub risk_level: RiskLevel,
+    /// What the content showed, in one line
+    pub summary: String,
+    /// Internal routes named in the content (metric labels, handler mappings, in-flight requests)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<String>,
+    /// Environment variable and property names; values are not kept
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_vars: Vec<String>,
+    /// Sensitive names whose value was readable (not masked)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+    /// Heap dump or heap profile description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heap: Option<String>,
+}
+
+impl ObservabilityEndpoint {
+    fn new(url: String, kind: EndpointKind, risk_level: RiskLevel, summary: String) -> Self {
+        Self { url, kind, risk_level, summary, routes: Vec::new(), env_vars: Vec::new(), secrets: Vec::new(), heap: None }
+    }
+}
+
+fn push_unique(out: &mut Vec<String>, value: &str) {
+    if out.len() < MAX_ITEMS && !out.iter().any(|v| v == value) {
+        out.push(value.to_string());
+    }
+}
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_NAMES.iter().any(|s| name.contains(s))
+}
+
+/// Masked the way Spring Boot and most tools mask (`******`), or empty
+fn is_masked(value: &str) -> bool {
+    let v = value.trim();
+    v.is_empty() || v.chars().all(|c| c == '*')
+}
+
+/// Metric names and route labels of a Prometheus exposition; `None` if it isn't one
+pub fn parse_prometheus(text: &str) -> Option<(Vec<String>, Vec<String>)> {
+    let metrics: Vec<String> = PROM_TYPE_RE.captures_iter(text).map(|c| c[1].to_string()).collect();
+    if metrics.is_empty() {
+        return None;
+    }
+    let mut routes = Vec::new();
+    for c in PROM_ROUTE_RE.captures_iter(text) {
+        push_unique(&mut routes, &c[1]);
+    }
+    Some((metrics, routes))
+}
+
+/// Endpoint names linked from the actuator index (`_links`)
+pub fn parse_actuator_links(v: &Value) -> Option<Vec<String>> {
+    let links = v.get("_links")?.as_object()?;
+    Some(links.keys().filter(|k| *k != "self").cloned().collect())
+}
+
+/// Property names of `/actuator/env` (Boot 2+ `propertySources`, Boot 1 flat sources) and the sensitive
+/// ones that came unmasked
+pub fn parse_actuator_env(v: &Value) -> Option<(Vec<String>, Vec<String>)> {
+    let mut names = Vec::new();
+    let mut secrets = Vec::new();
+    let mut record = |name: &str, value: &Value| {
+        push_unique(&mut names, name);
+        let value = match value {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        if is_sensitive(name) && !is_masked(&value) {
+            push_unique(&mut secrets, name);
+        }
+    };
+    match v.get("propertySources").and_then(Value::as_array) {
+        Some(sources) => {
+            for props in sources.iter().filter_map(|s| s.get("properties").and_then(Value::as_object)) {
+                for (name, entry) in props {
+                    record(name, entry.get("value").unwrap_or(&Value::Null));
+                }
+            }
+        }
+        None => {
+            let sources = v.as_object()?;
+            if !sources.contains_key("systemProperties") && !sources.contains_key("systemEnvironment") {
+                return None;
+            }
+            for props in sources.values().filter_map(Value::as_object) {
+                for (name, value) in props {
+                    record(name, value);
+                }
+            }
+        }
+    }
+    Some((names, secrets))
+}
+
+/// Request mapping patterns of `/actuator/mappings`: the `predicate` of Boot 2+ (`{GET [/api/users]}`) and the
+/// keys of Boot 1 (`{[/api/users],methods=[GET]}`)
+pub fn parse_actuator_mappings(v: &Value) -> Vec<String> {
+    static PATTERN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[(/[^\],\s]*)").unwrap());
+    let mut routes = Vec::new();
+    fn walk(v: &Value, routes: &mut Vec<String>) {
+        match v {
+            Value::Object(map) => {
+                for (k, val) in map {
+                    // Boot 1: keys like `{[/api/users],methods=[GET]}`
+                    if let Some(c) = PATTERN_RE.captures(k) {
+                        push_unique(routes, &c[1]);
+                    }
+                    if k == "predicate" {
+                        if let Some(c) = val.as_str().and_then(|p| PATTERN_RE.captures(p)) {
+                            push_unique(routes, &c[1]);
+                        }
+                    }
+                    walk(val, routes);
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|i| walk(i, routes)),
+            _ => {}
+        }
+    }
+    walk(v, &mut routes);
+    routes
+}
+
+/// Requested paths in Apache's `server-status` (other clients' in-flight requests)
+pub fn parse_server_status(html: &str) -> Option<Vec<String>> {
+    if !html.contains("Apache Server Status for") {
+        return None;
+    }
+    let mut routes = Vec::new();
+    for c in STATUS_REQUEST_RE.captures_iter(html) {
+        push_unique(&mut routes, &c[1]);
+    }
+    Some(routes)
+}
+
+/// HPROF heap dump header, e.g. `JAVA PROFILE 1.0.2`
+pub fn hprof_version(bytes: &[u8]) -> Option<String> {
+    let header = bytes.strip_prefix(b"JAVA PROFILE ")?;
+    let end = header.iter().position(|b| *b == 0)?;
+    Some(format!("JAVA PROFILE {}", String::from_utf8_lossy(&header[..end])))
+}
+
+/// `heap profile: <objects>: <bytes> [...]` line of a Go heap profile
+pub fn parse_pprof_heap(text: &str) -> Option<String> {
+    let c = PPROF_HEAP_RE.captures(text.trim_start())?;
+    Some(format!("Go heap profile: {} objects in use, {} bytes", &c[1], &c[2]))
+}
+
+/// Recognize and parse one response; `None` if the content isn't what the path should serve
+/// (soft-404 pages, login forms)
+pub fn analyze(url: &str, kind: EndpointKind, body: &[u8], content_length: Option<u64>) -> Option<ObservabilityEndpoint> {
+    use EndpointKind::*;
+    let text = String::from_utf8_lossy(body);
+    let json = || serde_json::from_slice::<Value>(body).ok();
+    let found = |risk: RiskLevel, summary: String| Some(ObservabilityEndpoint::new(url.to_string(), kind, risk, summary));
+    match kind {
+        Prometheus => {
+            let (metrics, routes) = parse_prometheus(&text)?;
+            let mut e = found(RiskLevel::Medium, format!("{} metrics, {} routes in labels", metrics.len(), routes.len()))?;
+            e.routes = routes;
+            Some(e)
+        }
+        ActuatorIndex => {
+            let links = parse_actuator_links(&json()?)?;
+            let risky = links.iter().any(|l| matches!(l.as_str(), "env" | "heapdump" | "httptrace" | "httpexchanges" | "jolokia" | "threaddump" | "configprops"));
+            found(if risky { RiskLevel::High } else { RiskLevel::Medium }, format!("Actuator endpoints: {}", links.join(", ")))
+        }
+        ActuatorEnv => {
+            let (names, secrets) = parse_actuator_env(&json()?)?;
+            let summary = format!("{} properties, {} sensitive values unmasked", names.len(), secrets.len());
+            let mut e = found(RiskLevel::Critical, summary)?;
+            e.env_vars = names;
+            e.secrets = secrets;
+            Some(e)
+        }
+        ActuatorHeapdump => {
+            let version = hprof_version(body)?;
+            let size = content_length.filter(|n| *n >= 1024 * 1024).map(|n| format!(", {} MB", n / (1024 * 1024))).unwrap_or_default();
+            let mut e = found(RiskLevel::Critical, "Java heap dump downloadable: memory holds credentials, sessions and keys".to_string())?;
+            e.heap = Some(format!("{}{}", version, size));
+            Some(e)
+        }
+        ActuatorMappings => {
+            let v = json()?;
+            let routes = parse_actuator_mappings(&v);
+            if routes.is_empty() {
+                return None;
+            }
+            let mut e = found(RiskLevel::High, format!("{} request mappings", routes.len()))?;
+            e.routes = routes;
+            Some(e)
+        }
+        ActuatorConfigprops => {
+            let v = json()?;
+            v.get("contexts").or_else(|| v.get("configurationProperties"))?;
+            found(RiskLevel::High, "Configuration properties of all beans".to_string())
+        }
+        ActuatorHttpTrace => {
+            let v = json()?;
+            let traces = v.get("exchanges").or_else(|| v.get("traces")).and_then(Value::as_array).map(Vec::len).or_else(|| v.as_array().map(Vec::len))?;
+            found(RiskLevel::High, format!("{} recent HTTP exchanges with their headers (cookies, Authorization)", traces))
+        }
+        ActuatorThreaddump => {
+            let threads = json()?.get("threads")?.as_array()?.len();
+            found(RiskLevel::High, format!("Thread dump of {} threads", threads))
+        }
+        ActuatorBeans => {
+            json()?.get("contexts")?;
+            found(RiskLevel::Medium, "Application beans and their dependencies".to_string())
+        }
+        ActuatorLoggers => {
+            let loggers = json()?.get("loggers")?.as_object()?.len();
+            found(RiskLevel::Medium, format!("{} loggers; levels can be changed with POST", loggers))
+        }
+        ActuatorGatewayRoutes => {
+            let v = json()?;
+            let routes: Vec<String> = v.as_array()?.iter()
+                .filter_map(|r| Some(format!("{} -> {}", r.get("route_id")?.as_str()?, r.get("uri")?.as_str()?)))
+                .take(MAX_ITEMS)
+                .collect();
+            let mut e = found(RiskLevel::High, format!("{} gateway routes; routes can be added with POST", routes.len()))?;
+            e.routes = routes;
+            Some(e)
+        }
+        Pprof => {
+            if !text.contains("/debug/pprof/") || !(text.contains("Types of profiles available") || text.contains("goroutine")) {
+                return None;
+            }
+            found(RiskLevel::High, "Go pprof index: heap, goroutine and CPU profiles".to_string())
+        }
+        PprofHeap => {
+            let mut e = found(RiskLevel::High, "Go heap profile with allocation stacks".to_string())?;
+            e.heap = Some(parse_pprof_heap(&text)?);
+            Some(e)
+        }
+        PprofCmdline => {
+            // NUL-separated arguments of the running binary, the first one a path
+            if text.contains('<') || text.contains('\n') {
+                return None;
+            }
+            let args: Vec<&str> = text.split('\0').filter(|a| !a.is_empty()).collect();
+            if !args.first()?.contains('/') {
+                return None;
+            }
+            let mut e = found(RiskLevel::High, format!("Command line of the process ({} arguments)", args.len()))?;
+            for a in &args {
+                if let Some((name, value)) = a.trim_start_matches('-').split_once('=') {
+                    if is_sensitive(name) && !is_masked(value) {
+                        push_unique(&mut e.secrets, name);
+                    }
+                }
+            }
+            if !e.secrets.is_empty() {
+                e.risk_level = RiskLevel::Critical;
+            }
+            Some(e)
+        }
+        Expvar => {
+            let v = json()?;
+            v.get("memstats")?;
+            let mut e = found(RiskLevel::High, "Go expvar: memory statistics and command line".to_string())?;
+            if let Some(cmdline) = v.get("cmdline").and_then(Value::as_array) {
+                for a in cmdline.iter().filter_map(Value::as_str) {
+                    if let Some((name, value)) = a.trim_start_matches('-').split_once('=') {
+                        if is_sensitive(name) && !is_masked(value) {
+                            push_unique(&mut e.secrets, name);
+                        }
+                    }
+                }
+            }
+            if !e.secrets.is_empty() {
+                e.risk_level = RiskLevel::Critical;
+            }
+            Some(e)
+        }
+        ApacheStatus => {
+            let routes = parse_server_status(&text)?;
+            let mut e = found(RiskLevel::High, format!("Apache mod_status: {} in-flight requests of other clients", routes.len()))?;
+            e.routes = routes;
+            Some(e)
+        }
+        ApacheInfo => {
+            if !text.contains("Apache Server Information") {
+                return None;
+            }
+            found(RiskLevel::High, "Apache mod_info: loaded modules and configuration".to_string())
+        }
+        NginxStatus => {
+            if !text.starts_with("Active connections:") {
+                return None;
+            }
+            found(RiskLevel::Low, "nginx stub_status connection counters".to_string())
+        }
+    }
+}
+
+/// Probe a base URL for metrics, Spring Boot actuators, Go pprof/expvar and server status pages
+pub async fn scan_observability(client: &Client, base_url: &str) -> Result<Vec<ObservabilityEndpoint>> {
+    let mut results = Vec::new();
+
+    let base = crate::probe::soft404::origin_of(base_url).ok_or_else(|| anyhow::anyhow!("invalid base URL: {}", base_url))?;
+    let mut found_kinds = std::collections::HashSet::new();
+
+    for probe in PROBES {
+        // The Boot 1 paths and alternate metric paths only matter if the first one wasn't there
+        if found_kinds.contains(&probe.kind) {
+            continue;
+        }
+        let test_url = format!("{}{}", base, probe.path);
+
+        let resp = match client.get(&test_url)
+            .timeout(std::time::Duration::from_secs(5))
+            .send_paced()
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+
+        if resp.status().is_success() {
+            let content_length = resp.content_length();
+            // Heap dumps run into gigabytes; the header is enough
+            let limit = if probe.kind == EndpointKind::ActuatorHeapdump { HEAP_PEEK } else { 2 * 1024 * 1024 };
+            let body = crate::probe::http_probe::read_limited(resp, limit).await.map(|(b, _)| b).unwrap_or_default();
+            if let Some(endpoint) = analyze(&test_url, probe.kind, &body, content_length) {
+                found_kinds.insert(probe.kind);
+                results.push(endpoint);
+            }
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    Ok(results)
+}
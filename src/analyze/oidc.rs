@@ -0,0 +1,299 @@
+//! OpenID Connect / JWKS discovery analysis
+//! Each origin's `/.well-known/openid-configuration` (or the OAuth `oauth-authorization-server`
+//! metadata) and the key set it points to are checked for: `none` among the signing algorithms, RSA keys
+//! under 2048 bits, symmetric or private key material in the public JWKS, a code flow without PKCE (or
+//! `plain` only), and tokens of the issuer that live longer than a day or never expire.
+//! `redirect_uri` validation needs a client id: when the scan saw one (`client_id=` in a URL), the
+//! authorization endpoint is asked to send the code to an external host. Only a redirect that really
+//! goes there counts; servers that show a login page first can't be judged this way.
+
+use crate::analyze::open_redirect::{is_external, EVIL_HOST};
+use crate::filter::third_party::on_target;
+use crate::http_client::Paced;
+use crate::security::jwt_forge;
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Discovery documents, in the order they are tried
+pub const DISCOVERY_PATHS: &[&str] = &["/.well-known/openid-configuration", "/.well-known/oauth-authorization-server"];
+
+/// Fields listing signing algorithms
+const ALG_FIELDS: &[&str] = &[
+    "id_token_signing_alg_values_supported",
+    "userinfo_signing_alg_values_supported",
+    "request_object_signing_alg_values_supported",
+    "token_endpoint_auth_signing_alg_values_supported",
+    "introspection_signing_alg_values_supported",
+];
+
+/// RSA keys below this many bits are weak; below `BROKEN_RSA_BITS` they are factorable
+pub const MIN_RSA_BITS: usize = 2048;
+const BROKEN_RSA_BITS: usize = 1024;
+
+/// Tokens valid for longer than this (seconds) are long-lived
+pub const LONG_LIVED_SECS: i64 = 24 * 3600;
+
+/// Bytes read of a discovery document or key set
+const DOC_LIMIT: usize = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OidcCheck {
+    NoneAlgorithm,
+    WeakKey,
+    SymmetricKeyExposed,
+    PrivateKeyExposed,
+    MissingPkce,
+    PlainPkceOnly,
+    OpenRedirectUri,
+    LongLivedToken,
+    NonExpiringToken,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OidcFinding {
+    pub check: OidcCheck,
+    pub severity: String,
+    pub detail: String,
+}
+
+impl OidcFinding {
+    fn new(check: OidcCheck, severity: &str, detail: String) -> Self {
+        Self { check, severity: severity.to_string(), detail }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OidcReport {
+    pub origin: String,
+    pub config_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_uri: Option<String>,
+    pub keys: usize,
+    pub findings: Vec<OidcFinding>,
+}
+
+/// `none` among the advertised signing algorithms
+pub fn check_config(config: &Value) -> Vec<OidcFinding> {
+    let mut findings = Vec::new();
+    for field in ALG_FIELDS {
+        let algs = config[*field].as_array().map(|a| a.iter().filter_map(Value::as_str).collect::<Vec<_>>()).unwrap_or_default();
+        if algs.iter().any(|a| a.eq_ignore_ascii_case("none")) {
+            findings.push(OidcFinding::new(OidcCheck::NoneAlgorithm, "HIGH", format!("{} includes 'none': unsigned tokens may be accepted", field)));
+        }
+    }
+    findings.extend(check_pkce(config));
+    findings
+}
+
+/// A code flow should advertise `S256` in `code_challenge_methods_supported`
+pub fn check_pkce(config: &Value) -> Vec<OidcFinding> {
+    if config["authorization_endpoint"].as_str().is_none() {
+        return Vec::new();
+    }
+    // Without `response_types_supported` the code flow is the default
+    let code_flow = config["response_types_supported"].as_array()
+        .is_none_or(|types| types.iter().filter_map(Value::as_str).any(|t| t.split(' ').any(|p| p == "code")));
+    if !code_flow {
+        return Vec::new();
+    }
+    match config["code_challenge_methods_supported"].as_array() {
+        None => vec![OidcFinding::new(OidcCheck::MissingPkce, "MEDIUM", "Authorization code flow without code_challenge_methods_supported: PKCE is not advertised".to_string())],
+        Some(methods) if !methods.iter().filter_map(Value::as_str).any(|m| m == "S256") => {
+            vec![OidcFinding::new(OidcCheck::PlainPkceOnly, "MEDIUM", "PKCE only with the 'plain' method, the code verifier travels in the clear".to_string())]
+        }
+        Some(_) => Vec::new(),
+    }
+}
+
+/// Bits of a big-endian unsigned integer
+fn bit_length(bytes: &[u8]) -> usize {
+    let bytes: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+    bytes.first().map(|b| bytes.len() * 8 - b.leading_zeros() as usize).unwrap_or(0)
+}
+
+/// Weak RSA moduli, and symmetric or private key material in a public key set
+pub fn check_jwks(jwks: &Value) -> Vec<OidcFinding> {
+    let Some(keys) = jwks["keys"].as_array() else { return Vec::new() };
+    let mut findings = Vec::new();
+    for (i, key) in keys.iter().enumerate() {
+        let name = key["kid"].as_str().map(|k| format!("key '{}'", k)).unwrap_or_else(|| format!("key #{}", i + 1));
+        match key["kty"].as_str().unwrap_or_default() {
+            "RSA" => {
+                let bits = key["n"].as_str()
+                    .and_then(|n| general_purpose::URL_SAFE_NO_PAD.decode(n.trim_end_matches('=')).ok())
+                    .map(|n| bit_length(&n))
+                    .unwrap_or(0);
+                if bits > 0 && bits < MIN_RSA_BITS {
+                    let severity = if bits <= BROKEN_RSA_BITS { "HIGH" } else { "MEDIUM" };
+                    findings.push(OidcFinding::new(OidcCheck::WeakKey, severity, format!("{}: {}-bit RSA modulus (minimum {})", name, bits, MIN_RSA_BITS)));
+                }
+            }
+            "oct" => findings.push(OidcFinding::new(OidcCheck::SymmetricKeyExposed, "HIGH", format!("{}: symmetric (oct) key published, anyone can sign HS tokens", name))),
+            _ => {}
+        }
+        let private: Vec<&str> = ["d", "p", "q", "dp", "dq", "qi"].into_iter().filter(|f| key.get(*f).is_some()).collect();
+        if !private.is_empty() {
+            findings.push(OidcFinding::new(OidcCheck::PrivateKeyExposed, "HIGH", format!("{}: private key parameters published ({})", name, private.join(", "))));
+        }
+    }
+    findings
+}
+
+/// Tokens of `issuer` (all tokens if unknown) valid for more than a day or without `exp`
+pub fn check_token_lifetimes(tokens: &[String], issuer: Option<&str>) -> Vec<OidcFinding> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+    let same = |a: &str, b: &str| a.trim_end_matches('/') == b.trim_end_matches('/');
+    let mut findings = Vec::new();
+    for token in tokens {
+        let Some((_, claims)) = jwt_forge::decode(token) else { continue };
+        if let (Some(issuer), Some(iss)) = (issuer, claims["iss"].as_str()) {
+            if !same(issuer, iss) {
+                continue;
+            }
+        } else if issuer.is_some() {
+            continue;
+        }
+        let subject = claims["sub"].as_str().map(|s| format!("token for '{}'", s)).unwrap_or_else(|| "token".to_string());
+        match claims["exp"].as_i64() {
+            None => findings.push(OidcFinding::new(OidcCheck::NonExpiringToken, "MEDIUM", format!("{} has no exp claim and never expires", subject))),
+            Some(exp) => {
+                let lifetime = exp - claims["iat"].as_i64().or(claims["nbf"].as_i64()).unwrap_or(now);
+                if lifetime > LONG_LIVED_SECS {
+                    findings.push(OidcFinding::new(OidcCheck::LongLivedToken, "MEDIUM", format!("{} is valid for {}h", subject, lifetime / 3600)));
+                }
+            }
+        }
+    }
+    findings
+}
+
+/// Authorization request sending the code for `client_id` to the external host
+pub fn redirect_probe_url(authorization_endpoint: &str, client_id: &str) -> Option<String> {
+    let mut url = url::Url::parse(authorization_endpoint).ok()?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", client_id)
+        .append_pair("redirect_uri", &format!("https://{}/callback", EVIL_HOST))
+        .append_pair("scope", "openid")
+        .append_pair("state", "apihunter");
+    Some(url.to_string())
+}
+
+/// `client_id` values in the query of `urls`
+pub fn client_ids<'a>(urls: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut ids: Vec<String> = urls.into_iter()
+        .filter_map(|u| url::Url::parse(u).ok())
+        .flat_map(|u| u.query_pairs().filter(|(k, _)| k == "client_id").map(|(_, v)| v.into_owned()).collect::<Vec<_>>())
+        .filter(|v| !v.is_empty())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+pub struct OidcAnalyzer {
+    client: Client,
+    no_redirect: Client,
+    /// Key sets and authorization endpoints on other hosts get nothing meant for the target
+    third_party: Client,
+    third_party_no_redirect: Client,
+    domain: String,
+}
+
+impl OidcAnalyzer {
+    /// Analyzer for providers of the scan target `domain`
+    pub fn new(timeout: Duration, domain: &str) -> Result<Self> {
+        let build = |builder: reqwest::ClientBuilder, policy| builder
+            .timeout(timeout)
+            .redirect(policy)
+            .danger_accept_invalid_certs(true)
+            .build();
+        Ok(Self {
+            client: build(crate::http_client::builder("analyze::oidc"), Policy::limited(5))?,
+            no_redirect: build(crate::http_client::builder("analyze::oidc"), Policy::none())?,
+            third_party: build(crate::http_client::third_party(), Policy::limited(5))?,
+            third_party_no_redirect: build(crate::http_client::third_party(), Policy::none())?,
+            domain: domain.to_string(),
+        })
+    }
+
+    /// Client for `url`: the target's, or a third-party one off-target
+    fn client(&self, url: &str, follow: bool) -> &Client {
+        match (on_target(url, &self.domain), follow) {
+            (true, true) => &self.client,
+            (true, false) => &self.no_redirect,
+            (false, true) => &self.third_party,
+            (false, false) => &self.third_party_no_redirect,
+        }
+    }
+
+    async fn json(&self, url: &str) -> Option<Value> {
+        let resp = self.client(url, true).get(url).send_paced().await.ok()?;
+        if !resp.status().is_success() {
+            return None;
+        }
+        let (bytes, _) = crate::probe::http_probe::read_limited(resp, DOC_LIMIT).await.ok()?;
+        serde_json::from_slice::<Value>(&bytes).ok().filter(Value::is_object)
+    }
+
+    /// The authorization endpoint redirects to an unregistered external `redirect_uri`
+    pub async fn test_redirect_uri(&self, authorization_endpoint: &str, client_id: &str) -> Option<OidcFinding> {
+        let url = redirect_probe_url(authorization_endpoint, client_id)?;
+        let resp = self.client(&url, false).get(&url).send_paced().await.ok()?;
+        if !resp.status().is_redirection() {
+            return None;
+        }
+        let location = resp.headers().get(reqwest::header::LOCATION)?.to_str().ok()?.to_string();
+        is_external(&url, &location).then(|| OidcFinding::new(
+            OidcCheck::OpenRedirectUri,
+            "HIGH",
+            format!("client '{}': redirect_uri https://{}/callback accepted, authorization codes go to {}", client_id, EVIL_HOST, location),
+        ))
+    }
+
+    /// Discovery document, key set and checks for `origin`; `None` if it has no discovery document
+    pub async fn analyze(&self, origin: &str, client_ids: &[String], tokens: &[String]) -> Option<OidcReport> {
+        let origin = origin.trim_end_matches('/');
+        let mut found = None;
+        for path in DISCOVERY_PATHS {
+            let url = format!("{}{}", origin, path);
+            if let Some(config) = self.json(&url).await {
+                if config.get("issuer").is_some() || config.get("authorization_endpoint").is_some() || config.get("jwks_uri").is_some() {
+                    found = Some((url, config));
+                    break;
+                }
+            }
+        }
+        let (config_url, config) = found?;
+        let issuer = config["issuer"].as_str().map(str::to_string);
+        let jwks_uri = config["jwks_uri"].as_str().map(str::to_string);
+        let mut findings = check_config(&config);
+
+        let jwks_url = jwks_uri.clone().unwrap_or_else(|| format!("{}/.well-known/jwks.json", origin));
+        let jwks = self.json(&jwks_url).await;
+        let keys = jwks.as_ref().and_then(|j| j["keys"].as_array()).map(Vec::len).unwrap_or(0);
+        if let Some(jwks) = &jwks {
+            findings.extend(check_jwks(jwks));
+        }
+
+        if let Some(endpoint) = config["authorization_endpoint"].as_str() {
+            for client_id in client_ids.iter().take(3) {
+                if let Some(f) = self.test_redirect_uri(endpoint, client_id).await {
+                    findings.push(f);
+                    break;
+                }
+            }
+        }
+        findings.extend(check_token_lifetimes(tokens, issuer.as_deref()));
+        tracing::info!("OIDC: {} ({} keys, {} findings)", config_url, keys, findings.len());
+        Some(OidcReport { origin: origin.to_string(), config_url, issuer, jwks_uri, keys, findings })
+    }
+}
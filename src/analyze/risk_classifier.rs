@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use super::vulnerability_scanner::VulnerabilitySeverity;
+use super::cors_checker::PreflightAnalysis;
 
 /// Strict risk classification with detailed scoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,17 +107,43 @@ impl RiskClassifier {
         }
     }
     
-    /// Classify CORS issues (STRICT: Critical only if credentials + wildcard)
+    /// Classify CORS issues (STRICT: Critical only if credentials + wildcard, or a preflight accepting
+    /// any origin with credentials)
     pub fn classify_cors_issue(
         has_wildcard: bool,
         has_credentials: bool,
         allows_dangerous_methods: bool,
         accepts_null_origin: bool,
         has_sensitive_operations: bool,
+        preflight: Option<&PreflightAnalysis>,
     ) -> RiskClassification {
         let mut score = 0.0;
         let mut factors = Vec::new();
         
+        // CRITICAL: Preflight lets any origin send credentialed requests
+        if let Some(p) = preflight.filter(|p| p.arbitrary_origin && p.credentials) {
+            score = 9.5;
+            factors.push(ClassificationFactor {
+                factor: "Preflight: Any Origin + Credentials".to_string(),
+                weight: 9.5,
+                description: format!("CRITICAL: Any domain can send {} with user credentials", if p.dangerous_methods.is_empty() { "requests".to_string() } else { p.dangerous_methods.join("/") }),
+            });
+            
+            return RiskClassification {
+                final_severity: VulnerabilitySeverity::Critical,
+                score,
+                category: "CORS Misconfiguration".to_string(),
+                justification: "Preflight reflects arbitrary origins with credentials - cross-origin state changes as the victim".to_string(),
+                factors,
+            };
+        }
+        
+        // Preflight answers count like the simple-request headers
+        let preflight_methods = preflight.is_some_and(|p| !p.dangerous_methods.is_empty());
+        let accepts_null_origin = accepts_null_origin || preflight.is_some_and(|p| p.null_origin);
+        let allows_dangerous_methods = allows_dangerous_methods || preflight_methods;
+        let has_sensitive_operations = has_sensitive_operations || preflight_methods;
+        
         // CRITICAL: Wildcard + Credentials = Account Takeover
         if has_wildcard && has_credentials {
             score = 9.5;
@@ -155,6 +182,22 @@ impl RiskClassifier {
                 weight: 4.0,
                 description: "Any domain can read responses (no credentials)".to_string(),
             });
+        } else if preflight.is_some_and(|p| p.arbitrary_origin) {
+            score = 4.0;
+            factors.push(ClassificationFactor {
+                factor: "Preflight: Any Origin".to_string(),
+                weight: 4.0,
+                description: "Preflights from any domain are accepted (no credentials)".to_string(),
+            });
+        }
+        
+        if preflight.is_some_and(|p| p.any_header && (p.arbitrary_origin || p.null_origin)) {
+            score += 1.0;
+            factors.push(ClassificationFactor {
+                factor: "Any Request Header".to_string(),
+                weight: 1.0,
+                description: "Preflight allows arbitrary request headers".to_string(),
+            });
         }
         
         if allows_dangerous_methods {
@@ -379,4 +422,52 @@ impl RiskClassifier {
             factors,
         }
     }
+    
+    /// Classify template injection (STRICT: Critical only if the engine is known, i.e. public RCE payloads apply)
+    pub fn classify_ssti(
+        engine: &str,
+        engine_identified: bool,
+        confirmations: usize,
+    ) -> RiskClassification {
+        let mut score = 6.0;
+        let mut factors = vec![ClassificationFactor {
+            factor: "Expression Evaluated".to_string(),
+            weight: 6.0,
+            description: "Arithmetic in a template expression was computed by the server".to_string(),
+        }];
+        
+        if confirmations >= 2 {
+            score += 1.5;
+            factors.push(ClassificationFactor {
+                factor: "Reproducible".to_string(),
+                weight: 1.5,
+                description: format!("Evaluated {} times with different operands", confirmations),
+            });
+        }
+        
+        if engine_identified {
+            score += 2.0;
+            factors.push(ClassificationFactor {
+                factor: "Engine Identified".to_string(),
+                weight: 2.0,
+                description: format!("{} - known sandbox escapes lead to code execution", engine),
+            });
+        }
+        
+        let severity = if score >= 8.0 {
+            VulnerabilitySeverity::Critical
+        } else if score >= 6.0 {
+            VulnerabilitySeverity::High
+        } else {
+            VulnerabilitySeverity::Medium
+        };
+        
+        RiskClassification {
+            final_severity: severity,
+            score,
+            category: "Server-Side Template Injection".to_string(),
+            justification: format!("Template expressions evaluated ({} engine) with score {:.1}", engine, score),
+            factors,
+        }
+    }
 }
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use super::csp::CspAnalysis;
+
 #[derive(Debug, Clone)]
 pub struct SecurityHeaderAnalysis {
     pub has_hsts: bool,
@@ -8,12 +10,20 @@ pub struct SecurityHeaderAnalysis {
     pub has_x_content_type_options: bool,
     pub has_referrer_policy: bool,
     pub has_permissions_policy: bool,
+    /// Graded Content-Security-Policy (also when it is missing)
+    pub csp: CspAnalysis,
     pub missing_headers: Vec<String>,
     pub security_score: u8, // 0-100
     pub findings: Vec<String>,
 }
 
 impl SecurityHeaderAnalysis {
+    /// Analyze the headers captured during probing, without fetching the URL again
+    pub fn from_event(event: &crate::output::writer_jsonl::RawEvent) -> Self {
+        let headers: HashMap<String, String> = event.headers.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        Self::analyze(&headers)
+    }
+
     pub fn analyze(headers: &HashMap<String, String>) -> Self {
         let mut missing = Vec::new();
         let mut findings = Vec::new();
@@ -27,12 +37,17 @@ impl SecurityHeaderAnalysis {
             score = score.saturating_sub(15);
         }
 
-        // Check for CSP
+        // Check for CSP: missing, or graded by what it still allows
         let has_csp = headers.contains_key("content-security-policy");
+        let csp = CspAnalysis::analyze(headers);
         if !has_csp {
             missing.push("Content-Security-Policy".to_string());
             findings.push("Missing CSP - vulnerable to XSS".to_string());
             score = score.saturating_sub(20);
+        } else if matches!(csp.grade, 'C' | 'D' | 'F') {
+            let worst: Vec<&str> = csp.issues.iter().filter(|i| i.severity == csp.severity()).map(|i| i.detail.as_str()).collect();
+            findings.push(format!("Weak CSP (grade {}) - {}", csp.grade, worst.join("; ")));
+            score = score.saturating_sub(if csp.grade == 'C' { 5 } else { 15 });
         }
 
         // Check for X-Frame-Options
@@ -87,6 +102,7 @@ impl SecurityHeaderAnalysis {
             has_x_content_type_options: has_x_content,
             has_referrer_policy: has_referrer,
             has_permissions_policy: has_permissions,
+            csp,
             missing_headers: missing,
             security_score: score,
             findings,
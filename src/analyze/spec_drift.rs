@@ -0,0 +1,366 @@
+//! API spec drift against a provided OpenAPI/Swagger document (`--spec`)
+//! The documented paths are probed together with the discovered candidates (path parameters filled with
+//! `1`), then the probe results on the spec's hosts are compared with the document:
+//! - shadow endpoints: live paths no documented path matches, and live methods a documented path
+//!   doesn't list;
+//! - zombie endpoints: documented paths none of whose URLs answered (404, 410, soft-404, no response),
+//!   and documented methods the server refuses with 405/501.
+//!
+//! Servers on other hosts than the target (a production spec scanned against staging) are rebased onto
+//! the target, keeping their base path.
+
+use crate::output::writer_jsonl::RawEvent;
+use crate::output::Annotation;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Methods compared with the spec
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Statuses that mean the path isn't there
+const DEAD: &[u16] = &[404, 410];
+
+/// Statuses that mean the method isn't there
+const REFUSED: &[u16] = &[404, 405, 410, 501];
+
+/// The operations of an OpenAPI 3 or Swagger 2 document
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiSpec {
+    pub source: String,
+    pub title: Option<String>,
+    pub version: Option<String>,
+    /// Absolute base URLs without a trailing slash
+    pub bases: Vec<String>,
+    /// Path template (`/users/{id}`, relative to the bases) → documented methods
+    pub paths: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowEndpoint {
+    /// First URL seen
+    pub url: String,
+    /// Observed path, ID segments templated (`/internal/users/{userId}`)
+    pub path: String,
+    pub status: u16,
+    /// Live methods missing from the spec
+    pub methods: Vec<String>,
+    /// The documented path, when only methods are undocumented
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documented_path: Option<String>,
+    pub severity: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZombieEndpoint {
+    /// Documented path template
+    pub path: String,
+    pub methods: Vec<String>,
+    /// URLs probed for it
+    pub urls: Vec<String>,
+    pub reason: String,
+    /// The path has parameters, probed with placeholder values: a 404 may only mean the object doesn't exist
+    pub placeholder: bool,
+    pub severity: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub spec: String,
+    pub documented_paths: usize,
+    pub documented_operations: usize,
+    pub shadow: Vec<ShadowEndpoint>,
+    pub zombies: Vec<ZombieEndpoint>,
+}
+
+impl DriftReport {
+    pub fn is_empty(&self) -> bool {
+        self.shadow.is_empty() && self.zombies.is_empty()
+    }
+}
+
+/// `/users/{id}/` → `/users/{id}`
+fn normalize_path(path: &str) -> String {
+    let trimmed = path.trim().trim_end_matches('/');
+    if trimmed.starts_with('/') { trimmed.to_string() } else { format!("/{}", trimmed) }
+}
+
+/// Server URL with its variables replaced by their defaults
+fn server_url(server: &Value) -> Option<String> {
+    let mut url = server.get("url")?.as_str()?.to_string();
+    for (name, var) in server.get("variables").and_then(Value::as_object).into_iter().flatten() {
+        if let Some(default) = var.get("default").and_then(Value::as_str) {
+            url = url.replace(&format!("{{{}}}", name), default);
+        }
+    }
+    Some(url)
+}
+
+fn in_scope(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+impl ApiSpec {
+    /// Read a JSON or YAML document
+    pub fn load(path: &Path, domain: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        let value: Value = serde_json::from_str(&text)
+            .or_else(|_| serde_yaml::from_str(&text))
+            .with_context(|| format!("{} is neither JSON nor YAML", path.display()))?;
+        Self::from_value(&value, &path.display().to_string(), domain)
+    }
+
+    /// Operations and base URLs of a parsed document; relative and out-of-scope servers are resolved
+    /// against `https://<domain>`
+    pub fn from_value(spec: &Value, source: &str, domain: &str) -> Result<Self> {
+        let Some(raw_paths) = spec.get("paths").and_then(Value::as_object) else {
+            bail!("{} is not an OpenAPI or Swagger document (no `paths`)", source)
+        };
+        let target = url::Url::parse(&format!("https://{}/", domain)).with_context(|| format!("invalid target {}", domain))?;
+
+        // OpenAPI 3 `servers`; Swagger 2 `schemes`, `host` and `basePath`
+        let mut raw_bases: Vec<String> = spec.get("servers").and_then(Value::as_array).into_iter().flatten().filter_map(server_url).collect();
+        if raw_bases.is_empty() {
+            let base_path = spec.get("basePath").and_then(Value::as_str).unwrap_or("/");
+            raw_bases.push(match spec.get("host").and_then(Value::as_str) {
+                Some(host) => {
+                    let scheme = spec.get("schemes").and_then(Value::as_array).and_then(|s| s.first()).and_then(Value::as_str).unwrap_or("https");
+                    format!("{}://{}{}", scheme, host, base_path)
+                }
+                None => base_path.to_string(),
+            });
+        }
+        let mut bases = Vec::new();
+        for raw in raw_bases {
+            let Ok(mut url) = target.join(&raw) else { continue };
+            if !url.host_str().is_some_and(|h| in_scope(h, domain)) {
+                url = target.join(url.path()).unwrap_or_else(|_| target.clone());
+            }
+            let base = url.as_str().split(['?', '#']).next().unwrap_or_default().trim_end_matches('/').to_string();
+            if !bases.contains(&base) {
+                bases.push(base);
+            }
+        }
+
+        let mut paths = BTreeMap::new();
+        for (path, item) in raw_paths {
+            let methods: BTreeSet<String> = item.as_object().into_iter().flatten()
+                .map(|(k, _)| k.to_ascii_uppercase())
+                .filter(|m| METHODS.contains(&m.as_str()) || matches!(m.as_str(), "HEAD" | "OPTIONS" | "TRACE"))
+                .collect();
+            if !methods.is_empty() {
+                paths.entry(normalize_path(path)).or_insert_with(BTreeSet::new).extend(methods);
+            }
+        }
+        Ok(Self {
+            source: source.to_string(),
+            title: spec.pointer("/info/title").and_then(Value::as_str).map(str::to_string),
+            version: spec.pointer("/info/version").and_then(Value::as_str).map(str::to_string),
+            bases,
+            paths,
+        })
+    }
+
+    pub fn operation_count(&self) -> usize {
+        self.paths.values().map(|m| m.len()).sum()
+    }
+
+    /// One URL per documented path and base, path parameters filled with `1`
+    pub fn probe_urls(&self) -> Vec<String> {
+        let mut urls = Vec::new();
+        for base in &self.bases {
+            for path in self.paths.keys() {
+                urls.push(format!("{}{}", base, concrete(path)));
+            }
+        }
+        urls
+    }
+}
+
+/// `/users/{id}/orders` → `/users/1/orders`
+fn concrete(template: &str) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        out.push_str(&rest[..start]);
+        out.push('1');
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Regex matching the concrete paths of a template
+fn matcher(template: &str) -> Option<Regex> {
+    let mut pattern = String::from("^");
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        pattern.push_str(&regex::escape(&rest[..start]));
+        pattern.push_str("[^/]+");
+        rest = &rest[start + len + 1..];
+    }
+    pattern.push_str(&regex::escape(rest));
+    pattern.push_str("/?$");
+    Regex::new(&pattern).ok()
+}
+
+/// What the probe saw of one documented path
+#[derive(Default)]
+struct Observed {
+    live: bool,
+    statuses: BTreeSet<u16>,
+    urls: BTreeSet<String>,
+    methods: BTreeSet<String>,
+    refused: BTreeSet<String>,
+}
+
+/// Live methods of an event: GET unless refused, and the method matrix
+fn live_methods(ev: &RawEvent) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut live = BTreeSet::new();
+    let mut refused = BTreeSet::new();
+    let statuses = std::iter::once(("GET".to_string(), ev.status)).chain(ev.methods.iter().map(|(m, s)| (m.to_ascii_uppercase(), *s)));
+    for (method, status) in statuses {
+        if !METHODS.contains(&method.as_str()) {
+            continue;
+        }
+        if REFUSED.contains(&status) {
+            refused.insert(method);
+        } else {
+            live.insert(method);
+        }
+    }
+    refused.retain(|m| !live.contains(m));
+    (live, refused)
+}
+
+/// Shadow and zombie endpoints of the probe results against the spec
+pub fn compare(spec: &ApiSpec, events: &[RawEvent]) -> DriftReport {
+    // Base host and path per base URL
+    let bases: Vec<(String, String)> = spec.bases.iter()
+        .filter_map(|b| url::Url::parse(b).ok())
+        .filter_map(|u| Some((u.host_str()?.to_ascii_lowercase(), u.path().trim_end_matches('/').to_string())))
+        .collect();
+    // Literal paths first, so `/users/me` wins over `/users/{id}`
+    let mut templates: Vec<(&String, Regex)> = spec.paths.keys().filter_map(|p| Some((p, matcher(p)?))).collect();
+    templates.sort_by_key(|(p, _)| p.matches('{').count());
+
+    let mut observed: BTreeMap<&String, Observed> = BTreeMap::new();
+    let mut shadow: BTreeMap<(String, String), ShadowEndpoint> = BTreeMap::new();
+    for ev in events {
+        // A WAF block page says nothing about the endpoint behind it
+        if ev.annotations.contains(&Annotation::WafBlock) {
+            continue;
+        }
+        let Ok(url) = url::Url::parse(&ev.final_url) else { continue };
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let path = url.path();
+        let relative: Vec<&str> = bases.iter()
+            .filter(|(h, _)| *h == host)
+            .filter_map(|(_, base_path)| path.strip_prefix(base_path.as_str()))
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+            .collect();
+        if relative.is_empty() {
+            continue;
+        }
+        let live = !DEAD.contains(&ev.status) && !ev.annotations.contains(&Annotation::Soft404);
+        let documented = templates.iter()
+            .find(|(_, re)| relative.iter().any(|rest| re.is_match(if rest.is_empty() { "/" } else { rest })))
+            .map(|(p, _)| *p);
+
+        match documented {
+            Some(template) => {
+                let entry = observed.entry(template).or_default();
+                entry.urls.insert(ev.final_url.clone());
+                entry.statuses.insert(ev.status);
+                if live {
+                    entry.live = true;
+                    let (methods, refused) = live_methods(ev);
+                    entry.methods.extend(methods);
+                    entry.refused.extend(refused);
+                }
+            }
+            None if live => {
+                let (methods, _) = live_methods(ev);
+                let template = crate::output::openapi::path_template(&ev.final_url).map(|(_, p, _)| p).unwrap_or_else(|| path.to_string());
+                let entry = shadow.entry((host.clone(), template.clone())).or_insert_with(|| ShadowEndpoint {
+                    url: ev.final_url.clone(),
+                    path: template,
+                    status: ev.status,
+                    methods: Vec::new(),
+                    documented_path: None,
+                    severity: if (200..300).contains(&ev.status) { "MEDIUM" } else { "LOW" }.to_string(),
+                });
+                for m in methods {
+                    if !entry.methods.contains(&m) {
+                        entry.methods.push(m);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    let mut zombies = Vec::new();
+    let mut method_shadows = Vec::new();
+    for (template, documented) in &spec.paths {
+        let placeholder = template.contains('{');
+        let seen = observed.remove(template).unwrap_or_default();
+        let urls: Vec<String> = if seen.urls.is_empty() {
+            spec.bases.iter().map(|b| format!("{}{}", b, concrete(template))).collect()
+        } else {
+            seen.urls.iter().cloned().collect()
+        };
+        if !seen.live {
+            let reason = if seen.statuses.is_empty() {
+                "no response".to_string()
+            } else {
+                format!("answered {}", seen.statuses.iter().map(u16::to_string).collect::<Vec<_>>().join(", "))
+            };
+            zombies.push(ZombieEndpoint {
+                path: template.clone(),
+                methods: documented.iter().cloned().collect(),
+                urls,
+                reason,
+                placeholder,
+                severity: if placeholder { "INFO" } else { "LOW" }.to_string(),
+            });
+            continue;
+        }
+        let refused: Vec<String> = documented.iter().filter(|m| seen.refused.contains(*m)).cloned().collect();
+        if !refused.is_empty() {
+            zombies.push(ZombieEndpoint {
+                path: template.clone(),
+                methods: refused,
+                urls: urls.clone(),
+                reason: "the server refuses the documented method (404/405/501)".to_string(),
+                placeholder: false,
+                severity: "LOW".to_string(),
+            });
+        }
+        let undocumented: Vec<String> = seen.methods.iter().filter(|m| !documented.contains(*m)).cloned().collect();
+        if !undocumented.is_empty() {
+            let state_changing = undocumented.iter().any(|m| m != "GET");
+            method_shadows.push(ShadowEndpoint {
+                url: urls[0].clone(),
+                path: template.clone(),
+                status: *seen.statuses.iter().next().unwrap_or(&0),
+                methods: undocumented,
+                documented_path: Some(template.clone()),
+                severity: if state_changing { "MEDIUM" } else { "LOW" }.to_string(),
+            });
+        }
+    }
+
+    DriftReport {
+        spec: spec.source.clone(),
+        documented_paths: spec.paths.len(),
+        documented_operations: spec.operation_count(),
+        shadow: shadow.into_values().chain(method_shadows).collect(),
+        zombies,
+    }
+}
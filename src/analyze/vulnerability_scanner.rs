@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use anyhow::Result;
 use reqwest::Client;
 use serde::{Serialize, Deserialize};
@@ -42,7 +43,7 @@ impl VulnerabilityScanner {
         ];
         
         let mut evidence = Vec::new();
-        let baseline = client.get(url).send().await;
+        let baseline = client.get(url).send_paced().await;
         let baseline_body = if let Ok(resp) = baseline {
             resp.text().await.unwrap_or_default()
         } else {
@@ -57,7 +58,7 @@ impl VulnerabilityScanner {
                 format!("{}?test={}", url, urlencoding::encode(payload))
             };
             
-            if let Ok(resp) = client.get(&test_url).send().await {
+            if let Ok(resp) = client.get(&test_url).send_paced().await {
                 let body = resp.text().await.unwrap_or_default();
                 
                 // Check for SQL error messages
@@ -103,7 +104,7 @@ impl VulnerabilityScanner {
         let mut evidence = Vec::new();
         
         // Test 1: Try without authentication
-        let resp_no_auth = client.get(url).send().await?;
+        let resp_no_auth = client.get(url).send_paced().await?;
         let status_no_auth = resp_no_auth.status().as_u16();
         
         // Test 2: Try with broken/tampered tokens
@@ -119,7 +120,7 @@ impl VulnerabilityScanner {
         for (header, value) in broken_tokens {
             let resp = client.get(url)
                 .header(header, value)
-                .send()
+                .send_paced()
                 .await?;
             
             if resp.status().as_u16() == 200 && status_no_auth != 200 {
@@ -160,7 +161,7 @@ impl VulnerabilityScanner {
                 format!("{}?file={}", url, urlencoding::encode(payload))
             };
             
-            if let Ok(resp) = client.get(&test_url).send().await {
+            if let Ok(resp) = client.get(&test_url).send_paced().await {
                 let body = resp.text().await.unwrap_or_default();
                 
                 // Check for file contents
@@ -209,7 +210,7 @@ impl VulnerabilityScanner {
             
             if let Ok(resp) = client.get(&test_url)
                 .timeout(std::time::Duration::from_secs(3))
-                .send()
+                .send_paced()
                 .await {
                 
                 let body = resp.text().await.unwrap_or_default();
@@ -262,7 +263,7 @@ impl VulnerabilityScanner {
                 format!("{}?cmd={}", url, urlencoding::encode(payload))
             };
             
-            if let Ok(resp) = client.get(&test_url).send().await {
+            if let Ok(resp) = client.get(&test_url).send_paced().await {
                 let body = resp.text().await.unwrap_or_default();
                 
                 // Check for command output
@@ -311,7 +312,7 @@ impl VulnerabilityScanner {
                 format!("{}?q={}", url, urlencoding::encode(payload))
             };
             
-            if let Ok(resp) = client.get(&test_url).send().await {
+            if let Ok(resp) = client.get(&test_url).send_paced().await {
                 let body = resp.text().await.unwrap_or_default();
                 
                 // Check if payload is reflected unencoded
@@ -403,7 +404,8 @@ impl VulnerabilityScanner {
                         }
                     }
                     
-                    // Fast timeout (2 seconds max per request)
+                    // Fast timeout (2 seconds max per request, not counting the wait for the rate cap)
+                    crate::http_client::pace().await;
                     if let Ok(resp) = tokio::time::timeout(
                         tokio::time::Duration::from_secs(2),
                         client.get(&test_url).send()
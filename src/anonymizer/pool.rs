@@ -0,0 +1,329 @@
+//! Proxy pool (`--proxy-list`)
+//! Proxies from a file are health-checked before the scan, then every client picks one per connection,
+//! either sticky per target host or rotating per request. Outcomes reported by the send paths in
+//! `http_client` count against the proxy that carried them: a proxy that stops connecting or keeps
+//! getting blocked (407, or a run of 403/429) is blacklisted and the rest of the pool takes over.
+//! With per-request rotation the carrying proxy is known from the response's peer address, so requests
+//! that fail without a response are not attributed; only the health check catches dead proxies there.
+
+use anyhow::{bail, Result};
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Consecutive connection failures before a proxy is considered dead
+const DEAD_STRIKES: u32 = 3;
+/// Consecutive 403/429 responses before a proxy is considered blocked
+const BLOCK_STRIKES: u32 = 5;
+
+static SHARED: Lazy<RwLock<Option<Arc<ProxyPool>>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    /// A new proxy for every request (connections are not reused)
+    Request,
+    /// The same proxy for all requests to a host while it stays healthy
+    Host,
+}
+
+impl Rotation {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "request" => Some(Self::Request),
+            "host" => Some(Self::Host),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyState {
+    Healthy,
+    /// Failed the health check or stopped connecting
+    Dead,
+    /// Rejected by the proxy (407) or by the target (repeated 403/429)
+    Blocked,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyStats {
+    /// Proxy URL without credentials
+    pub proxy: String,
+    pub state: ProxyState,
+    pub requests: usize,
+    pub failures: usize,
+    pub blocked_responses: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+struct Entry {
+    url: String,
+    display: String,
+    state: Mutex<EntryState>,
+}
+
+struct EntryState {
+    state: ProxyState,
+    requests: usize,
+    failures: usize,
+    blocked_responses: usize,
+    /// Consecutive failures / blocks since the last good response
+    strikes: u32,
+    blocks: u32,
+    check_ms: Option<u64>,
+    reason: Option<String>,
+    /// Socket address of the proxy, as seen by the health check (or given as an IP literal)
+    addr: Option<SocketAddr>,
+}
+
+pub struct ProxyPool {
+    entries: Vec<Entry>,
+    rotation: Rotation,
+    next: AtomicUsize,
+    /// Host -> its sticky proxy with `Rotation::Host`
+    assigned: Mutex<HashMap<String, usize>>,
+    exhausted_warned: std::sync::atomic::AtomicBool,
+}
+
+/// One proxy per line: `scheme://[user:pass@]host:port` or `host:port` (HTTP); `#` starts a comment
+pub fn parse_list(text: &str) -> Result<Vec<String>> {
+    let mut out = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let url = if line.contains("://") { line.to_string() } else { format!("http://{}", line) };
+        let scheme = url.split("://").next().unwrap_or("").to_ascii_lowercase();
+        if !matches!(scheme.as_str(), "http" | "https" | "socks5" | "socks5h") {
+            bail!("line {}: unsupported proxy scheme '{}'", n + 1, scheme);
+        }
+        if reqwest::Proxy::all(url.as_str()).is_err() {
+            bail!("line {}: invalid proxy '{}'", n + 1, line);
+        }
+        if !out.contains(&url) {
+            out.push(url);
+        }
+    }
+    Ok(out)
+}
+
+pub fn load_list(path: &Path) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read proxy list {}: {}", path.display(), e))?;
+    let list = parse_list(&text).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    if list.is_empty() {
+        bail!("{}: no proxies", path.display());
+    }
+    Ok(list)
+}
+
+/// Address of a proxy given by IP
+fn literal_addr(url: &str) -> Option<SocketAddr> {
+    let u = url::Url::parse(url).ok()?;
+    let ip = u.host_str()?.trim_start_matches('[').trim_end_matches(']').parse().ok()?;
+    Some(SocketAddr::new(ip, u.port()?))
+}
+
+/// `scheme://host:port`, credentials dropped
+fn display(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(u) => format!("{}://{}:{}", u.scheme(), u.host_str().unwrap_or("?"), u.port_or_known_default().unwrap_or(0)),
+        Err(_) => url.to_string(),
+    }
+}
+
+impl ProxyPool {
+    pub fn new(urls: Vec<String>, rotation: Rotation) -> Self {
+        let entries = urls.into_iter()
+            .map(|url| Entry {
+                display: display(&url),
+                state: Mutex::new(EntryState {
+                    state: ProxyState::Healthy, requests: 0, failures: 0, blocked_responses: 0,
+                    strikes: 0, blocks: 0, check_ms: None, reason: None, addr: literal_addr(&url),
+                }),
+                url,
+            })
+            .collect();
+        Self {
+            entries,
+            rotation,
+            next: AtomicUsize::new(0),
+            assigned: Mutex::new(HashMap::new()),
+            exhausted_warned: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn healthy(&self) -> usize {
+        self.entries.iter().filter(|e| e.state.lock().state == ProxyState::Healthy).count()
+    }
+
+    /// Request `check_url` through every proxy; proxies that don't answer, or answer 407, are dropped
+    /// before the scan starts
+    pub async fn health_check(&self, check_url: &str, timeout: Duration, concurrency: usize) {
+        stream::iter(self.entries.iter())
+            .for_each_concurrent(concurrency.max(1), |entry| async move {
+                let outcome = async {
+                    let proxy = reqwest::Proxy::all(entry.url.as_str())?;
+                    let client = crate::net::apply(reqwest::Client::builder())
+                        .proxy(proxy)
+                        .timeout(timeout)
+                        .danger_accept_invalid_certs(true)
+                        .redirect(reqwest::redirect::Policy::none())
+                        .build()?;
+                    let start = Instant::now();
+                    let resp = client.head(check_url).send().await?;
+                    Ok::<_, reqwest::Error>((resp.status().as_u16(), resp.remote_addr(), start.elapsed()))
+                }.await;
+                let mut s = entry.state.lock();
+                match outcome {
+                    Ok((407, _, _)) => {
+                        s.state = ProxyState::Blocked;
+                        s.reason = Some("proxy authentication required (407)".to_string());
+                    }
+                    Ok((_, addr, elapsed)) => {
+                        s.check_ms = Some(elapsed.as_millis() as u64);
+                        s.addr = addr.or(s.addr);
+                    }
+                    Err(e) => {
+                        s.state = ProxyState::Dead;
+                        s.reason = Some(format!("health check: {}", crate::probe::failure::classify(&e)));
+                    }
+                }
+            })
+            .await;
+    }
+
+    fn usable(&self, idx: usize) -> bool {
+        self.entries[idx].state.lock().state == ProxyState::Healthy
+    }
+
+    /// Proxy for a request to `host`. With every proxy blacklisted the whole list is used again rather
+    /// than falling back to a direct connection.
+    pub fn select(&self, host: &str) -> String {
+        let n = self.entries.len();
+        let mut assigned = self.assigned.lock();
+        if self.rotation == Rotation::Host {
+            if let Some(&idx) = assigned.get(host) {
+                if self.usable(idx) {
+                    return self.entries[idx].url.clone();
+                }
+            }
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let idx = (0..n).map(|i| (start + i) % n).find(|&i| self.usable(i)).unwrap_or_else(|| {
+            if !self.exhausted_warned.swap(true, Ordering::Relaxed) {
+                tracing::warn!("All proxies in the pool are blacklisted; reusing them");
+            }
+            start % n
+        });
+        if self.rotation == Rotation::Host {
+            assigned.insert(host.to_string(), idx);
+        }
+        self.entries[idx].url.clone()
+    }
+
+    /// Count the outcome of a request to `host` against the proxy that carried it: the host's sticky
+    /// proxy, or with `Rotation::Request` the one at `peer`, the response's remote address
+    pub fn report(&self, host: &str, peer: Option<SocketAddr>, outcome: Result<u16, crate::probe::failure::FailureKind>) {
+        let idx = match self.rotation {
+            Rotation::Host => self.assigned.lock().get(host).copied(),
+            // Another request to the host may have picked a different proxy since
+            Rotation::Request => peer.and_then(|peer| self.entries.iter().position(|e| e.state.lock().addr == Some(peer))),
+        };
+        let Some(idx) = idx else { return };
+        let entry = &self.entries[idx];
+        let mut s = entry.state.lock();
+        s.requests += 1;
+        match outcome {
+            Ok(407) => {
+                s.blocked_responses += 1;
+                s.state = ProxyState::Blocked;
+                s.reason = Some("proxy authentication required (407)".to_string());
+            }
+            Ok(403) | Ok(429) => {
+                s.blocked_responses += 1;
+                s.strikes = 0;
+                s.blocks += 1;
+                if s.blocks >= BLOCK_STRIKES && s.state == ProxyState::Healthy {
+                    s.state = ProxyState::Blocked;
+                    s.reason = Some(format!("{} consecutive 403/429 responses", s.blocks));
+                }
+            }
+            Ok(_) => {
+                s.strikes = 0;
+                s.blocks = 0;
+            }
+            // A redirect loop is the target's doing
+            Err(crate::probe::failure::FailureKind::Redirect) => s.failures += 1,
+            // Through a proxy, refused, reset, DNS (of the proxy host) and timeouts point at the proxy
+            Err(kind) => {
+                s.failures += 1;
+                s.strikes += 1;
+                if s.strikes >= DEAD_STRIKES && s.state == ProxyState::Healthy {
+                    s.state = ProxyState::Dead;
+                    s.reason = Some(format!("{} consecutive failures ({})", s.strikes, kind));
+                }
+            }
+        }
+        if s.state != ProxyState::Healthy {
+            tracing::debug!("proxy {} blacklisted: {}", entry.display, s.reason.as_deref().unwrap_or(""));
+        }
+    }
+
+    pub fn stats(&self) -> Vec<ProxyStats> {
+        self.entries.iter()
+            .map(|e| {
+                let s = e.state.lock();
+                ProxyStats {
+                    proxy: e.display.clone(),
+                    state: s.state,
+                    requests: s.requests,
+                    failures: s.failures,
+                    blocked_responses: s.blocked_responses,
+                    check_ms: s.check_ms,
+                    reason: s.reason.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// reqwest proxy that asks the pool on every new connection
+    pub fn to_proxy(self: &Arc<Self>) -> reqwest::Proxy {
+        let pool = self.clone();
+        reqwest::Proxy::custom(move |url| url.host_str().map(|h| pool.select(h)))
+    }
+}
+
+/// Use `pool` for all clients built afterwards
+pub fn configure(pool: Option<ProxyPool>) {
+    *SHARED.write() = pool.map(Arc::new);
+}
+
+pub fn shared() -> Option<Arc<ProxyPool>> {
+    SHARED.read().clone()
+}
@@ -0,0 +1,164 @@
+//! Scripted login
+//! Runs a one-request YAML recipe before probing and turns the response into credentials for the
+//! scan (see `auth::session`), so tokens don't have to be harvested by hand. `{{env.NAME}}` in the
+//! recipe reads environment variables, which keeps passwords out of the file.
+//!
+//! ```yaml
+//! url: https://app.example.com/api/login
+//! json: { username: alice, password: "{{env.APP_PASSWORD}}" }
+//! extract:
+//!   token: "$.data.access_token"   # `$.json.path`, `header:Name` or `regex:...`
+//!   cookies: [session]             # from Set-Cookie; "*" for all
+//! set: "Authorization: Bearer {{token}}"
+//! expect: { status: [200] }
+//! ```
+
+use crate::http_client::Paced;
+use super::session::AuthConfig;
+use crate::fuzz::flows::{capture, render, Condition};
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::time::Duration;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginRecipe {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// JSON request body; string values are templated
+    #[serde(default)]
+    pub json: Option<Value>,
+    /// Form-encoded request body
+    #[serde(default)]
+    pub form: Option<HashMap<String, String>>,
+    /// Raw request body
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub extract: Extract,
+    /// Credential header built from the extracted token (`Name: value` with `{{token}}`); without it
+    /// the token is sent as a bearer token
+    #[serde(default)]
+    pub set: Option<String>,
+    /// Required response, e.g. `{ status: [200] }`
+    #[serde(default)]
+    pub expect: Option<Condition>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Extract {
+    /// Capture source for the token, as in flows: `$.json.path`, `header:Name` or `regex:pattern`
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Cookie names to keep from `Set-Cookie` (`*` for all)
+    #[serde(default)]
+    pub cookies: Vec<String>,
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+impl LoginRecipe {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read login recipe {}", path.display()))?;
+        Self::parse(&data).with_context(|| format!("invalid login recipe {}", path.display()))
+    }
+
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let recipe: LoginRecipe = serde_yaml::from_str(yaml)?;
+        if recipe.extract.token.is_none() && recipe.extract.cookies.is_empty() {
+            bail!("recipe extracts nothing, set extract.token or extract.cookies");
+        }
+        if recipe.set.is_some() && recipe.extract.token.is_none() {
+            bail!("'set' needs extract.token");
+        }
+        if [recipe.json.is_some(), recipe.form.is_some(), recipe.body.is_some()].iter().filter(|b| **b).count() > 1 {
+            bail!("use only one of json, form and body");
+        }
+        Ok(recipe)
+    }
+}
+
+/// `env.NAME` for every environment variable
+fn env_vars() -> HashMap<String, String> {
+    std::env::vars().map(|(k, v)| (format!("env.{}", k), v)).collect()
+}
+
+/// Send the login request and build credentials from the response
+pub async fn login(client: &Client, recipe: &LoginRecipe) -> Result<AuthConfig> {
+    let vars = env_vars();
+    let method = reqwest::Method::from_bytes(recipe.method.to_uppercase().as_bytes())
+        .map_err(|_| anyhow::anyhow!("invalid method {}", recipe.method))?;
+    let mut req = client.request(method, render(&recipe.url, &vars)).timeout(Duration::from_secs(15));
+    for (k, v) in &recipe.headers {
+        req = req.header(k, render(v, &vars));
+    }
+    if let Some(json) = &recipe.json {
+        req = req.json(&render_json(json, &vars));
+    } else if let Some(form) = &recipe.form {
+        let form: HashMap<&str, String> = form.iter().map(|(k, v)| (k.as_str(), render(v, &vars))).collect();
+        req = req.form(&form);
+    } else if let Some(body) = &recipe.body {
+        req = req.body(render(body, &vars));
+    }
+
+    let resp = req.send_paced().await.context("login request failed")?;
+    let status = resp.status().as_u16();
+    let headers = resp.headers().clone();
+    let body = resp.text().await.unwrap_or_default();
+    if let Some(cond) = &recipe.expect {
+        if !cond.matches(status, &body) {
+            bail!("login response did not match the expectation (status {})", status);
+        }
+    }
+
+    let mut auth = AuthConfig::default();
+    if let Some(source) = &recipe.extract.token {
+        let token = capture(source, status, &headers, &body)
+            .with_context(|| format!("no token at {} in the login response (status {})", source, status))?;
+        match &recipe.set {
+            Some(template) => {
+                let mut vars = vars.clone();
+                vars.insert("token".to_string(), token);
+                auth.header = Some(render(template, &vars));
+            }
+            None => auth.token = Some(token),
+        }
+    }
+    if !recipe.extract.cookies.is_empty() {
+        let cookies = set_cookies(&headers, &recipe.extract.cookies);
+        if cookies.is_empty() {
+            bail!("login response set none of the cookies {} (status {})", recipe.extract.cookies.join(", "), status);
+        }
+        auth.cookie = Some(cookies.join("; "));
+    }
+    Ok(auth)
+}
+
+/// `name=value` pairs from `Set-Cookie` whose name is in `names` (or all with `*`)
+fn set_cookies(headers: &reqwest::header::HeaderMap, names: &[String]) -> Vec<String> {
+    headers.get_all(reqwest::header::SET_COOKIE).iter()
+        .filter_map(|v| v.to_str().ok())
+        .filter_map(|v| v.split(';').next())
+        .filter_map(|pair| pair.split_once('=').map(|(n, v)| (n.trim(), v.trim())))
+        .filter(|(n, _)| names.iter().any(|w| w == "*" || w == n))
+        .map(|(n, v)| format!("{}={}", n, v))
+        .collect()
+}
+
+fn render_json(v: &Value, vars: &HashMap<String, String>) -> Value {
+    match v {
+        Value::String(s) => Value::String(render(s, vars)),
+        Value::Array(arr) => Value::Array(arr.iter().map(|i| render_json(i, vars)).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(k, i)| (k.clone(), render_json(i, vars))).collect()),
+        other => other.clone(),
+    }
+}
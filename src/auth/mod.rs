@@ -1,3 +1,5 @@
+pub mod login_flow;
+pub mod session;
 pub mod tester;
 
 pub use tester::AuthTester;
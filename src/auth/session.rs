@@ -0,0 +1,151 @@
+//! Authenticated scanning
+//! Credentials from the `auth` config section (or the `--auth-*` flags) become default headers on every
+//! client that talks to the target, through the same path as the identification headers. While probing,
+//! a `SessionMonitor` watches for a sudden wave of 401s after responses were mostly authorized: the
+//! session most likely expired and everything after it was scanned anonymously.
+
+use anyhow::{bail, Result};
+use base64::Engine;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE};
+use serde::Deserialize;
+use std::collections::VecDeque;
+
+/// Responses per window looked at by the monitor
+pub const WINDOW: usize = 40;
+
+/// Share of 401s (in %) in a window that counts as a wave
+const WAVE_PERCENT: usize = 75;
+
+/// Share of 401s (in %) below which a window counts as authorized
+const HEALTHY_PERCENT: usize = 25;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Bearer token, sent as `Authorization: Bearer <token>`
+    pub token: Option<String>,
+    /// Cookie string (`name=value; other=value`)
+    pub cookie: Option<String>,
+    /// API key or other credential header (`Name: value`)
+    pub header: Option<String>,
+    /// Basic credentials (`user:password`)
+    pub basic: Option<String>,
+    /// Login recipe (YAML, see `auth::login_flow`) run before the scan; what it acquires replaces the
+    /// fields above
+    pub login: Option<String>,
+}
+
+impl AuthConfig {
+    pub fn is_empty(&self) -> bool {
+        self.methods().is_empty()
+    }
+
+    /// Configured credential kinds, for the console
+    pub fn methods(&self) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        if self.token.is_some() { out.push("bearer token"); }
+        if self.basic.is_some() { out.push("basic auth"); }
+        if self.cookie.is_some() { out.push("cookie"); }
+        if self.header.is_some() { out.push("header"); }
+        out
+    }
+
+    /// Values set in `other` replace ours (flags over config file)
+    pub fn merge(mut self, other: AuthConfig) -> Self {
+        self.token = other.token.or(self.token);
+        self.cookie = other.cookie.or(self.cookie);
+        self.header = other.header.or(self.header);
+        self.basic = other.basic.or(self.basic);
+        self.login = other.login.or(self.login);
+        self
+    }
+
+    /// Request headers carrying the credentials, marked sensitive. A bearer token takes precedence over
+    /// basic credentials, both use `Authorization`.
+    pub fn headers(&self) -> Result<HeaderMap> {
+        let mut map = HeaderMap::new();
+        let value = |v: String| -> Result<HeaderValue> {
+            let mut v = HeaderValue::from_str(&v).map_err(|_| anyhow::anyhow!("invalid characters in auth value"))?;
+            v.set_sensitive(true);
+            Ok(v)
+        };
+        if let Some(basic) = &self.basic {
+            if !basic.contains(':') {
+                bail!("invalid basic credentials, expected user:password");
+            }
+            map.insert(AUTHORIZATION, value(format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(basic)))?);
+        }
+        if let Some(token) = &self.token {
+            let token = token.trim();
+            let token = token.strip_prefix("Bearer ").unwrap_or(token);
+            map.insert(AUTHORIZATION, value(format!("Bearer {}", token))?);
+        }
+        if let Some(cookie) = &self.cookie {
+            map.insert(COOKIE, value(cookie.trim().to_string())?);
+        }
+        if let Some(header) = &self.header {
+            let Some((name, v)) = header.split_once(':') else {
+                bail!("invalid auth header '{}', expected Name:value", header.split(':').next().unwrap_or_default());
+            };
+            let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| anyhow::anyhow!("invalid auth header name"))?;
+            map.insert(name, value(v.trim().to_string())?);
+        }
+        Ok(map)
+    }
+}
+
+/// Install the credentials on every target client built from now on
+pub fn configure(auth: &AuthConfig) -> Result<()> {
+    crate::identify::configure_auth(auth.headers()?);
+    Ok(())
+}
+
+/// Detects session expiry from the probe's response statuses
+#[derive(Default)]
+pub struct SessionMonitor {
+    state: Mutex<MonitorState>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    window: VecDeque<u16>,
+    seen: usize,
+    /// A full window was mostly authorized
+    healthy: bool,
+    /// Responses before the run of 401s that completed the wave
+    expired_at: Option<usize>,
+}
+
+impl SessionMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a response status. Returns true once, for the response that completes the 401 wave.
+    pub fn record(&self, status: u16) -> bool {
+        let mut s = self.state.lock();
+        s.seen += 1;
+        s.window.push_back(status);
+        if s.window.len() > WINDOW {
+            s.window.pop_front();
+        }
+        if s.window.len() < WINDOW || s.expired_at.is_some() {
+            return false;
+        }
+        let unauthorized = s.window.iter().filter(|st| **st == 401).count();
+        if unauthorized * 100 <= WINDOW * HEALTHY_PERCENT {
+            s.healthy = true;
+        } else if s.healthy && unauthorized * 100 >= WINDOW * WAVE_PERCENT {
+            let run = s.window.iter().rev().take_while(|st| **st == 401).count();
+            s.expired_at = Some(s.seen - run);
+            return true;
+        }
+        false
+    }
+
+    /// Responses recorded before the wave, if one was detected
+    pub fn expired_at(&self) -> Option<usize> {
+        self.state.lock().expired_at
+    }
+}
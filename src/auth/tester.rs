@@ -1,24 +1,26 @@
+use crate::analyze::cookies::{self, SetCookie};
 use crate::http_client::HttpClient;
+use serde::Serialize;
 use anyhow::Result;
 use std::collections::HashMap;
 use tokio::time::{timeout, Duration};
 use base64::Engine;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AuthResult {
     pub url: String,
     pub auth_methods: Vec<AuthMethod>,
     pub vulnerabilities: Vec<AuthVulnerability>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AuthMethod {
     pub method_type: String,
     pub detected_in: String,
     pub details: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AuthVulnerability {
     pub vuln_type: String,
     pub severity: String,
@@ -439,37 +441,17 @@ impl AuthTester {
                     }
                 }
 
-                // Check for missing security flags
-                if let Some(set_cookie) = response.headers().get("set-cookie") {
-                    if let Ok(cookie_value) = set_cookie.to_str() {
-                        let cookie_lower = cookie_value.to_lowercase();
-                        
-                        if !cookie_lower.contains("httponly") {
-                            vulns.push(AuthVulnerability {
-                                vuln_type: "Missing HTTPOnly Flag".to_string(),
-                                severity: "MEDIUM".to_string(),
-                                description: "Session cookies missing HTTPOnly flag - XSS can steal sessions".to_string(),
-                                evidence: Some("HTTPOnly flag not set".to_string()),
-                            });
-                        }
-
-                        if !cookie_lower.contains("secure") && url.starts_with("https://") {
-                            vulns.push(AuthVulnerability {
-                                vuln_type: "Missing Secure Flag".to_string(),
-                                severity: "MEDIUM".to_string(),
-                                description: "HTTPS session cookies missing Secure flag".to_string(),
-                                evidence: Some("Secure flag not set".to_string()),
-                            });
-                        }
-
-                        if !cookie_lower.contains("samesite") {
-                            vulns.push(AuthVulnerability {
-                                vuln_type: "Missing SameSite Flag".to_string(),
-                                severity: "MEDIUM".to_string(),
-                                description: "Session cookies missing SameSite flag - CSRF possible".to_string(),
-                                evidence: Some("SameSite flag not set".to_string()),
-                            });
-                        }
+                // Flag, prefix and scope checks live in analyze::cookies
+                let now = std::time::SystemTime::now();
+                for value in response.headers().get_all("set-cookie").iter().filter_map(|v| v.to_str().ok()) {
+                    let Some(cookie) = SetCookie::parse(value) else { continue };
+                    for issue in cookies::check_cookie(&cookie, url, now).into_iter().filter(|i| i.severity != "INFO") {
+                        vulns.push(AuthVulnerability {
+                            vuln_type: issue.check.label().to_string(),
+                            severity: issue.severity,
+                            description: issue.detail,
+                            evidence: Some(format!("Set-Cookie: {}=...", cookie.name)),
+                        });
                     }
                 }
             }
@@ -35,9 +35,63 @@ pub struct Cli {
     /// Enable verbose output
     #[arg(long, global = true)]
     pub verbose: bool,
+
+    /// Config file (JSON) with API keys [default: ~/.config/apihunter/config.json]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// Resolve and connect over IPv4 only
+    #[arg(short = '4', long = "ipv4", global = true, conflicts_with = "ipv6")]
+    pub ipv4: bool,
+
+    /// Resolve and connect over IPv6 only
+    #[arg(short = '6', long = "ipv6", global = true)]
+    pub ipv6: bool,
+
+    /// Resolve all names over DNS-over-HTTPS instead of the local resolver: cloudflare, google or a https://.../dns-query URL
+    #[arg(long, global = true, value_name = "SERVER")]
+    pub doh: Option<String>,
+
+    /// Send browser-like TLS handshakes and headers to the target: chrome120, firefox121, safari17
+    /// (certificates are not verified)
+    #[arg(long, global = true, value_name = "NAME", value_parser = ["chrome120", "firefox121", "safari17"])]
+    pub tls_profile: Option<String>,
+
+    /// HTTP version towards the target: auto (negotiated via ALPN), 1 (HTTP/1.1 only), 2 (HTTP/2 only)
+    #[arg(long, global = true, value_name = "VERSION", default_value = "auto", value_parser = ["auto", "1", "2"])]
+    pub http_version: String,
+
+    /// Identification header sent with every request to the target, e.g. "X-Bug-Bounty:username" (repeatable)
+    #[arg(long, global = true, value_name = "HEADER:VALUE")]
+    pub identify: Vec<String>,
+
+    /// Custom header sent with every request to the target, e.g. -H "Cookie: session=..." (repeatable)
+    #[arg(short = 'H', long = "header", global = true, value_name = "NAME:VALUE")]
+    pub headers: Vec<String>,
+
+    /// Bearer token sent as "Authorization: Bearer <TOKEN>" to the target
+    #[arg(long, global = true, value_name = "TOKEN")]
+    pub auth_token: Option<String>,
+
+    /// Session cookies sent to the target, e.g. "session=abc; csrf=def"
+    #[arg(long, global = true, value_name = "COOKIES")]
+    pub auth_cookie: Option<String>,
+
+    /// API key or other credential header, e.g. "X-Api-Key: abc"
+    #[arg(long, global = true, value_name = "NAME:VALUE")]
+    pub auth_header: Option<String>,
+
+    /// Basic auth credentials
+    #[arg(long, global = true, value_name = "USER:PASSWORD")]
+    pub auth_basic: Option<String>,
+
+    /// Log in with a YAML recipe before scanning and use the acquired token/cookies
+    #[arg(long, global = true, value_name = "FILE")]
+    pub login: Option<String>,
 }
 
 #[derive(clap::Subcommand, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     #[command(
         about = "Run a scan against a domain or file with domains",
@@ -56,6 +110,23 @@ pub enum Commands {
         #[arg(long, value_name = "FILE")]
         report: Option<String>,
 
+        /// Export the asset graph (domains, hosts, endpoints, parameters, findings) as DOT and GraphML;
+        /// the previous run's asset_graph.graphml is extended
+        #[arg(long)]
+        graph: bool,
+
+        /// Delete the previous run's results instead of archiving them under <out>/runs/
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Keep only the newest N archived runs (older ones are deleted)
+        #[arg(long, value_name = "N")]
+        keep_runs: Option<usize>,
+
+        /// Delete archived runs older than this many days
+        #[arg(long, value_name = "DAYS")]
+        keep_days: Option<u64>,
+
         // === SCAN MODES ===
         /// Conservative low-impact mode (fast, passive)
         #[arg(long)]
@@ -69,12 +140,49 @@ pub enum Commands {
         #[arg(short = 'A', long)]
         aggressive: bool,
 
+        /// Extra paths for the --aggressive path brute force (one per line, on top of the built-in list)
+        #[arg(long, value_name = "FILE", requires = "aggressive")]
+        path_wordlist: Option<String>,
+
+        /// Extensions the path brute force also tries on every word, comma-separated (e.g. json,bak,old)
+        #[arg(long, value_name = "EXT", value_delimiter = ',', requires = "aggressive")]
+        path_extensions: Vec<String>,
+
+        /// Directory levels the path brute force recurses into [default: 2]
+        #[arg(long, value_name = "N", default_value_t = 2, requires = "aggressive")]
+        path_depth: usize,
+
+        /// Extra names for the --aggressive parameter brute force (one per line, on top of the built-in list)
+        #[arg(long, value_name = "FILE", requires = "aggressive")]
+        param_wordlist: Option<String>,
+
+        /// Callback domain for blind SSRF/XXE/injection tests (--aggressive); needs a wildcard A record
+        /// pointing at this machine, or an NS record with --oob-dns
+        #[arg(long, value_name = "DOMAIN", requires = "aggressive")]
+        oob_domain: Option<String>,
+
+        /// Address of the callback HTTP listener [default: 0.0.0.0:80]
+        #[arg(long, value_name = "ADDR", default_value = "0.0.0.0:80", requires = "oob_domain")]
+        oob_listen: std::net::SocketAddr,
+
+        /// Also answer DNS for the callback domain on this UDP address (e.g. 0.0.0.0:53)
+        #[arg(long, value_name = "ADDR", requires = "oob_domain")]
+        oob_dns: Option<std::net::SocketAddr>,
+
+        /// IPv4 address the callback DNS server answers with (this machine's public IP)
+        #[arg(long, value_name = "IP", requires = "oob_dns")]
+        oob_ip: Option<std::net::Ipv4Addr>,
+
+        /// Seconds to wait for late callbacks after the payloads were sent [default: 15]
+        #[arg(long, value_name = "SECS", default_value_t = 15, requires = "oob_domain")]
+        oob_wait: u64,
+
         // === SECURITY SCANNING ===
         /// Scan for vulnerabilities (SQLi, XSS, RCE, SSRF, etc.)
         #[arg(long = "sV")]
         scan_vulns: bool,
 
-        /// Scan for admin/debug endpoints
+        /// Scan for admin/debug endpoints, exposed .git/.svn/.env/backup files and metrics/actuator/pprof endpoints
         #[arg(long = "sA")]
         scan_admin: bool,
 
@@ -82,10 +190,70 @@ pub enum Commands {
         #[arg(long)]
         jwt: bool,
 
+        /// Credentials of test user A for the BOLA/IDOR test, e.g. "Authorization: Bearer AAA" (repeatable)
+        #[arg(long, value_name = "NAME:VALUE", requires = "auth_b")]
+        auth_a: Vec<String>,
+
+        /// Credentials of test user B; objects readable by A are requested as B (repeatable)
+        #[arg(long, value_name = "NAME:VALUE", requires = "auth_a")]
+        auth_b: Vec<String>,
+
+        /// Don't send cookies set by the target back on later requests
+        #[arg(long)]
+        no_cookie_jar: bool,
+
+        /// Keep the target's cookies across scans (<out>/.cookies/<target>.json)
+        #[arg(long, conflicts_with = "no_cookie_jar")]
+        persist_cookies: bool,
+
         /// Deep JavaScript analysis: Extract endpoints, tokens, secrets
         #[arg(long)]
         deep_js: bool,
 
+        /// Minimum Shannon entropy (bits/char) for generic secrets in JS; 0 disables the entropy scanner [default: 4.5]
+        #[arg(long, value_name = "BITS")]
+        entropy_threshold: Option<f64>,
+
+        /// Extra secret regexes for the JS analysis, from a TOML or YAML file
+        #[arg(long, value_name = "FILE")]
+        secret_patterns: Option<String>,
+
+        /// Download limit per JS file in MB; bundles are scanned in overlapping windows up to this size
+        #[arg(long, value_name = "MB", default_value = "20")]
+        js_max_size: usize,
+
+        /// Check discovered AWS/Stripe/Slack keys with one read-only provider call each (sends the keys
+        /// to the provider; only with permission)
+        #[arg(long)]
+        verify_secrets: bool,
+
+        /// Skip the host reachability pre-flight (TCP/TLS/HTTP check per host before probing)
+        #[arg(long)]
+        no_preflight: bool,
+
+        /// Cheap HEAD/ranged-GET pass that drops 404s and redirects to the home page before the full
+        /// probe (for large candidate sets)
+        #[arg(long)]
+        prefilter: bool,
+
+        /// Don't fingerprint each host's not-found page (random path) to flag soft-404 responses
+        #[arg(long)]
+        no_soft404: bool,
+
+        /// Probe with HEAD only (GET when HEAD isn't enough) instead of GET + HEAD + OPTIONS (+ an empty
+        /// JSON POST with --aggressive)
+        #[arg(long)]
+        single_method: bool,
+
+        /// Keep the first <max-kb> KB of every response body in the probe results, so tokens and secrets
+        /// are also found in non-JSON responses
+        #[arg(long, value_name = "MAX_KB")]
+        store_bodies: Option<usize>,
+
+        /// Store bodies gzip-compressed and base64-encoded (also keeps binary bodies)
+        #[arg(long, requires = "store_bodies")]
+        compress_bodies: bool,
+
         // === DISCOVERY OPTIONS ===
         /// Enable subdomain enumeration (crt.sh + DNS bruteforce)
         #[arg(long)]
@@ -103,6 +271,95 @@ pub enum Commands {
         #[arg(long)]
         browser_depth: Option<usize>,
 
+        /// Crawl same-domain HTML pages for links, form actions and inline fetch targets (enabled by --deep)
+        #[arg(long)]
+        crawl: bool,
+
+        /// Crawler link depth from the start page [default: 2]
+        #[arg(long, value_name = "N")]
+        crawl_depth: Option<usize>,
+
+        /// Maximum pages to crawl [default: 50]
+        #[arg(long, value_name = "N")]
+        crawl_pages: Option<usize>,
+
+        /// Delay between crawled pages in ms [default: 250]
+        #[arg(long, value_name = "MS")]
+        crawl_delay: Option<u64>,
+
+        /// Import requests from a HAR capture (probed first, bypassing the API filter)
+        #[arg(long, value_name = "FILE")]
+        import_har: Option<String>,
+
+        /// Import proxy history from a Burp Suite "Save items" XML export (probed first, bypassing the API filter)
+        #[arg(long, value_name = "FILE")]
+        import_burp: Option<String>,
+
+        /// Compare the scan with an OpenAPI/Swagger document (JSON or YAML): its paths are probed, undocumented
+        /// live endpoints are reported as shadow and documented dead ones as zombie endpoints
+        #[arg(long, value_name = "FILE")]
+        spec: Option<String>,
+
+        /// Android APK or iOS IPA to mine for API URLs, keys and certificate pins (seeds the scan)
+        #[arg(long, value_name = "FILE")]
+        apk: Option<String>,
+
+        /// Search public GitHub code for the domain (needs a GitHub token)
+        #[arg(long)]
+        github: bool,
+
+        /// Query the CommonCrawl index for archived URLs (enabled by --deep)
+        #[arg(long)]
+        commoncrawl: bool,
+
+        /// Query AlienVault OTX for observed URLs (API key optional)
+        #[arg(long)]
+        otx: bool,
+
+        /// Query urlscan.io for scanned page and request URLs (API key optional)
+        #[arg(long)]
+        urlscan: bool,
+
+        /// Only use Wayback captures from this timestamp on (yyyyMMdd[hhmmss])
+        #[arg(long, value_name = "TIMESTAMP")]
+        wayback_from: Option<String>,
+
+        /// Only use Wayback captures up to this timestamp (yyyyMMdd[hhmmss])
+        #[arg(long, value_name = "TIMESTAMP")]
+        wayback_to: Option<String>,
+
+        /// Wayback MIME type filter, regex; prefix with ! to exclude (repeatable)
+        #[arg(long, value_name = "MIME")]
+        wayback_mime: Vec<String>,
+
+        /// Wayback status code filter, regex; prefix with ! to exclude (repeatable)
+        #[arg(long, value_name = "CODE")]
+        wayback_status: Vec<String>,
+
+        /// Reuse cached passive source responses (Wayback, crt.sh, CommonCrawl, OTX, urlscan, GitHub) younger than this [default: 24]
+        #[arg(long, value_name = "HOURS")]
+        cache_ttl: Option<u64>,
+
+        /// Reuse cached HTTP responses from the target (probe and analysis GET/HEAD/OPTIONS) younger than this [default: 60]
+        #[arg(long, value_name = "MINUTES")]
+        response_ttl: Option<u64>,
+
+        /// Always re-fetch passive sources and target responses instead of using the on-disk caches
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Additional third-party domain to exclude from active scanning (repeatable)
+        #[arg(long = "exclude-host", value_name = "DOMAIN")]
+        exclude_host: Vec<String>,
+
+        /// Scan a domain even if it is on the built-in third-party list (repeatable)
+        #[arg(long = "include-host", value_name = "DOMAIN")]
+        include_host: Vec<String>,
+
+        /// Disable the built-in third-party/CDN exclusion list
+        #[arg(long)]
+        no_default_excludes: bool,
+
         // === STEALTH & EVASION ===
         /// Anonymous mode: Residential proxies + human-like patterns
         #[arg(long)]
@@ -112,10 +369,54 @@ pub enum Commands {
         #[arg(long)]
         full_speed: bool,
 
+        /// Route all traffic through Tor (socks5h://127.0.0.1:9050), one circuit per host; with --anon instead of a residential proxy
+        #[arg(long)]
+        tor: bool,
+
+        /// Proxies to spread traffic over, one per line (http://, socks5://, socks5h://; user:pass@ allowed);
+        /// health-checked first, dead or blocked ones are dropped during the scan
+        #[arg(long, value_name = "FILE", conflicts_with = "tor")]
+        proxy_list: Option<String>,
+
+        /// Pick a new proxy from the list per request, or keep one per host [default: host]
+        #[arg(long, value_name = "request|host", requires = "proxy_list", value_parser = ["request", "host"])]
+        proxy_rotation: Option<String>,
+
         /// Enable WAF bypass techniques
         #[arg(long)]
         bypass_waf: bool,
 
+        /// Look for origin IPs behind Cloudflare/Akamai (historical DNS, subdomain leaks, cert search; report only)
+        #[arg(long)]
+        find_origin: bool,
+
+        /// Virtual host discovery: request the target's IPs with Host headers from the subdomain list and a wordlist
+        #[arg(long)]
+        vhosts: bool,
+
+        /// Extra vhost names (one word per line, tried as <word>.<domain>)
+        #[arg(long, value_name = "FILE", requires = "vhosts")]
+        vhost_wordlist: Option<String>,
+
+        /// Request endpoints of hosts advertising HTTP/3 (Alt-Svc) again over QUIC and report differences (needs curl with HTTP/3)
+        #[arg(long)]
+        http3: bool,
+
+        /// Probe for HTTP request smuggling (CL.TE / TE.CL) with raw ambiguous requests; a confirmed
+        /// desync poisons one follow-up request with a 404, which may hit another user
+        #[arg(long)]
+        smuggling: bool,
+
+        /// Test cached endpoints for web cache poisoning (unkeyed headers) and cache deception; poisoned
+        /// entries sit under fresh cache busters, but the requests still go through the target's cache
+        #[arg(long)]
+        cache_poisoning: bool,
+
+        /// Upload test files to discovered upload endpoints (--aggressive): dangerous extensions, spoofed
+        /// content types, SVG/XML payloads, traversal in file names. Uploaded files stay on the server
+        #[arg(long, requires = "aggressive")]
+        upload_tests: bool,
+
         // === TIMING & PERFORMANCE ===
         /// Timing template: T0 (paranoid) to T5 (insane) [default: T3]
         #[arg(short = 'T', long, value_parser = clap::value_parser!(u8).range(0..=5))]
@@ -129,6 +430,10 @@ pub enum Commands {
         #[arg(long)]
         per_host: Option<u16>,
 
+        /// Hard cap on requests per second to the target, across all phases and regardless of concurrency
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        rps: Option<u32>,
+
         /// Request timeout in seconds [default: 10]
         #[arg(long)]
         timeout: Option<u64>,
@@ -159,6 +464,127 @@ pub enum Commands {
         #[arg(short = 'n', long)]
         rate_limit: Option<u32>,
     },
+
+    #[command(
+        about = "Run multi-step logic flows defined in YAML",
+        long_about = "Execute request sequences with variable capture between steps (create resource -> access as other identity -> delete) to test business-logic issues like payment tampering or workflow bypass."
+    )]
+    Flow {
+        /// Flow definition files (YAML)
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Write results to this JSON file
+        #[arg(short = 'o', long)]
+        out: Option<String>,
+    },
+
+    #[command(
+        about = "Send one state-changing request many times at once to find race conditions",
+        long_about = "Fire N copies of a request (e.g. coupon redemption, balance transfer) over separate connections with last-byte synchronization, so they arrive within the same few milliseconds, and report when more of them succeed than the endpoint should allow."
+    )]
+    Race {
+        /// Endpoint URL
+        url: String,
+
+        /// HTTP method
+        #[arg(short = 'X', long, default_value = "POST")]
+        method: String,
+
+        /// Request body
+        #[arg(short = 'd', long)]
+        data: Option<String>,
+
+        /// Number of parallel requests
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+
+        /// Status codes that count as success, comma-separated [default: any 2xx]
+        #[arg(long, value_delimiter = ',')]
+        success_status: Vec<u16>,
+
+        /// Text in the response body that marks success
+        #[arg(long, value_name = "TEXT")]
+        success_match: Option<String>,
+
+        /// Successes the endpoint is meant to allow (more are reported)
+        #[arg(long, default_value_t = 1)]
+        expected: usize,
+
+        /// Seconds to wait for each response
+        #[arg(long, default_value_t = 15)]
+        timeout: u64,
+
+        /// Write the result to this JSON file
+        #[arg(short = 'o', long)]
+        out: Option<String>,
+
+        /// Save a race condition as a finding in a report (JSON or TXT format)
+        #[arg(long, value_name = "FILE")]
+        report: Option<String>,
+    },
+
+    #[command(
+        about = "Write JSON Schemas for the output files",
+        long_about = "Generate versioned JSON Schema files for target_raw.jsonl events, findings, the --report file and js_critical_info.json, for tooling built on API Hunter output."
+    )]
+    Schema {
+        /// Directory for the schema files
+        #[arg(short = 'o', long, default_value = "schemas")]
+        out: String,
+    },
+
+    #[command(
+        about = "Offline JWT attacks",
+        long_about = "Work on a captured JWT without contacting the target. `jwt crack` tries every line of a wordlist as the HMAC secret of an HS256/HS384/HS512 token, in parallel."
+    )]
+    Jwt {
+        #[command(subcommand)]
+        command: JwtCommand,
+    },
+
+    #[command(
+        about = "Manage the WAF signature database",
+        long_about = "WAF detection signatures (headers, cookie names, block page text) are read from ~/.config/apihunter/waf_signatures.json when it exists, otherwise the set built into this binary is used. Edit that file to add WAFs or patterns without recompiling."
+    )]
+    Signatures {
+        #[command(subcommand)]
+        command: SignaturesCommand,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum JwtCommand {
+    /// Brute force the HMAC secret of an HS256/HS384/HS512 token offline
+    Crack {
+        /// The token (header.payload.signature)
+        token: String,
+
+        /// Candidate secrets, one per line (e.g. rockyou.txt)
+        #[arg(short = 'w', long, value_name = "FILE")]
+        wordlist: String,
+
+        /// Worker threads [default: all cores]
+        #[arg(short = 't', long, default_value_t = 0)]
+        threads: usize,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum SignaturesCommand {
+    /// Fetch the latest signature set into the config directory
+    Update {
+        /// Source of the signature file
+        #[arg(long, default_value = api_hunter::waf::signatures::UPDATE_URL)]
+        url: String,
+
+        /// Write here instead of ~/.config/apihunter/waf_signatures.json
+        #[arg(short = 'o', long)]
+        out: Option<String>,
+    },
+
+    /// Show where the signatures are loaded from and what they cover
+    Show,
 }
 
 pub fn parse_cli() -> Cli {
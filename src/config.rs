@@ -1,14 +1,85 @@
+use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::path::{Path, PathBuf};
 
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub concurrency: u16,
     pub per_host: u16,
+    /// Keys for passive sources and lookup APIs
+    pub api_keys: ApiKeys,
+    /// Identification headers required by the program (`Name:value`), merged with `--identify`
+    pub identify: Vec<String>,
+    /// Custom headers for every request to the target (`Name:value`), merged with `-H`
+    pub headers: Vec<String>,
+    /// Credentials for scanning behind a login; the `--auth-*` flags override single fields
+    pub auth: crate::auth::session::AuthConfig,
+}
+
+/// API keys for optional data sources. Missing keys fall back to environment variables.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ApiKeys {
+    pub otx: Option<String>,
+    pub urlscan: Option<String>,
+    pub securitytrails: Option<String>,
+    pub shodan: Option<String>,
+    pub censys_id: Option<String>,
+    pub censys_secret: Option<String>,
+    pub github: Option<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self { concurrency: 50, per_host: 6 }
+        Self { concurrency: 50, per_host: 6, api_keys: ApiKeys::default(), identify: Vec::new(), headers: Vec::new(), auth: Default::default() }
+    }
+}
+
+impl Config {
+    /// Load a JSON config file. Without an explicit path, `~/.config/apihunter/config.json`
+    /// is used if it exists; otherwise defaults apply.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(p) => Some(p.to_path_buf()),
+            None => default_path().filter(|p| p.exists()),
+        };
+
+        let mut config = match path {
+            Some(p) => {
+                let data = std::fs::read_to_string(&p)
+                    .with_context(|| format!("failed to read config {}", p.display()))?;
+                serde_json::from_str(&data)
+                    .with_context(|| format!("invalid config {}", p.display()))?
+            }
+            None => Config::default(),
+        };
+
+        config.api_keys.fill_from_env();
+        Ok(config)
+    }
+}
+
+impl ApiKeys {
+    /// Fill unset keys from OTX_API_KEY, URLSCAN_API_KEY, SECURITYTRAILS_API_KEY,
+    /// SHODAN_API_KEY, CENSYS_API_ID, CENSYS_API_SECRET and GITHUB_TOKEN
+    pub fn fill_from_env(&mut self) {
+        let fill = |slot: &mut Option<String>, var: &str| {
+            if slot.as_deref().is_none_or(str::is_empty) {
+                *slot = std::env::var(var).ok().filter(|v| !v.is_empty());
+            }
+        };
+        fill(&mut self.otx, "OTX_API_KEY");
+        fill(&mut self.urlscan, "URLSCAN_API_KEY");
+        fill(&mut self.securitytrails, "SECURITYTRAILS_API_KEY");
+        fill(&mut self.shodan, "SHODAN_API_KEY");
+        fill(&mut self.censys_id, "CENSYS_API_ID");
+        fill(&mut self.censys_secret, "CENSYS_API_SECRET");
+        fill(&mut self.github, "GITHUB_TOKEN");
     }
 }
+
+fn default_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config/apihunter/config.json"))
+}
@@ -0,0 +1,119 @@
+//! Shared cookie jar
+//! All clients built through `http_client::builder` share one cookie store, so cookies the target sets
+//! (sessions, load balancer affinity, WAF clearance) are sent back like a browser would instead of
+//! every request looking like a fresh client. An explicit `Cookie` header (`-H`, `--auth-cookie`)
+//! takes precedence over the jar. With `--persist-cookies` the jar is saved per target and loaded
+//! again on the next scan.
+
+use crate::output::paths;
+use anyhow::{Context, Result};
+use cookie_store::{CookieStore, RawCookie};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use reqwest::header::HeaderValue;
+use reqwest::ClientBuilder;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use url::Url;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+static SHARED: Lazy<Arc<Jar>> = Lazy::new(|| Arc::new(Jar::new()));
+
+/// Thread-safe cookie store usable as a reqwest cookie provider
+#[derive(Default)]
+pub struct Jar {
+    store: RwLock<CookieStore>,
+}
+
+impl Jar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Unexpired cookies
+    pub fn len(&self) -> usize {
+        self.store.read().iter_unexpired().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `name=value` pairs that would be sent to `url`
+    pub fn cookies_for(&self, url: &Url) -> Vec<String> {
+        self.store.read().get_request_values(url).map(|(n, v)| format!("{}={}", n, v)).collect()
+    }
+
+    /// Replace the jar with cookies saved by `save`; returns the number of cookies loaded
+    pub fn load(&self, path: &Path) -> Result<usize> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open cookie jar {}", path.display()))?;
+        let store = CookieStore::load_json_all(BufReader::new(file))
+            .map_err(|e| anyhow::anyhow!("invalid cookie jar {}: {}", path.display(), e))?;
+        let mut current = self.store.write();
+        *current = store;
+        Ok(current.iter_unexpired().count())
+    }
+
+    /// Write the jar as JSON, session cookies included (a scan is one browser session)
+    pub fn save(&self, path: &Path) -> Result<usize> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::File::create(path)
+            .with_context(|| format!("failed to write cookie jar {}", path.display()))?;
+        let store = self.store.read();
+        store.save_incl_expired_and_nonpersistent_json(&mut file)
+            .map_err(|e| anyhow::anyhow!("failed to write cookie jar {}: {}", path.display(), e))?;
+        Ok(store.iter_unexpired().count())
+    }
+}
+
+impl reqwest::cookie::CookieStore for Jar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies: Vec<RawCookie<'static>> = cookie_headers
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| RawCookie::parse(v.to_string()).ok())
+            .collect();
+        if !cookies.is_empty() {
+            self.store.write().store_response_cookies(cookies.into_iter(), url);
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self.cookies_for(url).join("; ");
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+/// Enable or disable the shared jar for clients built afterwards (`--no-cookie-jar`, anonymous scans)
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The jar shared by all target clients
+pub fn shared() -> Arc<Jar> {
+    SHARED.clone()
+}
+
+/// Install the shared jar on a client
+pub fn apply(builder: ClientBuilder) -> ClientBuilder {
+    if !is_enabled() {
+        return builder;
+    }
+    builder.cookie_provider(shared())
+}
+
+/// Where `--persist-cookies` keeps the jar of `domain`
+pub fn path(workspace: &Path, domain: &str) -> std::path::PathBuf {
+    paths::artifact(&paths::artifact(workspace, ".cookies"), &format!("{}.json", domain))
+}
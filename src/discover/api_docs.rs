@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use reqwest::Client;
 use anyhow::Result;
 use serde_json::Value;
@@ -19,7 +20,7 @@ pub struct ApiDocsDiscovery {
 impl ApiDocsDiscovery {
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
+            client: crate::http_client::builder("discover::api_docs")
                 .timeout(Duration::from_secs(10))
                 .danger_accept_invalid_certs(true)
                 .build()
@@ -84,7 +85,7 @@ impl ApiDocsDiscovery {
         for (path, doc_type) in paths {
             let url = format!("{}{}", base_url.trim_end_matches('/'), path);
             
-            if let Ok(response) = self.client.get(&url).send().await {
+            if let Ok(response) = self.client.get(&url).send_paced().await {
                 if response.status().is_success() {
                     let content_type = response
                         .headers()
@@ -176,7 +177,7 @@ impl ApiDocsDiscovery {
 
     /// Extract endpoints from Swagger/OpenAPI spec
     pub async fn extract_swagger_endpoints(&self, url: &str) -> Result<Vec<String>> {
-        let response = self.client.get(url).send().await?;
+        let response = self.client.get(url).send_paced().await?;
         let json: Value = response.json().await?;
         
         let mut endpoints = Vec::new();
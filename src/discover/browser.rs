@@ -2,6 +2,7 @@
 
 use anyhow::{Result, Context};
 use chromiumoxide::browser::{Browser, BrowserConfig};
+use chromiumoxide::cdp::browser_protocol::network;
 use futures::StreamExt;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -64,6 +65,15 @@ impl BrowserDiscovery {
         };
         
         let page = self.browser.new_page("about:blank").await?;
+        if crate::identify::has_headers() {
+            let headers: serde_json::Map<String, serde_json::Value> = crate::identify::record("discover::browser")
+                .iter()
+                .filter_map(|(k, v)| Some((k.to_string(), serde_json::Value::String(v.to_str().ok()?.to_string()))))
+                .collect();
+            page.execute(network::SetExtraHttpHeadersParams::new(network::Headers::new(serde_json::Value::Object(headers))))
+                .await
+                .context("Failed to set request headers")?;
+        }
         let _ = page.goto(target_url).await;
         tokio::time::sleep(tokio::time::Duration::from_millis(self.wait_time_ms)).await;
 
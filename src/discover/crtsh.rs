@@ -1,8 +1,7 @@
 use anyhow::Result;
-use reqwest::Client;
 
 pub async fn crtsh_subdomains(domain: &str) -> Result<Vec<String>> {
-    let client = Client::new();
+    let client = crate::http_client::third_party().build()?;
     let q = format!("%25.{}", domain);
     let url = format!("https://crt.sh/?q={}&output=json", urlencoding::encode(&q));
     let resp = client.get(&url).send().await?;
@@ -1,5 +1,7 @@
 pub mod api_docs;
 pub mod crtsh;
+pub mod security_txt;
 pub mod browser;
 pub mod subdomain;
 pub mod versioning;
+pub mod vhost;
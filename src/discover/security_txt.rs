@@ -0,0 +1,206 @@
+//! security.txt and disclosure policy discovery (RFC 9116)
+//! Each scanned origin is asked for `/.well-known/security.txt`, then the legacy `/security.txt`. The
+//! fields say where to report what the scan found: `Contact`, `Policy`, `Encryption`, `Acknowledgments`,
+//! `Preferred-Languages`, `Canonical` and `Hiring`. Files are checked for what makes them unreliable: no
+//! `Contact`, no or an expired `Expires`, `Expires` more than a year ahead, a `Canonical` that doesn't
+//! list the fetched URL. PGP-signed files are read from inside the signature.
+
+use crate::http_client::Paced;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Locations, in the order they are tried
+pub const PATHS: &[&str] = &["/.well-known/security.txt", "/security.txt"];
+
+/// Bytes read of a security.txt
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// `Expires` further ahead than this (seconds) is against the RFC's advice
+const MAX_EXPIRY_AHEAD: i64 = 366 * 86_400;
+
+/// A parsed security.txt
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SecurityTxt {
+    pub url: String,
+    /// `mailto:`, `https:` or `tel:` URIs, in order of preference
+    pub contacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(default)]
+    pub expired: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub encryption: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acknowledgments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_languages: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub canonical: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hiring: Vec<String>,
+    /// PGP cleartext signature around the fields
+    #[serde(default)]
+    pub signed: bool,
+    /// Departures from RFC 9116 that make the file less trustworthy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<String>,
+}
+
+impl SecurityTxt {
+    /// Where to send findings: the first contact, with the policy when there is one
+    pub fn report_to(&self) -> Option<String> {
+        let contact = self.contacts.first()?;
+        Some(match self.policy.first() {
+            Some(policy) => format!("{} (policy: {})", contact, policy),
+            None => contact.clone(),
+        })
+    }
+}
+
+/// Seconds since the epoch of an RFC 3339 date-time (`2026-12-31T23:59:59Z`, `...+02:00`, fractions)
+pub fn parse_rfc3339(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (date, time) = s.split_once(['T', 't', ' '])?;
+    let mut d = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (d.next()??, d.next()??, d.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let (clock, offset) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else {
+        let pos = time.rfind(['+', '-'])?;
+        let (h, m) = time[pos + 1..].split_once(':')?;
+        let minutes = h.parse::<i64>().ok()? * 60 + m.parse::<i64>().ok()?;
+        (&time[..pos], if time.as_bytes()[pos] == b'-' { -minutes } else { minutes })
+    };
+    let clock = clock.split('.').next()?;
+    let mut t = clock.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute, second) = (t.next()??, t.next()??, t.next()??);
+
+    // Days since 1970-01-01 from the civil date (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset * 60)
+}
+
+/// The signed text of a PGP cleartext-signed message, or `None` if it isn't one
+fn unwrap_signed(text: &str) -> Option<String> {
+    let start = text.find("-----BEGIN PGP SIGNED MESSAGE-----")?;
+    let rest = &text[start..];
+    // Armor headers (`Hash: SHA512`) end at the first empty line
+    let body_start = rest.find("\n\n").or_else(|| rest.find("\r\n\r\n"))?;
+    let body = &rest[body_start..];
+    let end = body.find("-----BEGIN PGP SIGNATURE-----").unwrap_or(body.len());
+    // Dash-escaped lines (`- -----`)
+    Some(body[..end].lines().map(|l| l.strip_prefix("- ").unwrap_or(l)).collect::<Vec<_>>().join("\n"))
+}
+
+/// Parse a security.txt served at `url`; `None` if the text has no RFC 9116 field (HTML soft-404s, ...)
+pub fn parse(text: &str, url: &str, now: i64) -> Option<SecurityTxt> {
+    let signed_body = unwrap_signed(text);
+    let body = signed_body.as_deref().unwrap_or(text);
+    let mut txt = SecurityTxt { url: url.to_string(), signed: signed_body.is_some(), ..Default::default() };
+    let mut fields = 0;
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim().to_string();
+        if value.is_empty() || name.contains(char::is_whitespace) {
+            continue;
+        }
+        match name.to_ascii_lowercase().as_str() {
+            "contact" => txt.contacts.push(value),
+            "policy" => txt.policy.push(value),
+            "expires" => {
+                if txt.expires.is_some() {
+                    txt.issues.push("Expires appears more than once".to_string());
+                }
+                txt.expires = Some(value);
+            }
+            "encryption" => txt.encryption.push(value),
+            "acknowledgments" | "acknowledgements" => txt.acknowledgments.push(value),
+            "preferred-languages" => txt.preferred_languages = Some(value),
+            "canonical" => txt.canonical.push(value),
+            "hiring" => txt.hiring.push(value),
+            _ => continue,
+        }
+        fields += 1;
+    }
+    if fields == 0 {
+        return None;
+    }
+
+    if txt.contacts.is_empty() {
+        txt.issues.push("no Contact field: there is no way to report".to_string());
+    }
+    match txt.expires.as_deref().map(|e| (e, parse_rfc3339(e))) {
+        None => txt.issues.push("no Expires field".to_string()),
+        Some((e, None)) => txt.issues.push(format!("Expires is not an RFC 3339 date: {}", e)),
+        Some((e, Some(at))) if at <= now => {
+            txt.expired = true;
+            txt.issues.push(format!("expired on {}: the contacts may be stale", e));
+        }
+        Some((e, Some(at))) if at - now > MAX_EXPIRY_AHEAD => txt.issues.push(format!("Expires is more than a year ahead ({})", e)),
+        _ => {}
+    }
+    if !txt.canonical.is_empty() && !txt.canonical.iter().any(|c| c == url) {
+        txt.issues.push(format!("Canonical doesn't list {}", url));
+    }
+    if url.starts_with("http://") {
+        txt.issues.push("served over plain HTTP".to_string());
+    }
+    if let Some(c) = txt.contacts.iter().find(|c| !(c.starts_with("mailto:") || c.starts_with("https://") || c.starts_with("tel:"))) {
+        txt.issues.push(format!("Contact should be a mailto:, https: or tel: URI: {}", c));
+    }
+    Some(txt)
+}
+
+pub struct SecurityTxtFetcher {
+    client: Client,
+}
+
+impl SecurityTxtFetcher {
+    pub fn new(timeout: Duration) -> anyhow::Result<Self> {
+        let client = crate::http_client::builder_unauthenticated("discover::security_txt")
+            .timeout(timeout)
+            .danger_accept_invalid_certs(true)
+            .build()?;
+        Ok(Self { client })
+    }
+
+    /// The security.txt of `origin`, from the first location that serves one
+    pub async fn fetch(&self, origin: &str) -> Option<SecurityTxt> {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+        for path in PATHS {
+            let url = format!("{}{}", origin.trim_end_matches('/'), path);
+            let Ok(resp) = self.client.get(&url).send_paced().await else { continue };
+            let html = resp.headers().get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|ct| ct.contains("html"));
+            if !resp.status().is_success() || html {
+                continue;
+            }
+            // Redirects are allowed (RFC 9116 2.3); the final URL is what Canonical must list
+            let final_url = resp.url().to_string();
+            let Ok((bytes, _)) = crate::probe::http_probe::read_limited(resp, BODY_LIMIT).await else { continue };
+            let Some(mut txt) = parse(&String::from_utf8_lossy(&bytes), &final_url, now) else { continue };
+            if *path == "/security.txt" {
+                txt.issues.push("only at the legacy /security.txt location".to_string());
+            }
+            return Some(txt);
+        }
+        None
+    }
+}
@@ -1,3 +1,4 @@
+use crate::gather::cache::SourceCache;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -10,11 +11,12 @@ pub struct SubdomainResult {
 pub struct SubdomainEnumerator {
     client: reqwest::Client,
     common_prefixes: Vec<String>,
+    cache: SourceCache,
 }
 
 impl SubdomainEnumerator {
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
+        let client = crate::http_client::third_party()
             .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
             .timeout(std::time::Duration::from_secs(10))
             .build()
@@ -23,9 +25,16 @@ impl SubdomainEnumerator {
         Self {
             client,
             common_prefixes: Self::load_common_prefixes(),
+            cache: SourceCache::disabled(),
         }
     }
 
+    /// Reuse crt.sh responses from an on-disk cache
+    pub fn with_cache(mut self, cache: SourceCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
     /// Load common subdomain prefixes
     fn load_common_prefixes() -> Vec<String> {
         vec![
@@ -90,6 +99,10 @@ impl SubdomainEnumerator {
 
     /// Query crt.sh Certificate Transparency logs
     async fn query_crtsh(&self, domain: &str) -> Result<Vec<String>, String> {
+        if let Some(cached) = self.cache.get::<Vec<String>>("crtsh", domain) {
+            return Ok(cached);
+        }
+
         let url = format!("https://crt.sh/?q=%.{}&output=json", domain);
         
         tracing::debug!("Querying crt.sh for domain: {}", domain);
@@ -133,7 +146,9 @@ impl SubdomainEnumerator {
         }
 
         tracing::info!("crt.sh found {} subdomains", subdomains.len());
-        Ok(subdomains.into_iter().collect())
+        let subdomains: Vec<String> = subdomains.into_iter().collect();
+        self.cache.put("crtsh", domain, &subdomains);
+        Ok(subdomains)
     }
 
     /// DNS bruteforce with common prefixes
@@ -174,13 +189,7 @@ impl SubdomainEnumerator {
 
     /// Resolve DNS for a subdomain
     async fn dns_resolve(subdomain: &str) -> bool {
-        use tokio::net::lookup_host;
-        
-        // Try to resolve the subdomain
-        match lookup_host(format!("{}:443", subdomain)).await {
-            Ok(mut addrs) => addrs.next().is_some(),
-            Err(_) => false,
-        }
+        !crate::net::resolve(subdomain, 443).await.is_empty()
     }
 
     /// Generate subdomain report
@@ -1,16 +1,17 @@
 use crate::http_client::HttpClient;
+use serde::Serialize;
 use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use tokio::time::{timeout, Duration};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct VersionedEndpoint {
     pub base_url: String,
     pub versions: Vec<ApiVersion>,
     pub vulnerabilities: Vec<VersionVulnerability>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ApiVersion {
     pub version: String,
     pub url: String,
@@ -20,7 +21,7 @@ pub struct ApiVersion {
     pub endpoints_found: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct VersionVulnerability {
     pub vuln_type: String,
     pub severity: String,
@@ -0,0 +1,214 @@
+//! Virtual host discovery
+//! Requests the target's IPs directly with candidate `Host` headers (known subdomains plus a wordlist)
+//! and compares each response to the default virtual host. Candidates that answer with a different
+//! application are hidden vhosts - internal tools, staging sites or admin panels that share the server
+//! but aren't in public DNS.
+
+use crate::http_client::Paced;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use reqwest::header::{HeaderMap, HOST, LOCATION};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::time::Duration;
+
+/// Candidates tried per IP
+pub const MAX_CANDIDATES: usize = 2000;
+
+/// Bytes of each body fingerprinted
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Names commonly used for internal applications
+pub const DEFAULT_WORDS: &[&str] = &[
+    "admin", "administrator", "internal", "intranet", "private", "corp", "staff", "backoffice", "back-office",
+    "dev", "development", "staging", "stage", "test", "qa", "uat", "preprod", "sandbox", "demo", "beta",
+    "api", "api-internal", "internal-api", "api-dev", "api-staging", "gateway", "graphql", "grpc",
+    "dashboard", "console", "portal", "manage", "management", "mgmt", "ops", "devops", "panel", "cms",
+    "jenkins", "gitlab", "git", "jira", "confluence", "wiki", "docs", "swagger", "kibana", "grafana",
+    "prometheus", "metrics", "monitor", "monitoring", "status", "health", "logs", "elastic", "search",
+    "db", "database", "phpmyadmin", "pma", "adminer", "redis", "rabbitmq", "queue", "auth", "sso", "login",
+    "legacy", "old", "new", "v1", "v2", "app", "apps", "mobile", "partner", "partners", "vpn", "mail",
+    "localhost", "local", "origin", "backend", "web", "www2", "static", "files", "upload", "storage",
+];
+
+/// What a response looks like, for comparing vhosts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub status: u16,
+    pub length: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip)]
+    pub simhash: Option<u64>,
+}
+
+impl Fingerprint {
+    pub fn new(status: u16, headers: &HeaderMap, body: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(body);
+        Self {
+            status,
+            length: body.len(),
+            title: title(&text),
+            location: headers.get(LOCATION).and_then(|v| v.to_str().ok()).map(|s| s.to_string()),
+            simhash: crate::enrich::simhash::simhash(body),
+        }
+    }
+
+    /// Same application: status, redirect and title agree and the bodies are near-identical
+    /// (similar simhash or lengths within 10%, since many default pages echo the Host header)
+    pub fn matches(&self, other: &Fingerprint) -> bool {
+        if self.status != other.status || self.location != other.location || self.title != other.title {
+            return false;
+        }
+        if let (Some(a), Some(b)) = (self.simhash, other.simhash) {
+            if crate::enrich::simhash::distance(a, b) <= 6 {
+                return true;
+            }
+        }
+        let (min, max) = (self.length.min(other.length), self.length.max(other.length));
+        (max - min) * 10 <= max
+    }
+}
+
+fn title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let t = html.get(start..end)?.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!t.is_empty()).then(|| t.chars().take(120).collect())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VhostHit {
+    /// Base URL that was requested (`https://203.0.113.7:443`)
+    pub target: String,
+    pub host: String,
+    /// "subdomains" or "wordlist"
+    pub source: String,
+    /// Whether the name resolves in public DNS
+    pub resolves: bool,
+    #[serde(flatten)]
+    pub fingerprint: Fingerprint,
+}
+
+/// Candidate hosts for `domain`: known subdomains first, then `<word>.<domain>` for every word
+pub fn candidates(domain: &str, subdomains: &[String], words: &[String]) -> Vec<(String, String)> {
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    let mut out: BTreeMap<String, String> = BTreeMap::new();
+    for sub in subdomains {
+        let sub = sub.trim().trim_end_matches('.').to_lowercase();
+        if !sub.is_empty() && sub != domain && !sub.starts_with("*.") {
+            out.entry(sub).or_insert_with(|| "subdomains".to_string());
+        }
+    }
+    for word in words {
+        let word = word.trim().trim_matches('.').to_lowercase();
+        if word.is_empty() || word.starts_with('#') || word.contains(['/', ' ']) {
+            continue;
+        }
+        out.entry(format!("{}.{}", word, domain)).or_insert_with(|| "wordlist".to_string());
+    }
+    let (mut known, rest): (Vec<_>, Vec<_>) = out.into_iter().partition(|(_, s)| s == "subdomains");
+    known.extend(rest);
+    known.truncate(MAX_CANDIDATES);
+    known
+}
+
+/// Words from a wordlist file (one per line, `#` comments)
+pub fn load_wordlist(path: &Path) -> Result<Vec<String>> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read vhost wordlist {}: {}", path.display(), e))?;
+    Ok(data.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty() && !l.starts_with('#')).collect())
+}
+
+/// `http://ip:80` and `https://ip:443` for every IP
+pub fn targets(ips: &[IpAddr]) -> Vec<String> {
+    ips.iter()
+        .flat_map(|ip| [("https", 443), ("http", 80)].map(|(scheme, port)| format!("{}://{}", scheme, SocketAddr::new(*ip, port))))
+        .collect()
+}
+
+pub struct VhostScanner {
+    client: Client,
+    concurrency: usize,
+}
+
+impl VhostScanner {
+    pub fn new(timeout: Duration, concurrency: usize) -> Result<Self> {
+        // No credentials or cookie jar: cookies of one vhost would be sent to all others on the IP
+        let client = crate::http_client::builder_unauthenticated("discover::vhost")
+            .timeout(timeout)
+            .connect_timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            // The certificate belongs to one of the vhosts at best
+            .danger_accept_invalid_certs(true)
+            .user_agent(crate::tls_profile::user_agent_or("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36"))
+            .build()?;
+        Ok(Self { client, concurrency: concurrency.max(1) })
+    }
+
+    async fn fetch(&self, target: &str, host: &str) -> Option<Fingerprint> {
+        let resp = self.client.get(format!("{}/", target)).header(HOST, host).send_paced().await.ok()?;
+        let status = resp.status().as_u16();
+        let headers = resp.headers().clone();
+        let (body, _) = crate::probe::http_probe::read_limited(resp, BODY_LIMIT).await.ok()?;
+        Some(Fingerprint::new(status, &headers, &body))
+    }
+
+    /// Try every candidate against `target`. `domain` is the public site, which doesn't count as hidden.
+    pub async fn scan(&self, target: &str, domain: &str, candidates: &[(String, String)]) -> Vec<VhostHit> {
+        // The default vhost answers for unknown names and the bare IP
+        let bogus = format!("apihunter-{:08x}.invalid", rand::random::<u32>());
+        let authority = target.split("://").nth(1).unwrap_or(target).to_string();
+        let mut baselines = Vec::new();
+        for host in [bogus.as_str(), authority.as_str(), domain] {
+            if let Some(fp) = self.fetch(target, host).await {
+                baselines.push(fp);
+            }
+        }
+        if baselines.is_empty() {
+            tracing::debug!("vhost: {} did not answer", target);
+            return Vec::new();
+        }
+
+        let mut hits: Vec<VhostHit> = stream::iter(candidates)
+            .map(|(host, source)| {
+                let baselines = &baselines;
+                async move {
+                    let fp = self.fetch(target, host).await?;
+                    if baselines.iter().any(|b| b.matches(&fp)) {
+                        return None;
+                    }
+                    Some(VhostHit {
+                        target: target.to_string(),
+                        host: host.clone(),
+                        source: source.clone(),
+                        resolves: resolves(host).await,
+                        fingerprint: fp,
+                    })
+                }
+            })
+            .buffer_unordered(self.concurrency)
+            .filter_map(|h| async move { h })
+            .collect()
+            .await;
+
+        // A server that answers every name differently (echoing the Host header) makes every candidate a hit
+        if hits.len() > 10 && hits.len() * 2 > candidates.len() {
+            tracing::warn!("vhost: {} of {} candidates differ on {}, results discarded as unreliable", hits.len(), candidates.len(), target);
+            return Vec::new();
+        }
+        hits.sort_by(|a, b| a.resolves.cmp(&b.resolves).then(a.host.cmp(&b.host)));
+        hits
+    }
+}
+
+async fn resolves(host: &str) -> bool {
+    crate::net::resolves(host, Duration::from_secs(5)).await
+}
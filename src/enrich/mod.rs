@@ -1 +1,3 @@
 pub mod json_shape;
+pub mod pii;
+pub mod simhash;
@@ -0,0 +1,215 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::output::writer_jsonl::RawEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PiiKind {
+    Email,
+    Phone,
+    CreditCard,
+    Iban,
+    Ssn,
+}
+
+/// A PII value found in a JSON document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiMatch {
+    pub kind: PiiKind,
+    /// JSON path, e.g. `data.users[0].email`
+    pub path: String,
+    /// Masked value, safe to put in reports
+    pub sample: String,
+}
+
+static EMAIL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}$").unwrap());
+static SSN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{3}-\d{2}-\d{4}$").unwrap());
+static IBAN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z]{2}\d{2}[A-Z0-9]{11,30}$").unwrap());
+static PHONE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\+?[\d\s().-]{7,20}$").unwrap());
+
+/// Classify a single string value. `key` is the JSON key holding it, used to disambiguate numbers.
+pub fn classify_value(key: &str, value: &str) -> Option<PiiKind> {
+    let v = value.trim();
+    let key = key.to_lowercase();
+
+    if EMAIL_RE.is_match(v) {
+        return Some(PiiKind::Email);
+    }
+    if SSN_RE.is_match(v) && !v.starts_with("000") && !v.starts_with("666") {
+        return Some(PiiKind::Ssn);
+    }
+
+    let compact: String = v.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+    if IBAN_RE.is_match(&compact) && iban_valid(&compact) {
+        return Some(PiiKind::Iban);
+    }
+
+    let digits: String = v.chars().filter(|c| c.is_ascii_digit()).collect();
+    let only_card_chars = v.chars().all(|c| c.is_ascii_digit() || c == ' ' || c == '-');
+    // Plain numeric IDs happily pass Luhn, so ID-like keys are excluded
+    if only_card_chars && (13..=19).contains(&digits.len()) && luhn_valid(&digits) && !key.ends_with("id") {
+        return Some(PiiKind::CreditCard);
+    }
+
+    let phone_key = ["phone", "mobile", "tel", "fax", "msisdn"].iter().any(|k| key.contains(k));
+    if PHONE_RE.is_match(v) && (7..=15).contains(&digits.len()) && (phone_key || v.starts_with('+')) {
+        return Some(PiiKind::Phone);
+    }
+
+    None
+}
+
+/// PII leaked by one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiReport {
+    pub url: String,
+    pub status: u16,
+    pub matches: Vec<PiiMatch>,
+}
+
+// Free text has no keys to go by, so the candidates are stricter than in JSON: phone numbers need the
+// international prefix and card numbers a known issuer prefix
+static TEXT_EMAIL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap());
+static TEXT_SSN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").unwrap());
+static TEXT_IBAN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,4})?\b").unwrap());
+static TEXT_CARD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:4\d{3}|5[1-5]\d{2}|3[47]\d{2}|6011|65\d{2})(?:[ -]?\d){9,15}\b").unwrap());
+static TEXT_PHONE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\+\d[\d ().-]{6,18}\d").unwrap());
+
+/// File names like `logo@2x.png` match the email pattern
+const NOT_EMAIL_SUFFIXES: &[&str] = &[".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp", ".css", ".js"];
+
+/// Scan free text (HTML, error pages, logs) for PII. `path` is the byte offset, e.g. `@1520`.
+pub fn scan_text(text: &str) -> Vec<PiiMatch> {
+    let mut out = Vec::new();
+    let mut push = |kind: PiiKind, start: usize, value: &str| {
+        out.push(PiiMatch { kind, path: format!("@{}", start), sample: mask(value) });
+    };
+    for m in TEXT_EMAIL_RE.find_iter(text) {
+        let lower = m.as_str().to_ascii_lowercase();
+        if !NOT_EMAIL_SUFFIXES.iter().any(|s| lower.ends_with(s)) {
+            push(PiiKind::Email, m.start(), m.as_str());
+        }
+    }
+    for m in TEXT_SSN_RE.find_iter(text) {
+        if classify_value("ssn", m.as_str()) == Some(PiiKind::Ssn) {
+            push(PiiKind::Ssn, m.start(), m.as_str());
+        }
+    }
+    for m in TEXT_IBAN_RE.find_iter(text) {
+        if classify_value("iban", m.as_str()) == Some(PiiKind::Iban) {
+            push(PiiKind::Iban, m.start(), m.as_str());
+        }
+    }
+    for m in TEXT_CARD_RE.find_iter(text) {
+        if classify_value("card", m.as_str()) == Some(PiiKind::CreditCard) {
+            push(PiiKind::CreditCard, m.start(), m.as_str());
+        }
+    }
+    for m in TEXT_PHONE_RE.find_iter(text) {
+        if classify_value("phone", m.as_str()) == Some(PiiKind::Phone) {
+            push(PiiKind::Phone, m.start(), m.as_str());
+        }
+    }
+    out.sort_by_key(|m| m.path[1..].parse::<usize>().unwrap_or(0));
+    out
+}
+
+/// PII in a probe result: the stored body if there is one (as JSON when it parses), else the JSON sample.
+/// The same value found twice is reported once.
+pub fn scan_event(e: &RawEvent) -> Vec<PiiMatch> {
+    let mut found = match e.body.as_ref().and_then(|b| b.text()) {
+        Some(text) => match serde_json::from_str::<Value>(&text) {
+            Ok(v) => scan_json(&v),
+            Err(_) => scan_text(&text),
+        },
+        None => match &e.json_sample {
+            // Non-JSON responses keep a short text sample
+            Some(Value::Object(map)) if map.len() == 1 && map.contains_key("_sample") => {
+                map["_sample"].as_str().map(scan_text).unwrap_or_default()
+            }
+            Some(v) => scan_json(v),
+            None => Vec::new(),
+        },
+    };
+    let mut seen = std::collections::HashSet::new();
+    found.retain(|m| seen.insert((m.kind, m.sample.clone())));
+    found
+}
+
+/// Walk a JSON document and collect every PII-looking value
+pub fn scan_json(v: &Value) -> Vec<PiiMatch> {
+    let mut out = Vec::new();
+    walk(v, "", "", &mut out);
+    out
+}
+
+fn walk(v: &Value, path: &str, key: &str, out: &mut Vec<PiiMatch>) {
+    match v {
+        Value::Object(map) => {
+            for (k, val) in map {
+                let p = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+                walk(val, &p, k, out);
+            }
+        }
+        Value::Array(arr) => {
+            // Samples only - a few rows show whether the data is real
+            for (i, item) in arr.iter().take(20).enumerate() {
+                walk(item, &format!("{}[{}]", path, i), key, out);
+            }
+        }
+        Value::String(s) => {
+            if let Some(kind) = classify_value(key, s) {
+                out.push(PiiMatch { kind, path: path.to_string(), sample: mask(s) });
+            }
+        }
+        Value::Number(n) => {
+            if let Some(kind) = classify_value(key, &n.to_string()) {
+                out.push(PiiMatch { kind, path: path.to_string(), sample: mask(&n.to_string()) });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Luhn checksum over a digit string
+pub fn luhn_valid(digits: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in digits.chars().rev().enumerate() {
+        let Some(mut d) = c.to_digit(10) else { return false };
+        if i % 2 == 1 {
+            d *= 2;
+            if d > 9 {
+                d -= 9;
+            }
+        }
+        sum += d;
+    }
+    !digits.is_empty() && sum % 10 == 0
+}
+
+/// ISO 13616 mod-97 check
+fn iban_valid(iban: &str) -> bool {
+    let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+    let mut rem: u32 = 0;
+    for c in rearranged.chars() {
+        let n = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            'A'..='Z' => c as u32 - 'A' as u32 + 10,
+            _ => return false,
+        };
+        rem = if n >= 10 { (rem * 100 + n) % 97 } else { (rem * 10 + n) % 97 };
+    }
+    rem == 1
+}
+
+/// Keep the first and last two characters: "john@example.com" -> "jo************om"
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let middle = "*".repeat(chars.len() - 4);
+    format!("{}{}{}", chars[..2].iter().collect::<String>(), middle, chars[chars.len() - 2..].iter().collect::<String>())
+}
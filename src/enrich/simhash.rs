@@ -0,0 +1,134 @@
+//! Response similarity clustering
+//! A shop or CMS yields hundreds of candidates that are the same template with different content
+//! (`/product/1`, `/product/2`, ...). Each body gets a 64-bit simhash over token bigrams (numbers
+//! normalized); responses of one host with the same status and type whose hashes differ in at most a
+//! few bits form a cluster, and the sorted CSV and top list keep one representative per cluster.
+
+use crate::output::annotation::Annotation;
+use crate::output::writer_jsonl::RawEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Hamming distance up to which two responses count as the same template
+pub const DEFAULT_MAX_DISTANCE: u32 = 10;
+
+/// Bodies with fewer tokens carry too little signal to be clustered
+const MIN_TOKENS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    pub id: String,
+    /// `orig_url` of the member kept in the sorted output (highest score)
+    pub representative: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    pub size: usize,
+    pub members: Vec<String>,
+}
+
+/// Simhash of a body; `None` if it has too few tokens
+pub fn simhash(body: &[u8]) -> Option<u64> {
+    let text = String::from_utf8_lossy(body).to_lowercase();
+    let tokens: Vec<&str> = text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| if t.chars().any(|c| c.is_ascii_digit()) { "#" } else { t })
+        .collect();
+    if tokens.len() < MIN_TOKENS {
+        return None;
+    }
+    let mut weights = [0i32; 64];
+    for pair in tokens.windows(2) {
+        let h = fnv1a(pair[0].as_bytes(), fnv1a(b" ", fnv1a(pair[1].as_bytes(), FNV_OFFSET)));
+        for (bit, w) in weights.iter_mut().enumerate() {
+            if h >> bit & 1 == 1 { *w += 1 } else { *w -= 1 }
+        }
+    }
+    Some(weights.iter().enumerate().filter(|(_, w)| **w > 0).fold(0u64, |acc, (bit, _)| acc | 1 << bit))
+}
+
+/// Hex form stored in `RawEvent::body_simhash`
+pub fn to_hex(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+pub fn from_hex(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex, 16).ok()
+}
+
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+fn fnv1a(bytes: &[u8], seed: u64) -> u64 {
+    bytes.iter().fold(seed, |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Hash of an event: computed by the probe, else from the stored body or JSON sample (results loaded
+/// with `--resume`)
+fn event_hash(ev: &RawEvent) -> Option<u64> {
+    if let Some(h) = ev.body_simhash.as_deref().and_then(from_hex) {
+        return Some(h);
+    }
+    if let Some(text) = ev.body.as_ref().and_then(|b| b.text()) {
+        return simhash(text.as_bytes());
+    }
+    ev.json_sample.as_ref().and_then(|j| simhash(j.to_string().as_bytes()))
+}
+
+/// Responses that can share a cluster: host, status and MIME type
+type Bucket = (String, u16, String);
+
+/// Group near-identical responses. Members of clusters with more than one response get
+/// `Annotation::Cluster`; only those clusters are returned, largest first.
+pub fn cluster(events: &mut [RawEvent], max_distance: u32) -> Vec<Cluster> {
+    // First member's hash and all member indices, per bucket
+    let mut buckets: HashMap<Bucket, Vec<(u64, Vec<usize>)>> = HashMap::new();
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    // Highest score first, so it becomes the representative
+    order.sort_by(|&a, &b| events[b].score.cmp(&events[a].score)
+        .then(events[a].orig_url.len().cmp(&events[b].orig_url.len()))
+        .then(events[a].orig_url.cmp(&events[b].orig_url)));
+
+    for i in order {
+        let ev = &events[i];
+        let Some(hash) = event_hash(ev) else { continue };
+        let host = url::Url::parse(&ev.orig_url).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_default();
+        let mime = ev.content_type.as_deref().map(|c| c.split(';').next().unwrap_or(c).trim().to_ascii_lowercase()).unwrap_or_default();
+        let groups = buckets.entry((host, ev.status, mime)).or_default();
+        match groups.iter_mut().find(|(h, _)| distance(*h, hash) <= max_distance) {
+            Some((_, members)) => members.push(i),
+            None => groups.push((hash, vec![i])),
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = buckets.into_values().flatten().map(|(_, m)| m).filter(|m| m.len() > 1).collect();
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then(events[a[0]].orig_url.cmp(&events[b[0]].orig_url)));
+
+    groups.into_iter().enumerate().map(|(n, members)| {
+        let id = format!("c{}", n + 1);
+        for &i in &members {
+            events[i].annotate(Annotation::Cluster { id: id.clone() });
+        }
+        let rep = &events[members[0]];
+        Cluster {
+            representative: rep.orig_url.clone(),
+            status: rep.status,
+            content_type: rep.content_type.clone(),
+            size: members.len(),
+            members: members.iter().map(|&i| events[i].orig_url.clone()).collect(),
+            id,
+        }
+    }).collect()
+}
+
+/// Events outside clusters plus one representative per cluster, in input order
+pub fn representatives<'a>(events: &'a [RawEvent], clusters: &[Cluster]) -> Vec<&'a RawEvent> {
+    let mut hidden: std::collections::HashSet<&str> = clusters.iter().flat_map(|c| c.members.iter().map(String::as_str)).collect();
+    for c in clusters {
+        hidden.remove(c.representative.as_str());
+    }
+    events.iter().filter(|e| !hidden.contains(e.orig_url.as_str())).collect()
+}
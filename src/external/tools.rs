@@ -24,12 +24,3 @@ pub async fn try_run_hakrawler(domain: &str, tx: Sender<String>) -> Result<Optio
     let pid = runner::stream_cmd_lines("hakrawler", &args, tx).await?;
     Ok(Some(pid))
 }
-
-#[allow(dead_code)]
-pub async fn try_run_ffuf(target: &str, wordlist: &str, tx: Sender<String>) -> Result<Option<u32>> {
-    if which("ffuf").is_err() { return Ok(None); }
-    // Example: ffuf -u https://example.com/FUZZ -w wordlist
-    let args = ["-u", &format!("{}{{}}", target), "-w", wordlist];
-    let pid = runner::stream_cmd_lines("ffuf", &args, tx).await?;
-    Ok(Some(pid))
-}
@@ -1 +1,2 @@
 pub mod api_patterns;
+pub mod third_party;
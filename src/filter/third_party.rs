@@ -0,0 +1,141 @@
+//! Third-party host exclusion
+//! Analytics, monitoring and CDN hosts referenced from JS are reported instead of actively scanned
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Analytics, monitoring, CDN and SaaS hosts that show up in almost every JS bundle.
+/// They are out of scope for nearly every program, so they are reported instead of probed.
+const DEFAULT_THIRD_PARTY_DOMAINS: &[&str] = &[
+    // Google
+    "google.com", "googleapis.com", "gstatic.com", "google-analytics.com", "googletagmanager.com",
+    "googlesyndication.com", "googleadservices.com", "doubleclick.net", "youtube.com", "ytimg.com",
+    "recaptcha.net",
+    // Monitoring / analytics
+    "sentry.io", "sentry-cdn.com", "newrelic.com", "nr-data.net", "datadoghq.com", "datadoghq.eu",
+    "bugsnag.com", "hotjar.com", "hotjar.io", "segment.com", "segment.io", "mixpanel.com",
+    "amplitude.com", "fullstory.com", "clarity.ms", "optimizely.com", "launchdarkly.com",
+    // CDNs
+    "cloudflare.com", "cdnjs.cloudflare.com", "cloudflareinsights.com", "jsdelivr.net", "unpkg.com",
+    "akamaihd.net", "akamaized.net", "fastly.net", "cloudfront.net", "bootstrapcdn.com",
+    "jquery.com", "fontawesome.com", "typekit.net",
+    // Social / ads
+    "facebook.com", "facebook.net", "fbcdn.net", "twitter.com", "twimg.com", "linkedin.com",
+    "licdn.com", "tiktok.com", "pinterest.com", "bing.com", "adnxs.com", "criteo.com",
+    // SaaS widgets
+    "intercom.io", "intercomcdn.com", "zendesk.com", "zdassets.com", "hubspot.com", "hs-scripts.com",
+    "stripe.com", "stripe.network", "paypal.com", "paypalobjects.com", "onetrust.com",
+    "cookielaw.org", "cookiebot.com", "gravatar.com", "w3.org", "schema.org", "github.com",
+];
+
+/// Decides which hosts are actively scanned and which are only referenced
+#[derive(Debug, Clone)]
+pub struct HostExclusion {
+    excluded: Vec<String>,
+    included: Vec<String>,
+}
+
+/// A third-party host seen during discovery but not scanned
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ThirdPartyReference {
+    pub host: String,
+    pub count: usize,
+    pub sample_urls: Vec<String>,
+}
+
+impl HostExclusion {
+    /// `use_defaults` enables the built-in list, `extra_excludes` adds domains,
+    /// `includes` forces domains back into scope even if they are on the list.
+    pub fn new(use_defaults: bool, extra_excludes: &[String], includes: &[String]) -> Self {
+        let mut excluded: Vec<String> = if use_defaults {
+            DEFAULT_THIRD_PARTY_DOMAINS.iter().map(|d| d.to_string()).collect()
+        } else {
+            Vec::new()
+        };
+        excluded.extend(extra_excludes.iter().map(|d| normalize_domain(d)));
+
+        Self {
+            excluded,
+            included: includes.iter().map(|d| normalize_domain(d)).collect(),
+        }
+    }
+
+    /// True if `host` should not be actively scanned. The scan target and its subdomains are never excluded.
+    pub fn is_excluded(&self, host: &str, target: &str) -> bool {
+        let host = host.to_lowercase();
+        if host_matches(&host, &target.to_lowercase()) {
+            return false;
+        }
+        if self.included.iter().any(|d| host_matches(&host, d)) {
+            return false;
+        }
+        self.excluded.iter().any(|d| host_matches(&host, d))
+    }
+
+    /// Split URLs into in-scope candidates and third-party references grouped by host
+    pub fn partition(&self, urls: Vec<String>, target: &str) -> (Vec<String>, Vec<ThirdPartyReference>) {
+        let mut keep = Vec::new();
+        let mut refs: BTreeMap<String, ThirdPartyReference> = BTreeMap::new();
+
+        for u in urls {
+            let host = url::Url::parse(&u).ok().and_then(|p| p.host_str().map(|h| h.to_string()));
+            match host {
+                Some(h) if self.is_excluded(&h, target) => {
+                    let entry = refs.entry(h.clone()).or_insert_with(|| ThirdPartyReference {
+                        host: h,
+                        count: 0,
+                        sample_urls: Vec::new(),
+                    });
+                    entry.count += 1;
+                    if entry.sample_urls.len() < 5 {
+                        entry.sample_urls.push(u);
+                    }
+                }
+                _ => keep.push(u),
+            }
+        }
+
+        let mut refs: Vec<ThirdPartyReference> = refs.into_values().collect();
+        refs.sort_by_key(|r| std::cmp::Reverse(r.count));
+        (keep, refs)
+    }
+}
+
+fn normalize_domain(d: &str) -> String {
+    d.trim().trim_start_matches("*.").trim_start_matches('.').to_lowercase()
+}
+
+fn host_matches(host: &str, domain: &str) -> bool {
+    !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+}
+
+/// True if the host of `url` is `domain` or one of its subdomains
+pub fn on_target(url: &str, domain: &str) -> bool {
+    url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_lowercase)).is_some_and(|h| host_matches(&h, &domain.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_third_party_exclusion() {
+        let ex = HostExclusion::new(true, &["tracker.io".to_string()], &["sentry.io".to_string()]);
+        assert!(ex.is_excluded("www.google-analytics.com", "example.com"));
+        assert!(ex.is_excluded("cdn.tracker.io", "example.com"));
+        assert!(!ex.is_excluded("o123.ingest.sentry.io", "example.com"));
+        assert!(!ex.is_excluded("api.example.com", "example.com"));
+        // Scanning google.com itself must not exclude it
+        assert!(!ex.is_excluded("maps.google.com", "google.com"));
+    }
+
+    #[test]
+    fn test_on_target() {
+        assert!(on_target("https://API.example.com/oauth/authorize", "example.com"));
+        assert!(on_target("https://example.com", "example.com"));
+        assert!(!on_target("https://accounts.google.com/o/oauth2/auth", "example.com"));
+        assert!(!on_target("https://notexample.com/", "example.com"));
+        assert!(!on_target("not a url", "example.com"));
+    }
+}
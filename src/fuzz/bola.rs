@@ -0,0 +1,268 @@
+//! Two-identity BOLA/IDOR testing
+//! Every object endpoint is fetched as user A, as user B and without credentials. An issue is only
+//! reported when B gets A's data back: A's response is readable, the anonymous request is refused (so
+//! the data isn't public) and B's body carries A's values, not B's own version of the resource.
+//! Object URLs also come from A's list responses (`/orders` returning `[{"id": 17}]` gives `/orders/17`).
+
+use crate::http_client::Paced;
+use crate::enrich::simhash;
+use crate::fuzz::param_discovery::detect_path_ids;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// URLs tested per scan
+pub const MAX_TARGETS: usize = 150;
+
+/// Object URLs derived from one list response
+const IDS_PER_LIST: usize = 3;
+
+/// Bytes of each body compared
+const BODY_LIMIT: usize = 256 * 1024;
+
+/// Share of A's values (in %) B must receive for a non-identical body to count as A's data
+const LEAK_PERCENT: usize = 80;
+
+/// Credentials of one test user
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub name: String,
+    pub headers: HeaderMap,
+}
+
+impl Identity {
+    /// From `Name:value` header specs
+    pub fn from_specs(name: &str, specs: &[String]) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        for spec in specs {
+            let (k, mut v) = crate::identify::parse(spec)?;
+            v.set_sensitive(true);
+            headers.append(k, v);
+        }
+        Ok(Self { name: name.to_string(), headers })
+    }
+}
+
+/// One response, as far as it's needed for the comparison
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub status: u16,
+    pub body: String,
+    pub json: Option<Value>,
+}
+
+impl Fetched {
+    pub fn new(status: u16, body: String) -> Self {
+        let json = serde_json::from_str(&body).ok();
+        Self { status, body, json }
+    }
+
+    fn readable(&self) -> bool {
+        (200..300).contains(&self.status) && !self.body.trim().is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum Verdict {
+    /// B received A's data
+    Vulnerable { identical: bool, leaked: Vec<String> },
+    /// B was refused
+    Denied,
+    /// Readable without credentials too
+    Public,
+    /// B got its own version of the resource
+    Different,
+    /// A couldn't read it, nothing to compare
+    Unusable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BolaFinding {
+    pub url: String,
+    /// "high" for identical bodies, "medium" otherwise
+    pub severity: String,
+    pub status_a: u16,
+    pub status_b: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_anonymous: Option<u16>,
+    pub identical: bool,
+    /// A's values (`path=value`) found in B's response
+    pub leaked: Vec<String>,
+}
+
+/// Decide whether `b` is A's data. `anonymous` is the response without credentials.
+pub fn compare(a: &Fetched, b: &Fetched, anonymous: Option<&Fetched>) -> Verdict {
+    if !a.readable() {
+        return Verdict::Unusable;
+    }
+    if !b.readable() {
+        return Verdict::Denied;
+    }
+    if let Some(anon) = anonymous {
+        if anon.readable() && same_data(a, anon) {
+            return Verdict::Public;
+        }
+    }
+    if a.body == b.body {
+        let leaked = a.json.as_ref().map(|j| leaves(j, "").into_iter().take(10).collect()).unwrap_or_default();
+        return Verdict::Vulnerable { identical: true, leaked };
+    }
+    match (&a.json, &b.json) {
+        (Some(ja), Some(jb)) => {
+            let ours = leaves(ja, "");
+            if ours.is_empty() {
+                return Verdict::Different;
+            }
+            let theirs: BTreeSet<String> = leaves(jb, "").into_iter().collect();
+            let leaked: Vec<String> = ours.iter().filter(|l| theirs.contains(*l)).cloned().collect();
+            if leaked.len() * 100 >= ours.len() * LEAK_PERCENT {
+                Verdict::Vulnerable { identical: false, leaked: leaked.into_iter().take(10).collect() }
+            } else {
+                Verdict::Different
+            }
+        }
+        _ if same_data(a, b) => Verdict::Vulnerable { identical: false, leaked: Vec::new() },
+        _ => Verdict::Different,
+    }
+}
+
+/// Same body, or near-identical by simhash
+fn same_data(x: &Fetched, y: &Fetched) -> bool {
+    if x.body == y.body {
+        return true;
+    }
+    match (simhash::simhash(x.body.as_bytes()), simhash::simhash(y.body.as_bytes())) {
+        (Some(hx), Some(hy)) => simhash::distance(hx, hy) <= 3,
+        _ => false,
+    }
+}
+
+/// Identifying scalar values as `path=value`: strings of 3+ characters and numbers other than 0/1
+/// (booleans, nulls and flags say nothing about whose object it is)
+fn leaves(v: &Value, path: &str) -> Vec<String> {
+    match v {
+        Value::Object(map) => map.iter().flat_map(|(k, v)| leaves(v, &if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) })).collect(),
+        Value::Array(arr) => arr.iter().enumerate().flat_map(|(i, v)| leaves(v, &format!("{}[{}]", path, i))).collect(),
+        Value::String(s) if s.chars().count() >= 3 => vec![format!("{}={}", path, s)],
+        Value::Number(n) if n.as_f64().is_some_and(|f| f.abs() > 1.0) => vec![format!("{}={}", path, n)],
+        _ => Vec::new(),
+    }
+}
+
+/// Object URLs from a list response: `{url}/{id}` for the first entries with an `id`
+pub fn object_urls(url: &str, json: &Value) -> Vec<String> {
+    let items = match json {
+        Value::Array(items) => items,
+        Value::Object(map) => match map.values().find_map(|v| v.as_array()) {
+            Some(items) => items,
+            None => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    let base = url.split(['?', '#']).next().unwrap_or(url).trim_end_matches('/');
+    items.iter()
+        .filter_map(|item| ["id", "_id", "uuid"].iter().find_map(|k| item.get(*k)))
+        .filter_map(|id| match id {
+            Value::String(s) if !s.is_empty() && !s.contains('/') => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+        .take(IDS_PER_LIST)
+        .map(|id| format!("{}/{}", base, urlencoding::encode(&id)))
+        .collect()
+}
+
+/// Whether a URL addresses a single object (ID in the path or an id-like query parameter)
+pub fn is_object_url(url: &str) -> bool {
+    if !detect_path_ids(url).is_empty() {
+        return true;
+    }
+    url::Url::parse(url).map(|u| u.query_pairs().any(|(k, _)| {
+        let k = k.to_ascii_lowercase();
+        k == "id" || k.ends_with("_id") || (k.ends_with("id") && k.len() > 2)
+    })).unwrap_or(false)
+}
+
+pub struct BolaTester {
+    client: Client,
+    a: Identity,
+    b: Identity,
+    timeout: Duration,
+}
+
+impl BolaTester {
+    /// `client` must not carry credentials of its own (see `http_client::builder_unauthenticated`)
+    pub fn new(client: Client, a: Identity, b: Identity, timeout: Duration) -> Self {
+        Self { client, a, b, timeout }
+    }
+
+    async fn fetch(&self, url: &str, identity: Option<&Identity>) -> Option<Fetched> {
+        let mut req = self.client.get(url).timeout(self.timeout);
+        if let Some(id) = identity {
+            req = req.headers(id.headers.clone());
+        }
+        let resp = req.send_paced().await.ok()?;
+        let status = resp.status().as_u16();
+        let (bytes, _) = crate::probe::http_probe::read_limited(resp, BODY_LIMIT).await.ok()?;
+        Some(Fetched::new(status, String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Test one URL; `None` if a request failed
+    pub async fn test_url(&self, url: &str) -> Option<(Verdict, Option<BolaFinding>)> {
+        let a = self.fetch(url, Some(&self.a)).await?;
+        let b = self.fetch(url, Some(&self.b)).await?;
+        let anonymous = self.fetch(url, None).await;
+        let verdict = compare(&a, &b, anonymous.as_ref());
+        let finding = match &verdict {
+            Verdict::Vulnerable { identical, leaked } => Some(BolaFinding {
+                url: url.to_string(),
+                severity: if *identical { "high" } else { "medium" }.to_string(),
+                status_a: a.status,
+                status_b: b.status,
+                status_anonymous: anonymous.as_ref().map(|f| f.status),
+                identical: *identical,
+                leaked: leaked.clone(),
+            }),
+            _ => None,
+        };
+        Some((verdict, finding))
+    }
+
+    /// Test `urls` plus object URLs derived from A's list responses
+    pub async fn run(&self, urls: &[String], concurrency: usize) -> Vec<BolaFinding> {
+        let mut targets: BTreeSet<String> = urls.iter().filter(|u| is_object_url(u)).take(MAX_TARGETS).cloned().collect();
+        let lists: Vec<&String> = urls.iter().filter(|u| !is_object_url(u)).take(MAX_TARGETS).collect();
+        let derived: Vec<Vec<String>> = stream::iter(lists)
+            .map(|u| async move {
+                match self.fetch(u, Some(&self.a)).await {
+                    Some(f) if f.readable() => f.json.as_ref().map(|j| object_urls(u, j)).unwrap_or_default(),
+                    _ => Vec::new(),
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+        for url in derived.into_iter().flatten() {
+            if targets.len() >= MAX_TARGETS {
+                break;
+            }
+            targets.insert(url);
+        }
+        tracing::info!("BOLA: testing {} object URLs as {} and {}", targets.len(), self.a.name, self.b.name);
+
+        let mut findings: Vec<BolaFinding> = stream::iter(targets.iter())
+            .map(|u| self.test_url(u))
+            .buffer_unordered(concurrency.max(1))
+            .filter_map(|r| async move { r.and_then(|(_, f)| f) })
+            .collect()
+            .await;
+        findings.sort_by(|x, y| x.severity.cmp(&y.severity).then(x.url.cmp(&y.url)));
+        findings
+    }
+}
@@ -0,0 +1,329 @@
+//! Field selection and sort key enumeration on search/filter endpoints
+//! Many list APIs let the client choose returned fields (`fields=`, `select=`, `$select=`) and sort keys.
+//! Requesting sensitive fields by name (`fields=*,password`) or sorting by them (`sort=password`)
+//! exposes data the default projection hides, and rejected names often leak the schema in the error.
+
+use crate::http_client::HttpClient;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use tokio::time::{timeout, Duration};
+
+/// Parameters that mark an endpoint as search/filter/list style
+const SEARCH_PARAMS: &[&str] = &[
+    "q", "query", "search", "s", "term", "keyword", "filter", "filters", "where",
+    "sort", "sort_by", "sortBy", "order", "order_by", "orderBy", "$orderby",
+    "fields", "field", "select", "$select", "include", "expand", "projection",
+    "page", "limit", "per_page", "pageSize", "offset",
+];
+
+/// Parameters that choose which fields are returned
+const SELECTOR_PARAMS: &[&str] = &[
+    "fields", "field", "select", "$select", "include", "expand", "projection", "only", "attributes",
+];
+
+/// Parameters that choose the sort key
+const SORT_PARAMS: &[&str] = &["sort", "sort_by", "sortBy", "order", "order_by", "orderBy", "$orderby"];
+
+/// Field names worth asking for explicitly
+const SENSITIVE_FIELDS: &[&str] = &[
+    "password", "password_hash", "passwordHash", "hash", "salt", "secret", "token", "api_key", "apiKey",
+    "reset_token", "resetToken", "mfa_secret", "otp_secret", "ssn", "credit_card", "role", "is_admin",
+    "email", "phone",
+];
+
+/// A name no schema has, used as the negative control
+const BOGUS_FIELD: &str = "apihunter_nonexistent_field";
+
+static SCHEMA_LEAK_RES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        ("SQL unknown column", r#"(?i)unknown column '?([\w.]+)'?"#),
+        ("SQL column does not exist", r#"(?i)column "?([\w.]+)"? does not exist"#),
+        ("SQLite no such column", r"(?i)no such column:?\s*([\w.]+)"),
+        ("ORM invalid field", r#"(?i)(?:invalid|unknown|unrecognized) (?:field|attribute|property|sort key)[^\n]{0,40}?["'`]?([\w.]+)"#),
+        ("GraphQL-style field error", r#"(?i)cannot query field "?([\w.]+)"#),
+        ("Field suggestion", r#"(?i)did you mean "?([\w.]+)"#),
+        ("SQLSTATE error", r"(SQLSTATE\[\w+\])"),
+        ("MongoDB projection error", r"(?i)(MongoError|MongoServerError|\$project)"),
+    ]
+    .into_iter()
+    .map(|(name, re)| (name, Regex::new(re).unwrap()))
+    .collect()
+});
+
+/// Lists of allowed fields in validation errors: `allowed fields: id, name, email`
+static FIELD_LIST_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(?:allowed|valid|available|permitted|expected one of)[ \w]{0,20}?(?:fields|values|keys|columns)?\s*(?:are|is)?\s*[:=]?\s*\[?((?:["'`]?[\w.]+["'`]?\s*,\s*)+["'`]?[\w.]+["'`]?)"#).unwrap()
+});
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSelectionResult {
+    pub url: String,
+    /// Selector parameters the endpoint honored
+    pub selector_params: Vec<String>,
+    /// Sensitive fields that exist according to the sort-key oracle
+    pub sortable_fields: Vec<String>,
+    /// Field names recovered from error messages
+    pub leaked_fields: Vec<String>,
+    pub vulnerabilities: Vec<FieldSelectionVuln>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSelectionVuln {
+    pub vuln_type: String,
+    pub severity: String,
+    pub description: String,
+    pub parameter: String,
+    pub payload: String,
+}
+
+/// A schema leak found in an error response
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaLeak {
+    pub pattern: &'static str,
+    pub evidence: String,
+    pub fields: Vec<String>,
+}
+
+/// Does the URL look like a search/filter/list endpoint?
+pub fn is_search_endpoint(url: &str) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else { return false };
+    if parsed.query_pairs().any(|(k, _)| SEARCH_PARAMS.contains(&k.as_ref())) {
+        return true;
+    }
+    let path = parsed.path().to_lowercase();
+    let last = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+    matches!(last, "search" | "filter" | "query" | "list" | "find" | "lookup")
+}
+
+/// Set (or replace) a query parameter
+pub fn with_param(url: &str, name: &str, value: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else { return url.to_string() };
+    let pairs: Vec<(String, String)> = parsed.query_pairs()
+        .filter(|(k, _)| k != name)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs).append_pair(name, value);
+    parsed.to_string()
+}
+
+/// Look for database/ORM errors that name columns or list the valid fields
+pub fn schema_leak(body: &str) -> Option<SchemaLeak> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut hit: Option<(&'static str, String)> = None;
+
+    for (name, re) in SCHEMA_LEAK_RES.iter() {
+        if let Some(c) = re.captures(body) {
+            if hit.is_none() {
+                hit = Some((name, c[0].chars().take(160).collect()));
+            }
+            let field = c[1].trim_matches(|ch| ch == '"' || ch == '\'' || ch == '`');
+            if field != BOGUS_FIELD && !field.starts_with("SQLSTATE") && field.chars().all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '.') {
+                fields.push(field.to_string());
+            }
+        }
+    }
+    if let Some(c) = FIELD_LIST_RE.captures(body) {
+        if hit.is_none() {
+            hit = Some(("Allowed field list", c[0].chars().take(160).collect()));
+        }
+        fields.extend(c[1].split(',')
+            .map(|f| f.trim().trim_matches(|ch| ch == '"' || ch == '\'' || ch == '`').to_string())
+            .filter(|f| !f.is_empty() && f != BOGUS_FIELD));
+    }
+
+    let (pattern, evidence) = hit?;
+    let mut seen = HashSet::new();
+    fields.retain(|f| seen.insert(f.clone()));
+    Some(SchemaLeak { pattern, evidence, fields })
+}
+
+/// Sensitive keys with a non-null value in `response` that the baseline response didn't have
+pub fn new_sensitive_keys(baseline: &Value, response: &Value) -> Vec<String> {
+    let before = keys(baseline);
+    let mut found: Vec<String> = keys_with_values(response).into_iter()
+        .filter(|k| !before.contains(k) && is_sensitive(k))
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let k = key.to_lowercase().replace(['_', '-'], "");
+    SENSITIVE_FIELDS.iter().any(|f| f.to_lowercase().replace('_', "") == k)
+        || k.contains("password")
+        || k.contains("secret")
+}
+
+fn keys(v: &Value) -> HashSet<String> {
+    let mut out = HashSet::new();
+    walk(v, &mut |k, _| { out.insert(k.to_string()); });
+    out
+}
+
+fn keys_with_values(v: &Value) -> HashSet<String> {
+    let mut out = HashSet::new();
+    walk(v, &mut |k, val| {
+        if !val.is_null() && val != &Value::String(String::new()) {
+            out.insert(k.to_string());
+        }
+    });
+    out
+}
+
+fn walk(v: &Value, f: &mut dyn FnMut(&str, &Value)) {
+    match v {
+        Value::Object(map) => {
+            for (k, val) in map {
+                f(k, val);
+                walk(val, f);
+            }
+        }
+        Value::Array(arr) => arr.iter().for_each(|i| walk(i, f)),
+        _ => {}
+    }
+}
+
+struct Probe {
+    status: u16,
+    body: String,
+    json: Option<Value>,
+}
+
+pub struct FieldSelectionTester {
+    client: HttpClient,
+}
+
+impl FieldSelectionTester {
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+
+    /// Enumerate field selectors and sort keys on a search/filter endpoint
+    pub async fn test_endpoint(&self, url: &str) -> Result<FieldSelectionResult> {
+        let mut result = FieldSelectionResult {
+            url: url.to_string(),
+            selector_params: Vec::new(),
+            sortable_fields: Vec::new(),
+            leaked_fields: Vec::new(),
+            vulnerabilities: Vec::new(),
+        };
+
+        let baseline = self.fetch(url).await?;
+        let Some(baseline_json) = baseline.json.clone() else {
+            // Field exposure is judged on JSON keys
+            return Ok(result);
+        };
+        let mut leaks_seen = HashSet::new();
+
+        for param in SELECTOR_PARAMS {
+            // Only parameters that change the response are worth enumerating
+            let narrowed = match self.fetch(&with_param(url, param, "id")).await {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+            self.record_leak(&mut result, &mut leaks_seen, param, "id", &narrowed);
+            let honored = narrowed.status == baseline.status
+                && narrowed.json.as_ref().is_some_and(|j| keys(j) != keys(&baseline_json));
+            if !honored {
+                continue;
+            }
+            result.selector_params.push(param.to_string());
+
+            if let Ok(bogus) = self.fetch(&with_param(url, param, BOGUS_FIELD)).await {
+                self.record_leak(&mut result, &mut leaks_seen, param, BOGUS_FIELD, &bogus);
+            }
+
+            let mut payloads: Vec<String> = vec![format!("*,{}", SENSITIVE_FIELDS.join(","))];
+            payloads.extend(SENSITIVE_FIELDS.iter().map(|f| format!("*,{}", f)));
+            for payload in payloads {
+                let Ok(probe) = self.fetch(&with_param(url, param, &payload)).await else { continue };
+                self.record_leak(&mut result, &mut leaks_seen, param, &payload, &probe);
+                let Some(json) = &probe.json else { continue };
+                let exposed = new_sensitive_keys(&baseline_json, json);
+                if exposed.is_empty() {
+                    continue;
+                }
+                let critical = exposed.iter().any(|k| {
+                    let k = k.to_lowercase();
+                    k.contains("password") || k.contains("secret") || k.contains("token") || k.contains("hash") || k.contains("key")
+                });
+                result.vulnerabilities.push(FieldSelectionVuln {
+                    vuln_type: "Field-Level Data Exposure".to_string(),
+                    severity: if critical { "CRITICAL" } else { "HIGH" }.to_string(),
+                    description: format!("'{}={}' returns fields hidden by default: {}", param, payload, exposed.join(", ")),
+                    parameter: param.to_string(),
+                    payload,
+                });
+                // The combined payload already shows everything; per-field requests would only repeat it
+                break;
+            }
+        }
+
+        for param in SORT_PARAMS {
+            let (Ok(known), Ok(bogus)) = (
+                self.fetch(&with_param(url, param, "id")).await,
+                self.fetch(&with_param(url, param, BOGUS_FIELD)).await,
+            ) else { continue };
+            self.record_leak(&mut result, &mut leaks_seen, param, BOGUS_FIELD, &bogus);
+
+            // The oracle needs a known field and an unknown one to be answered differently
+            if known.status == bogus.status || known.status != baseline.status {
+                continue;
+            }
+            for field in SENSITIVE_FIELDS {
+                let Ok(probe) = self.fetch(&with_param(url, param, field)).await else { continue };
+                self.record_leak(&mut result, &mut leaks_seen, param, field, &probe);
+                if probe.status == known.status && !result.sortable_fields.iter().any(|f| f == field) {
+                    result.sortable_fields.push(field.to_string());
+                }
+            }
+            if !result.sortable_fields.is_empty() {
+                result.vulnerabilities.push(FieldSelectionVuln {
+                    vuln_type: "Sortable Sensitive Field".to_string(),
+                    severity: "MEDIUM".to_string(),
+                    description: format!(
+                        "'{}' accepts hidden fields as sort keys ({}) - result ordering can leak their values",
+                        param, result.sortable_fields.join(", ")
+                    ),
+                    parameter: param.to_string(),
+                    payload: format!("{}={}", param, result.sortable_fields[0]),
+                });
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn record_leak(&self, result: &mut FieldSelectionResult, seen: &mut HashSet<&'static str>, param: &str, payload: &str, probe: &Probe) {
+        let Some(leak) = schema_leak(&probe.body) else { return };
+        for f in &leak.fields {
+            if !result.leaked_fields.contains(f) {
+                result.leaked_fields.push(f.clone());
+            }
+        }
+        // One finding per error type; the field list keeps growing
+        if seen.insert(leak.pattern) {
+            result.vulnerabilities.push(FieldSelectionVuln {
+                vuln_type: "Error-Based Schema Leak".to_string(),
+                severity: if leak.fields.is_empty() { "LOW" } else { "MEDIUM" }.to_string(),
+                description: format!("{} in response (status {}): {}", leak.pattern, probe.status, leak.evidence),
+                parameter: param.to_string(),
+                payload: payload.to_string(),
+            });
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Probe> {
+        let resp = timeout(Duration::from_secs(5), self.client.get(url)).await??;
+        let status = resp.status().as_u16();
+        let body = resp.text().await?;
+        let json = serde_json::from_str(&body).ok();
+        Ok(Probe { status, body, json })
+    }
+}
@@ -0,0 +1,355 @@
+//! Multi-step logic flow testing
+//! Runs user-defined request sequences from YAML with variable capture between steps,
+//! e.g. create a resource as one identity, read it as another, then delete it.
+//!
+//! ```yaml
+//! name: order-idor
+//! base_url: https://api.example.com
+//! identities:
+//!   alice: { headers: { Authorization: "Bearer AAA" } }
+//!   bob:   { headers: { Authorization: "Bearer BBB" } }
+//! steps:
+//!   - name: create order
+//!     as: alice
+//!     method: POST
+//!     path: /api/orders
+//!     json: { item: 42, price: 100 }
+//!     capture: { order_id: "$.id" }
+//!     expect: { status: [200, 201] }
+//!   - name: read order as bob
+//!     as: bob
+//!     path: /api/orders/{{order_id}}
+//!     vulnerable_if: { status: [200] }
+//!     description: Orders are readable by other users
+//! ```
+
+use crate::http_client::Paced;
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::time::Duration;
+
+static VAR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([\w.-]+)\s*\}\}").unwrap());
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Flow {
+    pub name: String,
+    /// Prefix for step paths; steps may also use absolute `url`s
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub identities: HashMap<String, Identity>,
+    /// Initial variables, available as `{{name}}`
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Identity {
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Step {
+    pub name: String,
+    /// Identity whose headers are sent with this step
+    #[serde(default, rename = "as")]
+    pub identity: Option<String>,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Raw request body
+    #[serde(default)]
+    pub body: Option<String>,
+    /// JSON request body; string values are templated
+    #[serde(default)]
+    pub json: Option<Value>,
+    /// Variable name -> source: `$.path.to[0].field`, `header:Name`, `status` or `regex:pattern`
+    #[serde(default)]
+    pub capture: HashMap<String, String>,
+    /// Required for the flow to continue (setup steps)
+    #[serde(default)]
+    pub expect: Option<Condition>,
+    /// Matching response means the flow found an issue
+    #[serde(default)]
+    pub vulnerable_if: Option<Condition>,
+    #[serde(default = "default_severity")]
+    pub severity: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// All listed checks must hold for the condition to match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Condition {
+    #[serde(default)]
+    pub status: Vec<u16>,
+    #[serde(default)]
+    pub body_contains: Vec<String>,
+    #[serde(default)]
+    pub body_not_contains: Vec<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_severity() -> String {
+    "HIGH".to_string()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepOutcome {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub captured: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowFinding {
+    pub flow: String,
+    pub step: String,
+    pub severity: String,
+    pub description: String,
+    pub url: String,
+    pub status: u16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowResult {
+    pub name: String,
+    pub steps: Vec<StepOutcome>,
+    pub findings: Vec<FlowFinding>,
+    /// Why the flow stopped early (request error, failed `expect`, missing capture)
+    pub aborted: Option<String>,
+}
+
+impl Flow {
+    /// Load a flow definition from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read flow {}", path.display()))?;
+        Self::parse(&data).with_context(|| format!("invalid flow {}", path.display()))
+    }
+
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let flow: Flow = serde_yaml::from_str(yaml)?;
+        for step in &flow.steps {
+            if step.path.is_none() && step.url.is_none() {
+                bail!("step '{}' needs a path or url", step.name);
+            }
+            if step.path.is_some() && step.url.is_none() && flow.base_url.is_none() {
+                bail!("step '{}' uses a path but the flow has no base_url", step.name);
+            }
+            if let Some(id) = &step.identity {
+                if !flow.identities.contains_key(id) {
+                    bail!("step '{}' uses unknown identity '{}'", step.name, id);
+                }
+            }
+        }
+        Ok(flow)
+    }
+}
+
+pub struct FlowRunner {
+    client: Client,
+}
+
+impl FlowRunner {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Execute all steps in order. Variables captured by a step are available to every later step.
+    pub async fn run(&self, flow: &Flow) -> FlowResult {
+        let mut vars = flow.variables.clone();
+        let mut result = FlowResult {
+            name: flow.name.clone(),
+            steps: Vec::new(),
+            findings: Vec::new(),
+            aborted: None,
+        };
+
+        for step in &flow.steps {
+            let url = match (&step.url, &step.path, &flow.base_url) {
+                (Some(u), _, _) => render(u, &vars),
+                (None, Some(p), Some(base)) => format!("{}{}", base.trim_end_matches('/'), render(p, &vars)),
+                _ => unreachable!("validated in Flow::parse"),
+            };
+            if let Some(missing) = unresolved(&url) {
+                result.aborted = Some(format!("step '{}': variable '{}' was never captured", step.name, missing));
+                break;
+            }
+
+            let method = match reqwest::Method::from_bytes(step.method.to_uppercase().as_bytes()) {
+                Ok(m) => m,
+                Err(_) => {
+                    result.aborted = Some(format!("step '{}': invalid method {}", step.name, step.method));
+                    break;
+                }
+            };
+
+            let mut req = self.client.request(method, &url).timeout(Duration::from_secs(15));
+            if let Some(identity) = step.identity.as_ref().and_then(|i| flow.identities.get(i)) {
+                for (k, v) in &identity.headers {
+                    req = req.header(k, render(v, &vars));
+                }
+            }
+            for (k, v) in &step.headers {
+                req = req.header(k, render(v, &vars));
+            }
+            if let Some(json) = &step.json {
+                req = req.json(&render_json(json, &vars));
+            } else if let Some(body) = &step.body {
+                req = req.body(render(body, &vars));
+            }
+
+            let resp = match req.send_paced().await {
+                Ok(r) => r,
+                Err(e) => {
+                    result.aborted = Some(format!("step '{}': request failed: {}", step.name, e));
+                    break;
+                }
+            };
+            let status = resp.status().as_u16();
+            let headers = resp.headers().clone();
+            let body = resp.text().await.unwrap_or_default();
+
+            let mut captured = HashMap::new();
+            for (name, source) in &step.capture {
+                match capture(source, status, &headers, &body) {
+                    Some(value) => {
+                        captured.insert(name.clone(), value.clone());
+                        vars.insert(name.clone(), value);
+                    }
+                    None => tracing::debug!("flow '{}' step '{}': capture {} ({}) found nothing", flow.name, step.name, name, source),
+                }
+            }
+
+            result.steps.push(StepOutcome {
+                name: step.name.clone(),
+                method: step.method.to_uppercase(),
+                url: url.clone(),
+                status,
+                captured,
+            });
+
+            if let Some(cond) = &step.vulnerable_if {
+                if cond.matches(status, &body) {
+                    result.findings.push(FlowFinding {
+                        flow: flow.name.clone(),
+                        step: step.name.clone(),
+                        severity: step.severity.to_uppercase(),
+                        description: step.description.clone()
+                            .unwrap_or_else(|| format!("Step '{}' returned {} where it should have been refused", step.name, status)),
+                        url: url.clone(),
+                        status,
+                    });
+                }
+            }
+
+            if let Some(cond) = &step.expect {
+                if !cond.matches(status, &body) {
+                    result.aborted = Some(format!("step '{}': expectation not met (status {})", step.name, status));
+                    break;
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl Condition {
+    pub fn matches(&self, status: u16, body: &str) -> bool {
+        (self.status.is_empty() || self.status.contains(&status))
+            && self.body_contains.iter().all(|s| body.contains(s.as_str()))
+            && !self.body_not_contains.iter().any(|s| body.contains(s.as_str()))
+    }
+}
+
+/// Replace `{{var}}` placeholders; unknown variables are left in place
+pub fn render(template: &str, vars: &HashMap<String, String>) -> String {
+    VAR_RE.replace_all(template, |c: &regex::Captures| {
+        vars.get(&c[1]).cloned().unwrap_or_else(|| c[0].to_string())
+    }).into_owned()
+}
+
+fn render_json(v: &Value, vars: &HashMap<String, String>) -> Value {
+    match v {
+        Value::String(s) => {
+            // A value that is only a placeholder keeps the captured value's JSON type
+            if let Some(c) = VAR_RE.captures(s).filter(|c| c[0].len() == s.len()) {
+                if let Some(val) = vars.get(&c[1]) {
+                    return serde_json::from_str::<Value>(val)
+                        .ok()
+                        .filter(|v| v.is_number() || v.is_boolean())
+                        .unwrap_or_else(|| Value::String(val.clone()));
+                }
+            }
+            Value::String(render(s, vars))
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(|i| render_json(i, vars)).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(k, i)| (k.clone(), render_json(i, vars))).collect()),
+        other => other.clone(),
+    }
+}
+
+fn unresolved(s: &str) -> Option<String> {
+    VAR_RE.captures(s).map(|c| c[1].to_string())
+}
+
+/// Extract a value from a response according to a capture source
+pub fn capture(source: &str, status: u16, headers: &reqwest::header::HeaderMap, body: &str) -> Option<String> {
+    if source == "status" {
+        return Some(status.to_string());
+    }
+    if let Some(name) = source.strip_prefix("header:") {
+        return headers.get(name.trim()).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+    }
+    if let Some(pattern) = source.strip_prefix("regex:") {
+        let re = Regex::new(pattern).ok()?;
+        let caps = re.captures(body)?;
+        return caps.get(1).or_else(|| caps.get(0)).map(|m| m.as_str().to_string());
+    }
+
+    let json: Value = serde_json::from_str(body).ok()?;
+    match json_path(&json, source)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Minimal JSONPath: `$.data.items[0].id`
+pub fn json_path<'a>(v: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim().trim_start_matches('$');
+    let mut cur = v;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(i) => (&segment[..i], &segment[i..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            cur = cur.get(key)?;
+        }
+        for idx in indexes.split('[').filter(|s| !s.is_empty()) {
+            cur = cur.get(idx.trim_end_matches(']').parse::<usize>().ok()?)?;
+        }
+    }
+    Some(cur)
+}
@@ -0,0 +1,318 @@
+//! HTTP parameter pollution
+//! A parameter is sent twice with different values: twice in the query string, once in the query and
+//! once in a form body, or as a duplicated key in a JSON object. Which value the back end uses
+//! (the first, the last, both joined) is read from the response: a reflected value says it directly,
+//! otherwise the polluted response is compared with the responses to each value alone. Layers that
+//! disagree here (a gateway or validator reading the first value, the application the last) let a value
+//! past a check that would reject it, so parameters named like an identifier, role or price are also sent
+//! with a tampered value: rejected alone, accepted with a legitimate value next to it, is a finding.
+
+use crate::fuzz::param_bruteforce::ParamLocation;
+use crate::http_client::HttpClient;
+use crate::output::clean_reporter::Severity;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Bytes of each response body compared
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Simhash distance up to which two bodies are the same page
+const MAX_DISTANCE: u32 = 3;
+
+/// Names checked for authorization bypasses
+pub const AUTHZ_PARAMS: &[&str] = &[
+    "id", "user", "user_id", "userid", "uid", "account", "account_id", "accountid", "owner", "owner_id",
+    "ownerid", "customer_id", "customerid", "tenant", "tenant_id", "tenantid", "org_id", "orgid", "role",
+    "roles", "admin", "is_admin", "isadmin", "group", "group_id", "scope", "permission", "access_level",
+];
+
+/// Names checked for pricing manipulation
+pub const PRICING_PARAMS: &[&str] = &[
+    "price", "amount", "total", "cost", "discount", "coupon", "quantity", "qty", "currency", "plan", "tier",
+    "credits", "balance", "fee", "tax", "shipping",
+];
+
+/// How the duplicated values are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    /// `?name=a&name=b`
+    Query,
+    /// `?name=a` with `name=b` in a form-encoded POST body
+    QueryAndBody,
+    /// `{"name": a, "name": b}` POSTed as JSON
+    JsonKey,
+}
+
+/// Which of the duplicated values the back end used. For `QueryAndBody` the first value is the query's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precedence {
+    First,
+    Last,
+    /// Both values, joined (`a,b`) or as a list
+    Concatenated,
+    /// Error status the single values don't get
+    Rejected,
+    /// The response doesn't tell
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    Authorization,
+    Pricing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Behavior {
+    pub channel: Channel,
+    pub precedence: Precedence,
+    pub evidence: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HppFinding {
+    pub url: String,
+    pub parameter: String,
+    pub location: ParamLocation,
+    pub behaviors: Vec<Behavior>,
+    /// Set when a tampered value got through next to a legitimate one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact: Option<Impact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+}
+
+/// One response, reduced to what is compared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub status: u16,
+    pub body: String,
+}
+
+/// `url` with `name` set to each of `values` in turn (none: removed)
+pub fn polluted_query(url: &str, name: &str, values: &[&str]) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else { return url.to_string() };
+    let pairs: Vec<(String, String)> = parsed.query_pairs()
+        .filter(|(k, _)| k != name)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    {
+        let mut query = parsed.query_pairs_mut();
+        query.clear().extend_pairs(pairs);
+        for value in values {
+            query.append_pair(name, value);
+        }
+    }
+    if parsed.query() == Some("") {
+        parsed.set_query(None);
+    }
+    parsed.to_string()
+}
+
+/// JSON object with `name` repeated for each value; serde would keep only one
+pub fn duplicate_key_json(name: &str, values: &[&str]) -> String {
+    let key = serde_json::to_string(name).unwrap_or_default();
+    let fields: Vec<String> = values.iter().map(|v| format!("{}:{}", key, json_value(v))).collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Numbers and booleans stay unquoted, so typed fields accept them
+fn json_value(value: &str) -> String {
+    if value.parse::<i64>().is_ok() || value == "true" || value == "false" {
+        value.to_string()
+    } else {
+        serde_json::to_string(value).unwrap_or_default()
+    }
+}
+
+fn same_page(a: &Snapshot, b: &Snapshot) -> bool {
+    if a.status != b.status {
+        return false;
+    }
+    if a.body == b.body {
+        return true;
+    }
+    match (crate::enrich::simhash::simhash(a.body.as_bytes()), crate::enrich::simhash::simhash(b.body.as_bytes())) {
+        (Some(x), Some(y)) => crate::enrich::simhash::distance(x, y) <= MAX_DISTANCE,
+        _ => false,
+    }
+}
+
+/// Which value the polluted response reflects or resembles
+pub fn classify(first: &str, last: &str, polluted: &Snapshot, only_first: &Snapshot, only_last: &Snapshot) -> (Precedence, String) {
+    let has = |snap: &Snapshot, v: &str| snap.body.contains(v);
+    // Reflected values, if the single responses reflect them too
+    if has(only_first, first) && has(only_last, last) {
+        return match (has(polluted, first), has(polluted, last)) {
+            (true, true) => {
+                let joined = [",", ", ", " ", "\",\""].iter().find(|sep| polluted.body.contains(&format!("{}{}{}", first, sep, last)));
+                (Precedence::Concatenated, match joined {
+                    Some(sep) => format!("both values reflected as {}{}{}", first, sep, last),
+                    None => "both values reflected".to_string(),
+                })
+            }
+            (true, false) => (Precedence::First, format!("{} reflected, {} not", first, last)),
+            (false, true) => (Precedence::Last, format!("{} reflected, {} not", last, first)),
+            (false, false) => (Precedence::Unknown, "neither value reflected".to_string()),
+        };
+    }
+    if polluted.status >= 400 && only_first.status < 400 && only_last.status < 400 {
+        return (Precedence::Rejected, format!("{} for the duplicate, {}/{} for single values", polluted.status, only_first.status, only_last.status));
+    }
+    if same_page(only_first, only_last) {
+        return (Precedence::Unknown, "both values give the same response".to_string());
+    }
+    match (same_page(polluted, only_first), same_page(polluted, only_last)) {
+        (true, false) => (Precedence::First, format!("same response as {} alone ({})", first, polluted.status)),
+        (false, true) => (Precedence::Last, format!("same response as {} alone ({})", last, polluted.status)),
+        _ => (Precedence::Unknown, format!("response ({}) matches neither value alone", polluted.status)),
+    }
+}
+
+/// Whether pollution matters for `name`, and a value worth smuggling past a check
+pub fn tampered_value(name: &str, original: &str) -> Option<(Impact, String)> {
+    let lower = name.to_ascii_lowercase();
+    let numeric = original.parse::<i64>().ok();
+    if AUTHZ_PARAMS.contains(&lower.as_str()) {
+        let value = match numeric {
+            Some(n) => (n + 1).to_string(),
+            None if ["true", "false", "0", "1"].contains(&original) => "true".to_string(),
+            None => "admin".to_string(),
+        };
+        return Some((Impact::Authorization, value));
+    }
+    if PRICING_PARAMS.contains(&lower.as_str()) {
+        let value = if numeric == Some(0) { "-1" } else { "0" };
+        return Some((Impact::Pricing, value.to_string()));
+    }
+    None
+}
+
+fn denied(status: u16) -> bool {
+    matches!(status, 400 | 401 | 403 | 409 | 422)
+}
+
+pub struct HppTester<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+}
+
+impl<'a> HppTester<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout }
+    }
+
+    /// `first` values go in the query (or JSON object), `last` in the form body for `QueryAndBody`
+    async fn send(&self, channel: Channel, url: &str, name: &str, first: &[&str], last: &[&str]) -> Option<Snapshot> {
+        let req = match channel {
+            Channel::Query => {
+                let values: Vec<&str> = first.iter().chain(last).copied().collect();
+                self.http.client().get(polluted_query(url, name, &values))
+            }
+            Channel::QueryAndBody => {
+                let body = url::form_urlencoded::Serializer::new(String::new()).extend_pairs(last.iter().map(|v| (name, *v))).finish();
+                self.http.client()
+                    .post(polluted_query(url, name, first))
+                    .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(body)
+            }
+            Channel::JsonKey => {
+                let values: Vec<&str> = first.iter().chain(last).copied().collect();
+                self.http.client()
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(duplicate_key_json(name, &values))
+            }
+        };
+        let resp = self.http.send(req.timeout(self.timeout)).await.ok()?;
+        let status = resp.status().as_u16();
+        let (bytes, _) = tokio::time::timeout(self.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some(Snapshot { status, body: String::from_utf8_lossy(&bytes).into_owned() })
+    }
+
+    /// Responses to `a` alone, `b` alone and both
+    async fn triple(&self, channel: Channel, url: &str, name: &str, a: &str, b: &str) -> Option<(Snapshot, Snapshot, Snapshot)> {
+        let only_a = self.send(channel, url, name, &[a], &[]).await?;
+        let only_b = self.send(channel, url, name, &[], &[b]).await?;
+        let both = self.send(channel, url, name, &[a], &[b]).await?;
+        Some((only_a, only_b, both))
+    }
+
+    /// Test one parameter; `None` if the endpoint didn't answer
+    pub async fn test(&self, url: &str, name: &str, location: ParamLocation) -> Option<HppFinding> {
+        let original = url::Url::parse(url).ok()?
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| !v.is_empty());
+        let channels: &[Channel] = match location {
+            ParamLocation::Query => &[Channel::Query, Channel::QueryAndBody],
+            ParamLocation::Json => &[Channel::JsonKey],
+        };
+        let canary = format!("hpp{:06x}", rand::random::<u32>() & 0xffffff);
+        // The real value, where known, makes the two single responses differ on endpoints that don't echo
+        let first = original.clone().unwrap_or_else(|| format!("hpp{:06x}", rand::random::<u32>() & 0xffffff));
+
+        let mut finding = HppFinding {
+            url: url.to_string(),
+            parameter: name.to_string(),
+            location,
+            behaviors: Vec::new(),
+            impact: None,
+            channel: None,
+            severity: None,
+            evidence: None,
+        };
+        for &channel in channels {
+            let Some((only_first, only_last, both)) = self.triple(channel, url, name, &first, &canary).await else { continue };
+            // POST not handled at all: nothing to learn from the body channel
+            if channel != Channel::Query && matches!(only_first.status, 404 | 405 | 501) {
+                continue;
+            }
+            let (precedence, evidence) = classify(&first, &canary, &both, &only_first, &only_last);
+            finding.behaviors.push(Behavior { channel, precedence, evidence });
+        }
+        if finding.behaviors.is_empty() {
+            return None;
+        }
+
+        // A tampered value that is rejected alone but accepted next to the legitimate one
+        if let Some((impact, tampered)) = original.as_deref().and_then(|v| tampered_value(name, v)) {
+            let legit = original.as_deref().unwrap_or_default();
+            let tested: Vec<Channel> = finding.behaviors.iter().map(|b| b.channel).collect();
+            for channel in tested {
+                let Some(alone) = self.send(channel, url, name, &[&tampered], &[]).await else { continue };
+                let Some(reference) = self.send(channel, url, name, &[legit], &[]).await else { continue };
+                if !denied(alone.status) || !(200..300).contains(&reference.status) {
+                    continue;
+                }
+                for (order, a, b) in [("legitimate first", legit, tampered.as_str()), ("tampered first", tampered.as_str(), legit)] {
+                    let Some(polluted) = self.send(channel, url, name, &[a], &[b]).await else { continue };
+                    if !(200..300).contains(&polluted.status) {
+                        continue;
+                    }
+                    // The tampered value took effect: it shows up, or the page is not the legitimate one
+                    let took_effect = (polluted.body.contains(&tampered) && !reference.body.contains(&tampered)) || !same_page(&polluted, &reference);
+                    if took_effect {
+                        finding.impact = Some(impact);
+                        finding.channel = Some(channel);
+                        finding.severity = Some(Severity::High);
+                        finding.evidence = Some(format!(
+                            "{}={} alone: {}; with {}={} ({}): {} and a different response than {} alone",
+                            name, tampered, alone.status, name, legit, order, polluted.status, legit
+                        ));
+                        return Some(finding);
+                    }
+                }
+            }
+        }
+        Some(finding)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use anyhow::Result;
 use reqwest::Client;
 use url::Url;
@@ -198,7 +199,7 @@ fn analyze_idor_response(
 async fn fetch_response(client: &Client, url: &str) -> Result<(u16, usize, String)> {
     let resp = client.get(url)
         .timeout(std::time::Duration::from_secs(5))
-        .send()
+        .send_paced()
         .await?;
     
     let status = resp.status().as_u16();
@@ -0,0 +1,516 @@
+//! JSON request body fuzzing
+//! Takes request bodies the API is known to accept - captured in HAR/Burp imports or built from an
+//! OpenAPI/Swagger spec - and sends them again with one field changed at a time: a different type
+//! (string to array or object, number to string), boundary values (huge and negative numbers, empty and
+//! very long strings, null) and injection payloads. Responses are compared with the unmodified request
+//! and classified: database errors, template evaluation and operator injection point at injection, type
+//! errors and stack traces at missing input validation (type confusion).
+
+use crate::http_client::HttpClient;
+use crate::output::clean_reporter::Severity;
+use crate::waf::bypass::BypassTechnique;
+use crate::waf::mutation::{Context, WafMutators};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// Fields mutated per body (leaves, depth-first)
+pub const MAX_FIELDS: usize = 25;
+
+/// Bytes of each response body classified
+const BODY_LIMIT: usize = 32 * 1024;
+
+/// Nesting followed when building bodies from a schema
+const MAX_SCHEMA_DEPTH: usize = 6;
+
+/// Where a seed body came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedSource {
+    /// Captured traffic (`--import-har`, `--import-burp`)
+    Observed,
+    /// Built from an OpenAPI/Swagger request schema
+    Spec,
+}
+
+/// A request body the endpoint is expected to accept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BodySeed {
+    pub method: String,
+    pub url: String,
+    pub body: Value,
+    pub source: SeedSource,
+}
+
+impl BodySeed {
+    /// A captured write request with a JSON body
+    pub fn observed(req: &crate::gather::har::HarRequest) -> Option<Self> {
+        let method = req.method.to_ascii_uppercase();
+        if !matches!(method.as_str(), "POST" | "PUT" | "PATCH") {
+            return None;
+        }
+        let body = req.json_body.clone().filter(|b| b.is_object() || b.is_array())?;
+        Some(Self { method, url: req.url.clone(), body, source: SeedSource::Observed })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationKind {
+    /// Value wrapped in an array
+    ToArray,
+    /// Value wrapped in an object
+    ToObject,
+    /// Number sent as string or string as number
+    SwapType,
+    Null,
+    HugeNumber,
+    NegativeNumber,
+    EmptyString,
+    LongString,
+    /// `'"` - breaks SQL string literals
+    SqlQuote,
+    /// `{"$ne": null}` - MongoDB operator instead of a value
+    NoSqlOperator,
+    /// `{{1337*7}}` in several template syntaxes - evaluated by template engines
+    Template,
+    PathTraversal,
+    CommandInjection,
+}
+
+impl MutationKind {
+    fn is_type_change(&self) -> bool {
+        matches!(self, Self::ToArray | Self::ToObject | Self::SwapType | Self::Null)
+    }
+
+    /// String payloads a WAF signature would match
+    fn is_injection(&self) -> bool {
+        matches!(self, Self::SqlQuote | Self::Template | Self::PathTraversal | Self::CommandInjection)
+    }
+}
+
+/// The replacement value for a field
+pub fn mutate(value: &Value, kind: MutationKind) -> Option<Value> {
+    Some(match kind {
+        MutationKind::ToArray => json!([value]),
+        MutationKind::ToObject => json!({ "value": value }),
+        MutationKind::SwapType => match value {
+            Value::Number(n) => Value::String(n.to_string()),
+            Value::String(_) => json!(1),
+            Value::Bool(b) => Value::String(b.to_string()),
+            _ => return None,
+        },
+        MutationKind::Null => {
+            if value.is_null() {
+                return None;
+            }
+            Value::Null
+        }
+        MutationKind::HugeNumber => {
+            if !value.is_number() {
+                return None;
+            }
+            // Overflows i64, and as a float loses precision
+            serde_json::from_str("99999999999999999999999999").ok()?
+        }
+        MutationKind::NegativeNumber => {
+            if !value.is_number() {
+                return None;
+            }
+            json!(-1)
+        }
+        MutationKind::EmptyString => {
+            if !value.is_string() || value.as_str() == Some("") {
+                return None;
+            }
+            json!("")
+        }
+        MutationKind::LongString => {
+            if !value.is_string() {
+                return None;
+            }
+            Value::String("A".repeat(10_000))
+        }
+        MutationKind::SqlQuote => string_payload(value, "'\"")?,
+        MutationKind::NoSqlOperator => {
+            if !value.is_string() && !value.is_number() {
+                return None;
+            }
+            json!({ "$ne": null })
+        }
+        MutationKind::Template => string_payload(value, "{{1337*7}}${1337*7}<%= 1337*7 %>#{1337*7}")?,
+        MutationKind::PathTraversal => string_payload(value, "../../../../etc/passwd")?,
+        MutationKind::CommandInjection => string_payload(value, ";id;`id`$(id)")?,
+    })
+}
+
+/// `payload` appended to a string value
+fn string_payload(value: &Value, payload: &str) -> Option<Value> {
+    Some(Value::String(format!("{}{}", value.as_str()?, payload)))
+}
+
+pub const ALL_MUTATIONS: [MutationKind; 13] = [
+    MutationKind::ToArray, MutationKind::ToObject, MutationKind::SwapType, MutationKind::Null,
+    MutationKind::HugeNumber, MutationKind::NegativeNumber, MutationKind::EmptyString, MutationKind::LongString,
+    MutationKind::SqlQuote, MutationKind::NoSqlOperator, MutationKind::Template, MutationKind::PathTraversal,
+    MutationKind::CommandInjection,
+];
+
+/// JSON pointers of the scalar fields of `body`, depth-first (arrays contribute their first element)
+pub fn leaves(body: &Value) -> Vec<String> {
+    fn walk(v: &Value, pointer: String, out: &mut Vec<String>) {
+        if out.len() >= MAX_FIELDS {
+            return;
+        }
+        match v {
+            Value::Object(map) => {
+                for (k, child) in map {
+                    walk(child, format!("{}/{}", pointer, k.replace('~', "~0").replace('/', "~1")), out);
+                }
+            }
+            Value::Array(items) => {
+                if let Some(first) = items.first() {
+                    walk(first, format!("{}/0", pointer), out);
+                }
+            }
+            _ => out.push(pointer),
+        }
+    }
+    let mut out = Vec::new();
+    walk(body, String::new(), &mut out);
+    out
+}
+
+/// Every single-field mutation of `body`: (pointer, kind, mutated body)
+pub fn mutations(body: &Value) -> Vec<(String, MutationKind, Value)> {
+    let mut out = Vec::new();
+    for pointer in leaves(body) {
+        let Some(original) = body.pointer(&pointer) else { continue };
+        for kind in ALL_MUTATIONS {
+            let Some(replacement) = mutate(original, kind) else { continue };
+            let mut mutated = body.clone();
+            if let Some(slot) = mutated.pointer_mut(&pointer) {
+                *slot = replacement;
+                out.push((pointer.clone(), kind, mutated));
+            }
+        }
+    }
+    out
+}
+
+/// What a response to a mutated body points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Indicator {
+    /// Database error message
+    SqlError,
+    /// MongoDB error, or the operator changed a rejection into success
+    NoSqlInjection,
+    /// `1337*7` came back as 9359
+    TemplateInjection,
+    /// `/etc/passwd` or command output in the response
+    CommandOrFileInjection,
+    /// Type error from the language runtime or the JSON binding
+    TypeConfusion,
+    /// Stack trace in the response
+    StackTrace,
+    /// 5xx where the original body got none
+    ServerError,
+}
+
+impl Indicator {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::SqlError | Self::NoSqlInjection | Self::TemplateInjection | Self::CommandOrFileInjection => Severity::High,
+            Self::TypeConfusion | Self::StackTrace => Severity::Medium,
+            Self::ServerError => Severity::Low,
+        }
+    }
+}
+
+const SQL_ERRORS: &[&str] = &[
+    "sql syntax", "sqlstate", "ora-0", "ora-1", "pg::", "psql:", "pq: ", "sqlite3", "sqlite_error",
+    "unterminated quoted string", "unclosed quotation mark", "syntax error at or near", "mysql_fetch",
+    "odbc driver", "jdbc", "sqlexception", "you have an error in your sql",
+];
+
+const NOSQL_ERRORS: &[&str] = &["mongoerror", "mongoservererror", "casterror", "bsontypeerror", "unknown operator: $"];
+
+const TYPE_ERRORS: &[&str] = &[
+    "typeerror", "is not a function", "cannot read propert", "cannot read properties of", "undefined method",
+    "nomethoderror", "classcastexception", "jsonmappingexception", "mismatchedinputexception",
+    "cannot deserialize", "json: cannot unmarshal", "invalid type: ", "numberformatexception", "valueerror",
+    "attributeerror", "invalidcastexception", "argumentexception", "expected a string", "must be of type",
+];
+
+const STACK_TRACES: &[&str] = &[
+    "traceback (most recent call last)", "at java.", "at org.", "at object.<anonymous>", "/node_modules/",
+    "at system.", "stack trace:", "goroutine ", ".java:", ".py\", line ", "in /var/www/",
+];
+
+/// Classify the response to a mutation. `baseline` is the status of the unmodified body and its text.
+pub fn classify(kind: MutationKind, baseline: (u16, &str), status: u16, body: &str) -> Option<(Indicator, String)> {
+    let lower = body.to_ascii_lowercase();
+    let base_lower = baseline.1.to_ascii_lowercase();
+    let new = |needle: &str| lower.contains(needle) && !base_lower.contains(needle);
+    let evidence = |needle: &str| {
+        let at = lower.find(needle).unwrap_or(0);
+        let boundary = |mut i: usize| { while !body.is_char_boundary(i) { i -= 1; } i };
+        let start = boundary(at.saturating_sub(40));
+        let end = boundary((at + needle.len() + 80).min(body.len()));
+        body[start..end].split_whitespace().collect::<Vec<_>>().join(" ")
+    };
+
+    match kind {
+        MutationKind::SqlQuote => {
+            if let Some(n) = SQL_ERRORS.iter().find(|n| new(n)) {
+                return Some((Indicator::SqlError, evidence(n)));
+            }
+        }
+        MutationKind::NoSqlOperator => {
+            if let Some(n) = NOSQL_ERRORS.iter().find(|n| new(n)) {
+                return Some((Indicator::NoSqlInjection, evidence(n)));
+            }
+            if (400..500).contains(&baseline.0) && (200..300).contains(&status) {
+                return Some((Indicator::NoSqlInjection, format!("status {} -> {}", baseline.0, status)));
+            }
+        }
+        MutationKind::Template if new("9359") => {
+            return Some((Indicator::TemplateInjection, evidence("9359")));
+        }
+        MutationKind::PathTraversal | MutationKind::CommandInjection => {
+            for n in ["root:x:0:0", "uid=0(", "gid=0("] {
+                if new(n) {
+                    return Some((Indicator::CommandOrFileInjection, evidence(n)));
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(n) = STACK_TRACES.iter().find(|n| new(n)) {
+        return Some((Indicator::StackTrace, evidence(n)));
+    }
+    if let Some(n) = TYPE_ERRORS.iter().find(|n| new(n)) {
+        return Some((Indicator::TypeConfusion, evidence(n)));
+    }
+    if status >= 500 && baseline.0 < 500 {
+        let indicator = if kind.is_type_change() { Indicator::TypeConfusion } else { Indicator::ServerError };
+        return Some((indicator, format!("status {} -> {}", baseline.0, status)));
+    }
+    None
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonFuzzFinding {
+    pub method: String,
+    pub url: String,
+    /// JSON pointer of the mutated field
+    pub field: String,
+    pub mutation: MutationKind,
+    pub indicator: Indicator,
+    pub severity: Severity,
+    pub baseline_status: u16,
+    pub status: u16,
+    /// The part of the response that triggered the finding
+    pub evidence: String,
+    pub source: SeedSource,
+    /// Transform that got the payload past the host's WAF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf_bypass: Option<BypassTechnique>,
+}
+
+/// Example request body for an OpenAPI/JSON schema (`example`, `default` and `enum` first)
+pub fn example_from_schema(schema: &Value, spec: &Value) -> Value {
+    fn build(schema: &Value, spec: &Value, depth: usize) -> Value {
+        if depth > MAX_SCHEMA_DEPTH {
+            return Value::Null;
+        }
+        if let Some(r) = schema.get("$ref").and_then(Value::as_str) {
+            return match r.strip_prefix('#').and_then(|p| spec.pointer(p)) {
+                Some(target) => build(target, spec, depth + 1),
+                None => Value::Null,
+            };
+        }
+        for key in ["example", "default"] {
+            if let Some(v) = schema.get(key) {
+                return v.clone();
+            }
+        }
+        if let Some(first) = schema.get("enum").and_then(Value::as_array).and_then(|e| e.first()) {
+            return first.clone();
+        }
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in all {
+                if let Value::Object(m) = build(part, spec, depth + 1) {
+                    merged.extend(m);
+                }
+            }
+            return Value::Object(merged);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = schema.get(key).and_then(Value::as_array).and_then(|a| a.first()) {
+                return build(first, spec, depth + 1);
+            }
+        }
+        let ty = schema.get("type").and_then(Value::as_str).unwrap_or(if schema.get("properties").is_some() { "object" } else { "string" });
+        match ty {
+            "object" => Value::Object(
+                schema.get("properties").and_then(Value::as_object).into_iter().flatten()
+                    .map(|(k, v)| (k.clone(), build(v, spec, depth + 1)))
+                    .collect(),
+            ),
+            "array" => json!([build(schema.get("items").unwrap_or(&Value::Null), spec, depth + 1)]),
+            "integer" => json!(1),
+            "number" => json!(1.5),
+            "boolean" => json!(true),
+            _ => match schema.get("format").and_then(Value::as_str) {
+                Some("email") => json!("user@example.com"),
+                Some("date-time") => json!("2024-01-01T00:00:00Z"),
+                Some("date") => json!("2024-01-01"),
+                Some("uuid") => json!("00000000-0000-4000-8000-000000000001"),
+                Some("uri") | Some("url") => json!("https://example.com/"),
+                _ => json!("test"),
+            },
+        }
+    }
+    build(schema, spec, 0)
+}
+
+/// Request bodies of all POST/PUT/PATCH operations in an OpenAPI 3 or Swagger 2 spec. `spec_url` is where
+/// the spec was found; relative server URLs and Swagger's `basePath` are resolved against it.
+pub fn seeds_from_openapi(spec: &Value, spec_url: &str) -> Vec<BodySeed> {
+    let Ok(spec_url) = url::Url::parse(spec_url) else { return Vec::new() };
+    let base = spec.get("servers").and_then(Value::as_array).and_then(|s| s.first())
+        .and_then(|s| s.get("url")).and_then(Value::as_str)
+        .or_else(|| spec.get("basePath").and_then(Value::as_str))
+        .and_then(|b| spec_url.join(b).ok())
+        .unwrap_or_else(|| spec_url.join("/").unwrap_or_else(|_| spec_url.clone()));
+    let base = base.as_str().trim_end_matches('/').to_string();
+
+    let mut seeds = Vec::new();
+    let Some(paths) = spec.get("paths").and_then(Value::as_object) else { return seeds };
+    for (path, item) in paths {
+        for method in ["post", "put", "patch"] {
+            let Some(op) = item.get(method) else { continue };
+            // OpenAPI 3: requestBody (possibly a $ref); Swagger 2: a parameter `in: body`
+            let request_body = op.get("requestBody").map(|rb| match rb.get("$ref").and_then(Value::as_str) {
+                Some(r) => r.strip_prefix('#').and_then(|p| spec.pointer(p)).unwrap_or(rb),
+                None => rb,
+            });
+            let schema = request_body
+                .and_then(|rb| rb.get("content"))
+                .and_then(Value::as_object)
+                .and_then(|c| c.iter().find(|(mime, _)| mime.contains("json")).map(|(_, m)| m))
+                .and_then(|m| m.get("example").map(|e| json!({ "example": e })).or_else(|| m.get("schema").cloned()))
+                .or_else(|| {
+                    op.get("parameters").and_then(Value::as_array)?.iter()
+                        .find(|p| p.get("in").and_then(Value::as_str) == Some("body"))
+                        .and_then(|p| p.get("schema").cloned())
+                });
+            let Some(schema) = schema else { continue };
+            let body = example_from_schema(&schema, spec);
+            if !body.is_object() && !body.is_array() {
+                continue;
+            }
+            // Path parameters get a plausible ID
+            let concrete: String = path.split('/')
+                .map(|seg| if seg.starts_with('{') && seg.ends_with('}') { "1" } else { seg })
+                .collect::<Vec<_>>()
+                .join("/");
+            seeds.push(BodySeed { method: method.to_uppercase(), url: format!("{}{}", base, concrete), body, source: SeedSource::Spec });
+        }
+    }
+    seeds
+}
+
+/// The spec URL names an OpenAPI/Swagger document
+pub fn is_spec_url(url: &str) -> bool {
+    let path = url::Url::parse(url).map(|u| u.path().to_ascii_lowercase()).unwrap_or_default();
+    ["openapi.json", "swagger.json", "/api-docs", "/v2/api-docs", "/v3/api-docs", "openapi.yaml", "swagger.yaml"]
+        .iter()
+        .any(|s| path.ends_with(s))
+}
+
+pub struct JsonFuzzer<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+    waf: Option<&'a WafMutators>,
+}
+
+impl<'a> JsonFuzzer<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout, waf: None }
+    }
+
+    /// Resend injection payloads a host's WAF blocks through the transforms recommended for it
+    pub fn with_waf(mut self, waf: &'a WafMutators) -> Self {
+        self.waf = Some(waf);
+        self
+    }
+
+    async fn send(&self, method: &str, url: &str, body: &Value) -> Option<(u16, String)> {
+        self.send_raw(method, url, serde_json::to_string(body).ok()?).await
+    }
+
+    async fn send_raw(&self, method: &str, url: &str, body: String) -> Option<(u16, String)> {
+        let method = reqwest::Method::from_bytes(method.as_bytes()).ok()?;
+        let req = self.http.client().request(method, url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .timeout(self.timeout);
+        let resp = self.http.send(req).await.ok()?;
+        let status = resp.status().as_u16();
+        let (bytes, _) = tokio::time::timeout(self.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some((status, String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Send every mutation of the seed body; one finding per field and indicator
+    pub async fn run(&self, seed: &BodySeed) -> Vec<JsonFuzzFinding> {
+        let Some((base_status, base_body)) = self.send(&seed.method, &seed.url, &seed.body).await else { return Vec::new() };
+        // Without access to the endpoint, every mutation is rejected before the body is read
+        if matches!(base_status, 401 | 403 | 404 | 405) {
+            tracing::debug!("json fuzz: {} {} answers {}, skipped", seed.method, seed.url, base_status);
+            return Vec::new();
+        }
+        let mut findings: Vec<JsonFuzzFinding> = Vec::new();
+        for (field, kind, body) in mutations(&seed.body) {
+            // Injected strings can be transformed; the raw body has the transformed literal in their place
+            let payload = self.waf.and_then(|_| body.pointer(&field)).and_then(Value::as_str).filter(|_| kind.is_injection());
+            let (status, text, waf_bypass) = match (self.waf, payload) {
+                (Some(waf), Some(payload)) => {
+                    let plain = serde_json::to_string(&body).unwrap_or_default();
+                    let literal = serde_json::to_string(payload).unwrap_or_default();
+                    let send = |raw: Option<String>| {
+                        let body = raw.map(|r| plain.replacen(&literal, &r, 1)).unwrap_or_else(|| plain.clone());
+                        self.send_raw(&seed.method, &seed.url, body)
+                    };
+                    let Some(answer) = waf.send(&seed.url, &field, payload, Context::Json, base_status, send).await else { continue };
+                    answer
+                }
+                _ => {
+                    let Some((status, text)) = self.send(&seed.method, &seed.url, &body).await else { continue };
+                    (status, text, None)
+                }
+            };
+            let Some((indicator, evidence)) = classify(kind, (base_status, &base_body), status, &text) else { continue };
+            if findings.iter().any(|f| f.field == field && f.indicator == indicator) {
+                continue;
+            }
+            findings.push(JsonFuzzFinding {
+                method: seed.method.clone(),
+                url: seed.url.clone(),
+                field,
+                mutation: kind,
+                indicator,
+                severity: indicator.severity(),
+                baseline_status: base_status,
+                status,
+                evidence,
+                source: seed.source,
+                waf_bypass,
+            });
+        }
+        findings
+    }
+}
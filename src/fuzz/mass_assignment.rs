@@ -1,16 +1,17 @@
 use crate::http_client::HttpClient;
+use serde::{Deserialize, Serialize};
 use anyhow::Result;
 use serde_json::{json, Value};
 use tokio::time::{timeout, Duration};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MassAssignmentResult {
     pub url: String,
     pub vulnerabilities: Vec<MassAssignmentVuln>,
     pub hidden_params: Vec<HiddenParameter>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MassAssignmentVuln {
     pub vuln_type: String,
     pub severity: String,
@@ -19,7 +20,7 @@ pub struct MassAssignmentVuln {
     pub payload: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HiddenParameter {
     pub name: String,
     pub accepted: bool,
@@ -489,4 +490,54 @@ impl MassAssignmentTester {
 
         Ok(hidden_params)
     }
+
+    /// Test body parameters found by `param_bruteforce`: each is sent with a value a client shouldn't be
+    /// able to set and reported when the response takes it over
+    pub async fn test_discovered(&self, url: &str, method: &str, names: &[String]) -> Result<MassAssignmentResult> {
+        let mut result = MassAssignmentResult {
+            url: url.to_string(),
+            vulnerabilities: Vec::new(),
+            hidden_params: Vec::new(),
+        };
+
+        for name in names {
+            let value = privileged_value(name);
+            let payload = json!({ name.as_str(): value.clone() });
+            let Ok((status, body)) = self.send_request(url, method, &payload).await else { continue };
+            if !(200..300).contains(&status) || !self.check_param_accepted(&body, name, &value) {
+                continue;
+            }
+            result.vulnerabilities.push(MassAssignmentVuln {
+                vuln_type: "Discovered Parameter Injection".to_string(),
+                severity: "MEDIUM".to_string(),
+                description: format!("Undocumented body parameter '{}' (found by parameter brute force) accepted", name),
+                parameter: name.clone(),
+                payload: Some(payload.to_string()),
+            });
+            result.hidden_params.push(HiddenParameter {
+                name: name.clone(),
+                accepted: true,
+                potential_impact: "MEDIUM - Undocumented field writable".to_string(),
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+/// A value for `name` that only a privileged caller should be able to set
+fn privileged_value(name: &str) -> Value {
+    let lower = name.to_ascii_lowercase();
+    if lower.starts_with("is_") || (lower.starts_with("is") && name.chars().nth(2).is_some_and(|c| c.is_ascii_uppercase()))
+        || ["admin", "superuser", "staff", "verified", "active", "enabled", "approved", "confirmed", "internal"].iter().any(|w| lower.contains(w)) {
+        json!(true)
+    } else if ["role", "type", "tier", "plan", "scope"].iter().any(|w| lower.contains(w)) {
+        json!("admin")
+    } else if ["price", "amount", "total", "balance", "credits", "discount", "quantity", "qty"].iter().any(|w| lower.contains(w)) {
+        json!(0)
+    } else if lower.ends_with("id") {
+        json!(1)
+    } else {
+        json!("apihunter")
+    }
 }
@@ -2,3 +2,14 @@ pub mod param_discovery;
 pub mod param_fuzzer;
 pub mod idor_tester;
 pub mod mass_assignment;
+pub mod flows;
+pub mod bola;
+pub mod field_selection;
+pub mod path_bruteforce;
+pub mod param_bruteforce;
+pub mod json_fuzzer;
+pub mod ssti;
+pub mod race;
+pub mod hpp;
+pub mod prototype_pollution;
+pub mod upload;
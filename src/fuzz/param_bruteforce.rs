@@ -0,0 +1,291 @@
+//! Parameter name discovery (Arjun-style)
+//! Sends candidate parameter names in large chunks, each with its own random value, and compares the
+//! response with a baseline request that carries one junk parameter. A chunk that changes the answer
+//! (status, size or content once the echoed names and values are removed, or a reflected value) is split
+//! in halves until the parameters responsible are left; each is confirmed with a second value. A few
+//! hundred names cost a handful of requests instead of one request per name. Found names are handed to
+//! the parameter fuzzer (query) and the mass-assignment tester (JSON body).
+
+use crate::http_client::HttpClient;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+/// Bytes of each body compared
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Simhash distance up to which two (normalized) bodies are the same page
+const MAX_DISTANCE: u32 = 3;
+
+/// Names beyond `param_discovery::common_params`: internal switches, privilege fields and framework
+/// parameters that rarely show up in URLs or responses
+pub const EXTRA_WORDS: &[&str] = &[
+    "admin", "is_admin", "isAdmin", "role", "roles", "user_role", "userRole", "is_staff", "isStaff",
+    "superuser", "is_superuser", "permissions", "privileges", "scope", "scopes", "access_level", "accessLevel",
+    "internal", "is_internal", "test", "testing", "dev", "preview", "draft", "beta", "feature", "features",
+    "flag", "flags", "mode", "env", "environment", "config", "settings", "options", "admin_mode", "adminMode",
+    "show_hidden", "showHidden", "hidden", "all", "deleted", "include_deleted", "includeDeleted", "archived",
+    "raw", "full", "details", "detail", "extended", "view", "template", "layout", "theme", "cache", "nocache",
+    "no_cache", "refresh", "reload", "sync", "async", "dry_run", "dryRun", "preview_mode", "explain",
+    "owner", "owner_id", "ownerId", "tenant", "tenant_id", "tenantId", "org", "org_id", "orgId",
+    "organization", "organization_id", "organizationId", "team", "team_id", "teamId", "workspace", "project",
+    "project_id", "projectId", "parent", "parent_id", "parentId", "ref", "reference", "uuid", "guid", "slug",
+    "price", "amount", "total", "discount", "coupon", "credits", "balance", "quantity", "qty", "plan", "tier",
+    "subscription", "verified", "is_verified", "isVerified", "active", "is_active", "isActive", "enabled",
+    "disabled", "approved", "confirmed", "locked", "banned", "email_verified", "emailVerified",
+    "jsonp", "cb", "_method", "method", "action", "cmd", "command", "exec", "func", "function", "op",
+    "operation", "module", "controller", "handler", "service", "endpoint", "host", "domain", "port", "proxy",
+    "target", "dest", "destination", "continue", "goto", "forward", "back", "site", "origin", "referer",
+    "lang", "l", "p", "t", "u", "c", "d", "f", "k", "n", "r", "x", "y", "z",
+];
+
+/// Where the candidate names are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamLocation {
+    /// GET query string
+    Query,
+    /// Keys of a JSON object POSTed to the endpoint
+    Json,
+}
+
+/// Why a name counts as accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    /// Its value appears in the response
+    Reflected,
+    /// The status changed
+    Status,
+    /// The body size changed
+    Length,
+    /// The body changed at the same size
+    Content,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredParam {
+    pub url: String,
+    pub name: String,
+    pub location: ParamLocation,
+    pub reason: Reason,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParamBruteforceConfig {
+    pub words: Vec<String>,
+    /// Names per request
+    pub chunk_size: usize,
+    pub timeout: Duration,
+}
+
+impl Default for ParamBruteforceConfig {
+    fn default() -> Self {
+        Self { words: default_words(), chunk_size: 128, timeout: Duration::from_secs(10) }
+    }
+}
+
+/// `param_discovery::common_params` plus `EXTRA_WORDS`, without duplicates
+pub fn default_words() -> Vec<String> {
+    let mut seen = HashSet::new();
+    super::param_discovery::common_params().into_iter()
+        .chain(EXTRA_WORDS.iter().copied())
+        .filter(|w| seen.insert(*w))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Names from a wordlist file (one per line, `#` comments)
+pub fn load_wordlist(path: &Path) -> Result<Vec<String>> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read parameter wordlist {}: {}", path.display(), e))?;
+    Ok(data.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty() && !l.starts_with('#')).collect())
+}
+
+/// Random value, distinct enough to be found again in a response
+fn random_value() -> String {
+    format!("ah{:08x}", rand::random::<u32>())
+}
+
+/// One response, reduced to what is compared
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Snapshot {
+    /// Body with the sent names and values removed, so pages that echo the request compare equal
+    pub fn normalized(&self, sent: &[(String, String)]) -> String {
+        let mut body = self.body.clone();
+        for (name, value) in sent {
+            body = body.replace(&format!("{}={}", name, value), "")
+                .replace(&format!("\"{}\":\"{}\"", name, value), "")
+                .replace(&format!("\"{}\": \"{}\"", name, value), "")
+                .replace(value.as_str(), "");
+        }
+        body.replace('&', "")
+    }
+}
+
+/// The baseline and how much it varies between two junk requests
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    pub status: u16,
+    /// Normalized body
+    pub body: String,
+    /// Sizes differed between the two baseline requests
+    pub dynamic_length: bool,
+    /// Content differed beyond `MAX_DISTANCE`
+    pub dynamic_content: bool,
+    /// The endpoint echoes any parameter it gets, so a reflected value says nothing
+    pub reflects: bool,
+}
+
+impl Baseline {
+    pub fn new(first: (&Snapshot, &[(String, String)]), second: (&Snapshot, &[(String, String)])) -> Option<Self> {
+        if first.0.status != second.0.status {
+            return None;
+        }
+        let (a, b) = (first.0.normalized(first.1), second.0.normalized(second.1));
+        let reflects = [first, second].iter().any(|(snap, sent)| sent.iter().any(|(_, v)| snap.body.contains(v.as_str())));
+        Some(Self {
+            status: first.0.status,
+            reflects,
+            dynamic_length: a.len() != b.len(),
+            dynamic_content: !same_content(&a, &b),
+            body: a,
+        })
+    }
+
+    /// How the response to `sent` differs from the baseline, if it does
+    pub fn compare(&self, snap: &Snapshot, sent: &[(String, String)]) -> Option<Reason> {
+        if !self.reflects && sent.iter().any(|(_, v)| snap.body.contains(v.as_str())) {
+            return Some(Reason::Reflected);
+        }
+        if snap.status != self.status {
+            return Some(Reason::Status);
+        }
+        let body = snap.normalized(sent);
+        if !self.dynamic_length && body.len() != self.body.len() {
+            return Some(Reason::Length);
+        }
+        if !self.dynamic_content && !same_content(&body, &self.body) {
+            return Some(Reason::Content);
+        }
+        None
+    }
+}
+
+fn same_content(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (crate::enrich::simhash::simhash(a.as_bytes()), crate::enrich::simhash::simhash(b.as_bytes())) {
+        (Some(x), Some(y)) => crate::enrich::simhash::distance(x, y) <= MAX_DISTANCE,
+        _ => false,
+    }
+}
+
+pub struct ParamBruteforcer<'a> {
+    http: &'a HttpClient,
+    config: ParamBruteforceConfig,
+}
+
+impl<'a> ParamBruteforcer<'a> {
+    pub fn new(http: &'a HttpClient, config: ParamBruteforceConfig) -> Self {
+        Self { http, config }
+    }
+
+    /// Send `names` (each with a fresh random value) and return the response with what was sent
+    async fn send(&self, url: &str, location: ParamLocation, names: &[String]) -> Option<(Snapshot, Vec<(String, String)>)> {
+        let sent: Vec<(String, String)> = names.iter().map(|n| (n.clone(), random_value())).collect();
+        let client = self.http.client();
+        let req = match location {
+            ParamLocation::Query => {
+                let mut u = url::Url::parse(url).ok()?;
+                u.query_pairs_mut().extend_pairs(sent.iter());
+                client.get(u)
+            }
+            ParamLocation::Json => {
+                let body: serde_json::Map<String, serde_json::Value> = sent.iter().map(|(n, v)| (n.clone(), serde_json::Value::String(v.clone()))).collect();
+                client.post(url).json(&body)
+            }
+        };
+        let resp = self.http.send(req.timeout(self.config.timeout)).await.ok()?;
+        let status = resp.status().as_u16();
+        let (body, _) = tokio::time::timeout(self.config.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some((Snapshot { status, body: String::from_utf8_lossy(&body).into_owned() }, sent))
+    }
+
+    async fn baseline(&self, url: &str, location: ParamLocation) -> Option<Baseline> {
+        // One junk name each; the two answers show how much the endpoint varies by itself
+        let (a, sent_a) = self.send(url, location, &[random_value()]).await?;
+        let (b, sent_b) = self.send(url, location, &[random_value()]).await?;
+        Baseline::new((&a, &sent_a), (&b, &sent_b))
+    }
+
+    /// Names among `chunk` that change the response; halves are tested until single names remain
+    async fn narrow(&self, url: &str, location: ParamLocation, baseline: &Baseline, chunk: Vec<String>) -> Vec<(String, Reason)> {
+        let mut found = Vec::new();
+        let mut stack = vec![chunk];
+        while let Some(names) = stack.pop() {
+            let Some((snap, sent)) = self.send(url, location, &names).await else { continue };
+            // Too many parameters for the server: split without judging
+            if matches!(snap.status, 413 | 414 | 431) && names.len() > 1 && baseline.status != snap.status {
+                let (left, right) = names.split_at(names.len() / 2);
+                stack.push(right.to_vec());
+                stack.push(left.to_vec());
+                continue;
+            }
+            let Some(reason) = baseline.compare(&snap, &sent) else { continue };
+            if reason == Reason::Reflected {
+                // The reflected names are known right away; the rest of the chunk is checked again
+                let (reflected, rest): (Vec<_>, Vec<_>) = sent.into_iter().partition(|(_, v)| snap.body.contains(v.as_str()));
+                found.extend(reflected.into_iter().map(|(n, _)| (n, Reason::Reflected)));
+                if !rest.is_empty() {
+                    stack.push(rest.into_iter().map(|(n, _)| n).collect());
+                }
+            } else if names.len() == 1 {
+                // Confirmed with another value, so one-off noise isn't reported
+                if let Some((snap, sent)) = self.send(url, location, &names).await {
+                    if baseline.compare(&snap, &sent).is_some() {
+                        found.push((names[0].clone(), reason));
+                    }
+                }
+            } else {
+                let (left, right) = names.split_at(names.len() / 2);
+                stack.push(right.to_vec());
+                stack.push(left.to_vec());
+            }
+        }
+        found
+    }
+
+    /// Discover the parameters `url` accepts at `location`
+    pub async fn run(&self, url: &str, location: ParamLocation) -> Vec<DiscoveredParam> {
+        let Some(baseline) = self.baseline(url, location).await else {
+            tracing::debug!("param bruteforce: {} has no stable baseline", url);
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        let words: Vec<String> = self.config.words.iter().filter(|w| seen.insert(w.as_str())).cloned().collect();
+        let mut found = Vec::new();
+        for chunk in words.chunks(self.config.chunk_size.max(1)) {
+            found.extend(self.narrow(url, location, &baseline, chunk.to_vec()).await);
+        }
+        // An endpoint where most names "change" the answer just varies; nothing reliable to report
+        if found.len() > 10 && found.len() * 4 > words.len() {
+            tracing::warn!("param bruteforce: {} of {} names changed {}, results discarded as unreliable", found.len(), words.len(), url);
+            return Vec::new();
+        }
+        let mut params: Vec<DiscoveredParam> = found.into_iter()
+            .map(|(name, reason)| DiscoveredParam { url: url.to_string(), name, location, reason })
+            .collect();
+        params.sort_by(|a, b| a.name.cmp(&b.name));
+        params.dedup_by(|a, b| a.name == b.name);
+        params
+    }
+}
@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use anyhow::Result;
 use reqwest::Client;
 use url::Url;
@@ -71,7 +72,7 @@ pub async fn fuzz_parameter(
         let test_url = build_url_with_param(base_url, param_name, test_value)?;
         
         let start = std::time::Instant::now();
-        match client.get(&test_url).send().await {
+        match client.get(&test_url).send_paced().await {
             Ok(resp) => {
                 let status = resp.status().as_u16();
                 let content_type = resp.headers()
@@ -227,7 +228,7 @@ pub async fn smart_fuzz_parameter(
     // Get baseline if current value exists
     let (baseline_status, baseline_size) = if let Some(val) = current_value {
         let baseline_url = build_url_with_param(base_url, param_name, val)?;
-        if let Ok(resp) = client.get(&baseline_url).send().await {
+        if let Ok(resp) = client.get(&baseline_url).send_paced().await {
             let status = resp.status().as_u16();
             let size = resp.bytes().await.map(|b| b.len()).unwrap_or(0);
             (Some(status), Some(size))
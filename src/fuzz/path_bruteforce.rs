@@ -0,0 +1,206 @@
+//! Path brute force (`--aggressive`)
+//! Requests wordlist paths (optionally with extensions) below every probed host and reports the ones
+//! that exist, recursing into directories that are found. Each directory's not-found page is learned
+//! first (a random name in it), so catch-all routes and WAF block pages aren't reported as hits; the
+//! requests go through the scan's `HttpClient`, i.e. the per-host throttle, `--rps` and retries.
+
+use crate::http_client::HttpClient;
+use crate::probe::soft404::{self, Baseline};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+/// Paths commonly found on API hosts
+pub const DEFAULT_WORDS: &[&str] = &[
+    "api", "apis", "rest", "v1", "v2", "v3", "graphql", "graphiql", "gql", "rpc", "jsonrpc", "soap", "ws",
+    "swagger", "swagger-ui", "swagger.json", "swagger.yaml", "openapi", "openapi.json", "openapi.yaml",
+    "api-docs", "docs", "redoc", "spec", "schema", "wsdl",
+    "admin", "administrator", "internal", "private", "debug", "dev", "test", "staging", "beta", "legacy",
+    "console", "dashboard", "manage", "management", "system", "config", "settings",
+    "actuator", "health", "healthz", "status", "metrics", "prometheus", "info", "env", "version", "ping",
+    "auth", "oauth", "oauth2", "login", "logout", "register", "signup", "token", "tokens", "session", "sso",
+    "user", "users", "me", "account", "accounts", "profile", "customers", "orders", "payments", "invoices",
+    "products", "items", "search", "export", "import", "upload", "uploads", "files", "download", "reports",
+    "webhook", "webhooks", "callback", "notifications", "events", "jobs", "tasks", "logs", "audit",
+    "backup", "backups", "old", "tmp", "mobile", "app", "public", "static", "assets",
+];
+
+/// Statuses taken as "exists" (as ffuf's default matcher)
+const MATCH_STATUS: &[u16] = &[200, 201, 202, 203, 204, 301, 302, 307, 308, 401, 403, 405, 500];
+
+/// Bytes of each body read for the soft-404 comparison
+const BODY_LIMIT: usize = soft404::SAMPLE_LIMIT;
+
+#[derive(Debug, Clone)]
+pub struct BruteforceConfig {
+    pub words: Vec<String>,
+    /// Tried in addition to the bare word, without the dot (`json`, `bak`)
+    pub extensions: Vec<String>,
+    /// Directory levels below the start path (1 = no recursion)
+    pub depth: usize,
+    /// Requests per start URL, recursion included
+    pub max_requests: usize,
+    pub concurrency: usize,
+    pub timeout: Duration,
+}
+
+impl Default for BruteforceConfig {
+    fn default() -> Self {
+        Self {
+            words: DEFAULT_WORDS.iter().map(|w| w.to_string()).collect(),
+            extensions: Vec::new(),
+            depth: 2,
+            max_requests: 5000,
+            concurrency: 10,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathHit {
+    pub url: String,
+    pub status: u16,
+    /// Bytes read (at most the soft-404 sample size)
+    pub length: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Where a redirect pointed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Directory level the path was found on (1 = directly below the start URL)
+    pub depth: usize,
+    /// Looks like a directory (redirects to `<path>/`, or answers without a file extension), so it
+    /// was recursed into unless the depth limit was reached
+    pub directory: bool,
+}
+
+/// Words from a wordlist file (one per line, `#` comments, leading `/` removed)
+pub fn load_wordlist(path: &Path) -> Result<Vec<String>> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read path wordlist {}: {}", path.display(), e))?;
+    Ok(data.lines()
+        .map(|l| l.trim().trim_start_matches('/').to_string())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect())
+}
+
+/// Paths to request in a directory: every word, and every word with each extension unless it has one
+pub fn paths(words: &[String], extensions: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for word in words {
+        let word = word.trim().trim_start_matches('/');
+        if word.is_empty() || word.contains(char::is_whitespace) {
+            continue;
+        }
+        let has_extension = word.rsplit('/').next().is_some_and(|last| last.contains('.'));
+        let variants = std::iter::once(word.to_string()).chain(
+            extensions.iter()
+                .filter(|_| !has_extension)
+                .map(|e| format!("{}.{}", word, e.trim().trim_start_matches('.'))),
+        );
+        for p in variants {
+            if seen.insert(p.clone()) {
+                out.push(p);
+            }
+        }
+    }
+    out
+}
+
+/// `url` with exactly one trailing slash
+fn as_directory(url: &str) -> String {
+    format!("{}/", url.trim_end_matches('/'))
+}
+
+pub struct PathBruteforcer<'a> {
+    http: &'a HttpClient,
+    config: BruteforceConfig,
+}
+
+impl<'a> PathBruteforcer<'a> {
+    pub fn new(http: &'a HttpClient, config: BruteforceConfig) -> Self {
+        Self { http, config }
+    }
+
+    async fn fetch(&self, url: &str) -> Option<(reqwest::Url, u16, Option<String>, Option<String>, Vec<u8>, bool)> {
+        let req = self.http.client().get(url).timeout(self.config.timeout);
+        let resp = self.http.send(req).await.ok()?;
+        let final_url = resp.url().clone();
+        let status = resp.status().as_u16();
+        let header = |name: reqwest::header::HeaderName| resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let location = header(reqwest::header::LOCATION);
+        let (body, truncated) = tokio::time::timeout(self.config.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some((final_url, status, content_type, location, body, truncated))
+    }
+
+    /// Check one path against the directory's not-found page
+    async fn check(&self, url: String, depth: usize, baseline: Option<&Baseline>) -> Option<PathHit> {
+        let (final_url, status, content_type, location, body, truncated) = self.fetch(&url).await?;
+        if !MATCH_STATUS.contains(&status) {
+            return None;
+        }
+        let content_length = Some(body.len() as u64).filter(|_| !truncated);
+        let resp = soft404::Response { status, content_type: content_type.as_deref(), content_length, body: Some((&body, truncated)) };
+        if baseline.is_some_and(|b| b.matches(&url, &resp)) {
+            return None;
+        }
+        // `/admin` -> `/admin/`, whether the client followed the redirect or not
+        let slash = as_directory(&url);
+        let redirected_to_slash = final_url.as_str() == slash
+            || location.as_deref().is_some_and(|l| l == slash || (l.ends_with('/') && slash.ends_with(l)));
+        let has_extension = url.rsplit('/').next().is_some_and(|last| last.contains('.'));
+        let directory = redirected_to_slash || (!has_extension && matches!(status, 200..=299 | 401 | 403));
+        Some(PathHit {
+            url,
+            status,
+            length: body.len(),
+            content_type: content_type.map(|ct| ct.split(';').next().unwrap_or_default().trim().to_string()),
+            location,
+            depth,
+            directory,
+        })
+    }
+
+    /// Brute-force below `start` (a host or directory URL), recursing into found directories
+    pub async fn run(&self, start: &str) -> Vec<PathHit> {
+        let candidates = paths(&self.config.words, &self.config.extensions);
+        let mut hits: Vec<PathHit> = Vec::new();
+        let mut budget = self.config.max_requests;
+        let mut queue = vec![as_directory(start)];
+        let mut visited = HashSet::new();
+        for depth in 1..=self.config.depth.max(1) {
+            let mut next = Vec::new();
+            for dir in std::mem::take(&mut queue) {
+                if budget == 0 || !visited.insert(dir.clone()) {
+                    continue;
+                }
+                let baseline = soft404::learn(self.http.client(), &dir, self.config.timeout).await;
+                let urls: Vec<String> = candidates.iter().take(budget).map(|p| format!("{}{}", dir, p)).collect();
+                budget -= urls.len();
+                let requested = urls.len();
+                let found: Vec<PathHit> = stream::iter(urls)
+                    .map(|url| self.check(url, depth, baseline.as_ref()))
+                    .buffer_unordered(self.config.concurrency.max(1))
+                    .filter_map(|h| async move { h })
+                    .collect()
+                    .await;
+                // A directory where most words "exist" answers everything; its hits are noise
+                if found.len() > 20 && found.len() * 2 > requested {
+                    tracing::warn!("path bruteforce: {} of {} paths in {} answered, results discarded as unreliable", found.len(), requested, dir);
+                    continue;
+                }
+                next.extend(found.iter().filter(|h| h.directory).map(|h| as_directory(&h.url)));
+                hits.extend(found);
+            }
+            queue = next;
+        }
+        hits.sort_by(|a, b| a.url.cmp(&b.url));
+        hits
+    }
+}
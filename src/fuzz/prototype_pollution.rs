@@ -0,0 +1,222 @@
+//! Server-side prototype pollution
+//! JSON bodies are sent again with a `__proto__` (or `constructor.prototype`) object next to the real
+//! fields. A Node.js back end that deep-merges request bodies into plain objects writes those properties
+//! onto `Object.prototype`, where every object of the process inherits them. Three signals, from strongest:
+//! - JSON spaces: `{"json spaces": 10}` makes Express indent every later `res.json` response; the
+//!   setting is put back to 0 afterwards
+//! - Reflection: a canary property comes back as a plain property of the echoed object, without the
+//!   `__proto__` key it was sent under
+//! - Behavior: the payload changes the status while the same body under a harmless key doesn't
+//!
+//! Findings carry the framework fingerprint of the endpoint's response headers when there is one.
+
+use crate::analyze::fingerprint::TechnologyFingerprint;
+use crate::http_client::HttpClient;
+use crate::output::clean_reporter::Severity;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Bytes of each response body inspected
+const BODY_LIMIT: usize = 32 * 1024;
+
+/// Indentation the JSON spaces probe asks for
+const JSON_SPACES: usize = 10;
+
+/// Where the polluting properties are nested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Vector {
+    /// `{"__proto__": {...}}`
+    Proto,
+    /// `{"constructor": {"prototype": {...}}}` - for merges that skip `__proto__`
+    ConstructorPrototype,
+}
+
+pub const ALL_VECTORS: [Vector; 2] = [Vector::Proto, Vector::ConstructorPrototype];
+
+impl Vector {
+    /// `body` with `properties` added under this vector; `None` unless `body` is an object
+    pub fn inject(&self, body: &Value, properties: Value) -> Option<Value> {
+        let mut out = body.as_object()?.clone();
+        match self {
+            Self::Proto => out.insert("__proto__".to_string(), properties),
+            Self::ConstructorPrototype => out.insert("constructor".to_string(), json!({ "prototype": properties })),
+        };
+        Some(Value::Object(out))
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Proto => "__proto__",
+            Self::ConstructorPrototype => "constructor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Technique {
+    /// Later JSON responses came back indented
+    JsonSpaces,
+    /// The canary property was echoed as a plain property
+    Reflection,
+    /// The payload changed the status, a harmless key didn't
+    Behavior,
+}
+
+impl Technique {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::JsonSpaces => Severity::High,
+            Self::Reflection => Severity::Medium,
+            Self::Behavior => Severity::Low,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrototypePollutionFinding {
+    pub method: String,
+    pub url: String,
+    pub vector: Vector,
+    pub technique: Technique,
+    pub severity: Severity,
+    pub payload: Value,
+    pub evidence: String,
+    /// Framework fingerprint of the endpoint, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+}
+
+/// Framework (or language, or server) from response headers; Express's weak ETag format counts as Node.js
+pub fn framework_hint(headers: &BTreeMap<String, String>) -> Option<String> {
+    let lower: HashMap<String, String> = headers.iter().map(|(k, v)| (k.to_ascii_lowercase(), v.clone())).collect();
+    let fp = TechnologyFingerprint::analyze(&lower, "");
+    if !fp.framework.is_empty() {
+        return Some(fp.framework.join(", "));
+    }
+    let express_etag = lower.get("etag").is_some_and(|e| {
+        e.strip_prefix("W/\"").and_then(|rest| rest.split_once('-')).is_some_and(|(len, _)| !len.is_empty() && len.chars().all(|c| c.is_ascii_hexdigit()))
+    });
+    if express_etag {
+        return Some("Node.js".to_string());
+    }
+    fp.language.or(fp.server)
+}
+
+/// Lines of `body` indented by exactly `spaces` before a key or value
+pub fn indented(body: &str, spaces: usize) -> bool {
+    let prefix = format!("\n{}", " ".repeat(spaces));
+    body.match_indices(&prefix).any(|(i, _)| {
+        body[i + prefix.len()..].chars().next().is_some_and(|c| c != ' ')
+    })
+}
+
+/// Whether `body` has `name: value` as a property, while the `vector` key is gone
+pub fn reflects(body: &str, vector: Vector, name: &str, value: &str) -> bool {
+    let Ok(json) = serde_json::from_str::<Value>(body) else { return false };
+    fn walk(v: &Value, key: &str, name: &str, value: &str) -> (bool, bool) {
+        match v {
+            Value::Object(map) => {
+                let mut found = map.get(name).and_then(Value::as_str) == Some(value);
+                let mut key_seen = map.contains_key(key);
+                for child in map.values() {
+                    let (f, k) = walk(child, key, name, value);
+                    found |= f;
+                    key_seen |= k;
+                }
+                (found, key_seen)
+            }
+            Value::Array(items) => items.iter().map(|i| walk(i, key, name, value)).fold((false, false), |a, b| (a.0 | b.0, a.1 | b.1)),
+            _ => (false, false),
+        }
+    }
+    let (found, key_seen) = walk(&json, vector.key(), name, value);
+    found && !key_seen
+}
+
+pub struct PrototypePollutionTester<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+}
+
+impl<'a> PrototypePollutionTester<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout }
+    }
+
+    async fn send(&self, method: &str, url: &str, body: &Value) -> Option<(u16, String)> {
+        let method = reqwest::Method::from_bytes(method.as_bytes()).ok()?;
+        let req = self.http.client().request(method, url).json(body).timeout(self.timeout);
+        let resp = self.http.send(req).await.ok()?;
+        let status = resp.status().as_u16();
+        let (bytes, _) = tokio::time::timeout(self.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some((status, String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Test one JSON endpoint with a body it accepts; at most one finding, the strongest signal
+    pub async fn test(&self, method: &str, url: &str, body: &Value, framework: Option<&str>) -> Option<PrototypePollutionFinding> {
+        let (base_status, base_body) = self.send(method, url, body).await?;
+        if matches!(base_status, 401 | 403 | 404 | 405) {
+            return None;
+        }
+        let finding = |vector, technique: Technique, payload, evidence| PrototypePollutionFinding {
+            method: method.to_string(),
+            url: url.to_string(),
+            vector,
+            technique,
+            severity: technique.severity(),
+            payload,
+            evidence,
+            framework: framework.map(str::to_string),
+        };
+
+        // JSON spaces: the baseline must not already be indented that way
+        if !indented(&base_body, JSON_SPACES) {
+            for vector in ALL_VECTORS {
+                let payload = vector.inject(body, json!({ "json spaces": JSON_SPACES }))?;
+                self.send(method, url, &payload).await;
+                let after = self.send(method, url, body).await;
+                let hit = after.as_ref().is_some_and(|(_, b)| indented(b, JSON_SPACES));
+                if hit {
+                    // Back to compact output for everyone else
+                    if let Some(reset) = vector.inject(body, json!({ "json spaces": 0 })) {
+                        self.send(method, url, &reset).await;
+                    }
+                    let evidence = format!("responses indented by {} spaces after the payload, compact before", JSON_SPACES);
+                    return Some(finding(vector, Technique::JsonSpaces, payload, evidence));
+                }
+            }
+        }
+
+        // Reflection of a canary property
+        let name = format!("ah{:06x}", rand::random::<u32>() & 0xffffff);
+        let value = format!("polluted{:06x}", rand::random::<u32>() & 0xffffff);
+        for vector in ALL_VECTORS {
+            let payload = vector.inject(body, json!({ name.clone(): value.clone() }))?;
+            let Some((_, text)) = self.send(method, url, &payload).await else { continue };
+            if reflects(&text, vector, &name, &value) {
+                let evidence = format!("{}: {} echoed as a plain property, {} key gone", name, value, vector.key());
+                return Some(finding(vector, Technique::Reflection, payload, evidence));
+            }
+        }
+
+        // Behavior: compared with the same properties under a key nothing treats specially
+        let control_key = format!("ah{:06x}", rand::random::<u32>() & 0xffffff);
+        for vector in ALL_VECTORS {
+            let properties = json!({ "toString": name.clone(), "valueOf": name.clone() });
+            let payload = vector.inject(body, properties.clone())?;
+            let mut control = body.as_object()?.clone();
+            control.insert(control_key.clone(), properties);
+            let Some((status, _)) = self.send(method, url, &payload).await else { continue };
+            let Some((control_status, _)) = self.send(method, url, &Value::Object(control)).await else { continue };
+            if status != base_status && control_status == base_status && (status >= 500 || base_status >= 400) {
+                let evidence = format!("{} with the payload, {} without and with the same properties under another key", status, base_status);
+                return Some(finding(vector, Technique::Behavior, payload, evidence));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,229 @@
+//! Race-condition testing (`apihunter race`)
+//! The same state-changing request (redeem a coupon, transfer a balance) is sent N times at once. Each
+//! copy gets its own connection and is written except for its last byte; once all are in place, the last
+//! bytes go out together, so the server receives the requests within a few milliseconds ("last-byte
+//! sync") regardless of connection setup and network jitter. An endpoint that lets more requests succeed
+//! than it should (e.g. one redemption) checks and updates its state non-atomically.
+//! Requests are written by hand over raw sockets and are not paced by `--rps`; sending them together is
+//! the point.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Bytes of each response kept for the success check
+const RESPONSE_LIMIT: usize = 64 * 1024;
+
+/// Time for the partial requests to reach the server before the last bytes are sent
+const SETTLE: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct RaceRequest {
+    pub method: String,
+    pub url: String,
+    /// Headers on top of the scan-wide ones (`--identify`, `-H`, credentials), replacing those of the same
+    /// name; Host, Content-Length and Connection are set here
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl RaceRequest {
+    /// The request as written to the socket, recorded for the identification audit
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let url = url::Url::parse(&self.url).with_context(|| format!("invalid URL: {}", self.url))?;
+        let host = url.host_str().context("URL without host")?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let path = match url.query() {
+            Some(q) => format!("{}?{}", url.path(), q),
+            None => url.path().to_string(),
+        };
+        let mut out = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", self.method.to_ascii_uppercase(), path, host);
+        let mut headers: Vec<(String, String)> = crate::identify::record("fuzz::race").iter()
+            .filter(|(name, _)| !self.headers.iter().any(|(n, _)| n.eq_ignore_ascii_case(name.as_str())))
+            .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or_default().to_string()))
+            .collect();
+        headers.extend(self.headers.iter().cloned());
+        let reserved = ["host", "content-length", "connection", "transfer-encoding"];
+        for (name, value) in headers.iter().filter(|(n, _)| !reserved.contains(&n.to_ascii_lowercase().as_str())) {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if !headers.iter().any(|(n, _)| n.eq_ignore_ascii_case("user-agent")) {
+            out.push_str(&format!("User-Agent: {}\r\n", crate::tls_profile::user_agent_or("api-hunter/0.1")));
+        }
+        let body = self.body.as_deref().unwrap_or_default();
+        if !body.is_empty() || !matches!(self.method.to_ascii_uppercase().as_str(), "GET" | "HEAD" | "DELETE") {
+            out.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        out.push_str("Connection: close\r\n\r\n");
+        out.push_str(body);
+        Ok(out.into_bytes())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RaceConfig {
+    /// Parallel copies of the request
+    pub count: usize,
+    pub timeout: Duration,
+    /// Status codes that mean success; empty: any 2xx
+    pub success_status: Vec<u16>,
+    /// Body text that must also be present for success
+    pub success_match: Option<String>,
+    /// Successes the endpoint is meant to allow
+    pub expected: usize,
+}
+
+impl Default for RaceConfig {
+    fn default() -> Self {
+        Self { count: 20, timeout: Duration::from_secs(15), success_status: Vec::new(), success_match: None, expected: 1 }
+    }
+}
+
+impl RaceConfig {
+    pub fn is_success(&self, status: u16, body: &str) -> bool {
+        let status_ok = if self.success_status.is_empty() { (200..300).contains(&status) } else { self.success_status.contains(&status) };
+        status_ok && self.success_match.as_deref().is_none_or(|m| body.contains(m))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceResponse {
+    pub status: u16,
+    pub success: bool,
+    pub body_len: usize,
+    /// From the last byte sent to the end of the response
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceResult {
+    pub method: String,
+    pub url: String,
+    pub sent: usize,
+    pub successes: usize,
+    pub expected: usize,
+    /// Requests without a response (connection failed, timed out)
+    pub failed: usize,
+    pub status_counts: BTreeMap<u16, usize>,
+    /// Time between the first and the last final byte written
+    pub sync_spread_ms: f64,
+    pub vulnerable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    pub responses: Vec<RaceResponse>,
+}
+
+/// Status line and body of a raw response
+pub fn parse_response(raw: &[u8]) -> Option<(u16, String)> {
+    let text = String::from_utf8_lossy(raw);
+    let status = text.split_whitespace().nth(1)?.parse().ok()?;
+    let body = text.split_once("\r\n\r\n").map(|(_, b)| b.to_string()).unwrap_or_default();
+    Some((status, body))
+}
+
+/// Fire `config.count` copies of `request` with last-byte sync
+pub async fn run(request: &RaceRequest, config: &RaceConfig) -> Result<RaceResult> {
+    let url = url::Url::parse(&request.url).with_context(|| format!("invalid URL: {}", request.url))?;
+    let https = match url.scheme() {
+        "https" => true,
+        "http" => false,
+        other => bail!("unsupported scheme: {}", other),
+    };
+    let host = url.host_str().context("URL without host")?.to_string();
+    let port = url.port_or_known_default().context("URL without port")?;
+    let bytes = request.to_bytes()?;
+    let (head, last) = bytes.split_at(bytes.len() - 1);
+    let count = config.count.max(2);
+
+    // All connections first, then everything but the last byte
+    let connections = futures::future::join_all((0..count).map(|_| crate::net::connect_raw(&host, port, https, config.timeout))).await;
+    let mut streams = Vec::new();
+    for mut stream in connections.into_iter().flatten() {
+        if stream.write_all(head).await.is_ok() && stream.flush().await.is_ok() {
+            streams.push(stream);
+        }
+    }
+    if streams.is_empty() {
+        bail!("could not connect to {}:{}", host, port);
+    }
+    tokio::time::sleep(SETTLE).await;
+
+    let barrier = Arc::new(tokio::sync::Barrier::new(streams.len()));
+    let tasks: Vec<_> = streams
+        .into_iter()
+        .map(|mut stream| {
+            let barrier = barrier.clone();
+            let last = last.to_vec();
+            let timeout = config.timeout;
+            tokio::spawn(async move {
+                barrier.wait().await;
+                let sent_at = Instant::now();
+                stream.write_all(&last).await.ok()?;
+                stream.flush().await.ok()?;
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 8192];
+                let _ = tokio::time::timeout(timeout, async {
+                    while raw.len() < RESPONSE_LIMIT {
+                        match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => break,
+                            Ok(n) => raw.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                })
+                .await;
+                Some((sent_at, sent_at.elapsed(), raw))
+            })
+        })
+        .collect();
+
+    let mut responses = Vec::new();
+    let mut sent_times = Vec::new();
+    let mut failed = count - tasks.len();
+    for task in tasks {
+        match task.await.ok().flatten() {
+            Some((sent_at, elapsed, raw)) => {
+                sent_times.push(sent_at);
+                match parse_response(&raw) {
+                    Some((status, body)) => responses.push(RaceResponse {
+                        status,
+                        success: config.is_success(status, &body),
+                        body_len: body.len(),
+                        elapsed_ms: elapsed.as_millis() as u64,
+                    }),
+                    None => failed += 1,
+                }
+            }
+            None => failed += 1,
+        }
+    }
+
+    let spread = match (sent_times.iter().min(), sent_times.iter().max()) {
+        (Some(first), Some(last)) => last.duration_since(*first).as_secs_f64() * 1000.0,
+        _ => 0.0,
+    };
+    let mut status_counts = BTreeMap::new();
+    for r in &responses {
+        *status_counts.entry(r.status).or_insert(0) += 1;
+    }
+    let successes = responses.iter().filter(|r| r.success).count();
+    let vulnerable = successes > config.expected;
+    Ok(RaceResult {
+        method: request.method.to_ascii_uppercase(),
+        url: request.url.clone(),
+        sent: count,
+        successes,
+        expected: config.expected,
+        failed,
+        status_counts,
+        sync_spread_ms: spread,
+        vulnerable,
+        severity: vulnerable.then(|| "high".to_string()),
+        responses,
+    })
+}
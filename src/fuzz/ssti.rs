@@ -0,0 +1,242 @@
+//! Server-Side Template Injection
+//! Sends a polyglot of arithmetic expressions in the common template syntaxes (`{{a*b}}`, `${a*b}`,
+//! `<%= a*b %>`, `#{a*b}`) as a parameter value, each syntax with its own operands, and looks for the
+//! products in the response. A syntax whose product shows up (and wasn't in the baseline) is sent again
+//! alone with new operands to confirm, then engine-specific expressions tell the engines apart:
+//! `{{7*'7'}}` is `7777777` in Jinja2 and `49` in Twig, `<%= %>` is ERB, `${}` Mako or FreeMarker.
+//! Confirmed findings are scored by `RiskClassifier::classify_ssti`.
+
+use crate::analyze::risk_classifier::{RiskClassification, RiskClassifier};
+use crate::fuzz::param_bruteforce::ParamLocation;
+use crate::http_client::HttpClient;
+use crate::waf::bypass::BypassTechnique;
+use crate::waf::mutation::{with_raw_param, Context, WafMutators};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+/// Bytes of each response body searched
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Expression syntaxes in the polyglot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Syntax {
+    /// `{{ }}` - Jinja2, Twig, Nunjucks, Handlebars (no arithmetic)
+    Curly,
+    /// `${ }` - Mako, FreeMarker, Thymeleaf, JS template literals
+    Dollar,
+    /// `<%= %>` - ERB, EJS
+    Erb,
+    /// `#{ }` - Ruby interpolation (Slim, Haml), Pug
+    Hash,
+}
+
+pub const ALL_SYNTAXES: [Syntax; 4] = [Syntax::Curly, Syntax::Dollar, Syntax::Erb, Syntax::Hash];
+
+impl Syntax {
+    /// `expr` in this syntax
+    pub fn wrap(&self, expr: &str) -> String {
+        match self {
+            Self::Curly => format!("{{{{{}}}}}", expr),
+            Self::Dollar => format!("${{{}}}", expr),
+            Self::Erb => format!("<%= {} %>", expr),
+            Self::Hash => format!("#{{{}}}", expr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemplateEngine {
+    Jinja2,
+    Twig,
+    Erb,
+    Mako,
+    FreeMarker,
+    /// Evaluates expressions, but none of the fingerprints matched
+    Unknown,
+}
+
+impl std::fmt::Display for TemplateEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Jinja2 => "Jinja2",
+            Self::Twig => "Twig",
+            Self::Erb => "ERB",
+            Self::Mako => "Mako",
+            Self::FreeMarker => "FreeMarker",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+/// Engine fingerprints per syntax: expression and the output that identifies the engine
+pub fn fingerprints(syntax: Syntax) -> &'static [(&'static str, &'static str, TemplateEngine)] {
+    match syntax {
+        Syntax::Curly => &[("7*'7'", "7777777", TemplateEngine::Jinja2), ("7*'7'", "49", TemplateEngine::Twig)],
+        Syntax::Dollar => &[("'z'.join('ab')", "azb", TemplateEngine::Mako), ("'ab'?upper_case", "AB", TemplateEngine::FreeMarker)],
+        Syntax::Erb => &[("'ab'.upcase", "AB", TemplateEngine::Erb)],
+        Syntax::Hash => &[],
+    }
+}
+
+/// A multiplication whose product doesn't occur by chance: (expression, product)
+pub fn arithmetic() -> (String, String) {
+    let mut rng = rand::thread_rng();
+    let (a, b): (u32, u32) = (rng.gen_range(1000..10000), rng.gen_range(1000..10000));
+    (format!("{}*{}", a, b), (a as u64 * b as u64).to_string())
+}
+
+/// The polyglot: every syntax with its own expression, and the product each one should produce
+pub fn polyglot() -> (String, Vec<(Syntax, String)>) {
+    let mut payload = String::new();
+    let mut expected = Vec::new();
+    for syntax in ALL_SYNTAXES {
+        let (expr, product) = arithmetic();
+        payload.push_str(&syntax.wrap(&expr));
+        expected.push((syntax, product));
+    }
+    (payload, expected)
+}
+
+/// Syntaxes whose product is in `body` but not in the baseline
+pub fn evaluated(expected: &[(Syntax, String)], baseline: &str, body: &str) -> Vec<Syntax> {
+    expected.iter()
+        .filter(|(_, product)| body.contains(product.as_str()) && !baseline.contains(product.as_str()))
+        .map(|(syntax, _)| *syntax)
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SstiFinding {
+    pub url: String,
+    pub parameter: String,
+    pub location: ParamLocation,
+    pub syntax: Syntax,
+    pub engine: TemplateEngine,
+    /// The confirming payload
+    pub payload: String,
+    /// What the payload evaluated to
+    pub evidence: String,
+    pub classification: RiskClassification,
+    /// Transform that got the confirming payload past the host's WAF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf_bypass: Option<BypassTechnique>,
+}
+
+pub struct SstiTester<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+    waf: Option<&'a WafMutators>,
+}
+
+impl<'a> SstiTester<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout, waf: None }
+    }
+
+    /// Resend payloads a host's WAF blocks through the transforms recommended for it
+    pub fn with_waf(mut self, waf: &'a WafMutators) -> Self {
+        self.waf = Some(waf);
+        self
+    }
+
+    /// Send `value` as the parameter: replaced in the query string, or as the only key of a POSTed JSON object
+    async fn send(&self, url: &str, name: &str, location: ParamLocation, value: &str) -> Option<(u16, String)> {
+        let req = match location {
+            ParamLocation::Query => self.http.client().get(crate::fuzz::field_selection::with_param(url, name, value)),
+            ParamLocation::Json => self.http.client().post(url).json(&json!({ name: value })),
+        };
+        self.fetch(req).await
+    }
+
+    /// Send an already encoded value (a transformed payload): raw query value or raw JSON literal
+    async fn send_raw(&self, url: &str, name: &str, location: ParamLocation, raw: &str) -> Option<(u16, String)> {
+        let req = match location {
+            ParamLocation::Query => self.http.client().get(with_raw_param(url, name, raw)),
+            ParamLocation::Json => self.http.client().post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(format!("{{{}: {}}}", serde_json::to_string(name).ok()?, raw)),
+        };
+        self.fetch(req).await
+    }
+
+    async fn fetch(&self, req: reqwest::RequestBuilder) -> Option<(u16, String)> {
+        let resp = self.http.send(req.timeout(self.timeout)).await.ok()?;
+        let status = resp.status().as_u16();
+        let (bytes, _) = tokio::time::timeout(self.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some((status, String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Send a payload, through the host's WAF transforms when one blocks it
+    async fn inject(&self, url: &str, name: &str, location: ParamLocation, value: &str, baseline_status: u16) -> Option<(u16, String, Option<BypassTechnique>)> {
+        let Some(waf) = self.waf else {
+            return self.send(url, name, location, value).await.map(|(status, body)| (status, body, None));
+        };
+        let context = match location {
+            ParamLocation::Query => Context::Query,
+            ParamLocation::Json => Context::Json,
+        };
+        let send = |raw: Option<String>| async move {
+            match raw {
+                Some(raw) => self.send_raw(url, name, location, &raw).await,
+                None => self.send(url, name, location, value).await,
+            }
+        };
+        waf.send(url, name, value, context, baseline_status, send).await
+    }
+
+    /// Test one parameter; `None` unless an expression was evaluated twice
+    pub async fn test(&self, url: &str, name: &str, location: ParamLocation) -> Option<SstiFinding> {
+        let (base_status, baseline) = self.send(url, name, location, "hunter").await?;
+        let (payload, expected) = polyglot();
+        let (status, body, _) = self.inject(url, name, location, &payload, base_status).await?;
+        let mut candidates = evaluated(&expected, &baseline, &body);
+        // A syntax error in one part can abort the whole template; then each syntax on its own
+        if candidates.is_empty() && status >= 500 {
+            for syntax in ALL_SYNTAXES {
+                let (expr, product) = arithmetic();
+                let Some((_, body, _)) = self.inject(url, name, location, &syntax.wrap(&expr), base_status).await else { continue };
+                candidates.extend(evaluated(&[(syntax, product)], &baseline, &body));
+            }
+        }
+
+        for syntax in candidates {
+            let (expr, product) = arithmetic();
+            let payload = syntax.wrap(&expr);
+            let Some((_, body, waf_bypass)) = self.inject(url, name, location, &payload, base_status).await else { continue };
+            if evaluated(&[(syntax, product.clone())], &baseline, &body).is_empty() {
+                continue;
+            }
+            let mut engine = TemplateEngine::Unknown;
+            let mut last: Option<(&str, String)> = None;
+            for (expr, output, candidate) in fingerprints(syntax) {
+                // Engines told apart by the output of the same expression share a request
+                if last.as_ref().is_none_or(|(e, _)| e != expr) {
+                    let Some((_, body, _)) = self.inject(url, name, location, &syntax.wrap(expr), base_status).await else { continue };
+                    last = Some((expr, body));
+                }
+                let body = last.as_ref().map(|(_, b)| b.as_str()).unwrap_or_default();
+                if body.contains(output) && !baseline.contains(output) {
+                    engine = *candidate;
+                    break;
+                }
+            }
+            let identified = engine != TemplateEngine::Unknown;
+            let classification = RiskClassifier::classify_ssti(&engine.to_string(), identified, 2);
+            return Some(SstiFinding {
+                url: url.to_string(),
+                parameter: name.to_string(),
+                location,
+                syntax,
+                engine,
+                payload,
+                evidence: format!("{} -> {}", expr, product),
+                classification,
+                waf_bypass,
+            });
+        }
+        None
+    }
+}
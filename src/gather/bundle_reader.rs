@@ -0,0 +1,88 @@
+//! Reading large JavaScript bundles
+//! Bundles are scanned in fixed-size windows that overlap, so a match straddling a window edge is still
+//! seen whole, and memory stays bounded for multi-megabyte files. Binary assets (WASM, images, fonts,
+//! archives) served under script URLs are recognized up front instead of being decoded as text.
+
+/// Window size for scanning large bundles
+pub const WINDOW_SIZE: usize = 2 * 1024 * 1024;
+/// Overlap between consecutive windows; longer than any single match the extractors look for
+pub const WINDOW_OVERLAP: usize = 16 * 1024;
+/// Bytes needed to classify content as text or binary
+pub const SNIFF_LEN: usize = 1024;
+
+/// What a non-text asset is, from its content type and magic bytes
+pub fn binary_kind(head: &[u8], content_type: Option<&str>) -> Option<&'static str> {
+    let ct = content_type.unwrap_or("").to_ascii_lowercase();
+    if head.starts_with(b"\0asm") || ct.contains("application/wasm") {
+        return Some("wasm");
+    }
+    let magic: &[(&[u8], &'static str)] = &[
+        (b"\x89PNG", "image"),
+        (b"GIF8", "image"),
+        (b"\xFF\xD8\xFF", "image"),
+        (b"RIFF", "image"),
+        (b"wOFF", "font"),
+        (b"wOF2", "font"),
+        (b"PK\x03\x04", "archive"),
+        (b"\x1F\x8B", "archive"),
+        (b"%PDF", "pdf"),
+    ];
+    if let Some((_, kind)) = magic.iter().find(|(m, _)| head.starts_with(m)) {
+        return Some(kind);
+    }
+    if ct.starts_with("image/") || ct.starts_with("font/") || ct.starts_with("video/") || ct.starts_with("audio/") {
+        return Some("media");
+    }
+    // Text has no NULs and almost no other control bytes
+    let sample = &head[..head.len().min(SNIFF_LEN)];
+    let control = sample.iter().filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C)).count();
+    if sample.contains(&0) || control * 10 > sample.len() {
+        return Some("binary");
+    }
+    None
+}
+
+/// Splits a byte stream into overlapping text windows
+pub struct WindowedReader {
+    buf: Vec<u8>,
+    window: usize,
+    overlap: usize,
+}
+
+impl WindowedReader {
+    pub fn new(window: usize, overlap: usize) -> Self {
+        Self { buf: Vec::new(), window: window.max(1), overlap: overlap.min(window / 2) }
+    }
+
+    /// Add bytes; returns the windows that are complete
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        while self.buf.len() >= self.window + self.overlap {
+            let end = char_boundary(&self.buf, self.window + self.overlap);
+            out.push(String::from_utf8_lossy(&self.buf[..end]).into_owned());
+            let advance = char_boundary(&self.buf, self.window);
+            self.buf.drain(..advance);
+        }
+        out
+    }
+
+    /// The final window (everything not yet returned, plus the overlap)
+    pub fn finish(self) -> Option<String> {
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(String::from_utf8_lossy(&self.buf).into_owned())
+        }
+    }
+}
+
+/// Largest index <= `at` that doesn't split a UTF-8 sequence
+fn char_boundary(bytes: &[u8], at: usize) -> usize {
+    let mut i = at.min(bytes.len());
+    // Continuation bytes are 10xxxxxx; a sequence is at most 4 bytes
+    while i > 0 && i < bytes.len() && bytes[i] & 0xC0 == 0x80 && at - i < 3 {
+        i -= 1;
+    }
+    i
+}
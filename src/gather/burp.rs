@@ -0,0 +1,138 @@
+//! Burp Suite "Save items" XML import
+//! Replays existing proxy history through the scan pipeline without re-crawling the target
+
+use super::har::HarRequest;
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+static ITEM_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<item>(.*?)</item>").unwrap());
+
+/// Load a Burp XML export from disk.
+/// Items are returned as [`HarRequest`] so both importers feed the same pipeline.
+pub fn import_burp(path: &Path) -> Result<Vec<HarRequest>> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read Burp export {}", path.display()))?;
+    parse_burp(&data)
+}
+
+/// Parse Burp XML content. Requests are deduplicated by method + URL, keeping the first occurrence.
+pub fn parse_burp(data: &str) -> Result<Vec<HarRequest>> {
+    if !data.contains("<items") {
+        anyhow::bail!("invalid Burp export: missing <items> root element");
+    }
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+
+    for cap in ITEM_RE.captures_iter(data) {
+        let item = &cap[1];
+
+        let Some(url) = extract_tag(item, "url").map(|(_, v)| v) else { continue };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            continue;
+        }
+
+        let method = extract_tag(item, "method")
+            .map(|(_, v)| v.to_uppercase())
+            .unwrap_or_else(|| "GET".to_string());
+        if !seen.insert(format!("{} {}", method, url)) {
+            continue;
+        }
+
+        // The raw request is base64-encoded unless "Base64-encode requests and responses" was unticked
+        let raw = extract_tag(item, "request").and_then(|(attrs, body)| {
+            if attrs.contains("base64=\"true\"") {
+                general_purpose::STANDARD.decode(body.trim()).ok()
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+            } else {
+                Some(body)
+            }
+        });
+
+        let (headers, json_body) = raw.as_deref().map(parse_raw_request).unwrap_or_default();
+
+        out.push(HarRequest {
+            method,
+            url,
+            headers,
+            json_body,
+        });
+    }
+
+    tracing::debug!(count = out.len(), "Burp import extracted requests");
+    Ok(out)
+}
+
+/// Returns (attributes, text) of the first `<tag ...>text</tag>`, with CDATA unwrapped and entities decoded
+fn extract_tag(item: &str, tag: &str) -> Option<(String, String)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+
+    let mut search_from = 0;
+    let start = loop {
+        let idx = search_from + item[search_from..].find(&open)?;
+        // Make sure we matched <url> and not <urlfoo>
+        match item[idx + open.len()..].chars().next() {
+            Some('>') | Some(' ') | Some('\t') | Some('\n') | Some('/') => break idx,
+            _ => search_from = idx + open.len(),
+        }
+    };
+
+    let tag_end = start + item[start..].find('>')?;
+    let attrs = item[start + open.len()..tag_end].to_string();
+    if attrs.trim_end().ends_with('/') {
+        return Some((attrs, String::new()));
+    }
+
+    let content_end = tag_end + item[tag_end..].find(&close)?;
+    let content = item[tag_end + 1..content_end].trim();
+
+    let text = if let Some(inner) = content.strip_prefix("<![CDATA[").and_then(|c| c.strip_suffix("]]>")) {
+        inner.to_string()
+    } else {
+        decode_entities(content)
+    };
+
+    Some((attrs, text))
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Split a raw HTTP/1.x request into lowercased headers and an optional JSON body
+fn parse_raw_request(raw: &str) -> (HashMap<String, String>, Option<serde_json::Value>) {
+    let (head, body) = match raw.find("\r\n\r\n") {
+        Some(i) => (&raw[..i], &raw[i + 4..]),
+        None => match raw.find("\n\n") {
+            Some(i) => (&raw[..i], &raw[i + 2..]),
+            None => (raw, ""),
+        },
+    };
+
+    // First line is the request line
+    let headers = head.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_lowercase(), value.trim().to_string()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let trimmed = body.trim();
+    let json_body = if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        serde_json::from_str(trimmed).ok()
+    } else {
+        None
+    };
+
+    (headers, json_body)
+}
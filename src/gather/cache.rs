@@ -0,0 +1,127 @@
+//! On-disk cache for passive source responses (Wayback, crt.sh, CommonCrawl, OTX, urlscan, GitHub)
+//! Entries are keyed by source + query and expire after a configurable TTL, so re-running recon
+//! against the same target doesn't re-fetch identical data from rate-limited services.
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{self, Sender};
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    source: String,
+    query: String,
+    /// Unix seconds
+    stored_at: u64,
+    value: T,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceCache {
+    /// `None` disables the cache
+    dir: Option<PathBuf>,
+    ttl: Duration,
+}
+
+impl SourceCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir: Some(dir), ttl }
+    }
+
+    /// A cache that never stores or returns anything
+    pub fn disabled() -> Self {
+        Self { dir: None, ttl: Duration::ZERO }
+    }
+
+    fn path(&self, source: &str, query: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let digest = Sha256::digest(format!("{}\n{}", source, query).as_bytes());
+        let hex: String = digest.iter().take(12).map(|b| format!("{:02x}", b)).collect();
+        Some(dir.join(format!("{}-{}.json", source, hex)))
+    }
+
+    /// Cached value for `source`/`query` if present and younger than the TTL
+    pub fn get<T: DeserializeOwned>(&self, source: &str, query: &str) -> Option<T> {
+        let path = self.path(source, query)?;
+        let data = std::fs::read(&path).ok()?;
+        let entry: Entry<T> = serde_json::from_slice(&data).ok()?;
+        // Hash collisions are theoretical, but a mismatch must never return someone else's data
+        if entry.source != source || entry.query != query {
+            return None;
+        }
+        let age = now_secs().saturating_sub(entry.stored_at);
+        if age > self.ttl.as_secs() {
+            tracing::debug!("cache: {} entry for {} expired ({}s old)", source, query, age);
+            return None;
+        }
+        tracing::info!("cache: using {} response for {} ({}s old)", source, query, age);
+        Some(entry.value)
+    }
+
+    pub fn put<T: Serialize>(&self, source: &str, query: &str, value: &T) {
+        let Some(path) = self.path(source, query) else { return };
+        let entry = Entry { source: source.to_string(), query: query.to_string(), stored_at: now_secs(), value };
+        let write = || -> Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // Write then rename so a concurrent reader never sees a partial file
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+            std::fs::rename(&tmp, &path)?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            tracing::debug!("cache: failed to store {} response: {}", source, e);
+        }
+    }
+
+    /// Run a streaming gatherer through the cache. On a hit the cached URLs are replayed into `tx`;
+    /// otherwise `fetch` runs, its output is forwarded to `tx` and stored once it finishes successfully.
+    /// Gatherers return an error when they stop early (failed page, rate limit), so a truncated list
+    /// is never cached.
+    pub async fn stream<F, Fut>(&self, source: &str, query: &str, tx: Sender<String>, fetch: F) -> Result<usize>
+    where
+        F: FnOnce(Sender<String>) -> Fut,
+        Fut: Future<Output = Result<usize>>,
+    {
+        if let Some(urls) = self.get::<Vec<String>>(source, query) {
+            let count = urls.len();
+            for u in urls {
+                if tx.send(u).await.is_err() {
+                    break;
+                }
+            }
+            return Ok(count);
+        }
+        if self.dir.is_none() {
+            return fetch(tx).await;
+        }
+
+        let (inner_tx, mut inner_rx) = mpsc::channel::<String>(1024);
+        let forward = async {
+            let mut seen = Vec::new();
+            while let Some(u) = inner_rx.recv().await {
+                seen.push(u.clone());
+                if tx.send(u).await.is_err() {
+                    return None;
+                }
+            }
+            Some(seen)
+        };
+        let (res, seen) = tokio::join!(fetch(inner_tx), forward);
+
+        // Only complete runs are cached; a closed receiver means the output was cut short
+        if let (Ok(_), Some(seen)) = (&res, seen) {
+            self.put(source, query, &seen);
+        }
+        res
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,101 @@
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Client;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+const COLLINFO_URL: &str = "https://index.commoncrawl.org/collinfo.json";
+
+/// Number of most recent crawls to query. Older crawls mostly repeat the same URLs.
+const DEFAULT_INDEXES: usize = 3;
+/// Pages fetched per index (each page is up to ~15k records)
+const MAX_PAGES_PER_INDEX: u64 = 5;
+
+/// Stream URLs under `*.domain` from the most recent CommonCrawl indexes into `tx`,
+/// the same way external tools feed discovery. Returns the number of URLs sent, or an error if an index
+/// could not be read completely (the URLs streamed until then stay sent).
+pub async fn stream_commoncrawl_urls(domain: &str, tx: Sender<String>) -> Result<usize> {
+    let client = crate::http_client::third_party()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+
+    let indexes = latest_indexes(&client, DEFAULT_INDEXES).await?;
+    let pattern = format!("*.{}", domain);
+    let mut sent = 0;
+    // First failure; the remaining indexes are still read
+    let mut failed = None;
+
+    for api in indexes {
+        let pages = match num_pages(&client, &api, &pattern).await {
+            Ok(p) => p.min(MAX_PAGES_PER_INDEX),
+            Err(e) => {
+                tracing::debug!("CommonCrawl {} page count failed: {}", api, e);
+                failed.get_or_insert(e.context(format!("{} page count", api)));
+                continue;
+            }
+        };
+
+        for page in 0..pages {
+            let resp = client.get(&api)
+                .query(&[("url", pattern.as_str()), ("output", "json"), ("fl", "url"), ("page", &page.to_string())])
+                .send()
+                .await;
+
+            let body = match resp {
+                Ok(r) if r.status().is_success() => r.text().await.map_err(anyhow::Error::from),
+                Ok(r) => Err(anyhow!("HTTP {}", r.status())),
+                Err(e) => Err(e.into()),
+            };
+            let body = match body {
+                Ok(body) => body,
+                Err(e) => {
+                    tracing::debug!("CommonCrawl {} page {} failed: {}", api, page, e);
+                    failed.get_or_insert(e.context(format!("{} page {}", api, page)));
+                    break;
+                }
+            };
+
+            // One JSON object per line: {"url": "..."}
+            for line in body.lines() {
+                let Ok(v) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+                if let Some(url) = v.get("url").and_then(|u| u.as_str()) {
+                    if tx.send(url.to_string()).await.is_err() {
+                        // Receiver is gone, discovery is over
+                        return Ok(sent);
+                    }
+                    sent += 1;
+                }
+            }
+        }
+    }
+
+    tracing::info!("CommonCrawl: {} URLs streamed", sent);
+    if let Some(e) = failed {
+        bail!("incomplete after {} URLs: {:#}", sent, e);
+    }
+    Ok(sent)
+}
+
+/// CDX API endpoints of the `count` most recent crawls
+async fn latest_indexes(client: &Client, count: usize) -> Result<Vec<String>> {
+    let v: serde_json::Value = client.get(COLLINFO_URL)
+        .send().await?
+        .error_for_status()?
+        .json().await
+        .context("invalid CommonCrawl collinfo response")?;
+
+    // collinfo.json is ordered newest first
+    Ok(v.as_array().into_iter().flatten()
+        .filter_map(|c| c.get("cdx-api").and_then(|a| a.as_str()).map(|a| a.to_string()))
+        .take(count)
+        .collect())
+}
+
+async fn num_pages(client: &Client, api: &str, pattern: &str) -> Result<u64> {
+    let v: serde_json::Value = client.get(api)
+        .query(&[("url", pattern), ("output", "json"), ("showNumPages", "true")])
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    Ok(v.get("pages").and_then(|p| p.as_u64()).unwrap_or(0))
+}
@@ -0,0 +1,260 @@
+//! Bounded-depth HTML crawler
+//! Follows same-domain links breadth-first and collects `<a href>`, `<form action>` and endpoints
+//! called from inline scripts (`fetch`, `axios`, jQuery, `XMLHttpRequest.open`).
+
+use crate::http_client::Paced;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use url::Url;
+
+static INLINE_CALL_RES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r#"fetch\(\s*["'`]([^"'`\s]+)["'`]"#,
+        r#"axios(?:\.(?:get|post|put|patch|delete|head|request))?\(\s*["'`]([^"'`\s]+)["'`]"#,
+        r#"\$\.(?:get|post|getJSON|ajax)\(\s*["'`]([^"'`\s]+)["'`]"#,
+        r#"\burl\s*:\s*["'`](/[^"'`\s]*|https?://[^"'`\s]+)["'`]"#,
+        r#"\.open\(\s*["'](?:GET|POST|PUT|PATCH|DELETE|HEAD|OPTIONS)["']\s*,\s*["'`]([^"'`\s]+)["'`]"#,
+    ]
+    .iter()
+    .map(|r| Regex::new(r).unwrap())
+    .collect()
+});
+
+#[derive(Debug, Clone)]
+pub struct CrawlConfig {
+    /// Link depth from the start page (0 = start page only)
+    pub max_depth: usize,
+    pub max_pages: usize,
+    /// Pause between page fetches
+    pub delay: Duration,
+}
+
+impl Default for CrawlConfig {
+    fn default() -> Self {
+        Self { max_depth: 2, max_pages: 50, delay: Duration::from_millis(250) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FormTarget {
+    pub action: String,
+    pub method: String,
+    /// Names of the form's inputs
+    pub inputs: Vec<String>,
+    /// Page the form was found on
+    pub page: String,
+}
+
+/// Links and endpoints found on one page
+#[derive(Debug, Clone, Default)]
+pub struct PageLinks {
+    pub links: Vec<String>,
+    pub forms: Vec<FormTarget>,
+    pub fetch_targets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrawlResult {
+    /// HTML pages fetched
+    pub pages: Vec<String>,
+    /// Same-domain links seen (crawled or not)
+    pub links: Vec<String>,
+    pub forms: Vec<FormTarget>,
+    /// URLs called from inline scripts
+    pub fetch_targets: Vec<String>,
+}
+
+impl CrawlResult {
+    /// Everything worth probing: links, form actions (with their inputs as a query) and fetch targets
+    pub fn candidate_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = self.links.iter().cloned()
+            .chain(self.fetch_targets.iter().cloned())
+            .chain(self.forms.iter().map(|f| {
+                if f.inputs.is_empty() {
+                    return f.action.clone();
+                }
+                let query: Vec<String> = f.inputs.iter().map(|i| format!("{}=", i)).collect();
+                let sep = if f.action.contains('?') { '&' } else { '?' };
+                format!("{}{}{}", f.action, sep, query.join("&"))
+            }))
+            .collect();
+        urls.sort();
+        urls.dedup();
+        urls
+    }
+}
+
+/// Is `url` on the crawled domain (same host or a subdomain of it)?
+pub fn same_domain(url: &Url, domain: &str) -> bool {
+    url.host_str().is_some_and(|h| {
+        let h = h.trim_start_matches("www.");
+        let d = domain.trim_start_matches("www.");
+        h == d || h.ends_with(&format!(".{}", d))
+    })
+}
+
+/// Extract links, forms and inline fetch targets from a page. URLs are absolute, fragments removed.
+pub fn extract(html: &str, page_url: &Url) -> PageLinks {
+    let document = Html::parse_document(html);
+    let mut out = PageLinks::default();
+
+    // <base href> changes what relative links resolve against
+    let base = Selector::parse("base[href]").ok()
+        .and_then(|sel| document.select(&sel).next().and_then(|b| b.value().attr("href")).map(str::to_string))
+        .and_then(|href| page_url.join(&href).ok())
+        .unwrap_or_else(|| page_url.clone());
+    let resolve = |href: &str| -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") || href.starts_with("mailto:")
+            || href.starts_with("tel:") || href.starts_with("data:") {
+            return None;
+        }
+        let mut u = base.join(href).ok()?;
+        if u.scheme() != "http" && u.scheme() != "https" {
+            return None;
+        }
+        u.set_fragment(None);
+        Some(u.to_string())
+    };
+
+    if let Ok(sel) = Selector::parse("a[href], area[href]") {
+        out.links.extend(document.select(&sel).filter_map(|a| a.value().attr("href")).filter_map(&resolve));
+    }
+
+    if let (Ok(form_sel), Ok(input_sel)) = (Selector::parse("form"), Selector::parse("input[name], select[name], textarea[name]")) {
+        for form in document.select(&form_sel) {
+            // A form without an action submits to the page itself
+            let action = form.value().attr("action").and_then(&resolve).unwrap_or_else(|| {
+                let mut u = page_url.clone();
+                u.set_fragment(None);
+                u.to_string()
+            });
+            let method = form.value().attr("method").unwrap_or("GET").to_uppercase();
+            let mut inputs: Vec<String> = form.select(&input_sel)
+                .filter_map(|i| i.value().attr("name").map(str::to_string))
+                .collect();
+            inputs.dedup();
+            out.forms.push(FormTarget { action, method, inputs, page: page_url.to_string() });
+        }
+    }
+
+    if let Ok(sel) = Selector::parse("script:not([src])") {
+        for script in document.select(&sel) {
+            let text: String = script.text().collect();
+            for re in INLINE_CALL_RES.iter() {
+                out.fetch_targets.extend(re.captures_iter(&text).filter_map(|c| resolve(&c[1])));
+            }
+        }
+    }
+
+    out.links.sort();
+    out.links.dedup();
+    out.fetch_targets.sort();
+    out.fetch_targets.dedup();
+    out
+}
+
+pub struct Crawler {
+    client: Client,
+    config: CrawlConfig,
+}
+
+impl Crawler {
+    pub fn new(config: CrawlConfig) -> Result<Self> {
+        let client = crate::http_client::builder("gather::crawler")
+            .timeout(Duration::from_secs(10))
+            .danger_accept_invalid_certs(true)
+            .redirect(reqwest::redirect::Policy::limited(5))
+            .user_agent(crate::tls_profile::user_agent_or("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"))
+            .build()?;
+        Ok(Self { client, config })
+    }
+
+    /// Crawl breadth-first from `start_url`, staying on its domain
+    pub async fn crawl(&self, start_url: &str) -> Result<CrawlResult> {
+        let start = Url::parse(start_url)?;
+        let domain = start.host_str().unwrap_or_default().to_string();
+        let mut result = CrawlResult::default();
+        let mut queue: VecDeque<(String, usize)> = VecDeque::from([(start.to_string(), 0)]);
+        let mut queued: HashSet<String> = HashSet::from([start.to_string()]);
+        let mut links: HashSet<String> = HashSet::new();
+        let mut fetch_targets: HashSet<String> = HashSet::new();
+
+        while let Some((page, depth)) = queue.pop_front() {
+            if result.pages.len() >= self.config.max_pages {
+                break;
+            }
+            if !result.pages.is_empty() && !self.config.delay.is_zero() {
+                tokio::time::sleep(self.config.delay).await;
+            }
+
+            let Some((final_url, html)) = self.fetch_html(&page).await else { continue };
+            // Redirects off the domain (SSO, CDN) are not followed further
+            if !same_domain(&final_url, &domain) {
+                continue;
+            }
+            result.pages.push(page.clone());
+
+            let found = extract(&html, &final_url);
+            for link in found.links {
+                let Ok(u) = Url::parse(&link) else { continue };
+                if !same_domain(&u, &domain) {
+                    continue;
+                }
+                if depth < self.config.max_depth && looks_like_page(&u) && queued.insert(link.clone()) {
+                    queue.push_back((link.clone(), depth + 1));
+                }
+                links.insert(link);
+            }
+            for form in found.forms {
+                if Url::parse(&form.action).is_ok_and(|u| same_domain(&u, &domain)) && !result.forms.contains(&form) {
+                    result.forms.push(form);
+                }
+            }
+            fetch_targets.extend(found.fetch_targets.into_iter()
+                .filter(|t| Url::parse(t).is_ok_and(|u| same_domain(&u, &domain))));
+        }
+
+        result.links = links.into_iter().collect();
+        result.links.sort();
+        result.fetch_targets = fetch_targets.into_iter().collect();
+        result.fetch_targets.sort();
+        tracing::info!("Crawler: {} pages, {} links, {} forms, {} inline fetch targets",
+            result.pages.len(), result.links.len(), result.forms.len(), result.fetch_targets.len());
+        Ok(result)
+    }
+
+    async fn fetch_html(&self, url: &str) -> Option<(Url, String)> {
+        let resp = self.client.get(url).send_paced().await.ok()?;
+        if !resp.status().is_success() {
+            return None;
+        }
+        let is_html = resp.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.contains("html"));
+        if !is_html {
+            return None;
+        }
+        let final_url = resp.url().clone();
+        let bytes = resp.bytes().await.ok()?;
+        // Same cap js_fisher uses for root pages
+        let body = String::from_utf8_lossy(&bytes[..bytes.len().min(256 * 1024)]).into_owned();
+        Some((final_url, body))
+    }
+}
+
+/// Skip links that are obviously not HTML (assets, downloads) when choosing pages to crawl
+fn looks_like_page(url: &Url) -> bool {
+    let path = url.path().to_lowercase();
+    let ext = path.rsplit('/').next().and_then(|seg| seg.rsplit_once('.')).map(|(_, e)| e).unwrap_or("");
+    !matches!(ext,
+        "js" | "mjs" | "css" | "png" | "jpg" | "jpeg" | "gif" | "svg" | "ico" | "webp" | "woff" | "woff2" | "ttf"
+        | "pdf" | "zip" | "gz" | "mp4" | "mp3" | "webm" | "xml" | "json" | "txt" | "map")
+        && !path.contains("logout") && !path.contains("signout")
+}
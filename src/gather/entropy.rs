@@ -0,0 +1,112 @@
+//! Entropy-based generic secret detection
+//! Fixed regexes only catch known token formats. Random-looking string literals assigned to something
+//! named like a credential (`key`, `secret`, `token`, ...) are reported too, so unknown providers' tokens
+//! aren't missed.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Default minimum entropy (bits per char) for base64-like literals
+pub const DEFAULT_THRESHOLD: f64 = 4.5;
+
+/// Quoted literals made of token characters
+static LITERAL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["'`]([A-Za-z0-9+/=_\-.~]{20,200})["'`]"#).unwrap());
+
+/// Names that make a random literal a probable credential
+static CONTEXT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(api[_-]?key|apikey|secret|token|passw(?:or)?d|pwd|credential|private[_-]?key|access[_-]?key|auth|signature|signing|salt|client[_-]?id|dsn|license)")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntropyHit {
+    pub value: String,
+    /// Context keyword that made the literal a candidate
+    pub keyword: String,
+    pub entropy: f64,
+    /// Byte offset of the literal in the scanned content
+    pub offset: usize,
+}
+
+/// Shannon entropy in bits per character
+pub fn shannon_entropy(s: &str) -> f64 {
+    if s.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for b in s.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = s.len() as f64;
+    counts.iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Hex strings top out at 4 bits/char, so they get a proportionally lower bar. Short strings can't
+/// reach high entropy either (at most log2(len) bits), so literals under 64 chars are scaled down.
+fn threshold_for(value: &str, threshold: f64) -> f64 {
+    let stripped: String = value.chars().filter(|c| *c != '-').collect();
+    let base = if stripped.chars().all(|c| c.is_ascii_hexdigit()) {
+        threshold * 2.0 / 3.0
+    } else {
+        threshold
+    };
+    base * ((value.len() as f64).log2() / 6.0).min(1.0)
+}
+
+/// Paths, module names, identifiers and well-known formats that look random but aren't secrets
+fn is_noise(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    value.starts_with("eyJ") // JWTs are matched by their own pattern
+        || value.contains("://")
+        || value.starts_with('/') || value.starts_with("./") || value.starts_with("../")
+        || [".js", ".css", ".png", ".svg", ".json", ".html", ".woff"].iter().any(|ext| lower.contains(ext))
+        // Identifiers and sentences: no digits at all
+        || !value.chars().any(|c| c.is_ascii_digit())
+        // Mostly digits: timestamps, numeric ids, version strings
+        || value.chars().filter(|c| c.is_ascii_alphabetic()).count() < 4
+        || value.contains("--") || value.contains("__")
+        // Word lists like `chunk-vendors-2a` or `en_us_1234` where no segment mixes classes
+        || value.split(['-', '_', '.']).filter(|p| !p.is_empty()).all(|p| p.chars().all(|c| c.is_ascii_lowercase()) || p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Find high-entropy literals that sit next to a credential-like name on the same statement
+pub fn find_secrets(content: &str, threshold: f64) -> Vec<EntropyHit> {
+    let mut hits: Vec<EntropyHit> = Vec::new();
+    for cap in LITERAL_RE.captures_iter(content) {
+        let m = cap.get(1).unwrap();
+        let value = m.as_str();
+        if is_noise(value) {
+            continue;
+        }
+        let entropy = shannon_entropy(value);
+        if entropy < threshold_for(value, threshold) {
+            continue;
+        }
+
+        // The name being assigned: up to 60 chars before the literal, within the statement
+        let mut ctx_start = m.start().saturating_sub(61);
+        while !content.is_char_boundary(ctx_start) {
+            ctx_start += 1;
+        }
+        let before = &content[ctx_start..m.start() - 1];
+        let before = before.rsplit([';', '\n', ',', '{', '(']).next().unwrap_or(before);
+        let Some(keyword) = CONTEXT_RE.find(before) else { continue };
+
+        if hits.iter().any(|h| h.value == value) {
+            continue;
+        }
+        hits.push(EntropyHit {
+            value: value.to_string(),
+            keyword: keyword.as_str().to_string(),
+            entropy,
+            offset: m.start(),
+        });
+    }
+    hits
+}
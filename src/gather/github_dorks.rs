@@ -0,0 +1,134 @@
+//! GitHub code search for the target domain
+//! Finds public code mentioning the domain and extracts URLs, API paths and potential secrets
+
+use super::js_deep_analyzer::{JsDeepAnalyzer, Secret};
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// 100 results per page; code search allows ~10 requests/minute
+const MAX_PAGES: u32 = 3;
+
+static API_PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"["'`](/(?:api|v[0-9]+|graphql|rest|internal|admin)(?:/[\w\-.{}:]*)*)"#).unwrap()
+});
+
+/// A code search hit with everything extracted from its matched fragments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubLeak {
+    pub repository: String,
+    pub path: String,
+    pub html_url: String,
+    pub urls: Vec<String>,
+    pub api_paths: Vec<String>,
+    pub secrets: Vec<Secret>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GithubDorkResult {
+    pub total_hits: u64,
+    pub leaks: Vec<GithubLeak>,
+    /// Target URLs to feed into probing (leaked URLs + API paths joined to the domain)
+    pub candidates: Vec<String>,
+}
+
+pub struct GithubDorker {
+    client: Client,
+    token: String,
+}
+
+impl GithubDorker {
+    pub fn new(token: String) -> Self {
+        let client = crate::http_client::third_party()
+            .user_agent("api_hunter")
+            .timeout(Duration::from_secs(20))
+            .build()
+            .unwrap_or_default();
+        Self { client, token }
+    }
+
+    /// Search public code for `"domain"`
+    pub async fn search(&self, domain: &str) -> Result<GithubDorkResult> {
+        let url_re = Regex::new(&format!(
+            r#"https?://(?:[\w-]+\.)*{}(?::\d+)?(?:/[^\s"'`<>)\]\\]*)?"#,
+            regex::escape(domain)
+        ))?;
+
+        let mut result = GithubDorkResult::default();
+        let mut candidates = BTreeSet::new();
+
+        for page in 1..=MAX_PAGES {
+            if page > 1 {
+                // Stay under the code search secondary rate limit
+                tokio::time::sleep(Duration::from_secs(6)).await;
+            }
+
+            let resp = self.client.get("https://api.github.com/search/code")
+                .query(&[("q", format!("\"{}\"", domain)), ("per_page", "100".to_string()), ("page", page.to_string())])
+                .header("Authorization", format!("Bearer {}", self.token))
+                // text-match media type returns the matching fragments
+                .header("Accept", "application/vnd.github.text-match+json")
+                .send()
+                .await?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                if page == 1 {
+                    bail!("GitHub search returned {}", status);
+                }
+                tracing::debug!("GitHub search page {} returned {}", page, status);
+                break;
+            }
+
+            let v: serde_json::Value = resp.json().await?;
+            result.total_hits = v["total_count"].as_u64().unwrap_or(0);
+            let items = v["items"].as_array().cloned().unwrap_or_default();
+            if items.is_empty() {
+                break;
+            }
+
+            for item in &items {
+                let html_url = item["html_url"].as_str().unwrap_or_default().to_string();
+                let fragments: Vec<&str> = item["text_matches"].as_array().into_iter().flatten()
+                    .filter_map(|m| m["fragment"].as_str())
+                    .collect();
+                let text = fragments.join("\n");
+
+                let urls: BTreeSet<String> = url_re.find_iter(&text)
+                    .map(|m| m.as_str().trim_end_matches(['.', ',', ';']).to_string())
+                    .collect();
+                let api_paths: BTreeSet<String> = API_PATH_RE.captures_iter(&text)
+                    .map(|c| c[1].to_string())
+                    .collect();
+                let secrets = JsDeepAnalyzer::extract_secrets(&text, &html_url);
+
+                candidates.extend(urls.iter().cloned());
+                candidates.extend(api_paths.iter().map(|p| format!("https://{}{}", domain, p)));
+
+                if urls.is_empty() && api_paths.is_empty() && secrets.is_empty() {
+                    continue;
+                }
+
+                result.leaks.push(GithubLeak {
+                    repository: item["repository"]["full_name"].as_str().unwrap_or_default().to_string(),
+                    path: item["path"].as_str().unwrap_or_default().to_string(),
+                    html_url,
+                    urls: urls.into_iter().collect(),
+                    api_paths: api_paths.into_iter().collect(),
+                    secrets,
+                });
+            }
+
+            if items.len() < 100 {
+                break;
+            }
+        }
+
+        result.candidates = candidates.into_iter().collect();
+        Ok(result)
+    }
+}
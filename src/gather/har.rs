@@ -0,0 +1,111 @@
+//! HAR (HTTP Archive) import for browser-captured traffic
+//! Lets users browse the target manually (DevTools / proxy export) and feed the captured requests into the scan
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A single request extracted from a HAR capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    /// Request body, only kept when it parses as JSON
+    pub json_body: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct HarFile {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+struct HarEntry {
+    request: HarEntryRequest,
+}
+
+#[derive(Deserialize)]
+struct HarEntryRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<HarHeader>,
+    #[serde(rename = "postData")]
+    post_data: Option<HarPostData>,
+}
+
+#[derive(Deserialize)]
+struct HarHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct HarPostData {
+    #[serde(rename = "mimeType", default)]
+    mime_type: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// Load a HAR file from disk and extract all HTTP(S) requests
+pub fn import_har(path: &Path) -> Result<Vec<HarRequest>> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read HAR file {}", path.display()))?;
+    parse_har(&data)
+}
+
+/// Parse HAR JSON content. Requests are deduplicated by method + URL, keeping the first occurrence.
+pub fn parse_har(data: &str) -> Result<Vec<HarRequest>> {
+    let har: HarFile = serde_json::from_str(data).context("invalid HAR document")?;
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+
+    for entry in har.log.entries {
+        let req = entry.request;
+
+        // Skip data:, blob:, chrome-extension: and similar browser-internal URLs
+        if !req.url.starts_with("http://") && !req.url.starts_with("https://") {
+            continue;
+        }
+
+        let method = req.method.to_uppercase();
+        if !seen.insert(format!("{} {}", method, req.url)) {
+            continue;
+        }
+
+        // HTTP/2 pseudo-headers (:authority, :path, ...) are not replayable
+        let headers = req.headers.into_iter()
+            .filter(|h| !h.name.starts_with(':'))
+            .map(|h| (h.name.to_lowercase(), h.value))
+            .collect();
+
+        let json_body = req.post_data.and_then(|pd| {
+            let text = pd.text?;
+            if pd.mime_type.contains("json") || text.trim_start().starts_with('{') || text.trim_start().starts_with('[') {
+                serde_json::from_str(&text).ok()
+            } else {
+                None
+            }
+        });
+
+        out.push(HarRequest {
+            method,
+            url: req.url,
+            headers,
+            json_body,
+        });
+    }
+
+    tracing::debug!(count = out.len(), "HAR import extracted requests");
+    Ok(out)
+}
@@ -1,20 +1,84 @@
 //! Advanced JavaScript Deep Analysis - Extract all critical information from JS files
 //! This module mimics what a bug bounty hunter manually does in F12 DevTools Network tab
 
+use crate::http_client::Paced;
 use anyhow::Result;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use reqwest::Client;
 use scraper::{Html, Selector};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use url::Url;
 use dashmap::DashMap;
 use std::sync::Arc;
 use tokio::task::JoinSet;
+use futures::StreamExt;
+use super::bundle_reader::{self, WindowedReader};
+use super::entropy;
+use super::secret_patterns::SecretPattern;
+use crate::output::paths;
+use super::js_libraries::{self, JsLibrary, VulnerableLibrary};
+use super::source_map;
+use super::wasm;
+use super::webpack;
+use crate::security::token_classifier::{self, TokenClass};
+use std::path::{Path, PathBuf};
+
+/// Web storage writes: (storage, key)
+static STORAGE_WRITE_RES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r#"(localStorage|sessionStorage)\.setItem\(\s*["'`]([^"'`]*)["'`]"#,
+        r#"(localStorage|sessionStorage)\[\s*["'`]([^"'`]*)["'`]\s*\]\s*=[^=]"#,
+        r#"(localStorage|sessionStorage)\.([A-Za-z_$][\w$]*)\s*=[^=]"#,
+    ]
+    .iter()
+    .map(|p| Regex::new(p).unwrap())
+    .collect()
+});
+static SENSITIVE_STORAGE_KEY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)token|jwt|auth|session|secret|password|passwd|api_?key|credential|bearer"#).unwrap());
+static MESSAGE_LISTENER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"addEventListener\(\s*["'`]message["'`]|\bonmessage\s*=[^=]"#).unwrap());
+static SINK_RES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        ("eval", r#"\beval\s*\("#),
+        ("Function", r#"\bnew\s+Function\s*\("#),
+        ("innerHTML", r#"\.innerHTML\s*\+?=[^=]"#),
+        ("outerHTML", r#"\.outerHTML\s*\+?=[^=]"#),
+        ("insertAdjacentHTML", r#"\.insertAdjacentHTML\s*\("#),
+        ("document.write", r#"\bdocument\.write(?:ln)?\s*\("#),
+        ("dangerouslySetInnerHTML", r#"dangerouslySetInnerHTML"#),
+        ("setTimeout(string)", r#"\bset(?:Timeout|Interval)\s*\(\s*["'`]"#),
+    ]
+    .iter()
+    .map(|(name, p)| (*name, Regex::new(p).unwrap()))
+    .collect()
+});
+static OAUTH_URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"https?://[^\s"'`<>\\]+?/[^\s"'`<>\\?]*(?i:authori[sz]e)[^\s"'`<>\\]*"#).unwrap());
+static OAUTH_ENDPOINT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)["']?(?:authorization_?endpoint|authori[sz]ation_?url|authori[sz]e_?(?:url|uri|endpoint)|auth_?url)["']?\s*[:=]\s*["'`](https?://[^"'`\s]+)["'`]"#).unwrap());
+/// `name: "value"` in a config object, `value` matching the second pattern
+fn config_field_re(name: &str, value: &str) -> Regex {
+    Regex::new(&format!(r#"(?i)["']?{}["']?\s*[:=]\s*["'`]({})["'`]"#, name, value)).unwrap()
+}
+static OAUTH_CLIENT_ID_RE: Lazy<Regex> = Lazy::new(|| config_field_re("client_?id", r#"[^"'`\s]{3,}"#));
+static OAUTH_REDIRECT_URI_RE: Lazy<Regex> = Lazy::new(|| config_field_re("redirect_?ur[il]", r#"https?://[^"'`\s]+"#));
+static OAUTH_RESPONSE_TYPE_RE: Lazy<Regex> = Lazy::new(|| config_field_re("response_?type", r#"[\w ]+"#));
+
+/// Upper bound on JS files per analysis; large SPAs can list thousands of chunks
+const MAX_JS_FILES: usize = 1000;
+
+/// Download limit per JS file; larger bundles are analyzed up to this size
+pub const DEFAULT_MAX_JS_SIZE: usize = 20 * 1024 * 1024;
+
+/// Source maps embed every original file and get large
+const MAX_SOURCE_MAP_SIZE: usize = 30 * 1024 * 1024;
 
 /// Critical information extracted from JavaScript files
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct JsCriticalInfo {
+    /// Output schema version (`output::schema::SCHEMA_VERSION`); 0 for files written before versioning
+    #[serde(default = "crate::output::schema::unversioned")]
+    pub schema_version: u32,
     /// API endpoints discovered
     pub endpoints: Vec<ApiEndpoint>,
     /// Authentication tokens, API keys, secrets
@@ -41,9 +105,75 @@ pub struct JsCriticalInfo {
     pub source_maps: Vec<String>,
     /// Version information
     pub versions: HashMap<String, String>,
+    /// Client-side security issues (token storage, postMessage handlers, DOM sinks)
+    #[serde(default)]
+    pub client_side_issues: Vec<ClientSideIssue>,
+    /// Bundled npm libraries
+    #[serde(default)]
+    pub libraries: Vec<JsLibrary>,
+    /// Bundled libraries with known advisories
+    #[serde(default)]
+    pub vulnerable_libraries: Vec<VulnerableLibrary>,
+    /// Chunk files enumerated from webpack runtime chunk maps
+    #[serde(default)]
+    pub webpack_chunks: Vec<String>,
+    /// Original files recovered from source maps (`<map url>#<path>`)
+    #[serde(default)]
+    pub original_sources: Vec<String>,
+    /// Non-text assets served under script URLs (WASM, images, archives), not analyzed as JS
+    #[serde(default)]
+    pub binary_assets: Vec<BinaryAsset>,
+    /// Bundles larger than the size limit; only the first part was analyzed
+    #[serde(default)]
+    pub truncated_files: Vec<String>,
+    /// WebAssembly modules whose data strings were analyzed
+    #[serde(default)]
+    pub wasm_modules: Vec<wasm::WasmModule>,
+    /// OAuth/OIDC authorization requests configured in the client
+    #[serde(default)]
+    pub oauth_flows: Vec<OAuthFlow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BinaryAsset {
+    pub url: String,
+    /// `wasm`, `image`, `font`, `archive`, `pdf`, `media` or `binary`
+    pub kind: String,
+    /// Content-Length, if sent
+    pub size: Option<u64>,
+}
+
+impl JsCriticalInfo {
+    /// Append everything from `other` (duplicates are removed when results are aggregated)
+    pub fn absorb(&mut self, other: JsCriticalInfo) {
+        self.endpoints.extend(other.endpoints);
+        self.secrets.extend(other.secrets);
+        self.domains.extend(other.domains);
+        self.parameters.extend(other.parameters);
+        self.websockets.extend(other.websockets);
+        self.graphql.extend(other.graphql);
+        self.routes.extend(other.routes);
+        self.cloud_storage.extend(other.cloud_storage);
+        self.emails.extend(other.emails);
+        self.comments.extend(other.comments);
+        self.integrations.extend(other.integrations);
+        self.source_maps.extend(other.source_maps);
+        for (k, v) in other.versions {
+            self.versions.entry(k).or_insert(v);
+        }
+        self.client_side_issues.extend(other.client_side_issues);
+        self.libraries.extend(other.libraries);
+        self.vulnerable_libraries.extend(other.vulnerable_libraries);
+        self.webpack_chunks.extend(other.webpack_chunks);
+        self.original_sources.extend(other.original_sources);
+        self.binary_assets.extend(other.binary_assets);
+        self.truncated_files.extend(other.truncated_files);
+        self.wasm_modules.extend(other.wasm_modules);
+        self.oauth_flows.extend(other.oauth_flows);
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ApiEndpoint {
     pub url: String,
     pub method: String,
@@ -51,15 +181,24 @@ pub struct ApiEndpoint {
     pub context: String, // Surrounding code for context
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Secret {
     pub secret_type: SecretType,
     pub value: String,
     pub source_file: String,
     pub line_context: String,
+    /// Shannon entropy, for secrets found by the entropy scanner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<f64>,
+    /// Live check result (`--verify-secrets`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<crate::security::secret_verifier::Verification>,
+    /// Provider and privilege, for tokens of a known format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<TokenClass>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub enum SecretType {
     ApiKey,
     BearerToken,
@@ -71,9 +210,26 @@ pub enum SecretType {
     WebhookUrl,
     DatabaseUrl,
     Generic,
+    /// Matched by a user-supplied pattern (`--secret-patterns`)
+    Custom(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Secret detection settings beyond the built-in patterns
+#[derive(Debug, Clone)]
+pub struct SecretRules {
+    /// Minimum entropy for generic secrets; `None` disables the entropy scanner
+    pub entropy_threshold: Option<f64>,
+    /// Extra patterns loaded from `--secret-patterns`
+    pub custom: Vec<SecretPattern>,
+}
+
+impl Default for SecretRules {
+    fn default() -> Self {
+        Self { entropy_threshold: Some(entropy::DEFAULT_THRESHOLD), custom: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Parameter {
     pub name: String,
     pub param_type: ParamType,
@@ -81,7 +237,7 @@ pub struct Parameter {
     pub source_file: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ParamType {
     Query,
     Path,
@@ -89,7 +245,7 @@ pub enum ParamType {
     Header,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GraphQLInfo {
     pub endpoint: String,
     pub queries: Vec<String>,
@@ -97,14 +253,14 @@ pub struct GraphQLInfo {
     pub source_file: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CloudStorage {
     pub storage_type: StorageType,
     pub bucket_url: String,
     pub source_file: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum StorageType {
     S3,
     GCS,
@@ -112,38 +268,129 @@ pub enum StorageType {
     Cloudflare,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Integration {
     pub service: String,
     pub identifier: String,
     pub source_file: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClientSideIssue {
+    pub issue_type: ClientSideIssueType,
+    /// Matched API, e.g. "localStorage.setItem(token)" or "innerHTML"
+    pub detail: String,
+    pub source_file: String,
+    /// Surrounding code
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub enum ClientSideIssueType {
+    /// Auth token written to localStorage/sessionStorage (readable by any XSS)
+    StorageTokenPersistence,
+    /// message event listener without an origin check
+    PostMessageNoOriginCheck,
+    /// eval/Function/innerHTML/document.write style sink
+    DangerousSink,
+}
+
+/// An OAuth/OIDC authorization request found in client code
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct OAuthFlow {
+    pub authorization_endpoint: String,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub response_type: Option<String>,
+    /// The complete authorization URL, when the code contains one (config objects don't)
+    pub url: Option<String>,
+    pub source_file: String,
+}
+
+impl OAuthFlow {
+    /// From a complete authorization URL; `None` unless it carries `client_id` or `response_type`
+    pub fn from_url(url: &str, source_file: &str) -> Option<Self> {
+        let parsed = Url::parse(url).ok()?;
+        let param = |k: &str| parsed.query_pairs().find(|(n, _)| n == k).map(|(_, v)| v.into_owned()).filter(|v| !v.is_empty());
+        let (client_id, response_type) = (param("client_id"), param("response_type"));
+        if client_id.is_none() && response_type.is_none() {
+            return None;
+        }
+        let mut endpoint = parsed.clone();
+        endpoint.set_query(None);
+        endpoint.set_fragment(None);
+        Some(Self {
+            authorization_endpoint: endpoint.to_string(),
+            client_id,
+            redirect_uri: param("redirect_uri"),
+            response_type,
+            url: Some(url.to_string()),
+            source_file: source_file.to_string(),
+        })
+    }
+
+    /// The authorization URL in the code carries `state`; `None` when the request is built at runtime
+    pub fn has_state(&self) -> Option<bool> {
+        let url = Url::parse(self.url.as_deref()?).ok()?;
+        Some(url.query_pairs().any(|(k, _)| k == "state"))
+    }
+}
+
 /// Deep JavaScript Analyzer
 pub struct JsDeepAnalyzer {
     client: Client,
     base_domain: String,
     max_js_size: usize,
     max_concurrent: usize,
+    /// Where recovered original sources are written, if anywhere
+    source_output: Option<PathBuf>,
+    secret_rules: Arc<SecretRules>,
 }
 
 impl JsDeepAnalyzer {
     pub fn new(base_domain: String, timeout_secs: u64, max_concurrent: usize) -> Result<Self> {
-        let client = Client::builder()
+        let client = crate::http_client::builder("gather::js_deep_analyzer")
             .timeout(std::time::Duration::from_secs(timeout_secs))
             .danger_accept_invalid_certs(true)
             .pool_max_idle_per_host(50)
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+            .user_agent(crate::tls_profile::user_agent_or("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"))
             .build()?;
 
         Ok(Self {
             client,
             base_domain,
-            max_js_size: 2 * 1024 * 1024, // 2MB per file
+            max_js_size: DEFAULT_MAX_JS_SIZE,
             max_concurrent,
+            source_output: None,
+            secret_rules: Arc::new(SecretRules::default()),
         })
     }
 
+    /// Write original sources recovered from source maps below `dir` (one folder per host)
+    pub fn with_source_output(mut self, dir: PathBuf) -> Self {
+        self.source_output = Some(dir);
+        self
+    }
+
+    /// Entropy threshold (bits/char) for generic high-entropy secrets; `None` disables them
+    pub fn with_entropy_threshold(mut self, threshold: Option<f64>) -> Self {
+        Arc::make_mut(&mut self.secret_rules).entropy_threshold = threshold;
+        self
+    }
+
+    /// Per-file download limit in bytes (files are analyzed in overlapping windows, so this only bounds
+    /// transfer and time, not memory)
+    pub fn with_max_js_size(mut self, bytes: usize) -> Self {
+        self.max_js_size = bytes.max(bundle_reader::SNIFF_LEN);
+        self
+    }
+
+    /// Additional secret patterns, checked after the built-in ones
+    pub fn with_secret_patterns(mut self, patterns: Vec<SecretPattern>) -> Self {
+        Arc::make_mut(&mut self.secret_rules).custom = patterns;
+        self
+    }
+
     /// Main entry point: Discover and analyze all JavaScript files
     pub async fn analyze_all(&self) -> Result<JsCriticalInfo> {
         tracing::info!("Starting deep JavaScript analysis for {}", self.base_domain);
@@ -156,39 +403,86 @@ impl JsDeepAnalyzer {
             return Ok(JsCriticalInfo::default());
         }
 
-        // Step 2: Analyze all JS files concurrently
+        // Step 2: Analyze all JS files concurrently. Webpack runtimes yield their chunk maps,
+        // which are analyzed in follow-up rounds until no new chunks appear.
         let results = Arc::new(DashMap::new());
-        let mut tasks = JoinSet::new();
+        let mut analyzed: HashSet<String> = HashSet::new();
+        let mut chunk_files: Vec<String> = Vec::new();
+        let mut pending = js_files;
+        let fetched_maps: Arc<DashMap<String, ()>> = Arc::new(DashMap::new());
+
+        while !pending.is_empty() {
+            let chunk_urls = Arc::new(DashMap::new());
+            let mut tasks = JoinSet::new();
+
+            for (idx, js_url) in pending.iter().enumerate() {
+                if analyzed.len() >= MAX_JS_FILES {
+                    tracing::warn!("JS file limit ({}) reached, skipping remaining files", MAX_JS_FILES);
+                    break;
+                }
+                analyzed.insert(js_url.clone());
 
-        for (idx, js_url) in js_files.iter().enumerate() {
-            if idx >= self.max_concurrent && tasks.len() >= self.max_concurrent {
-                // Wait for one task to complete before adding more
-                let _ = tasks.join_next().await;
+                if idx >= self.max_concurrent && tasks.len() >= self.max_concurrent {
+                    // Wait for one task to complete before adding more
+                    let _ = tasks.join_next().await;
+                }
+
+                let client = self.client.clone();
+                let url = js_url.clone();
+                let results = Arc::clone(&results);
+                let chunk_urls = Arc::clone(&chunk_urls);
+                let max_size = self.max_js_size;
+                let base_domain = self.base_domain.clone();
+                let fetched_maps = Arc::clone(&fetched_maps);
+                let source_output = self.source_output.clone();
+                let secret_rules = Arc::clone(&self.secret_rules);
+
+                tasks.spawn(async move {
+                    match Self::fetch_and_analyze_js(&client, &url, &base_domain, max_size, &secret_rules).await {
+                        Ok((info, chunks, maps)) => {
+                            results.insert(url.clone(), info);
+                            for c in chunks {
+                                chunk_urls.insert(c, ());
+                            }
+                            for map in maps {
+                                if fetched_maps.insert(map.clone(), ()).is_some() {
+                                    continue;
+                                }
+                                match Self::analyze_source_map(&client, &map, &base_domain, max_size, source_output.as_deref(), &secret_rules).await {
+                                    Ok(originals) => {
+                                        tracing::info!("Source map {}: {} original files", source_map_label(&map), originals.len());
+                                        for (key, info) in originals {
+                                            results.insert(key, info);
+                                        }
+                                    }
+                                    Err(e) => tracing::debug!("Source map {} unusable: {}", source_map_label(&map), e),
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            tracing::debug!("Failed to analyze {}: {}", url, e);
+                        }
+                    }
+                });
             }
 
-            let client = self.client.clone();
-            let url = js_url.clone();
-            let results = Arc::clone(&results);
-            let max_size = self.max_js_size;
-            let base_domain = self.base_domain.clone();
+            // Wait for all tasks to complete
+            while tasks.join_next().await.is_some() {}
 
-            tasks.spawn(async move {
-                match Self::fetch_and_analyze_js(&client, &url, &base_domain, max_size).await {
-                    Ok(info) => {
-                        results.insert(url.clone(), info);
-                    }
-                    Err(e) => {
-                        tracing::debug!("Failed to analyze {}: {}", url, e);
-                    }
-                }
-            });
+            pending = chunk_urls.iter()
+                .map(|e| e.key().clone())
+                .filter(|u| !analyzed.contains(u))
+                .collect();
+            if !pending.is_empty() {
+                tracing::info!("Webpack chunk maps: {} additional chunks", pending.len());
+            }
+            chunk_files.extend(pending.iter().filter(|u| !u.contains(".wasm")).cloned());
         }
 
-        // Wait for all tasks to complete
-        while tasks.join_next().await.is_some() {}
-
         // Step 3: Aggregate all results
-        let aggregated = self.aggregate_results(results);
+        let mut aggregated = self.aggregate_results(results);
+        chunk_files.sort();
+        aggregated.webpack_chunks = chunk_files;
         
         tracing::info!(
             "Deep JS Analysis complete: {} endpoints, {} secrets, {} parameters",
@@ -204,9 +498,15 @@ impl JsDeepAnalyzer {
     async fn discover_js_files(&self) -> Result<Vec<String>> {
         let base_url = format!("https://{}", self.base_domain);
         let mut js_files = HashSet::new();
+        // Asset manifests list every bundle, including lazily-loaded ones
+        let mut manifests = vec![
+            format!("{}/asset-manifest.json", base_url),
+            format!("{}/.vite/manifest.json", base_url),
+            format!("{}/manifest.json", base_url),
+        ];
 
         // Fetch root page
-        match self.client.get(&base_url).send().await {
+        match self.client.get(&base_url).send_paced().await {
             Ok(resp) => {
                 if let Ok(body) = resp.text().await {
                     let document = Html::parse_document(&body);
@@ -224,6 +524,13 @@ impl JsDeepAnalyzer {
                         }
                     }
 
+                    // Lazily-loaded bundles referenced from inline module scripts
+                    js_files.extend(webpack::dynamic_chunk_urls(&body, &base_url));
+                    js_files.extend(wasm::wasm_urls(&body, &base_url));
+                    if let Some(build_id) = webpack::next_build_id(&body) {
+                        manifests.push(format!("{}/_next/static/{}/_buildManifest.js", base_url, build_id));
+                    }
+
                     // Also extract JS files referenced in inline scripts and HTML
                     let js_ref_regex = Regex::new(r#"["'`]((?:https?:)?//[^"'`\s]+\.js(?:\?[^"'`\s]*)?|/[^"'`\s]+\.js(?:\?[^"'`\s]*)?)["'`]"#).unwrap();
                     for cap in js_ref_regex.captures_iter(&body) {
@@ -240,6 +547,25 @@ impl JsDeepAnalyzer {
             }
         }
 
+        let fetched = futures::future::join_all(manifests.iter().map(|m| async move {
+            let resp = self.client.get(m).send_paced().await.ok()?;
+            if !resp.status().is_success() {
+                return None;
+            }
+            Some((m, resp.text().await.ok()?))
+        })).await;
+        for (manifest_url, body) in fetched.into_iter().flatten() {
+            let found = if manifest_url.ends_with(".js") {
+                webpack::dynamic_chunk_urls(&body, manifest_url)
+            } else {
+                webpack::manifest_js_urls(&body, manifest_url)
+            };
+            if !found.is_empty() {
+                tracing::info!("{}: {} JS files", manifest_url, found.len());
+            }
+            js_files.extend(found);
+        }
+
         // Also check common JS paths
         let common_paths = vec![
             "/static/js/main.js",
@@ -260,28 +586,225 @@ impl JsDeepAnalyzer {
         Ok(js_files.into_iter().collect())
     }
 
-    /// Fetch and analyze a single JavaScript file
+    /// Fetch and analyze a single JavaScript file. Also returns chunk URLs if it is a webpack runtime
+    /// and the source maps it references (absolute URLs or inline `data:` URIs).
     async fn fetch_and_analyze_js(
         client: &Client,
         js_url: &str,
         base_domain: &str,
         max_size: usize,
-    ) -> Result<JsCriticalInfo> {
-        let resp = client.get(js_url).send().await?;
-        let bytes = resp.bytes().await?;
-        
-        let content = String::from_utf8_lossy(
-            &bytes[..std::cmp::min(bytes.len(), max_size)]
-        ).to_string();
+        rules: &SecretRules,
+    ) -> Result<(JsCriticalInfo, Vec<String>, Vec<String>)> {
+        let resp = client.get(js_url).send_paced().await?;
+        let header_map = resp.headers().get("sourcemap")
+            .or_else(|| resp.headers().get("x-sourcemap"))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let content_type = resp.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let content_length = resp.content_length();
+
+        // Large bundles are scanned window by window as they download. The first bytes are held back
+        // until the content is classified, so binary assets are never decoded as text.
+        let mut info = JsCriticalInfo::default();
+        let mut chunks = Vec::new();
+        let mut reader = WindowedReader::new(bundle_reader::WINDOW_SIZE, bundle_reader::WINDOW_OVERLAP);
+        let mut head: Vec<u8> = Vec::new();
+        let mut sniffed = false;
+        let mut total = 0usize;
+        let mut truncated = false;
+        let mut last_window = String::new();
+        let mut stream = resp.bytes_stream();
+        let scan = |windows: Vec<String>, info: &mut JsCriticalInfo, chunks: &mut Vec<String>, last: &mut String| {
+            for window in windows {
+                chunks.extend(webpack::chunk_urls(&window, js_url));
+                chunks.extend(webpack::dynamic_chunk_urls(&window, js_url));
+                chunks.extend(wasm::wasm_urls(&window, js_url));
+                info.absorb(Self::analyze_js_content(&window, js_url, base_domain, rules));
+                *last = window;
+            }
+        };
+
+        while let Some(piece) = stream.next().await {
+            let piece = piece?;
+            let take = piece.len().min(max_size - total);
+            total += take;
+            truncated = take < piece.len();
+
+            if sniffed {
+                scan(reader.push(&piece[..take]), &mut info, &mut chunks, &mut last_window);
+            } else {
+                head.extend_from_slice(&piece[..take]);
+                if head.len() >= bundle_reader::SNIFF_LEN {
+                    sniffed = true;
+                    if let Some(kind) = bundle_reader::binary_kind(&head, content_type.as_deref()) {
+                        if kind == "wasm" {
+                            // WASM is analyzed whole, up to the same size limit
+                            while total < max_size {
+                                let Some(piece) = stream.next().await else { break };
+                                let piece = piece?;
+                                let take = piece.len().min(max_size - total);
+                                total += take;
+                                head.extend_from_slice(&piece[..take]);
+                            }
+                        }
+                        return Ok((Self::binary_asset_info(js_url, kind, &head, content_length, base_domain, rules), Vec::new(), Vec::new()));
+                    }
+                    scan(reader.push(&std::mem::take(&mut head)), &mut info, &mut chunks, &mut last_window);
+                }
+            }
+            if truncated || total >= max_size {
+                truncated = truncated || content_length.is_some_and(|l| l as usize > max_size);
+                break;
+            }
+        }
+        if !sniffed {
+            if let Some(kind) = bundle_reader::binary_kind(&head, content_type.as_deref()) {
+                return Ok((Self::binary_asset_info(js_url, kind, &head, content_length, base_domain, rules), Vec::new(), Vec::new()));
+            }
+            scan(reader.push(&head), &mut info, &mut chunks, &mut last_window);
+        }
+        scan(reader.finish().into_iter().collect(), &mut info, &mut chunks, &mut last_window);
+        if truncated {
+            tracing::warn!("{} exceeds the {} byte JS size limit, analyzed the first {} bytes", js_url, max_size, total);
+            info.truncated_files.push(js_url.to_string());
+        }
+
+        chunks.sort();
+        chunks.dedup();
+        // sourceMappingURL sits at the end of the file, so the last window carries it
+        let maps = Self::source_map_refs(&last_window, js_url, header_map.as_deref());
+        Ok((info, chunks, maps))
+    }
+
+    /// Non-text asset: WASM modules get their data strings analyzed, everything else is only recorded
+    fn binary_asset_info(url: &str, kind: &str, bytes: &[u8], size: Option<u64>, base_domain: &str, rules: &SecretRules) -> JsCriticalInfo {
+        if kind == "wasm" {
+            return Self::analyze_wasm(bytes, url, base_domain, rules);
+        }
+        tracing::info!("Skipping binary asset {} ({})", url, kind);
+        JsCriticalInfo {
+            binary_assets: vec![BinaryAsset { url: url.to_string(), kind: kind.to_string(), size }],
+            ..Default::default()
+        }
+    }
+
+    /// Run the JS extractors over the strings of a WASM module's data section. Endpoint patterns need
+    /// call syntax (`fetch("...")`), which compiled code lacks, so bare URLs and API paths among the
+    /// strings are taken as endpoints too.
+    fn analyze_wasm(module: &[u8], url: &str, base_domain: &str, rules: &SecretRules) -> JsCriticalInfo {
+        let strings = wasm::extract_strings(module);
+        let content = strings.join("\n");
+        let mut info = Self::analyze_js_content(&content, url, base_domain, rules);
+
+        let candidates = wasm::endpoint_candidates(&strings);
+        for candidate in &candidates {
+            if Self::is_valid_endpoint(candidate) && !info.endpoints.iter().any(|e| &e.url == candidate) {
+                info.endpoints.push(ApiEndpoint {
+                    url: candidate.clone(),
+                    method: "GET".to_string(),
+                    source_file: url.to_string(),
+                    context: "string in WASM data section".to_string(),
+                });
+            }
+        }
+        tracing::info!("WASM module {}: {} strings, {} endpoint candidates", url, strings.len(), candidates.len());
+        info.wasm_modules.push(wasm::WasmModule {
+            url: url.to_string(),
+            size: module.len(),
+            strings: strings.len(),
+            endpoints: candidates.len(),
+        });
+        info
+    }
+
+    /// Source maps for a JS file: the `sourceMappingURL` comment, the SourceMap header,
+    /// or `<file>.map` as a guess when neither is present
+    fn source_map_refs(content: &str, js_url: &str, header_map: Option<&str>) -> Vec<String> {
+        let re = Regex::new(r#"[#@]\s*sourceMappingURL=([^\s'"*]+)"#).unwrap();
+        let mut refs: Vec<String> = re.captures_iter(content)
+            .map(|c| c[1].to_string())
+            .chain(header_map.map(|s| s.to_string()))
+            .collect();
+
+        if refs.is_empty() {
+            let base = js_url.split(['?', '#']).next().unwrap_or(js_url);
+            if base.ends_with(".js") {
+                refs.push(format!("{}.map", base));
+            }
+        }
+
+        let Ok(base) = Url::parse(js_url) else { return vec![] };
+        let mut out: Vec<String> = refs.into_iter()
+            .filter_map(|r| if r.starts_with("data:") { Some(r) } else { base.join(&r).ok().map(|u| u.to_string()) })
+            .collect();
+        out.dedup();
+        out
+    }
+
+    /// Fetch a source map, rebuild its first-party original sources and analyze each of them
+    async fn analyze_source_map(
+        client: &Client,
+        map_ref: &str,
+        base_domain: &str,
+        max_size: usize,
+        output: Option<&Path>,
+        rules: &SecretRules,
+    ) -> Result<Vec<(String, JsCriticalInfo)>> {
+        let (json, map_label) = if map_ref.starts_with("data:") {
+            let json = source_map::decode_data_uri(map_ref).ok_or_else(|| anyhow::anyhow!("invalid data URI"))?;
+            (json, "inline".to_string())
+        } else {
+            let resp = client.get(map_ref).send_paced().await?;
+            if !resp.status().is_success() {
+                anyhow::bail!("HTTP {}", resp.status());
+            }
+            let bytes = resp.bytes().await?;
+            if bytes.len() > MAX_SOURCE_MAP_SIZE {
+                anyhow::bail!("too large ({} bytes)", bytes.len());
+            }
+            (String::from_utf8_lossy(&bytes).to_string(), map_ref.to_string())
+        };
+
+        let host = Url::parse(&map_label).ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+            .unwrap_or_else(|| base_domain.to_string());
+
+        let mut out = Vec::new();
+        for original in source_map::parse(&json)? {
+            if !source_map::is_first_party(&original.path) {
+                continue;
+            }
+            let key = format!("{}#{}", map_label, original.path);
+            let mut end = original.content.len().min(max_size);
+            while !original.content.is_char_boundary(end) {
+                end -= 1;
+            }
+            let content = &original.content[..end];
+
+            if let Some(dir) = output {
+                let path = paths::host_dir(dir, &host).join(source_map::relative_path(&original.path));
+                if let Some(parent) = path.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                if let Err(e) = std::fs::write(&path, &original.content) {
+                    tracing::debug!("Failed to write {}: {}", path.display(), e);
+                }
+            }
 
-        Ok(Self::analyze_js_content(&content, js_url, base_domain))
+            let mut info = Self::analyze_js_content(content, &key, base_domain, rules);
+            info.original_sources.push(key.clone());
+            out.push((key, info));
+        }
+        Ok(out)
     }
 
     /// Analyze JavaScript content and extract critical information
-    fn analyze_js_content(content: &str, source_file: &str, base_domain: &str) -> JsCriticalInfo {
+    fn analyze_js_content(content: &str, source_file: &str, base_domain: &str, rules: &SecretRules) -> JsCriticalInfo {
         let mut info = JsCriticalInfo::default();
         info.endpoints = Self::extract_endpoints(content, source_file, base_domain);
-        info.secrets = Self::extract_secrets(content, source_file);
+        info.secrets = Self::extract_secrets_with_rules(content, source_file, rules);
         info.domains = Self::extract_domains(content);
         info.parameters = Self::extract_parameters(content, source_file);
         info.websockets = Self::extract_websockets(content);
@@ -293,6 +816,10 @@ impl JsDeepAnalyzer {
         info.integrations = Self::extract_integrations(content, source_file);
         info.source_maps = Self::extract_source_maps(content);
         info.versions = Self::extract_versions(content);
+        info.client_side_issues = Self::extract_client_side_issues(content, source_file);
+        info.oauth_flows = Self::extract_oauth_flows(content, source_file);
+        info.libraries = js_libraries::detect_libraries(content, source_file);
+        info.vulnerable_libraries = js_libraries::find_vulnerable(&info.libraries);
 
         info
     }
@@ -358,7 +885,18 @@ impl JsDeepAnalyzer {
     }
 
     /// Extract secrets, API keys, tokens
-    fn extract_secrets(content: &str, source_file: &str) -> Vec<Secret> {
+    pub fn extract_secrets(content: &str, source_file: &str) -> Vec<Secret> {
+        Self::extract_secrets_with_entropy(content, source_file, Some(entropy::DEFAULT_THRESHOLD))
+    }
+
+    /// Known-format secrets plus, unless `entropy_threshold` is `None`, high-entropy literals
+    /// assigned to credential-like names
+    pub fn extract_secrets_with_entropy(content: &str, source_file: &str, entropy_threshold: Option<f64>) -> Vec<Secret> {
+        Self::extract_secrets_with_rules(content, source_file, &SecretRules { entropy_threshold, custom: Vec::new() })
+    }
+
+    /// Built-in patterns, then user-supplied patterns, then the entropy scanner
+    pub fn extract_secrets_with_rules(content: &str, source_file: &str, rules: &SecretRules) -> Vec<Secret> {
         let mut secrets = Vec::new();
 
         let patterns = vec![
@@ -370,6 +908,9 @@ impl JsDeepAnalyzer {
             (r#"(?i)authorization\s*:\s*["'`]Bearer\s+([^"'`]+)["'`]"#, SecretType::BearerToken),
             // JWT tokens (looks like xxx.yyy.zzz)
             (r#"eyJ[A-Za-z0-9_\-]+\.eyJ[A-Za-z0-9_\-]+\.[A-Za-z0-9_\-]+"#, SecretType::JwtToken),
+            // Stripe secret/restricted keys and Slack tokens
+            (r#"\b[sr]k_live_[0-9A-Za-z]{24,}"#, SecretType::ApiKey),
+            (r#"\bxox[abposr]-[0-9A-Za-z\-]{10,}"#, SecretType::BearerToken),
             // AWS keys
             (r#"(?i)AKIA[0-9A-Z]{16}"#, SecretType::AwsKey),
             (r#"(?i)aws[_-]?secret[_-]?access[_-]?key\s*[:=]\s*["'`]([^"'`]+)["'`]"#, SecretType::AwsKey),
@@ -380,7 +921,7 @@ impl JsDeepAnalyzer {
             // Client secrets
             (r#"(?i)client[_-]?secret\s*[:=]\s*["'`]([A-Za-z0-9_\-]{20,})["'`]"#, SecretType::ClientSecret),
             // Webhook URLs
-            (r#"https://hooks\.slack\.com/services/[A-Z0-9/]+"#, SecretType::WebhookUrl),
+            (r#"https://hooks\.slack\.com/services/[A-Za-z0-9/]+"#, SecretType::WebhookUrl),
             (r#"https://discord\.com/api/webhooks/[0-9]+/[A-Za-z0-9_\-]+"#, SecretType::WebhookUrl),
             // Database URLs
             (r#"(?i)(?:mongodb|mysql|postgresql|postgres)://[^"'\s]+"#, SecretType::DatabaseUrl),
@@ -408,6 +949,9 @@ impl JsDeepAnalyzer {
                                 value: value.to_string(),
                                 source_file: source_file.to_string(),
                                 line_context,
+                                entropy: None,
+                                verification: None,
+                                classification: token_classifier::classify(value),
                             });
                         }
                     }
@@ -415,9 +959,89 @@ impl JsDeepAnalyzer {
             }
         }
 
+        for pattern in &rules.custom {
+            for (offset, value) in pattern.find(content) {
+                if value.is_empty() || Self::is_test_value(value) || secrets.iter().any(|s| s.value == value) {
+                    continue;
+                }
+                secrets.push(Secret {
+                    secret_type: SecretType::Custom(pattern.label()),
+                    value: value.to_string(),
+                    source_file: source_file.to_string(),
+                    line_context: Self::context_around(content, offset, value.len()),
+                    entropy: None,
+                    verification: None,
+                    classification: token_classifier::classify(value),
+                });
+            }
+        }
+
+        // Provider formats the patterns above don't cover
+        for (offset, value) in token_classifier::find_tokens(content) {
+            // Test-mode keys are real credentials, only the mode marker says "test"
+            if Self::is_test_value(&value.replacen("_test_", "_", 1)) || secrets.iter().any(|s| s.value.contains(value) || value.contains(&s.value)) {
+                continue;
+            }
+            let classification = token_classifier::classify(value);
+            let secret_type = match classification.as_ref().map(|c| (c.provider.as_str(), c.kind.as_str())) {
+                Some((_, "private key")) => SecretType::PrivateKey,
+                Some(("aws", _)) => SecretType::AwsKey,
+                Some((_, kind)) if kind.contains("webhook") && value.starts_with("https://") => SecretType::WebhookUrl,
+                Some((_, kind)) if kind.contains("secret") => SecretType::ClientSecret,
+                Some((_, kind)) if kind.contains("token") => SecretType::BearerToken,
+                _ => SecretType::ApiKey,
+            };
+            secrets.push(Secret {
+                secret_type,
+                value: value.to_string(),
+                source_file: source_file.to_string(),
+                line_context: Self::context_around(content, offset, value.len()),
+                entropy: None,
+                verification: None,
+                classification,
+            });
+        }
+
+        if let Some(threshold) = rules.entropy_threshold {
+            for hit in entropy::find_secrets(content, threshold) {
+                // Already reported with a specific type
+                if secrets.iter().any(|s| s.value.contains(&hit.value) || hit.value.contains(&s.value)) {
+                    continue;
+                }
+                if Self::is_test_value(&hit.value) {
+                    continue;
+                }
+                let classification = token_classifier::classify(&hit.value);
+                secrets.push(Secret {
+                    secret_type: SecretType::Generic,
+                    line_context: Self::context_around(content, hit.offset, hit.value.len()),
+                    value: hit.value,
+                    source_file: source_file.to_string(),
+                    entropy: Some((hit.entropy * 100.0).round() / 100.0),
+                    verification: None,
+                    classification,
+                });
+            }
+        }
+
         secrets
     }
 
+    /// Line context for a match. Minified bundles are one long line, so only the text around it is kept.
+    fn context_around(content: &str, offset: usize, len: usize) -> String {
+        let line_start = content[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = content[offset..].find('\n').map(|i| offset + i).unwrap_or(content.len());
+        let mut start = offset.saturating_sub(80).max(line_start);
+        while !content.is_char_boundary(start) {
+            start += 1;
+        }
+        let mut end = (offset + len + 20).min(line_end);
+        while !content.is_char_boundary(end) {
+            end += 1;
+        }
+        content[start..end].trim().to_string()
+    }
+
     /// Extract subdomains and external domains
     fn extract_domains(content: &str) -> Vec<String> {
         let mut domains = HashSet::new();
@@ -710,6 +1334,116 @@ impl JsDeepAnalyzer {
         source_maps.into_iter().collect()
     }
 
+    /// Flag token persistence in web storage, postMessage handlers without origin checks and DOM/code sinks
+    fn extract_client_side_issues(content: &str, source_file: &str) -> Vec<ClientSideIssue> {
+        // Minified bundles can contain hundreds of sinks - keep the report readable
+        const MAX_PER_KIND: usize = 20;
+        let mut issues = Vec::new();
+
+        // Web storage holding credentials; the cap counts sensitive keys only, so harmless writes
+        // earlier in a bundle can't crowd them out
+        for re in STORAGE_WRITE_RES.iter() {
+            for cap in re.captures_iter(content).filter(|c| SENSITIVE_STORAGE_KEY_RE.is_match(&c[2])).take(MAX_PER_KIND) {
+                let key = &cap[2];
+                let m = cap.get(0).unwrap();
+                issues.push(ClientSideIssue {
+                    issue_type: ClientSideIssueType::StorageTokenPersistence,
+                    detail: format!("{}[{}]", &cap[1], key),
+                    source_file: source_file.to_string(),
+                    context: Self::snippet(content, m.start(), m.end()),
+                });
+            }
+        }
+
+        // postMessage receivers: look at the handler body for any origin comparison
+        let listeners: Vec<_> = MESSAGE_LISTENER_RE.find_iter(content).take(MAX_PER_KIND).collect();
+        for (i, m) in listeners.iter().enumerate() {
+            // The handler body ends at the next listener at the latest
+            let next_start = listeners.get(i + 1).map(|n| n.start()).unwrap_or(content.len());
+            let mut window_end = std::cmp::min(next_start, m.end() + 600);
+            while !content.is_char_boundary(window_end) {
+                window_end -= 1;
+            }
+            let handler = &content[m.end()..window_end];
+            if !handler.contains("origin") {
+                issues.push(ClientSideIssue {
+                    issue_type: ClientSideIssueType::PostMessageNoOriginCheck,
+                    detail: "message listener without origin check".to_string(),
+                    source_file: source_file.to_string(),
+                    context: Self::snippet(content, m.start(), m.end()),
+                });
+            }
+        }
+
+        for (name, re) in SINK_RES.iter() {
+            for m in re.find_iter(content).take(MAX_PER_KIND) {
+                issues.push(ClientSideIssue {
+                    issue_type: ClientSideIssueType::DangerousSink,
+                    detail: name.to_string(),
+                    source_file: source_file.to_string(),
+                    context: Self::snippet(content, m.start(), m.end()),
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Authorization URLs (`.../authorize?client_id=...`) and OAuth client configs
+    /// (`authorizationEndpoint`/`authUrl` with `clientId`, `redirectUri`, `responseType` nearby)
+    pub fn extract_oauth_flows(content: &str, source_file: &str) -> Vec<OAuthFlow> {
+        let mut flows: Vec<OAuthFlow> = OAUTH_URL_RE.find_iter(content)
+            .filter_map(|m| OAuthFlow::from_url(m.as_str(), source_file))
+            .take(20)
+            .collect();
+
+        for cap in OAUTH_ENDPOINT_RE.captures_iter(content).take(20) {
+            let endpoint = &cap[1];
+            if flows.iter().any(|f| f.url.as_deref() == Some(endpoint)) {
+                continue;
+            }
+            let m = cap.get(0).unwrap();
+            // Client settings sit in the same config object, close to the endpoint
+            let mut from = m.start().saturating_sub(1000);
+            while !content.is_char_boundary(from) {
+                from -= 1;
+            }
+            let mut to = std::cmp::min(content.len(), m.end() + 1000);
+            while !content.is_char_boundary(to) {
+                to += 1;
+            }
+            let window = &content[from..to];
+            let near = |re: &Regex| re.captures(window).map(|c| c[1].to_string());
+            let mut flow = OAuthFlow::from_url(endpoint, source_file).unwrap_or_else(|| OAuthFlow {
+                authorization_endpoint: endpoint.to_string(),
+                client_id: None,
+                redirect_uri: None,
+                response_type: None,
+                url: None,
+                source_file: source_file.to_string(),
+            });
+            flow.client_id = flow.client_id.or_else(|| near(&OAUTH_CLIENT_ID_RE));
+            flow.redirect_uri = flow.redirect_uri.or_else(|| near(&OAUTH_REDIRECT_URI_RE));
+            flow.response_type = flow.response_type.or_else(|| near(&OAUTH_RESPONSE_TYPE_RE));
+            flows.push(flow);
+        }
+        flows.dedup();
+        flows
+    }
+
+    /// Up to 80 chars of code around a match, safe for minified single-line bundles
+    fn snippet(content: &str, start: usize, end: usize) -> String {
+        let mut from = start.saturating_sub(80);
+        while !content.is_char_boundary(from) {
+            from -= 1;
+        }
+        let mut to = std::cmp::min(content.len(), end + 80);
+        while !content.is_char_boundary(to) {
+            to += 1;
+        }
+        content[from..to].replace('\n', " ").trim().to_string()
+    }
+
     /// Extract version information
     fn extract_versions(content: &str) -> HashMap<String, String> {
         let mut versions = HashMap::new();
@@ -749,23 +1483,7 @@ impl JsDeepAnalyzer {
         let mut aggregated = JsCriticalInfo::default();
 
         for entry in results.iter() {
-            let info = entry.value();
-            aggregated.endpoints.extend(info.endpoints.clone());
-            aggregated.secrets.extend(info.secrets.clone());
-            aggregated.domains.extend(info.domains.clone());
-            aggregated.parameters.extend(info.parameters.clone());
-            aggregated.websockets.extend(info.websockets.clone());
-            aggregated.graphql.extend(info.graphql.clone());
-            aggregated.routes.extend(info.routes.clone());
-            aggregated.cloud_storage.extend(info.cloud_storage.clone());
-            aggregated.emails.extend(info.emails.clone());
-            aggregated.comments.extend(info.comments.clone());
-            aggregated.integrations.extend(info.integrations.clone());
-            aggregated.source_maps.extend(info.source_maps.clone());
-            
-            for (k, v) in &info.versions {
-                aggregated.versions.entry(k.clone()).or_insert_with(|| v.clone());
-            }
+            aggregated.absorb(entry.value().clone());
         }
 
         // Deduplicate
@@ -790,6 +1508,35 @@ impl JsDeepAnalyzer {
         aggregated.routes.sort();
         aggregated.routes.dedup();
 
+        aggregated.client_side_issues.sort_by(|a, b| (&a.source_file, &a.context, &a.detail).cmp(&(&b.source_file, &b.context, &b.detail)));
+        aggregated.client_side_issues.dedup_by(|a, b| a.source_file == b.source_file && a.context == b.context && a.detail == b.detail);
+
+        js_libraries::dedup_libraries(&mut aggregated.libraries);
+
+        aggregated.original_sources.sort();
+
+        aggregated.comments.sort();
+        aggregated.comments.dedup();
+        aggregated.source_maps.sort();
+        aggregated.source_maps.dedup();
+        aggregated.cloud_storage.sort_by(|a, b| a.bucket_url.cmp(&b.bucket_url));
+        aggregated.cloud_storage.dedup_by(|a, b| a.bucket_url == b.bucket_url);
+        aggregated.integrations.sort_by(|a, b| (&a.service, &a.identifier).cmp(&(&b.service, &b.identifier)));
+        aggregated.integrations.dedup_by(|a, b| a.service == b.service && a.identifier == b.identifier);
+        aggregated.graphql.sort_by(|a, b| (&a.endpoint, &a.source_file).cmp(&(&b.endpoint, &b.source_file)));
+        aggregated.graphql.dedup_by(|a, b| a.endpoint == b.endpoint && a.source_file == b.source_file && a.queries == b.queries);
+        aggregated.binary_assets.sort_by(|a, b| a.url.cmp(&b.url));
+        aggregated.binary_assets.dedup_by(|a, b| a.url == b.url);
+        aggregated.truncated_files.sort();
+        aggregated.truncated_files.dedup();
+        aggregated.wasm_modules.sort_by(|a, b| a.url.cmp(&b.url));
+        aggregated.wasm_modules.dedup_by(|a, b| a.url == b.url);
+        aggregated.oauth_flows.sort_by(|a, b| (&a.authorization_endpoint, &a.client_id, &a.url).cmp(&(&b.authorization_endpoint, &b.client_id, &b.url)));
+        aggregated.oauth_flows.dedup_by(|a, b| a.authorization_endpoint == b.authorization_endpoint && a.client_id == b.client_id && a.redirect_uri == b.redirect_uri && a.response_type == b.response_type && a.url == b.url);
+
+        aggregated.vulnerable_libraries.sort_by(|a, b| (&a.name, &a.version, &a.identifiers).cmp(&(&b.name, &b.version, &b.identifiers)));
+        aggregated.vulnerable_libraries.dedup_by(|a, b| a.name == b.name && a.version == b.version && a.identifiers == b.identifiers);
+
         aggregated
     }
 
@@ -837,9 +1584,15 @@ impl JsDeepAnalyzer {
     }
 }
 
+/// Inline maps are whole data URIs - don't log them
+fn source_map_label(map: &str) -> &str {
+    if map.starts_with("data:") { "inline" } else { map }
+}
+
 impl Default for JsCriticalInfo {
     fn default() -> Self {
         Self {
+            schema_version: crate::output::schema::SCHEMA_VERSION,
             endpoints: Vec::new(),
             secrets: Vec::new(),
             domains: Vec::new(),
@@ -853,6 +1606,42 @@ impl Default for JsCriticalInfo {
             integrations: Vec::new(),
             source_maps: Vec::new(),
             versions: HashMap::new(),
+            client_side_issues: Vec::new(),
+            libraries: Vec::new(),
+            vulnerable_libraries: Vec::new(),
+            webpack_chunks: Vec::new(),
+            original_sources: Vec::new(),
+            binary_assets: Vec::new(),
+            truncated_files: Vec::new(),
+            wasm_modules: Vec::new(),
+            oauth_flows: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_side_issues() {
+        let js = r#"localStorage.setItem("access_token",t);localStorage.setItem("theme","dark");
+window.addEventListener("message",function(e){el.innerHTML=e.data});
+window.addEventListener("message",function(e){if(e.origin!==ORIGIN)return;run(e.data)});"#;
+        let issues = JsDeepAnalyzer::extract_client_side_issues(js, "app.js");
+        let count = |t: ClientSideIssueType| issues.iter().filter(|i| i.issue_type == t).count();
+        assert_eq!(count(ClientSideIssueType::StorageTokenPersistence), 1);
+        assert_eq!(count(ClientSideIssueType::PostMessageNoOriginCheck), 1);
+        assert_eq!(count(ClientSideIssueType::DangerousSink), 1);
+    }
+
+    #[test]
+    fn test_storage_cap_counts_sensitive_keys_only() {
+        let mut js: String = (0..30).map(|i| format!("localStorage.setItem(\"pref{}\",v);", i)).collect();
+        js.push_str("sessionStorage.setItem(\"id_token\",t);");
+        let issues = JsDeepAnalyzer::extract_client_side_issues(&js, "vendor.js");
+        let storage: Vec<_> = issues.iter().filter(|i| i.issue_type == ClientSideIssueType::StorageTokenPersistence).collect();
+        assert_eq!(storage.len(), 1);
+        assert_eq!(storage[0].detail, "sessionStorage[id_token]");
+    }
+}
@@ -1,6 +1,6 @@
+use crate::http_client::Paced;
 use anyhow::Result;
 use regex::Regex;
-use reqwest::Client;
 use scraper::{Html, Selector};
 use url::Url;
 
@@ -9,7 +9,7 @@ pub async fn fetch_and_extract(domain: &str) -> Result<Vec<String>> {
     let base = format!("https://{}", domain);
     
     // Build a client with reasonable timeouts to prevent hangs
-    let client = match Client::builder()
+    let client = match crate::http_client::builder("gather::js_fisher")
         .timeout(std::time::Duration::from_secs(10))
         .build() {
         Ok(c) => c,
@@ -22,7 +22,7 @@ pub async fn fetch_and_extract(domain: &str) -> Result<Vec<String>> {
     let mut out = Vec::new();
     
     // Fetch root page with error handling
-    let resp = match client.get(&base).send().await {
+    let resp = match client.get(&base).send_paced().await {
         Ok(r) => r,
         Err(e) => {
             tracing::debug!(domain=%domain, error=%e, "js_fisher: failed to fetch root page");
@@ -62,7 +62,7 @@ pub async fn fetch_and_extract(domain: &str) -> Result<Vec<String>> {
                 .unwrap_or_else(|_| src.to_string());
             
             // Fetch JS file with error handling
-            let js_resp = match client.get(&js_url).send().await {
+            let js_resp = match client.get(&js_url).send_paced().await {
                 Ok(r) => r,
                 Err(_) => continue, // Skip this JS file on error
             };
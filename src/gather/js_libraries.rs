@@ -0,0 +1,218 @@
+//! Client-side library detection for JavaScript bundles
+//! Finds bundled npm packages (banners, package.json leaks, webpack/pnpm module paths, CDN URLs)
+//! and matches them against a built-in advisory list derived from retire.js data
+
+use crate::output::clean_reporter::Severity;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// A library found in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct JsLibrary {
+    pub name: String,
+    /// Not every detection method yields a version (e.g. webpack module headers)
+    pub version: Option<String>,
+    pub source_file: String,
+    /// How it was detected: banner, package.json, webpack-module, pnpm-path, cdn-path
+    pub detection: String,
+}
+
+/// A detected library version covered by a known advisory
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VulnerableLibrary {
+    pub name: String,
+    pub version: String,
+    pub severity: Severity,
+    pub identifiers: Vec<String>,
+    pub summary: String,
+    pub source_file: String,
+}
+
+struct Advisory {
+    library: &'static str,
+    at_or_above: Option<&'static str>,
+    below: &'static str,
+    severity: Severity,
+    identifiers: &'static [&'static str],
+    summary: &'static str,
+}
+
+const ADVISORIES: &[Advisory] = &[
+    Advisory { library: "jquery", at_or_above: None, below: "1.9.0", severity: Severity::Medium, identifiers: &["CVE-2012-6708"], summary: "XSS via selector string starting with non-'<' HTML" },
+    Advisory { library: "jquery", at_or_above: Some("1.4.0"), below: "3.0.0", severity: Severity::Medium, identifiers: &["CVE-2015-9251"], summary: "XSS when cross-domain ajax responses are executed as script" },
+    Advisory { library: "jquery", at_or_above: None, below: "3.4.0", severity: Severity::Medium, identifiers: &["CVE-2019-11358"], summary: "Prototype pollution in jQuery.extend(true, ...)" },
+    Advisory { library: "jquery", at_or_above: Some("1.2.0"), below: "3.5.0", severity: Severity::Medium, identifiers: &["CVE-2020-11022", "CVE-2020-11023"], summary: "XSS in htmlPrefilter when passing HTML to DOM manipulation methods" },
+    Advisory { library: "jquery-ui", at_or_above: None, below: "1.13.0", severity: Severity::Medium, identifiers: &["CVE-2021-41182", "CVE-2021-41183", "CVE-2021-41184"], summary: "XSS via untrusted option values (altField, *Text, of)" },
+    Advisory { library: "jquery-ui", at_or_above: None, below: "1.13.2", severity: Severity::Medium, identifiers: &["CVE-2022-31160"], summary: "XSS in checkboxradio label refresh" },
+    Advisory { library: "angular.js", at_or_above: None, below: "1.8.0", severity: Severity::Medium, identifiers: &["CVE-2020-7676"], summary: "XSS via <option> elements in <select> inside jqLite/jQuery" },
+    Advisory { library: "angular.js", at_or_above: None, below: "1.7.9", severity: Severity::High, identifiers: &["CVE-2019-10768"], summary: "Prototype pollution in merge()" },
+    Advisory { library: "lodash", at_or_above: None, below: "4.17.12", severity: Severity::High, identifiers: &["CVE-2019-10744"], summary: "Prototype pollution in defaultsDeep" },
+    Advisory { library: "lodash", at_or_above: None, below: "4.17.21", severity: Severity::High, identifiers: &["CVE-2021-23337"], summary: "Command injection via template" },
+    Advisory { library: "underscore", at_or_above: Some("1.3.2"), below: "1.12.1", severity: Severity::High, identifiers: &["CVE-2021-23358"], summary: "Arbitrary code execution via template" },
+    Advisory { library: "moment", at_or_above: None, below: "2.29.2", severity: Severity::High, identifiers: &["CVE-2022-24785"], summary: "Path traversal in locale loading" },
+    Advisory { library: "moment", at_or_above: Some("2.18.0"), below: "2.29.4", severity: Severity::High, identifiers: &["CVE-2022-31129"], summary: "ReDoS in RFC2822 date parsing" },
+    Advisory { library: "handlebars", at_or_above: None, below: "4.7.7", severity: Severity::Critical, identifiers: &["CVE-2021-23369", "CVE-2021-23383"], summary: "Remote code execution when compiling untrusted templates" },
+    Advisory { library: "bootstrap", at_or_above: None, below: "3.4.1", severity: Severity::Medium, identifiers: &["CVE-2019-8331"], summary: "XSS in tooltip/popover data-template" },
+    Advisory { library: "bootstrap", at_or_above: Some("4.0.0"), below: "4.3.1", severity: Severity::Medium, identifiers: &["CVE-2019-8331"], summary: "XSS in tooltip/popover data-template" },
+    Advisory { library: "vue", at_or_above: None, below: "2.5.17", severity: Severity::Medium, identifiers: &["retire-vue-xss"], summary: "Potential XSS in server-rendered templates" },
+    Advisory { library: "dompurify", at_or_above: None, below: "2.0.17", severity: Severity::Medium, identifiers: &["CVE-2020-26870"], summary: "Mutation XSS bypass" },
+    Advisory { library: "dompurify", at_or_above: None, below: "2.5.4", severity: Severity::High, identifiers: &["CVE-2024-45801"], summary: "Nesting-based mXSS and prototype pollution bypass" },
+    Advisory { library: "axios", at_or_above: None, below: "0.21.1", severity: Severity::Medium, identifiers: &["CVE-2020-28168"], summary: "SSRF via redirect to localhost" },
+    Advisory { library: "axios", at_or_above: Some("0.8.1"), below: "1.6.0", severity: Severity::Medium, identifiers: &["CVE-2023-45857"], summary: "XSRF-TOKEN leaked to third-party hosts" },
+    Advisory { library: "marked", at_or_above: None, below: "4.0.10", severity: Severity::Medium, identifiers: &["CVE-2022-21680", "CVE-2022-21681"], summary: "ReDoS in block and inline parsing" },
+    Advisory { library: "next", at_or_above: Some("13.4.0"), below: "14.1.1", severity: Severity::High, identifiers: &["CVE-2024-34351"], summary: "SSRF in Server Actions via Host header" },
+    Advisory { library: "next", at_or_above: Some("11.1.4"), below: "14.2.25", severity: Severity::Critical, identifiers: &["CVE-2025-29927"], summary: "Middleware authorization bypass via x-middleware-subrequest" },
+];
+
+/// Banner comments: `/*! jQuery v3.4.1 |`, `/** @license DOMPurify 2.3.3`, `/*! jQuery UI - v1.12.1`
+static BANNER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"/\*[!*]\s*(?:@license\s+)?([A-Za-z][\w.]*(?:[ -][A-Z][\w.]*)?)\s+(?:-\s+)?v?(\d+\.\d+\.\d+(?:-[\w.]+)?)"#).unwrap()
+});
+/// Leaked package.json: `"name":"lodash","version":"4.17.15"`
+static PACKAGE_JSON_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#""name"\s*:\s*"(@?[\w.-]+(?:/[\w.-]+)?)"\s*,\s*"version"\s*:\s*"(\d+\.\d+\.\d+[^"]*)""#).unwrap()
+});
+/// pnpm store paths keep the version: `node_modules/.pnpm/lodash@4.17.15/`, `@scope+pkg@1.2.3`
+static PNPM_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"node_modules/\.pnpm/(@?[\w.-]+(?:\+[\w.-]+)?)@(\d+\.\d+\.\d+[\w.-]*)"#).unwrap()
+});
+/// CDN URLs: `unpkg.com/axios@0.21.0/`, `cdn.jsdelivr.net/npm/vue@2.5.16/`
+static CDN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:unpkg\.com/|jsdelivr\.net/npm/|esm\.sh/|skypack\.dev/)(@?[\w.-]+(?:/[\w.-]+)?)@(\d+\.\d+\.\d+[\w.-]*)"#).unwrap()
+});
+/// Webpack development bundles name each module: `!*** ./node_modules/axios/lib/axios.js ***!`
+static WEBPACK_MODULE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\./node_modules/(@[\w.-]+/[\w.-]+|[\w.-]+)/"#).unwrap()
+});
+/// Library-specific version markers for minified builds without banners
+static MARKER_RES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    vec![
+        ("jquery", Regex::new(r#"\bjquery\s*[:=]\s*["'](\d+\.\d+\.\d+)["']"#).unwrap()),
+        ("angular.js", Regex::new(r#"AngularJS v(\d+\.\d+\.\d+)"#).unwrap()),
+        ("lodash", Regex::new(r#"(?s)lodash.{0,300}?VERSION\s*=\s*["'](\d+\.\d+\.\d+)["']"#).unwrap()),
+        ("handlebars", Regex::new(r#"Handlebars\.VERSION\s*=\s*["'](\d+\.\d+\.\d+)["']|COMPILER_REVISION[^;]{0,80}VERSION\s*=\s*["'](\d+\.\d+\.\d+)["']"#).unwrap()),
+    ]
+});
+
+/// Detect bundled libraries in a JS file
+pub fn detect_libraries(content: &str, source_file: &str) -> Vec<JsLibrary> {
+    let mut libs = Vec::new();
+    let mut push = |name: &str, version: Option<&str>, detection: &str| {
+        libs.push(JsLibrary {
+            name: normalize_name(name),
+            version: version.map(|v| v.to_string()),
+            source_file: source_file.to_string(),
+            detection: detection.to_string(),
+        });
+    };
+
+    for cap in BANNER_RE.captures_iter(content) {
+        push(&cap[1], Some(&cap[2]), "banner");
+    }
+    for cap in PACKAGE_JSON_RE.captures_iter(content) {
+        push(&cap[1], Some(&cap[2]), "package.json");
+    }
+    for cap in PNPM_RE.captures_iter(content) {
+        push(&cap[1].replace('+', "/"), Some(&cap[2]), "pnpm-path");
+    }
+    for cap in CDN_RE.captures_iter(content) {
+        push(&cap[1], Some(&cap[2]), "cdn-path");
+    }
+    for (name, re) in MARKER_RES.iter() {
+        if let Some(cap) = re.captures(content) {
+            if let Some(v) = cap.iter().skip(1).flatten().next() {
+                push(name, Some(v.as_str()), "marker");
+            }
+        }
+    }
+
+    let mut seen_modules = HashSet::new();
+    for cap in WEBPACK_MODULE_RE.captures_iter(content) {
+        if seen_modules.insert(cap[1].to_string()) {
+            push(&cap[1], None, "webpack-module");
+        }
+    }
+
+    dedup_libraries(&mut libs);
+    libs
+}
+
+/// Keep one entry per name+version, dropping version-less entries when a versioned one exists
+pub fn dedup_libraries(libs: &mut Vec<JsLibrary>) {
+    let versioned: HashSet<String> = libs.iter()
+        .filter(|l| l.version.is_some())
+        .map(|l| l.name.clone())
+        .collect();
+    libs.retain(|l| l.version.is_some() || !versioned.contains(&l.name));
+
+    libs.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    libs.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+}
+
+/// Match detected libraries against the advisory list
+pub fn find_vulnerable(libs: &[JsLibrary]) -> Vec<VulnerableLibrary> {
+    let mut out = Vec::new();
+
+    for lib in libs {
+        let Some(ref version) = lib.version else { continue };
+        for adv in ADVISORIES.iter().filter(|a| a.library == lib.name) {
+            let above = adv.at_or_above.is_none_or(|min| compare_versions(version, min) != Ordering::Less);
+            if above && compare_versions(version, adv.below) == Ordering::Less {
+                out.push(VulnerableLibrary {
+                    name: lib.name.clone(),
+                    version: version.clone(),
+                    severity: adv.severity,
+                    identifiers: adv.identifiers.iter().map(|s| s.to_string()).collect(),
+                    summary: adv.summary.to_string(),
+                    source_file: lib.source_file.clone(),
+                });
+            }
+        }
+    }
+
+    out
+}
+
+/// Map banner spellings onto npm package names
+fn normalize_name(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    match lower.as_str() {
+        "jquery ui" | "jquery-ui" | "jqueryui" => "jquery-ui".to_string(),
+        "angularjs" | "angular" | "angular.js" => "angular.js".to_string(),
+        "vue.js" | "vuejs" => "vue".to_string(),
+        "moment.js" | "momentjs" => "moment".to_string(),
+        "underscore.js" => "underscore".to_string(),
+        "handlebars.js" => "handlebars".to_string(),
+        "next.js" => "next".to_string(),
+        _ => lower,
+    }
+}
+
+/// Semver-style comparison; a pre-release sorts before its release ("3.0.0-beta" < "3.0.0")
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> (Vec<u64>, bool) {
+        let (core, pre) = match v.split_once('-') {
+            Some((c, _)) => (c, true),
+            None => (v, false),
+        };
+        let nums = core.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+        (nums, pre)
+    };
+
+    let (na, pa) = split(a);
+    let (nb, pb) = split(b);
+    for i in 0..na.len().max(nb.len()) {
+        let x = na.get(i).copied().unwrap_or(0);
+        let y = nb.get(i).copied().unwrap_or(0);
+        match x.cmp(&y) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    // Same core version: pre-release < release
+    pb.cmp(&pa)
+}
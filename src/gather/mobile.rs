@@ -0,0 +1,189 @@
+//! APK/IPA static analysis (`--apk <file>`)
+//! Mobile apps talk to the same backend as the web app, often to endpoints the web app never uses.
+//! The archive is unpacked in memory; strings from DEX/native code, compiled resources (UTF-8 and
+//! UTF-16) and bundled assets are mined for API base URLs, endpoint paths, secrets and certificate pins.
+
+use crate::gather::js_deep_analyzer::{JsDeepAnalyzer, Secret, SecretRules};
+use crate::gather::wasm;
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::Path;
+use url::Url;
+
+/// OkHttp `CertificatePinner`, Retrofit configs: `sha256/AAAA...=`
+static PIN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(sha(?:256|1)/[A-Za-z0-9+/]{27,43}=)").unwrap());
+/// A bare base64 SHA-256 digest, as in `network_security_config.xml` `<pin>` or TrustKit plists
+static DIGEST_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9+/]{43}=$").unwrap());
+
+/// Entries larger than this are skipped
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+const MIN_STRING_LEN: usize = 6;
+
+/// Hosts from XML namespaces, DTDs and SDK boilerplate
+const NOISE_HOSTS: &[&str] = &[
+    "schemas.android.com", "www.w3.org", "www.apple.com", "ns.adobe.com", "xmlpull.org", "www.xmlpull.org",
+    "schemas.microsoft.com", "schemas.xmlsoap.org", "www.googleapis.com", "goo.gl", "developer.android.com",
+    "play.google.com", "issuetracker.google.com", "xml.org", "www.example.com", "example.com", "localhost",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificatePin {
+    /// `sha256/<base64>` (bare digests are prefixed)
+    pub pin: String,
+    pub source_file: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MobileAnalysis {
+    pub file: String,
+    /// `android`, `ios` or `unknown`
+    pub platform: String,
+    pub entries_scanned: usize,
+    /// Scheme and host of every backend URL (`https://api.example.com`)
+    pub base_urls: Vec<String>,
+    /// Full URLs and API paths
+    pub endpoints: Vec<String>,
+    pub secrets: Vec<Secret>,
+    pub certificate_pins: Vec<CertificatePin>,
+}
+
+impl MobileAnalysis {
+    /// Candidate URLs for the scan: endpoints on the target or its subdomains, and relative API paths
+    /// resolved against the target
+    pub fn seed_urls(&self, domain: &str) -> Vec<String> {
+        let base = format!("https://{}", domain);
+        let mut out = BTreeSet::new();
+        for e in &self.endpoints {
+            if e.starts_with('/') {
+                out.insert(format!("{}{}", base, e));
+            } else if let Some(host) = Url::parse(e).ok().and_then(|u| u.host_str().map(str::to_lowercase)) {
+                if host == domain || host.ends_with(&format!(".{}", domain)) {
+                    out.insert(e.clone());
+                }
+            }
+        }
+        out.into_iter().collect()
+    }
+}
+
+/// Unpack and analyze an APK or IPA
+pub fn analyze(path: &Path, rules: &SecretRules) -> Result<MobileAnalysis> {
+    let file = std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).with_context(|| format!("{} is not an APK/IPA archive", path.display()))?;
+    let archive_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    let platform = if names.iter().any(|n| n == "AndroidManifest.xml" || n.ends_with(".dex")) {
+        "android"
+    } else if names.iter().any(|n| n.starts_with("Payload/")) {
+        "ios"
+    } else {
+        "unknown"
+    };
+
+    let mut analysis = MobileAnalysis { file: archive_name.clone(), platform: platform.to_string(), ..Default::default() };
+    let mut endpoints = BTreeSet::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() || entry.size() > MAX_ENTRY_SIZE || !is_interesting(entry.name()) {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        if let Err(e) = (&mut entry).take(MAX_ENTRY_SIZE).read_to_end(&mut bytes) {
+            tracing::debug!("{}: {} unreadable: {}", archive_name, name, e);
+            continue;
+        }
+        analysis.entries_scanned += 1;
+
+        let strings = extract_strings(&bytes);
+        let source = format!("{}!{}", archive_name, name);
+        endpoints.extend(wasm::endpoint_candidates(&strings));
+
+        let content = strings.join("\n");
+        analysis.secrets.extend(JsDeepAnalyzer::extract_secrets_with_rules(&content, &source, rules));
+
+        for cap in PIN_RE.captures_iter(&content) {
+            analysis.certificate_pins.push(CertificatePin { pin: cap[1].to_string(), source_file: source.clone() });
+        }
+        // Pin sets list bare digests next to their config keys
+        if name.contains("network_security_config") || content.contains("TSKPublicKeyHashes") || content.contains("pin-set") {
+            for s in strings.iter().filter(|s| DIGEST_RE.is_match(s)) {
+                analysis.certificate_pins.push(CertificatePin { pin: format!("sha256/{}", s), source_file: source.clone() });
+            }
+        }
+    }
+
+    analysis.endpoints = endpoints.into_iter()
+        .filter(|e| {
+            Url::parse(e).ok()
+                .map(|u| u.host_str().is_some_and(|h| !NOISE_HOSTS.contains(&h)))
+                .unwrap_or(true)
+        })
+        .collect();
+    analysis.base_urls = analysis.endpoints.iter()
+        .filter_map(|e| Url::parse(e).ok())
+        .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+        .map(|u| u.origin().ascii_serialization())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    analysis.secrets.sort_by(|a, b| a.value.cmp(&b.value));
+    analysis.secrets.dedup_by(|a, b| a.value == b.value);
+    analysis.certificate_pins.sort_by(|a, b| a.pin.cmp(&b.pin));
+    analysis.certificate_pins.dedup_by(|a, b| a.pin == b.pin);
+    Ok(analysis)
+}
+
+/// Code, compiled resources and text assets; images, fonts and media are skipped
+fn is_interesting(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let file = lower.rsplit('/').next().unwrap_or(&lower);
+    if file.ends_with(".dex") || file.ends_with(".so") || file == "resources.arsc" || file == "androidmanifest.xml" {
+        return true;
+    }
+    if lower.starts_with("res/") && !file.ends_with(".xml") && !lower.starts_with("res/raw/") {
+        return false;
+    }
+    const TEXT: &[&str] = &[
+        ".xml", ".json", ".js", ".jsbundle", ".bundle", ".plist", ".properties", ".txt", ".html", ".strings",
+        ".cfg", ".conf", ".yaml", ".yml", ".env",
+    ];
+    if TEXT.iter().any(|ext| file.ends_with(ext)) {
+        return true;
+    }
+    // iOS app executable: Payload/App.app/App (no extension)
+    let parts: Vec<&str> = name.split('/').collect();
+    parts.len() == 3 && parts[0] == "Payload" && parts[1].ends_with(".app") && !parts[2].contains('.')
+}
+
+/// ASCII runs plus UTF-16LE runs (Android binary XML and resource tables store most strings as UTF-16)
+pub fn extract_strings(bytes: &[u8]) -> Vec<String> {
+    let mut out: Vec<String> = bytes.split(|&b| !(0x20..0x7F).contains(&b))
+        .filter(|run| run.len() >= MIN_STRING_LEN)
+        .map(|run| String::from_utf8_lossy(run).into_owned())
+        .collect();
+
+    for offset in 0..2 {
+        let mut current = String::new();
+        for pair in bytes[offset.min(bytes.len())..].chunks_exact(2) {
+            if pair[1] == 0 && (0x20..0x7F).contains(&pair[0]) {
+                current.push(pair[0] as char);
+            } else {
+                if current.len() >= MIN_STRING_LEN {
+                    out.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            }
+        }
+        if current.len() >= MIN_STRING_LEN {
+            out.push(current);
+        }
+    }
+    out
+}
@@ -1,3 +1,20 @@
 pub mod wayback;
 pub mod js_fisher;
 pub mod js_deep_analyzer;
+pub mod js_libraries;
+pub mod har;
+pub mod burp;
+pub mod commoncrawl;
+pub mod otx;
+pub mod urlscan;
+pub mod webpack;
+pub mod source_map;
+pub mod github_dorks;
+pub mod cache;
+pub mod service_worker;
+pub mod crawler;
+pub mod entropy;
+pub mod secret_patterns;
+pub mod bundle_reader;
+pub mod wasm;
+pub mod mobile;
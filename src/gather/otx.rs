@@ -0,0 +1,49 @@
+use anyhow::{bail, Result};
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+/// OTX caps the page size at 500; stop after this many pages
+const MAX_PAGES: u32 = 20;
+
+/// Stream URLs AlienVault OTX has observed for `domain` into `tx`. The key is optional but raises rate limits.
+/// Returns the number of URLs sent, or an error if a page failed (the URLs streamed until then stay sent).
+pub async fn stream_otx_urls(domain: &str, api_key: Option<&str>, tx: Sender<String>) -> Result<usize> {
+    let client = crate::http_client::third_party()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+
+    let mut sent = 0;
+    for page in 1..=MAX_PAGES {
+        let url = format!(
+            "https://otx.alienvault.com/api/v1/indicators/domain/{}/url_list?limit=500&page={}",
+            domain, page
+        );
+        let mut req = client.get(&url);
+        if let Some(key) = api_key {
+            req = req.header("X-OTX-API-KEY", key);
+        }
+
+        let resp = req.send().await?;
+        if !resp.status().is_success() {
+            bail!("page {} returned {} after {} URLs", page, resp.status(), sent);
+        }
+        let v: serde_json::Value = resp.json().await?;
+
+        for entry in v["url_list"].as_array().into_iter().flatten() {
+            if let Some(u) = entry["url"].as_str() {
+                if tx.send(u.to_string()).await.is_err() {
+                    return Ok(sent);
+                }
+                sent += 1;
+            }
+        }
+
+        if !v["has_next"].as_bool().unwrap_or(false) {
+            break;
+        }
+    }
+
+    tracing::info!("OTX: {} URLs streamed", sent);
+    Ok(sent)
+}
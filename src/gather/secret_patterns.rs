@@ -0,0 +1,88 @@
+//! User-supplied secret patterns (`--secret-patterns <file>`)
+//! Extra provider regexes for the JS deep analyzer, loaded from TOML or YAML (JSON works too, as YAML),
+//! so new token formats don't need a rebuild:
+//!
+//! ```yaml
+//! patterns:
+//!   - name: acme_api_key
+//!     provider: Acme
+//!     regex: 'acme_(?:live|test)_[A-Za-z0-9]{32}'
+//!   - name: internal_token
+//!     regex: 'X-Internal-Token["'']?\s*[:=]\s*["'']([^"'']{16,})'
+//!     group: 1
+//! ```
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct PatternFile {
+    #[serde(default, alias = "secrets")]
+    patterns: Vec<PatternDef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatternDef {
+    name: String,
+    regex: String,
+    #[serde(default)]
+    provider: Option<String>,
+    /// Capture group holding the secret; defaults to 1 if the regex has groups, else the whole match
+    #[serde(default)]
+    group: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecretPattern {
+    pub name: String,
+    pub provider: Option<String>,
+    pub regex: Regex,
+    pub group: usize,
+}
+
+impl SecretPattern {
+    /// Secret values matched in `content`, with the byte offset of each match
+    pub fn find<'a>(&self, content: &'a str) -> Vec<(usize, &'a str)> {
+        self.regex.captures_iter(content)
+            .filter_map(|c| {
+                let whole = c.get(0)?;
+                let value = c.get(self.group)?.as_str();
+                Some((whole.start(), value))
+            })
+            .collect()
+    }
+
+    /// Label used as the secret type (`Acme: acme_api_key`)
+    pub fn label(&self) -> String {
+        match &self.provider {
+            Some(p) => format!("{}: {}", p, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Load patterns from a `.toml`, `.yaml`/`.yml` or `.json` file
+pub fn load(path: &Path) -> Result<Vec<SecretPattern>> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read secret patterns {}", path.display()))?;
+    let is_toml = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+    parse(&data, is_toml).with_context(|| format!("invalid secret patterns {}", path.display()))
+}
+
+/// Parse a pattern file; `toml` selects TOML, otherwise YAML (which also accepts JSON)
+pub fn parse(data: &str, toml: bool) -> Result<Vec<SecretPattern>> {
+    let file: PatternFile = if toml { toml::from_str(data)? } else { serde_yaml::from_str(data)? };
+    let mut patterns = Vec::new();
+    for def in file.patterns {
+        let regex = Regex::new(&def.regex).with_context(|| format!("pattern '{}' has an invalid regex", def.name))?;
+        let groups = regex.captures_len() - 1;
+        let group = def.group.unwrap_or(if groups > 0 { 1 } else { 0 });
+        if group > groups {
+            bail!("pattern '{}' uses group {} but the regex has {} capture groups", def.name, group, groups);
+        }
+        patterns.push(SecretPattern { name: def.name, provider: def.provider, regex, group });
+    }
+    Ok(patterns)
+}
@@ -0,0 +1,289 @@
+//! Web app manifest and service worker analysis
+//! A SPA's service worker precaches nearly every route and asset the app ships (Workbox `precacheAndRoute`,
+//! CRA `precache-manifest.*.js`, Angular `ngsw.json`) and registers runtime caching rules for its APIs.
+
+use crate::http_client::Paced;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+static MANIFEST_LINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)<link[^>]+rel=["']?manifest["']?[^>]*>"#).unwrap()
+});
+static HREF_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)href=["']?([^"'\s>]+)"#).unwrap());
+static SCRIPT_SRC_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)<script[^>]+src=["']?([^"'\s>]+)"#).unwrap());
+static SW_REGISTER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"serviceWorker\s*\.\s*register\(\s*["'`]([^"'`]+)["'`]"#).unwrap()
+});
+static IMPORT_SCRIPTS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"importScripts\(([^)]*)\)").unwrap());
+static STRING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["'`]([^"'`]+)["'`]"#).unwrap());
+/// Workbox/CRA precache entries: `{url:"/index.html",revision:"..."}` in either key order
+static PRECACHE_URL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{\s*["']?url["']?\s*:\s*["']([^"']+)["']\s*,\s*["']?revision["']?"#).unwrap()
+});
+static PRECACHE_REV_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{\s*["']?revision["']?\s*:\s*(?:["'][^"']*["']|null)\s*,\s*["']?url["']?\s*:\s*["']([^"']+)["']"#).unwrap()
+});
+/// Legacy sw-precache: `precacheConfig = [["index.html","<md5>"], ...]`
+static SW_PRECACHE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\[\s*["']([^"']+)["']\s*,\s*["'][0-9a-f]{32}["']\s*\]"#).unwrap()
+});
+static ROUTE_STRING_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"registerRoute\(\s*["'`]([^"'`]+)["'`]"#).unwrap()
+});
+static ROUTE_REGEX_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"registerRoute\(\s*(?:new RegExp\(\s*)?/((?:\\/|[^/\n])+)/[gimsuy]*").unwrap()
+});
+static PATH_PREFIX_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"pathname\.(?:startsWith|includes)\(\s*["'`](/[^"'`]+)["'`]"#).unwrap()
+});
+
+/// Manifest locations tried when the page doesn't link one
+const MANIFEST_PATHS: &[&str] = &["/manifest.json", "/manifest.webmanifest", "/site.webmanifest"];
+/// Common service worker file names tried when no registration is found
+const SW_PATHS: &[&str] = &["/sw.js", "/service-worker.js", "/serviceworker.js", "/ngsw-worker.js", "/firebase-messaging-sw.js"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServiceWorkerReport {
+    pub manifest_url: Option<String>,
+    /// `start_url`, `scope`, shortcuts, share targets and protocol handlers from the manifest
+    pub manifest_urls: Vec<String>,
+    pub service_workers: Vec<String>,
+    /// Scripts pulled in with `importScripts`
+    pub imported_scripts: Vec<String>,
+    /// Every URL in the precache lists, resolved against the worker
+    pub precache_urls: Vec<String>,
+    /// Runtime caching routes (paths, or regex sources for RegExp routes)
+    pub runtime_routes: Vec<String>,
+}
+
+impl ServiceWorkerReport {
+    pub fn is_empty(&self) -> bool {
+        self.manifest_url.is_none() && self.service_workers.is_empty()
+    }
+
+    /// Concrete URLs worth probing (regex routes are left out)
+    pub fn candidate_urls(&self, base: &str) -> Vec<String> {
+        let Ok(base) = Url::parse(base) else { return vec![] };
+        let mut urls: Vec<String> = self.manifest_urls.iter()
+            .chain(self.precache_urls.iter())
+            .cloned()
+            .chain(self.runtime_routes.iter()
+                .filter(|r| r.starts_with('/') && !r.contains(['\\', '(', '[', '*', '^', '$']))
+                .filter_map(|r| base.join(r).ok().map(|u| u.to_string())))
+            .collect();
+        urls.sort();
+        urls.dedup();
+        urls
+    }
+}
+
+/// Manifest URL linked from a page (`<link rel="manifest" href="...">`)
+pub fn manifest_link(html: &str) -> Option<String> {
+    let tag = MANIFEST_LINK_RE.find(html)?;
+    HREF_RE.captures(tag.as_str()).map(|c| c[1].to_string())
+}
+
+/// URLs declared by a web app manifest, resolved against the manifest's location
+pub fn manifest_urls(manifest: &str, manifest_url: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(manifest_url) else { return vec![] };
+    let Ok(json) = serde_json::from_str::<Value>(manifest) else { return vec![] };
+
+    let mut raw: Vec<&str> = Vec::new();
+    for key in ["start_url", "scope"] {
+        if let Some(s) = json.get(key).and_then(Value::as_str) {
+            raw.push(s);
+        }
+    }
+    for shortcut in json.get("shortcuts").and_then(Value::as_array).into_iter().flatten() {
+        if let Some(s) = shortcut.get("url").and_then(Value::as_str) {
+            raw.push(s);
+        }
+    }
+    if let Some(s) = json.pointer("/share_target/action").and_then(Value::as_str) {
+        raw.push(s);
+    }
+    for handler in json.get("protocol_handlers").and_then(Value::as_array).into_iter().flatten() {
+        if let Some(s) = handler.get("url").and_then(Value::as_str) {
+            // `%s` is the handled URL placeholder
+            raw.push(s.split("%s").next().unwrap_or(s));
+        }
+    }
+    resolve_all(&base, raw)
+}
+
+/// Service worker scripts registered with `navigator.serviceWorker.register(...)`
+pub fn registrations(js: &str) -> Vec<String> {
+    SW_REGISTER_RE.captures_iter(js).map(|c| c[1].to_string()).collect()
+}
+
+/// Scripts loaded with `importScripts(...)`
+pub fn imported_scripts(sw: &str) -> Vec<String> {
+    IMPORT_SCRIPTS_RE.captures_iter(sw)
+        .flat_map(|c| STRING_RE.captures_iter(&c[1]).map(|s| s[1].to_string()).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Precache entries from Workbox, CRA `precache-manifest.*.js` and sw-precache
+pub fn precache_entries(sw: &str) -> Vec<String> {
+    let mut out: Vec<String> = PRECACHE_URL_RE.captures_iter(sw)
+        .chain(PRECACHE_REV_RE.captures_iter(sw))
+        .chain(SW_PRECACHE_RE.captures_iter(sw))
+        .map(|c| c[1].to_string())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Runtime caching routes: string and RegExp `registerRoute` matchers, `pathname.startsWith(...)` checks
+pub fn runtime_routes(sw: &str) -> Vec<String> {
+    let mut out: Vec<String> = ROUTE_STRING_RE.captures_iter(sw)
+        .chain(PATH_PREFIX_RE.captures_iter(sw))
+        .map(|c| c[1].to_string())
+        .chain(ROUTE_REGEX_RE.captures_iter(sw).map(|c| c[1].replace("\\/", "/")))
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Angular `ngsw.json`: asset group URLs are precached files, data group patterns are API routes
+pub fn ngsw_entries(ngsw: &str) -> (Vec<String>, Vec<String>) {
+    let Ok(json) = serde_json::from_str::<Value>(ngsw) else { return (vec![], vec![]) };
+    let collect = |group: &str, key: &str| -> Vec<String> {
+        json.get(group).and_then(Value::as_array).into_iter().flatten()
+            .flat_map(|g| g.get(key).and_then(Value::as_array).into_iter().flatten())
+            .filter_map(|u| u.as_str().map(|s| s.to_string()))
+            .collect()
+    };
+    let mut assets = collect("assetGroups", "urls");
+    if let Some(hashes) = json.get("hashTable").and_then(Value::as_object) {
+        assets.extend(hashes.keys().cloned());
+    }
+    let data = collect("dataGroups", "patterns");
+    (assets, data)
+}
+
+fn resolve_all<'a>(base: &Url, raw: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut out: Vec<String> = raw.into_iter()
+        .filter(|s| !s.is_empty() && !s.starts_with("data:"))
+        .filter_map(|s| base.join(s).ok())
+        .map(|u| u.to_string())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// A 200 with HTML is the SPA fallback, not a worker
+fn looks_like_worker(body: &str) -> bool {
+    let head = body.trim_start();
+    !head.starts_with('<')
+        && (body.contains("addEventListener") || body.contains("importScripts") || body.contains("workbox")
+            || body.contains("self.") || body.contains("caches."))
+}
+
+async fn fetch_text(client: &Client, url: &str) -> Option<String> {
+    let resp = client.get(url).send_paced().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let bytes = resp.bytes().await.ok()?;
+    Some(String::from_utf8_lossy(&bytes[..bytes.len().min(2 * 1024 * 1024)]).into_owned())
+}
+
+/// Fetch the manifest and service workers of a site and extract everything they enumerate
+pub async fn analyze(base_url: &str) -> Result<ServiceWorkerReport> {
+    let client = crate::http_client::builder("gather::service_worker")
+        .timeout(std::time::Duration::from_secs(10))
+        .danger_accept_invalid_certs(true)
+        .build()?;
+    let base = Url::parse(base_url)?;
+    let mut report = ServiceWorkerReport::default();
+
+    let html = fetch_text(&client, base.as_str()).await.unwrap_or_default();
+
+    // Manifest: linked one first, then the usual locations
+    let mut manifest_candidates: Vec<String> = manifest_link(&html)
+        .and_then(|h| base.join(&h).ok())
+        .map(|u| vec![u.to_string()])
+        .unwrap_or_default();
+    manifest_candidates.extend(MANIFEST_PATHS.iter().filter_map(|p| base.join(p).ok()).map(|u| u.to_string()));
+    for url in manifest_candidates {
+        let Some(body) = fetch_text(&client, &url).await else { continue };
+        let Ok(json) = serde_json::from_str::<Value>(&body) else { continue };
+        // Asset manifests are also called manifest.json; a web app manifest has app metadata
+        if json.get("name").is_none() && json.get("short_name").is_none() && json.get("start_url").is_none() {
+            continue;
+        }
+        report.manifest_urls = manifest_urls(&body, &url);
+        report.manifest_url = Some(url);
+        break;
+    }
+
+    // Registrations in inline scripts and same-origin bundles
+    let mut sw_urls: Vec<String> = registrations(&html);
+    for src in SCRIPT_SRC_RE.captures_iter(&html).map(|c| c[1].to_string()).take(10) {
+        let Ok(script_url) = base.join(&src) else { continue };
+        if script_url.host_str() != base.host_str() {
+            continue;
+        }
+        if let Some(js) = fetch_text(&client, script_url.as_str()).await {
+            sw_urls.extend(registrations(&js));
+        }
+    }
+    if sw_urls.is_empty() {
+        sw_urls.extend(SW_PATHS.iter().map(|p| p.to_string()));
+    }
+    let mut sw_urls: Vec<String> = sw_urls.iter().filter_map(|u| base.join(u).ok()).map(|u| u.to_string()).collect();
+    sw_urls.sort();
+    sw_urls.dedup();
+
+    for sw_url in sw_urls {
+        let Some(sw) = fetch_text(&client, &sw_url).await else { continue };
+        if !looks_like_worker(&sw) {
+            continue;
+        }
+        let Ok(sw_base) = Url::parse(&sw_url) else { continue };
+        tracing::info!("Service worker found: {}", sw_url);
+        report.service_workers.push(sw_url.clone());
+
+        let mut sources = vec![sw.clone()];
+        for import in imported_scripts(&sw) {
+            let Ok(import_url) = sw_base.join(&import) else { continue };
+            report.imported_scripts.push(import_url.to_string());
+            // Workbox runtime from a CDN has no app data; precache manifests are served next to the worker
+            if import_url.host_str() == sw_base.host_str() {
+                if let Some(js) = fetch_text(&client, import_url.as_str()).await {
+                    sources.push(js);
+                }
+            }
+        }
+
+        for src in &sources {
+            report.precache_urls.extend(resolve_all(&sw_base, precache_entries(src).iter().map(String::as_str)));
+            report.runtime_routes.extend(runtime_routes(src));
+        }
+
+        if sw_url.ends_with("ngsw-worker.js") {
+            if let Some(ngsw) = fetch_text(&client, sw_base.join("ngsw.json")?.as_str()).await {
+                let (assets, data) = ngsw_entries(&ngsw);
+                report.precache_urls.extend(resolve_all(&sw_base, assets.iter().map(String::as_str)));
+                report.runtime_routes.extend(data);
+            }
+        }
+    }
+
+    report.precache_urls.sort();
+    report.precache_urls.dedup();
+    report.runtime_routes.sort();
+    report.runtime_routes.dedup();
+    report.imported_scripts.sort();
+    report.imported_scripts.dedup();
+    Ok(report)
+}
@@ -0,0 +1,97 @@
+//! Source map parsing
+//! Rebuilds the original (unminified) sources embedded in `.map` files via `sourcesContent`
+
+use anyhow::{bail, Result};
+use base64::Engine;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    #[serde(default)]
+    source_root: Option<String>,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    sources_content: Vec<Option<String>>,
+    /// Index maps nest regular maps in sections
+    #[serde(default)]
+    sections: Vec<RawSection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSection {
+    map: Option<RawSourceMap>,
+}
+
+/// One original file recovered from a source map
+#[derive(Debug, Clone)]
+pub struct OriginalSource {
+    /// Path as listed in `sources` (with `sourceRoot` applied)
+    pub path: String,
+    pub content: String,
+}
+
+/// Parse a source map and return every source that has embedded content
+pub fn parse(json: &str) -> Result<Vec<OriginalSource>> {
+    // Some servers prefix maps with the XSSI guard `)]}'`
+    let json = json.trim_start().trim_start_matches(")]}'").trim_start();
+    let map: RawSourceMap = serde_json::from_str(json)?;
+    let mut out = Vec::new();
+    collect(&map, &mut out);
+    if out.is_empty() && map.sources.is_empty() && map.sections.is_empty() {
+        bail!("not a source map");
+    }
+    Ok(out)
+}
+
+fn collect(map: &RawSourceMap, out: &mut Vec<OriginalSource>) {
+    let root = map.source_root.as_deref().unwrap_or("");
+    for (path, content) in map.sources.iter().zip(map.sources_content.iter()) {
+        if let Some(content) = content {
+            let path = if root.is_empty() || path.contains("://") {
+                path.clone()
+            } else {
+                format!("{}/{}", root.trim_end_matches('/'), path)
+            };
+            out.push(OriginalSource { path, content: content.clone() });
+        }
+    }
+    for section in &map.sections {
+        if let Some(m) = &section.map {
+            collect(m, out);
+        }
+    }
+}
+
+/// Dependencies and bundler internals are public code - only the application's own sources are analyzed
+pub fn is_first_party(path: &str) -> bool {
+    let p = path.to_lowercase();
+    !(p.contains("node_modules/")
+        || p.contains("/~/")
+        || p.contains("(webpack)")
+        || p.starts_with("webpack/bootstrap")
+        || p.contains("webpack/runtime")
+        || p.contains("external ")
+        || p.contains("ignored|"))
+}
+
+/// Turn a `sources` entry into a safe relative file path: `webpack://app/./src/api.ts` -> `app/src/api.ts`
+pub fn relative_path(path: &str) -> PathBuf {
+    let path = path.split_once("://").map(|(_, rest)| rest).unwrap_or(path);
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    crate::output::paths::relative_path(path)
+}
+
+/// Decode an inline `data:application/json;base64,...` source map
+pub fn decode_data_uri(uri: &str) -> Option<String> {
+    let rest = uri.strip_prefix("data:")?;
+    let (meta, data) = rest.split_once(',')?;
+    if meta.ends_with(";base64") {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(data.trim()).ok()?;
+        String::from_utf8(bytes).ok()
+    } else {
+        urlencoding::decode(data).ok().map(|s| s.into_owned())
+    }
+}
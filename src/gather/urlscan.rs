@@ -0,0 +1,69 @@
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+const MAX_PAGES: usize = 10;
+
+/// Stream page and request URLs from urlscan.io scans of `domain` into `tx`.
+/// Works without a key at a lower rate limit and result size. Returns the number of URLs sent, or an error
+/// if a search page failed (the URLs streamed until then stay sent).
+pub async fn stream_urlscan_urls(domain: &str, api_key: Option<&str>, tx: Sender<String>) -> Result<usize> {
+    let client = crate::http_client::third_party()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+
+    let query = format!("domain:{}", domain);
+    let size = if api_key.is_some() { "1000" } else { "100" };
+    let mut search_after: Option<String> = None;
+    let mut seen = HashSet::new();
+    let mut sent = 0;
+
+    for _ in 0..MAX_PAGES {
+        let mut params = vec![("q", query.clone()), ("size", size.to_string())];
+        if let Some(ref sa) = search_after {
+            params.push(("search_after", sa.clone()));
+        }
+
+        let mut req = client.get("https://urlscan.io/api/v1/search/").query(&params);
+        if let Some(key) = api_key {
+            req = req.header("API-Key", key);
+        }
+
+        let resp = req.send().await?;
+        if !resp.status().is_success() {
+            bail!("search returned {} after {} URLs", resp.status(), sent);
+        }
+        let v: serde_json::Value = resp.json().await?;
+        let results = v["results"].as_array().cloned().unwrap_or_default();
+        if results.is_empty() {
+            break;
+        }
+
+        for r in &results {
+            for u in [r["page"]["url"].as_str(), r["task"]["url"].as_str()].into_iter().flatten() {
+                if seen.insert(u.to_string()) {
+                    if tx.send(u.to_string()).await.is_err() {
+                        return Ok(sent);
+                    }
+                    sent += 1;
+                }
+            }
+        }
+
+        // Cursor is the sort array of the last hit, comma-joined
+        search_after = results.last()
+            .and_then(|r| r["sort"].as_array())
+            .map(|sort| sort.iter()
+                .map(|s| s.as_str().map(|x| x.to_string()).unwrap_or_else(|| s.to_string()))
+                .collect::<Vec<_>>()
+                .join(","));
+        if search_after.is_none() || !v["has_more"].as_bool().unwrap_or(false) {
+            break;
+        }
+    }
+
+    tracing::info!("urlscan: {} URLs streamed", sent);
+    Ok(sent)
+}
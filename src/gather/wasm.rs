@@ -0,0 +1,166 @@
+//! WebAssembly module analysis
+//! SPAs built with Rust/Go/C++ compile API clients to `.wasm`; their base URLs, paths and keys sit in the
+//! module's data segments as plain bytes. Modules referenced from HTML/JS are downloaded, the printable
+//! strings of the data section are extracted and run through the same extractors as JS.
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// `"app_bg.wasm"`, `new URL('./pkg/app.wasm', import.meta.url)`, `fetch("/main.wasm?v=3")`
+static WASM_REF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"["'`]((?:https?:)?//[^"'`\s]+\.wasm|[^"'`\s:]*\.wasm)(?:\?[^"'`\s]*)?["'`]"#).unwrap()
+});
+/// Absolute URLs inside extracted strings (Rust/Go pack string literals without separators)
+static URL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:https?|wss?)://[A-Za-z0-9.-]+(?::\d{1,5})?(?:/[A-Za-z0-9._~%!$&'()*+,;=:@/{}-]*)?"#).unwrap()
+});
+/// API-looking paths inside extracted strings
+static PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"/(?:api|v[0-9]{1,2}|graphql|rest|auth|oauth2?|internal|admin)(?:/[A-Za-z0-9._~%{}:-]+)*/?"#).unwrap()
+});
+
+/// Shortest byte run kept as a string
+pub const MIN_STRING_LEN: usize = 6;
+
+const DATA_SECTION: u8 = 11;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WasmModule {
+    pub url: String,
+    pub size: usize,
+    /// Printable strings extracted from the data section
+    pub strings: usize,
+    /// Endpoint candidates among them
+    pub endpoints: usize,
+}
+
+/// `.wasm` URLs referenced in HTML or JS, resolved against `base_url`
+pub fn wasm_urls(content: &str, base_url: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(base_url) else { return vec![] };
+    let mut urls: Vec<String> = WASM_REF_RE.captures_iter(content)
+        .filter_map(|c| base.join(&c[1]).ok())
+        .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+        .map(|u| u.to_string())
+        .collect();
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+/// Printable strings from the module's data segments. Falls back to scanning the whole file if the
+/// module can't be parsed (truncated download, unknown extension).
+pub fn extract_strings(module: &[u8]) -> Vec<String> {
+    match data_segments(module) {
+        Ok(segments) if !segments.is_empty() => segments.iter().flat_map(|s| printable_runs(s)).collect(),
+        _ => printable_runs(module),
+    }
+}
+
+/// URLs and API paths found in extracted strings
+pub fn endpoint_candidates(strings: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for s in strings {
+        let urls: Vec<&str> = URL_RE.find_iter(s).map(|m| m.as_str()).collect();
+        out.extend(urls.iter().map(|u| u.to_string()));
+        // Paths outside of the absolute URLs
+        let mut rest = s.clone();
+        for u in &urls {
+            rest = rest.replace(u, " ");
+        }
+        out.extend(PATH_RE.find_iter(&rest).map(|m| m.as_str().to_string()));
+    }
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Raw bytes of every data segment
+pub fn data_segments(module: &[u8]) -> Result<Vec<&[u8]>> {
+    if module.len() < 8 || &module[..4] != b"\0asm" {
+        bail!("not a WebAssembly module");
+    }
+    let mut r = Reader { bytes: module, pos: 8 };
+    let mut segments = Vec::new();
+    while r.pos < module.len() {
+        let id = r.byte()?;
+        let size = r.uleb()? as usize;
+        let end = r.pos.checked_add(size).filter(|&e| e <= module.len());
+        let Some(end) = end else { bail!("section {} runs past the end of the module", id) };
+        if id == DATA_SECTION {
+            let count = r.uleb()?;
+            for _ in 0..count {
+                match r.uleb()? {
+                    0 => r.skip_const_expr()?,
+                    1 => {}
+                    2 => {
+                        r.uleb()?;
+                        r.skip_const_expr()?;
+                    }
+                    flags => bail!("unknown data segment flags {}", flags),
+                }
+                let len = r.uleb()? as usize;
+                segments.push(r.take(len)?);
+            }
+        }
+        r.pos = end;
+    }
+    Ok(segments)
+}
+
+fn printable_runs(bytes: &[u8]) -> Vec<String> {
+    bytes.split(|&b| !(0x20..0x7F).contains(&b) && b != b'\t')
+        .filter(|run| run.len() >= MIN_STRING_LEN)
+        .map(|run| String::from_utf8_lossy(run).into_owned())
+        .collect()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8> {
+        let Some(&b) = self.bytes.get(self.pos) else { bail!("unexpected end of module") };
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let Some(slice) = self.pos.checked_add(len).and_then(|end| self.bytes.get(self.pos..end)) else {
+            bail!("unexpected end of module")
+        };
+        self.pos += len;
+        Ok(slice)
+    }
+
+    /// LEB128; signed values are skipped the same way
+    fn uleb(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            value |= u64::from(b & 0x7F) << shift;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("LEB128 value too long")
+    }
+
+    /// Segment offset: `i32.const`/`i64.const`/`global.get` followed by `end`
+    fn skip_const_expr(&mut self) -> Result<()> {
+        loop {
+            match self.byte()? {
+                0x0B => return Ok(()),
+                0x41 | 0x42 | 0x23 => {
+                    self.uleb()?;
+                }
+                op => bail!("unsupported opcode {:#x} in constant expression", op),
+            }
+        }
+    }
+}
@@ -1,25 +1,223 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const CDX_ENDPOINT: &str = "https://web.archive.org/cdx/search/cdx";
+
+/// Static assets that never contain API endpoints. Used when no MIME filter is given.
+const DEFAULT_MIME_EXCLUDES: &[&str] = &[
+    "!mimetype:image/.*",
+    "!mimetype:text/css",
+    "!mimetype:font/.*",
+    "!mimetype:video/.*",
+    "!mimetype:audio/.*",
+    "!mimetype:application/font.*",
+];
+
+/// Options for a Wayback CDX query. Filters are evaluated server-side.
+#[derive(Debug, Clone)]
+pub struct WaybackQuery {
+    /// Earliest capture timestamp, 1-14 digits (yyyyMMddhhmmss prefix)
+    pub from: Option<String>,
+    /// Latest capture timestamp, 1-14 digits
+    pub to: Option<String>,
+    /// MIME type regexes; prefix with `!` to exclude
+    pub mime_filters: Vec<String>,
+    /// Status code regexes; prefix with `!` to exclude
+    pub status_filters: Vec<String>,
+    /// Rows requested per page
+    pub page_size: usize,
+    /// Stop paging after this long and return what was collected
+    pub time_budget: Duration,
+    /// Continue a previous query from this CDX resume key
+    pub resume_key: Option<String>,
+}
+
+impl Default for WaybackQuery {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            mime_filters: Vec::new(),
+            status_filters: Vec::new(),
+            page_size: 5000,
+            time_budget: Duration::from_secs(60),
+            resume_key: None,
+        }
+    }
+}
+
+/// Result of a paginated CDX query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaybackResult {
+    pub urls: Vec<String>,
+    pub pages: usize,
+    /// Set when the query stopped early (time budget, failed page); pass it back via `WaybackQuery::resume_key`
+    pub resume_key: Option<String>,
+}
+
+impl WaybackResult {
+    /// Append a query continued from this result's resume key
+    pub fn merge(&mut self, next: WaybackResult) {
+        self.urls.extend(next.urls);
+        self.urls.sort();
+        self.urls.dedup();
+        self.pages += next.pages;
+        self.resume_key = next.resume_key;
+    }
+}
 
 /// Query Wayback CDX API for URLs related to the domain.
 pub async fn wayback_urls(domain: &str) -> Result<Vec<String>> {
-    // cdX API: return original URLs
-    let url = format!("https://web.archive.org/cdx/search/cdx?url=*.{}&output=json&fl=original&collapse=urlkey", domain);
-    let client = Client::new();
-    let resp = client.get(&url).send().await?;
+    Ok(wayback_query(domain, &WaybackQuery::default()).await?.urls)
+}
+
+/// Paginated CDX query using resume keys. Failed pages are retried from the last key,
+/// so a flaky connection doesn't restart the whole download. Fails if not even the first page
+/// could be fetched; a later page that keeps failing ends the query with its resume key set.
+pub async fn wayback_query(domain: &str, query: &WaybackQuery) -> Result<WaybackResult> {
+    for ts in [&query.from, &query.to].into_iter().flatten() {
+        if ts.is_empty() || ts.len() > 14 || !ts.chars().all(|c| c.is_ascii_digit()) {
+            bail!("invalid Wayback timestamp '{}': expected 1-14 digits (yyyyMMddhhmmss)", ts);
+        }
+    }
+
+    let client = crate::http_client::third_party()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap_or_default();
+
+    let started = Instant::now();
+    let mut urls = Vec::new();
+    let mut pages = 0;
+    let mut resume_key = query.resume_key.clone();
+
+    loop {
+        if started.elapsed() >= query.time_budget {
+            tracing::warn!("Wayback CDX: time budget reached after {} pages ({} URLs)", pages, urls.len());
+            break;
+        }
+
+        let params = build_params(domain, query, resume_key.as_deref());
+        let mut attempt = 0;
+        let page = loop {
+            match fetch_page(&client, &params).await {
+                Ok(page) => break Ok(page),
+                Err(e) if attempt < 3 => {
+                    attempt += 1;
+                    tracing::debug!("Wayback CDX page {} failed ({}), retry {}", pages + 1, e, attempt);
+                    tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+
+        let (mut page_urls, next_key) = match page {
+            Ok(page) => page,
+            // Nothing collected: an error rather than an empty result that looks complete
+            Err(e) if pages == 0 => return Err(e.context("Wayback CDX query failed")),
+            Err(e) => {
+                // The resume key still points at the failed page, so the next run retries it
+                tracing::warn!("Wayback CDX page {} failed: {}", pages + 1, e);
+                break;
+            }
+        };
+        pages += 1;
+        urls.append(&mut page_urls);
+        resume_key = next_key;
+
+        if resume_key.is_none() {
+            break;
+        }
+    }
+
+    urls.sort();
+    urls.dedup();
+    Ok(WaybackResult { urls, pages, resume_key })
+}
+
+fn build_params(domain: &str, query: &WaybackQuery, resume_key: Option<&str>) -> Vec<(String, String)> {
+    let mut params = vec![
+        ("url".to_string(), format!("*.{}", domain)),
+        ("output".to_string(), "json".to_string()),
+        ("fl".to_string(), "original".to_string()),
+        ("collapse".to_string(), "urlkey".to_string()),
+        ("limit".to_string(), query.page_size.to_string()),
+        ("showResumeKey".to_string(), "true".to_string()),
+    ];
+
+    if let Some(ref from) = query.from {
+        params.push(("from".to_string(), from.clone()));
+    }
+    if let Some(ref to) = query.to {
+        params.push(("to".to_string(), to.clone()));
+    }
+
+    if query.mime_filters.is_empty() {
+        params.extend(DEFAULT_MIME_EXCLUDES.iter().map(|f| ("filter".to_string(), f.to_string())));
+    } else {
+        params.extend(query.mime_filters.iter().map(|f| ("filter".to_string(), cdx_filter("mimetype", f))));
+    }
+    params.extend(query.status_filters.iter().map(|f| ("filter".to_string(), cdx_filter("statuscode", f))));
+
+    if let Some(key) = resume_key {
+        params.push(("resumeKey".to_string(), key.to_string()));
+    }
+    params
+}
+
+/// "!image/.*" -> "!mimetype:image/.*"
+fn cdx_filter(field: &str, value: &str) -> String {
+    match value.strip_prefix('!') {
+        Some(v) => format!("!{}:{}", field, v),
+        None => format!("{}:{}", field, value),
+    }
+}
+
+async fn fetch_page(client: &Client, params: &[(String, String)]) -> Result<(Vec<String>, Option<String>)> {
+    let resp = client.get(CDX_ENDPOINT).query(params).send().await?;
     if !resp.status().is_success() {
-        return Ok(vec![]);
+        bail!("HTTP {}", resp.status());
+    }
+    let text = resp.text().await?;
+    if text.trim().is_empty() {
+        return Ok((vec![], None));
     }
-    let v: serde_json::Value = resp.json().await?;
+    let v: serde_json::Value = serde_json::from_str(&text)?;
+    Ok(parse_cdx_page(&v))
+}
+
+/// Rows look like `[["original"], ["http://..."], ..., [], ["<resume key>"]]`
+fn parse_cdx_page(v: &serde_json::Value) -> (Vec<String>, Option<String>) {
     let mut out = Vec::new();
-    if let Some(arr) = v.as_array() {
-        for item in arr.iter().skip(1) {
-            if let Some(s) = item.as_str() {
-                out.push(s.to_string());
+    let mut resume_key = None;
+
+    if let Some(rows) = v.as_array() {
+        let mut rows = rows.iter().skip(1);
+        while let Some(row) = rows.next() {
+            match row.as_array() {
+                Some(cols) if cols.is_empty() => {
+                    // Empty row separates the data from the resume key
+                    resume_key = rows.next()
+                        .and_then(|r| r.get(0))
+                        .and_then(|k| k.as_str())
+                        .map(|k| k.to_string());
+                    break;
+                }
+                Some(cols) => {
+                    if let Some(s) = cols.first().and_then(|c| c.as_str()) {
+                        out.push(s.to_string());
+                    }
+                }
+                None => {
+                    if let Some(s) = row.as_str() {
+                        out.push(s.to_string());
+                    }
+                }
             }
         }
     }
-    out.sort();
-    out.dedup();
-    Ok(out)
+
+    (out, resume_key)
 }
@@ -0,0 +1,182 @@
+//! Webpack runtime chunk map parsing
+//! Rebuilds every lazy-loaded chunk URL from the runtime's `{id:"hash"}[id]` maps,
+//! so chunks are found even when nothing references them by name.
+//! Also covers dynamic `import()`s, Vite preload lists, Next.js build manifests and
+//! asset manifests (CRA `asset-manifest.json`, Vite `.vite/manifest.json`).
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+/// `{12:"a1b2c3",34:"d4e5f6"}[e]` - chunk id -> content hash, indexed by the chunk id variable
+static HASH_MAP_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{((?:\s*"?[\w-]+"?\s*:\s*"[0-9a-fA-F]{4,32}"\s*,?)+)\s*\}\s*\[\s*(\w+)\s*\]"#).unwrap()
+});
+/// Text right before the hash map: `"static/js/"+e+"."+` or `"static/js/"+({12:"vendor"}[e]||e)+"."+`
+static PREFIX_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:"([^"]*)"\s*\+\s*)?(?:\(\s*\{([^{}]*)\}\s*\[\s*\w+\s*\]\s*\|\|\s*\w+\s*\)|\w+)\s*\+\s*"([^"]*)"\s*\+\s*$"#).unwrap()
+});
+/// Text right after the hash map: `+".chunk.js"`
+static SUFFIX_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*\+\s*"([^"]*)""#).unwrap());
+/// `__webpack_require__.p = "/"` or minified `r.p="/_next/"`
+static PUBLIC_PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(?:__webpack_require__|[A-Za-z_$]{1,3})\.p\s*=\s*"([^"]*)""#).unwrap()
+});
+static MAP_ENTRY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""?([\w-]+)"?\s*:\s*"([^"]*)""#).unwrap());
+/// `import("./Admin-3f2a.js")` - native dynamic imports (Vite, Rollup, esbuild)
+static DYNAMIC_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bimport\(\s*["'`]([^"'`\s]+\.m?js)(?:\?[^"'`\s]*)?["'`]\s*\)"#).unwrap()
+});
+/// `__vitePreload(()=>import("./A.js"),["assets/A.js","assets/A.css"])` - deps are relative to the site base
+static VITE_PRELOAD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"__vitePreload\([^\[\]]{0,300}\[([^\]]*)\]"#).unwrap());
+/// Next.js `_buildManifest.js`: `"/admin":["static/chunks/pages/admin-1a2b.js"]`
+static NEXT_CHUNK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["'](static/chunks/[^"']+\.js)["']"#).unwrap());
+static JS_STRING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["']([^"'\s]+\.m?js)["']"#).unwrap());
+/// `"buildId":"abc123"` in `__NEXT_DATA__`
+static NEXT_BUILD_ID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""buildId"\s*:\s*"([\w-]+)""#).unwrap());
+
+/// Build the URL of every JS chunk listed in a webpack runtime.
+/// `runtime_url` is where `content` was loaded from; it anchors relative public paths.
+pub fn chunk_urls(content: &str, runtime_url: &str) -> Vec<String> {
+    let Ok(runtime) = Url::parse(runtime_url) else { return vec![] };
+    let public_path = PUBLIC_PATH_RE.captures(content).map(|c| c[1].to_string());
+
+    let mut urls = HashSet::new();
+
+    for cap in HASH_MAP_RE.captures_iter(content) {
+        let whole = cap.get(0).unwrap();
+
+        let mut window_start = whole.start().saturating_sub(400);
+        while !content.is_char_boundary(window_start) {
+            window_start += 1;
+        }
+        let Some(prefix) = PREFIX_RE.captures(&content[window_start..whole.start()]) else { continue };
+        let Some(suffix) = SUFFIX_RE.captures(&content[whole.end()..]) else { continue };
+
+        let suffix = &suffix[1];
+        if !suffix.ends_with(".js") {
+            // CSS chunk maps use the same shape
+            continue;
+        }
+
+        let dir = prefix.get(1).map(|m| m.as_str()).unwrap_or("");
+        let separator = &prefix[3];
+        let names = prefix.get(2).map(|m| parse_map(m.as_str())).unwrap_or_default();
+        let hashes = parse_map(&cap[1]);
+
+        let Some(base) = chunk_base(&runtime, public_path.as_deref(), dir) else { continue };
+
+        for (id, hash) in &hashes {
+            let name = names.get(id).unwrap_or(id);
+            let file = format!("{}{}{}{}{}", dir, name, separator, hash, suffix);
+            if let Ok(u) = base.join(&file) {
+                urls.insert(u.to_string());
+            }
+        }
+    }
+
+    let mut urls: Vec<String> = urls.into_iter().collect();
+    urls.sort();
+    urls
+}
+
+fn parse_map(body: &str) -> HashMap<String, String> {
+    MAP_ENTRY_RE.captures_iter(body)
+        .map(|c| (c[1].to_string(), c[2].to_string()))
+        .collect()
+}
+
+/// Resolve the URL chunk paths are relative to
+fn chunk_base(runtime: &Url, public_path: Option<&str>, dir: &str) -> Option<Url> {
+    match public_path {
+        Some(p) if !p.is_empty() && p != "auto" => {
+            let p = if p.ends_with('/') { p.to_string() } else { format!("{}/", p) };
+            runtime.join(&p).ok()
+        }
+        _ => {
+            // "auto"/missing public path: if the runtime lives under the chunk directory
+            // (e.g. /static/js/runtime.js with "static/js/"), the base is the part before it
+            let path = runtime.path();
+            if !dir.is_empty() {
+                if let Some(idx) = path.find(&format!("/{}", dir)) {
+                    return runtime.join(&path[..=idx]).ok();
+                }
+            }
+            runtime.join("./").ok()
+        }
+    }
+}
+
+/// Lazily-loaded JS referenced from `content`: dynamic imports, Vite preload lists and Next.js
+/// build manifest chunks. `file_url` is where `content` was loaded from.
+pub fn dynamic_chunk_urls(content: &str, file_url: &str) -> Vec<String> {
+    let Ok(file) = Url::parse(file_url) else { return vec![] };
+    let mut urls = HashSet::new();
+
+    for cap in DYNAMIC_IMPORT_RE.captures_iter(content) {
+        if let Ok(u) = file.join(&cap[1]) {
+            urls.insert(u.to_string());
+        }
+    }
+
+    for cap in VITE_PRELOAD_RE.captures_iter(content) {
+        for dep in JS_STRING_RE.captures_iter(&cap[1]) {
+            let path = dep[1].trim_start_matches("./");
+            if let Ok(u) = file.join(&format!("/{}", path.trim_start_matches('/'))) {
+                urls.insert(u.to_string());
+            }
+        }
+    }
+
+    // Build manifest chunk paths are relative to /_next/
+    if file.path().contains("/_next/") {
+        let next_root = &file.path()[..file.path().find("/_next/").unwrap() + "/_next/".len()];
+        for cap in NEXT_CHUNK_RE.captures_iter(content) {
+            if let Ok(u) = file.join(&format!("{}{}", next_root, &cap[1])) {
+                urls.insert(u.to_string());
+            }
+        }
+    }
+
+    let mut urls: Vec<String> = urls.into_iter().collect();
+    urls.sort();
+    urls
+}
+
+/// JS files listed in an asset manifest (CRA `asset-manifest.json`, Vite `manifest.json`).
+/// Relative entries are resolved against the site root.
+pub fn manifest_js_urls(manifest: &str, manifest_url: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(manifest_url) else { return vec![] };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(manifest) else { return vec![] };
+
+    fn walk(v: &serde_json::Value, out: &mut Vec<String>) {
+        match v {
+            serde_json::Value::String(s) if s.ends_with(".js") || s.ends_with(".mjs") => out.push(s.clone()),
+            serde_json::Value::Array(a) => a.iter().for_each(|i| walk(i, out)),
+            serde_json::Value::Object(o) => o.values().for_each(|i| walk(i, out)),
+            _ => {}
+        }
+    }
+    let mut paths = Vec::new();
+    walk(&json, &mut paths);
+
+    let mut urls: Vec<String> = paths.iter()
+        .filter_map(|p| {
+            if p.starts_with("http://") || p.starts_with("https://") || p.starts_with('/') {
+                base.join(p).ok()
+            } else {
+                base.join(&format!("/{}", p.trim_start_matches("./"))).ok()
+            }
+        })
+        .map(|u| u.to_string())
+        .collect();
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+/// Next.js build id from a page's `__NEXT_DATA__`, used to locate `_buildManifest.js`
+pub fn next_build_id(html: &str) -> Option<String> {
+    NEXT_BUILD_ID_RE.captures(html).map(|c| c[1].to_string())
+}
@@ -0,0 +1,335 @@
+//! Schema-driven GraphQL fuzzing
+//! Every query field of the introspected schema gets a valid query: required arguments filled with
+//! values of their type (enums from the schema, input objects with their required fields), the scalar
+//! fields of the result selected. From there:
+//! - access: the answer is classified as data, an authorization error or null, and authorization errors
+//!   on single subfields (`errors[].path`) are recorded. A subfield refused through one query field and
+//!   returned through another, or a type refused by one ID lookup and returned by another, is reported
+//!   as inconsistent authorization.
+//! - injection: string and ID arguments get the JSON fuzzer's SQL, template, traversal and command
+//!   payloads, classified the same way
+//! - IDs: ID-like arguments are set to 1, 2 and 3; different objects for each is IDOR-style enumeration
+//!
+//! Only query fields are sent; mutations would change data.
+
+use super::tester::{FieldDef, GraphQLSchema, GraphQLTester, GraphQLVulnerability};
+use crate::fuzz::json_fuzzer::{self, MutationKind};
+use crate::http_client::HttpClient;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// Query fields fuzzed per endpoint
+pub const MAX_FIELDS: usize = 25;
+
+/// String and ID arguments given injection payloads per field
+const MAX_INJECTION_ARGS: usize = 3;
+
+/// Nesting of input objects built for arguments
+const MAX_INPUT_DEPTH: usize = 3;
+
+const INJECTIONS: [MutationKind; 4] = [MutationKind::SqlQuote, MutationKind::Template, MutationKind::PathTraversal, MutationKind::CommandInjection];
+
+/// Words of authorization errors
+const AUTH_ERRORS: &[&str] = &[
+    "unauthorized", "unauthorised", "not authorized", "not authorised", "unauthenticated", "not authenticated",
+    "forbidden", "access denied", "permission", "not allowed", "login required", "must be logged in",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    /// Data came back
+    Allowed,
+    /// Authorization error, or 401/403
+    Denied,
+    /// Null without an authorization error (not found, or no data for the arguments)
+    Null,
+    /// Other errors (validation, server)
+    Error,
+}
+
+/// How one query field answered
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldAccess {
+    pub field: String,
+    pub return_type: String,
+    pub query: String,
+    pub access: Access,
+    /// Takes an ID-like argument: looks up one object
+    pub by_id: bool,
+    /// Subfields of the result refused with an authorization error
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub denied_subfields: Vec<String>,
+    /// Subfields of the result that came back with a value
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_subfields: Vec<String>,
+}
+
+/// GraphQL literal of a value of the given type; `None` if the type can't be built (unknown enum)
+pub fn literal(schema: &GraphQLSchema, name: &str, type_name: &str, kind: &str, list: bool, depth: usize) -> Option<String> {
+    let lower = name.to_ascii_lowercase();
+    let single = match kind {
+        "ENUM" => schema.enum_values.get(type_name)?.first()?.clone(),
+        "INPUT_OBJECT" => {
+            if depth >= MAX_INPUT_DEPTH {
+                return None;
+            }
+            let fields = schema.input_fields.get(type_name)?;
+            let mut parts = Vec::new();
+            for f in fields.iter().filter(|f| f.required) {
+                parts.push(format!("{}: {}", f.name, literal(schema, &f.name, &f.type_name, &f.kind, f.list, depth + 1)?));
+            }
+            format!("{{{}}}", parts.join(", "))
+        }
+        _ => match type_name {
+            "Int" => "1".to_string(),
+            "Float" => "1.5".to_string(),
+            "Boolean" => "true".to_string(),
+            "ID" => "\"1\"".to_string(),
+            _ if lower.contains("email") => "\"user@example.com\"".to_string(),
+            _ if lower.contains("url") || lower.contains("uri") => "\"https://example.com/\"".to_string(),
+            _ if type_name.contains("Date") || type_name.contains("Time") => "\"2024-01-01T00:00:00Z\"".to_string(),
+            _ => "\"test\"".to_string(),
+        },
+    };
+    Some(if list { format!("[{}]", single) } else { single })
+}
+
+/// Selection set for a field's return type; scalars have none
+fn selection(schema: &GraphQLSchema, field: &FieldDef) -> String {
+    match field.kind.as_str() {
+        "OBJECT" | "INTERFACE" => GraphQLTester::build_selection(schema, &field.type_name, 0).unwrap_or_else(|| "{ __typename }".to_string()),
+        "UNION" => "{ __typename }".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// A valid query for `field`: required arguments filled in, `overrides` (literals) replacing or adding
+/// arguments
+pub fn field_query(schema: &GraphQLSchema, field: &FieldDef, overrides: &[(&str, String)]) -> Option<String> {
+    let mut args = Vec::new();
+    for arg in &field.args {
+        if let Some((_, value)) = overrides.iter().find(|(n, _)| *n == arg.name) {
+            args.push(format!("{}: {}", arg.name, value));
+        } else if arg.required {
+            args.push(format!("{}: {}", arg.name, literal(schema, &arg.name, &arg.type_name, &arg.kind, arg.list, 0)?));
+        }
+    }
+    let mut query = format!("query Fuzz {{ {}", field.name);
+    if !args.is_empty() {
+        query.push_str(&format!("({})", args.join(", ")));
+    }
+    let selection = selection(schema, field);
+    if !selection.is_empty() {
+        query.push(' ');
+        query.push_str(&selection);
+    }
+    query.push_str(" }");
+    Some(query)
+}
+
+fn is_auth_error(error: &Value) -> bool {
+    let message = error.get("message").and_then(Value::as_str).unwrap_or_default().to_ascii_lowercase();
+    let code = error.pointer("/extensions/code").and_then(Value::as_str).unwrap_or_default().to_ascii_uppercase();
+    AUTH_ERRORS.iter().any(|w| message.contains(w)) || matches!(code.as_str(), "FORBIDDEN" | "UNAUTHENTICATED" | "UNAUTHORIZED")
+}
+
+/// Classify the answer to the query of `field`
+pub fn classify_access(status: u16, body: &Value, field: &str) -> Access {
+    if matches!(status, 401 | 403) {
+        return Access::Denied;
+    }
+    let errors: Vec<&Value> = body.get("errors").and_then(Value::as_array).map(|e| e.iter().collect()).unwrap_or_default();
+    let data = body.get("data").and_then(|d| d.get(field)).filter(|d| !d.is_null());
+    // Errors on the field itself, not on one of its subfields
+    let own_errors: Vec<&&Value> = errors.iter()
+        .filter(|e| e.get("path").and_then(Value::as_array).is_none_or(|p| p.len() <= 1))
+        .collect();
+    match data {
+        Some(_) => Access::Allowed,
+        None if own_errors.iter().any(|e| is_auth_error(e)) => Access::Denied,
+        None if errors.is_empty() => Access::Null,
+        None => Access::Error,
+    }
+}
+
+/// Subfields refused with an authorization error (last path element that is a name)
+pub fn denied_subfields(body: &Value) -> Vec<String> {
+    let mut out = BTreeSet::new();
+    for error in body.get("errors").and_then(Value::as_array).into_iter().flatten() {
+        let Some(path) = error.get("path").and_then(Value::as_array).filter(|p| p.len() > 1) else { continue };
+        if is_auth_error(error) {
+            if let Some(name) = path.iter().rev().find_map(Value::as_str) {
+                out.insert(name.to_string());
+            }
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Names of the scalar subfields with a value anywhere in `data`
+pub fn allowed_subfields(data: &Value) -> Vec<String> {
+    fn walk(v: &Value, out: &mut BTreeSet<String>, depth: usize) {
+        if depth > 4 {
+            return;
+        }
+        match v {
+            Value::Object(map) => {
+                for (k, child) in map {
+                    match child {
+                        Value::Null => {}
+                        Value::Object(_) | Value::Array(_) => walk(child, out, depth + 1),
+                        _ => {
+                            out.insert(k.clone());
+                        }
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|i| walk(i, out, depth + 1)),
+            _ => {}
+        }
+    }
+    let mut out = BTreeSet::new();
+    walk(data, &mut out, 0);
+    out.remove("__typename");
+    out.into_iter().collect()
+}
+
+fn id_like(name: &str) -> bool {
+    name == "id" || name.ends_with("Id") || name.ends_with("_id") || name.ends_with("ID")
+}
+
+pub struct SchemaFuzzer<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+}
+
+impl<'a> SchemaFuzzer<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout }
+    }
+
+    async fn send(&self, url: &str, query: &str) -> Option<(u16, String, Value)> {
+        let req = self.http.client().post(url).json(&json!({ "query": query })).timeout(self.timeout);
+        let resp = self.http.send(req).await.ok()?;
+        let status = resp.status().as_u16();
+        let text = tokio::time::timeout(self.timeout, resp.text()).await.ok()?.ok()?;
+        let body = serde_json::from_str(&text).unwrap_or(Value::Null);
+        Some((status, text, body))
+    }
+
+    /// Fuzz the query fields of `schema`: the access of each field and the findings
+    pub async fn run(&self, url: &str, schema: &GraphQLSchema) -> (Vec<FieldAccess>, Vec<GraphQLVulnerability>) {
+        let mut accesses = Vec::new();
+        let mut vulns = Vec::new();
+        let Some(fields) = schema.query_type.as_deref().and_then(|q| schema.type_fields.get(q)) else { return (accesses, vulns) };
+
+        for field in fields.iter().filter(|f| !f.name.starts_with("__")).take(MAX_FIELDS) {
+            let Some(query) = field_query(schema, field, &[]) else { continue };
+            let Some((status, text, body)) = self.send(url, &query).await else { continue };
+            let access = classify_access(status, &body, &field.name);
+            let data = body.get("data").and_then(|d| d.get(&field.name)).cloned().unwrap_or(Value::Null);
+            accesses.push(FieldAccess {
+                field: field.name.clone(),
+                return_type: field.type_name.clone(),
+                query: query.clone(),
+                access,
+                by_id: field.args.iter().any(|a| id_like(&a.name)),
+                denied_subfields: denied_subfields(&body),
+                allowed_subfields: allowed_subfields(&data),
+            });
+            if access == Access::Denied {
+                continue;
+            }
+
+            // Injection payloads in string and ID arguments
+            let string_args = field.args.iter()
+                .filter(|a| a.kind == "SCALAR" && !a.list && matches!(a.type_name.as_str(), "String" | "ID"))
+                .take(MAX_INJECTION_ARGS);
+            for arg in string_args {
+                for kind in INJECTIONS {
+                    let Some(Value::String(payload)) = json_fuzzer::mutate(&json!("test"), kind) else { continue };
+                    let literal = serde_json::to_string(&payload).unwrap_or_default();
+                    let Some(query) = field_query(schema, field, &[(arg.name.as_str(), literal)]) else { continue };
+                    let Some((fuzz_status, fuzz_text, _)) = self.send(url, &query).await else { continue };
+                    if let Some((indicator, evidence)) = json_fuzzer::classify(kind, (status, &text), fuzz_status, &fuzz_text) {
+                        vulns.push(GraphQLVulnerability {
+                            vuln_type: "GraphQL Argument Injection".to_string(),
+                            severity: indicator.severity().label().to_string(),
+                            description: format!("{}({}): {:?} - {}", field.name, arg.name, indicator, evidence),
+                            payload: Some(query),
+                        });
+                        break;
+                    }
+                }
+            }
+
+            // Neighbouring IDs
+            if let Some(arg) = field.args.iter().find(|a| id_like(&a.name) && !a.list && matches!(a.type_name.as_str(), "ID" | "Int")) {
+                let mut objects: Vec<Value> = Vec::new();
+                let mut last_query = None;
+                for id in 1..=3 {
+                    let value = if arg.type_name == "Int" { id.to_string() } else { format!("\"{}\"", id) };
+                    let Some(query) = field_query(schema, field, &[(arg.name.as_str(), value)]) else { continue };
+                    let Some((_, _, body)) = self.send(url, &query).await else { continue };
+                    if let Some(obj) = body.get("data").and_then(|d| d.get(&field.name)).filter(|d| !d.is_null() && !objects.contains(d)) {
+                        objects.push(obj.clone());
+                        last_query = Some(query);
+                    }
+                }
+                if objects.len() >= 2 {
+                    let pii: Vec<_> = objects.iter().flat_map(crate::enrich::pii::scan_json).collect();
+                    vulns.push(GraphQLVulnerability {
+                        vuln_type: "GraphQL IDOR Enumeration".to_string(),
+                        severity: if pii.is_empty() { "MEDIUM" } else { "HIGH" }.to_string(),
+                        description: format!(
+                            "{}({}) returns a different object for each of {} sequential IDs{}",
+                            field.name, arg.name, objects.len(),
+                            if pii.is_empty() { String::new() } else { format!(", with personal data ({} values)", pii.len()) }
+                        ),
+                        payload: last_query,
+                    });
+                }
+            }
+        }
+
+        vulns.extend(inconsistent_authorization(&accesses));
+        (accesses, vulns)
+    }
+}
+
+/// Types and subfields refused through one query field but returned through another
+pub fn inconsistent_authorization(accesses: &[FieldAccess]) -> Vec<GraphQLVulnerability> {
+    let mut vulns = Vec::new();
+    let mut by_type: BTreeMap<&str, Vec<&FieldAccess>> = BTreeMap::new();
+    for a in accesses {
+        by_type.entry(a.return_type.as_str()).or_default().push(a);
+    }
+    for (type_name, group) in by_type {
+        // Lookups of one object by ID only: `users` refused next to `me` allowed is normal
+        let names = |access: Access| group.iter().filter(|a| a.by_id && a.access == access).map(|a| a.field.as_str()).collect::<Vec<_>>();
+        let (denied, allowed) = (names(Access::Denied), names(Access::Allowed));
+        if !denied.is_empty() && !allowed.is_empty() {
+            vulns.push(GraphQLVulnerability {
+                vuln_type: "Inconsistent Field Authorization".to_string(),
+                severity: "HIGH".to_string(),
+                description: format!("{} is refused through {} but returned through {}", type_name, denied.join(", "), allowed.join(", ")),
+                payload: group.iter().find(|a| a.by_id && a.access == Access::Allowed).map(|a| a.query.clone()),
+            });
+        }
+        let subfields: BTreeSet<&str> = group.iter().flat_map(|a| a.denied_subfields.iter().map(String::as_str)).collect();
+        for sub in subfields {
+            let refused: Vec<&str> = group.iter().filter(|a| a.denied_subfields.iter().any(|d| d == sub)).map(|a| a.field.as_str()).collect();
+            let Some(leak) = group.iter().find(|a| a.allowed_subfields.iter().any(|s| s == sub)) else { continue };
+            vulns.push(GraphQLVulnerability {
+                vuln_type: "Inconsistent Field Authorization".to_string(),
+                severity: "HIGH".to_string(),
+                description: format!("{}.{} is refused through {} but returned through {}", type_name, sub, refused.join(", "), leak.field),
+                payload: Some(leak.query.clone()),
+            });
+        }
+    }
+    vulns
+}
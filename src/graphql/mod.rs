@@ -1,3 +1,5 @@
 pub mod tester;
+pub mod fuzzer;
+pub mod recovery;
 
 pub use tester::GraphQLTester;
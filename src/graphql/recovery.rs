@@ -0,0 +1,317 @@
+//! Schema recovery without introspection
+//! With introspection disabled, the validation errors of a GraphQL server still describe the schema:
+//! - `Cannot query field "usr" on type "Query". Did you mean "user" or "users"?` - field names
+//! - `Field "user" of type "User" must have a selection of subfields` - object return types
+//! - `Field "id" must not have a selection since type "ID!" has no subfields` - leaf return types
+//! - `Field "user" argument "id" of type "ID!" is required, but it was not provided.` - required arguments
+//! - `Unknown argument "xid" on field "Query.user". Did you mean "id"?` - argument names
+//!
+//! Batches of common names are selected on the query and mutation types, then on each object type
+//! reached from them. Names not reported unknown exist; suggestions add names outside the wordlist.
+//! Every probe also selects a field that can't exist, so validation fails and nothing is executed,
+//! mutations included.
+
+use super::tester::{ArgDef, FieldDef, GraphQLSchema};
+use crate::http_client::HttpClient;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::Duration;
+
+/// Selected in every probe so that validation always fails
+pub const SENTINEL: &str = "ahNoSuchField0";
+
+/// Names selected per probe
+const BATCH: usize = 60;
+
+/// Object types explored below the root types
+const MAX_TYPES: usize = 8;
+
+/// Root fields whose arguments are guessed
+const MAX_ARG_FIELDS: usize = 15;
+
+/// Common field names, for root types and object types alike; near misses come back as suggestions
+pub const FIELD_WORDS: &[&str] = &[
+    "me", "viewer", "node", "nodes", "user", "users", "account", "accounts", "profile", "admin", "admins",
+    "customer", "customers", "order", "orders", "product", "products", "item", "items", "post", "posts",
+    "comment", "comments", "message", "messages", "notification", "notifications", "invoice", "invoices",
+    "payment", "payments", "transaction", "transactions", "file", "files", "upload", "document", "documents",
+    "organization", "organizations", "team", "teams", "group", "groups", "project", "projects", "role", "roles",
+    "permission", "permissions", "session", "sessions", "token", "tokens", "apiKey", "apiKeys", "setting",
+    "settings", "config", "search", "health", "version", "login", "logout", "register", "signup",
+    "createUser", "updateUser", "deleteUser", "resetPassword", "changePassword", "id", "uuid", "name",
+    "title", "description", "email", "username", "password", "passwordHash", "phone", "address", "firstName",
+    "lastName", "isAdmin", "status", "type", "price", "total", "amount", "currency", "secret",
+    "createdAt", "updatedAt", "owner", "author", "edges", "pageInfo", "totalCount", "url", "key", "value",
+];
+
+/// Common argument names
+pub const ARG_WORDS: &[&str] = &[
+    "id", "ids", "uuid", "slug", "name", "email", "username", "first", "last", "limit", "offset", "page",
+    "after", "before", "filter", "where", "orderBy", "sort", "search", "query", "q", "input", "data", "token",
+    "type", "status",
+];
+
+static UNKNOWN_FIELD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^Cannot query field "([^"]+)" on type "([^"]+)"(.*)$"#).unwrap());
+static NEEDS_SELECTION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^Field "([^"]+)" of type "([^"]+)" must have a selection of subfields"#).unwrap());
+static LEAF_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^Field "([^"]+)" must not have a selection since type "([^"]+)" has no subfields"#).unwrap());
+static REQUIRED_ARG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^Field "([^"]+)" argument "([^"]+)" of type "([^"]+)" is required"#).unwrap());
+static UNKNOWN_ARG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^Unknown argument "([^"]+)" on field "([^"]+)"(.*)$"#).unwrap());
+static QUOTED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#""([_A-Za-z][_0-9A-Za-z]*)""#).unwrap());
+
+/// What one validation error says about the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    UnknownField { name: String, on_type: String, suggestions: Vec<String> },
+    NeedsSelection { field: String, type_ref: String },
+    Leaf { field: String, type_ref: String },
+    RequiredArg { field: String, arg: String, type_ref: String },
+    UnknownArg { arg: String, field: String, suggestions: Vec<String> },
+}
+
+/// Names after "Did you mean"
+fn suggestions(rest: &str) -> Vec<String> {
+    let Some(at) = rest.find("Did you mean") else { return Vec::new() };
+    // `Did you mean to use an inline fragment on "User"?` names types, not fields
+    if rest.contains("inline fragment") {
+        return Vec::new();
+    }
+    QUOTED_RE.captures_iter(&rest[at..]).map(|c| c[1].to_string()).collect()
+}
+
+/// Parse one error message
+pub fn parse_hint(message: &str) -> Option<Hint> {
+    if let Some(c) = UNKNOWN_FIELD_RE.captures(message) {
+        return Some(Hint::UnknownField { name: c[1].to_string(), on_type: c[2].to_string(), suggestions: suggestions(&c[3]) });
+    }
+    if let Some(c) = NEEDS_SELECTION_RE.captures(message) {
+        return Some(Hint::NeedsSelection { field: c[1].to_string(), type_ref: c[2].to_string() });
+    }
+    if let Some(c) = LEAF_RE.captures(message) {
+        return Some(Hint::Leaf { field: c[1].to_string(), type_ref: c[2].to_string() });
+    }
+    if let Some(c) = REQUIRED_ARG_RE.captures(message) {
+        return Some(Hint::RequiredArg { field: c[1].to_string(), arg: c[2].to_string(), type_ref: c[3].to_string() });
+    }
+    if let Some(c) = UNKNOWN_ARG_RE.captures(message) {
+        // `Query.user` in current graphql-js, `user` (followed by `of type "Query"`) in older versions
+        let field = c[2].rsplit('.').next().unwrap_or_default().to_string();
+        return Some(Hint::UnknownArg { arg: c[1].to_string(), field, suggestions: suggestions(&c[3]) });
+    }
+    None
+}
+
+/// Hints of all errors of a response
+pub fn hints(body: &Value) -> Vec<Hint> {
+    body.get("errors").and_then(Value::as_array).into_iter().flatten()
+        .filter_map(|e| parse_hint(e.get("message")?.as_str()?))
+        .collect()
+}
+
+/// Named type and list flag of a type reference like `[User!]!`
+pub fn parse_type_ref(type_ref: &str) -> (String, bool) {
+    (type_ref.trim_matches(|c| matches!(c, '[' | ']' | '!')).to_string(), type_ref.contains('['))
+}
+
+/// `op { a { b { selections SENTINEL } } }` for the path `[a, b]`
+pub fn document(op: &str, path: &[String], selections: &[String]) -> String {
+    let mut inner = selections.iter().map(String::as_str).chain([SENTINEL]).collect::<Vec<_>>().join(" ");
+    for field in path.iter().rev() {
+        inner = format!("{} {{ {} }}", field, inner);
+    }
+    format!("{} {{ {} }}", op, inner)
+}
+
+/// A partial schema rebuilt from validation errors
+#[derive(Debug, Clone)]
+pub struct RecoveredSchema {
+    pub schema: GraphQLSchema,
+    /// The server answers with "Did you mean" suggestions
+    pub suggestions: bool,
+    pub requests: usize,
+}
+
+pub struct SchemaRecovery<'a> {
+    http: &'a HttpClient,
+    timeout: Duration,
+}
+
+/// Fields found on one type, by name
+type Fields = BTreeMap<String, FieldDef>;
+
+impl<'a> SchemaRecovery<'a> {
+    pub fn new(http: &'a HttpClient, timeout: Duration) -> Self {
+        Self { http, timeout }
+    }
+
+    async fn send(&self, url: &str, query: &str, requests: &mut usize) -> Option<Vec<Hint>> {
+        *requests += 1;
+        let req = self.http.client().post(url).json(&json!({ "query": query })).timeout(self.timeout);
+        let resp = self.http.send(req).await.ok()?;
+        let text = tokio::time::timeout(self.timeout, resp.text()).await.ok()?.ok()?;
+        Some(hints(&serde_json::from_str(&text).ok()?))
+    }
+
+    /// Names that exist on the type at `path`, and that type's name. `None` if the server doesn't
+    /// report unknown fields (the sentinel)
+    async fn names(&self, url: &str, op: &str, path: &[String], state: &mut State) -> Option<(String, BTreeSet<String>)> {
+        let mut type_name = None;
+        let mut found = BTreeSet::new();
+        for chunk in FIELD_WORDS.chunks(BATCH) {
+            let words: Vec<String> = chunk.iter().map(|w| w.to_string()).collect();
+            let hints = self.send(url, &document(op, path, &words), &mut state.requests).await?;
+            let mut unknown = BTreeSet::new();
+            for hint in &hints {
+                if let Hint::UnknownField { name, on_type, suggestions } = hint {
+                    if name == SENTINEL {
+                        type_name = Some(on_type.clone());
+                    }
+                    unknown.insert(name.clone());
+                    state.suggestions |= !suggestions.is_empty();
+                    found.extend(suggestions.iter().cloned());
+                }
+            }
+            // The sentinel wasn't reported: errors are masked or this isn't the expected type
+            type_name.as_ref()?;
+            found.extend(words.into_iter().filter(|w| !unknown.contains(w)));
+        }
+        Some((type_name?, found))
+    }
+
+    /// Return types and required arguments of `names` on the type at `path`
+    async fn describe(&self, url: &str, op: &str, path: &[String], names: &BTreeSet<String>, state: &mut State) -> Fields {
+        let mut fields: Fields = names.iter()
+            .map(|n| (n.clone(), FieldDef { name: n.clone(), type_name: String::new(), kind: String::new(), args: Vec::new() }))
+            .collect();
+        let names: Vec<String> = names.iter().cloned().collect();
+        for chunk in names.chunks(BATCH) {
+            // Bare: objects ask for a selection, required arguments are reported
+            for hint in self.send(url, &document(op, path, chunk), &mut state.requests).await.unwrap_or_default() {
+                match hint {
+                    Hint::NeedsSelection { field, type_ref } => {
+                        if let Some(def) = fields.get_mut(&field) {
+                            def.type_name = parse_type_ref(&type_ref).0;
+                            def.kind = "OBJECT".to_string();
+                        }
+                    }
+                    Hint::RequiredArg { field, arg, type_ref } => {
+                        let Some(def) = fields.get_mut(&field) else { continue };
+                        if def.args.iter().all(|a| a.name != arg) {
+                            def.args.push(arg_def(&arg, &type_ref, true));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // With a selection: leaves name their type
+            let selected: Vec<String> = chunk.iter().filter(|n| fields[n.as_str()].kind.is_empty()).map(|n| format!("{} {{ __typename }}", n)).collect();
+            if selected.is_empty() {
+                continue;
+            }
+            for hint in self.send(url, &document(op, path, &selected), &mut state.requests).await.unwrap_or_default() {
+                if let Hint::Leaf { field, type_ref } = hint {
+                    if let Some(def) = fields.get_mut(&field) {
+                        def.type_name = parse_type_ref(&type_ref).0;
+                        def.kind = "SCALAR".to_string();
+                    }
+                }
+            }
+        }
+        fields
+    }
+
+    /// Optional arguments of a root field, from the argument wordlist and suggestions
+    async fn arguments(&self, url: &str, op: &str, field: &FieldDef, state: &mut State) -> Vec<ArgDef> {
+        let args: Vec<String> = ARG_WORDS.iter().map(|a| format!("{}: 1", a)).collect();
+        let selection = if field.kind == "OBJECT" { " { __typename }" } else { "" };
+        let query = document(op, &[], &[format!("{}({}){}", field.name, args.join(", "), selection)]);
+        let Some(hints) = self.send(url, &query, &mut state.requests).await else { return Vec::new() };
+        let mut unknown = BTreeSet::new();
+        let mut found = BTreeSet::new();
+        for hint in hints {
+            if let Hint::UnknownArg { arg, field: on, suggestions } = hint {
+                if on == field.name {
+                    state.suggestions |= !suggestions.is_empty();
+                    found.extend(suggestions);
+                    unknown.insert(arg);
+                }
+            }
+        }
+        // Servers that don't report unknown arguments would make every word an argument
+        if unknown.is_empty() {
+            return Vec::new();
+        }
+        found.extend(ARG_WORDS.iter().map(|a| a.to_string()).filter(|a| !unknown.contains(a)));
+        found.into_iter()
+            .filter(|a| field.args.iter().all(|known| &known.name != a))
+            .map(|a| ArgDef { name: a, required: false, type_name: String::new(), kind: String::new(), list: false })
+            .collect()
+    }
+
+    /// Rebuild what validation errors reveal of the schema; `None` if they reveal nothing
+    pub async fn recover(&self, url: &str) -> Option<RecoveredSchema> {
+        let mut state = State::default();
+        let mut schema = GraphQLSchema::default();
+        let mut types: BTreeMap<String, Fields> = BTreeMap::new();
+        let mut queue = VecDeque::new();
+
+        for op in ["query", "mutation"] {
+            let Some((root, names)) = self.names(url, op, &[], &mut state).await else { continue };
+            let mut fields = self.describe(url, op, &[], &names, &mut state).await;
+            for def in fields.values_mut().take(MAX_ARG_FIELDS) {
+                let optional = self.arguments(url, op, def, &mut state).await;
+                def.args.extend(optional);
+            }
+            if op == "query" {
+                schema.query_type = Some(root.clone());
+                schema.queries = fields.keys().cloned().collect();
+                // Object types reachable from queries; mutations would need valid inputs to read back
+                queue.extend(fields.values().filter(|f| f.kind == "OBJECT").map(|f| (f.type_name.clone(), vec![f.name.clone()])));
+            } else {
+                schema.mutations = fields.keys().cloned().collect();
+            }
+            types.insert(root, fields);
+        }
+        schema.query_type.as_ref()?;
+
+        let mut explored = 0;
+        while let Some((type_name, path)) = queue.pop_front() {
+            if explored >= MAX_TYPES || types.contains_key(&type_name) {
+                continue;
+            }
+            explored += 1;
+            let Some((_, names)) = self.names(url, "query", &path, &mut state).await else { continue };
+            let fields = self.describe(url, "query", &path, &names, &mut state).await;
+            if path.len() < 3 {
+                for f in fields.values().filter(|f| f.kind == "OBJECT") {
+                    let mut next = path.clone();
+                    next.push(f.name.clone());
+                    queue.push_back((f.type_name.clone(), next));
+                }
+            }
+            types.insert(type_name, fields);
+        }
+
+        let leaf_types: BTreeSet<String> = types.values().flat_map(|f| f.values()).filter(|f| f.kind == "SCALAR").map(|f| f.type_name.clone()).collect();
+        schema.types = types.keys().cloned().chain(leaf_types.into_iter().filter(|t| !types.contains_key(t))).collect();
+        schema.type_fields = types.into_iter().map(|(name, fields)| (name, fields.into_values().collect())).collect();
+        schema.mark_sensitive();
+        if schema.queries.is_empty() && schema.mutations.is_empty() {
+            return None;
+        }
+        Some(RecoveredSchema { schema, suggestions: state.suggestions, requests: state.requests })
+    }
+}
+
+#[derive(Default)]
+struct State {
+    suggestions: bool,
+    requests: usize,
+}
+
+fn arg_def(name: &str, type_ref: &str, required: bool) -> ArgDef {
+    let (type_name, list) = parse_type_ref(type_ref);
+    let kind = if matches!(type_name.as_str(), "ID" | "String" | "Int" | "Float" | "Boolean") { "SCALAR" } else { "" };
+    ArgDef { name: name.to_string(), required, type_name, kind: kind.to_string(), list }
+}
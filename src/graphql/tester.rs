@@ -1,26 +1,189 @@
 use crate::http_client::HttpClient;
+use serde::Serialize;
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tokio::time::{timeout, Duration};
+use crate::enrich::pii::{self, PiiMatch};
+use super::fuzzer::{FieldAccess, SchemaFuzzer};
+use super::recovery::SchemaRecovery;
 
-#[derive(Debug, Clone)]
+/// Field names that mark an object type as holding personal data
+const PII_FIELD_HINTS: &[&str] = &[
+    "email", "phone", "mobile", "address", "street", "zip", "postal", "birth", "dob",
+    "ssn", "iban", "card", "firstname", "first_name", "lastname", "last_name", "fullname",
+];
+
+/// Arguments that can safely be set to 1 to keep sample queries tiny
+const LIMIT_ARGS: &[&str] = &["first", "last", "limit", "take", "size", "count", "perpage", "per_page", "pagesize", "page_size"];
+
+/// Maximum number of PII sample queries per endpoint
+const MAX_PII_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct GraphQLEndpoint {
     pub url: String,
     pub has_introspection: bool,
     pub schema: Option<GraphQLSchema>,
+    /// `schema` was rebuilt from validation errors, not introspected
+    pub schema_recovered: bool,
     pub vulnerabilities: Vec<GraphQLVulnerability>,
+    /// Read-only sample queries that returned personal data without authentication
+    pub pii_samples: Vec<GraphQLPiiSample>,
+    /// How each query field answered the schema fuzzer
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub field_access: Vec<FieldAccess>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GraphQLSchema {
     pub types: Vec<String>,
     pub queries: Vec<String>,
     pub mutations: Vec<String>,
     pub has_sensitive_fields: bool,
+    /// Object types with personal-data fields (email, phone, address, ...)
+    pub sensitive_types: Vec<String>,
+    #[serde(skip)]
+    pub query_type: Option<String>,
+    #[serde(skip)]
+    pub type_fields: HashMap<String, Vec<FieldDef>>,
+    /// Fields of input object types
+    #[serde(skip)]
+    pub input_fields: HashMap<String, Vec<ArgDef>>,
+    #[serde(skip)]
+    pub enum_values: HashMap<String, Vec<String>>,
+}
+
+impl GraphQLSchema {
+    /// Schema from an introspection response (`{"data": {"__schema": ...}}`)
+    pub fn from_introspection(data: &Value) -> Self {
+        let mut schema = GraphQLSchema::default();
+
+        if let Some(schema_data) = data.get("data").and_then(|d| d.get("__schema")) {
+            let query_type = schema_data.get("queryType")
+                .and_then(|q| q.get("name"))
+                .and_then(|n| n.as_str())
+                .unwrap_or("Query")
+                .to_string();
+
+            // Extract types
+            if let Some(types) = schema_data.get("types").and_then(|t| t.as_array()) {
+                for type_obj in types {
+                    if let Some(name) = type_obj.get("name").and_then(|n| n.as_str()) {
+                        schema.types.push(name.to_string());
+
+                        if let Some(fields) = type_obj.get("fields").and_then(|f| f.as_array()) {
+                            let mut defs = Vec::new();
+                            for field in fields {
+                                if let Some(field_name) = field.get("name").and_then(|n| n.as_str()) {
+                                    if name == query_type {
+                                        schema.queries.push(field_name.to_string());
+                                    }
+
+                                    let (type_name, kind) = GraphQLTester::unwrap_type(&field["type"]);
+                                    let args = field.get("args").and_then(|a| a.as_array()).into_iter().flatten()
+                                        .filter_map(ArgDef::from_introspection)
+                                        .collect();
+                                    defs.push(FieldDef { name: field_name.to_string(), type_name, kind, args });
+                                }
+                            }
+                            schema.type_fields.insert(name.to_string(), defs);
+                        }
+                        if let Some(inputs) = type_obj.get("inputFields").and_then(|f| f.as_array()) {
+                            schema.input_fields.insert(name.to_string(), inputs.iter().filter_map(ArgDef::from_introspection).collect());
+                        }
+                        if let Some(values) = type_obj.get("enumValues").and_then(|v| v.as_array()) {
+                            schema.enum_values.insert(name.to_string(), values.iter().filter_map(|v| Some(v.get("name")?.as_str()?.to_string())).collect());
+                        }
+                    }
+                }
+            }
+            schema.query_type = Some(query_type);
+
+            // Extract mutations
+            if let Some(mutation_type) = schema_data.get("mutationType") {
+                if let Some(mutation_name) = mutation_type.get("name").and_then(|n| n.as_str()) {
+                    schema.mutations.push(mutation_name.to_string());
+                }
+            }
+        }
+
+        schema.mark_sensitive();
+        schema
+    }
+
+    /// Set `has_sensitive_fields` and `sensitive_types` from the field names in `type_fields`
+    pub(crate) fn mark_sensitive(&mut self) {
+        for name in &self.types {
+            let Some(fields) = self.type_fields.get(name) else { continue };
+            let mut has_pii_field = false;
+            for field in fields {
+                let lower = field.name.to_lowercase();
+                if lower.contains("password") || lower.contains("token")
+                    || lower.contains("secret") || lower.contains("key")
+                    || lower.contains("credit") || lower.contains("ssn") {
+                    self.has_sensitive_fields = true;
+                }
+                if PII_FIELD_HINTS.iter().any(|h| lower.contains(h)) {
+                    has_pii_field = true;
+                }
+            }
+            if has_pii_field && !name.starts_with("__") {
+                self.sensitive_types.push(name.to_string());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDef {
+    pub name: String,
+    /// Named type after unwrapping NON_NULL/LIST
+    pub type_name: String,
+    pub kind: String,
+    pub args: Vec<ArgDef>,
 }
 
+/// Argument or input object field
 #[derive(Debug, Clone)]
+pub struct ArgDef {
+    pub name: String,
+    pub required: bool,
+    /// Named type after unwrapping NON_NULL/LIST
+    pub type_name: String,
+    pub kind: String,
+    /// Wrapped in LIST somewhere
+    pub list: bool,
+}
+
+impl ArgDef {
+    fn from_introspection(v: &Value) -> Option<Self> {
+        let (type_name, kind) = GraphQLTester::unwrap_type(&v["type"]);
+        let mut list = false;
+        let mut cur = &v["type"];
+        while let Some(k) = cur.get("kind").and_then(Value::as_str) {
+            list |= k == "LIST";
+            cur = &cur["ofType"];
+        }
+        Some(Self {
+            name: v.get("name")?.as_str()?.to_string(),
+            required: v["type"]["kind"].as_str() == Some("NON_NULL"),
+            type_name,
+            kind,
+            list,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphQLPiiSample {
+    pub field: String,
+    pub return_type: String,
+    pub query: String,
+    pub pii: Vec<PiiMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct GraphQLVulnerability {
     pub vuln_type: String,
     pub severity: String,
@@ -30,11 +193,25 @@ pub struct GraphQLVulnerability {
 
 pub struct GraphQLTester {
     client: HttpClient,
+    /// Without the scan credentials and cookie jar, for the anonymous PII check
+    anonymous: Option<HttpClient>,
+    fuzz: bool,
 }
 
 impl GraphQLTester {
     pub fn new(client: HttpClient) -> Self {
-        Self { client }
+        let anonymous = crate::http_client::builder_unauthenticated("graphql::tester")
+            .danger_accept_invalid_certs(true)
+            .build()
+            .ok()
+            .map(HttpClient::new);
+        Self { client, anonymous, fuzz: false }
+    }
+
+    /// Also fuzz the query fields of an introspected schema (see `graphql::fuzzer`)
+    pub fn with_fuzzing(mut self, fuzz: bool) -> Self {
+        self.fuzz = fuzz;
+        self
     }
 
     /// Test if URL is a GraphQL endpoint
@@ -80,13 +257,30 @@ impl GraphQLTester {
             url: url.to_string(),
             has_introspection: false,
             schema: None,
+            schema_recovered: false,
             vulnerabilities: Vec::new(),
+            pii_samples: Vec::new(),
+            field_access: Vec::new(),
         };
 
         // Test introspection
         if let Ok(schema) = self.test_introspection(url).await {
             endpoint.has_introspection = true;
             endpoint.schema = Some(schema);
+        } else if let Some(recovered) = SchemaRecovery::new(&self.client, Duration::from_secs(5)).recover(url).await {
+            let fields: usize = recovered.schema.type_fields.values().map(Vec::len).sum();
+            endpoint.vulnerabilities.push(GraphQLVulnerability {
+                vuln_type: if recovered.suggestions { "Field Suggestions Enabled" } else { "Schema Recoverable Without Introspection" }.to_string(),
+                severity: if recovered.suggestions { "MEDIUM" } else { "LOW" }.to_string(),
+                description: format!(
+                    "Introspection is disabled, but validation errors{} disclosed {} types and {} fields in {} requests",
+                    if recovered.suggestions { " with \"Did you mean\" suggestions" } else { "" },
+                    recovered.schema.type_fields.len(), fields, recovered.requests
+                ),
+                payload: None,
+            });
+            endpoint.schema_recovered = true;
+            endpoint.schema = Some(recovered.schema);
         }
 
         // Test for vulnerabilities sequentially
@@ -110,8 +304,33 @@ impl GraphQLTester {
             endpoint.vulnerabilities.extend(vulns);
         }
 
-        // Test mutations if schema available
+        // Confirm whether sensitive types actually return personal data anonymously
         if let Some(ref schema) = endpoint.schema {
+            if !schema.sensitive_types.is_empty() {
+                endpoint.pii_samples = self.sample_pii(url, schema).await;
+                if !endpoint.pii_samples.is_empty() {
+                    let fields: Vec<&str> = endpoint.pii_samples.iter().map(|p| p.field.as_str()).collect();
+                    endpoint.vulnerabilities.push(GraphQLVulnerability {
+                        vuln_type: "Anonymous PII Exposure".to_string(),
+                        severity: "HIGH".to_string(),
+                        description: format!("Queries return personal data without authentication: {}", fields.join(", ")),
+                        payload: endpoint.pii_samples.first().map(|p| p.query.clone()),
+                    });
+                }
+            }
+        }
+
+        // Valid queries per field from the schema: access, argument injection, sequential IDs
+        if self.fuzz {
+            if let Some(ref schema) = endpoint.schema {
+                let (access, vulns) = SchemaFuzzer::new(&self.client, Duration::from_secs(5)).run(url, schema).await;
+                endpoint.field_access = access;
+                endpoint.vulnerabilities.extend(vulns);
+            }
+        }
+
+        // Test mutations if schema available; recovered mutations are real fields that would run
+        if let Some(schema) = endpoint.schema.as_ref().filter(|_| !endpoint.schema_recovered) {
             if !schema.mutations.is_empty() {
                 if let Ok(mutation_vulns) = self.test_mutations(url, schema).await {
                     endpoint.vulnerabilities.extend(mutation_vulns);
@@ -135,8 +354,11 @@ impl GraphQLTester {
                             kind
                             fields {
                                 name
-                                type { name }
+                                args { name type { name kind ofType { name kind ofType { name kind ofType { name kind } } } } }
+                                type { name kind ofType { name kind ofType { name kind ofType { name kind } } } }
                             }
+                            inputFields { name type { name kind ofType { name kind ofType { name kind ofType { name kind } } } } }
+                            enumValues { name }
                         }
                     }
                 }
@@ -150,51 +372,118 @@ impl GraphQLTester {
 
         let body = response.text().await?;
         let data: Value = serde_json::from_str(&body)?;
+        Ok(GraphQLSchema::from_introspection(&data))
+    }
 
-        let mut schema = GraphQLSchema {
-            types: Vec::new(),
-            queries: Vec::new(),
-            mutations: Vec::new(),
-            has_sensitive_fields: false,
-        };
+    /// Strip NON_NULL/LIST wrappers: returns (named type, kind)
+    pub(crate) fn unwrap_type(t: &Value) -> (String, String) {
+        let mut cur = t;
+        for _ in 0..5 {
+            if let Some(name) = cur.get("name").and_then(|n| n.as_str()) {
+                let kind = cur.get("kind").and_then(|k| k.as_str()).unwrap_or_default();
+                return (name.to_string(), kind.to_string());
+            }
+            match cur.get("ofType") {
+                Some(next) if !next.is_null() => cur = next,
+                _ => break,
+            }
+        }
+        (String::new(), String::new())
+    }
 
-        if let Some(schema_data) = data.get("data").and_then(|d| d.get("__schema")) {
-            // Extract types
-            if let Some(types) = schema_data.get("types").and_then(|t| t.as_array()) {
-                for type_obj in types {
-                    if let Some(name) = type_obj.get("name").and_then(|n| n.as_str()) {
-                        schema.types.push(name.to_string());
+    /// Selection set with the scalar fields of a type, following connection wrappers (edges/node/items)
+    pub(crate) fn build_selection(schema: &GraphQLSchema, type_name: &str, depth: usize) -> Option<String> {
+        let fields = schema.type_fields.get(type_name)?;
+        let mut parts = Vec::new();
 
-                        // Check for sensitive fields
-                        if let Some(fields) = type_obj.get("fields").and_then(|f| f.as_array()) {
-                            for field in fields {
-                                if let Some(field_name) = field.get("name").and_then(|n| n.as_str()) {
-                                    let lower = field_name.to_lowercase();
-                                    if lower.contains("password") || lower.contains("token") 
-                                        || lower.contains("secret") || lower.contains("key")
-                                        || lower.contains("credit") || lower.contains("ssn") {
-                                        schema.has_sensitive_fields = true;
-                                    }
-
-                                    if type_obj.get("name").and_then(|n| n.as_str()) == Some("Query") {
-                                        schema.queries.push(field_name.to_string());
-                                    }
-                                }
-                            }
-                        }
+        for f in fields {
+            // Fields with required arguments can't be selected blindly
+            if f.args.iter().any(|a| a.required) {
+                continue;
+            }
+            match f.kind.as_str() {
+                "SCALAR" | "ENUM" if parts.len() < 20 => parts.push(f.name.clone()),
+                "OBJECT" if depth < 2 && matches!(f.name.as_str(), "edges" | "node" | "nodes" | "items" | "results" | "data") => {
+                    if let Some(sub) = Self::build_selection(schema, &f.type_name, depth + 1) {
+                        parts.push(format!("{} {}", f.name, sub));
                     }
                 }
+                _ => {}
             }
+        }
 
-            // Extract mutations
-            if let Some(mutation_type) = schema_data.get("mutationType") {
-                if let Some(mutation_name) = mutation_type.get("name").and_then(|n| n.as_str()) {
-                    schema.mutations.push(mutation_name.to_string());
+        if parts.is_empty() { None } else { Some(format!("{{ {} }}", parts.join(" "))) }
+    }
+
+    /// Does `type_name` (or an object it wraps via edges/node/items) hold personal data?
+    fn reaches_sensitive_type(schema: &GraphQLSchema, type_name: &str, depth: usize) -> bool {
+        if schema.sensitive_types.iter().any(|t| t == type_name) {
+            return true;
+        }
+        depth < 2 && schema.type_fields.get(type_name).into_iter().flatten().any(|f| {
+            f.kind == "OBJECT"
+                && matches!(f.name.as_str(), "edges" | "node" | "nodes" | "items" | "results" | "data")
+                && Self::reaches_sensitive_type(schema, &f.type_name, depth + 1)
+        })
+    }
+
+    /// Run read-only queries (limit 1) against query fields returning sensitive types and
+    /// classify the returned data for PII, without the scan credentials
+    async fn sample_pii(&self, url: &str, schema: &GraphQLSchema) -> Vec<GraphQLPiiSample> {
+        let mut samples = Vec::new();
+        let Some(client) = &self.anonymous else { return samples };
+        let Some(query_type) = schema.query_type.as_deref() else { return samples };
+        let Some(query_fields) = schema.type_fields.get(query_type) else { return samples };
+
+        let mut attempted = 0;
+        for field in query_fields {
+            if attempted >= MAX_PII_SAMPLES {
+                break;
+            }
+            if field.kind != "OBJECT" || !Self::reaches_sensitive_type(schema, &field.type_name, 0) {
+                continue;
+            }
+
+            // Only limit-style arguments are filled in; anything else required means skip
+            let mut args = Vec::new();
+            let mut unsupported = false;
+            for arg in &field.args {
+                let is_limit = LIMIT_ARGS.contains(&arg.name.to_lowercase().as_str());
+                if is_limit && args.is_empty() {
+                    args.push(format!("{}: 1", arg.name));
+                } else if arg.required {
+                    unsupported = true;
+                    break;
                 }
             }
+            if unsupported {
+                continue;
+            }
+
+            let Some(selection) = Self::build_selection(schema, &field.type_name, 0) else { continue };
+            let args = if args.is_empty() { String::new() } else { format!("({})", args.join(", ")) };
+            let query = format!("query PiiSample {{ {}{} {} }}", field.name, args, selection);
+            attempted += 1;
+
+            let resp = match timeout(Duration::from_secs(5), client.post_json(url, &json!({ "query": query }))).await {
+                Ok(Ok(r)) => r,
+                _ => continue,
+            };
+            let Ok(body) = resp.json::<Value>().await else { continue };
+            let Some(data) = body.get("data").filter(|d| !d.is_null()) else { continue };
+
+            let found = pii::scan_json(data);
+            if !found.is_empty() {
+                samples.push(GraphQLPiiSample {
+                    field: field.name.clone(),
+                    return_type: field.type_name.clone(),
+                    query,
+                    pii: found,
+                });
+            }
         }
 
-        Ok(schema)
+        samples
     }
 
     /// Test batch query attack (resource exhaustion)
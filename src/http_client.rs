@@ -1,9 +1,361 @@
-use reqwest::{Client, ClientBuilder, Response};
-use std::time::Duration;
+//! HTTP clients and request middleware
+//! Every client that talks to the target is built with `builder`/`apply`, which installs the
+//! client-level layers in one place: identification, custom and auth headers (`identify`), the shared
+//! cookie jar (`cookie_jar`), the address family (`net`), the scan-wide HTTP or SOCKS proxy (`configure_proxy`)
+//! the HTTP version (`--http-version`) and the browser TLS profile (`tls_profile`).
+//! Clients for third-party services (Wayback, crt.sh, intel APIs) use `third_party`, which skips the
+//! headers and cookies meant for the target. Request-level layers - retry, throttle, logging - wrap
+//! `HttpClient` as a middleware stack. The `--rps` cap sits below every stack, so retries count too;
+//! modules that send with a plain client go through `Paced::send_paced` to stay under it. With
+//! `--proxy-list` the proxy pool comes before the scan-wide proxy, and the same send paths report each
+//! outcome to it (`observe`).
+
+use reqwest::{Client, ClientBuilder, Request, RequestBuilder, Response};
+use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use std::sync::Arc;
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use serde::Serialize;
 use anyhow::Result;
+use crate::probe::throttle::{self, Throttle};
+
+/// Tor's default SOCKS port; `socks5h` so hostnames are resolved by the exit, not locally
+pub const TOR_PROXY: &str = "socks5h://127.0.0.1:9050";
+
+/// Proxy for all clients (anonymous mode, `--tor`); `None` connects directly
+static PROXY: Lazy<RwLock<Option<ProxyConfig>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` (DNS through the proxy)
+    pub url: String,
+    /// Send a different SOCKS username per target host. Tor isolates streams by SOCKS credentials
+    /// (`IsolateSOCKSAuth`), so every host is reached over its own circuit and exit.
+    pub isolate_per_host: bool,
+}
+
+impl ProxyConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), isolate_per_host: false }
+    }
+
+    /// The local Tor daemon with a circuit per host
+    pub fn tor() -> Self {
+        Self { url: TOR_PROXY.to_string(), isolate_per_host: true }
+    }
+
+    /// Proxy URL for requests to `host`
+    pub fn url_for(&self, host: &str) -> String {
+        if !self.isolate_per_host {
+            return self.url.clone();
+        }
+        match url::Url::parse(&self.url) {
+            Ok(mut u) => {
+                // The password only has to differ between scans, so a new scan gets new circuits
+                let _ = u.set_username(host);
+                let _ = u.set_password(Some(&ISOLATION_NONCE));
+                u.to_string()
+            }
+            Err(_) => self.url.clone(),
+        }
+    }
+
+    pub fn to_proxy(&self) -> reqwest::Result<reqwest::Proxy> {
+        if !self.isolate_per_host {
+            return reqwest::Proxy::all(self.url.as_str());
+        }
+        // Fail on a bad URL here rather than on every request
+        reqwest::Proxy::all(self.url.as_str())?;
+        let config = self.clone();
+        Ok(reqwest::Proxy::custom(move |u| u.host_str().map(|h| config.url_for(h))))
+    }
+}
+
+static ISOLATION_NONCE: Lazy<String> = Lazy::new(|| format!("{:016x}", rand::random::<u64>()));
+
+/// Route clients built afterwards through `proxy`
+pub fn configure_proxy(proxy: Option<ProxyConfig>) {
+    *PROXY.write() = proxy;
+}
+
+pub fn proxy() -> Option<ProxyConfig> {
+    PROXY.read().clone()
+}
+
+/// HTTP version for target clients (`--http-version`)
+static HTTP_VERSION: Lazy<RwLock<HttpVersion>> = Lazy::new(|| RwLock::new(HttpVersion::Auto));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    /// HTTP/2 if the server picks it via ALPN, HTTP/1.1 otherwise (and always over plain HTTP)
+    #[default]
+    Auto,
+    /// HTTP/1.1 only, for origins that mishandle HTTP/2
+    Http1,
+    /// HTTP/2 only; without TLS with prior knowledge, so HTTP/1.1-only origins fail
+    Http2,
+}
+
+impl HttpVersion {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "1" | "1.1" | "http1" => Some(Self::Http1),
+            "2" | "http2" => Some(Self::Http2),
+            _ => None,
+        }
+    }
+
+    /// Protocols offered in the TLS handshake
+    pub fn alpn(&self) -> Vec<Vec<u8>> {
+        match self {
+            Self::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            Self::Http1 => vec![b"http/1.1".to_vec()],
+            Self::Http2 => vec![b"h2".to_vec()],
+        }
+    }
+}
+
+/// Use `version` for target clients built afterwards
+pub fn configure_http_version(version: HttpVersion) {
+    *HTTP_VERSION.write() = version;
+}
+
+pub fn http_version() -> HttpVersion {
+    *HTTP_VERSION.read()
+}
+
+/// `HTTP/1.1`, `HTTP/2`, ... as recorded in the results
+pub fn version_label(version: reqwest::Version) -> &'static str {
+    match version {
+        reqwest::Version::HTTP_09 => "HTTP/0.9",
+        reqwest::Version::HTTP_10 => "HTTP/1.0",
+        reqwest::Version::HTTP_2 => "HTTP/2",
+        reqwest::Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP/1.1",
+    }
+}
+
+/// Scan-wide requests-per-second cap; `None` sends as fast as concurrency allows
+static RATE: Lazy<RwLock<Option<Arc<RateLimiter>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Token bucket of one: requests are spaced `1/rps` apart, so no one-second window holds more than
+/// `rps` requests, however many tasks are waiting
+pub struct RateLimiter {
+    rps: u32,
+    interval: Duration,
+    next: parking_lot::Mutex<Option<tokio::time::Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(rps: u32) -> Self {
+        let rps = rps.max(1);
+        Self { rps, interval: Duration::from_secs(1) / rps, next: parking_lot::Mutex::new(None) }
+    }
+
+    /// Wait for the next free slot (slots are handed out in call order)
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock();
+            let now = tokio::time::Instant::now();
+            let slot = next.map_or(now, |n| n.max(now));
+            *next = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Cap all requests to the target at `rps` per second (`None` or 0 removes the cap)
+pub fn configure_rps(rps: Option<u32>) {
+    *RATE.write() = rps.filter(|r| *r > 0).map(|r| Arc::new(RateLimiter::new(r)));
+}
+
+/// The configured cap, if any
+pub fn rps() -> Option<u32> {
+    RATE.read().as_ref().map(|r| r.rps)
+}
+
+/// Wait until the `--rps` cap allows another request (returns at once without a cap)
+pub async fn pace() {
+    let limiter = RATE.read().clone();
+    if let Some(limiter) = limiter {
+        limiter.acquire().await;
+    }
+}
+
+/// `send` under the `--rps` cap, for requests that don't go through an `HttpClient`
+#[async_trait::async_trait]
+pub trait Paced {
+    async fn send_paced(self) -> reqwest::Result<Response>;
+}
+
+#[async_trait::async_trait]
+impl Paced for RequestBuilder {
+    async fn send_paced(self) -> reqwest::Result<Response> {
+        let (client, req) = self.build_split();
+        let req = req?;
+        let url = req.url().clone();
+        pace().await;
+        let res = client.execute(req).await;
+        observe(&url, &res);
+        res
+    }
+}
+
+/// Report the outcome of a request to the proxy pool, which blacklists proxies that stop working
+pub fn observe(url: &reqwest::Url, result: &reqwest::Result<Response>) {
+    let Some(pool) = crate::anonymizer::pool::shared() else { return };
+    let Some(host) = url.host_str() else { return };
+    let peer = result.as_ref().ok().and_then(|r| r.remote_addr());
+    pool.report(host, peer, result.as_ref().map(|r| r.status().as_u16()).map_err(|e| crate::probe::failure::classify(e)));
+}
+
+/// Address family and proxy; shared by target and third-party clients
+fn network(builder: ClientBuilder) -> ClientBuilder {
+    let mut builder = crate::net::apply(builder);
+    if let Some(pool) = crate::anonymizer::pool::shared() {
+        builder = builder.proxy(pool.to_proxy());
+        // The proxy is picked per connection, so per-request rotation means no reuse
+        if pool.rotation() == crate::anonymizer::pool::Rotation::Request {
+            builder = builder.pool_max_idle_per_host(0);
+        }
+    }
+    match proxy().map(|p| p.to_proxy()) {
+        Some(Ok(p)) => builder.proxy(p),
+        Some(Err(e)) => {
+            tracing::warn!("Ignoring invalid proxy: {}", e);
+            builder
+        }
+        None => builder,
+    }
+}
+
+/// HTTP version negotiation. rustls offers the versions via ALPN (the native TLS backend offers none,
+/// so HTTP/2 could only be reached with prior knowledge).
+fn protocol(builder: ClientBuilder) -> ClientBuilder {
+    let builder = builder.use_rustls_tls();
+    match http_version() {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+    }
+}
+
+/// Install all client-level layers for talking to the target on `builder`. Proxies already set on the
+/// builder take precedence over the scan-wide one.
+pub fn apply(builder: ClientBuilder, component: &str) -> ClientBuilder {
+    crate::identify::apply(crate::tls_profile::apply(protocol(network(crate::cookie_jar::apply(builder)))), component)
+}
+
+/// Client builder for the target, with all client-level layers
+pub fn builder(component: &str) -> ClientBuilder {
+    apply(ClientBuilder::new(), component)
+}
+
+/// Target client without the scan credentials and the shared cookie jar, for requests that bring their
+/// own (e.g. the two identities of the BOLA test)
+pub fn builder_unauthenticated(component: &str) -> ClientBuilder {
+    crate::identify::apply_unauthenticated(crate::tls_profile::apply(protocol(network(ClientBuilder::new()))), component)
+}
+
+/// Client builder for third-party services: address family and proxy, nothing meant for the target
+pub fn third_party() -> ClientBuilder {
+    network(ClientBuilder::new())
+}
+
+/// A request-level layer. Layers run in the order they were added; `next.run` passes the request on.
+#[async_trait::async_trait]
+pub trait Middleware: Send + Sync {
+    async fn handle(&self, req: Request, next: Next<'_>) -> Result<Response>;
+}
+
+/// The rest of the stack below a layer
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    client: &'a Client,
+    layers: &'a [Arc<dyn Middleware>],
+}
+
+impl Next<'_> {
+    pub async fn run(self, req: Request) -> Result<Response> {
+        match self.layers.split_first() {
+            Some((layer, rest)) => layer.handle(req, Next { client: self.client, layers: rest }).await,
+            None => {
+                let url = req.url().clone();
+                pace().await;
+                let res = self.client.execute(req).await;
+                observe(&url, &res);
+                Ok(res?)
+            }
+        }
+    }
+}
+
+/// Retry transient failures (timeouts, resets) with exponential backoff. Requests with streaming
+/// bodies can't be cloned and are sent once.
+pub struct Retry {
+    attempts: usize,
+    backoff: Duration,
+}
+
+impl Retry {
+    pub fn new(attempts: usize) -> Self {
+        Self { attempts: attempts.clamp(1, 10), backoff: Duration::from_millis(200) }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for Retry {
+    async fn handle(&self, req: Request, next: Next<'_>) -> Result<Response> {
+        let mut backoff = self.backoff;
+        for _ in 1..self.attempts {
+            let Some(copy) = req.try_clone() else { break };
+            match next.run(copy).await {
+                Err(e) if e.downcast_ref::<reqwest::Error>().is_some_and(|re| crate::probe::failure::classify(re).is_transient()) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_secs(5));
+                }
+                res => return res,
+            }
+        }
+        next.run(req).await
+    }
+}
+
+/// Per-host concurrency shared with the probe; 429/503 responses slow the host down
+pub struct Throttled(pub Arc<Throttle>);
+
+#[async_trait::async_trait]
+impl Middleware for Throttled {
+    async fn handle(&self, req: Request, next: Next<'_>) -> Result<Response> {
+        let Some(host) = req.url().host_str().map(str::to_string) else { return next.run(req).await };
+        let _permit = self.0.acquire(&host).await;
+        let resp = next.run(req).await?;
+        let retry_after = resp.headers().get(reqwest::header::RETRY_AFTER).and_then(|v| v.to_str().ok()).and_then(throttle::parse_retry_after);
+        self.0.record(&host, resp.status().as_u16(), retry_after);
+        Ok(resp)
+    }
+}
+
+/// Debug log line per request
+pub struct Logging(pub &'static str);
+
+#[async_trait::async_trait]
+impl Middleware for Logging {
+    async fn handle(&self, req: Request, next: Next<'_>) -> Result<Response> {
+        let (method, url) = (req.method().clone(), req.url().to_string());
+        let start = Instant::now();
+        let res = next.run(req).await;
+        match &res {
+            Ok(resp) => tracing::debug!("[{}] {} {} -> {} ({}ms)", self.0, method, url, resp.status().as_u16(), start.elapsed().as_millis()),
+            Err(e) => tracing::debug!("[{}] {} {} -> error: {}", self.0, method, url, e),
+        }
+        res
+    }
+}
 
 /// High-performance HTTP client with all optimizations enabled
 pub static OPTIMIZED_CLIENT: Lazy<Client> = Lazy::new(|| {
@@ -12,7 +364,7 @@ pub static OPTIMIZED_CLIENT: Lazy<Client> = Lazy::new(|| {
 
 /// Create optimized HTTP client with connection pooling and HTTP/2
 pub fn create_optimized_client(timeout_secs: u64, max_idle_connections: usize) -> Client {
-    ClientBuilder::new()
+    builder("http_client::optimized")
         // Connection pooling - reuse connections aggressively
         .pool_max_idle_per_host(max_idle_connections)
         .pool_idle_timeout(Some(Duration::from_secs(90)))
@@ -28,7 +380,6 @@ pub fn create_optimized_client(timeout_secs: u64, max_idle_connections: usize) -
         .brotli(true)
         
         // TLS optimizations
-        .use_rustls_tls()
         .tls_sni(true)
         .https_only(false) // Allow both HTTP and HTTPS
         
@@ -36,7 +387,7 @@ pub fn create_optimized_client(timeout_secs: u64, max_idle_connections: usize) -
         .redirect(reqwest::redirect::Policy::limited(5))
         
         // User agent
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+        .user_agent(crate::tls_profile::user_agent_or("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"))
         
         // Disable certificate validation for pentesting (ONLY for security research!)
         .danger_accept_invalid_certs(true)
@@ -52,38 +403,54 @@ pub fn create_aggressive_client() -> Client {
 
 /// Create client for stealth/lite mode
 pub fn create_stealth_client() -> Client {
-    ClientBuilder::new()
-        .http1_only() // Some targets don't like HTTP/2
+    // Some targets don't like HTTP/2; an explicit --http-version wins
+    let builder = builder("http_client::stealth");
+    let builder = if http_version() == HttpVersion::Auto { builder.http1_only() } else { builder };
+    builder
         .pool_max_idle_per_host(10)
         .timeout(Duration::from_secs(15))
         .connect_timeout(Duration::from_secs(10))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0")
-        .use_rustls_tls()
+        .user_agent(crate::tls_profile::user_agent_or("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0"))
         .danger_accept_invalid_certs(true)
         .build()
         .expect("Failed to build stealth client")
 }
 
-/// HTTP Client wrapper with convenience methods
+/// HTTP Client wrapper with convenience methods; every request passes the middleware stack
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
+    layers: Vec<Arc<dyn Middleware>>,
 }
 
 impl HttpClient {
     pub fn new(client: Client) -> Self {
-        Self { client }
+        Self { client, layers: Vec::new() }
     }
 
     pub fn from_optimized() -> Self {
-        Self {
-            client: OPTIMIZED_CLIENT.clone(),
-        }
+        Self::new(OPTIMIZED_CLIENT.clone())
+    }
+
+    /// Add a layer below the ones added before
+    pub fn with<M: Middleware + 'static>(mut self, layer: M) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+
+    /// The underlying client, for requests that must bypass the stack
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Send a request built from `client()` through the stack
+    pub async fn send(&self, req: RequestBuilder) -> Result<Response> {
+        Next { client: &self.client, layers: &self.layers }.run(req.build()?).await
     }
 
     /// GET request
     pub async fn get(&self, url: &str) -> Result<Response> {
-        Ok(self.client.get(url).send().await?)
+        self.send(self.client.get(url)).await
     }
 
     /// GET request with custom headers
@@ -92,27 +459,27 @@ impl HttpClient {
         for (key, value) in headers {
             req = req.header(key, value);
         }
-        Ok(req.send().await?)
+        self.send(req).await
     }
 
     /// POST JSON request
     pub async fn post_json<T: Serialize>(&self, url: &str, json: &T) -> Result<Response> {
-        Ok(self.client.post(url).json(json).send().await?)
+        self.send(self.client.post(url).json(json)).await
     }
 
     /// PUT JSON request
     pub async fn put_json<T: Serialize>(&self, url: &str, json: &T) -> Result<Response> {
-        Ok(self.client.put(url).json(json).send().await?)
+        self.send(self.client.put(url).json(json)).await
     }
 
     /// PATCH JSON request
     pub async fn patch_json<T: Serialize>(&self, url: &str, json: &T) -> Result<Response> {
-        Ok(self.client.patch(url).json(json).send().await?)
+        self.send(self.client.patch(url).json(json)).await
     }
 
     /// POST with form data
     pub async fn post_form(&self, url: &str, form: &HashMap<String, String>) -> Result<Response> {
-        Ok(self.client.post(url).form(form).send().await?)
+        self.send(self.client.post(url).form(form)).await
     }
 }
 
@@ -123,6 +490,6 @@ mod tests {
     #[test]
     fn test_client_creation() {
         let client = create_optimized_client(10, 100);
-        assert!(client.timeout().is_some());
+        assert!(client.get("https://example.com").build().is_ok());
     }
 }
@@ -0,0 +1,164 @@
+//! Scanner identification headers required by bug bounty programs (e.g. `X-Bug-Bounty: <username>`)
+//! and custom headers from `-H` (cookies, auth tokens, ...).
+//! Configured once at startup and installed as default headers on every client that talks to the
+//! target. Each client build is recorded so the audit log can show nothing went out without them.
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::ClientBuilder;
+use serde::Serialize;
+
+static HEADERS: Lazy<RwLock<HeaderMap>> = Lazy::new(|| RwLock::new(HeaderMap::new()));
+/// `-H` headers; values never appear in the audit since they may carry credentials
+static CUSTOM: Lazy<RwLock<HeaderMap>> = Lazy::new(|| RwLock::new(HeaderMap::new()));
+/// Credentials from `auth::session`
+static AUTH: Lazy<RwLock<HeaderMap>> = Lazy::new(|| RwLock::new(HeaderMap::new()));
+static CLIENTS: Lazy<RwLock<Vec<ClientRecord>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientRecord {
+    /// Module that built the client
+    pub component: String,
+    /// Header names installed on it (identification and custom)
+    pub headers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentifyAudit {
+    /// Configured headers (`Name: value`)
+    pub headers: Vec<String>,
+    pub clients: Vec<ClientRecord>,
+    /// Components whose client is missing one or more configured headers
+    pub missing: Vec<String>,
+    pub verified: bool,
+}
+
+/// Parse `Name:value` (also accepts `Name: value`)
+pub fn parse(spec: &str) -> Result<(HeaderName, HeaderValue)> {
+    let Some((name, value)) = spec.split_once(':') else {
+        bail!("invalid header '{}', expected Name:value", spec);
+    };
+    let name = HeaderName::from_bytes(name.trim().as_bytes())
+        .map_err(|_| anyhow::anyhow!("invalid header name in '{}'", spec))?;
+    let value = HeaderValue::from_str(value.trim())
+        .map_err(|_| anyhow::anyhow!("invalid header value in '{}'", spec))?;
+    Ok((name, value))
+}
+
+fn parse_all(specs: &[String]) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for spec in specs {
+        let (name, value) = parse(spec)?;
+        map.append(name, value);
+    }
+    Ok(map)
+}
+
+/// Set the identification headers. Must run before any target client is built.
+pub fn configure(specs: &[String]) -> Result<()> {
+    *HEADERS.write() = parse_all(specs)?;
+    Ok(())
+}
+
+/// Set the custom `-H` headers. Must run before any target client is built.
+pub fn configure_custom(specs: &[String]) -> Result<()> {
+    *CUSTOM.write() = parse_all(specs)?;
+    Ok(())
+}
+
+/// Set the credential headers (see `auth::session`). Must run before any target client is built.
+pub fn configure_auth(headers: HeaderMap) {
+    *AUTH.write() = headers;
+}
+
+/// Everything sent to the target: custom headers, credentials, then identification headers. Each
+/// layer replaces headers of the same name from the ones before.
+pub fn headers() -> HeaderMap {
+    let mut map = CUSTOM.read().clone();
+    for layer in [AUTH.read().clone(), HEADERS.read().clone()] {
+        for name in layer.keys() {
+            map.remove(name);
+        }
+        for (name, value) in layer.iter() {
+            map.append(name.clone(), value.clone());
+        }
+    }
+    map
+}
+
+/// Credentials are configured
+pub fn has_auth() -> bool {
+    !AUTH.read().is_empty()
+}
+
+/// Identification headers are configured
+pub fn is_configured() -> bool {
+    !HEADERS.read().is_empty()
+}
+
+/// Any header (identification or custom) is sent to the target
+pub fn has_headers() -> bool {
+    is_configured() || has_auth() || !CUSTOM.read().is_empty()
+}
+
+/// Install the identification headers on a client builder and record it for the audit.
+/// Overrides earlier `default_headers` calls on the same builder.
+pub fn apply(builder: ClientBuilder, component: &str) -> ClientBuilder {
+    let headers = record(component);
+    if headers.is_empty() {
+        return builder;
+    }
+    builder.default_headers(headers)
+}
+
+/// Like `apply`, but without the scan credentials; for clients that authenticate per request
+/// (e.g. the two identities of the BOLA test)
+pub fn apply_unauthenticated(builder: ClientBuilder, component: &str) -> ClientBuilder {
+    let mut headers = record(component);
+    for name in AUTH.read().keys() {
+        headers.remove(name);
+    }
+    if headers.is_empty() {
+        return builder;
+    }
+    builder.default_headers(headers)
+}
+
+/// Record that `component` sends the current identification headers and return them,
+/// for request paths that don't go through a reqwest client (e.g. the headless browser)
+pub fn record(component: &str) -> HeaderMap {
+    let headers = headers();
+    let record = ClientRecord {
+        component: component.to_string(),
+        headers: headers.keys().map(|k| k.to_string()).collect(),
+    };
+    // Clients built before `configure` are recorded too, so the audit can flag them
+    let mut clients = CLIENTS.write();
+    if !clients.iter().any(|c| c.component == record.component && c.headers == record.headers) {
+        clients.push(record);
+    }
+    headers
+}
+
+/// Check every recorded client carries all configured headers
+pub fn audit() -> IdentifyAudit {
+    let configured = HEADERS.read().clone();
+    let clients = CLIENTS.read().clone();
+    let mut missing: Vec<String> = clients.iter()
+        .filter(|c| configured.keys().any(|k| !c.headers.iter().any(|h| h == k.as_str())))
+        .map(|c| c.component.clone())
+        .collect();
+    missing.sort();
+    missing.dedup();
+
+    IdentifyAudit {
+        headers: configured.iter()
+            .map(|(k, v)| format!("{}: {}", k, v.to_str().unwrap_or("<binary>")))
+            .collect(),
+        verified: missing.is_empty(),
+        clients,
+        missing,
+    }
+}
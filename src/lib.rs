@@ -1,6 +1,10 @@
 pub mod enrich;
 pub mod filter;
 pub mod config;
+pub mod identify;
+pub mod cookie_jar;
+pub mod net;
+pub mod tls_profile;
 pub mod output;
 pub mod utils;
 pub mod probe;
@@ -18,6 +22,7 @@ pub mod test_endpoint;
 pub mod security;
 pub mod graphql;
 pub mod auth;
+pub mod oob;
 
 // re-export modules used in tests
 pub use crate::enrich::*;
@@ -0,0 +1,235 @@
+//! DNS over HTTPS (`--doh`)
+//! With a DoH server set, every lookup the scanner makes - the resolver of all HTTP clients and
+//! `net::resolve` (subdomain brute force, pre-flight, origin and vhost checks) - is an HTTPS request to
+//! that server instead of a query to the local resolver, so neither the network nor the ISP's resolver
+//! sees the target list. Queries use the RFC 8484 wire format over GET, which Cloudflare, Google and
+//! self-hosted resolvers (dnsdist, CoreDNS, AdGuard) all accept. Cloudflare and Google are reached by IP,
+//! so not even their own names are looked up locally; a custom server's name is.
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::AddressFamily;
+
+/// Answers are cached at least this long, whatever their TTL
+const MIN_TTL: Duration = Duration::from_secs(60);
+
+/// How long a failed or empty lookup is remembered
+const NEGATIVE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DohServer {
+    Cloudflare,
+    Google,
+    /// Full URL of the `dns-query` endpoint
+    Custom(String),
+}
+
+impl DohServer {
+    /// `cloudflare`, `google` or an `https://` URL
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cloudflare" => Ok(Self::Cloudflare),
+            "google" => Ok(Self::Google),
+            _ => {
+                let url = url::Url::parse(s.trim()).map_err(|e| anyhow!("invalid DoH server '{}': {}", s, e))?;
+                if !matches!(url.scheme(), "https" | "http") {
+                    bail!("DoH server must be cloudflare, google or an https:// URL, got '{}'", s);
+                }
+                Ok(Self::Custom(url.to_string()))
+            }
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            Self::Cloudflare => "https://cloudflare-dns.com/dns-query",
+            Self::Google => "https://dns.google/dns-query",
+            Self::Custom(url) => url,
+        }
+    }
+
+    /// Fixed addresses of the well-known servers
+    fn bootstrap(&self) -> Option<(&'static str, Vec<SocketAddr>)> {
+        let ips: &[IpAddr] = match self {
+            Self::Cloudflare => &[IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), IpAddr::V4(Ipv4Addr::new(1, 0, 0, 1))],
+            Self::Google => &[IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)), IpAddr::V4(Ipv4Addr::new(8, 8, 4, 4))],
+            Self::Custom(_) => return None,
+        };
+        let host = match self {
+            Self::Cloudflare => "cloudflare-dns.com",
+            _ => "dns.google",
+        };
+        Some((host, ips.iter().map(|ip| SocketAddr::new(*ip, 443)).collect()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordType {
+    A,
+    Aaaa,
+}
+
+impl RecordType {
+    fn code(&self) -> u16 {
+        match self {
+            Self::A => 1,
+            Self::Aaaa => 28,
+        }
+    }
+}
+
+/// DNS query message for `name` (RFC 1035), ID 0 as RFC 8484 recommends for cacheability
+pub fn encode_query(name: &str, qtype: RecordType) -> Result<Vec<u8>> {
+    let mut msg = vec![0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            bail!("invalid DNS name '{}'", name);
+        }
+        msg.push(label.len() as u8);
+        msg.extend_from_slice(label.as_bytes());
+    }
+    msg.push(0);
+    msg.extend_from_slice(&qtype.code().to_be_bytes());
+    msg.extend_from_slice(&1u16.to_be_bytes());
+    Ok(msg)
+}
+
+fn skip_name(msg: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *msg.get(pos)? as usize;
+        match len {
+            0 => return Some(pos + 1),
+            l if l & 0xC0 == 0xC0 => return Some(pos + 2),
+            l => pos += 1 + l,
+        }
+    }
+}
+
+fn read_u16(msg: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*msg.get(pos)?, *msg.get(pos + 1)?]))
+}
+
+/// Addresses of type `qtype` in a response and their lowest TTL. A name that doesn't exist is an empty
+/// answer, not an error.
+pub fn parse_response(msg: &[u8], qtype: RecordType) -> Result<(Vec<IpAddr>, Duration)> {
+    let malformed = || anyhow!("malformed DNS response");
+    let flags = read_u16(msg, 2).ok_or_else(malformed)?;
+    match flags & 0x000F {
+        0 | 3 => {}
+        rcode => bail!("DNS server answered with rcode {}", rcode),
+    }
+    let questions = read_u16(msg, 4).ok_or_else(malformed)?;
+    let answers = read_u16(msg, 6).ok_or_else(malformed)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(msg, pos).ok_or_else(malformed)? + 4;
+    }
+    let mut ips = Vec::new();
+    let mut ttl = u32::MAX;
+    for _ in 0..answers {
+        pos = skip_name(msg, pos).ok_or_else(malformed)?;
+        let rtype = read_u16(msg, pos).ok_or_else(malformed)?;
+        let rttl = u32::from_be_bytes(msg.get(pos + 4..pos + 8).ok_or_else(malformed)?.try_into()?);
+        let len = read_u16(msg, pos + 8).ok_or_else(malformed)? as usize;
+        let data = msg.get(pos + 10..pos + 10 + len).ok_or_else(malformed)?;
+        pos += 10 + len;
+        let ip = match (rtype, len) {
+            (1, 4) if qtype == RecordType::A => IpAddr::V4(Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+            (28, 16) if qtype == RecordType::Aaaa => {
+                let octets: [u8; 16] = data.try_into()?;
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            // CNAMEs on the way to the address
+            _ => continue,
+        };
+        ttl = ttl.min(rttl);
+        ips.push(ip);
+    }
+    Ok((ips, Duration::from_secs(ttl as u64)))
+}
+
+pub struct DohResolver {
+    server: DohServer,
+    client: reqwest::Client,
+    cache: DashMap<(String, RecordType), (Vec<IpAddr>, Instant)>,
+}
+
+impl DohResolver {
+    pub fn new(server: DohServer) -> Result<Self> {
+        // Plain builder: the scan's clients resolve through this one
+        let mut builder = reqwest::Client::builder()
+            .use_rustls_tls()
+            .timeout(Duration::from_secs(5))
+            .pool_max_idle_per_host(4);
+        if let Some((host, addrs)) = server.bootstrap() {
+            builder = builder.resolve_to_addrs(host, &addrs);
+        }
+        Ok(Self { server, client: builder.build()?, cache: DashMap::new() })
+    }
+
+    pub fn server(&self) -> &DohServer {
+        &self.server
+    }
+
+    /// Records of one type, from the cache while their TTL lasts
+    pub async fn lookup(&self, name: &str, qtype: RecordType) -> Result<Vec<IpAddr>> {
+        let key = (name.trim_end_matches('.').to_ascii_lowercase(), qtype);
+        if let Some(entry) = self.cache.get(&key) {
+            if entry.1 > Instant::now() {
+                return Ok(entry.0.clone());
+            }
+        }
+        let result = self.query(&key.0, qtype).await;
+        let (ips, ttl) = match &result {
+            Ok((ips, ttl)) if !ips.is_empty() => (ips.clone(), (*ttl).max(MIN_TTL)),
+            _ => (Vec::new(), NEGATIVE_TTL),
+        };
+        self.cache.insert(key, (ips, Instant::now() + ttl));
+        result.map(|(ips, _)| ips)
+    }
+
+    async fn query(&self, name: &str, qtype: RecordType) -> Result<(Vec<IpAddr>, Duration)> {
+        let dns = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(encode_query(name, qtype)?);
+        let resp = self.client.get(self.server.url())
+            .query(&[("dns", dns)])
+            .header(reqwest::header::ACCEPT, "application/dns-message")
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            bail!("DoH server answered {}", resp.status());
+        }
+        parse_response(&resp.bytes().await?, qtype)
+    }
+
+    /// A and/or AAAA records of `name`, as the family allows; IPv4 first
+    pub async fn lookup_ip(&self, name: &str, family: AddressFamily) -> Result<Vec<IpAddr>> {
+        let (v4, v6) = match family {
+            AddressFamily::V4 => (self.lookup(name, RecordType::A).await, Ok(Vec::new())),
+            AddressFamily::V6 => (Ok(Vec::new()), self.lookup(name, RecordType::Aaaa).await),
+            AddressFamily::Any => tokio::join!(self.lookup(name, RecordType::A), self.lookup(name, RecordType::Aaaa)),
+        };
+        match (v4, v6) {
+            (Err(e), Err(_)) => Err(e),
+            (v4, v6) => Ok(v4.unwrap_or_default().into_iter().chain(v6.unwrap_or_default()).collect()),
+        }
+    }
+}
+
+static SHARED: Lazy<RwLock<Option<Arc<DohResolver>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Resolve through `server` for all lookups and clients built afterwards; `None` uses the system resolver
+pub fn configure(server: Option<DohServer>) -> Result<()> {
+    *SHARED.write() = server.map(DohResolver::new).transpose()?.map(Arc::new);
+    Ok(())
+}
+
+pub fn shared() -> Option<Arc<DohResolver>> {
+    SHARED.read().clone()
+}
@@ -0,0 +1,156 @@
+//! Address family selection (`-4`/`-6`) and name resolution
+//! DNS lookups made by the scanner go through `resolve`, and target clients built with
+//! `http_client::builder` get a resolver that drops addresses of the other family. Without a flag both A and
+//! AAAA records are used, in the order the system resolver returns them, so IPv6-only APIs are reached too.
+//! With `--doh` both ask the DoH server (`doh`) instead of the system resolver.
+
+pub mod doh;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl AddressFamily {
+    /// From the `-4`/`-6` flags
+    pub fn from_flags(ipv4: bool, ipv6: bool) -> Self {
+        match (ipv4, ipv6) {
+            (true, false) => Self::V4,
+            (false, true) => Self::V6,
+            _ => Self::Any,
+        }
+    }
+
+    pub fn allows(&self, ip: &IpAddr) -> bool {
+        match self {
+            Self::Any => true,
+            Self::V4 => ip.is_ipv4(),
+            Self::V6 => ip.is_ipv6(),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Any => "IPv4+IPv6",
+            Self::V4 => "IPv4",
+            Self::V6 => "IPv6",
+        }
+    }
+}
+
+static FAMILY: Lazy<RwLock<AddressFamily>> = Lazy::new(|| RwLock::new(AddressFamily::Any));
+
+/// Set the family for all lookups and clients built afterwards
+pub fn set_family(family: AddressFamily) {
+    *FAMILY.write() = family;
+}
+
+pub fn family() -> AddressFamily {
+    *FAMILY.read()
+}
+
+/// Addresses of `host` in the selected family; an IP literal (brackets allowed) is returned as is if allowed
+pub async fn resolve(host: &str, port: u16) -> Vec<SocketAddr> {
+    let family = family();
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => match doh::shared() {
+            Some(resolver) => resolver.lookup_ip(host, family).await
+                .map(|ips| ips.into_iter().map(|ip| SocketAddr::new(ip, port)).collect())
+                .unwrap_or_default(),
+            None => match tokio::net::lookup_host((host, port)).await {
+                Ok(addrs) => addrs.collect(),
+                Err(_) => Vec::new(),
+            },
+        },
+    };
+    let mut out: Vec<SocketAddr> = Vec::new();
+    for addr in addrs.into_iter().filter(|a| family.allows(&a.ip())) {
+        if !out.contains(&addr) {
+            out.push(addr);
+        }
+    }
+    out
+}
+
+/// Whether `host` has an address in the selected family, within `timeout`
+pub async fn resolves(host: &str, timeout: Duration) -> bool {
+    matches!(tokio::time::timeout(timeout, resolve(host, 443)).await, Ok(addrs) if !addrs.is_empty())
+}
+
+/// Connect to the first reachable address of `host` in the selected family
+pub async fn connect(host: &str, port: u16, timeout: Duration) -> Option<tokio::net::TcpStream> {
+    for addr in resolve(host, port).await {
+        if let Ok(Ok(stream)) = tokio::time::timeout(timeout, tokio::net::TcpStream::connect(addr)).await {
+            return Some(stream);
+        }
+    }
+    None
+}
+
+/// Byte stream of a raw HTTP/1.1 connection, plain or TLS
+pub trait RawStream: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send {}
+impl<T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send> RawStream for T {}
+
+/// Connection for hand-written requests: TCP, plus a TLS handshake for `https` (certificate not verified,
+/// no ALPN, so the server speaks HTTP/1.1)
+pub async fn connect_raw(host: &str, port: u16, https: bool, timeout: Duration) -> Option<Box<dyn RawStream>> {
+    let tcp = connect(host, port, timeout).await?;
+    if !https {
+        return Some(Box::new(tcp));
+    }
+    let config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(crate::probe::tls::AcceptAny))
+        .with_no_client_auth();
+    let name = rustls::ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']')).ok()?;
+    let tls = tokio::time::timeout(timeout, tokio_rustls::TlsConnector::from(Arc::new(config)).connect(name, tcp)).await.ok()?.ok()?;
+    Some(Box::new(tls))
+}
+
+/// reqwest resolver restricted to the selected family, asking the DoH server if one is set
+pub struct FamilyResolver {
+    family: AddressFamily,
+    doh: Option<Arc<doh::DohResolver>>,
+}
+
+impl Resolve for FamilyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let family = self.family;
+        let doh = self.doh.clone();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = match doh {
+                Some(doh) => doh.lookup_ip(name.as_str(), family).await?.into_iter().map(|ip| SocketAddr::new(ip, 0)).collect(),
+                None => tokio::net::lookup_host((name.as_str(), 0)).await?.collect(),
+            };
+            let addrs: Vec<SocketAddr> = addrs.into_iter().filter(|a| family.allows(&a.ip())).collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no {} address", name.as_str(), family.label()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Install the family resolver on a client (nothing to do without `-4`/`-6` and `--doh`)
+pub fn apply(builder: ClientBuilder) -> ClientBuilder {
+    match (family(), doh::shared()) {
+        (AddressFamily::Any, None) => builder,
+        (family, doh) => builder.dns_resolver(Arc::new(FamilyResolver { family, doh })),
+    }
+}
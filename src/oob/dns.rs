@@ -0,0 +1,63 @@
+//! Minimal DNS wire format for the callback listener: the question of a query, and an answer with
+//! one A record (or none) for it.
+
+use std::net::Ipv4Addr;
+
+pub const TYPE_A: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Question {
+    pub id: u16,
+    /// Lowercase, without the trailing dot
+    pub name: String,
+    pub qtype: u16,
+    /// End of the question section in the packet
+    end: usize,
+}
+
+/// The first question of a query packet
+pub fn parse_query(packet: &[u8]) -> Option<Question> {
+    if packet.len() < 12 || packet[2] & 0x80 != 0 || u16::from_be_bytes([packet[4], packet[5]]) == 0 {
+        return None;
+    }
+    let mut labels = Vec::new();
+    let mut pos = 12;
+    loop {
+        let len = *packet.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        // Compression pointers don't occur in questions
+        if len > 63 {
+            return None;
+        }
+        labels.push(String::from_utf8_lossy(packet.get(pos..pos + len)?).to_ascii_lowercase());
+        pos += len;
+    }
+    let qtype = u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]);
+    Some(Question { id: u16::from_be_bytes([packet[0], packet[1]]), name: labels.join("."), qtype, end: pos + 4 })
+}
+
+/// Authoritative answer to `query`: `ip` for A questions, an empty answer otherwise
+pub fn answer(query: &[u8], question: &Question, ip: Option<Ipv4Addr>) -> Vec<u8> {
+    let record = ip.filter(|_| question.qtype == TYPE_A);
+    let mut out = Vec::with_capacity(question.end + 16);
+    out.extend_from_slice(&question.id.to_be_bytes());
+    // QR, AA, RD copied from the query
+    out.extend_from_slice(&[0x84 | (query[2] & 0x01), 0x00]);
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&(record.is_some() as u16).to_be_bytes());
+    out.extend_from_slice(&[0, 0, 0, 0]);
+    out.extend_from_slice(&query[12..question.end]);
+    if let Some(ip) = record {
+        // Name: pointer to the question
+        out.extend_from_slice(&[0xc0, 0x0c]);
+        out.extend_from_slice(&TYPE_A.to_be_bytes());
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&60u32.to_be_bytes());
+        out.extend_from_slice(&4u16.to_be_bytes());
+        out.extend_from_slice(&ip.octets());
+    }
+    out
+}
@@ -0,0 +1,318 @@
+//! Out-of-band interaction detection
+//! Blind SSRF, XXE and injections don't show in the response, but the server resolves or fetches a URL
+//! it was given. Every payload gets a callback host `<id>.<domain>` with its own id; a built-in HTTP
+//! listener (and optionally a DNS server, authoritative for the domain) records incoming requests and
+//! lookups, and `correlate` maps them back to the request that carried the id.
+//!
+//! Self-hosted: the callback domain (`--oob-domain`) needs a wildcard A record pointing at this machine,
+//! or an NS record for it when `--oob-dns` is used. interactsh servers aren't supported - their poll API
+//! needs RSA/AES-CFB, which the crate doesn't carry.
+
+pub mod dns;
+pub mod payloads;
+
+use crate::fuzz::param_bruteforce::ParamLocation;
+use crate::http_client::HttpClient;
+use crate::output::clean_reporter::Severity;
+use anyhow::Result;
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Head bytes of a callback request recorded
+const REQUEST_LIMIT: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OobKind {
+    Ssrf,
+    Xxe,
+    CommandInjection,
+    SqlInjection,
+    /// `${jndi:...}` lookups (Log4Shell)
+    Jndi,
+}
+
+impl OobKind {
+    /// Severity when the server called back: over HTTP it fetched the URL, over DNS only it resolved the
+    /// name (resolvers, link previews and WAFs do that too for SSRF/XXE)
+    pub fn severity(&self, http: bool) -> Severity {
+        match self {
+            Self::CommandInjection | Self::SqlInjection | Self::Jndi => Severity::Critical,
+            Self::Ssrf | Self::Xxe if http => Severity::High,
+            Self::Ssrf | Self::Xxe => Severity::Medium,
+        }
+    }
+
+    /// Payloads for a callback host
+    pub fn payloads(&self, host: &str) -> Vec<String> {
+        let url = format!("http://{}/", host);
+        match self {
+            Self::Ssrf => payloads::ssrf(&url),
+            Self::Xxe => payloads::xxe(&url),
+            Self::CommandInjection => payloads::command(host),
+            Self::SqlInjection => payloads::sql(host),
+            Self::Jndi => payloads::jndi(host),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Dns,
+    Http,
+}
+
+/// A request that carried a callback
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Probe {
+    pub id: String,
+    pub kind: OobKind,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+    pub payloads: Vec<String>,
+    pub sent_at: u64,
+}
+
+/// A lookup or request that reached the listener
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub id: String,
+    pub protocol: Protocol,
+    pub remote: String,
+    /// Query name, or the request line and Host header
+    pub data: String,
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OobFinding {
+    pub probe: Probe,
+    pub severity: Severity,
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OobConfig {
+    /// Callback domain; ids become its subdomains
+    pub domain: String,
+    pub http_listen: SocketAddr,
+    /// DNS server for the domain (UDP)
+    pub dns_listen: Option<SocketAddr>,
+    /// Address the DNS server answers A queries with (this machine's public IP)
+    pub answer_ip: Option<Ipv4Addr>,
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Callback id in a host name below `domain` (the label right before it), lowercase
+pub fn id_from_host(host: &str, domain: &str) -> Option<String> {
+    let host = host.trim().to_ascii_lowercase();
+    let host = host.rsplit_once(':').filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit())).map_or(host.as_str(), |(h, _)| h);
+    let host = host.trim_end_matches('.');
+    let prefix = host.strip_suffix(&domain.to_ascii_lowercase())?.strip_suffix('.')?;
+    prefix.rsplit('.').next().filter(|id| !id.is_empty()).map(str::to_string)
+}
+
+/// Request line and Host header of a raw HTTP request
+fn request_summary(head: &str) -> (String, Option<String>) {
+    let line = head.lines().next().unwrap_or_default().to_string();
+    let host = head.lines()
+        .find_map(|l| l.split_once(':').filter(|(k, _)| k.eq_ignore_ascii_case("host")).map(|(_, v)| v.trim().to_string()));
+    (line, host)
+}
+
+struct State {
+    domain: String,
+    probes: Mutex<HashMap<String, Probe>>,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl State {
+    fn record(&self, id: String, protocol: Protocol, remote: SocketAddr, data: String) {
+        tracing::debug!("oob: {:?} interaction {} from {}: {}", protocol, id, remote, data);
+        self.interactions.lock().push(Interaction { id, protocol, remote: remote.to_string(), data, at: unix_now() });
+    }
+}
+
+pub struct OobServer {
+    state: Arc<State>,
+    http_addr: SocketAddr,
+    dns_addr: Option<SocketAddr>,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl OobServer {
+    /// Bind the listeners
+    pub async fn start(config: OobConfig) -> Result<Self> {
+        let state = Arc::new(State {
+            domain: config.domain.trim_end_matches('.').to_ascii_lowercase(),
+            probes: Mutex::new(HashMap::new()),
+            interactions: Mutex::new(Vec::new()),
+        });
+        let listener = tokio::net::TcpListener::bind(config.http_listen).await
+            .map_err(|e| anyhow::anyhow!("failed to bind OOB HTTP listener on {}: {}", config.http_listen, e))?;
+        let http_addr = listener.local_addr()?;
+        let mut tasks = vec![tokio::spawn(serve_http(listener, state.clone()))];
+        let mut dns_addr = None;
+        if let Some(addr) = config.dns_listen {
+            let socket = tokio::net::UdpSocket::bind(addr).await
+                .map_err(|e| anyhow::anyhow!("failed to bind OOB DNS server on {}: {}", addr, e))?;
+            dns_addr = Some(socket.local_addr()?);
+            tasks.push(tokio::spawn(serve_dns(socket, state.clone(), config.answer_ip)));
+        }
+        Ok(Self { state, http_addr, dns_addr, tasks })
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.state.domain
+    }
+
+    pub fn http_addr(&self) -> SocketAddr {
+        self.http_addr
+    }
+
+    pub fn dns_addr(&self) -> Option<SocketAddr> {
+        self.dns_addr
+    }
+
+    /// New callback: (id, host)
+    pub fn callback(&self) -> (String, String) {
+        let mut rng = rand::thread_rng();
+        let id: String = (0..12).map(|_| char::from(b"abcdefghijklmnopqrstuvwxyz0123456789"[rng.gen_range(0..36)])).collect();
+        let host = format!("{}.{}", id, self.state.domain);
+        (id, host)
+    }
+
+    /// Remember what was sent with a callback id; interactions with unregistered ids are ignored
+    pub fn register(&self, id: &str, kind: OobKind, url: &str, parameter: Option<&str>, payloads: Vec<String>) {
+        let probe = Probe { id: id.to_string(), kind, url: url.to_string(), parameter: parameter.map(str::to_string), payloads, sent_at: unix_now() };
+        self.state.probes.lock().insert(id.to_string(), probe);
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.interactions.lock().clone()
+    }
+
+    /// Probes with at least one interaction, most severe first
+    pub fn correlate(&self) -> Vec<OobFinding> {
+        let probes = self.state.probes.lock();
+        let mut by_id: HashMap<String, Vec<Interaction>> = HashMap::new();
+        for i in self.state.interactions.lock().iter().filter(|i| probes.contains_key(&i.id)) {
+            by_id.entry(i.id.clone()).or_default().push(i.clone());
+        }
+        let mut findings: Vec<OobFinding> = by_id.into_iter()
+            .map(|(id, interactions)| {
+                let probe = probes[&id].clone();
+                let http = interactions.iter().any(|i| i.protocol == Protocol::Http);
+                OobFinding { severity: probe.kind.severity(http), probe, interactions }
+            })
+            .collect();
+        findings.sort_by(|a, b| a.severity.cmp(&b.severity).then(a.probe.url.cmp(&b.probe.url)));
+        findings
+    }
+}
+
+impl Drop for OobServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn serve_http(listener: tokio::net::TcpListener, state: Arc<State>) {
+    loop {
+        let Ok((mut sock, remote)) = listener.accept().await else { continue };
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; REQUEST_LIMIT];
+            let Ok(Ok(n)) = tokio::time::timeout(Duration::from_secs(5), sock.read(&mut buf)).await else { return };
+            let head = String::from_utf8_lossy(&buf[..n]).to_string();
+            let (line, host) = request_summary(&head);
+            // The id is in the Host header; the path as fallback for callbacks that went to the IP
+            let id = host.as_deref().and_then(|h| id_from_host(h, &state.domain)).or_else(|| {
+                let probes = state.probes.lock();
+                line.split(['/', ' ', '?', '.']).find(|seg| probes.contains_key(*seg)).map(str::to_string)
+            });
+            if let Some(id) = id {
+                state.record(id, Protocol::Http, remote, format!("{} (Host: {})", line, host.unwrap_or_default()));
+            }
+            let _ = sock.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+        });
+    }
+}
+
+async fn serve_dns(socket: tokio::net::UdpSocket, state: Arc<State>, answer_ip: Option<Ipv4Addr>) {
+    let mut buf = [0u8; 512];
+    loop {
+        let Ok((n, remote)) = socket.recv_from(&mut buf).await else { continue };
+        let packet = &buf[..n];
+        let Some(question) = dns::parse_query(packet) else { continue };
+        if question.name != state.domain && !question.name.ends_with(&format!(".{}", state.domain)) {
+            continue;
+        }
+        if let Some(id) = id_from_host(&question.name, &state.domain) {
+            state.record(id, Protocol::Dns, remote, question.name.clone());
+        }
+        let _ = socket.send_to(&dns::answer(packet, &question, answer_ip), remote).await;
+    }
+}
+
+/// Sends callback payloads to the target
+pub struct OobTester<'a> {
+    http: &'a HttpClient,
+    server: &'a OobServer,
+    timeout: Duration,
+}
+
+impl<'a> OobTester<'a> {
+    pub fn new(http: &'a HttpClient, server: &'a OobServer, timeout: Duration) -> Self {
+        Self { http, server, timeout }
+    }
+
+    async fn send(&self, req: reqwest::RequestBuilder) {
+        // The answer doesn't matter, the callback does
+        let _ = self.http.send(req.timeout(self.timeout)).await;
+    }
+
+    /// SSRF, command, SQL and JNDI payloads in one parameter, each kind with its own callback
+    pub async fn inject(&self, url: &str, name: &str, location: ParamLocation) -> usize {
+        let mut sent = 0;
+        for kind in [OobKind::Ssrf, OobKind::CommandInjection, OobKind::SqlInjection, OobKind::Jndi] {
+            let (id, host) = self.server.callback();
+            let values = kind.payloads(&host);
+            self.server.register(&id, kind, url, Some(name), values.clone());
+            for value in values {
+                let req = match location {
+                    ParamLocation::Query => self.http.client().get(crate::fuzz::field_selection::with_param(url, name, &value)),
+                    ParamLocation::Json => self.http.client().post(url).json(&serde_json::json!({ name: value })),
+                };
+                self.send(req).await;
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// XML bodies with external entities pointing at a callback
+    pub async fn xxe(&self, url: &str) -> usize {
+        let (id, host) = self.server.callback();
+        let bodies = OobKind::Xxe.payloads(&host);
+        self.server.register(&id, OobKind::Xxe, url, None, bodies.clone());
+        for body in &bodies {
+            let req = self.http.client().post(url).header(reqwest::header::CONTENT_TYPE, "application/xml").body(body.clone());
+            self.send(req).await;
+        }
+        bodies.len()
+    }
+}
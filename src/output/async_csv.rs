@@ -20,7 +20,7 @@ pub fn spawn_csv_writer(path: PathBuf, mut rx: mpsc::Receiver<RawEvent>) -> toki
                     // helper to quote and escape CSV field
                     let q = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
                     let content_type = ev.content_type.as_ref().map(|v| v.as_str()).unwrap_or("");
-                    let notes = ev.notes.join(";");
+                    let notes = crate::output::annotation::join(&ev.annotations, ";");
                     let is_graphql = if ev.is_graphql { "1" } else { "0" };
                     let response_ms = ev.response_ms.map(|m| m.to_string()).unwrap_or_default();
                     let status = ev.status.to_string();
@@ -1,9 +1,13 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use crate::discover::security_txt::SecurityTxt;
+use crate::filter::third_party::ThirdPartyReference;
+use crate::probe::preflight::HostState;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Severity {
     Critical,
     High,
@@ -48,7 +52,7 @@ impl Severity {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Finding {
     pub severity: Severity,
     pub category: String,
@@ -59,17 +63,49 @@ pub struct Finding {
     pub remediation: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScanReport {
+    /// Output schema version (`output::schema::SCHEMA_VERSION`); 0 for reports written before versioning
+    #[serde(default = "crate::output::schema::unversioned")]
+    pub schema_version: u32,
     pub target: String,
     pub scan_duration_seconds: u64,
     pub total_endpoints: usize,
     pub findings: Vec<Finding>,
     pub endpoints_tested: Vec<String>,
     pub js_analysis: Option<JsAnalysisSummary>,
+    /// Third-party hosts referenced by the target but excluded from active scanning
+    #[serde(default)]
+    pub third_party_references: Vec<ThirdPartyReference>,
+    /// Per-host outcome, so a dead host isn't mistaken for one without APIs
+    #[serde(default)]
+    pub hosts: Vec<HostSummary>,
+    /// security.txt of the scanned origins: where to report the findings
+    #[serde(default)]
+    pub disclosure_policies: Vec<SecurityTxt>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HostSummary {
+    pub host: String,
+    pub state: HostState,
+    /// Endpoints that answered the probe
+    pub endpoints: usize,
+}
+
+impl HostSummary {
+    pub fn describe(&self) -> String {
+        match self.state {
+            HostState::Unresolved => "unresolved (no DNS record)".to_string(),
+            HostState::Dead => "dead host (no port open)".to_string(),
+            HostState::NoHttp if self.endpoints == 0 => "no HTTP service (TLS/handshake failure)".to_string(),
+            _ if self.endpoints == 0 => "no API endpoints found".to_string(),
+            _ => format!("{} endpoints", self.endpoints),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct JsAnalysisSummary {
     pub endpoints_found: usize,
     pub secrets_found: usize,
@@ -80,12 +116,16 @@ pub struct JsAnalysisSummary {
 impl ScanReport {
     pub fn new(target: String) -> Self {
         Self {
+            schema_version: crate::output::schema::SCHEMA_VERSION,
             target,
             scan_duration_seconds: 0,
             total_endpoints: 0,
             findings: Vec::new(),
             endpoints_tested: Vec::new(),
             js_analysis: None,
+            third_party_references: Vec::new(),
+            hosts: Vec::new(),
+            disclosure_policies: Vec::new(),
         }
     }
 
@@ -120,6 +160,10 @@ impl ScanReport {
         println!("   Target: {}", self.target);
         println!("   Duration: {}s", self.scan_duration_seconds);
         println!("   Endpoints: {}", self.total_endpoints);
+        let dead = self.hosts.iter().filter(|h| matches!(h.state, HostState::Dead | HostState::Unresolved)).count();
+        if dead > 0 {
+            println!("   Hosts: {} scanned, {} dead/unresolved (skipped)", self.hosts.len() - dead, dead);
+        }
 
         if let Some(ref js) = self.js_analysis {
             println!("\n[DIR] JavaScript Analysis:");
@@ -174,6 +218,12 @@ impl ScanReport {
             }
         }
 
+        for policy in &self.disclosure_policies {
+            if let Some(report_to) = policy.report_to() {
+                println!("\n[*] Report to: {}{}", report_to, if policy.expired { " [security.txt expired]" } else { "" });
+            }
+        }
+
         println!("\n[=] Detailed results saved to: ./results/");
         println!();
     }
@@ -216,6 +266,45 @@ impl ScanReport {
             report.push_str(&format!("  - Domains found: {}\n\n", js.domains_found));
         }
 
+        if !self.hosts.is_empty() {
+            report.push_str("Hosts:\n");
+            for h in &self.hosts {
+                report.push_str(&format!("  - {}: {}\n", h.host, h.describe()));
+            }
+            report.push('\n');
+        }
+
+        if !self.disclosure_policies.is_empty() {
+            report.push_str("Disclosure Policy (security.txt):\n");
+            for p in &self.disclosure_policies {
+                report.push_str(&format!("  {}\n", p.url));
+                for contact in &p.contacts {
+                    report.push_str(&format!("  - Contact: {}\n", contact));
+                }
+                for policy in &p.policy {
+                    report.push_str(&format!("  - Policy: {}\n", policy));
+                }
+                for key in &p.encryption {
+                    report.push_str(&format!("  - Encryption: {}\n", key));
+                }
+                if let Some(ref expires) = p.expires {
+                    report.push_str(&format!("  - Expires: {}{}\n", expires, if p.expired { " (expired)" } else { "" }));
+                }
+                for issue in &p.issues {
+                    report.push_str(&format!("  ! {}\n", issue));
+                }
+            }
+            report.push('\n');
+        }
+
+        if !self.third_party_references.is_empty() {
+            report.push_str("Third-Party References (not scanned):\n");
+            for r in &self.third_party_references {
+                report.push_str(&format!("  - {} ({} URLs)\n", r.host, r.count));
+            }
+            report.push('\n');
+        }
+
         let counts = self.severity_counts();
         report.push_str("Security Findings:\n");
         report.push_str(&format!("  [!] CRITICAL: {}\n", counts.get(&Severity::Critical).unwrap_or(&0)));
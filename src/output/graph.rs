@@ -0,0 +1,256 @@
+//! Asset graph: domains → hosts → endpoints → parameters / findings
+//! Exported as DOT (Graphviz, Obsidian) and GraphML (Gephi, yEd). GraphML files written here can be
+//! read back, so repeated scans of a large API estate accumulate into one graph.
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use url::Url;
+
+static GRAPHML_NODE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?s)<node id="([^"]*)">(.*?)</node>"#).unwrap());
+static GRAPHML_DATA_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?s)<data key="([^"]*)">(.*?)</data>"#).unwrap());
+static GRAPHML_EDGE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<edge source="([^"]*)" target="([^"]*)""#).unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Domain,
+    Host,
+    Endpoint,
+    Parameter,
+    Finding,
+}
+
+impl NodeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Domain => "domain",
+            NodeKind::Host => "host",
+            NodeKind::Endpoint => "endpoint",
+            NodeKind::Parameter => "parameter",
+            NodeKind::Finding => "finding",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "domain" => NodeKind::Domain,
+            "host" => NodeKind::Host,
+            "endpoint" => NodeKind::Endpoint,
+            "parameter" => NodeKind::Parameter,
+            "finding" => NodeKind::Finding,
+            _ => return None,
+        })
+    }
+
+    fn dot_style(&self) -> &'static str {
+        match self {
+            NodeKind::Domain => "shape=doubleoctagon, style=filled, fillcolor=\"#9ecae1\"",
+            NodeKind::Host => "shape=box, style=filled, fillcolor=\"#c6dbef\"",
+            NodeKind::Endpoint => "shape=ellipse",
+            NodeKind::Parameter => "shape=note, fontsize=10",
+            NodeKind::Finding => "shape=octagon, style=filled, fillcolor=\"#fc9272\"",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub id: String,
+    pub kind: NodeKind,
+    pub label: String,
+    /// Extra attributes (status, severity, ...)
+    pub attrs: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct AssetGraph {
+    nodes: Vec<Node>,
+    index: HashMap<String, usize>,
+    edges: Vec<(String, String)>,
+    edge_set: HashSet<(String, String)>,
+}
+
+impl AssetGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[(String, String)] {
+        &self.edges
+    }
+
+    /// Insert a node, or merge attributes into the existing one with the same id
+    fn node(&mut self, id: String, kind: NodeKind, label: String, attrs: BTreeMap<String, String>) -> String {
+        match self.index.get(&id) {
+            Some(&i) => self.nodes[i].attrs.extend(attrs),
+            None => {
+                self.index.insert(id.clone(), self.nodes.len());
+                self.nodes.push(Node { id: id.clone(), kind, label, attrs });
+            }
+        }
+        id
+    }
+
+    fn edge(&mut self, source: &str, target: &str) {
+        let e = (source.to_string(), target.to_string());
+        if self.edge_set.insert(e.clone()) {
+            self.edges.push(e);
+        }
+    }
+
+    /// Add an endpoint with its domain, host and query parameters. Returns the endpoint node id.
+    /// `root` is the scan target; hosts outside it are grouped under their own registrable domain.
+    pub fn add_endpoint(&mut self, url: &str, root: &str, status: Option<u16>) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        let host = parsed.host_str()?.to_lowercase();
+        let domain = if host == root || host.ends_with(&format!(".{}", root)) {
+            root.to_string()
+        } else {
+            registrable_domain(&host)
+        };
+
+        let domain_id = self.node(format!("domain:{}", domain), NodeKind::Domain, domain, BTreeMap::new());
+        let host_id = self.node(format!("host:{}", host), NodeKind::Host, host.clone(), BTreeMap::new());
+        self.edge(&domain_id, &host_id);
+
+        let path = if parsed.path().is_empty() { "/" } else { parsed.path() };
+        let mut attrs = BTreeMap::new();
+        if let Some(status) = status {
+            attrs.insert("status".to_string(), status.to_string());
+        }
+        let endpoint_id = self.node(
+            format!("endpoint:{}://{}{}", parsed.scheme(), host, path),
+            NodeKind::Endpoint,
+            path.to_string(),
+            attrs,
+        );
+        self.edge(&host_id, &endpoint_id);
+
+        for (name, _) in parsed.query_pairs() {
+            self.add_parameter(&endpoint_id, &name);
+        }
+        Some(endpoint_id)
+    }
+
+    pub fn add_parameter(&mut self, endpoint_id: &str, name: &str) {
+        let id = self.node(format!("param:{}#{}", endpoint_id.trim_start_matches("endpoint:"), name),
+            NodeKind::Parameter, name.to_string(), BTreeMap::new());
+        self.edge(endpoint_id, &id);
+    }
+
+    /// Attach a finding to the endpoint at `url` (the endpoint is created if needed)
+    pub fn add_finding(&mut self, url: &str, root: &str, severity: &str, title: &str) {
+        let Some(endpoint_id) = self.add_endpoint(url, root, None) else { return };
+        let mut attrs = BTreeMap::new();
+        attrs.insert("severity".to_string(), severity.to_uppercase());
+        let id = self.node(format!("finding:{}|{}", endpoint_id.trim_start_matches("endpoint:"), title),
+            NodeKind::Finding, title.to_string(), attrs);
+        self.edge(&endpoint_id, &id);
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph assets {\n  rankdir=LR;\n  node [fontname=\"Helvetica\"];\n");
+        for n in &self.nodes {
+            let mut label = dot_escape(&n.label);
+            for (k, v) in &n.attrs {
+                label.push_str(&format!("\\n{}: {}", dot_escape(k), dot_escape(v)));
+            }
+            out.push_str(&format!("  \"{}\" [label=\"{}\", {}];\n", dot_escape(&n.id), label, n.kind.dot_style()));
+        }
+        for (s, t) in &self.edges {
+            out.push_str(&format!("  \"{}\" -> \"{}\";\n", dot_escape(s), dot_escape(t)));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut attr_keys: Vec<&String> = self.nodes.iter().flat_map(|n| n.attrs.keys()).collect();
+        attr_keys.sort();
+        attr_keys.dedup();
+
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        ));
+        for k in &attr_keys {
+            out.push_str(&format!("  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"string\"/>\n", xml_escape(k)));
+        }
+        out.push_str("  <graph id=\"assets\" edgedefault=\"directed\">\n");
+        for n in &self.nodes {
+            out.push_str(&format!("    <node id=\"{}\">", xml_escape(&n.id)));
+            out.push_str(&format!("<data key=\"kind\">{}</data>", n.kind.as_str()));
+            out.push_str(&format!("<data key=\"label\">{}</data>", xml_escape(&n.label)));
+            for (k, v) in &n.attrs {
+                out.push_str(&format!("<data key=\"{}\">{}</data>", xml_escape(k), xml_escape(v)));
+            }
+            out.push_str("</node>\n");
+        }
+        for (s, t) in &self.edges {
+            out.push_str(&format!("    <edge source=\"{}\" target=\"{}\"/>\n", xml_escape(s), xml_escape(t)));
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Read a graph previously written by [`AssetGraph::to_graphml`]
+    pub fn from_graphml(xml: &str) -> Result<Self> {
+        if !xml.contains("<graphml") {
+            bail!("not a GraphML document");
+        }
+        let mut graph = Self::new();
+        for c in GRAPHML_NODE_RE.captures_iter(xml) {
+            let id = xml_unescape(&c[1]);
+            let mut kind = None;
+            let mut label = String::new();
+            let mut attrs = BTreeMap::new();
+            for d in GRAPHML_DATA_RE.captures_iter(&c[2]) {
+                let value = xml_unescape(&d[2]);
+                match &d[1] {
+                    "kind" => kind = NodeKind::parse(&value),
+                    "label" => label = value,
+                    key => { attrs.insert(xml_unescape(key), value); }
+                }
+            }
+            let Some(kind) = kind else { bail!("node '{}' has no known kind", id) };
+            graph.node(id, kind, label, attrs);
+        }
+        for c in GRAPHML_EDGE_RE.captures_iter(xml) {
+            let (s, t) = (xml_unescape(&c[1]), xml_unescape(&c[2]));
+            if graph.index.contains_key(&s) && graph.index.contains_key(&t) {
+                graph.edge(&s, &t);
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Last two labels, or three for common second-level public suffixes (`co.uk`, `com.au`)
+fn registrable_domain(host: &str) -> String {
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() <= 2 || host.parse::<std::net::IpAddr>().is_ok() {
+        return host.to_string();
+    }
+    let n = labels.len();
+    let take = if matches!(labels[n - 2], "co" | "com" | "org" | "net" | "gov" | "ac" | "edu") && labels[n - 1].len() == 2 { 3 } else { 2 };
+    labels[n - take.min(n)..].join(".")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"").replace("&gt;", ">").replace("&lt;", "<").replace("&amp;", "&")
+}
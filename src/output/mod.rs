@@ -1,14 +1,20 @@
+pub mod annotation;
 pub mod async_csv;
 pub mod async_writer;
 pub mod results_manager;
 pub mod writer_csv;
 pub mod writer_jsonl;
 pub mod clean_reporter;
+pub mod graph;
+pub mod openapi;
+pub mod paths;
+pub mod schema;
 
 pub use async_csv::spawn_csv_writer;
 pub use async_writer::spawn_jsonl_writer;
-pub use results_manager::{cleanup_results, calculate_statistics, ScanStatistics};
+pub use results_manager::{calculate_statistics, ArtifactRegistry, RetentionPolicy, ScanRun, ScanStatistics};
 pub use writer_csv::write_csv;
 pub use writer_jsonl::write_jsonl;
 pub use writer_jsonl::write_top_txt;
 pub use writer_jsonl::RawEvent;
+pub use annotation::Annotation;
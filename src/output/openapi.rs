@@ -0,0 +1,562 @@
+//! OpenAPI 3 spec reverse-engineered from the scan
+//! Every probed endpoint becomes a path: ID-like segments (`/users/42`, UUIDs, object ids) are turned
+//! into path parameters, so `/users/42` and `/users/7` share `/users/{userId}`. Operations come from
+//! the method matrix (statuses other than 404/405/501, plus methods advertised in `Allow`), query
+//! parameters from the probed URLs and the parameter brute force, request bodies from the JSON keys
+//! POST accepted, response schemas from the JSON the endpoints returned. Schemas carry types and
+//! formats but no example values, so the spec can be shared without leaking response data.
+
+use crate::fuzz::param_bruteforce::{DiscoveredParam, ParamLocation};
+use crate::output::writer_jsonl::RawEvent;
+use crate::output::Annotation;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+/// Methods documented as operations (HEAD and OPTIONS say nothing about the API)
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Statuses that mean the method isn't there
+const ABSENT: &[u16] = &[404, 405, 501];
+
+/// Nesting followed into response bodies
+const MAX_DEPTH: usize = 8;
+
+/// Array elements looked at for the item schema
+const MAX_ITEMS: usize = 32;
+
+static UUID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$").unwrap());
+static DATE_TIME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}(:\d{2})?").unwrap());
+static DATE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+static EMAIL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[A-Za-z]{2,}$").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenApi {
+    pub openapi: String,
+    pub info: Info,
+    pub servers: Vec<Server>,
+    pub paths: BTreeMap<String, PathItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Components>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Info {
+    pub title: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Server {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathItem {
+    /// Only set when the path was seen on some of the scanned origins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<Server>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+    /// Lower-case method → operation
+    #[serde(flatten)]
+    pub operations: BTreeMap<String, Operation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: String,
+    pub required: bool,
+    pub schema: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Operation {
+    #[serde(rename = "operationId")]
+    pub operation_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+    #[serde(rename = "requestBody", default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<RequestBody>,
+    /// Status code (or `default`) → response
+    pub responses: BTreeMap<String, Response>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestBody {
+    pub content: BTreeMap<String, MediaType>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<String, MediaType>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaType {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Components {
+    #[serde(rename = "securitySchemes")]
+    pub security_schemes: BTreeMap<String, Value>,
+}
+
+impl OpenApi {
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    pub fn operation_count(&self) -> usize {
+        self.paths.values().map(|p| p.operations.len()).sum()
+    }
+}
+
+/// Schema of a JSON value: types, formats and nullability, no examples
+pub fn infer_schema(value: &Value) -> Value {
+    infer_at(value, 0)
+}
+
+fn infer_at(value: &Value, depth: usize) -> Value {
+    match value {
+        Value::Null => json!({"nullable": true}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({"type": "integer"}),
+        Value::Number(_) => json!({"type": "number"}),
+        Value::String(s) => match string_format(s) {
+            Some(format) => json!({"type": "string", "format": format}),
+            None => json!({"type": "string"}),
+        },
+        Value::Array(_) | Value::Object(_) if depth >= MAX_DEPTH => json!({"type": value_type(value)}),
+        Value::Array(items) => {
+            let item = items.iter().take(MAX_ITEMS)
+                .map(|v| infer_at(v, depth + 1))
+                .reduce(|a, b| merge_schemas(&a, &b))
+                .unwrap_or_else(|| json!({}));
+            json!({"type": "array", "items": item})
+        }
+        Value::Object(map) => {
+            let properties: Map<String, Value> = map.iter().map(|(k, v)| (k.clone(), infer_at(v, depth + 1))).collect();
+            json!({"type": "object", "properties": properties})
+        }
+    }
+}
+
+fn value_type(value: &Value) -> &'static str {
+    if value.is_array() { "array" } else { "object" }
+}
+
+fn string_format(s: &str) -> Option<&'static str> {
+    if UUID_RE.is_match(s) {
+        Some("uuid")
+    } else if DATE_TIME_RE.is_match(s) {
+        Some("date-time")
+    } else if DATE_RE.is_match(s) {
+        Some("date")
+    } else if EMAIL_RE.is_match(s) {
+        Some("email")
+    } else if s.starts_with("http://") || s.starts_with("https://") {
+        Some("uri")
+    } else {
+        None
+    }
+}
+
+/// Schema that admits both: objects union their properties, `integer` widens to `number`, differing
+/// formats are dropped, `null` makes the other side nullable, anything else becomes `oneOf`
+pub fn merge_schemas(a: &Value, b: &Value) -> Value {
+    if a == b {
+        return a.clone();
+    }
+    let nullable = a["nullable"] == true || b["nullable"] == true;
+    let mut merged = match (a.get("type").and_then(Value::as_str), b.get("type").and_then(Value::as_str)) {
+        // `{}` (an empty array's items) and `{"nullable": true}` say nothing about the type
+        (None, _) if a.get("oneOf").is_none() => b.clone(),
+        (_, None) if b.get("oneOf").is_none() => a.clone(),
+        (Some("object"), Some("object")) => {
+            let mut properties = a["properties"].as_object().cloned().unwrap_or_default();
+            for (k, v) in b["properties"].as_object().into_iter().flatten() {
+                let merged = match properties.get(k) {
+                    Some(existing) => merge_schemas(existing, v),
+                    None => v.clone(),
+                };
+                properties.insert(k.clone(), merged);
+            }
+            if properties.is_empty() { json!({"type": "object"}) } else { json!({"type": "object", "properties": properties}) }
+        }
+        (Some("array"), Some("array")) => json!({"type": "array", "items": merge_schemas(&a["items"], &b["items"])}),
+        (Some(x), Some(y)) if x == y => {
+            let mut s = json!({"type": x});
+            if a.get("format").is_some() && a.get("format") == b.get("format") {
+                s["format"] = a["format"].clone();
+            }
+            s
+        }
+        (Some("integer" | "number"), Some("integer" | "number")) => json!({"type": "number"}),
+        _ => {
+            let mut alternatives: Vec<Value> = Vec::new();
+            for s in [a, b] {
+                let parts = match s.get("oneOf").and_then(Value::as_array) {
+                    Some(parts) => parts.clone(),
+                    None => vec![strip_nullable(s)],
+                };
+                for part in parts {
+                    if !alternatives.contains(&part) {
+                        alternatives.push(part);
+                    }
+                }
+            }
+            json!({"oneOf": alternatives})
+        }
+    };
+    if nullable {
+        merged["nullable"] = json!(true);
+    }
+    merged
+}
+
+fn strip_nullable(schema: &Value) -> Value {
+    let mut s = schema.clone();
+    if let Some(map) = s.as_object_mut() {
+        map.remove("nullable");
+    }
+    s
+}
+
+/// Parameter schemas only widen: same → same, integer/number → number, otherwise string
+fn widen(a: &Value, b: &Value) -> Value {
+    match (a["type"].as_str(), b["type"].as_str()) {
+        _ if a == b => a.clone(),
+        (Some("integer" | "number"), Some("integer" | "number")) => json!({"type": "number"}),
+        _ => json!({"type": "string"}),
+    }
+}
+
+fn scalar_schema(value: &str) -> Value {
+    if value.parse::<i64>().is_ok() {
+        json!({"type": "integer"})
+    } else if value == "true" || value == "false" {
+        json!({"type": "boolean"})
+    } else if UUID_RE.is_match(value) {
+        json!({"type": "string", "format": "uuid"})
+    } else {
+        json!({"type": "string"})
+    }
+}
+
+/// `users` → `user`, `categories` → `category`, `addresses` → `address`
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if word.ends_with("sses") || word.ends_with("xes") || word.ends_with("ches") || word.ends_with("shes") {
+        word[..word.len() - 2].to_string()
+    } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+fn camel(words: &str, capitalize_first: bool) -> String {
+    let mut out = String::new();
+    for (i, part) in words.split(|c: char| !c.is_ascii_alphanumeric()).filter(|p| !p.is_empty()).enumerate() {
+        let mut chars = part.chars();
+        let first = chars.next().unwrap_or_default();
+        if i == 0 && !capitalize_first {
+            out.extend(first.to_lowercase());
+        } else {
+            out.extend(first.to_uppercase());
+        }
+        out.push_str(chars.as_str());
+    }
+    out
+}
+
+/// Origin, templated path and path parameters of `url`: `https://api.example/v1/users/42/orders` →
+/// (`https://api.example`, `/v1/users/{userId}/orders`, `userId: integer`)
+pub fn path_template(url: &str) -> Option<(String, String, Vec<Parameter>)> {
+    let parsed = url::Url::parse(url).ok()?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return None;
+    }
+    let origin = parsed.origin().ascii_serialization();
+    let ids: BTreeMap<usize, String> = crate::fuzz::param_discovery::detect_path_ids(url).into_iter().collect();
+    let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+    let mut path = String::new();
+    let mut params: Vec<Parameter> = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        path.push('/');
+        let Some(id) = ids.get(&i) else {
+            path.push_str(segment);
+            continue;
+        };
+        let base = match i.checked_sub(1).filter(|prev| !ids.contains_key(prev)) {
+            Some(prev) => format!("{}Id", camel(&singular(segments[prev]), false)),
+            None => "id".to_string(),
+        };
+        let base = if base == "Id" { "id".to_string() } else { base };
+        let mut name = base.clone();
+        let mut n = 2;
+        while params.iter().any(|p| p.name == name) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        let schema = if id.chars().all(|c| c.is_ascii_digit()) {
+            json!({"type": "integer"})
+        } else if UUID_RE.is_match(id) {
+            json!({"type": "string", "format": "uuid"})
+        } else {
+            json!({"type": "string"})
+        };
+        path.push_str(&format!("{{{}}}", name));
+        params.push(Parameter { name, location: "path".to_string(), required: true, schema });
+    }
+    if path.is_empty() {
+        path.push('/');
+    }
+    Some((origin, path, params))
+}
+
+/// `get` + `/v1/users/{userId}` → `getV1UsersByUserId`
+fn operation_id(method: &str, path: &str) -> String {
+    let mut id = method.to_string();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => {
+                id.push_str("By");
+                id.push_str(&camel(param, true));
+            }
+            None => id.push_str(&camel(segment, true)),
+        }
+    }
+    if id == method {
+        id.push_str("Root");
+    }
+    id
+}
+
+/// Media type without parameters (`application/json; charset=utf-8` → `application/json`)
+fn media_type(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+/// Schema of the JSON body: the stored body when it parses, else the probe's JSON sample
+fn body_schema(ev: &RawEvent) -> Option<Value> {
+    if let Some(value) = ev.body.as_ref().and_then(|b| b.text()).and_then(|t| serde_json::from_str::<Value>(&t).ok()) {
+        return Some(infer_schema(&value));
+    }
+    let sample = ev.json_sample.as_ref()?;
+    // Non-JSON bodies are stored as `{"_sample": "..."}`
+    if sample.as_object().is_some_and(|m| m.len() == 1 && m.contains_key("_sample")) {
+        return None;
+    }
+    Some(infer_schema(sample))
+}
+
+/// `Bearer realm="api"` → (`bearerAuth`, `bearer`)
+fn auth_scheme(www_authenticate: &str) -> Option<(String, String)> {
+    let scheme = www_authenticate.split_whitespace().next()?.trim_end_matches(',').to_ascii_lowercase();
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some((format!("{}Auth", camel(&scheme, false)), scheme))
+}
+
+fn status_description(status: &str) -> String {
+    status.parse::<u16>().ok()
+        .and_then(|s| reqwest::StatusCode::from_u16(s).ok())
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Observed response")
+        .to_string()
+}
+
+#[derive(Default)]
+struct OperationAgg {
+    query: BTreeMap<String, Value>,
+    body: BTreeMap<String, Value>,
+    responses: BTreeMap<String, Response>,
+    security: BTreeSet<String>,
+}
+
+impl OperationAgg {
+    fn respond(&mut self, status: String, media: Option<(String, Option<Value>)>) {
+        let description = if status == "default" { "Advertised by the server, not probed".to_string() } else { status_description(&status) };
+        let response = self.responses.entry(status).or_insert_with(|| Response { description, content: BTreeMap::new() });
+        if let Some((media, schema)) = media {
+            let entry = response.content.entry(media).or_default();
+            entry.schema = match (entry.schema.take(), schema) {
+                (Some(a), Some(b)) => Some(merge_schemas(&a, &b)),
+                (a, b) => a.or(b),
+            };
+        }
+    }
+}
+
+#[derive(Default)]
+struct PathAgg {
+    origins: BTreeSet<String>,
+    params: Vec<Parameter>,
+    operations: BTreeMap<String, OperationAgg>,
+}
+
+#[derive(Default)]
+struct SpecBuilder {
+    paths: BTreeMap<String, PathAgg>,
+    schemes: BTreeMap<String, String>,
+}
+
+impl SpecBuilder {
+    fn path(&mut self, url: &str) -> Option<&mut PathAgg> {
+        let (origin, path, params) = path_template(url)?;
+        let agg = self.paths.entry(path).or_default();
+        agg.origins.insert(origin);
+        if agg.params.is_empty() {
+            agg.params = params;
+        } else {
+            for (existing, p) in agg.params.iter_mut().zip(params) {
+                existing.schema = widen(&existing.schema, &p.schema);
+            }
+        }
+        Some(agg)
+    }
+
+    fn add_event(&mut self, ev: &RawEvent) {
+        if ev.status == 404 || ev.annotations.iter().any(|a| matches!(a, Annotation::Soft404 | Annotation::WafBlock)) {
+            return;
+        }
+        let scheme = match ev.status {
+            401 | 403 => ev.header("www-authenticate").and_then(auth_scheme),
+            _ => None,
+        };
+        if let Some((name, scheme)) = &scheme {
+            self.schemes.insert(name.clone(), scheme.clone());
+        }
+        let query: Vec<(String, String)> = url::Url::parse(&ev.final_url)
+            .map(|u| u.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect())
+            .unwrap_or_default();
+        let Some(agg) = self.path(&ev.final_url) else { return };
+
+        if !ABSENT.contains(&ev.status) {
+            let get = agg.operations.entry("get".to_string()).or_default();
+            let media = ev.content_type.as_deref().map(media_type).filter(|m| !m.is_empty() && ![204, 304].contains(&ev.status));
+            let schema = media.as_ref().filter(|m| m.contains("json")).and_then(|_| body_schema(ev));
+            get.respond(ev.status.to_string(), media.map(|m| (m, schema)));
+            for (name, value) in &query {
+                let schema = scalar_schema(value);
+                let merged = get.query.get(name).map(|s| widen(s, &schema)).unwrap_or(schema);
+                get.query.insert(name.clone(), merged);
+            }
+            if let Some((name, _)) = scheme {
+                get.security.insert(name);
+            }
+        }
+        for (method, status) in &ev.methods {
+            let method = method.to_ascii_uppercase();
+            if !METHODS.contains(&method.as_str()) || ABSENT.contains(status) || (method == "GET" && *status == ev.status) {
+                continue;
+            }
+            agg.operations.entry(method.to_ascii_lowercase()).or_default().respond(status.to_string(), None);
+        }
+        for method in &ev.allowed_methods {
+            let method = method.trim().to_ascii_uppercase();
+            if !METHODS.contains(&method.as_str()) {
+                continue;
+            }
+            let op = agg.operations.entry(method.to_ascii_lowercase()).or_default();
+            if op.responses.is_empty() {
+                op.respond("default".to_string(), None);
+            }
+        }
+    }
+
+    fn add_param(&mut self, param: &DiscoveredParam) {
+        let Some(agg) = self.path(&param.url) else { return };
+        match param.location {
+            ParamLocation::Query => {
+                let get = agg.operations.entry("get".to_string()).or_default();
+                get.query.entry(param.name.clone()).or_insert_with(|| json!({"type": "string"}));
+                if get.responses.is_empty() {
+                    get.respond("default".to_string(), None);
+                }
+            }
+            ParamLocation::Json => {
+                let post = agg.operations.entry("post".to_string()).or_default();
+                post.body.entry(param.name.clone()).or_insert_with(|| json!({}));
+                if post.responses.is_empty() {
+                    post.respond("default".to_string(), None);
+                }
+            }
+        }
+    }
+
+    fn build(self, title: &str, observed: usize) -> OpenApi {
+        let all_origins: BTreeSet<String> = self.paths.values().flat_map(|p| p.origins.iter().cloned()).collect();
+        let mut paths = BTreeMap::new();
+        for (path, agg) in self.paths {
+            let mut operations = BTreeMap::new();
+            for (method, op) in agg.operations {
+                let parameters = op.query.into_iter()
+                    .map(|(name, schema)| Parameter { name, location: "query".to_string(), required: false, schema })
+                    .collect();
+                let request_body = (!op.body.is_empty()).then(|| {
+                    let schema = json!({"type": "object", "properties": op.body});
+                    RequestBody { content: BTreeMap::from([("application/json".to_string(), MediaType { schema: Some(schema) })]) }
+                });
+                let security = op.security.into_iter().map(|name| BTreeMap::from([(name, Vec::new())])).collect();
+                operations.insert(method.clone(), Operation {
+                    operation_id: operation_id(&method, &path),
+                    parameters,
+                    request_body,
+                    responses: op.responses,
+                    security,
+                });
+            }
+            let servers = if agg.origins.len() < all_origins.len() {
+                agg.origins.into_iter().map(|url| Server { url }).collect()
+            } else {
+                Vec::new()
+            };
+            paths.insert(path, PathItem { servers, parameters: agg.params, operations });
+        }
+        let components = (!self.schemes.is_empty()).then(|| Components {
+            security_schemes: self.schemes.into_iter().map(|(name, scheme)| (name, json!({"type": "http", "scheme": scheme}))).collect(),
+        });
+        OpenApi {
+            openapi: OPENAPI_VERSION.to_string(),
+            info: Info {
+                title: title.to_string(),
+                version: "observed".to_string(),
+                description: Some(format!("Reverse-engineered by API Hunter from {} observed responses. Only what the scan saw is documented.", observed)),
+            },
+            servers: all_origins.into_iter().map(|url| Server { url }).collect(),
+            paths,
+            components,
+        }
+    }
+}
+
+/// Spec of everything the scan observed: probe results and brute-forced parameters
+pub fn generate(title: &str, events: &[RawEvent], params: &[DiscoveredParam]) -> OpenApi {
+    let mut builder = SpecBuilder::default();
+    for ev in events {
+        builder.add_event(ev);
+    }
+    for p in params {
+        builder.add_param(p);
+    }
+    builder.build(title, events.len())
+}
@@ -0,0 +1,92 @@
+//! Artifact path construction
+//! All files below the output directory are built here, so names stay valid on Windows (no `:`/`?`/`*`,
+//! no reserved device names, no trailing dots) and two different hosts or endpoints never map to the
+//! same file.
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Longest file name component written (well below the 255 limit of common filesystems)
+const MAX_COMPONENT_LEN: usize = 100;
+
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul",
+    "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9",
+    "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Make one path component safe on every platform. Unsafe characters become `_`; over-long names are
+/// truncated and suffixed with a hash of the original so they stay distinct.
+pub fn sanitize_component(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') || c.is_control() { '_' } else { c })
+        .collect();
+    // Windows strips trailing dots and spaces, which would merge `a.` and `a`
+    while out.ends_with('.') || out.ends_with(' ') {
+        out.pop();
+    }
+    if out.is_empty() || out == "." || out == ".." {
+        out = "_".to_string();
+    }
+    let stem = out.split('.').next().unwrap_or("").to_lowercase();
+    if RESERVED_NAMES.contains(&stem.as_str()) {
+        out.insert(0, '_');
+    }
+    if out.len() > MAX_COMPONENT_LEN {
+        let mut end = MAX_COMPONENT_LEN - 9;
+        while !out.is_char_boundary(end) {
+            end -= 1;
+        }
+        out = format!("{}-{}", &out[..end], short_hash(name));
+    }
+    out
+}
+
+/// A top-level artifact in the output directory (`analysis_results.json`, `target_raw.jsonl`, ...)
+pub fn artifact(out_dir: &Path, name: &str) -> PathBuf {
+    out_dir.join(sanitize_component(name))
+}
+
+/// Per-host directory below `dir` (`sources/<host>`). Ports are kept: `api.example.com_8443`.
+pub fn host_dir(dir: &Path, host: &str) -> PathBuf {
+    dir.join(sanitize_component(&host.to_lowercase()))
+}
+
+/// Relative path mirroring a slash-separated path (source map entries, URL paths). `.`/`..` and empty
+/// segments are dropped, so the result can never escape the directory it is joined to.
+pub fn relative_path(path: &str) -> PathBuf {
+    path.split(['/', '\\'])
+        .filter(|c| !c.is_empty() && *c != "." && *c != "..")
+        .map(sanitize_component)
+        .collect()
+}
+
+/// File for one endpoint inside its host directory: readable slug of the path plus a hash of the full
+/// URL, so `/a/b`, `/a_b` and `/a/b?x=1` get different files.
+pub fn endpoint_file(dir: &Path, url: &str, extension: &str) -> PathBuf {
+    let (host, path) = match Url::parse(url) {
+        Ok(u) => {
+            let host = match (u.host_str(), u.port()) {
+                (Some(h), Some(p)) => format!("{}:{}", h, p),
+                (Some(h), None) => h.to_string(),
+                (None, _) => "_".to_string(),
+            };
+            (host, u.path().to_string())
+        }
+        Err(_) => ("_".to_string(), url.to_string()),
+    };
+    let slug: String = path.trim_matches('/').replace('/', "_");
+    let slug = if slug.is_empty() { "root".to_string() } else { slug };
+    let mut end = slug.len().min(60);
+    while !slug.is_char_boundary(end) {
+        end -= 1;
+    }
+    let name = format!("{}-{}.{}", &slug[..end], short_hash(url), extension.trim_start_matches('.'));
+    host_dir(dir, &host).join(sanitize_component(&name))
+}
+
+fn short_hash(s: &str) -> String {
+    Sha256::digest(s.as_bytes()).iter().take(4).map(|b| format!("{:02x}", b)).collect()
+}
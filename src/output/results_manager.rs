@@ -1,27 +1,314 @@
 use std::fs;
-use std::path::Path;
-use anyhow::Result;
-
-/// Clean up results directory before new scan
-pub fn cleanup_results(results_dir: &str) -> Result<()> {
-    let path = Path::new(results_dir);
-    
-    if path.exists() {
-        // Silently remove all files in results directory
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    let _ = fs::remove_file(&path);
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Directory below the output directory holding archived runs, one folder per scan ID
+pub const RUNS_DIR: &str = "runs";
+const REGISTRY_FILE: &str = "registry.json";
+const LOCK_FILE: &str = ".registry.lock";
+/// Entries in the output directory that belong to the workspace, not to a single run
+const WORKSPACE_ENTRIES: &[&str] = &[RUNS_DIR, ".cache", LOCK_FILE];
+/// A lock older than this is left over from a crashed process
+const STALE_LOCK: Duration = Duration::from_secs(60);
+/// Unfinished runs older than this are assumed to have crashed
+const STALE_RUN: Duration = Duration::from_secs(12 * 3600);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunRecord {
+    pub scan_id: String,
+    pub target: String,
+    /// Unix seconds
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    pub pid: u32,
+    /// Where the run's files are, relative to the output directory (`.` while it is the latest run)
+    pub location: String,
+    /// Files and folders the run wrote
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Registry {
+    pub runs: Vec<RunRecord>,
+}
+
+/// Which archived runs to keep. Runs matching either limit are removed; the default keeps everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// Keep only the newest N archived runs
+    pub keep_runs: Option<usize>,
+    /// Remove archived runs older than this
+    pub max_age: Option<Duration>,
+}
+
+/// A scan registered with [`ArtifactRegistry::begin_scan`]
+#[derive(Debug, Clone)]
+pub struct ScanRun {
+    pub scan_id: String,
+    /// Directory the scan writes its artifacts to
+    pub dir: PathBuf,
+}
+
+/// Versions scan outputs in an output directory. The latest run's files stay at the top level; when a
+/// new scan starts they move to `runs/<scan id>/` and `runs/registry.json` records where each run is.
+/// Nothing is deleted unless the scan asks to overwrite or a retention policy is applied.
+pub struct ArtifactRegistry {
+    root: PathBuf,
+}
+
+impl ArtifactRegistry {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn load(&self) -> Result<Registry> {
+        let path = self.registry_path();
+        match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).with_context(|| format!("invalid registry {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Registry::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Register a new scan. The previous run's top-level files are archived (or deleted with
+    /// `overwrite`). If another scan is still writing to the top level, the new scan gets its own
+    /// `runs/<scan id>/` directory instead, so concurrent scans never clobber each other.
+    pub fn begin_scan(&self, target: &str, overwrite: bool) -> Result<ScanRun> {
+        fs::create_dir_all(self.root.join(RUNS_DIR))?;
+        self.with_lock(|registry| {
+            let scan_id = new_scan_id(registry);
+            let busy = registry.runs.iter().any(|r| r.location == "." && is_running(r));
+
+            let dir = if busy {
+                self.root.join(RUNS_DIR).join(&scan_id)
+            } else {
+                let previous = self.top_level_entries()?;
+                if overwrite {
+                    for entry in &previous {
+                        let path = self.root.join(entry);
+                        if path.is_dir() { fs::remove_dir_all(&path)?; } else { fs::remove_file(&path)?; }
+                    }
+                    registry.runs.retain(|r| r.location != ".");
+                } else if !previous.is_empty() {
+                    self.archive_top_level(registry, previous)?;
+                }
+                self.root.clone()
+            };
+            fs::create_dir_all(&dir)?;
+
+            registry.runs.push(RunRecord {
+                scan_id: scan_id.clone(),
+                target: target.to_string(),
+                started_at: now_secs(),
+                finished_at: None,
+                pid: std::process::id(),
+                location: if busy { format!("{}/{}", RUNS_DIR, scan_id) } else { ".".to_string() },
+                files: Vec::new(),
+            });
+            Ok(ScanRun { scan_id, dir })
+        })
+    }
+
+    /// Mark a scan finished and record the files it wrote
+    pub fn finish_scan(&self, run: &ScanRun) -> Result<()> {
+        let files = if run.dir == self.root { self.top_level_entries()? } else { list_dir(&run.dir)? };
+        self.with_lock(|registry| {
+            if let Some(record) = registry.runs.iter_mut().find(|r| r.scan_id == run.scan_id) {
+                record.finished_at = Some(now_secs());
+                record.files = files.clone();
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove archived runs outside the policy. Returns the removed scan IDs.
+    pub fn apply_retention(&self, policy: RetentionPolicy) -> Result<Vec<String>> {
+        if policy.keep_runs.is_none() && policy.max_age.is_none() {
+            return Ok(Vec::new());
+        }
+        self.with_lock(|registry| {
+            let now = now_secs();
+            // Runs are recorded in start order; newest first
+            let archived: Vec<&RunRecord> = registry.runs.iter().rev()
+                .filter(|r| r.location != "." && !is_running(r))
+                .collect();
+
+            let mut removed = Vec::new();
+            for (i, record) in archived.iter().enumerate() {
+                let too_many = policy.keep_runs.is_some_and(|keep| i >= keep);
+                let too_old = policy.max_age.is_some_and(|age| now.saturating_sub(record.started_at) > age.as_secs());
+                if too_many || too_old {
+                    let dir = self.root.join(&record.location);
+                    if dir.starts_with(self.root.join(RUNS_DIR)) && dir.exists() {
+                        fs::remove_dir_all(&dir)?;
+                    }
+                    removed.push(record.scan_id.clone());
                 }
             }
+            registry.runs.retain(|r| !removed.contains(&r.scan_id));
+            Ok(removed)
+        })
+    }
+
+    /// Newest copy of an artifact: the top-level file, else the most recent archived run that has it
+    pub fn latest_artifact(&self, name: &str) -> Option<PathBuf> {
+        let current = super::paths::artifact(&self.root, name);
+        if current.exists() {
+            return Some(current);
+        }
+        self.load().ok()?.runs.iter().rev()
+            .map(|r| super::paths::artifact(&self.root.join(&r.location), name))
+            .find(|p| p.exists())
+    }
+
+    fn registry_path(&self) -> PathBuf {
+        self.root.join(RUNS_DIR).join(REGISTRY_FILE)
+    }
+
+    /// Files and folders at the top level that belong to the latest run
+    fn top_level_entries(&self) -> Result<Vec<String>> {
+        Ok(list_dir(&self.root)?.into_iter().filter(|e| !WORKSPACE_ENTRIES.contains(&e.as_str())).collect())
+    }
+
+    /// Move the top-level files into `runs/<id>/` under the ID of the run that wrote them
+    fn archive_top_level(&self, registry: &mut Registry, entries: Vec<String>) -> Result<()> {
+        let index = match registry.runs.iter().position(|r| r.location == ".") {
+            Some(i) => i,
+            None => {
+                // Results from before the registry existed, or from a run that lost its record
+                let started_at = entries.iter()
+                    .filter_map(|e| fs::metadata(self.root.join(e)).and_then(|m| m.modified()).ok())
+                    .filter_map(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .min()
+                    .unwrap_or_else(now_secs);
+                let scan_id = format!("{}-legacy", format_timestamp(started_at));
+                registry.runs.push(RunRecord {
+                    scan_id,
+                    target: String::new(),
+                    started_at,
+                    finished_at: None,
+                    pid: 0,
+                    location: ".".to_string(),
+                    files: entries.clone(),
+                });
+                registry.runs.len() - 1
+            }
+        };
+        let record = &mut registry.runs[index];
+        let dest = self.root.join(RUNS_DIR).join(&record.scan_id);
+        fs::create_dir_all(&dest)?;
+        for entry in &entries {
+            fs::rename(self.root.join(entry), dest.join(entry))
+                .with_context(|| format!("failed to archive {}", entry))?;
+        }
+        record.location = format!("{}/{}", RUNS_DIR, record.scan_id);
+        record.files = entries;
+        Ok(())
+    }
+
+    /// Run `f` on the registry while holding the lock file, then save it atomically
+    fn with_lock<T>(&self, f: impl FnOnce(&mut Registry) -> Result<T>) -> Result<T> {
+        let lock_path = self.root.join(LOCK_FILE);
+        let mut attempts = 0;
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+                Ok(_) => break,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&lock_path).and_then(|m| m.modified()).ok()
+                        .and_then(|t| t.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK);
+                    if stale {
+                        let _ = fs::remove_file(&lock_path);
+                        continue;
+                    }
+                    attempts += 1;
+                    if attempts > 100 {
+                        anyhow::bail!("registry in {} is locked by another scan", self.root.display());
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(e) => return Err(e.into()),
+            }
         }
-    } else {
-        // Create results directory if it doesn't exist
-        fs::create_dir_all(path)?;
+
+        let result = (|| {
+            let mut registry = self.load()?;
+            let value = f(&mut registry)?;
+            let path = self.registry_path();
+            // Write then rename so readers never see a partial registry
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_string_pretty(&registry)?)?;
+            fs::rename(&tmp, &path)?;
+            Ok(value)
+        })();
+        let _ = fs::remove_file(&lock_path);
+        result
+    }
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<String>> {
+    let mut entries: Vec<String> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// An unfinished run is still going if its process exists (Linux) or it started recently
+fn is_running(record: &RunRecord) -> bool {
+    if record.finished_at.is_some() {
+        return false;
+    }
+    if record.pid == std::process::id() {
+        return true;
+    }
+    let proc = Path::new("/proc");
+    if proc.is_dir() {
+        return proc.join(record.pid.to_string()).exists();
     }
-    
-    Ok(())
+    now_secs().saturating_sub(record.started_at) < STALE_RUN.as_secs()
+}
+
+/// `20261016T093012Z`, unique within the registry
+fn new_scan_id(registry: &Registry) -> String {
+    let base = format_timestamp(now_secs());
+    let mut id = base.clone();
+    let mut n = 1;
+    while registry.runs.iter().any(|r| r.scan_id == id) {
+        n += 1;
+        id = format!("{}-{}", base, n);
+    }
+    id
+}
+
+/// UTC timestamp in compact ISO 8601 form
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
 }
 
 /// Enhanced result statistics
@@ -134,7 +421,7 @@ pub fn calculate_statistics(
     stats.total_scan_time_seconds = scan_duration;
 
     // Read CSV for detailed stats
-    let csv_path = format!("{}/target_apis_sorted.csv", results_dir);
+    let csv_path = super::paths::artifact(Path::new(results_dir), "target_apis_sorted.csv");
     if let Ok(content) = fs::read_to_string(&csv_path) {
         let lines: Vec<&str> = content.lines().skip(1).collect(); // Skip header
         stats.total_apis_found = lines.len();
@@ -0,0 +1,77 @@
+//! Versioned output schemas (`api-hunter schema`)
+//! `target_raw.jsonl`, `js_critical_info.json` and the `--report` file carry a `schema_version`; the
+//! JSON Schema of each is generated from the types themselves, so the published files can't drift from
+//! what the scanner writes.
+//! Compatibility rules: within a version, fields are only added (with defaults) and never renamed or
+//! removed; readers ignore unknown fields, so outputs of newer releases still load. Anything else bumps
+//! `SCHEMA_VERSION`.
+
+use anyhow::Result;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use std::path::{Path, PathBuf};
+
+use crate::gather::js_deep_analyzer::JsCriticalInfo;
+use crate::output::clean_reporter::{Finding, ScanReport};
+use crate::output::writer_jsonl::RawEvent;
+
+/// Version of all output schemas written by this build
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `schema_version` of files written before versioning; their layout is that of version 1
+pub fn unversioned() -> u32 {
+    0
+}
+
+/// Output types with a published schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// One line of `target_raw.jsonl`
+    RawEvent,
+    /// One entry of `ScanReport::findings`
+    Finding,
+    /// The `--report` file
+    ScanReport,
+    /// `js_critical_info.json`
+    JsCriticalInfo,
+}
+
+impl Artifact {
+    pub const ALL: [Artifact; 4] = [Artifact::RawEvent, Artifact::Finding, Artifact::ScanReport, Artifact::JsCriticalInfo];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Artifact::RawEvent => "raw_event",
+            Artifact::Finding => "finding",
+            Artifact::ScanReport => "scan_report",
+            Artifact::JsCriticalInfo => "js_critical_info",
+        }
+    }
+
+    pub fn file_name(self) -> String {
+        format!("{}.v{}.schema.json", self.name(), SCHEMA_VERSION)
+    }
+
+    pub fn schema(self) -> RootSchema {
+        let mut root = match self {
+            Artifact::RawEvent => schema_for!(RawEvent),
+            Artifact::Finding => schema_for!(Finding),
+            Artifact::ScanReport => schema_for!(ScanReport),
+            Artifact::JsCriticalInfo => schema_for!(JsCriticalInfo),
+        };
+        root.schema.extensions.insert("x-schema-version".to_string(), SCHEMA_VERSION.into());
+        root
+    }
+}
+
+/// Write one `<name>.v<version>.schema.json` per artifact into `dir`
+pub fn write_all(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for artifact in Artifact::ALL {
+        let path = dir.join(artifact.file_name());
+        std::fs::write(&path, serde_json::to_string_pretty(&artifact.schema())?)?;
+        written.push(path);
+    }
+    Ok(written)
+}
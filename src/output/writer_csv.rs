@@ -20,7 +20,7 @@ pub fn write_csv(path: &Path, items: &[&RawEvent]) -> anyhow::Result<()> {
             it.response_ms.map(|v| v.to_string()).unwrap_or_default(),
             it.tls_issuer.clone().unwrap_or_default(),
             flags.to_string(),
-            it.notes.join(", "),
+            crate::output::annotation::join(&it.annotations, ", "),
         ])?;
     }
     w.flush()?;
@@ -1,11 +1,18 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::output::annotation::{self, Annotation};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RawEvent {
+    /// Output schema version (`output::schema::SCHEMA_VERSION`); 0 for lines written before versioning
+    #[serde(default = "crate::output::schema::unversioned")]
+    pub schema_version: u32,
     pub orig_url: String,
     pub final_url: String,
     pub status: u16,
@@ -17,7 +24,75 @@ pub struct RawEvent {
     pub is_graphql: bool,
     pub json_sample: Option<Value>,
     pub score: i32,
-    pub notes: Vec<String>,
+    /// Typed findings about the endpoint (serialized with a `type` tag; legacy `notes` strings load too)
+    #[serde(default, alias = "notes", deserialize_with = "crate::output::annotation::deserialize_annotations")]
+    pub annotations: Vec<Annotation>,
+    /// Attempts the probe needed (1 = no retry)
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    /// All response headers, lower-cased names. Repeated headers are joined with `, `
+    /// (`set-cookie` with newlines, since cookie dates contain commas).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Truncated response body (`--store-bodies`; always the sample of an event stream)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<crate::probe::body::StoredBody>,
+    /// Simhash of the body read by the probe (16 hex digits), for similarity clustering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_simhash: Option<String>,
+    /// Status per method (`HEAD`, `GET`, `OPTIONS`, `POST` with `--aggressive`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub methods: BTreeMap<String, u16>,
+    /// Methods advertised in the OPTIONS response (`Allow`, `Access-Control-Allow-Methods`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_methods: Vec<String>,
+    /// Protocol the probe's responses came over (`HTTP/1.1`, `HTTP/2`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<String>,
+}
+
+impl RawEvent {
+    /// Add an annotation unless an identical one is present; returns whether it was added
+    pub fn annotate(&mut self, annotation: Annotation) -> bool {
+        if self.annotations.contains(&annotation) {
+            return false;
+        }
+        self.annotations.push(annotation);
+        true
+    }
+
+    /// Name of the detected WAF, if any
+    pub fn waf(&self) -> Option<&str> {
+        self.annotations.iter().find_map(|a| match a {
+            Annotation::WafDetected { waf } => Some(waf.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Response header by name (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+}
+
+/// Flatten a header map into `RawEvent::headers` form
+pub fn collect_headers(headers: &reqwest::header::HeaderMap) -> BTreeMap<String, String> {
+    let mut out: BTreeMap<String, String> = BTreeMap::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        let separator = if name == reqwest::header::SET_COOKIE { "\n" } else { ", " };
+        out.entry(name.as_str().to_string())
+            .and_modify(|v| {
+                v.push_str(separator);
+                v.push_str(&value);
+            })
+            .or_insert(value);
+    }
+    out
+}
+
+fn default_attempts() -> u32 {
+    1
 }
 
 pub fn write_jsonl(path: &Path, items: &[&RawEvent]) -> anyhow::Result<()> {
@@ -37,7 +112,7 @@ pub fn write_top_txt(path: &Path, items: &[&RawEvent]) -> anyhow::Result<()> {
         let _tag = if it.json_sample.is_some() { "json" } else { "" };
         let ms = it.response_ms.map(|m| m.to_string()).unwrap_or_else(|| "-".into());
         lines.push(format!("[{}] {} {} — {}ms — notes: {}",
-            it.score, it.status, it.final_url, ms, it.notes.join(", ")));
+            it.score, it.status, it.final_url, ms, annotation::join(&it.annotations, ", ")));
     }
     fs::write(path, lines.join("\n"))?;
     Ok(())
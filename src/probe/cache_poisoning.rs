@@ -0,0 +1,360 @@
+//! Web cache poisoning and cache deception
+//! Cache headers tell which CDN or reverse proxy sits in front of an endpoint and whether it stores the
+//! response. Two attacks are then tested:
+//! - Unkeyed headers: headers the cache leaves out of its key (`X-Forwarded-Host`, `X-Original-URL`,
+//!   `X-Forwarded-Scheme`, ...) but the application uses. A request carrying one gets a canary reflected
+//!   or the response changed; the same URL requested again without the header shows whether the cache
+//!   kept the poisoned response for everyone.
+//! - Cache deception: a dynamic endpoint that also answers under a static-looking path
+//!   (`/api/me/x.css`, `/api/me;x.css`) may be stored by a cache that caches by extension. The path is
+//!   requested with the scan credentials, then again without; the second request getting the first one's
+//!   content from the cache means another user's data can be read that way.
+//!
+//! Every request carries a fresh cache buster (`apihunter_cb`) or a fresh path, so only keys nobody else
+//! uses are poisoned. A buster that is already a cache hit means the query string is unkeyed too; the
+//! header tests are skipped for that URL then.
+
+use crate::http_client::Paced;
+use crate::output::writer_jsonl::{collect_headers, RawEvent};
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Query parameter of the cache buster
+pub const BUSTER_PARAM: &str = "apihunter_cb";
+
+/// Largest response body read
+const BODY_LIMIT: usize = 256 * 1024;
+
+/// Simhash distance up to which a path-confused response counts as the original content
+const SAME_CONTENT_DISTANCE: u32 = 3;
+
+/// What an unkeyed header is set to and how its effect shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Injection {
+    /// A host name with the canary, reflected in links, redirects or script URLs
+    Host,
+    /// A path with the canary, overriding the routed path
+    Path,
+    /// A plain-HTTP scheme, answered with a redirect
+    Scheme,
+}
+
+/// Headers commonly left out of cache keys
+pub const UNKEYED_HEADERS: &[(&str, Injection)] = &[
+    ("X-Forwarded-Host", Injection::Host),
+    ("X-Host", Injection::Host),
+    ("X-Forwarded-Server", Injection::Host),
+    ("X-Original-Host", Injection::Host),
+    ("X-Original-URL", Injection::Path),
+    ("X-Rewrite-URL", Injection::Path),
+    ("X-Forwarded-Scheme", Injection::Scheme),
+    ("X-Forwarded-Proto", Injection::Scheme),
+];
+
+/// Path-confusion variants; `{}` is replaced by a fresh file name
+pub const DECEPTION_SUFFIXES: &[&str] = &["/{}.css", ";{}.css", "%2F{}.css"];
+
+/// Caching layer as seen in the response headers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CacheInfo {
+    /// CDN or cache product (`Cloudflare`, `Fastly`, `Varnish`, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cdn: Option<String>,
+    /// Cache status header as sent (`HIT`, `MISS`, `Hit from cloudfront`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    pub hit: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vary: Option<String>,
+}
+
+/// Cache status headers, most specific first
+const STATUS_HEADERS: &[&str] = &["cf-cache-status", "x-cache", "akamai-cache-status", "x-cache-status", "x-proxy-cache", "x-drupal-cache", "x-vercel-cache", "x-nf-cache-status"];
+
+impl CacheInfo {
+    /// From lower-cased response headers (`RawEvent::headers` form)
+    pub fn from_headers(headers: &BTreeMap<String, String>) -> Self {
+        let get = |name: &str| headers.get(name).map(String::as_str);
+        let via = get("via").unwrap_or_default().to_ascii_lowercase();
+        let cdn = if get("cf-cache-status").is_some() || get("cf-ray").is_some() {
+            Some("Cloudflare")
+        } else if get("x-amz-cf-id").is_some() || via.contains("cloudfront") {
+            Some("CloudFront")
+        } else if get("x-served-by").is_some_and(|s| s.contains("cache-")) || get("x-fastly-request-id").is_some() {
+            Some("Fastly")
+        } else if get("akamai-cache-status").is_some() || headers.keys().any(|k| k.starts_with("x-akamai")) {
+            Some("Akamai")
+        } else if get("x-azure-ref").is_some() {
+            Some("Azure Front Door")
+        } else if get("x-vercel-cache").is_some() {
+            Some("Vercel")
+        } else if get("x-nf-cache-status").is_some() {
+            Some("Netlify")
+        } else if get("x-varnish").is_some() || via.contains("varnish") {
+            Some("Varnish")
+        } else if get("x-proxy-cache").is_some() {
+            Some("Nginx")
+        } else if get("x-drupal-cache").is_some() {
+            Some("Drupal")
+        } else {
+            None
+        };
+        let status = STATUS_HEADERS.iter().find_map(|h| get(h)).map(str::to_string);
+        let age = get("age").and_then(|a| a.trim().parse().ok());
+        let hit = status.as_deref().is_some_and(|s| s.to_ascii_lowercase().contains("hit")) || age.is_some_and(|a| a > 0);
+        Self {
+            cdn: cdn.map(str::to_string),
+            status,
+            hit,
+            age,
+            cache_control: get("cache-control").map(str::to_string),
+            vary: get("vary").map(str::to_string),
+        }
+    }
+
+    /// Whether the response came through a cache at all
+    pub fn is_cached(&self) -> bool {
+        self.cdn.is_some() || self.status.is_some() || self.age.is_some()
+    }
+
+    /// Whether `Cache-Control` lets a shared cache store the response
+    pub fn cacheable(&self) -> bool {
+        let Some(cc) = self.cache_control.as_deref().map(str::to_ascii_lowercase) else { return false };
+        let directive = |name: &str| cc.split(',').map(str::trim).find(|d| d.split('=').next() == Some(name));
+        let positive = |name: &str| directive(name).and_then(|d| d.split_once('=')).is_some_and(|(_, v)| v.trim_matches('"').parse::<u64>().is_ok_and(|n| n > 0));
+        if ["private", "no-store", "no-cache"].iter().any(|d| directive(d).is_some()) {
+            return false;
+        }
+        directive("public").is_some() || positive("s-maxage") || positive("max-age")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheAttack {
+    UnkeyedHeader,
+    CacheDeception,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheFinding {
+    pub attack: CacheAttack,
+    /// The header as sent, or the path-confused URL
+    pub vector: String,
+    /// URL the (poisoned) response is stored under, with `Vary` when the cache sent one
+    pub cache_key: String,
+    /// What the vector did to the response
+    pub effect: String,
+    /// The poisoned response came back to a request without the vector (or the credentials)
+    pub confirmed: bool,
+    /// "high" when confirmed, "medium" when the response is cacheable, else "low"
+    pub severity: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheReport {
+    pub url: String,
+    pub cache: CacheInfo,
+    /// Whether a fresh cache buster got a fresh cache entry; the header tests need it
+    pub buster_keyed: bool,
+    pub findings: Vec<CacheFinding>,
+}
+
+/// One response, as far as the tests need it
+#[derive(Debug, Clone)]
+struct Observed {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Observed {
+    fn cache(&self) -> CacheInfo {
+        CacheInfo::from_headers(&self.headers)
+    }
+
+    fn contains(&self, needle: &str) -> bool {
+        String::from_utf8_lossy(&self.body).contains(needle) || self.headers.values().any(|v| v.contains(needle))
+    }
+
+    fn same_content(&self, other: &Observed) -> bool {
+        if self.body == other.body {
+            return !self.body.is_empty();
+        }
+        match (crate::enrich::simhash::simhash(&self.body), crate::enrich::simhash::simhash(&other.body)) {
+            (Some(a), Some(b)) => crate::enrich::simhash::distance(a, b) <= SAME_CONTENT_DISTANCE,
+            _ => false,
+        }
+    }
+}
+
+fn canary() -> String {
+    format!("apihunter{:012x}", rand::random::<u64>() & 0xffff_ffff_ffff)
+}
+
+/// `url` with a fresh cache buster
+pub fn with_buster(url: &str, buster: &str) -> String {
+    let base = url.split('#').next().unwrap_or(url);
+    let separator = if base.contains('?') { '&' } else { '?' };
+    format!("{}{}{}={}", base, separator, BUSTER_PARAM, buster)
+}
+
+/// Path-confused URL for `suffix` (one of `DECEPTION_SUFFIXES`), the query dropped
+pub fn deception_url(url: &str, suffix: &str, name: &str) -> Option<String> {
+    let mut u = url::Url::parse(url).ok()?;
+    u.set_query(None);
+    u.set_fragment(None);
+    let path = u.path().trim_end_matches('/').to_string();
+    Some(format!("{}{}{}", u.origin().ascii_serialization(), path, suffix.replace("{}", name)))
+}
+
+/// Describe how `poisoned` differs from `baseline` for an injection carrying `canary`; `None` if not
+fn effect(injection: Injection, canary: &str, baseline: &Observed, poisoned: &Observed) -> Option<String> {
+    if poisoned.contains(canary) {
+        let place = if String::from_utf8_lossy(&poisoned.body).contains(canary) { "body" } else { "headers" };
+        return Some(format!("canary reflected in the response {}", place));
+    }
+    let redirect = |o: &Observed| (300..400).contains(&o.status);
+    match injection {
+        Injection::Scheme if redirect(poisoned) && !redirect(baseline) => {
+            Some(format!("redirect ({}) to {}", poisoned.status, poisoned.headers.get("location").map(String::as_str).unwrap_or("?")))
+        }
+        Injection::Path if poisoned.status != baseline.status => Some(format!("status {} instead of {}: the routed path was overridden", poisoned.status, baseline.status)),
+        _ => None,
+    }
+}
+
+fn severity(confirmed: bool, cacheable: bool) -> String {
+    if confirmed { "high" } else if cacheable { "medium" } else { "low" }.to_string()
+}
+
+/// Endpoints to test: per origin the first response that came through a cache, then the first JSON
+/// response (the likeliest to carry per-user data), at most `limit`
+pub fn targets(results: &[RawEvent], limit: usize) -> Vec<String> {
+    let ok = |e: &&RawEvent| (200..300).contains(&e.status);
+    let cached = |e: &RawEvent| CacheInfo::from_headers(&e.headers).is_cached();
+    let json = |e: &RawEvent| e.content_type.as_deref().is_some_and(|c| c.contains("json"));
+    let mut per_origin: Vec<(String, Vec<String>)> = Vec::new();
+    for e in results.iter().filter(ok).filter(|e| cached(e)).chain(results.iter().filter(ok).filter(|e| json(e))) {
+        let Some(origin) = crate::probe::soft404::origin_of(&e.final_url) else { continue };
+        match per_origin.iter_mut().find(|(o, _)| *o == origin) {
+            Some((_, urls)) if urls.len() < 2 && !urls.contains(&e.final_url) => urls.push(e.final_url.clone()),
+            Some(_) => {}
+            None => per_origin.push((origin, vec![e.final_url.clone()])),
+        }
+    }
+    per_origin.into_iter().flat_map(|(_, urls)| urls).take(limit).collect()
+}
+
+pub struct CachePoisoner {
+    /// With the scan credentials and cookie jar
+    client: Client,
+    /// Without them, for the second request of the deception test
+    anonymous: Client,
+}
+
+impl CachePoisoner {
+    pub fn new(timeout: Duration) -> anyhow::Result<Self> {
+        let build = |builder: reqwest::ClientBuilder| builder
+            .timeout(timeout)
+            .danger_accept_invalid_certs(true)
+            .redirect(reqwest::redirect::Policy::none())
+            .build();
+        Ok(Self {
+            client: build(crate::http_client::builder("probe::cache_poisoning"))?,
+            anonymous: build(crate::http_client::builder_unauthenticated("probe::cache_poisoning"))?,
+        })
+    }
+
+    async fn fetch(&self, client: &Client, url: &str, header: Option<(&str, &str)>) -> Option<Observed> {
+        let mut req = client.get(url);
+        if let Some((name, value)) = header {
+            req = req.header(name, value);
+        }
+        let resp = req.send_paced().await.ok()?;
+        let status = resp.status().as_u16();
+        let headers = collect_headers(resp.headers());
+        let body = crate::probe::http_probe::read_limited(resp, BODY_LIMIT).await.map(|(b, _)| b).unwrap_or_default();
+        Some(Observed { status, headers, body })
+    }
+
+    /// Both tests on one endpoint; `None` if it didn't answer
+    pub async fn test(&self, url: &str) -> Option<CacheReport> {
+        let baseline = self.fetch(&self.client, &with_buster(url, &canary()), None).await?;
+        let cache = baseline.cache();
+        // A buster nobody requested before can't be a hit unless the query string is unkeyed
+        let buster_keyed = !cache.hit;
+        let vary = cache.vary.clone();
+        let key = |u: &str| match &vary {
+            Some(v) => format!("{} (Vary: {})", u, v),
+            None => u.to_string(),
+        };
+        let mut findings = Vec::new();
+
+        if buster_keyed {
+            for &(name, injection) in UNKEYED_HEADERS {
+                let canary = canary();
+                let value = match injection {
+                    Injection::Host => format!("{}.example.net", canary),
+                    Injection::Path => format!("/{}", canary),
+                    Injection::Scheme => "http".to_string(),
+                };
+                let busted = with_buster(url, &canary);
+                let Some(poisoned) = self.fetch(&self.client, &busted, Some((name, &value))).await else { continue };
+                let Some(changed) = effect(injection, &canary, &baseline, &poisoned) else { continue };
+                // Same key, no header: does the cache hand out the poisoned response?
+                let confirmed = match self.fetch(&self.client, &busted, None).await {
+                    Some(replay) => effect(injection, &canary, &baseline, &replay).is_some(),
+                    None => false,
+                };
+                findings.push(CacheFinding {
+                    attack: CacheAttack::UnkeyedHeader,
+                    vector: format!("{}: {}", name, value),
+                    cache_key: key(&busted),
+                    effect: changed,
+                    confirmed,
+                    severity: severity(confirmed, poisoned.cache().cacheable()),
+                });
+            }
+        }
+
+        // Deception only matters for content the cache wouldn't store under its own path
+        let dynamic = (200..300).contains(&baseline.status) && !cache.cacheable() && !baseline.body.is_empty();
+        if dynamic {
+            for suffix in DECEPTION_SUFFIXES {
+                let Some(target) = deception_url(url, suffix, &canary()) else { continue };
+                let Some(first) = self.fetch(&self.client, &target, None).await else { continue };
+                if !(200..300).contains(&first.status) || !first.same_content(&baseline) {
+                    continue;
+                }
+                let confirmed = match self.fetch(&self.anonymous, &target, None).await {
+                    Some(second) => second.cache().hit && second.same_content(&first),
+                    None => false,
+                };
+                let cacheable = first.cache().cacheable() || first.cache().hit;
+                findings.push(CacheFinding {
+                    attack: CacheAttack::CacheDeception,
+                    vector: target.clone(),
+                    cache_key: key(&target),
+                    effect: match (confirmed, cacheable) {
+                        (true, _) => "the endpoint's content was served from the cache to a request without credentials".to_string(),
+                        (false, true) => "the endpoint's content is returned under a static path and marked cacheable".to_string(),
+                        (false, false) => "the endpoint's content is returned under a static path (not cached here)".to_string(),
+                    },
+                    confirmed,
+                    severity: severity(confirmed, cacheable),
+                });
+                // One path-confusion variant is enough proof
+                break;
+            }
+        }
+
+        Some(CacheReport { url: url.to_string(), cache, buster_keyed, findings })
+    }
+}
@@ -0,0 +1,184 @@
+//! Typed probe failures
+//! A failed probe used to be a debug log line. The reason is now classified (DNS, refused, reset,
+//! timeout, TLS, ...) and written to `failures.jsonl` with the attempt count, so dead hosts can be told
+//! apart from hosts that drop or block the scanner, and only transient failures are retried.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Host name doesn't resolve
+    Dns,
+    /// Nothing listens on the port
+    ConnectionRefused,
+    /// Connection dropped mid-request; typical for firewalls and WAFs cutting off the scanner
+    ConnectionReset,
+    Timeout,
+    /// Handshake or certificate failure
+    Tls,
+    /// Redirect loop or too many redirects
+    Redirect,
+    /// Malformed response or unsupported protocol
+    Protocol,
+    Other,
+}
+
+impl FailureKind {
+    /// Worth another attempt: the same request may succeed later. DNS, refused and TLS failures don't
+    /// change between attempts.
+    pub fn is_transient(self) -> bool {
+        matches!(self, FailureKind::Timeout | FailureKind::ConnectionReset | FailureKind::Other)
+    }
+
+    /// The host answered at the network level but didn't let the request through
+    pub fn suggests_blocking(self) -> bool {
+        matches!(self, FailureKind::ConnectionReset | FailureKind::Timeout)
+    }
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FailureKind::Dns => "dns",
+            FailureKind::ConnectionRefused => "connection_refused",
+            FailureKind::ConnectionReset => "connection_reset",
+            FailureKind::Timeout => "timeout",
+            FailureKind::Tls => "tls",
+            FailureKind::Redirect => "redirect",
+            FailureKind::Protocol => "protocol",
+            FailureKind::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Classify an error by walking its source chain
+pub fn classify(err: &(dyn std::error::Error + 'static)) -> FailureKind {
+    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    let mut fallback = FailureKind::Other;
+    while let Some(e) = current {
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+            match io.kind() {
+                ErrorKind::ConnectionRefused => return FailureKind::ConnectionRefused,
+                ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof => {
+                    return FailureKind::ConnectionReset
+                }
+                ErrorKind::TimedOut => return FailureKind::Timeout,
+                _ => {}
+            }
+        }
+        if e.downcast_ref::<tokio::time::error::Elapsed>().is_some() {
+            return FailureKind::Timeout;
+        }
+        if let Some(re) = e.downcast_ref::<reqwest::Error>() {
+            if re.is_timeout() {
+                return FailureKind::Timeout;
+            }
+            if re.is_redirect() {
+                return FailureKind::Redirect;
+            }
+            // Its message only carries the URL; the cause is further down
+            current = e.source();
+            continue;
+        }
+        let kind = classify_message(&e.to_string());
+        if kind != FailureKind::Other {
+            // Keep looking: a more specific cause may sit further down the chain
+            if fallback == FailureKind::Other {
+                fallback = kind;
+            }
+            if kind != FailureKind::Protocol {
+                return kind;
+            }
+        }
+        current = e.source();
+    }
+    fallback
+}
+
+/// Classify from an error message (hyper/rustls errors are mostly plain strings)
+pub fn classify_message(message: &str) -> FailureKind {
+    let m = message.to_lowercase();
+    if m.contains("dns error") || m.contains("failed to lookup address") || m.contains("name or service not known")
+        || m.contains("no such host") || m.contains("nodename nor servname")
+    {
+        FailureKind::Dns
+    } else if m.contains("connection refused") {
+        FailureKind::ConnectionRefused
+    } else if m.contains("connection reset") || m.contains("connection closed") || m.contains("broken pipe")
+        || m.contains("unexpected eof") || m.contains("connection aborted")
+    {
+        FailureKind::ConnectionReset
+    } else if m.contains("timed out") || m.contains("deadline has elapsed") {
+        FailureKind::Timeout
+    } else if m.contains("certificate") || m.contains("tls") || m.contains("ssl") || m.contains("handshake") {
+        FailureKind::Tls
+    } else if m.contains("redirect") {
+        FailureKind::Redirect
+    } else if m.contains("invalid http") || m.contains("parse") || m.contains("protocol") {
+        FailureKind::Protocol
+    } else {
+        FailureKind::Other
+    }
+}
+
+/// Error returned by the probe once all attempts failed
+#[derive(Debug, Clone)]
+pub struct ProbeError {
+    pub kind: FailureKind,
+    pub message: String,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+}
+
+impl std::fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} after {} attempt(s): {}", self.kind, self.attempts, self.message)
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+/// One line of `failures.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeFailure {
+    pub url: String,
+    pub host: String,
+    pub kind: FailureKind,
+    pub message: String,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+}
+
+impl ProbeFailure {
+    pub fn from_error(url: &str, err: &anyhow::Error) -> Self {
+        let host = url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_default();
+        match err.downcast_ref::<ProbeError>() {
+            Some(p) => Self { url: url.to_string(), host, kind: p.kind, message: p.message.clone(), attempts: p.attempts, elapsed_ms: p.elapsed_ms },
+            None => Self { url: url.to_string(), host, kind: classify(err.as_ref()), message: format!("{:#}", err), attempts: 1, elapsed_ms: 0 },
+        }
+    }
+}
+
+/// Failures per kind, for the scan summary
+pub fn count_by_kind(failures: &[ProbeFailure]) -> BTreeMap<FailureKind, usize> {
+    let mut counts = BTreeMap::new();
+    for f in failures {
+        *counts.entry(f.kind).or_insert(0) += 1;
+    }
+    counts
+}
+
+pub fn write_failures(path: &Path, failures: &[ProbeFailure]) -> anyhow::Result<()> {
+    let mut f = std::fs::File::create(path)?;
+    for failure in failures {
+        f.write_all(serde_json::to_string(failure)?.as_bytes())?;
+        f.write_all(b"\n")?;
+    }
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use reqwest::Client;
 use serde_json::{json, Value};
 use anyhow::Result;
@@ -20,7 +21,7 @@ pub struct GraphQLTester {
 impl GraphQLTester {
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
+            client: crate::http_client::builder("probe::graphql")
                 .timeout(std::time::Duration::from_secs(10))
                 .danger_accept_invalid_certs(true)
                 .build()
@@ -56,7 +57,7 @@ impl GraphQLTester {
             .post(url)
             .json(&introspection_query)
             .header("Content-Type", "application/json")
-            .send()
+            .send_paced()
             .await?;
 
         let elapsed = start.elapsed().as_millis();
@@ -139,7 +140,7 @@ impl GraphQLTester {
             "query": "{ a { b { c { d { e { f { g { h { i { j } } } } } } } } } }"
         });
 
-        if let Ok(response) = self.client.post(url).json(&deep_query).send().await {
+        if let Ok(response) = self.client.post(url).json(&deep_query).send_paced().await {
             if response.status().is_success() {
                 vulns.push(GraphQLVulnerability {
                     name: "No Query Depth Limit".to_string(),
@@ -159,7 +160,7 @@ impl GraphQLTester {
             {"query": "{ __typename }"},
         ]);
 
-        if let Ok(response) = self.client.post(url).json(&batch_query).send().await {
+        if let Ok(response) = self.client.post(url).json(&batch_query).send_paced().await {
             if response.status().is_success() {
                 vulns.push(GraphQLVulnerability {
                     name: "Batch Query DoS".to_string(),
@@ -175,7 +176,7 @@ impl GraphQLTester {
             "query": "{ __typename __typename __typename __typename __typename }"
         });
 
-        if let Ok(response) = self.client.post(url).json(&dup_query).send().await {
+        if let Ok(response) = self.client.post(url).json(&dup_query).send_paced().await {
             if response.status().is_success() {
                 vulns.push(GraphQLVulnerability {
                     name: "Field Duplication Attack".to_string(),
@@ -215,7 +216,7 @@ impl GraphQLTester {
                 .post(&url)
                 .json(&test_query)
                 .header("Content-Type", "application/json")
-                .send()
+                .send_paced()
                 .await
             {
                 if response.status().is_success() {
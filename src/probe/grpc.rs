@@ -0,0 +1,394 @@
+//! gRPC and gRPC-web detection with server reflection
+//! Hosts whose responses are `application/grpc*` or came over HTTP/2 get a call to the server
+//! reflection service (`grpc.reflection.v1` and `v1alpha`), first as native gRPC over HTTP/2, then as
+//! gRPC-web over HTTP/1.1 for services behind a proxy like Envoy. A gRPC answer marks the host as a gRPC
+//! service; a service list means reflection is open, and each service's file descriptor gives its
+//! methods. The calls carry no credentials, so whatever reflection returns is public.
+
+use crate::http_client::Paced;
+use crate::output::writer_jsonl::RawEvent;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Reflection RPCs, newest first
+pub const REFLECTION_METHODS: &[&str] = &[
+    "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo",
+    "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo",
+];
+
+/// Services whose descriptors are fetched
+const MAX_SERVICES: usize = 25;
+
+/// Largest response body read
+const BODY_LIMIT: usize = 1024 * 1024;
+
+/// Flag of the gRPC-web trailer frame
+pub const TRAILER_FLAG: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Transport {
+    /// `application/grpc` over HTTP/2
+    Grpc,
+    /// `application/grpc-web+proto` over HTTP/1.1
+    GrpcWeb,
+}
+
+impl Transport {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Transport::Grpc => "application/grpc",
+            Transport::GrpcWeb => "application/grpc-web+proto",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrpcMethod {
+    pub name: String,
+    pub input_type: String,
+    pub output_type: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub client_streaming: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub server_streaming: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrpcService {
+    /// Fully qualified name, e.g. `shop.v1.OrderService`
+    pub name: String,
+    pub methods: Vec<GrpcMethod>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrpcReport {
+    pub origin: String,
+    pub transport: Transport,
+    /// Reflection RPC that answered with a service list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<GrpcService>,
+    /// `grpc-status`/`grpc-message` of a refused reflection call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+}
+
+impl GrpcReport {
+    pub fn methods(&self) -> usize {
+        self.services.iter().map(|s| s.methods.len()).sum()
+    }
+}
+
+/// Protobuf field value; fixed-width values are skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let b = *buf.get(*pos)?;
+        *pos += 1;
+        value |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Length-delimited field `number` holding `value`
+pub fn encode_bytes_field(number: u32, value: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint(&mut out, ((number as u64) << 3) | 2);
+    write_varint(&mut out, value.len() as u64);
+    out.extend_from_slice(value);
+    out
+}
+
+/// Fields of a protobuf message in wire order; `None` if it is malformed
+pub fn decode_fields(buf: &[u8]) -> Option<Vec<(u32, Field)>> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < buf.len() {
+        let key = read_varint(buf, &mut pos)?;
+        let number = (key >> 3) as u32;
+        match key & 7 {
+            0 => fields.push((number, Field::Varint(read_varint(buf, &mut pos)?))),
+            1 => pos = pos.checked_add(8).filter(|&p| p <= buf.len())?,
+            2 => {
+                let len = read_varint(buf, &mut pos)? as usize;
+                let end = pos.checked_add(len).filter(|&e| e <= buf.len())?;
+                fields.push((number, Field::Bytes(buf[pos..end].to_vec())));
+                pos = end;
+            }
+            5 => pos = pos.checked_add(4).filter(|&p| p <= buf.len())?,
+            _ => return None,
+        }
+    }
+    Some(fields)
+}
+
+fn string_field(fields: &[(u32, Field)], number: u32) -> Option<String> {
+    fields.iter().find_map(|(n, f)| match f {
+        Field::Bytes(b) if *n == number => Some(String::from_utf8_lossy(b).into_owned()),
+        _ => None,
+    })
+}
+
+fn bool_field(fields: &[(u32, Field)], number: u32) -> bool {
+    fields.iter().any(|(n, f)| *n == number && *f == Field::Varint(1))
+}
+
+fn messages(fields: &[(u32, Field)], number: u32) -> impl Iterator<Item = &[u8]> {
+    fields.iter().filter_map(move |(n, f)| match f {
+        Field::Bytes(b) if *n == number => Some(b.as_slice()),
+        _ => None,
+    })
+}
+
+/// gRPC length-prefixed message (uncompressed)
+pub fn frame(flag: u8, message: &[u8]) -> Vec<u8> {
+    let mut out = vec![flag];
+    out.extend((message.len() as u32).to_be_bytes());
+    out.extend_from_slice(message);
+    out
+}
+
+/// Length-prefixed messages in a response body: flag and payload
+pub fn parse_frames(body: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut frames = Vec::new();
+    let mut rest = body;
+    while rest.len() >= 5 {
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let Some(payload) = rest.get(5..5 + len) else { break };
+        frames.push((rest[0], payload.to_vec()));
+        rest = &rest[5 + len..];
+    }
+    frames
+}
+
+/// `ServerReflectionRequest` with `list_services` (field 7)
+pub fn list_services_request() -> Vec<u8> {
+    encode_bytes_field(7, b"*")
+}
+
+/// `ServerReflectionRequest` with `file_containing_symbol` (field 4)
+pub fn file_containing_symbol_request(symbol: &str) -> Vec<u8> {
+    encode_bytes_field(4, symbol.as_bytes())
+}
+
+/// What a `ServerReflectionResponse` carries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReflectionResponse {
+    pub services: Vec<String>,
+    /// Serialized `FileDescriptorProto`s
+    pub files: Vec<Vec<u8>>,
+    /// `error_response`: code and message
+    pub error: Option<(u64, String)>,
+}
+
+pub fn parse_reflection_response(message: &[u8]) -> Option<ReflectionResponse> {
+    let fields = decode_fields(message)?;
+    let mut response = ReflectionResponse::default();
+    // list_services_response (6) -> repeated ServiceResponse service (1) -> name (1)
+    for list in messages(&fields, 6) {
+        for service in messages(&decode_fields(list)?, 1) {
+            response.services.extend(string_field(&decode_fields(service)?, 1));
+        }
+    }
+    // file_descriptor_response (4) -> repeated bytes file_descriptor_proto (1)
+    for files in messages(&fields, 4) {
+        response.files.extend(messages(&decode_fields(files)?, 1).map(<[u8]>::to_vec));
+    }
+    // error_response (7) -> error_code (1), error_message (2)
+    if let Some(error) = messages(&fields, 7).next() {
+        let error = decode_fields(error)?;
+        let code = error.iter().find_map(|(n, f)| match f {
+            Field::Varint(v) if *n == 1 => Some(*v),
+            _ => None,
+        });
+        response.error = Some((code.unwrap_or(0), string_field(&error, 2).unwrap_or_default()));
+    }
+    Some(response)
+}
+
+/// Services and methods declared in a serialized `FileDescriptorProto`
+pub fn parse_file_descriptor(file: &[u8]) -> Vec<GrpcService> {
+    let Some(fields) = decode_fields(file) else { return Vec::new() };
+    let package = string_field(&fields, 2).filter(|p| !p.is_empty());
+    let qualify = |name: String| match &package {
+        Some(p) => format!("{}.{}", p, name),
+        None => name,
+    };
+    let strip = |t: String| t.trim_start_matches('.').to_string();
+    messages(&fields, 6)
+        .filter_map(decode_fields)
+        .filter_map(|service| {
+            let name = qualify(string_field(&service, 1)?);
+            let methods = messages(&service, 2)
+                .filter_map(decode_fields)
+                .filter_map(|m| Some(GrpcMethod {
+                    name: string_field(&m, 1)?,
+                    input_type: string_field(&m, 2).map(strip).unwrap_or_default(),
+                    output_type: string_field(&m, 3).map(strip).unwrap_or_default(),
+                    client_streaming: bool_field(&m, 5),
+                    server_streaming: bool_field(&m, 6),
+                }))
+                .collect();
+            Some(GrpcService { name, methods })
+        })
+        .collect()
+}
+
+/// Origins worth a reflection call: gRPC content types first, then hosts that answered over HTTP/2
+pub fn targets(results: &[RawEvent], limit: usize) -> Vec<String> {
+    let is_grpc = |e: &RawEvent| e.content_type.as_deref().is_some_and(|c| c.starts_with("application/grpc"));
+    let is_h2 = |e: &RawEvent| e.http_version.as_deref() == Some("HTTP/2");
+    let mut origins: Vec<String> = Vec::new();
+    for e in results.iter().filter(|e| is_grpc(e)).chain(results.iter().filter(|e| !is_grpc(e) && is_h2(e))) {
+        if let Some(origin) = crate::probe::soft404::origin_of(&e.final_url) {
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+        }
+    }
+    origins.truncate(limit);
+    origins
+}
+
+/// Reply to one reflection call
+struct Reply {
+    grpc: bool,
+    messages: Vec<Vec<u8>>,
+    status: Option<String>,
+}
+
+pub struct GrpcProber {
+    timeout: Duration,
+}
+
+impl GrpcProber {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    fn client(&self, transport: Transport) -> Option<Client> {
+        let builder = crate::http_client::builder_unauthenticated("probe::grpc")
+            .timeout(self.timeout)
+            .danger_accept_invalid_certs(true);
+        match transport {
+            // h2c on plain HTTP, ALPN h2 only on TLS
+            Transport::Grpc => builder.http2_prior_knowledge(),
+            Transport::GrpcWeb => builder.http1_only(),
+        }
+        .build()
+        .ok()
+    }
+
+    /// One unary-style call of the streaming reflection RPC; `None` if nothing answered
+    async fn call(&self, client: &Client, transport: Transport, url: &str, request: &[u8]) -> Option<Reply> {
+        let mut req = client
+            .post(url)
+            .header("Content-Type", transport.content_type())
+            .body(frame(0, request));
+        req = match transport {
+            Transport::Grpc => req.header("TE", "trailers"),
+            Transport::GrpcWeb => req.header("X-Grpc-Web", "1").header("Accept", transport.content_type()),
+        };
+        let resp = req.send_paced().await.ok()?;
+        let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let grpc = header("content-type").is_some_and(|c| c.starts_with("application/grpc")) || header("grpc-status").is_some();
+        let mut status = header("grpc-status").filter(|s| s != "0").map(|s| match header("grpc-message") {
+            Some(m) => format!("{} {}", s, urlencoding::decode(&m).map(|m| m.into_owned()).unwrap_or(m)),
+            None => s,
+        });
+        let body = crate::probe::http_probe::read_limited(resp, BODY_LIMIT).await.map(|(b, _)| b).unwrap_or_default();
+        let mut messages = Vec::new();
+        for (flag, payload) in parse_frames(&body) {
+            if flag & TRAILER_FLAG == 0 {
+                messages.push(payload);
+                continue;
+            }
+            // gRPC-web trailers travel as a body frame
+            let trailers = String::from_utf8_lossy(&payload).into_owned();
+            let trailer = |name: &str| trailers.lines()
+                .filter_map(|l| l.split_once(':'))
+                .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.trim().to_string());
+            if let Some(s) = trailer("grpc-status").filter(|s| s != "0") {
+                status = Some(trailer("grpc-message").map(|m| format!("{} {}", s, m)).unwrap_or(s));
+            }
+        }
+        Some(Reply { grpc, messages, status })
+    }
+
+    /// Detection and reflection for one origin; `None` if it doesn't speak gRPC
+    pub async fn probe(&self, origin: &str) -> Option<GrpcReport> {
+        let origin = origin.trim_end_matches('/');
+        let mut detected: Option<GrpcReport> = None;
+        for transport in [Transport::Grpc, Transport::GrpcWeb] {
+            let Some(client) = self.client(transport) else { continue };
+            for method in REFLECTION_METHODS {
+                let url = format!("{}{}", origin, method);
+                let Some(reply) = self.call(&client, transport, &url, &list_services_request()).await else { continue };
+                if !reply.grpc {
+                    continue;
+                }
+                let services: Vec<String> = reply.messages.iter()
+                    .filter_map(|m| parse_reflection_response(m))
+                    .flat_map(|r| r.services)
+                    .collect();
+                if services.is_empty() {
+                    detected.get_or_insert(GrpcReport {
+                        origin: origin.to_string(),
+                        transport,
+                        reflection: None,
+                        services: Vec::new(),
+                        status: reply.status,
+                        severity: None,
+                    });
+                    continue;
+                }
+                let mut report = GrpcReport {
+                    origin: origin.to_string(),
+                    transport,
+                    reflection: Some(method.trim_start_matches('/').to_string()),
+                    services: Vec::new(),
+                    status: None,
+                    severity: None,
+                };
+                for name in services.iter().take(MAX_SERVICES) {
+                    let described = self.call(&client, transport, &url, &file_containing_symbol_request(name)).await
+                        .map(|r| r.messages)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|m| parse_reflection_response(m))
+                        .flat_map(|r| r.files)
+                        .flat_map(|f| parse_file_descriptor(&f))
+                        .find(|s| &s.name == name);
+                    report.services.push(described.unwrap_or_else(|| GrpcService { name: name.clone(), methods: Vec::new() }));
+                }
+                // The reflection service itself and health checks are present on every server
+                let application = report.services.iter().any(|s| !s.name.starts_with("grpc."));
+                report.severity = Some(if application { "MEDIUM" } else { "LOW" }.to_string());
+                return Some(report);
+            }
+        }
+        detected
+    }
+}
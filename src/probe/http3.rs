@@ -0,0 +1,273 @@
+//! HTTP/3 comparison (`--http3`)
+//! Hosts that advertise HTTP/3 in `Alt-Svc` get their endpoints requested a second time over QUIC, and
+//! the answers are compared with the TCP ones (HTTP/1.1 or HTTP/2). WAF and CDN rules are often attached
+//! to only one of the two listeners, so an endpoint that is blocked over TCP may answer over QUIC, or the
+//! other way round. QUIC requests go through curl (7.88+ built with HTTP/3), like the optional gau and
+//! waybackurls integrations; without it the advertising hosts are still listed. No proxy carries QUIC,
+//! so the phase is skipped when the scan is proxied.
+
+use crate::output::clean_reporter::Severity;
+use crate::output::writer_jsonl::RawEvent;
+use anyhow::{bail, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Endpoints compared per host, highest score first
+pub const MAX_PER_HOST: usize = 25;
+
+/// Bytes of each QUIC response body read (requested with a `Range` header, like the probe)
+const BODY_LIMIT: usize = 16 * 1024;
+
+/// One `Alt-Svc` alternative
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AltSvc {
+    /// ALPN id, e.g. `h3` or `h3-29`
+    pub protocol: String,
+    /// Another host serving the alternative; `None` for the same host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+}
+
+impl AltSvc {
+    pub fn is_h3(&self) -> bool {
+        self.protocol == "h3" || self.protocol.starts_with("h3-")
+    }
+}
+
+/// Parse an `Alt-Svc` header value (RFC 7838), e.g. `h3=":443"; ma=86400, h3-29=":443"`
+pub fn parse_alt_svc(value: &str) -> Vec<AltSvc> {
+    if value.trim().eq_ignore_ascii_case("clear") {
+        return Vec::new();
+    }
+    value.split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let (protocol, authority) = parts.next()?.split_once('=')?;
+            let authority = authority.trim().trim_matches('"');
+            let (host, port) = authority.rsplit_once(':')?;
+            let max_age = parts
+                .filter_map(|p| p.split_once('='))
+                .find(|(k, _)| k.trim().eq_ignore_ascii_case("ma"))
+                .and_then(|(_, v)| v.trim().parse().ok());
+            Some(AltSvc {
+                protocol: protocol.trim().to_ascii_lowercase(),
+                host: (!host.is_empty()).then(|| host.to_string()),
+                port: port.parse().ok()?,
+                max_age,
+            })
+        })
+        .collect()
+}
+
+/// The HTTP/3 alternative a probed endpoint advertises, if any
+pub fn advertised(ev: &RawEvent) -> Option<AltSvc> {
+    parse_alt_svc(ev.header("alt-svc")?).into_iter().find(|a| a.is_h3())
+}
+
+/// A response received over QUIC
+#[derive(Debug, Clone, Serialize)]
+pub struct H3Response {
+    pub status: u16,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    pub length: usize,
+    #[serde(skip)]
+    pub simhash: Option<u64>,
+}
+
+/// Parse curl's `-i` output: status line, headers, blank line, body
+pub fn parse_curl_output(raw: &[u8]) -> Option<H3Response> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    for line in lines {
+        if let Some((k, v)) = line.split_once(':') {
+            headers.entry(k.trim().to_ascii_lowercase())
+                .and_modify(|existing| { existing.push_str(", "); existing.push_str(v.trim()); })
+                .or_insert_with(|| v.trim().to_string());
+        }
+    }
+    Some(H3Response { status, headers, length: body.len(), simhash: crate::enrich::simhash::simhash(body) })
+}
+
+/// curl is installed and built with HTTP/3
+pub fn curl_supports_http3() -> bool {
+    std::process::Command::new("curl")
+        .arg("--version")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().any(|l| l.starts_with("Features:") && l.contains("HTTP3")))
+        .unwrap_or(false)
+}
+
+/// Request `url` over HTTP/3 only, with the scan's identification, custom and auth headers
+pub async fn fetch(url: &str, alt: &AltSvc, timeout: Duration) -> Result<H3Response> {
+    let parsed = url::Url::parse(url)?;
+    let Some(host) = parsed.host_str() else { bail!("no host in {}", url) };
+    let mut args: Vec<String> = vec![
+        "-s".into(), "-i".into(), "-k".into(), "--http3-only".into(),
+        "--max-time".into(), timeout.as_secs().max(1).to_string(),
+        "-A".into(), crate::tls_profile::user_agent_or("api-hunter/0.1").to_string(),
+        "-H".into(), format!("Range: bytes=0-{}", BODY_LIMIT - 1),
+    ];
+    for (name, value) in crate::identify::record("probe::http3").iter() {
+        args.push("-H".into());
+        args.push(format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes())));
+    }
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let alt_host = alt.host.as_deref().unwrap_or(host);
+    if alt_host != host || alt.port != port {
+        args.push("--connect-to".into());
+        args.push(format!("{}:{}:{}:{}", host, port, alt_host, alt.port));
+    }
+    args.push(url.to_string());
+
+    crate::http_client::pace().await;
+    let output = tokio::process::Command::new("curl").args(&args).kill_on_drop(true).output().await?;
+    if !output.status.success() {
+        bail!("curl exited with {} ({})", output.status, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    match parse_curl_output(&output.stdout) {
+        Some(resp) => Ok(resp),
+        None => bail!("no HTTP/3 response"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difference {
+    /// Refused over TCP, answered over QUIC: the filter only sits on the TCP listener
+    BlockedOverTcpOnly,
+    /// Refused over QUIC only
+    BlockedOverH3Only,
+    /// Different status class
+    StatusDiffers,
+    /// Same status, different page
+    ContentDiffers,
+}
+
+impl Difference {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::BlockedOverTcpOnly => Severity::High,
+            Self::StatusDiffers => Severity::Medium,
+            Self::BlockedOverH3Only | Self::ContentDiffers => Severity::Low,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct H3Finding {
+    pub url: String,
+    pub difference: Difference,
+    pub severity: Severity,
+    /// Protocol of the TCP response (`HTTP/1.1`, `HTTP/2`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_version: Option<String>,
+    pub tcp_status: u16,
+    pub h3_status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub h3_server: Option<String>,
+}
+
+fn blocked(status: u16) -> bool {
+    matches!(status, 403 | 406 | 429)
+}
+
+/// How the QUIC answer differs from the probe's TCP answer, if it does
+pub fn compare(ev: &RawEvent, h3: &H3Response) -> Option<H3Finding> {
+    let tcp = ev.status;
+    let difference = if blocked(tcp) && !blocked(h3.status) && h3.status < 500 {
+        Difference::BlockedOverTcpOnly
+    } else if blocked(h3.status) && !blocked(tcp) {
+        Difference::BlockedOverH3Only
+    } else if tcp / 100 != h3.status / 100 {
+        Difference::StatusDiffers
+    } else {
+        let tcp_hash = ev.body_simhash.as_deref().and_then(crate::enrich::simhash::from_hex)?;
+        let distance = crate::enrich::simhash::distance(tcp_hash, h3.simhash?);
+        if tcp != h3.status || distance <= crate::enrich::simhash::DEFAULT_MAX_DISTANCE {
+            return None;
+        }
+        Difference::ContentDiffers
+    };
+    Some(H3Finding {
+        url: ev.final_url.clone(),
+        difference,
+        severity: difference.severity(),
+        tcp_version: ev.http_version.clone(),
+        tcp_status: tcp,
+        h3_status: h3.status,
+        tcp_server: ev.server.clone(),
+        h3_server: h3.headers.get("server").cloned(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct H3Host {
+    pub host: String,
+    pub alt_svc: AltSvc,
+    /// Endpoints that answered over QUIC
+    pub answered: usize,
+    /// Endpoints that didn't (timeout, QUIC blocked by the network, curl error)
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Http3Report {
+    pub hosts: Vec<H3Host>,
+    /// curl with HTTP/3 was available, so the hosts were actually requested
+    pub probed: bool,
+    pub findings: Vec<H3Finding>,
+}
+
+/// Compare up to `MAX_PER_HOST` endpoints of every host that advertises HTTP/3
+pub async fn run(events: &[RawEvent], timeout: Duration, concurrency: usize) -> Http3Report {
+    let mut by_host: BTreeMap<String, (AltSvc, Vec<&RawEvent>)> = BTreeMap::new();
+    for ev in events {
+        let Some(alt) = advertised(ev) else { continue };
+        let Some(host) = url::Url::parse(&ev.final_url).ok().and_then(|u| u.host_str().map(|h| h.to_string())) else { continue };
+        by_host.entry(host).or_insert_with(|| (alt, Vec::new())).1.push(ev);
+    }
+    let probed = !by_host.is_empty() && curl_supports_http3();
+    let mut report = Http3Report { probed, ..Default::default() };
+    for (host, (alt, mut endpoints)) in by_host {
+        let mut entry = H3Host { host, alt_svc: alt.clone(), answered: 0, failed: 0 };
+        if probed {
+            endpoints.sort_by_key(|e| std::cmp::Reverse(e.score));
+            endpoints.truncate(MAX_PER_HOST);
+            let results: Vec<_> = stream::iter(endpoints)
+                .map(|ev| {
+                    let alt = alt.clone();
+                    async move { (ev, fetch(&ev.final_url, &alt, timeout).await) }
+                })
+                .buffer_unordered(concurrency.clamp(1, 8))
+                .collect()
+                .await;
+            for (ev, result) in results {
+                match result {
+                    Ok(h3) => {
+                        entry.answered += 1;
+                        report.findings.extend(compare(ev, &h3));
+                    }
+                    Err(e) => {
+                        tracing::debug!("http3: {}: {}", ev.final_url, e);
+                        entry.failed += 1;
+                    }
+                }
+            }
+        }
+        report.hosts.push(entry);
+    }
+    report.findings.sort_by(|a, b| a.url.cmp(&b.url));
+    report
+}
@@ -3,49 +3,96 @@ use reqwest::Client;
 use serde_json::json;
 use url::Url;
 
-use crate::output::writer_jsonl::RawEvent;
-use crate::probe::throttle::Throttle;
+use crate::output::annotation::Annotation;
+use crate::output::writer_jsonl::{collect_headers, RawEvent};
+use crate::probe::body::{BodyCapture, StoredBody};
+use crate::probe::failure::{self, FailureKind, ProbeError};
+use crate::probe::soft404::{self, Baselines};
+use crate::probe::sse;
+use crate::probe::throttle::{self, Throttle};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 fn extract_host(url: &str) -> Option<String> {
     Url::parse(url).ok().and_then(|u| u.host_str().map(|s| s.to_string()))
 }
 
-/// Probe URL with optional throttle. If `throttle` is Some, an acquire is awaited before performing requests.
-pub async fn probe_url(client: &Client, url: &str, timeout_secs: u64, throttle: Option<&Throttle>, retries: usize, backoff_initial_ms: u64, backoff_max_ms: u64, aggressive: bool) -> anyhow::Result<RawEvent> {
-    // apply throttle if present
-    if let Some(t) = throttle {
-        if let Some(host) = extract_host(url) {
-            let _p = t.acquire(&host).await; // permit held until dropped
-            // After acquiring permit, perform probe with retries/backoff
-            return probe_with_retries(client, url, timeout_secs, Some(t), &host, retries, backoff_initial_ms, backoff_max_ms, aggressive).await;
-        }
+/// Probe settings shared by every candidate of a scan
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    pub timeout_secs: u64,
+    /// Attempts for transient failures (clamped to 1..=10)
+    pub retries: usize,
+    pub backoff_initial_ms: u64,
+    pub backoff_max_ms: u64,
+    /// Don't slow hosts down on 429/5xx
+    pub aggressive: bool,
+    /// Keep a truncated copy of each body (`--store-bodies`)
+    pub store_body: Option<BodyCapture>,
+    /// Per-host not-found responses; matching responses get `Annotation::Soft404`
+    pub soft404: Option<Arc<Baselines>>,
+    /// Send GET and OPTIONS besides HEAD (and an empty JSON POST when `aggressive`) and record the
+    /// status per method; off: HEAD, GET only when HEAD isn't enough
+    pub method_matrix: bool,
+}
+
+impl ProbeOptions {
+    pub fn new(timeout_secs: u64, retries: usize) -> Self {
+        Self { timeout_secs, retries, backoff_initial_ms: 200, backoff_max_ms: 5000, aggressive: false, store_body: None, soft404: None, method_matrix: true }
     }
-    probe_with_retries(client, url, timeout_secs, None, "", retries, backoff_initial_ms, backoff_max_ms, aggressive).await
 }
 
-async fn probe_with_retries(client: &Client, url: &str, timeout_secs: u64, throttle: Option<&Throttle>, host: &str, retries: usize, backoff_initial_ms: u64, backoff_max_ms: u64, aggressive: bool) -> anyhow::Result<RawEvent> {
-    let max_retries = std::cmp::min(std::cmp::max(1usize, retries), 10usize);
-    let mut backoff = backoff_initial_ms.max(1);
+/// Probe URL with optional throttle. If `throttle` is Some, a host slot is acquired for every attempt and
+/// the response status is fed back to it.
+pub async fn probe_url(client: &Client, url: &str, throttle: Option<&Throttle>, opts: &ProbeOptions) -> anyhow::Result<RawEvent> {
+    let host = extract_host(url);
+    let throttle = throttle.zip(host.as_deref());
+    probe_with_retries(client, url, throttle, opts).await
+}
+
+async fn probe_with_retries(client: &Client, url: &str, throttle: Option<(&Throttle, &str)>, opts: &ProbeOptions) -> anyhow::Result<RawEvent> {
+    let max_retries = opts.retries.clamp(1, 10);
+    let mut backoff = opts.backoff_initial_ms.max(1);
+    let start = Instant::now();
     for attempt in 1..=max_retries {
-        let res = probe_url_inner(client, url, timeout_secs).await;
+        let permit = match throttle {
+            Some((t, host)) => Some(t.acquire(host).await),
+            None => None,
+        };
+        let res = probe_url_inner(client, url, opts).await;
+        drop(permit);
         match res {
-            Ok(ev) => {
-                // If WAF-like responses (detected by notes containing waf or status 429/5xx repeated), cool down host
-                if (ev.status == 429 || (ev.status >= 500 && ev.status < 600)) && !aggressive {
-                    if let Some(t) = throttle {
-                        if !host.is_empty() {
-                            t.cool_down_host(host, 1, 30); // reduce to 1 for 30s
-                        }
+            Ok(mut ev) => {
+                ev.attempts = attempt as u32;
+                let rate_limited = ev.status == 429 || ev.status == 503;
+                let retry_after = ev.header("retry-after").and_then(throttle::parse_retry_after);
+                if let Some((t, host)) = throttle {
+                    if !opts.aggressive {
+                        t.record(host, ev.status, retry_after);
                     }
+                    // Blocks aren't rate limits: a WAF refusing most requests slows the host down in any mode
+                    t.record_block(host, ev.annotations.contains(&Annotation::WafBlock));
+                }
+                if !rate_limited || opts.aggressive || attempt >= max_retries {
+                    return Ok(ev);
+                }
+                // Throttled: the host slot waits out Retry-After; without a throttle wait here
+                if throttle.is_none() {
+                    let wait = retry_after.unwrap_or(std::time::Duration::from_millis(backoff)).min(std::time::Duration::from_millis(opts.backoff_max_ms));
+                    tokio::time::sleep(wait).await;
                 }
-                return Ok(ev);
+                backoff = backoff.saturating_mul(2);
+                continue;
             }
-            Err(e) => {
-                if attempt >= max_retries {
-                    return Err(e);
+            Err(mut e) => {
+                // DNS, refused and TLS failures won't change on retry
+                if attempt >= max_retries || !e.kind.is_transient() {
+                    e.attempts = attempt as u32;
+                    e.elapsed_ms = start.elapsed().as_millis() as u64;
+                    return Err(e.into());
                 }
                 // exponential backoff with cap
-                let wait_ms = std::cmp::min(backoff, backoff_max_ms);
+                let wait_ms = std::cmp::min(backoff, opts.backoff_max_ms);
                 tokio::time::sleep(std::time::Duration::from_millis(wait_ms)).await;
                 backoff = backoff.saturating_mul(2);
                 continue;
@@ -55,56 +102,99 @@ async fn probe_with_retries(client: &Client, url: &str, timeout_secs: u64, throt
     Err(anyhow::anyhow!("probe failed after {} attempts", max_retries))
 }
 
-async fn probe_url_inner(client: &Client, url: &str, timeout_secs: u64) -> anyhow::Result<RawEvent> {
+async fn probe_url_inner(client: &Client, url: &str, opts: &ProbeOptions) -> Result<RawEvent, ProbeError> {
     let start = Instant::now();
+    let timeout_secs = opts.timeout_secs;
+    let store_body = opts.store_body;
+    let baseline = opts.soft404.as_ref().and_then(|b| b.for_url(url));
+    let cache = super::response_cache::shared();
 
     // Try HEAD first
-    let head_resp = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), client.head(url).send()).await;
+    let head_resp = cache.fetch_timeout(client, client.head(url), 0, std::time::Duration::from_secs(timeout_secs)).await;
 
     let mut status = 0u16;
     let mut content_type: Option<String> = None;
     let mut server: Option<String> = None;
     let mut content_length: Option<u64> = None;
+    let mut headers = BTreeMap::new();
     let mut body_sample = None;
     let mut is_graphql = false;
+    let mut body = None;
+    let mut soft_404 = None;
+    let mut body_simhash = None;
+    let mut methods = BTreeMap::new();
+    let mut http_version = None;
+    let mut block_page = false;
+    let mut last_error: Option<(FailureKind, String)> = None;
 
     match head_resp {
         Ok(Ok(r)) => {
-            status = r.status().as_u16();
-            content_type = r.headers().get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
-            server = r.headers().get(reqwest::header::SERVER).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
-            content_length = r.headers().get(reqwest::header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|s| s.parse().ok());
-            // If HEAD indicates no body but status suggests body may exist, do GET
-            if status == 405 || status == 501 || content_type.is_none() {
-                // fallback to GET below
-            }
+            status = r.status;
+            methods.insert("HEAD".to_string(), status);
+            content_type = r.headers.get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+            server = r.headers.get(reqwest::header::SERVER).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+            content_length = r.headers.get(reqwest::header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|s| s.parse().ok());
+            headers = collect_headers(&r.headers);
+            http_version = r.version;
+        }
+        Ok(Err(e)) => {
+            // HEAD failed; we'll try GET
+            last_error = Some((failure::classify(&e), e.to_string()));
         }
-        _ => {
-            // HEAD failed or timed out; we'll try GET
+        Err(_) => {
+            last_error = Some((FailureKind::Timeout, format!("HEAD timed out after {}s", timeout_secs)));
         }
     }
 
-    // If HEAD didn't give us enough, do a partial GET
-    if content_type.is_none() || status == 405 || status == 501 || status == 0 {
-        let get_resp = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs),
-            client.get(url).header(reqwest::header::RANGE, "bytes=0-8191").send()).await;
+    // Partial GET for the method matrix, or if HEAD didn't give us enough (or bodies are stored, or the
+    // status is the host's not-found status and the body decides)
+    let like_not_found = baseline.is_some_and(|b| !b.is_strict() && b.status == status);
+    let event_stream = content_type.as_deref().is_some_and(sse::is_event_stream);
+    if opts.method_matrix || content_type.is_none() || status == 405 || status == 501 || status == 0 || store_body.is_some() || like_not_found || event_stream {
+        let mut limit = store_body.map_or(soft404::SAMPLE_LIMIT, |c| c.max_bytes.max(soft404::SAMPLE_LIMIT));
+        if event_stream {
+            limit = limit.max(sse::SAMPLE_BYTES);
+        }
+        // Servers may ignore Range, so the cache stops reading at the limit
+        let get_resp = cache.fetch_timeout(client, client.get(url).header(reqwest::header::RANGE, format!("bytes=0-{}", limit - 1)), limit,
+            std::time::Duration::from_secs(timeout_secs)).await;
 
+        match &get_resp {
+            Ok(Err(e)) if status == 0 => last_error = Some((failure::classify(e), e.to_string())),
+            Err(_) if status == 0 => last_error = Some((FailureKind::Timeout, format!("GET timed out after {}s", timeout_secs))),
+            _ => {}
+        }
         if let Ok(Ok(r)) = get_resp {
-            status = r.status().as_u16();
-            content_type = r.headers().get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
-            server = r.headers().get(reqwest::header::SERVER).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
-            content_length = r.headers().get(reqwest::header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|s| s.parse().ok());
-            if let Ok(bytes) = r.bytes().await {
-                let slice = &bytes[..std::cmp::min(4096, bytes.len())];
-                if let Ok(text) = std::str::from_utf8(slice) {
-                    // Try parse JSON sample
-                    if let Ok(j) = serde_json::from_str::<serde_json::Value>(text) {
-                        is_graphql = j.get("data").is_some() || j.get("errors").is_some();
-                        body_sample = Some(j);
-                    } else {
-                        // not JSON; keep small textual sample
-                        body_sample = Some(json!({"_sample": &text[0..std::cmp::min(200, text.len())]}));
-                    }
+            status = r.status;
+            methods.insert("GET".to_string(), status);
+            content_type = r.headers.get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+            server = r.headers.get(reqwest::header::SERVER).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+            content_length = r.headers.get(reqwest::header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|s| s.parse().ok());
+            headers = collect_headers(&r.headers);
+            http_version = r.version.or(http_version);
+            let (bytes, truncated) = (r.body, r.truncated);
+            body_simhash = crate::enrich::simhash::simhash(&bytes[..bytes.len().min(soft404::SAMPLE_LIMIT)]).map(crate::enrich::simhash::to_hex);
+            if let Some(b) = baseline {
+                let resp = soft404::Response { status, content_type: content_type.as_deref(), content_length, body: Some((&bytes, truncated)) };
+                soft_404 = Some(b.matches(url, &resp));
+            }
+            if content_type.as_deref().is_some_and(sse::is_event_stream) {
+                // Event stream samples are kept whole, with or without --store-bodies, for the event analysis
+                body = StoredBody::capture(&bytes, truncated, store_body.is_some_and(|c| c.compress));
+            } else if let Some(capture) = store_body {
+                let cut = bytes.len().min(capture.max_bytes);
+                body = StoredBody::capture(&bytes[..cut], truncated || cut < bytes.len(), capture.compress);
+            }
+            let slice = &bytes[..std::cmp::min(4096, bytes.len())];
+            if let Ok(text) = std::str::from_utf8(slice) {
+                block_page = crate::waf::WafDetector::is_blocked_response(status, text);
+                // Try parse JSON sample
+                if let Ok(j) = serde_json::from_str::<serde_json::Value>(text) {
+                    is_graphql = j.get("data").is_some() || j.get("errors").is_some();
+                    body_sample = Some(j);
+                } else {
+                    // not JSON; keep small textual sample
+                    body_sample = Some(json!({"_sample": &text[0..std::cmp::min(200, text.len())]}));
                 }
             }
         }
@@ -112,18 +202,48 @@ async fn probe_url_inner(client: &Client, url: &str, timeout_secs: u64) -> anyho
 
     let elapsed = start.elapsed().as_millis() as u64;
 
+    // No response at all: report why instead of a status-0 event
+    if status == 0 {
+        let (kind, message) = last_error.unwrap_or((FailureKind::Other, "no response".to_string()));
+        return Err(ProbeError { kind, message, attempts: 1, elapsed_ms: elapsed });
+    }
+
+    let mut allowed_methods = Vec::new();
+    if opts.method_matrix {
+        allowed_methods = probe_extra_methods(client, url, timeout_secs, opts.aggressive, &mut methods).await;
+    }
+
     let orig = url.to_string();
     let final_url = url.to_string();
 
     // WAF detection rudimentary: check server header for cloudflare
-    let mut notes = Vec::new();
+    let mut annotations = Vec::new();
     if let Some(ref s) = server {
         if s.to_lowercase().contains("cloudflare") {
-            notes.push("waf:cloudflare".to_string());
+            annotations.push(Annotation::WafDetected { waf: "Cloudflare".to_string() });
         }
     }
+    if block_page {
+        annotations.push(Annotation::WafBlock);
+    }
+    if status == 429 {
+        let retry_after = headers.get("retry-after").and_then(|v| throttle::parse_retry_after(v)).map(|d| d.as_secs());
+        annotations.push(Annotation::RateLimited { retry_after });
+    }
+    // Without a body only status, type and length can be compared
+    let soft_404 = soft_404.unwrap_or_else(|| {
+        let resp = soft404::Response { status, content_type: content_type.as_deref(), content_length, body: None };
+        baseline.is_some_and(|b| b.matches(url, &resp))
+    });
+    if soft_404 {
+        annotations.push(Annotation::Soft404);
+    }
+    for method in non_get_routes(&methods, &allowed_methods) {
+        annotations.push(Annotation::AcceptsMethod { method });
+    }
 
     Ok(RawEvent {
+        schema_version: crate::output::schema::SCHEMA_VERSION,
         orig_url: orig,
         final_url,
         status,
@@ -135,6 +255,73 @@ async fn probe_url_inner(client: &Client, url: &str, timeout_secs: u64) -> anyho
         is_graphql,
         json_sample: body_sample,
         score: 0,
-        notes,
+        annotations,
+        attempts: 1,
+        headers,
+        body,
+        body_simhash,
+        methods,
+        allowed_methods,
+        http_version,
     })
 }
+
+/// OPTIONS, and with `post` an empty JSON POST; statuses go into `methods`. Returns the methods the
+/// server advertises (`Allow`, `Access-Control-Allow-Methods`).
+async fn probe_extra_methods(client: &Client, url: &str, timeout_secs: u64, post: bool, methods: &mut BTreeMap<String, u16>) -> Vec<String> {
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    let mut allowed = Vec::new();
+    crate::http_client::pace().await;
+    if let Ok(Ok(r)) = tokio::time::timeout(timeout, client.request(reqwest::Method::OPTIONS, url).send()).await {
+        methods.insert("OPTIONS".to_string(), r.status().as_u16());
+        for name in ["allow", "access-control-allow-methods"] {
+            for value in r.headers().get_all(name).iter().filter_map(|v| v.to_str().ok()) {
+                allowed.extend(value.split(',').map(|m| m.trim().to_ascii_uppercase()).filter(|m| !m.is_empty() && m != "*"));
+            }
+        }
+    }
+    if post {
+        let req = client.post(url).header(reqwest::header::CONTENT_TYPE, "application/json").body("{}");
+        crate::http_client::pace().await;
+        if let Ok(Ok(r)) = tokio::time::timeout(timeout, req.send()).await {
+            methods.insert("POST".to_string(), r.status().as_u16());
+        }
+    }
+    allowed.sort();
+    allowed.dedup();
+    allowed
+}
+
+/// Methods the route answers to while GET doesn't (GET 404/405): a live POST, or the `Allow` list of a 405
+pub fn non_get_routes(methods: &BTreeMap<String, u16>, allowed: &[String]) -> Vec<String> {
+    let live = |s: u16| s != 0 && !matches!(s, 404 | 405 | 501);
+    let get = methods.get("GET").or(methods.get("HEAD")).copied().unwrap_or(0);
+    if !matches!(get, 404 | 405) {
+        return Vec::new();
+    }
+    let mut out: Vec<String> = methods.iter()
+        .filter(|(m, s)| !matches!(m.as_str(), "GET" | "HEAD" | "OPTIONS") && live(**s))
+        .map(|(m, _)| m.clone())
+        .collect();
+    if get == 405 {
+        out.extend(allowed.iter().filter(|m| !matches!(m.as_str(), "GET" | "HEAD" | "OPTIONS")).cloned());
+    }
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Read at most `limit` bytes of a body; the flag tells whether more was left
+pub(crate) async fn read_limited(mut resp: reqwest::Response, limit: usize) -> reqwest::Result<(Vec<u8>, bool)> {
+    let mut out = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        let room = limit - out.len();
+        if chunk.len() >= room {
+            out.extend_from_slice(&chunk[..room]);
+            let more = chunk.len() > room || resp.chunk().await.ok().flatten().is_some();
+            return Ok((out, more));
+        }
+        out.extend_from_slice(&chunk);
+    }
+    Ok((out, false))
+}
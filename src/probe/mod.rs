@@ -1,6 +1,19 @@
 pub mod advanced_tests;
 pub mod graphql;
+pub mod grpc;
+pub mod body;
+pub mod cache_poisoning;
+pub mod failure;
+pub mod http3;
 pub mod http_probe;
+pub mod prefilter;
+pub mod rate_limit_bypass;
+pub mod preflight;
+pub mod response_cache;
+pub mod smuggling;
+pub mod soft404;
+pub mod sse;
 pub mod throttle;
+pub mod tls;
 pub mod websocket;
 
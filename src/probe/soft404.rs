@@ -0,0 +1,174 @@
+//! Soft-404 baselines
+//! SPAs and catch-all routes answer every path with `200 OK` and the same page, so each guessed
+//! candidate looked like a live endpoint. Before probing, one random non-existent path is requested per
+//! host; a candidate whose response matches that baseline (status, content type and body hash, or body
+//! size for bodies read completely) is annotated as a soft-404.
+
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use url::Url;
+
+/// Bytes of each body that are hashed; the probe reads at least this much
+pub const SAMPLE_LIMIT: usize = 8192;
+
+/// Size difference (in % of the baseline) still taken as the same page
+const SIZE_SLACK_PERCENT: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// `scheme://host[:port]`
+    pub origin: String,
+    /// The random path that was requested
+    pub probe_url: String,
+    pub status: u16,
+    /// MIME type without parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<u64>,
+    /// Bytes read (at most `SAMPLE_LIMIT`)
+    pub size: usize,
+    /// The body was longer than `SAMPLE_LIMIT`
+    pub truncated: bool,
+    /// SHA-256 of the first `SAMPLE_LIMIT` bytes, with the requested path removed
+    pub body_hash: String,
+}
+
+/// A candidate's response, as far as the probe read it
+#[derive(Debug, Clone, Copy)]
+pub struct Response<'a> {
+    pub status: u16,
+    pub content_type: Option<&'a str>,
+    pub content_length: Option<u64>,
+    /// Body prefix and whether more followed; `None` if only HEAD was sent
+    pub body: Option<(&'a [u8], bool)>,
+}
+
+impl Baseline {
+    /// The host answers unknown paths with a real 404/410, so no response can be a soft-404
+    pub fn is_strict(&self) -> bool {
+        matches!(self.status, 404 | 410)
+    }
+
+    /// Whether `resp` (for `url`) is the host's not-found page
+    pub fn matches(&self, url: &str, resp: &Response) -> bool {
+        if self.is_strict() || resp.status != self.status || mime(resp.content_type) != self.content_type {
+            return false;
+        }
+        match resp.body {
+            Some((bytes, truncated)) => {
+                let sample = &bytes[..bytes.len().min(SAMPLE_LIMIT)];
+                if body_hash(sample, &path_of(url)) == self.body_hash {
+                    return true;
+                }
+                // Pages that embed a nonce or timestamp; only comparable when both were read completely
+                let truncated = truncated || bytes.len() > SAMPLE_LIMIT;
+                !truncated && !self.truncated && sample.len().abs_diff(self.size) * 100 <= self.size * SIZE_SLACK_PERCENT
+            }
+            None => resp.content_length.is_some() && resp.content_length == self.content_length,
+        }
+    }
+}
+
+/// Baselines of all probed hosts, by origin
+#[derive(Debug, Clone, Default)]
+pub struct Baselines(HashMap<String, Baseline>);
+
+impl Baselines {
+    pub fn insert(&mut self, baseline: Baseline) {
+        self.0.insert(baseline.origin.clone(), baseline);
+    }
+
+    /// Baseline of the host serving `url`
+    pub fn for_url(&self, url: &str) -> Option<&Baseline> {
+        self.0.get(&origin_of(url)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Hosts that answer unknown paths with something other than 404/410
+    pub fn catch_all(&self) -> impl Iterator<Item = &Baseline> {
+        self.0.values().filter(|b| !b.is_strict())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Baseline> {
+        self.0.values()
+    }
+}
+
+/// SHA-256 of a body with the requested path (raw and percent-encoded) removed, since not-found pages
+/// often echo it
+pub fn body_hash(body: &[u8], path: &str) -> String {
+    let mut text = String::from_utf8_lossy(body).into_owned();
+    if path.len() > 1 {
+        text = text.replace(path, "");
+        let encoded = urlencoding::encode(path).into_owned();
+        text = text.replace(&encoded, "");
+        if let Some(last) = path.rsplit('/').find(|s| !s.is_empty()) {
+            text = text.replace(last, "");
+        }
+    }
+    Sha256::digest(text.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn mime(content_type: Option<&str>) -> Option<String> {
+    content_type.map(|ct| ct.split(';').next().unwrap_or(ct).trim().to_ascii_lowercase())
+}
+
+fn path_of(url: &str) -> String {
+    Url::parse(url).map(|u| u.path().to_string()).unwrap_or_default()
+}
+
+pub fn origin_of(url: &str) -> Option<String> {
+    let u = Url::parse(url).ok()?;
+    u.host_str()?;
+    Some(u.origin().ascii_serialization())
+}
+
+/// Request a random non-existent path on `origin` and record the response
+pub async fn learn(client: &Client, origin: &str, timeout: Duration) -> Option<Baseline> {
+    let probe_url = format!("{}/{:016x}{:08x}", origin.trim_end_matches('/'), rand::random::<u64>(), rand::random::<u32>());
+    crate::http_client::pace().await;
+    let resp = tokio::time::timeout(timeout, client.get(&probe_url).send()).await.ok()?.ok()?;
+    let status = resp.status().as_u16();
+    let content_type = resp.headers().get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let content_length = resp.headers().get(reqwest::header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|s| s.parse().ok());
+    let (bytes, truncated) = tokio::time::timeout(timeout, super::http_probe::read_limited(resp, SAMPLE_LIMIT)).await.ok()?.ok()?;
+    Some(Baseline {
+        origin: origin.to_string(),
+        body_hash: body_hash(&bytes, &path_of(&probe_url)),
+        probe_url,
+        status,
+        content_type: mime(content_type.as_deref()),
+        content_length,
+        size: bytes.len(),
+        truncated,
+    })
+}
+
+/// Learn the baseline of every origin among `urls`; hosts that don't answer get none
+pub async fn learn_all(client: &Client, urls: &[String], concurrency: usize, timeout: Duration) -> Baselines {
+    let mut origins: Vec<String> = urls.iter().filter_map(|u| origin_of(u)).collect();
+    origins.sort();
+    origins.dedup();
+    let learned: Vec<Option<Baseline>> = stream::iter(origins.iter())
+        .map(|o| learn(client, o, timeout))
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    let mut baselines = Baselines::default();
+    for b in learned.into_iter().flatten() {
+        baselines.insert(b);
+    }
+    baselines
+}
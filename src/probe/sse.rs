@@ -0,0 +1,157 @@
+//! Server-Sent Events capture
+//! A `text/event-stream` response never ends, so the probe would sit on it until the timeout and lose
+//! the response. Streams are read for a bounded sample instead - a few seconds, `SAMPLE_EVENTS` events or
+//! `SAMPLE_BYTES` bytes, whichever comes first - and the connection is closed. The sampled events go
+//! through the secret extractors and the PII scanner: streams often push whole objects (notifications,
+//! orders, user records) that the endpoint's own GET never shows.
+
+use crate::enrich::pii::{self, PiiMatch};
+use crate::gather::js_deep_analyzer::{JsDeepAnalyzer, Secret, SecretRules};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Events read before the stream is closed
+pub const SAMPLE_EVENTS: usize = 20;
+
+/// Bytes read before the stream is closed
+pub const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Longest time a stream is read
+pub const SAMPLE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Event payloads kept in the capture, each cut to `PREVIEW_CHARS`
+const PREVIEW_EVENTS: usize = 3;
+const PREVIEW_CHARS: usize = 200;
+
+pub fn is_event_stream(content_type: &str) -> bool {
+    content_type.trim_start().to_ascii_lowercase().starts_with("text/event-stream")
+}
+
+/// One dispatched event
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SseEvent {
+    /// `event:` type; `message` when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub data: String,
+}
+
+/// Complete events in a stream sample (WHATWG event stream format). A trailing event without its blank
+/// line is still incomplete and left out.
+pub fn parse_events(text: &str) -> Vec<SseEvent> {
+    let mut events = Vec::new();
+    let mut current = SseEvent::default();
+    let mut data: Option<String> = None;
+    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines = normalized.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        // The last piece has no newline after it yet
+        if lines.peek().is_none() {
+            break;
+        }
+        if line.is_empty() {
+            if let Some(d) = data.take() {
+                current.data = d;
+                events.push(std::mem::take(&mut current));
+            }
+            current = SseEvent::default();
+            continue;
+        }
+        if line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => match &mut data {
+                Some(d) => {
+                    d.push('\n');
+                    d.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            },
+            "event" => current.event = Some(value.to_string()),
+            "id" => current.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Read a stream for at most `window`, `limit` bytes or `SAMPLE_EVENTS` events; the flag tells whether
+/// the stream was still open. Dropping the response closes the connection.
+pub async fn read_sample(mut resp: reqwest::Response, limit: usize, window: Duration) -> reqwest::Result<(Vec<u8>, bool)> {
+    let deadline = tokio::time::Instant::now() + window;
+    let mut out = Vec::new();
+    loop {
+        let Ok(chunk) = tokio::time::timeout_at(deadline, resp.chunk()).await else {
+            return Ok((out, true));
+        };
+        let Some(chunk) = chunk? else {
+            return Ok((out, false));
+        };
+        let room = limit - out.len();
+        if chunk.len() >= room {
+            out.extend_from_slice(&chunk[..room]);
+            return Ok((out, true));
+        }
+        out.extend_from_slice(&chunk);
+        if parse_events(&String::from_utf8_lossy(&out)).len() >= SAMPLE_EVENTS {
+            return Ok((out, true));
+        }
+    }
+}
+
+/// What a sampled stream carried
+#[derive(Debug, Clone, Serialize)]
+pub struct SseCapture {
+    pub url: String,
+    pub events: usize,
+    /// Distinct `event:` types
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event_id: Option<String>,
+    /// First payloads, shortened
+    pub preview: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<Secret>,
+    /// PII in JSON payloads; paths start with the event index, e.g. `events[2].user.email`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pii: Vec<PiiMatch>,
+}
+
+/// Parse a sample and run the extractors on the payloads; `None` if no complete event arrived
+pub fn analyze(url: &str, sample: &str, rules: &SecretRules) -> Option<SseCapture> {
+    let events = parse_events(sample);
+    if events.is_empty() {
+        return None;
+    }
+    let mut event_types: Vec<String> = Vec::new();
+    for t in events.iter().filter_map(|e| e.event.clone()) {
+        if !event_types.contains(&t) {
+            event_types.push(t);
+        }
+    }
+    let payloads: Vec<&str> = events.iter().map(|e| e.data.as_str()).collect();
+    let secrets = JsDeepAnalyzer::extract_secrets_with_rules(&payloads.join("\n"), url, rules);
+    let pii = events.iter().enumerate()
+        .filter_map(|(i, e)| serde_json::from_str::<serde_json::Value>(&e.data).ok().map(|v| (i, v)))
+        .flat_map(|(i, v)| pii::scan_json(&v).into_iter().map(move |mut m| {
+            let dot = if m.path.is_empty() || m.path.starts_with('[') { "" } else { "." };
+            m.path = format!("events[{}]{}{}", i, dot, m.path);
+            m
+        }))
+        .collect();
+    Some(SseCapture {
+        url: url.to_string(),
+        events: events.len(),
+        event_types,
+        last_event_id: events.iter().rev().find_map(|e| e.id.clone()),
+        preview: payloads.iter().take(PREVIEW_EVENTS).map(|d| d.chars().take(PREVIEW_CHARS).collect()).collect(),
+        secrets,
+        pii,
+    })
+}
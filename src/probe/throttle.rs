@@ -1,18 +1,93 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 use dashmap::DashMap;
-use tokio::sync::{OwnedSemaphorePermit, Semaphore};
-use std::time::Duration;
-use tokio::time::sleep;
+use parking_lot::Mutex;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use std::time::{Duration, Instant, SystemTime};
 
-/// A permit that holds both global and per-host semaphore permits.
+/// Longest `Retry-After` honored; servers sometimes ask for hours
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Pause after a 429/503 without `Retry-After`
+const DEFAULT_PAUSE: Duration = Duration::from_secs(1);
+
+/// Recent responses per host the WAF block rate is computed over
+pub const BLOCK_WINDOW: usize = 20;
+/// Responses needed before the rate counts
+const BLOCK_MIN_SAMPLES: usize = 10;
+/// Share of blocked responses that lowers the host's timing by one step
+pub const BLOCK_THRESHOLD: f32 = 0.3;
+/// Spacing between requests to a host per slow-down step, like a lower timing template
+const SLOWDOWN_DELAYS: [Duration; 3] = [Duration::from_millis(250), Duration::from_secs(1), Duration::from_secs(3)];
+
+/// A permit that holds both global and per-host slots.
 pub struct ThrottlePermit {
     _global: OwnedSemaphorePermit,
-    _host: OwnedSemaphorePermit,
+    _host: HostSlot,
+}
+
+/// Per-host concurrency under AIMD: halved on every 429/503, raised by one after a full window of
+/// unthrottled responses (one per allowed slot), up to the configured per-host limit. A WAF blocking
+/// too many of the recent responses lowers that ceiling and spaces the requests out for good.
+struct HostLimiter {
+    state: Mutex<HostState>,
+    changed: Notify,
+}
+
+struct HostState {
+    limit: usize,
+    in_flight: usize,
+    /// Successes since the last increase
+    window: usize,
+    /// No new requests before this (`Retry-After`)
+    paused_until: Option<Instant>,
+    throttled: u32,
+    /// Highest concurrency AIMD goes back up to
+    ceiling: usize,
+    /// Blocked or not, for the last `BLOCK_WINDOW` responses
+    recent: VecDeque<bool>,
+    /// Slow-down steps taken (index into `SLOWDOWN_DELAYS` + 1)
+    level: usize,
+    /// Earliest start of the next request while slowed down
+    next_start: Option<Instant>,
+}
+
+struct HostSlot(Arc<HostLimiter>);
+
+impl Drop for HostSlot {
+    fn drop(&mut self) {
+        self.0.state.lock().in_flight -= 1;
+        self.0.changed.notify_waiters();
+    }
+}
+
+/// Rate limiting seen on one host
+#[derive(Debug, Clone)]
+pub struct HostThrottling {
+    pub host: String,
+    /// 429/503 responses
+    pub throttled: u32,
+    /// Concurrency at the end of the scan
+    pub limit: usize,
+}
+
+/// A host slowed down because of WAF blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slowdown {
+    pub host: String,
+    /// Steps taken so far (1..=3)
+    pub level: usize,
+    /// Blocked share of the window that triggered the last step
+    pub block_rate: f32,
+    /// New per-host concurrency ceiling
+    pub per_host: usize,
+    /// Minimum spacing between requests
+    pub delay: Duration,
 }
 
 pub struct Throttle {
     global: Arc<Semaphore>,
-    per_host: DashMap<String, Arc<Semaphore>>,
+    per_host: DashMap<String, Arc<HostLimiter>>,
     default_per_host: usize,
 }
 
@@ -21,45 +96,158 @@ impl Throttle {
         Self {
             global: Arc::new(Semaphore::new(global_limit)),
             per_host: DashMap::new(),
-            default_per_host,
+            default_per_host: default_per_host.max(1),
         }
     }
 
     #[allow(dead_code)]
     pub fn set_host_limit(&self, host: &str, limit: usize) {
-        self.per_host.insert(host.to_string(), Arc::new(Semaphore::new(limit)));
+        let limiter = self.limiter(host);
+        limiter.state.lock().limit = limit.max(1);
+        limiter.changed.notify_waiters();
     }
 
-    /// Reduce host limit for a duration (cooldown_secs). After cooldown, restore to default_per_host.
-    pub fn cool_down_host(&self, host: &str, new_limit: usize, cooldown_secs: u64) {
-        let host_name = host.to_string();
-        let per_host_map = self.per_host.clone();
-        let default = self.default_per_host;
-        // Replace immediately
-        per_host_map.insert(host_name.clone(), Arc::new(Semaphore::new(new_limit)));
-
-        // Spawn a task to restore after cooldown
-        tokio::spawn(async move {
-            sleep(Duration::from_secs(cooldown_secs)).await;
-            per_host_map.insert(host_name.clone(), Arc::new(Semaphore::new(default)));
-        });
+    /// Current concurrency allowed for a host
+    pub fn host_limit(&self, host: &str) -> usize {
+        self.per_host.get(host).map_or(self.default_per_host, |l| l.state.lock().limit)
     }
 
-    async fn get_host_semaphore(&self, host: &str) -> Arc<Semaphore> {
-        if let Some(s) = self.per_host.get(host) {
-            return s.value().clone();
+    /// Feed a response status back. 429/503 halve the host's concurrency and pause it for `Retry-After`
+    /// (capped at `MAX_RETRY_AFTER`); anything else counts towards the next increase.
+    pub fn record(&self, host: &str, status: u16, retry_after: Option<Duration>) {
+        let limiter = self.limiter(host);
+        let mut s = limiter.state.lock();
+        if status == 429 || status == 503 {
+            s.limit = (s.limit / 2).max(1);
+            s.window = 0;
+            s.throttled += 1;
+            let until = Instant::now() + retry_after.unwrap_or(DEFAULT_PAUSE).min(MAX_RETRY_AFTER);
+            s.paused_until = Some(s.paused_until.map_or(until, |p| p.max(until)));
+            tracing::debug!("{} throttled ({}), concurrency now {}", host, status, s.limit);
+        } else if s.limit < s.ceiling {
+            s.window += 1;
+            if s.window >= s.limit {
+                s.limit += 1;
+                s.window = 0;
+                drop(s);
+                limiter.changed.notify_waiters();
+            }
         }
-        let sem = Arc::new(Semaphore::new(self.default_per_host));
-        self.per_host.insert(host.to_string(), sem.clone());
-        sem
+    }
+
+    /// Feed back whether a response was a WAF block page. Once more than `BLOCK_THRESHOLD` of the last
+    /// `BLOCK_WINDOW` responses were blocked, the host drops one timing step: half the concurrency
+    /// ceiling and a longer pause between requests. Returns the new setting when that happens.
+    pub fn record_block(&self, host: &str, blocked: bool) -> Option<Slowdown> {
+        let limiter = self.limiter(host);
+        let mut s = limiter.state.lock();
+        s.recent.push_back(blocked);
+        if s.recent.len() > BLOCK_WINDOW {
+            s.recent.pop_front();
+        }
+        let block_rate = s.recent.iter().filter(|b| **b).count() as f32 / s.recent.len() as f32;
+        if s.recent.len() < BLOCK_MIN_SAMPLES || block_rate <= BLOCK_THRESHOLD || s.level >= SLOWDOWN_DELAYS.len() {
+            return None;
+        }
+        s.level += 1;
+        s.ceiling = (s.ceiling / 2).max(1);
+        s.limit = s.limit.min(s.ceiling);
+        s.window = 0;
+        // The next step needs a full window at the new speed
+        s.recent.clear();
+        let slowdown = Slowdown { host: host.to_string(), level: s.level, block_rate, per_host: s.ceiling, delay: SLOWDOWN_DELAYS[s.level - 1] };
+        tracing::warn!("{}: {:.0}% of recent responses blocked by a WAF, slowing down to {} concurrent with {}ms between requests",
+            host, block_rate * 100.0, slowdown.per_host, slowdown.delay.as_millis());
+        Some(slowdown)
+    }
+
+    /// Hosts slowed down because of WAF blocks, in their final setting
+    pub fn slowdowns(&self) -> Vec<Slowdown> {
+        let mut out: Vec<Slowdown> = self.per_host.iter()
+            .filter_map(|e| {
+                let s = e.value().state.lock();
+                (s.level > 0).then(|| Slowdown {
+                    host: e.key().clone(),
+                    level: s.level,
+                    block_rate: 0.0,
+                    per_host: s.ceiling,
+                    delay: SLOWDOWN_DELAYS[s.level - 1],
+                })
+            })
+            .collect();
+        out.sort_by(|a, b| b.level.cmp(&a.level).then(a.host.cmp(&b.host)));
+        out
+    }
+
+    /// Hosts that sent 429/503, most throttled first
+    pub fn throttled_hosts(&self) -> Vec<HostThrottling> {
+        let mut out: Vec<HostThrottling> = self.per_host.iter()
+            .filter_map(|e| {
+                let s = e.value().state.lock();
+                (s.throttled > 0).then(|| HostThrottling { host: e.key().clone(), throttled: s.throttled, limit: s.limit })
+            })
+            .collect();
+        out.sort_by(|a, b| b.throttled.cmp(&a.throttled).then(a.host.cmp(&b.host)));
+        out
+    }
+
+    fn limiter(&self, host: &str) -> Arc<HostLimiter> {
+        self.per_host.entry(host.to_string())
+            .or_insert_with(|| Arc::new(HostLimiter {
+                state: Mutex::new(HostState {
+                    limit: self.default_per_host,
+                    in_flight: 0,
+                    window: 0,
+                    paused_until: None,
+                    throttled: 0,
+                    ceiling: self.default_per_host,
+                    recent: VecDeque::new(),
+                    level: 0,
+                    next_start: None,
+                }),
+                changed: Notify::new(),
+            }))
+            .value()
+            .clone()
     }
 
     pub async fn acquire(&self, host: &str) -> ThrottlePermit {
-        let g = self.global.clone();
-        let host_sem = self.get_host_semaphore(host).await;
-        // Acquire global then host
-        let gperm = g.clone().acquire_owned().await.expect("global semaphore closed");
-        let hperm = host_sem.clone().acquire_owned().await.expect("host semaphore closed");
-        ThrottlePermit { _global: gperm, _host: hperm }
+        let limiter = self.limiter(host);
+        // Host slot first, so a paused host doesn't hold global slots
+        loop {
+            let notified = limiter.changed.notified();
+            let wait = {
+                let mut s = limiter.state.lock();
+                let now = Instant::now();
+                match s.paused_until.max(s.next_start) {
+                    Some(until) if until > now => Some(until),
+                    _ if s.in_flight < s.limit => {
+                        s.in_flight += 1;
+                        if s.level > 0 {
+                            s.next_start = Some(now + SLOWDOWN_DELAYS[s.level - 1]);
+                        }
+                        break;
+                    }
+                    _ => None,
+                }
+            };
+            match wait {
+                Some(until) => tokio::time::sleep_until(until.into()).await,
+                None => notified.await,
+            }
+        }
+        let slot = HostSlot(limiter);
+        let gperm = self.global.clone().acquire_owned().await.expect("global semaphore closed");
+        ThrottlePermit { _global: gperm, _host: slot }
+    }
+}
+
+/// Parse `Retry-After`: delay in seconds or an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
     }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
 }
@@ -1,21 +1,363 @@
-use openssl::ssl::{SslConnector, SslMethod};
-use std::net::ToSocketAddrs;
-
-pub fn get_tls_issuer(host: &str) -> Option<String> {
-    // Connect to host:443 and obtain certificate issuer CN
-    let addr = format!("{}:443", host);
-    let addrs = addr.to_socket_addrs().ok()?;
-    let sock = addrs.into_iter().next()?;
-
-    let connector = SslConnector::builder(SslMethod::tls()).ok()?.build();
-    if let Ok(stream) = std::net::TcpStream::connect(sock) {
-        if let Ok(mut ssl_stream) = connector.connect(host, stream) {
-            if let Ok(cert) = ssl_stream.ssl().peer_certificate() {
-                if let Some(issuer) = cert.issuer_name().entries().next() {
-                    return issuer.data().as_utf8().ok().map(|s| s.to_string());
+//! TLS certificate capture
+//! One handshake per HTTPS host records the leaf certificate (issuer, SANs, validity, signature
+//! algorithm) and the negotiated protocol. The certificate is not verified, so expired and self-signed
+//! ones are read too. A second, raw ClientHello capped at TLS 1.0/1.1 tells whether legacy protocols
+//! are still accepted; rustls itself only speaks 1.2 and 1.3.
+
+use crate::output::clean_reporter::Severity;
+use futures::stream::{self, StreamExt};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, ServerName};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_rustls::TlsConnector;
+use url::Url;
+use x509_parser::prelude::*;
+
+/// Certificates expiring within this many days are reported
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertInfo {
+    pub host: String,
+    pub port: u16,
+    /// Negotiated protocol, e.g. "TLS 1.3"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cipher: Option<String>,
+    pub subject: String,
+    pub issuer: String,
+    /// Issuer common name (or organization), as shown in the CSV
+    pub issuer_name: String,
+    /// DNS names and IP addresses from subjectAltName
+    pub sans: Vec<String>,
+    /// HTTP dates
+    pub not_before: String,
+    pub not_after: String,
+    /// Negative once expired
+    pub days_left: i64,
+    pub signature_algorithm: String,
+    pub self_signed: bool,
+    /// Legacy protocols the server still negotiates ("TLS 1.0", "TLS 1.1")
+    #[serde(default)]
+    pub legacy_protocols: Vec<String>,
+    #[serde(default)]
+    pub issues: Vec<TlsIssue>,
+}
+
+/// Weak TLS configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TlsIssue {
+    Expired { days: i64 },
+    ExpiresSoon { days: i64 },
+    NotYetValid,
+    WeakSignature { algorithm: String },
+    LegacyProtocol { version: String },
+    SelfSigned,
+    /// The certificate doesn't cover the host name
+    HostnameMismatch,
+}
+
+impl TlsIssue {
+    /// Report severity: high, medium or low
+    pub fn severity(&self) -> Severity {
+        match self {
+            TlsIssue::Expired { .. } | TlsIssue::WeakSignature { .. } => Severity::High,
+            TlsIssue::LegacyProtocol { .. } | TlsIssue::SelfSigned | TlsIssue::HostnameMismatch | TlsIssue::NotYetValid => Severity::Medium,
+            TlsIssue::ExpiresSoon { .. } => Severity::Low,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            TlsIssue::Expired { days } => format!("TLS certificate expired {} days ago", days),
+            TlsIssue::ExpiresSoon { days } => format!("TLS certificate expires in {} days", days),
+            TlsIssue::NotYetValid => "TLS certificate not yet valid".to_string(),
+            TlsIssue::WeakSignature { algorithm } => format!("TLS certificate signed with {}", algorithm),
+            TlsIssue::LegacyProtocol { version } => format!("{} accepted", version),
+            TlsIssue::SelfSigned => "Self-signed TLS certificate".to_string(),
+            TlsIssue::HostnameMismatch => "TLS certificate does not match host".to_string(),
+        }
+    }
+}
+
+impl CertInfo {
+    /// Parse a DER leaf certificate of `host` and classify it as of `now`
+    pub fn from_der(host: &str, port: u16, der: &[u8], now: SystemTime) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(der).ok()?;
+        let validity = cert.validity();
+        let now_secs = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+        let not_before = validity.not_before.timestamp();
+        let not_after = validity.not_after.timestamp();
+
+        let mut sans = Vec::new();
+        if let Ok(Some(ext)) = cert.subject_alternative_name() {
+            for name in &ext.value.general_names {
+                match name {
+                    GeneralName::DNSName(dns) => sans.push(dns.to_ascii_lowercase()),
+                    GeneralName::IPAddress(ip) => {
+                        if let Some(addr) = ip_from_bytes(ip) {
+                            sans.push(addr.to_string());
+                        }
+                    }
+                    _ => {}
                 }
             }
         }
+
+        let issuer_name = cert.issuer().iter_common_name().next()
+            .or_else(|| cert.issuer().iter_organization().next())
+            .and_then(|a| a.as_str().ok())
+            .map(str::to_string)
+            .unwrap_or_else(|| cert.issuer().to_string());
+        let subject_cn = cert.subject().iter_common_name().next().and_then(|a| a.as_str().ok()).map(str::to_ascii_lowercase);
+
+        let mut info = CertInfo {
+            host: host.to_lowercase(),
+            port,
+            tls_version: None,
+            cipher: None,
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            issuer_name,
+            not_before: http_date(not_before),
+            not_after: http_date(not_after),
+            days_left: (not_after - now_secs).div_euclid(86_400),
+            signature_algorithm: signature_name(&cert.signature_algorithm.algorithm.to_id_string()),
+            self_signed: cert.subject().as_raw() == cert.issuer().as_raw(),
+            legacy_protocols: Vec::new(),
+            issues: Vec::new(),
+            sans,
+        };
+
+        if now_secs > not_after {
+            info.issues.push(TlsIssue::Expired { days: (now_secs - not_after) / 86_400 });
+        } else if info.days_left < EXPIRY_WARNING_DAYS {
+            info.issues.push(TlsIssue::ExpiresSoon { days: info.days_left });
+        }
+        if now_secs < not_before {
+            info.issues.push(TlsIssue::NotYetValid);
+        }
+        if is_weak_signature(&info.signature_algorithm) {
+            info.issues.push(TlsIssue::WeakSignature { algorithm: info.signature_algorithm.clone() });
+        }
+        if info.self_signed {
+            info.issues.push(TlsIssue::SelfSigned);
+        }
+        let names: Vec<&str> = if info.sans.is_empty() { subject_cn.iter().map(String::as_str).collect() } else { info.sans.iter().map(String::as_str).collect() };
+        if !names.iter().any(|n| name_matches(n, &info.host)) {
+            info.issues.push(TlsIssue::HostnameMismatch);
+        }
+        Some(info)
+    }
+
+    /// Record legacy protocols found by `legacy_protocols` as issues
+    pub fn set_legacy_protocols(&mut self, versions: Vec<String>) {
+        for v in &versions {
+            self.issues.push(TlsIssue::LegacyProtocol { version: v.clone() });
+        }
+        self.legacy_protocols = versions;
+    }
+
+    /// SAN host names under `domain` (wildcards reduced to their base), excluding `domain` itself
+    pub fn in_scope_sans(&self, domain: &str) -> Vec<String> {
+        let domain = domain.to_lowercase();
+        let suffix = format!(".{}", domain);
+        let mut out: Vec<String> = self.sans.iter()
+            .map(|s| s.trim_start_matches("*.").to_string())
+            .filter(|s| s.ends_with(&suffix))
+            .collect();
+        out.sort();
+        out.dedup();
+        out
+    }
+}
+
+/// Whether certificate name `pattern` covers `host` (one wildcard label at most)
+pub fn name_matches(pattern: &str, host: &str) -> bool {
+    let (pattern, host) = (pattern.to_ascii_lowercase(), host.to_ascii_lowercase());
+    match pattern.strip_prefix("*.") {
+        Some(base) => host.split_once('.').is_some_and(|(label, rest)| !label.is_empty() && rest == base),
+        None => pattern == host,
+    }
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<std::net::IpAddr> {
+    match bytes.len() {
+        4 => Some(std::net::IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(std::net::IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+fn http_date(secs: i64) -> String {
+    let t = if secs >= 0 { UNIX_EPOCH + Duration::from_secs(secs as u64) } else { UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) };
+    httpdate::fmt_http_date(t)
+}
+
+/// Readable name of a signature algorithm OID
+fn signature_name(oid: &str) -> String {
+    match oid {
+        "1.2.840.113549.1.1.4" => "md5WithRSAEncryption",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "rsassaPss",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.10040.4.3" => "dsaWithSHA1",
+        "1.2.840.10045.4.1" => "ecdsaWithSHA1",
+        "1.2.840.10045.4.3.2" => "ecdsaWithSHA256",
+        "1.2.840.10045.4.3.3" => "ecdsaWithSHA384",
+        "1.2.840.10045.4.3.4" => "ecdsaWithSHA512",
+        "1.3.101.112" => "ed25519",
+        other => other,
+    }.to_string()
+}
+
+fn is_weak_signature(name: &str) -> bool {
+    let n = name.to_ascii_lowercase();
+    n.contains("sha1") || n.contains("md5")
+}
+
+/// Accepts any certificate: we want to read it, not trust it
+pub(crate) struct AcceptAny;
+
+impl ServerCertVerifier for AcceptAny {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Hosts and HTTPS ports to inspect: explicit `https://host:port` ports, else 443
+pub fn targets(urls: &[String]) -> BTreeSet<(String, u16)> {
+    let mut explicit: BTreeSet<(String, u16)> = BTreeSet::new();
+    let mut hosts: BTreeSet<String> = BTreeSet::new();
+    for u in urls.iter().filter_map(|u| Url::parse(u).ok()) {
+        let Some(host) = u.host_str() else { continue };
+        if u.scheme() != "https" {
+            continue;
+        }
+        let host = host.to_lowercase();
+        match u.port() {
+            Some(port) => { explicit.insert((host, port)); }
+            None => { hosts.insert(host); }
+        }
     }
-    None
+    let with_port: BTreeSet<String> = explicit.iter().map(|(h, _)| h.clone()).collect();
+    explicit.extend(hosts.into_iter().filter(|h| !with_port.contains(h)).map(|h| (h, 443)));
+    explicit
+}
+
+/// Handshake with `host:port` and capture the leaf certificate and negotiated parameters
+pub async fn inspect(host: &str, port: u16, timeout: Duration) -> Option<CertInfo> {
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(AcceptAny))
+        .with_no_client_auth();
+    let name = ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']')).ok()?;
+    let tcp = crate::net::connect(host, port, timeout).await?;
+    let stream = tokio::time::timeout(timeout, TlsConnector::from(Arc::new(config)).connect(name, tcp)).await.ok()?.ok()?;
+    let conn = stream.get_ref().1;
+    let leaf = conn.peer_certificates()?.first()?;
+    let mut info = CertInfo::from_der(host, port, &leaf.0, SystemTime::now())?;
+    info.tls_version = conn.protocol_version().map(|v| match v {
+        rustls::ProtocolVersion::TLSv1_3 => "TLS 1.3".to_string(),
+        rustls::ProtocolVersion::TLSv1_2 => "TLS 1.2".to_string(),
+        other => format!("{:?}", other),
+    });
+    info.cipher = conn.negotiated_cipher_suite().map(|s| format!("{:?}", s.suite()));
+    Some(info)
+}
+
+/// Legacy protocols (TLS 1.0, TLS 1.1) the server still negotiates
+pub async fn legacy_protocols(host: &str, port: u16, timeout: Duration) -> Vec<String> {
+    let mut out = Vec::new();
+    for (minor, name) in [(1u8, "TLS 1.0"), (2u8, "TLS 1.1")] {
+        if accepts_version(host, port, minor, timeout).await {
+            out.push(name.to_string());
+        }
+    }
+    out
+}
+
+/// Send a ClientHello offering at most TLS 1.`minor - 1` and check for a ServerHello with that version
+async fn accepts_version(host: &str, port: u16, minor: u8, timeout: Duration) -> bool {
+    let addr = host.trim_start_matches('[').trim_end_matches(']');
+    let attempt = async {
+        let mut tcp = crate::net::connect(addr, port, timeout).await?;
+        tcp.write_all(&client_hello(addr, minor)).await.ok()?;
+        let mut buf = [0u8; 11];
+        tcp.read_exact(&mut buf).await.ok()?;
+        // Handshake record, ServerHello, server_version
+        Some(buf[0] == 0x16 && buf[5] == 0x02 && buf[9] == 3 && buf[10] == minor)
+    };
+    matches!(tokio::time::timeout(timeout, attempt).await, Ok(Some(true)))
+}
+
+/// Minimal ClientHello for TLS 1.0/1.1 with common CBC suites; SNI unless `host` is an address
+pub fn client_hello(host: &str, minor: u8) -> Vec<u8> {
+    const SUITES: [u16; 8] = [0xc014, 0xc013, 0xc00a, 0xc009, 0x0035, 0x002f, 0x000a, 0x0005];
+    let mut ext = Vec::new();
+    if host.parse::<std::net::IpAddr>().is_err() {
+        let name = host.as_bytes();
+        ext.extend_from_slice(&[0x00, 0x00]);
+        ext.extend_from_slice(&((name.len() + 5) as u16).to_be_bytes());
+        ext.extend_from_slice(&((name.len() + 3) as u16).to_be_bytes());
+        ext.push(0);
+        ext.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        ext.extend_from_slice(name);
+    }
+    // supported_groups (secp256r1, secp384r1, x25519), ec_point_formats, renegotiation_info
+    ext.extend_from_slice(&[0x00, 0x0a, 0x00, 0x08, 0x00, 0x06, 0x00, 0x17, 0x00, 0x18, 0x00, 0x1d]);
+    ext.extend_from_slice(&[0x00, 0x0b, 0x00, 0x02, 0x01, 0x00]);
+    ext.extend_from_slice(&[0xff, 0x01, 0x00, 0x01, 0x00]);
+
+    let mut body = vec![3, minor];
+    body.extend((0..32).map(|_| rand::random::<u8>()));
+    body.push(0);
+    body.extend_from_slice(&((SUITES.len() * 2) as u16).to_be_bytes());
+    for s in SUITES {
+        body.extend_from_slice(&s.to_be_bytes());
+    }
+    body.extend_from_slice(&[1, 0]);
+    body.extend_from_slice(&(ext.len() as u16).to_be_bytes());
+    body.extend_from_slice(&ext);
+
+    let mut hs = vec![0x01];
+    hs.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    hs.extend_from_slice(&body);
+
+    let mut record = vec![0x16, 3, 1];
+    record.extend_from_slice(&(hs.len() as u16).to_be_bytes());
+    record.extend_from_slice(&hs);
+    record
+}
+
+/// Inspect every target; hosts without a completed handshake are left out
+pub async fn inspect_all(targets: &BTreeSet<(String, u16)>, concurrency: usize, timeout: Duration) -> Vec<CertInfo> {
+    let mut certs: Vec<CertInfo> = stream::iter(targets.iter())
+        .map(|(host, port)| async move {
+            let mut info = inspect(host, *port, timeout).await?;
+            info.set_legacy_protocols(legacy_protocols(host, *port, timeout).await);
+            Some(info)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|c| async move { c })
+        .collect()
+        .await;
+    certs.sort_by(|a, b| a.host.cmp(&b.host).then(a.port.cmp(&b.port)));
+    certs
 }
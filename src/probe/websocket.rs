@@ -1,5 +1,28 @@
-use anyhow::Result;
+//! WebSocket endpoint analysis
+//! The endpoints come from the JS analysis (`ws://`/`wss://` URLs) and common paths. Each one gets a real
+//! RFC 6455 handshake (a 101 with a valid `Sec-WebSocket-Accept`), then:
+//! - the handshake again with a foreign and a `null` Origin (cross-site WebSocket hijacking)
+//! - the handshake without the scan's credentials and cookies, and whether the server pushes data
+//! - a text message with a marker, to see whether the server echoes it and whether markup survives
+
+use crate::analyze::open_redirect::EVIL_HOST;
+use crate::http_client::Paced;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use reqwest::{Client, Upgraded};
+use serde::Serialize;
+use sha1::{Digest, Sha1};
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Fixed GUID of the accept key (RFC 6455 section 1.3)
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest message read from an endpoint
+const MESSAGE_LIMIT: usize = 64 * 1024;
+
+pub const OP_TEXT: u8 = 0x1;
+pub const OP_BINARY: u8 = 0x2;
+pub const OP_CLOSE: u8 = 0x8;
 
 #[derive(Debug, Clone)]
 pub struct WebSocketEndpoint {
@@ -9,15 +32,123 @@ pub struct WebSocketEndpoint {
     pub extensions: Vec<String>,
 }
 
+/// `Sec-WebSocket-Accept` the server must answer `key` with
+pub fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(ACCEPT_GUID.as_bytes());
+    STANDARD.encode(hasher.finalize())
+}
+
+/// Client frame (always masked) with a single fragment
+pub fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => frame.push(0x80 | n as u8),
+        n if n <= u16::MAX as usize => {
+            frame.push(0x80 | 126);
+            frame.extend((n as u16).to_be_bytes());
+        }
+        n => {
+            frame.push(0x80 | 127);
+            frame.extend((n as u64).to_be_bytes());
+        }
+    }
+    frame.extend(mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    frame
+}
+
+/// First complete frame in `buf`: opcode, payload and bytes consumed
+pub fn decode_frame(buf: &[u8]) -> Option<(u8, Vec<u8>, usize)> {
+    let (&first, &second) = (buf.first()?, buf.get(1)?);
+    let (len, mut pos) = match second & 0x7f {
+        126 => (u16::from_be_bytes(buf.get(2..4)?.try_into().ok()?) as usize, 4),
+        127 => (u64::from_be_bytes(buf.get(2..10)?.try_into().ok()?) as usize, 10),
+        n => (n as usize, 2),
+    };
+    let mask: Option<[u8; 4]> = if second & 0x80 != 0 {
+        let mask = buf.get(pos..pos + 4)?.try_into().ok()?;
+        pos += 4;
+        Some(mask)
+    } else {
+        None
+    };
+    let end = pos.checked_add(len)?;
+    let mut payload = buf.get(pos..end)?.to_vec();
+    if let Some(mask) = mask {
+        payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+    }
+    Some((first & 0x0f, payload, end))
+}
+
+/// Next text or binary message within `wait`; control frames are skipped, a close ends the wait
+pub async fn read_message(stream: &mut Upgraded, wait: Duration) -> Option<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    tokio::time::timeout(wait, async {
+        loop {
+            while let Some((opcode, payload, used)) = decode_frame(&buf) {
+                buf.drain(..used);
+                match opcode {
+                    OP_TEXT | OP_BINARY => return Some(String::from_utf8_lossy(&payload).into_owned()),
+                    OP_CLOSE => return None,
+                    _ => {}
+                }
+            }
+            if buf.len() > MESSAGE_LIMIT {
+                return None;
+            }
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }).await.ok().flatten()
+}
+
+/// Completed handshake
+pub struct Handshake {
+    pub protocol: Option<String>,
+    pub extensions: Vec<String>,
+    pub stream: Upgraded,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebSocketReport {
+    pub url: String,
+    /// Negotiated `Sec-WebSocket-Protocol`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+    /// Handshake refused for a foreign Origin
+    pub origin_validated: bool,
+    /// Handshake accepted without credentials and cookies
+    pub unauthenticated: bool,
+    /// The test message came back
+    pub echoes_messages: bool,
+    pub vulnerabilities: Vec<WebSocketVulnerability>,
+}
+
 pub struct WebSocketTester {
     timeout: Duration,
 }
 
+impl Default for WebSocketTester {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WebSocketTester {
     pub fn new() -> Self {
-        Self {
-            timeout: Duration::from_secs(5),
-        }
+        Self::with_timeout(Duration::from_secs(5))
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self { timeout }
     }
 
     /// Detect WebSocket endpoints from common patterns
@@ -43,9 +174,9 @@ impl WebSocketTester {
 
         for path in common_paths {
             let url = format!("{}{}", ws_base.trim_end_matches('/'), path);
-            
+
             // Test if WebSocket upgrade is possible
-            if self.test_websocket_upgrade(&url).await {
+            if self.handshake(&url, None, true).await.is_some() {
                 println!("[+] WebSocket endpoint found: {}", url);
                 found.push(url);
             }
@@ -54,127 +185,147 @@ impl WebSocketTester {
         found
     }
 
-    /// Test if URL supports WebSocket upgrade
-    async fn test_websocket_upgrade(&self, url: &str) -> bool {
-        use reqwest::Client;
-        
-        let client = Client::builder()
-            .timeout(self.timeout)
-            .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap();
-
-        // Try HTTP Upgrade request
-        let http_url = url.replace("ws://", "http://").replace("wss://", "https://");
-        
-        if let Ok(response) = client
+    /// HTTP/1.1 client; the upgrade needs a connection of its own
+    fn client(&self, authenticated: bool) -> Option<Client> {
+        let builder = if authenticated {
+            crate::http_client::builder("probe::websocket")
+        } else {
+            crate::http_client::builder_unauthenticated("probe::websocket")
+        };
+        builder.timeout(self.timeout).http1_only().danger_accept_invalid_certs(true).build().ok()
+    }
+
+    /// Opening handshake with `origin` (the endpoint's own origin if `None`); `None` unless the server
+    /// switches protocols with a valid accept key
+    pub async fn handshake(&self, url: &str, origin: Option<&str>, authenticated: bool) -> Option<Handshake> {
+        let http_url = url.replacen("ws://", "http://", 1).replacen("wss://", "https://", 1);
+        let own_origin = crate::probe::soft404::origin_of(&http_url)?;
+        let key = STANDARD.encode(rand::random::<[u8; 16]>());
+        let resp = self.client(authenticated)?
             .get(&http_url)
             .header("Upgrade", "websocket")
             .header("Connection", "Upgrade")
             .header("Sec-WebSocket-Version", "13")
-            .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
-            .send()
+            .header("Sec-WebSocket-Key", &key)
+            .header("Origin", origin.unwrap_or(&own_origin))
+            .send_paced()
             .await
-        {
-            let status = response.status().as_u16();
-            // 101 Switching Protocols or 426 Upgrade Required
-            if status == 101 || status == 426 {
-                return true;
-            }
-            
-            // Check for WebSocket-related headers
-            let headers = response.headers();
-            if headers.contains_key("Sec-WebSocket-Accept") 
-                || headers.get("Upgrade").and_then(|v| v.to_str().ok()) == Some("websocket") {
-                return true;
+            .ok()?;
+        let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        if resp.status().as_u16() != 101 || header("sec-websocket-accept")? != accept_key(&key) {
+            return None;
+        }
+        let protocol = header("sec-websocket-protocol");
+        let extensions = header("sec-websocket-extensions")
+            .map(|e| e.split(',').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect())
+            .unwrap_or_default();
+        let stream = tokio::time::timeout(self.timeout, resp.upgrade()).await.ok()?.ok()?;
+        Some(Handshake { protocol, extensions, stream })
+    }
+
+    /// Foreign Origin the server accepts, if any
+    async fn accepted_origin(&self, url: &str) -> Option<String> {
+        for origin in [format!("https://{}", EVIL_HOST), "null".to_string()] {
+            if self.handshake(url, Some(&origin), true).await.is_some() {
+                return Some(origin);
             }
         }
+        None
+    }
 
-        false
+    /// Sends a marker wrapped in markup; `Some(raw)` if it comes back, `raw` when the markup is unchanged
+    async fn echo(&self, stream: &mut Upgraded) -> Option<bool> {
+        // A greeting sent on connect isn't the answer
+        let _ = read_message(stream, Duration::from_millis(500)).await;
+        let marker = format!("apihunter{:08x}", rand::random::<u32>());
+        let probe = format!(r#"{{"message":"<i>{}</i>"}}"#, marker);
+        stream.write_all(&encode_frame(OP_TEXT, probe.as_bytes(), rand::random())).await.ok()?;
+        for _ in 0..3 {
+            let message = read_message(stream, self.timeout).await?;
+            if message.contains(&marker) {
+                return Some(message.contains(&format!("<i>{}</i>", marker)));
+            }
+        }
+        None
     }
 
-    /// Test WebSocket vulnerabilities
-    pub async fn test_websocket_vulnerabilities(&self, url: &str) -> Vec<WebSocketVulnerability> {
+    /// Handshake and security checks; `None` if `url` isn't a WebSocket endpoint
+    pub async fn analyze(&self, url: &str) -> Option<WebSocketReport> {
+        let Handshake { protocol, extensions, mut stream } = self.handshake(url, None, true).await?;
         let mut vulns = Vec::new();
 
+        let echo = self.echo(&mut stream).await;
+        let _ = stream.write_all(&encode_frame(OP_CLOSE, &[], rand::random())).await;
+        drop(stream);
+        match echo {
+            Some(true) => vulns.push(WebSocketVulnerability {
+                name: "Unencoded Message Reflection".to_string(),
+                severity: "Medium".to_string(),
+                description: "WebSocket echoes messages with HTML markup unchanged".to_string(),
+                details: "Clients rendering messages with innerHTML are open to XSS through the socket".to_string(),
+            }),
+            Some(false) => vulns.push(WebSocketVulnerability {
+                name: "Message Echo".to_string(),
+                severity: "Low".to_string(),
+                description: "WebSocket reflects message content back to the sender".to_string(),
+                details: "Reflected fields are an injection surface for the client".to_string(),
+            }),
+            None => {}
+        }
+
         // Test 1: Missing origin check
-        if self.test_origin_bypass(url).await {
+        let foreign_origin = self.accepted_origin(url).await;
+        if let Some(origin) = &foreign_origin {
             vulns.push(WebSocketVulnerability {
                 name: "Missing Origin Validation".to_string(),
                 severity: "High".to_string(),
-                description: "WebSocket accepts connections from any origin".to_string(),
-                details: "CSRF attacks via WebSocket possible".to_string(),
+                description: format!("WebSocket accepts connections from Origin {}", origin),
+                details: "Cross-site WebSocket hijacking: any page can open the socket with the victim's cookies".to_string(),
             });
         }
 
         // Test 2: No authentication required
-        if self.test_no_auth(url).await {
-            vulns.push(WebSocketVulnerability {
-                name: "No Authentication Required".to_string(),
-                severity: "Critical".to_string(),
-                description: "WebSocket connection possible without authentication".to_string(),
-                details: "Unauthorized access to real-time data".to_string(),
-            });
+        let unauthenticated = self.handshake(url, None, false).await;
+        let unauthenticated_ok = unauthenticated.is_some();
+        if let Some(Handshake { mut stream, .. }) = unauthenticated {
+            let pushed = read_message(&mut stream, Duration::from_secs(2)).await;
+            let _ = stream.write_all(&encode_frame(OP_CLOSE, &[], rand::random())).await;
+            let sample: String = pushed.as_deref().unwrap_or_default().chars().take(100).collect();
+            if crate::identify::has_auth() {
+                vulns.push(WebSocketVulnerability {
+                    name: "No Authentication Required".to_string(),
+                    severity: if pushed.is_some() { "Critical" } else { "High" }.to_string(),
+                    description: "WebSocket connection possible without the scan's credentials".to_string(),
+                    details: if pushed.is_some() { format!("Unauthenticated clients receive data: {}", sample) } else { "Unauthorized access to real-time data".to_string() },
+                });
+            } else if pushed.is_some() {
+                vulns.push(WebSocketVulnerability {
+                    name: "Unauthenticated Data Stream".to_string(),
+                    severity: "Medium".to_string(),
+                    description: "WebSocket pushes data to clients without credentials".to_string(),
+                    details: format!("First message: {}", sample),
+                });
+            }
         }
 
-        vulns
+        Some(WebSocketReport {
+            url: url.to_string(),
+            protocol,
+            extensions,
+            origin_validated: foreign_origin.is_none(),
+            unauthenticated: unauthenticated_ok,
+            echoes_messages: echo.is_some(),
+            vulnerabilities: vulns,
+        })
     }
 
-    async fn test_origin_bypass(&self, url: &str) -> bool {
-        use reqwest::Client;
-        
-        let client = Client::builder()
-            .timeout(self.timeout)
-            .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap();
-
-        let http_url = url.replace("ws://", "http://").replace("wss://", "https://");
-        
-        if let Ok(response) = client
-            .get(&http_url)
-            .header("Upgrade", "websocket")
-            .header("Connection", "Upgrade")
-            .header("Sec-WebSocket-Version", "13")
-            .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
-            .header("Origin", "https://evil.com")
-            .send()
-            .await
-        {
-            response.status().as_u16() == 101
-        } else {
-            false
-        }
-    }
-
-    async fn test_no_auth(&self, url: &str) -> bool {
-        use reqwest::Client;
-        
-        let client = Client::builder()
-            .timeout(self.timeout)
-            .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap();
-
-        let http_url = url.replace("ws://", "http://").replace("wss://", "https://");
-        
-        if let Ok(response) = client
-            .get(&http_url)
-            .header("Upgrade", "websocket")
-            .header("Connection", "Upgrade")
-            .header("Sec-WebSocket-Version", "13")
-            .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
-            .send()
-            .await
-        {
-            response.status().as_u16() == 101
-        } else {
-            false
-        }
+    /// Test WebSocket vulnerabilities
+    pub async fn test_websocket_vulnerabilities(&self, url: &str) -> Vec<WebSocketVulnerability> {
+        self.analyze(url).await.map(|r| r.vulnerabilities).unwrap_or_default()
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WebSocketVulnerability {
     pub name: String,
     pub severity: String,
@@ -190,7 +341,7 @@ pub fn print_websocket_results(endpoints: &[String], vulns: &[WebSocketVulnerabi
     println!("\n[*] WebSocket Analysis Results");
     println!("================================================================================");
     println!("[+] Found {} WebSocket endpoint(s)", endpoints.len());
-    
+
     for (i, endpoint) in endpoints.iter().enumerate() {
         println!("  {}. {}", i + 1, endpoint);
     }
@@ -3,8 +3,8 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::io::Write;
 
-use crate::cli::{Cli, Commands};
-use api_hunter::output::{write_csv, write_top_txt, RawEvent};
+use crate::cli::{Cli, Commands, JwtCommand, SignaturesCommand};
+use api_hunter::output::{paths, write_csv, write_top_txt, Annotation, RawEvent};
 use std::time::Duration;
 
 fn print_ascii_logo() {
@@ -37,12 +37,65 @@ pub async fn run_from_cli(cli: Cli) -> anyhow::Result<()> {
         .with_target(false)
         .init();
 
+    let config = match api_hunter::config::Config::load(cli.config.as_deref().map(std::path::Path::new)) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("[!] Warning: {:#}, using defaults", e);
+            api_hunter::config::Config::default()
+        }
+    };
+
+    // Address family, identification and custom headers must be in place before the first target client is built
+    api_hunter::net::set_family(api_hunter::net::AddressFamily::from_flags(cli.ipv4, cli.ipv6));
+    api_hunter::net::doh::configure(cli.doh.as_deref().map(api_hunter::net::doh::DohServer::parse).transpose()?)?;
+    api_hunter::http_client::configure_http_version(api_hunter::http_client::HttpVersion::parse(&cli.http_version).unwrap_or_default());
+    api_hunter::tls_profile::set_profile(cli.tls_profile.as_deref().and_then(api_hunter::tls_profile::TlsProfile::parse));
+    let identify: Vec<String> = config.identify.iter().chain(cli.identify.iter()).cloned().collect();
+    api_hunter::identify::configure(&identify)?;
+    let headers: Vec<String> = config.headers.iter().chain(cli.headers.iter()).cloned().collect();
+    api_hunter::identify::configure_custom(&headers)?;
+    let mut auth = config.auth.clone().merge(api_hunter::auth::session::AuthConfig {
+        token: cli.auth_token.clone(),
+        cookie: cli.auth_cookie.clone(),
+        header: cli.auth_header.clone(),
+        basic: cli.auth_basic.clone(),
+        login: cli.login.clone(),
+    });
+    if let Some(path) = auth.login.clone() {
+        use api_hunter::auth::login_flow::{self, LoginRecipe};
+        let recipe = LoginRecipe::load(std::path::Path::new(&path))?;
+        println!("[*] Logging in: {}", recipe.url);
+        let client = api_hunter::http_client::builder("auth::login_flow").user_agent(api_hunter::tls_profile::user_agent_or("api-hunter/0.1")).build()?;
+        let acquired = login_flow::login(&client, &recipe).await.map_err(|e| e.context("login failed"))?;
+        auth = auth.merge(acquired);
+    }
+    api_hunter::auth::session::configure(&auth)?;
+    if !auth.is_empty() {
+        println!("[*] Authenticated scan: {}", auth.methods().join(", "));
+    }
+
     match cli.command {
         Commands::TestEndpoint { url, fuzz, rate_limit } => {
             let rate_limit = rate_limit.unwrap_or(100);
             return handle_test_endpoint_command(url, fuzz, rate_limit).await;
         }
-        Commands::Scan { target, out, timing, concurrency, per_host, lite, deep, aggressive, scan_vulns, scan_admin, browser, browser_wait, browser_depth, anon, full_speed, bypass_waf, subdomains, jwt, deep_js, timeout, retries, resume, report } => {
+        Commands::Flow { files, out } => {
+            return handle_flow_command(files, out).await;
+        }
+        Commands::Race { url, method, data, count, success_status, success_match, expected, timeout, out, report } => {
+            let config = api_hunter::fuzz::race::RaceConfig { count, timeout: Duration::from_secs(timeout), success_status, success_match, expected };
+            return handle_race_command(url, method, data, config, out, report).await;
+        }
+        Commands::Schema { out } => {
+            return handle_schema_command(&out);
+        }
+        Commands::Jwt { command } => {
+            return handle_jwt_command(command);
+        }
+        Commands::Signatures { command } => {
+            return handle_signatures_command(command).await;
+        }
+        Commands::Scan { target, out, timing, concurrency, per_host, lite, deep, aggressive, path_wordlist, path_extensions, path_depth, param_wordlist, oob_domain, oob_listen, oob_dns, oob_ip, oob_wait, scan_vulns, scan_admin, browser, browser_wait, browser_depth, crawl, crawl_depth, crawl_pages, crawl_delay, import_har, import_burp, spec, apk, commoncrawl, otx, urlscan, github, wayback_from, wayback_to, wayback_mime, wayback_status, cache_ttl, response_ttl, no_cache, exclude_host, include_host, no_default_excludes, anon, full_speed, tor, proxy_list, proxy_rotation, bypass_waf, find_origin, vhosts, vhost_wordlist, http3, smuggling, cache_poisoning, upload_tests, subdomains, jwt, auth_a, auth_b, no_cookie_jar, persist_cookies, deep_js, entropy_threshold, secret_patterns, js_max_size, verify_secrets, no_preflight, prefilter, no_soft404, single_method, store_bodies, compress_bodies, rps, timeout, retries, resume, report, graph, overwrite, keep_runs, keep_days } => {
             // Set defaults
             let out = out.unwrap_or_else(|| "./results".to_string());
             let timing = timing.unwrap_or(3);
@@ -50,6 +103,22 @@ pub async fn run_from_cli(cli: Cli) -> anyhow::Result<()> {
             let retries = retries.unwrap_or(3);
             let browser_wait = browser_wait.unwrap_or(3000);
             let browser_depth = browser_depth.unwrap_or(1);
+            let cache_ttl = cache_ttl.unwrap_or(24);
+            let response_ttl = response_ttl.map_or(api_hunter::probe::response_cache::DEFAULT_TTL, |m| Duration::from_secs(m * 60));
+            let retention = api_hunter::output::RetentionPolicy {
+                keep_runs,
+                max_age: keep_days.map(|d| Duration::from_secs(d * 86_400)),
+            };
+            // 0 turns the entropy scanner off
+            let entropy_threshold = match entropy_threshold {
+                Some(t) if t <= 0.0 => None,
+                Some(t) => Some(t),
+                None => Some(api_hunter::gather::entropy::DEFAULT_THRESHOLD),
+            };
+            let secret_patterns = match secret_patterns {
+                Some(path) => api_hunter::gather::secret_patterns::load(std::path::Path::new(&path))?,
+                None => Vec::new(),
+            };
 
             // Apply timing templates (like nmap -T0 to -T5)
             let (final_concurrency, final_per_host, final_retries) = match timing {
@@ -67,27 +136,57 @@ pub async fn run_from_cli(cli: Cli) -> anyhow::Result<()> {
             let retries_final = if retries == 3 { final_retries } else { retries };
             
             // Deep mode: Enable Wayback, GAU, vuln scanning automatically
-            let (with_wayback, with_gau, scan_vulns) = if deep {
-                (true, true, true)
+            let (with_wayback, with_gau, with_commoncrawl, scan_vulns, crawl) = if deep {
+                (true, true, true, true, true)
             } else {
-                (false, false, scan_vulns)
+                (false, false, commoncrawl, scan_vulns, crawl)
             };
             
             // Lite mode overrides
-            let (concurrency, per_host, retries, with_wayback, with_gau) = if lite {
-                (8, 2, 1, false, false)
+            let (concurrency, per_host, retries, with_wayback, with_gau, with_commoncrawl, crawl) = if lite {
+                (8, 2, 1, false, false, false, false)
             } else {
-                (concurrency, per_host, retries_final, with_wayback, with_gau)
+                (concurrency, per_host, retries_final, with_wayback, with_gau, with_commoncrawl, crawl)
+            };
+            let crawl_config = api_hunter::gather::crawler::CrawlConfig {
+                max_depth: crawl_depth.unwrap_or(2),
+                max_pages: crawl_pages.unwrap_or(50),
+                delay: Duration::from_millis(crawl_delay.unwrap_or(250)),
             };
             
             let retries = if retries > 10 { 10 } else { retries };
+            let bola = if auth_a.is_empty() {
+                None
+            } else {
+                use api_hunter::fuzz::bola::Identity;
+                Some((Identity::from_specs("A", &auth_a)?, Identity::from_specs("B", &auth_b)?))
+            };
             
-            tracing::info!(target=%target, out=%out, concurrency, per_host, timing, aggressive, deep, retries, timeout, anon, full_speed, bypass_waf, browser, "Starting scan");
+            tracing::info!(target=%target, out=%out, concurrency, per_host, timing, aggressive, deep, retries, timeout, anon, full_speed, tor, bypass_waf, browser, "Starting scan");
             
             // Print ASCII logo and scan configuration
             print_ascii_logo();
             println!("[>] Target: {}", target);
             println!("[~] Timing: T{} (concurrency: {}, per-host: {})", timing, concurrency, per_host);
+            // Enforced below every client, so it holds whatever the timing template allows
+            api_hunter::http_client::configure_rps(rps);
+            if let Some(rps) = rps {
+                println!("[~] Rate cap: {} requests/s", rps);
+                if browser {
+                    println!("[!] The headless browser is not covered by --rps; its page loads are not capped");
+                }
+            }
+            if let Some(doh) = api_hunter::net::doh::shared() {
+                println!("[~] DNS: over HTTPS via {}", doh.server().url());
+            }
+            match api_hunter::http_client::http_version() {
+                api_hunter::http_client::HttpVersion::Http1 => println!("[~] HTTP version: HTTP/1.1 only"),
+                api_hunter::http_client::HttpVersion::Http2 => println!("[~] HTTP version: HTTP/2 only"),
+                api_hunter::http_client::HttpVersion::Auto => {}
+            }
+            if let Some(profile) = api_hunter::tls_profile::profile() {
+                println!("[~] TLS profile: {} (certificates not verified)", profile.name());
+            }
             if lite {
                 println!("[·] Mode: Lite (low impact)");
             } else if aggressive {
@@ -98,22 +197,160 @@ pub async fn run_from_cli(cli: Cli) -> anyhow::Result<()> {
 
             println!("\n{}\n", "-".repeat(60));
             
+            let oob = oob_domain.map(|domain| api_hunter::oob::OobConfig { domain, http_listen: oob_listen, dns_listen: oob_dns, answer_ip: oob_ip });
+            let oob_wait = Duration::from_secs(oob_wait);
+
             // WAF detection is always enabled
-            run_scan(target, out, concurrency, per_host, aggressive, with_gau, with_wayback, resume, lite, retries, timeout, scan_vulns, scan_admin, anon, full_speed, true, bypass_waf, browser, browser_wait, browser_depth, subdomains, jwt, deep_js, report).await?;
+            run_scan(ScanOptions {
+                target, out, concurrency, per_host, aggressive, path_wordlist, path_extensions, path_depth, param_wordlist, oob, oob_wait, with_gau, with_wayback, with_commoncrawl, resume, lite, retries, timeout,
+                scan_vulns, scan_admin, anon, full_speed, tor, proxy_list, proxy_rotation, bypass_waf, find_origin, vhosts, vhost_wordlist, http3, smuggling, cache_poisoning, upload_tests, browser, browser_wait, browser_depth, crawl: crawl.then_some(crawl_config), subdomains,
+                jwt, bola, no_cookie_jar, persist_cookies, deep_js, entropy_threshold, secret_patterns, js_max_size: js_max_size.saturating_mul(1024 * 1024), verify_secrets, preflight: !no_preflight, prefilter, soft404: !no_soft404, method_matrix: !single_method, store_bodies, compress_bodies, report, graph, overwrite, retention, import_har, import_burp, spec, apk, with_otx: otx, with_urlscan: urlscan, with_github: github, wayback_from, wayback_to, wayback_mime, wayback_status,
+                cache_ttl, response_ttl, no_cache, exclude_host, include_host, no_default_excludes, api_keys: config.api_keys,
+            }).await?;
         }
     }
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16, aggressive: bool, with_gau: bool, with_wayback: bool, resume: Option<String>, lite: bool, retries: u8, timeout: u64, scan_vulns: bool, scan_admin: bool, anon: bool, full_speed: bool, _detect_waf: bool, bypass_waf: bool, browser: bool, browser_wait: u64, browser_depth: usize, subdomains: bool, jwt: bool, deep_js: bool, report: Option<String>) -> anyhow::Result<()> {
-    let out_dir = PathBuf::from(&out);
-    api_hunter::utils::ensure_dir(&out_dir)?;
+/// Resolved scan settings (CLI flags after timing templates and mode overrides are applied)
+struct ScanOptions {
+    target: String,
+    out: String,
+    concurrency: u16,
+    per_host: u16,
+    aggressive: bool,
+    /// Extra words for the path brute force
+    path_wordlist: Option<String>,
+    path_extensions: Vec<String>,
+    path_depth: usize,
+    /// Extra names for the parameter brute force
+    param_wordlist: Option<String>,
+    /// Callback listener for the blind OOB tests; `None` skips them
+    oob: Option<api_hunter::oob::OobConfig>,
+    /// Wait for late callbacks before correlating
+    oob_wait: Duration,
+    with_gau: bool,
+    with_wayback: bool,
+    with_commoncrawl: bool,
+    resume: Option<String>,
+    lite: bool,
+    retries: u8,
+    timeout: u64,
+    scan_vulns: bool,
+    scan_admin: bool,
+    anon: bool,
+    full_speed: bool,
+    /// Route everything through the local Tor daemon
+    tor: bool,
+    proxy_list: Option<String>,
+    /// "request" or "host"
+    proxy_rotation: Option<String>,
+    bypass_waf: bool,
+    find_origin: bool,
+    vhosts: bool,
+    /// Extra vhost candidates (`<word>.<domain>`) on top of the built-in list
+    vhost_wordlist: Option<String>,
+    http3: bool,
+    /// Raw-socket request smuggling probes
+    smuggling: bool,
+    /// Active web cache poisoning and deception tests
+    cache_poisoning: bool,
+    /// Multipart upload tests against upload forms and paths
+    upload_tests: bool,
+    browser: bool,
+    browser_wait: u64,
+    browser_depth: usize,
+    /// Crawler settings; `None` disables the crawler
+    crawl: Option<api_hunter::gather::crawler::CrawlConfig>,
+    subdomains: bool,
+    jwt: bool,
+    /// Users A and B for the two-identity BOLA test
+    bola: Option<(api_hunter::fuzz::bola::Identity, api_hunter::fuzz::bola::Identity)>,
+    no_cookie_jar: bool,
+    /// Load and save the cookie jar under <out>/.cookies
+    persist_cookies: bool,
+    deep_js: bool,
+    /// Entropy threshold for generic JS secrets; `None` disables the entropy scanner
+    entropy_threshold: Option<f64>,
+    /// Extra secret patterns from --secret-patterns
+    secret_patterns: Vec<api_hunter::gather::secret_patterns::SecretPattern>,
+    /// Per-file JS download limit in bytes
+    js_max_size: usize,
+    /// Validate recognized secrets against their providers
+    verify_secrets: bool,
+    /// Check host reachability before probing
+    preflight: bool,
+    /// HEAD pre-filter pass before the full probe
+    prefilter: bool,
+    soft404: bool,
+    method_matrix: bool,
+    /// Keep the first N KB of each response body in the probe results
+    store_bodies: Option<usize>,
+    compress_bodies: bool,
+    report: Option<String>,
+    graph: bool,
+    import_har: Option<String>,
+    import_burp: Option<String>,
+    /// OpenAPI/Swagger document to compare the scan with
+    spec: Option<String>,
+    /// APK/IPA for static analysis
+    apk: Option<String>,
+    with_otx: bool,
+    with_urlscan: bool,
+    with_github: bool,
+    wayback_from: Option<String>,
+    wayback_to: Option<String>,
+    wayback_mime: Vec<String>,
+    wayback_status: Vec<String>,
+    /// Hours a cached passive source response stays valid
+    cache_ttl: u64,
+    /// How long a cached HTTP response from the target stays valid
+    response_ttl: Duration,
+    no_cache: bool,
+    exclude_host: Vec<String>,
+    include_host: Vec<String>,
+    no_default_excludes: bool,
+    /// Delete the previous run's results instead of archiving them
+    overwrite: bool,
+    retention: api_hunter::output::RetentionPolicy,
+    api_keys: api_hunter::config::ApiKeys,
+}
+
+async fn run_scan(opts: ScanOptions) -> anyhow::Result<()> {
+    let ScanOptions {
+        target, out, concurrency, per_host, aggressive, path_wordlist, path_extensions, path_depth, param_wordlist, oob, oob_wait, with_gau, with_wayback, with_commoncrawl, resume, lite, retries, timeout,
+        scan_vulns, scan_admin, anon, full_speed, tor, proxy_list, proxy_rotation, bypass_waf, find_origin, vhosts, vhost_wordlist, http3, smuggling, cache_poisoning, upload_tests, browser, browser_wait, browser_depth, crawl, subdomains,
+        jwt, bola, no_cookie_jar, persist_cookies, deep_js, entropy_threshold, secret_patterns, js_max_size, verify_secrets, preflight, prefilter, soft404, method_matrix, store_bodies, compress_bodies, report, graph, overwrite, retention, import_har, import_burp, spec: spec_path, apk, with_otx, with_urlscan, with_github, wayback_from, wayback_to, wayback_mime, wayback_status,
+        cache_ttl, response_ttl, no_cache, exclude_host, include_host, no_default_excludes, api_keys,
+    } = opts;
+
+    let workspace = PathBuf::from(&out);
+    api_hunter::utils::ensure_dir(&workspace)?;
+
+    // Passive source responses are cached in the output workspace across runs
+    let cache = if no_cache {
+        api_hunter::gather::cache::SourceCache::disabled()
+    } else {
+        api_hunter::gather::cache::SourceCache::new(paths::artifact(&workspace, ".cache"), Duration::from_secs(cache_ttl * 3600))
+    };
+    // Target responses are cached next to them, so later phases and repeated scans reuse what the probe fetched
+    api_hunter::probe::response_cache::configure(if no_cache {
+        api_hunter::probe::response_cache::ResponseCache::disabled()
+    } else {
+        api_hunter::probe::response_cache::ResponseCache::new(paths::artifact(&paths::artifact(&workspace, ".cache"), "responses"), response_ttl)
+    });
 
-    // Clean up previous scan results
-    use api_hunter::output::cleanup_results;
-    if let Err(e) = cleanup_results(&out) {
-        eprintln!("[!] Warning: Failed to clean results directory: {}", e);
+    // Previous results are archived under runs/<scan id>/ (removed only with --overwrite)
+    let registry = api_hunter::output::ArtifactRegistry::new(&workspace);
+    let scan_run = registry.begin_scan(&target, overwrite)?;
+    let out_dir = scan_run.dir.clone();
+    if out_dir != workspace {
+        println!("[!] Another scan is writing to {}, results go to {}", workspace.display(), out_dir.display());
+    }
+    match registry.apply_retention(retention) {
+        Ok(removed) if !removed.is_empty() => println!("[~] Removed {} archived run(s) per retention policy: {}", removed.len(), removed.join(", ")),
+        Ok(_) => {}
+        Err(e) => eprintln!("[!] Warning: Failed to apply retention policy: {}", e),
     }
 
     let scan_start = std::time::Instant::now();
@@ -131,53 +368,124 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
     };
 
     if let Some(resume_path) = resume {
-        let events = api_hunter::utils::read_jsonl(PathBuf::from(resume_path))?;
+        let mut events = api_hunter::utils::read_jsonl(PathBuf::from(resume_path))?;
+        let clusters = api_hunter::enrich::simhash::cluster(&mut events, api_hunter::enrich::simhash::DEFAULT_MAX_DISTANCE);
         let refs: Vec<&RawEvent> = events.iter().collect();
-        let jsonl_path = out_dir.join("target_raw.jsonl");
-        let csv_path = out_dir.join("target_apis_sorted.csv");
-        let top_path = out_dir.join("target_top.txt");
+        let representatives = api_hunter::enrich::simhash::representatives(&events, &clusters);
+        let jsonl_path = paths::artifact(&out_dir, "target_raw.jsonl");
+        let csv_path = paths::artifact(&out_dir, "target_apis_sorted.csv");
+        let top_path = paths::artifact(&out_dir, "target_top.txt");
         api_hunter::output::write_jsonl(&jsonl_path, &refs)?;
-        write_csv(&csv_path, &refs)?;
-        write_top_txt(&top_path, &refs)?;
+        write_csv(&csv_path, &representatives)?;
+        write_top_txt(&top_path, &representatives)?;
+        if !clusters.is_empty() {
+            let _ = std::fs::write(paths::artifact(&out_dir, "clusters.json"), serde_json::to_string_pretty(&clusters).unwrap_or_default());
+        }
         println!("Wrote resumed outputs to {}", out_dir.display());
+        registry.finish_scan(&scan_run)?;
         return Ok(());
     }
 
+    // Tor replaces the residential proxy; check the daemon is there before anything leaves directly
+    if tor {
+        let tor_addr = std::net::SocketAddr::from(([127, 0, 0, 1], 9050));
+        if !matches!(tokio::time::timeout(Duration::from_secs(3), tokio::net::TcpStream::connect(tor_addr)).await, Ok(Ok(_))) {
+            anyhow::bail!("--tor: no Tor SOCKS proxy listening on {} (start tor first)", tor_addr);
+        }
+        api_hunter::http_client::configure_proxy(Some(api_hunter::http_client::ProxyConfig::tor()));
+        println!("[*] Routing through Tor ({}), one circuit per host", api_hunter::http_client::TOR_PROXY);
+    }
+
+    // Proxy pool: drop proxies that can't reach the target before the first request goes out
+    if let Some(path) = &proxy_list {
+        use api_hunter::anonymizer::pool::{self, ProxyPool, Rotation};
+        let rotation = proxy_rotation.as_deref().and_then(Rotation::parse).unwrap_or(Rotation::Host);
+        let proxy_pool = ProxyPool::new(pool::load_list(std::path::Path::new(path))?, rotation);
+        println!("[*] Proxy pool: health-checking {} proxies...", proxy_pool.len());
+        proxy_pool.health_check(&format!("https://{}/", domain), Duration::from_secs(timeout.clamp(5, 15)), 20).await;
+        if proxy_pool.healthy() == 0 {
+            let _ = std::fs::write(paths::artifact(&out_dir, "proxy_pool.json"), serde_json::to_string_pretty(&proxy_pool.stats()).unwrap_or_default());
+            anyhow::bail!("--proxy-list: none of the {} proxies reached {} (see proxy_pool.json)", proxy_pool.len(), domain);
+        }
+        println!("   [+] {}/{} healthy, rotating per {}", proxy_pool.healthy(), proxy_pool.len(), if rotation == Rotation::Host { "host" } else { "request" });
+        pool::configure(Some(proxy_pool));
+    }
+
     // Setup anonymous mode if requested
     let anonymizer = if anon {
+        // A browser ClientHello to go with the browser User-Agent, unless --tls-profile picked one
+        if api_hunter::tls_profile::profile().is_none() {
+            api_hunter::tls_profile::set_profile(Some(api_hunter::tls_profile::TlsProfile::Chrome120));
+        }
+        // Lookups of the target's names shouldn't go to the local resolver either, unless --doh picked a server
+        if api_hunter::net::doh::shared().is_none() && !tor {
+            api_hunter::net::doh::configure(Some(api_hunter::net::doh::DohServer::Cloudflare))?;
+            println!("[*] DNS over HTTPS via {}", api_hunter::net::doh::DohServer::Cloudflare.url());
+        }
         // Try to load from environment first
-        let anon_client = if let Some(anon) = api_hunter::anonymizer::Anonymizer::from_env(full_speed) {
+        let mut anon_client = if tor {
+            api_hunter::anonymizer::Anonymizer::tor(full_speed)
+        } else if proxy_list.is_some() {
+            api_hunter::anonymizer::Anonymizer::new(full_speed)
+        } else if let Some(anon) = api_hunter::anonymizer::Anonymizer::from_env(full_speed) {
             anon
         } else {
             // Fallback: Create without proxy (direct traffic with Human-like Patterns)
             api_hunter::anonymizer::Anonymizer::new(full_speed)
         };
+        // Same user agent and proxy session as the last scan of this target, so runs continue one browser session
+        let session_path = api_hunter::anonymizer::session_path(&workspace, &domain);
+        if session_path.exists() {
+            match anon_client.load_session(&session_path) {
+                Ok(true) => println!("[~] Continuing anonymous session from {}", session_path.display()),
+                Ok(false) => println!("[~] Saved anonymous session doesn't match the TLS profile, starting a new one"),
+                Err(e) => eprintln!("[!] Warning: {}", e),
+            }
+        }
         
         // Check if residential proxy is configured
-        if !anon_client.is_proxy_configured() {
+        if !anon_client.is_proxy_configured() && proxy_list.is_none() {
             println!("⚠️  No residential proxy configured - using direct connection");
         }
+        // Every other client (discovery, analysis, JS fetching) leaves through the same proxy
+        if !tor && proxy_list.is_none() {
+            api_hunter::http_client::configure_proxy(anon_client.get_residential_proxy_url().map(api_hunter::http_client::ProxyConfig::new));
+        }
         
         Some(anon_client)
     } else {
         None
     };
+    // Phases that open their own connections (pre-flight, TLS, vhosts) would bypass the proxy
+    let proxied = anonymizer.is_some() || tor || proxy_list.is_some();
+
+    // Cookies set by the target are sent back on later requests; not in anonymous mode, where they
+    // would link the rotated identities
+    api_hunter::cookie_jar::set_enabled(!no_cookie_jar && anonymizer.is_none());
+    let cookie_path = (persist_cookies && api_hunter::cookie_jar::is_enabled()).then(|| api_hunter::cookie_jar::path(&workspace, &domain));
+    if let Some(path) = cookie_path.as_ref().filter(|p| p.exists()) {
+        match api_hunter::cookie_jar::shared().load(path) {
+            Ok(n) => println!("[~] Loaded {} cookie(s) from {}", n, path.display()),
+            Err(e) => eprintln!("[!] Warning: {}", e),
+        }
+    }
 
     // Phase 1: WAF Detection (passive - during probing)
     // WAF detection happens during probing
 
     // Phase 1.5: Subdomain Enumeration (if enabled)
     let mut all_targets = vec![domain.clone()];
+    let mut known_subdomains: Vec<String> = Vec::new();
     if subdomains {
         println!("[*] Subdomain enumeration...");
         use api_hunter::discover::subdomain::SubdomainEnumerator;
         
-        let enumerator = SubdomainEnumerator::new();
+        let enumerator = SubdomainEnumerator::new().with_cache(cache.clone());
         let subdomain_results = enumerator.enumerate(&domain).await;
         
         // Save subdomain report
         let report = enumerator.generate_report(&subdomain_results);
-        let subdomain_path = out_dir.join("subdomains.txt");
+        let subdomain_path = paths::artifact(&out_dir, "subdomains.txt");
         let _ = std::fs::write(&subdomain_path, &report);
         
         // Add API-related subdomains to scan targets
@@ -186,6 +494,8 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
             r.subdomain.contains("graphql") || r.subdomain.contains("gateway")
         }).count();
         
+        known_subdomains = subdomain_results.iter().map(|r| r.subdomain.clone()).collect();
+
         for result in subdomain_results.iter() {
             if result.subdomain.contains("api") 
                 || result.subdomain.contains("rest") 
@@ -202,12 +512,84 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         }
     }
 
+    // Phase 1.6: Origin IP discovery (opt-in, passive towards the candidates)
+    if find_origin {
+        println!("[*] Origin IP discovery...");
+        let finder = api_hunter::waf::OriginFinder::new(&api_keys);
+        match tokio::time::timeout(std::time::Duration::from_secs(90), finder.discover(&domain, &known_subdomains)).await {
+            Ok(origin_report) => {
+                if let Some(ref cdn) = origin_report.cdn {
+                    println!("   [*] Edge: {} ({})", cdn, origin_report.edge_ips.join(", "));
+                }
+                for skipped in &origin_report.skipped_sources {
+                    tracing::debug!("Origin source skipped: {}", skipped);
+                }
+                if origin_report.candidates.is_empty() {
+                    println!("   [-] No origin candidates found");
+                } else {
+                    println!("   [+] {} origin candidate(s) - verify manually:", origin_report.candidates.len());
+                    for c in origin_report.candidates.iter().take(10) {
+                        println!("      [+] {} ({})", c.ip, c.sources.join(", "));
+                    }
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "origin_candidates.json"), serde_json::to_string_pretty(&origin_report).unwrap_or_default());
+            }
+            Err(_) => {
+                println!("   [!] Timeout");
+                tracing::warn!("Origin discovery timed out");
+            }
+        }
+    }
+
+    // Phase 1.7: Virtual host discovery (opt-in). Goes to the IPs directly, so not through a proxy.
+    let mut vhost_hits: Vec<api_hunter::discover::vhost::VhostHit> = Vec::new();
+    if vhosts && !proxied {
+        use api_hunter::discover::vhost::{self, VhostScanner};
+        println!("[*] Virtual host discovery...");
+        let mut words: Vec<String> = vhost::DEFAULT_WORDS.iter().map(|w| w.to_string()).collect();
+        if let Some(path) = &vhost_wordlist {
+            words.extend(vhost::load_wordlist(std::path::Path::new(path))?);
+        }
+        let candidates = vhost::candidates(&domain, &known_subdomains, &words);
+        let mut ips: Vec<std::net::IpAddr> = api_hunter::net::resolve(&domain, 443).await.into_iter().map(|a| a.ip()).collect();
+        ips.sort();
+        ips.dedup();
+        ips.truncate(4);
+        if ips.is_empty() {
+            println!("   [-] {} has no {} address", domain, api_hunter::net::family().label());
+        } else {
+            if let Some(cdn) = ips.iter().find_map(api_hunter::waf::origin::cdn_provider) {
+                println!("   [!] {} is behind {}; hidden vhosts are usually only reachable on the origin", domain, cdn);
+            }
+            println!("   [*] {} candidates against {}", candidates.len(), ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", "));
+            match VhostScanner::new(Duration::from_secs(timeout.clamp(2, 10)), concurrency as usize) {
+                Ok(scanner) => {
+                    for target in vhost::targets(&ips) {
+                        vhost_hits.extend(scanner.scan(&target, &domain, &candidates).await);
+                    }
+                }
+                Err(e) => eprintln!("   [!] Warning: vhost scanner unavailable: {}", e),
+            }
+            if vhost_hits.is_empty() {
+                println!("   [-] No hidden virtual hosts found");
+            }
+            for hit in &vhost_hits {
+                println!("      [+] {} on {} -> {}{}{}", hit.host, hit.target, hit.fingerprint.status,
+                    hit.fingerprint.title.as_ref().map(|t| format!(" \"{}\"", t)).unwrap_or_default(),
+                    if hit.resolves { "" } else { " (not in DNS)" });
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "vhosts.json"), serde_json::to_string_pretty(&vhost_hits).unwrap_or_default());
+        }
+    }
+
     // Discover and gather candidates
     println!("[*] API discovery...");
     let mut candidates: Vec<String> = Vec::new();
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(1024);
     
-    if with_wayback {
+    // The external tool can't filter, so any Wayback filter forces the CDX API path
+    let wayback_filtered = wayback_from.is_some() || wayback_to.is_some() || !wayback_mime.is_empty() || !wayback_status.is_empty();
+    if with_wayback && !wayback_filtered {
         tracing::debug!("Starting external waybackurls tool");
         let txc = tx.clone(); let t_target = domain.clone();
         tokio::spawn(async move { let _ = api_hunter::external::tools::try_run_waybackurls(&t_target, txc).await; });
@@ -217,18 +599,80 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         let txc = tx.clone(); let g_target = domain.clone();
         tokio::spawn(async move { let _ = api_hunter::external::tools::try_run_gau(&g_target, txc).await; });
     }
+    if with_commoncrawl {
+        tracing::debug!("Querying CommonCrawl index");
+        let txc = tx.clone(); let c_target = domain.clone(); let cache = cache.clone();
+        tokio::spawn(async move {
+            let fetch = |t| api_hunter::gather::commoncrawl::stream_commoncrawl_urls(&c_target, t);
+            if let Err(e) = cache.stream("commoncrawl", &c_target, txc, fetch).await {
+                tracing::warn!("CommonCrawl gather failed: {}", e);
+            }
+        });
+    }
+    if with_otx {
+        tracing::debug!("Querying AlienVault OTX");
+        let txc = tx.clone(); let o_target = domain.clone(); let key = api_keys.otx.clone(); let cache = cache.clone();
+        tokio::spawn(async move {
+            let fetch = |t| api_hunter::gather::otx::stream_otx_urls(&o_target, key.as_deref(), t);
+            if let Err(e) = cache.stream("otx", &o_target, txc, fetch).await {
+                tracing::warn!("OTX gather failed: {}", e);
+            }
+        });
+    }
+    if with_urlscan {
+        tracing::debug!("Querying urlscan.io");
+        let txc = tx.clone(); let u_target = domain.clone(); let key = api_keys.urlscan.clone(); let cache = cache.clone();
+        tokio::spawn(async move {
+            let fetch = |t| api_hunter::gather::urlscan::stream_urlscan_urls(&u_target, key.as_deref(), t);
+            if let Err(e) = cache.stream("urlscan", &u_target, txc, fetch).await {
+                tracing::warn!("urlscan gather failed: {}", e);
+            }
+        });
+    }
 
     // Bound gatherer calls so a slow remote or parsing bug won't hang discovery.
-    if !with_wayback {
+    if !with_wayback || wayback_filtered {
         tracing::debug!("Querying Wayback Machine CDX API...");
-        match tokio::time::timeout(Duration::from_secs(10), api_hunter::gather::wayback::wayback_urls(&domain)).await {
+        let mut query = api_hunter::gather::wayback::WaybackQuery {
+            from: wayback_from,
+            to: wayback_to,
+            mime_filters: wayback_mime,
+            status_filters: wayback_status,
+            ..Default::default()
+        };
+        let cache_key = format!("{}|from={:?}|to={:?}|mime={:?}|status={:?}",
+            domain, query.from, query.to, query.mime_filters, query.status_filters);
+        // The query stops paging on its own time budget; the outer timeout is only a safety net
+        let outer = query.time_budget + Duration::from_secs(15);
+        let cached = cache.get::<api_hunter::gather::wayback::WaybackResult>("wayback", &cache_key);
+        let from_cache = cached.as_ref().is_some_and(|w| w.resume_key.is_none());
+        let fetched = match cached {
+            Some(w) if w.resume_key.is_none() => Ok(Ok(w)),
+            // A query an earlier run stopped on its time budget continues from the cached resume key
+            partial => {
+                query.resume_key = partial.as_ref().and_then(|w| w.resume_key.clone());
+                tokio::time::timeout(outer, api_hunter::gather::wayback::wayback_query(&domain, &query)).await
+                    .map(|r| r.map(|next| match partial {
+                        Some(mut w) => { w.merge(next); w }
+                        None => next,
+                    }))
+            }
+        };
+        match fetched {
             Ok(Ok(mut w)) => {
-                let count = w.len();
-                candidates.append(&mut w);
-                tracing::info!("Wayback CDX: {} URLs found", count);
+                // Partial results are cached with their resume key, so the next run picks up from there
+                if !from_cache {
+                    cache.put("wayback", &cache_key, &w);
+                }
+                let count = w.urls.len();
+                candidates.append(&mut w.urls);
+                tracing::info!("Wayback CDX: {} URLs found ({} pages)", count, w.pages);
+                if w.resume_key.is_some() {
+                    tracing::warn!("Wayback CDX incomplete, the next run continues where this one stopped");
+                }
             }
             Ok(Err(e)) => { tracing::warn!("Wayback gather failed: {}", e); }
-            Err(_) => { tracing::warn!("Wayback gather timed out (10s)"); }
+            Err(_) => { tracing::warn!("Wayback gather timed out ({}s)", outer.as_secs()); }
         }
     }
 
@@ -243,23 +687,110 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         Err(_) => { tracing::warn!("JS extraction timed out (12s)"); }
     }
 
+    // Service worker precache lists and runtime routes enumerate most of a SPA's routes
+    tracing::debug!("Analyzing web app manifest and service workers...");
+    match tokio::time::timeout(Duration::from_secs(20), api_hunter::gather::service_worker::analyze(&format!("https://{}", domain))).await {
+        Ok(Ok(report)) if !report.is_empty() => {
+            let sw_urls = report.candidate_urls(&format!("https://{}", domain));
+            println!("   [*] Service worker: {} workers | {} precached URLs | {} runtime routes",
+                report.service_workers.len(), report.precache_urls.len(), report.runtime_routes.len());
+            candidates.extend(sw_urls);
+            let _ = std::fs::write(paths::artifact(&out_dir, "service_worker.json"), serde_json::to_string_pretty(&report).unwrap_or_default());
+        }
+        Ok(Ok(_)) => { tracing::debug!("No web app manifest or service worker found"); }
+        Ok(Err(e)) => { tracing::warn!("Service worker analysis failed: {}", e); }
+        Err(_) => { tracing::warn!("Service worker analysis timed out (20s)"); }
+    }
+
+    // Same-domain HTML crawl: links, form actions, inline fetch targets
+    if let Some(crawl_config) = crawl {
+        println!("   [*] Crawling (depth {}, max {} pages)...", crawl_config.max_depth, crawl_config.max_pages);
+        let budget = Duration::from_secs(60) + crawl_config.delay * crawl_config.max_pages as u32;
+        let crawl_fut = async {
+            api_hunter::gather::crawler::Crawler::new(crawl_config)?.crawl(&format!("https://{}", domain)).await
+        };
+        match tokio::time::timeout(budget, crawl_fut).await {
+            Ok(Ok(crawled)) => {
+                println!("      [+] {} pages | {} links | {} forms | {} inline fetch targets",
+                    crawled.pages.len(), crawled.links.len(), crawled.forms.len(), crawled.fetch_targets.len());
+                candidates.extend(crawled.candidate_urls());
+                let _ = std::fs::write(paths::artifact(&out_dir, "crawl_results.json"), serde_json::to_string_pretty(&crawled).unwrap_or_default());
+            }
+            Ok(Err(e)) => {
+                println!("      [!] Failed: {}", e);
+                tracing::warn!("Crawl failed: {}", e);
+            }
+            Err(_) => {
+                println!("      [!] Timeout after {}s", budget.as_secs());
+                tracing::warn!("Crawl timed out");
+            }
+        }
+    }
+
+    // GitHub code search: leaked URLs/paths become candidates, hits are informational findings
+    let mut github_leaks = Vec::new();
+    if with_github {
+        println!("   [*] GitHub code search...");
+        match api_keys.github.clone() {
+            Some(token) => {
+                let dorker = api_hunter::gather::github_dorks::GithubDorker::new(token);
+                let cached = cache.get::<api_hunter::gather::github_dorks::GithubDorkResult>("github", &domain);
+                let from_cache = cached.is_some();
+                let fetched = match cached {
+                    Some(res) => Ok(Ok(res)),
+                    None => tokio::time::timeout(Duration::from_secs(60), dorker.search(&domain)).await,
+                };
+                match fetched {
+                    Ok(Ok(res)) => {
+                        if !from_cache {
+                            cache.put("github", &domain, &res);
+                        }
+                        let secret_count: usize = res.leaks.iter().map(|l| l.secrets.len()).sum();
+                        print!("      [+] {} hits | {} files with references | {} candidates", res.total_hits, res.leaks.len(), res.candidates.len());
+                        if secret_count > 0 {
+                            print!(" | {} potential secrets [!]", secret_count);
+                        }
+                        println!();
+                        candidates.extend(res.candidates.iter().cloned());
+                        let _ = std::fs::write(paths::artifact(&out_dir, "github_findings.json"), serde_json::to_string_pretty(&res).unwrap_or_default());
+                        github_leaks = res.leaks;
+                    }
+                    Ok(Err(e)) => {
+                        println!("      [!] Failed: {}", e);
+                        tracing::warn!("GitHub search failed: {}", e);
+                    }
+                    Err(_) => {
+                        println!("      [!] Timeout");
+                        tracing::warn!("GitHub search timed out");
+                    }
+                }
+            }
+            None => println!("      [-] No GitHub token (config api_keys.github or GITHUB_TOKEN)"),
+        }
+    }
+
     // Deep JavaScript Analysis - Extract ALL critical information
+    let mut js_vulnerable_libs = Vec::new();
+    let mut js_oauth_flows = Vec::new();
+    let mut js_websockets = Vec::new();
     if deep_js {
         println!("   [*] Deep JS analysis...");
         
         match tokio::time::timeout(
-            Duration::from_secs(60),
+            Duration::from_secs(120),
             async {
                 let analyzer = api_hunter::gather::js_deep_analyzer::JsDeepAnalyzer::new(
                     domain.clone(),
                     timeout,
                     concurrency as usize,
-                )?;
+                )?.with_source_output(paths::artifact(&out_dir, "sources")).with_entropy_threshold(entropy_threshold)
+                    .with_secret_patterns(secret_patterns.clone()).with_max_js_size(js_max_size);
                 analyzer.analyze_all().await
             }
         ).await {
-            Ok(Ok(js_critical)) => {
-                let total_findings = js_critical.endpoints.len() + js_critical.secrets.len() + js_critical.parameters.len();
+            Ok(Ok(mut js_critical)) => {
+                let total_findings = js_critical.endpoints.len() + js_critical.secrets.len() + js_critical.parameters.len()
+                    + js_critical.client_side_issues.len() + js_critical.vulnerable_libraries.len();
                 
                 if total_findings > 0 {
                     print!("      [+] {} endpoints", js_critical.endpoints.len());
@@ -269,34 +800,76 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
                     if js_critical.parameters.len() > 0 {
                         print!(" | {} parameters", js_critical.parameters.len());
                     }
+                    if !js_critical.client_side_issues.is_empty() {
+                        print!(" | {} client-side issues", js_critical.client_side_issues.len());
+                    }
+                    if !js_critical.webpack_chunks.is_empty() {
+                        print!(" | {} webpack chunks", js_critical.webpack_chunks.len());
+                    }
+                    if !js_critical.original_sources.is_empty() {
+                        print!(" | {} original sources (source maps)", js_critical.original_sources.len());
+                    }
+                    if !js_critical.vulnerable_libraries.is_empty() {
+                        print!(" | {} vulnerable libraries [!]", js_critical.vulnerable_libraries.len());
+                    }
                     println!();
                 } else {
                     println!("      [-] No critical information found");
                 }
+                if !js_critical.wasm_modules.is_empty() {
+                    println!("      [+] {} WASM modules analyzed", js_critical.wasm_modules.len());
+                }
+                if !js_critical.binary_assets.is_empty() {
+                    println!("      [-] Skipped {} binary assets (WASM, images, archives)", js_critical.binary_assets.len());
+                }
+                if !js_critical.truncated_files.is_empty() {
+                    println!("      [!] {} bundles exceeded --js-max-size and were only partly analyzed", js_critical.truncated_files.len());
+                }
                 
                 // Add discovered endpoints to candidates
                 for endpoint in &js_critical.endpoints {
                     candidates.push(endpoint.url.clone());
                 }
                 
-                for ws in &js_critical.websockets {
-                    candidates.push(ws.clone());
-                }
+                // ws:// URLs fail in the HTTP prober; they get a handshake of their own
+                js_websockets = js_critical.websockets.clone();
                 
                 for gql in &js_critical.graphql {
                     candidates.push(gql.endpoint.clone());
                 }
                 
+                if verify_secrets && !js_critical.secrets.is_empty() {
+                    println!("   [*] Verifying secrets against provider APIs...");
+                    match api_hunter::security::secret_verifier::SecretVerifier::new() {
+                        Ok(verifier) => {
+                            verifier.verify_all(&mut js_critical.secrets).await;
+                            use api_hunter::security::secret_verifier::VerificationStatus;
+                            let count = |status| js_critical.secrets.iter()
+                                .filter(|s| s.verification.as_ref().is_some_and(|v| v.status == status))
+                                .count();
+                            let (active, revoked, unknown) = (count(VerificationStatus::Active), count(VerificationStatus::Revoked), count(VerificationStatus::Unknown));
+                            if active + revoked + unknown == 0 {
+                                println!("      [-] No verifiable key types");
+                            } else {
+                                println!("      [+] {} active [!] | {} revoked | {} inconclusive", active, revoked, unknown);
+                            }
+                        }
+                        Err(e) => println!("      [!] Failed: {}", e),
+                    }
+                }
+
                 // Save critical findings to a special output file
-                let js_critical_path = format!("{}/js_critical_info.json", out);
+                let js_critical_path = paths::artifact(&out_dir, "js_critical_info.json");
                 let _ = std::fs::write(&js_critical_path, serde_json::to_string_pretty(&js_critical).unwrap_or_default());
+                js_vulnerable_libs = js_critical.vulnerable_libraries;
+                js_oauth_flows = js_critical.oauth_flows;
             }
             Ok(Err(e)) => {
                 println!("      [!] Failed: {}", e);
                 tracing::warn!("Deep JS analysis failed: {}", e);
             }
             Err(_) => {
-                println!("      [!] Timeout after 60s");
+                println!("      [!] Timeout after 120s");
                 tracing::warn!("Deep JS analysis timed out");
             }
         }
@@ -335,6 +908,100 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         }
     }
 
+    // Imported traffic: HAR captures and Burp proxy history
+    let mut imported_urls: Vec<String> = Vec::new();
+    // JSON bodies of captured write requests, fuzzed under --aggressive
+    let mut body_seeds: Vec<api_hunter::fuzz::json_fuzzer::BodySeed> = Vec::new();
+    let mut imported_seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+    if let Some(ref har_path) = import_har {
+        println!("   [*] HAR import...");
+        match api_hunter::gather::har::import_har(std::path::Path::new(har_path)) {
+            Ok(har_requests) => {
+                let before = imported_urls.len();
+                for req in &har_requests {
+                    if imported_seen.insert(req.url.clone()) {
+                        imported_urls.push(req.url.clone());
+                    }
+                }
+                body_seeds.extend(har_requests.iter().filter_map(api_hunter::fuzz::json_fuzzer::BodySeed::observed));
+                println!("      [+] {} requests ({} unique URLs)", har_requests.len(), imported_urls.len() - before);
+                let _ = std::fs::write(paths::artifact(&out_dir, "har_requests.json"), serde_json::to_string_pretty(&har_requests).unwrap_or_default());
+            }
+            Err(e) => {
+                println!("      [!] Failed: {}", e);
+                tracing::warn!("HAR import failed: {}", e);
+            }
+        }
+    }
+
+    if let Some(ref burp_path) = import_burp {
+        println!("   [*] Burp import...");
+        match api_hunter::gather::burp::import_burp(std::path::Path::new(burp_path)) {
+            Ok(burp_requests) => {
+                let before = imported_urls.len();
+                for req in &burp_requests {
+                    if imported_seen.insert(req.url.clone()) {
+                        imported_urls.push(req.url.clone());
+                    }
+                }
+                body_seeds.extend(burp_requests.iter().filter_map(api_hunter::fuzz::json_fuzzer::BodySeed::observed));
+                println!("      [+] {} requests ({} unique URLs)", burp_requests.len(), imported_urls.len() - before);
+                let _ = std::fs::write(paths::artifact(&out_dir, "burp_requests.json"), serde_json::to_string_pretty(&burp_requests).unwrap_or_default());
+            }
+            Err(e) => {
+                println!("      [!] Failed: {}", e);
+                tracing::warn!("Burp import failed: {}", e);
+            }
+        }
+    }
+
+    // Documented API: its paths are probed like imported traffic, then compared with what answered
+    let mut api_spec: Option<api_hunter::analyze::spec_drift::ApiSpec> = None;
+    if let Some(ref spec_path) = spec_path {
+        println!("   [*] API spec...");
+        match api_hunter::analyze::spec_drift::ApiSpec::load(std::path::Path::new(spec_path), &domain) {
+            Ok(documented) => {
+                let before = imported_urls.len();
+                for url in documented.probe_urls() {
+                    if imported_seen.insert(url.clone()) {
+                        imported_urls.push(url);
+                    }
+                }
+                println!("      [+] {} ({} paths, {} operations, {} new URLs)", documented.title.as_deref().unwrap_or(spec_path), documented.paths.len(), documented.operation_count(), imported_urls.len() - before);
+                api_spec = Some(documented);
+            }
+            Err(e) => {
+                println!("      [!] Failed: {}", e);
+                tracing::warn!("API spec import failed: {}", e);
+            }
+        }
+    }
+
+    if let Some(ref apk_path) = apk {
+        println!("   [*] Mobile app analysis...");
+        let rules = api_hunter::gather::js_deep_analyzer::SecretRules { entropy_threshold, custom: secret_patterns.clone() };
+        match api_hunter::gather::mobile::analyze(std::path::Path::new(apk_path), &rules) {
+            Ok(mobile) => {
+                let seeds = mobile.seed_urls(&domain);
+                print!("      [+] {} ({}): {} base URLs | {} endpoints", mobile.file, mobile.platform, mobile.base_urls.len(), mobile.endpoints.len());
+                if !mobile.secrets.is_empty() {
+                    print!(" | {} secrets [!]", mobile.secrets.len());
+                }
+                if !mobile.certificate_pins.is_empty() {
+                    print!(" | {} certificate pins", mobile.certificate_pins.len());
+                }
+                println!();
+                println!("      [+] {} URLs on {} added as candidates", seeds.len(), domain);
+                candidates.extend(seeds);
+                let _ = std::fs::write(paths::artifact(&out_dir, "mobile_analysis.json"), serde_json::to_string_pretty(&mobile).unwrap_or_default());
+            }
+            Err(e) => {
+                println!("      [!] Failed: {}", e);
+                tracing::warn!("Mobile app analysis failed: {:#}", e);
+            }
+        }
+    }
+
     // Drop tx so the receiver knows no more data is coming
     drop(tx);
     
@@ -352,10 +1019,98 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         candidates.append(&mut s);
     }
 
+    candidates.extend(imported_urls.iter().cloned());
     candidates.sort(); candidates.dedup();
     let total_discovered = candidates.len();
+
+    // Analytics/CDN/SaaS hosts are only recorded, never probed
+    let host_exclusion = api_hunter::filter::third_party::HostExclusion::new(!no_default_excludes, &exclude_host, &include_host);
+    let (candidates, third_party_refs) = host_exclusion.partition(candidates, &domain);
+    if !third_party_refs.is_empty() {
+        let excluded_urls: usize = third_party_refs.iter().map(|r| r.count).sum();
+        println!("   [*] {} third-party hosts referenced ({} URLs, not scanned)", third_party_refs.len(), excluded_urls);
+        if let Ok(json) = serde_json::to_string_pretty(&third_party_refs) {
+            let _ = std::fs::write(paths::artifact(&out_dir, "third_party_references.json"), json);
+        }
+        let kept: std::collections::HashSet<&String> = candidates.iter().collect();
+        imported_urls.retain(|u| kept.contains(u));
+    }
     
-    let filtered: Vec<String> = candidates.into_iter().filter(|u| api_hunter::filter::api_patterns::is_api_candidate(u)).collect();
+    // Imported requests are real traffic: they skip the heuristic filter and are probed first
+    let imported_set: std::collections::HashSet<&String> = imported_urls.iter().collect();
+    let mut filtered: Vec<String> = imported_urls.clone();
+    filtered.extend(candidates.iter().filter(|u| !imported_set.contains(u) && api_hunter::filter::api_patterns::is_api_candidate(u)).cloned());
+
+    // Pre-flight: skip hosts that don't accept connections at all. Direct TCP connects would bypass
+    // the proxy, so it doesn't run in anonymous mode or over Tor.
+    let mut host_status: Vec<api_hunter::probe::preflight::HostStatus> = Vec::new();
+    if preflight && !proxied && !filtered.is_empty() {
+        use api_hunter::probe::preflight::{host_ports, retain_reachable, Preflight};
+        let targets = host_ports(&filtered);
+        println!("   [*] Pre-flight: {} hosts...", targets.len());
+        match Preflight::new(Duration::from_secs(timeout.clamp(2, 5)), concurrency as usize) {
+            Ok(checker) => {
+                host_status = checker.check_all(&targets).await;
+                host_status.sort_by(|a, b| a.host.cmp(&b.host));
+                let (kept, skipped) = retain_reachable(filtered, &host_status);
+                filtered = kept;
+                let dead = host_status.iter().filter(|h| !h.is_reachable()).count();
+                if dead > 0 {
+                    println!("      [+] {} reachable, {} dead/unresolved ({} URLs skipped)", host_status.len() - dead, dead, skipped);
+                } else {
+                    println!("      [+] All {} hosts reachable", host_status.len());
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "host_preflight.json"), serde_json::to_string_pretty(&host_status).unwrap_or_default());
+            }
+            Err(e) => println!("      [!] Failed: {}", e),
+        }
+    }
+
+    // Certificates of the HTTPS hosts: weak configurations become findings, SANs under the target
+    // domain are new subdomains. Direct connections only, like the pre-flight.
+    let mut tls_certs: Vec<api_hunter::probe::tls::CertInfo> = Vec::new();
+    if !proxied && !filtered.is_empty() {
+        use api_hunter::probe::tls;
+        let targets = tls::targets(&filtered);
+        if !targets.is_empty() {
+            println!("   [*] TLS certificates: {} hosts...", targets.len());
+            tls_certs = tls::inspect_all(&targets, (concurrency as usize).min(50), Duration::from_secs(timeout.clamp(2, 5))).await;
+            let weak = tls_certs.iter().filter(|c| !c.issues.is_empty()).count();
+            println!("      [+] {} captured, {} with weak TLS", tls_certs.len(), weak);
+            for c in tls_certs.iter().filter(|c| !c.issues.is_empty()).take(10) {
+                let issues: Vec<String> = c.issues.iter().map(|i| i.title()).collect();
+                println!("      [!] {}:{} - {}", c.host, c.port, issues.join(", "));
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "tls_certificates.json"), serde_json::to_string_pretty(&tls_certs).unwrap_or_default());
+
+            let mut seen: std::collections::HashSet<String> = filtered.iter()
+                .filter_map(|u| url::Url::parse(u).ok().and_then(|p| p.host_str().map(str::to_lowercase)))
+                .chain(known_subdomains.iter().map(|s| s.to_lowercase()))
+                .collect();
+            let mut san_hosts: Vec<String> = Vec::new();
+            for host in tls_certs.iter().flat_map(|c| c.in_scope_sans(&domain)) {
+                if seen.insert(host.clone()) {
+                    san_hosts.push(host);
+                }
+            }
+            if !san_hosts.is_empty() {
+                san_hosts.sort();
+                // API-related names become candidates, the same rule as subdomain enumeration
+                let api_hosts: Vec<&String> = san_hosts.iter()
+                    .filter(|h| h.contains("api") || h.contains("rest") || h.contains("graphql") || h.contains("gateway"))
+                    .collect();
+                filtered.extend(api_hosts.iter().map(|h| format!("https://{}/", h)));
+                println!("      [+] {} new subdomains from certificate SANs ({} API-related)", san_hosts.len(), api_hosts.len());
+                let mut report = std::fs::read_to_string(paths::artifact(&out_dir, "subdomains.txt")).unwrap_or_default();
+                report.push_str(&format!("\n[tls-san] - {} subdomains:\n", san_hosts.len()));
+                for h in &san_hosts {
+                    report.push_str(&format!("  - {}\n", h));
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "subdomains.txt"), report);
+            }
+        }
+    }
+    let tls_issuers: std::collections::HashMap<&str, &str> = tls_certs.iter().map(|c| (c.host.as_str(), c.issuer_name.as_str())).collect();
     let filtered_count = filtered.len();
     
     if filtered_count > 0 {
@@ -365,8 +1120,6 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
     }
 
     // Phase 3: Active Probing
-    println!("[>] Probing {} endpoints...", filtered_count);
-    
     // Create HTTP client based on anonymous mode
     let client = if let Some(ref anon) = anonymizer {
         match anon.create_stealth_client(timeout) {
@@ -376,32 +1129,81 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
             Err(e) => {
                 eprintln!("[-] Failed to create stealth client: {}", e);
                 eprintln!("[*] Falling back to direct connection...");
-                reqwest::Client::builder().user_agent("api-hunter/0.1").build()?
+                api_hunter::http_client::builder("runner::scan").user_agent(api_hunter::tls_profile::user_agent_or("api-hunter/0.1")).build()?
             }
         }
     } else {
-        reqwest::Client::builder().user_agent("api-hunter/0.1").build()?
+        api_hunter::http_client::builder("runner::scan").user_agent(api_hunter::tls_profile::user_agent_or("api-hunter/0.1")).build()?
+    };
+
+    // Cheap body-less pass first: drop 404s and redirects to the home page before the full probe.
+    // Imported requests are real traffic and always probed.
+    if prefilter && !filtered.is_empty() {
+        use api_hunter::probe::prefilter::{self, Verdict};
+        let (mut kept, to_check): (Vec<String>, Vec<String>) = filtered.into_iter().partition(|u| imported_set.contains(u));
+        println!("   [*] HEAD pre-filter: {} candidates...", to_check.len());
+        let checked = prefilter::run(&client, &to_check, (concurrency as usize * 4).min(400), Duration::from_secs(timeout.clamp(2, 5))).await;
+        let not_found = checked.iter().filter(|r| r.verdict == Verdict::NotFound).count();
+        let home = checked.iter().filter(|r| r.verdict == Verdict::RedirectHome).count();
+        kept.extend(checked.iter().filter(|r| r.verdict == Verdict::Keep).map(|r| r.url.clone()));
+        println!("      [+] {} kept, {} dropped ({} not found, {} redirect to home)", kept.len(), not_found + home, not_found, home);
+        let dropped: Vec<&prefilter::PrefilterResult> = checked.iter().filter(|r| r.verdict != Verdict::Keep).collect();
+        let _ = std::fs::write(paths::artifact(&out_dir, "prefilter_dropped.json"), serde_json::to_string_pretty(&dropped).unwrap_or_default());
+        filtered = kept;
+    }
+    // Fingerprint each host's not-found page so catch-all routes aren't taken for endpoints
+    let soft404_baselines = if soft404 && !filtered.is_empty() {
+        use api_hunter::probe::soft404;
+        let baselines = soft404::learn_all(&client, &filtered, (concurrency as usize).min(50), Duration::from_secs(timeout.clamp(2, 10))).await;
+        let catch_all = baselines.catch_all().count();
+        println!("   [*] Soft-404 baselines: {} hosts, {} answer unknown paths without a 404", baselines.len(), catch_all);
+        let list: Vec<&soft404::Baseline> = baselines.values().collect();
+        let _ = std::fs::write(paths::artifact(&out_dir, "soft404_baselines.json"), serde_json::to_string_pretty(&list).unwrap_or_default());
+        (catch_all > 0).then(|| Arc::new(baselines))
+    } else {
+        None
     };
+    println!("[>] Probing {} endpoints...", filtered.len());
     
-    let throttle = api_hunter::probe::throttle::Throttle::new(concurrency as usize, per_host as usize);
+    // Shared with the analysis phase, which keeps the per-host limits the probe learned
+    let throttle = std::sync::Arc::new(api_hunter::probe::throttle::Throttle::new(concurrency as usize, per_host as usize));
 
-    let jsonl_path = out_dir.join("target_raw.jsonl");
+    let jsonl_path = paths::artifact(&out_dir, "target_raw.jsonl");
     let (tx_jsonl, rx_jsonl) = tokio::sync::mpsc::channel::<RawEvent>(1024);
     let _jh_jsonl = api_hunter::output::spawn_jsonl_writer(jsonl_path.clone(), rx_jsonl);
 
-    let csv_stream_path = out_dir.join("target_apis_stream.csv");
+    let csv_stream_path = paths::artifact(&out_dir, "target_apis_stream.csv");
     let (tx_csv, rx_csv) = tokio::sync::mpsc::channel::<RawEvent>(1024);
     let _jh_csv = api_hunter::output::spawn_csv_writer(csv_stream_path.clone(), rx_csv);
 
     let probe_timeout = if lite { 3 } else { timeout };
+    let probe_opts = &api_hunter::probe::http_probe::ProbeOptions {
+        aggressive,
+        store_body: store_bodies.map(|kb| api_hunter::probe::body::BodyCapture { max_bytes: kb.max(1) * 1024, compress: compress_bodies }),
+        soft404: soft404_baselines,
+        method_matrix,
+        ..api_hunter::probe::http_probe::ProbeOptions::new(probe_timeout, retries as usize)
+    };
 
     use futures::stream::{self, StreamExt};
-    let cand_vec = filtered;
+    // Most interesting candidates first, so a scan timeout cuts off the least interesting ones.
+    // Imported requests are real traffic and go before everything else.
+    let mut probe_queue = api_hunter::scoring::priority::ProbeQueue::new();
+    for url in filtered {
+        let boost = if imported_set.contains(&url) { 100 } else { 0 };
+        probe_queue.push(url, boost);
+    }
     let client_ref = &client;
-    let throttle_ref = &throttle;
+    let throttle_ref: &api_hunter::probe::throttle::Throttle = &throttle;
 
-    let total = cand_vec.len();
+    let total = probe_queue.len();
     let processed = Arc::new(AtomicUsize::new(0));
+    let soft_404s = Arc::new(AtomicUsize::new(0));
+    let imported_ref = &imported_set;
+    let tls_issuers_ref = &tls_issuers;
+    // Credentials can expire mid-scan; a wave of 401s is reported once
+    let session_monitor = api_hunter::identify::has_auth().then(api_hunter::auth::session::SessionMonitor::new);
+    let session_ref = session_monitor.as_ref();
     let mut results: Vec<RawEvent> = Vec::new();
 
     // WAF Detector (always enabled now)
@@ -417,8 +1219,14 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         None
     };
     let jwt_results = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let probe_failures = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let response_secrets = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let sse_captures = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let pii_reports = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let error_reports = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let body_secret_rules = &api_hunter::gather::js_deep_analyzer::SecretRules { entropy_threshold, custom: secret_patterns.clone() };
 
-    let stream = stream::iter(cand_vec.into_iter())
+    let stream = stream::iter(probe_queue)
         .map(|cand| {
             let client = client_ref;
             let throttle = throttle_ref;
@@ -430,6 +1238,12 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
             let waf_detections = waf_detections.clone();
             let jwt_analyzer_ref = jwt_analyzer.clone();
             let jwt_results_ref = jwt_results.clone();
+            let probe_failures = probe_failures.clone();
+            let response_secrets = response_secrets.clone();
+            let sse_captures = sse_captures.clone();
+            let pii_reports = pii_reports.clone();
+            let error_reports = error_reports.clone();
+            let soft_404s = soft_404s.clone();
             async move {
                 // Human-like delay in anonymous mode (burst + pause pattern)
                 if let Some(anon) = anon_ref {
@@ -438,55 +1252,93 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
                 
                 let idx = processed.fetch_add(1, Ordering::SeqCst) + 1;
                 tracing::debug!("[{}/{}] Probing: {}", idx, total, cand);
-                let res = api_hunter::probe::http_probe::probe_url(client, &cand, probe_timeout, Some(throttle), retries as usize, 200, 5000, aggressive).await;
+                let res = api_hunter::probe::http_probe::probe_url(client, &cand, Some(throttle), probe_opts).await.map(|mut ev| {
+                    let host = url::Url::parse(&ev.final_url).ok().filter(|u| u.scheme() == "https").and_then(|u| u.host_str().map(str::to_lowercase));
+                    if let Some(issuer) = host.and_then(|h| tls_issuers_ref.get(h.as_str())) {
+                        ev.tls_issuer = Some(issuer.to_string());
+                    }
+                    ev
+                });
+                if let (Ok(ev), Some(monitor)) = (&res, session_ref) {
+                    if monitor.record(ev.status) {
+                        println!("\n   [!] Session expired? Most of the last {} responses were 401 - refresh the credentials and rerun", api_hunter::auth::session::WINDOW);
+                    }
+                }
                 match res {
+                    Ok(mut ev) if ev.annotations.contains(&Annotation::Soft404) && !imported_ref.contains(&cand) => {
+                        // Kept in the raw log only
+                        soft_404s.fetch_add(1, Ordering::Relaxed);
+                        tracing::debug!("[{}/{}] {} -> {} (soft-404)", idx, total, cand, ev.status);
+                        ev.score = api_hunter::scoring::score::score_event(&ev);
+                        let _ = tx_jsonl.send(ev).await;
+                        None
+                    }
                     Ok(mut ev) => {
                         ev.score = api_hunter::scoring::score::score_event(&ev);
-                        if let Some(ref js) = ev.json_sample { let keys = api_hunter::enrich::json_shape::detect_keys(js); for k in keys.iter().take(5) { ev.notes.push(format!("key:{}", k)); } }
+                        if let Some(ref js) = ev.json_sample { let keys = api_hunter::enrich::json_shape::detect_keys(js); for k in keys.iter().take(5) { ev.annotate(Annotation::JsonKey { key: k.to_string() }); } }
                         
-                        // WAF Detection (passive - always active)
-                        if let Some(_detector) = waf_detector_ref {
-                            let server = ev.server.as_deref().unwrap_or("");
-                            let mut waf_found = None;
-                            
-                            if server.to_lowercase().contains("cloudflare") {
-                                waf_found = Some("Cloudflare");
-                            } else if server.to_lowercase().contains("akamai") {
-                                waf_found = Some("Akamai");
-                            } else if server.to_lowercase().contains("sucuri") {
-                                waf_found = Some("Sucuri");
-                            } else if server.to_lowercase().contains("imperva") || server.to_lowercase().contains("incapsula") {
-                                waf_found = Some("Imperva");
-                            } else if server.to_lowercase().contains("big-ip") || server.to_lowercase().contains("bigip") {
-                                waf_found = Some("F5 BIG-IP");
-                            } else if server.to_lowercase().contains("barracuda") {
-                                waf_found = Some("Barracuda");
-                            } else if server.to_lowercase().contains("fortiweb") {
-                                waf_found = Some("FortiWeb");
+                        // WAF Detection (passive - always active): signatures on the captured headers,
+                        // cookie names and body (server, cf-ray, x-iinfo, incap_ses_, ...)
+                        if let Some(detector) = waf_detector_ref {
+                            let detection = detector.detect_event(&ev);
+                            if detection.waf_type != api_hunter::waf::WafType::None && ev.waf().is_none() {
+                                ev.annotate(Annotation::WafDetected { waf: detection.waf_type.name().to_string() });
                             }
-                            
-                            if let Some(waf_name) = waf_found {
-                                ev.notes.push(format!("WAF:{}", waf_name));
-                                let mut detections = waf_detections.lock();
-                                *detections.entry(waf_name.to_string()).or_insert(0) += 1;
+                            if let Some(waf_name) = ev.waf() {
+                                *waf_detections.lock().entry(waf_name.to_string()).or_insert(0) += 1;
                             }
                             
                             // Check for WAF block response patterns
                             if ev.status == 403 || ev.status == 406 || ev.status == 429 {
-                                if !ev.notes.iter().any(|n| n.starts_with("WAF:")) {
-                                    ev.notes.push("WAF:UnknownBlock".to_string());
+                                if ev.waf().is_none() {
+                                    ev.annotate(Annotation::WafBlock);
                                 }
                             }
                         }
                         
+                        // Secrets in stored bodies (HTML error pages, config endpoints, debug output); event
+                        // streams get the secret and PII extractors on the sampled events
+                        let event_stream = ev.content_type.as_deref().is_some_and(api_hunter::probe::sse::is_event_stream);
+                        if let Some(text) = ev.body.as_ref().and_then(|b| b.text()) {
+                            if event_stream {
+                                if let Some(capture) = api_hunter::probe::sse::analyze(&cand, &text, body_secret_rules) {
+                                    ev.annotate(Annotation::EventStream { events: capture.events });
+                                    if !capture.secrets.is_empty() {
+                                        ev.annotate(Annotation::SecretsFound { count: capture.secrets.len() });
+                                        response_secrets.lock().extend(capture.secrets.iter().cloned());
+                                    }
+                                    sse_captures.lock().push(capture);
+                                }
+                            } else {
+                                let secrets = api_hunter::gather::js_deep_analyzer::JsDeepAnalyzer::extract_secrets_with_rules(&text, &cand, body_secret_rules);
+                                if !secrets.is_empty() {
+                                    ev.annotate(Annotation::SecretsFound { count: secrets.len() });
+                                    response_secrets.lock().extend(secrets);
+                                }
+                            }
+                        }
+                        
+                        // PII in the body or JSON sample; streams were scanned event by event above
+                        if !event_stream {
+                            let matches = api_hunter::enrich::pii::scan_event(&ev);
+                            if !matches.is_empty() {
+                                ev.annotate(Annotation::PiiFound { count: matches.len() });
+                                pii_reports.lock().push(api_hunter::enrich::pii::PiiReport { url: cand.clone(), status: ev.status, matches });
+                            }
+                            // Debug pages and stack traces, classified by framework
+                            if let Some(disclosure) = api_hunter::analyze::error_disclosure::classify_event(&ev) {
+                                error_reports.lock().push(api_hunter::analyze::error_disclosure::ErrorReport { url: cand.clone(), status: ev.status, disclosure });
+                            }
+                        }
+                        
                         // JWT Token Analysis (if enabled)
                         if let Some(ref analyzer) = jwt_analyzer_ref {
-                            // Try to extract tokens from response (use json_sample if available)
-                            let body_text = if let Some(ref json) = ev.json_sample {
+                            // Stored body if available (--store-bodies), else the JSON sample
+                            let body_text = if let Some(text) = ev.body.as_ref().and_then(|b| b.text()) {
+                                text
+                            } else if let Some(ref json) = ev.json_sample {
                                 serde_json::to_string(json).unwrap_or_default()
                             } else {
-                                // For non-JSON responses, we'd need the body. Since we don't store it,
-                                // we'll skip JWT extraction for now or only check JSON responses
                                 String::new()
                             };
                             
@@ -494,7 +1346,7 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
                                 let tokens = analyzer.extract_tokens_from_response(&body_text);
                                 if !tokens.is_empty() {
                                     tracing::info!("Found {} JWT token(s) in response from {}", tokens.len(), cand);
-                                    ev.notes.push(format!("JWT:{}", tokens.len()));
+                                    ev.annotate(Annotation::JwtFound { count: tokens.len() });
                                     
                                     // Analyze each token
                                     for token in tokens {
@@ -516,6 +1368,7 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
                     }
                     Err(e) => {
                         tracing::debug!("[{}/{}] {} -> Error: {}", idx, total, cand, e);
+                        probe_failures.lock().push(api_hunter::probe::failure::ProbeFailure::from_error(&cand, &e));
                         None
                     }
                 }
@@ -536,7 +1389,10 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
     match tokio::time::timeout(scan_timeout, scan_fut).await {
         Ok(Ok(_)) => tracing::info!("Probe stream completed within {}s timeout", timeout),
         Ok(Err(e)) => tracing::error!("Probe stream aborted with error: {}", e),
-        Err(_) => tracing::warn!("Global scan timeout reached ({}s), aborting remaining probes", timeout),
+        Err(_) => {
+            tracing::warn!("Global scan timeout reached ({}s), aborting remaining probes", timeout);
+            println!("   [!] Scan timeout ({}s) reached after {}/{} candidates; the highest-priority ones were probed first", timeout, processed.load(Ordering::SeqCst), total);
+        }
     }
 
     tracing::debug!("Flushing output writers...");
@@ -545,12 +1401,131 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         tracing::warn!("Output writers did not finish within 5s");
     }
 
-    let refs: Vec<&RawEvent> = results.iter().collect();
-    let success_count = refs.len();
+    let throttled = throttle.throttled_hosts();
+    if !throttled.is_empty() {
+        let sent: u32 = throttled.iter().map(|h| h.throttled).sum();
+        println!("   [!] {} hosts rate-limited the scan ({} responses with 429/503); concurrency reduced", throttled.len(), sent);
+        for h in throttled.iter().take(5) {
+            println!("      [-] {}: {}x, {} concurrent at the end", h.host, h.throttled, h.limit);
+        }
+    }
+    let slowdowns = throttle.slowdowns();
+    if !slowdowns.is_empty() {
+        println!("   [!] {} hosts blocked too many requests (WAF); timing lowered for them", slowdowns.len());
+        for s in slowdowns.iter().take(5) {
+            println!("      [-] {}: {} concurrent, {}ms between requests", s.host, s.per_host, s.delay.as_millis());
+        }
+    }
+    if let Some(at) = session_monitor.as_ref().and_then(|m| m.expired_at()) {
+        println!("   [!] Authentication lost after ~{} responses; later results are likely unauthenticated", at);
+    }
+    let soft_404s = soft_404s.load(Ordering::Relaxed);
+    if soft_404s > 0 {
+        println!("   [-] {} responses matched their host's not-found page (soft-404), excluded from results", soft_404s);
+    }
+    let non_get = results.iter().filter(|e| e.annotations.iter().any(|a| matches!(a, Annotation::AcceptsMethod { .. }))).count();
+    if non_get > 0 {
+        println!("   [+] {} routes only answer non-GET methods", non_get);
+    }
+    let probe_failures = std::mem::take(&mut *probe_failures.lock());
+    if !probe_failures.is_empty() {
+        let counts = api_hunter::probe::failure::count_by_kind(&probe_failures);
+        let breakdown: Vec<String> = counts.iter().map(|(kind, n)| format!("{} {}", n, kind)).collect();
+        println!("   [!] {} probes failed: {}", probe_failures.len(), breakdown.join(", "));
+        let _ = api_hunter::probe::failure::write_failures(&paths::artifact(&out_dir, "failures.jsonl"), &probe_failures);
+    }
+    let mut response_secrets = std::mem::take(&mut *response_secrets.lock());
+    if !response_secrets.is_empty() {
+        response_secrets.sort_by(|a, b| (&a.value, &a.source_file).cmp(&(&b.value, &b.source_file)));
+        response_secrets.dedup_by(|a, b| a.value == b.value && a.source_file == b.source_file);
+        println!("   [!] {} secrets in response bodies", response_secrets.len());
+        let _ = std::fs::write(paths::artifact(&out_dir, "response_secrets.json"), serde_json::to_string_pretty(&response_secrets).unwrap_or_default());
+    }
+    let mut pii_reports: Vec<api_hunter::enrich::pii::PiiReport> = std::mem::take(&mut *pii_reports.lock());
+    if !pii_reports.is_empty() {
+        pii_reports.sort_by(|a, b| a.url.cmp(&b.url));
+        let values: usize = pii_reports.iter().map(|r| r.matches.len()).sum();
+        println!("   [!] {} endpoints leak PII ({} values)", pii_reports.len(), values);
+        let _ = std::fs::write(paths::artifact(&out_dir, "pii_findings.json"), serde_json::to_string_pretty(&pii_reports).unwrap_or_default());
+    }
+    let mut error_reports: Vec<api_hunter::analyze::error_disclosure::ErrorReport> = std::mem::take(&mut *error_reports.lock());
+    if !error_reports.is_empty() {
+        error_reports.sort_by(|a, b| a.url.cmp(&b.url));
+        let debug = error_reports.iter().filter(|r| matches!(r.disclosure.severity.as_str(), "CRITICAL" | "HIGH")).count();
+        println!("   [!] {} endpoints return verbose errors ({} debug pages or credential leaks)", error_reports.len(), debug);
+        let _ = std::fs::write(paths::artifact(&out_dir, "error_disclosure.json"), serde_json::to_string_pretty(&error_reports).unwrap_or_default());
+    }
+    let sse_captures: Vec<api_hunter::probe::sse::SseCapture> = std::mem::take(&mut *sse_captures.lock());
+    if !sse_captures.is_empty() {
+        let events: usize = sse_captures.iter().map(|c| c.events).sum();
+        println!("   [+] {} event streams sampled ({} events)", sse_captures.len(), events);
+        for c in sse_captures.iter().filter(|c| !c.secrets.is_empty() || !c.pii.is_empty()) {
+            println!("      [!] {}: {} secrets, {} PII values in the stream", c.url, c.secrets.len(), c.pii.len());
+        }
+        let _ = std::fs::write(paths::artifact(&out_dir, "sse_streams.json"), serde_json::to_string_pretty(&sse_captures).unwrap_or_default());
+    }
+    let retried = results.iter().filter(|e| e.attempts > 1).count();
+    if retried > 0 {
+        tracing::info!("{} probes succeeded after retrying", retried);
+    }
+
+    // Active WAF fingerprinting: a benign and an attack-looking request per host
+    let fingerprint_hosts: Vec<String> = {
+        let mut seen = std::collections::HashSet::new();
+        results.iter()
+            .filter_map(|e| url::Url::parse(&e.final_url).ok())
+            .filter_map(|u| {
+                let host = u.host_str()?.to_ascii_lowercase();
+                seen.insert(host).then(|| format!("{}/", u.origin().ascii_serialization()))
+            })
+            .take(25)
+            .collect()
+    };
+    if !fingerprint_hosts.is_empty() {
+        let waf_http = api_hunter::http_client::HttpClient::new(client.clone())
+            .with(api_hunter::http_client::Logging("runner::waf"))
+            .with(api_hunter::http_client::Throttled(throttle.clone()));
+        let detector = api_hunter::waf::WafDetector::new();
+        let fingerprint_timeout = Duration::from_secs(timeout.clamp(2, 10));
+        let fingerprints: Vec<api_hunter::waf::HostFingerprint> = stream::iter(fingerprint_hosts)
+            .map(|url| {
+                let (waf_http, detector) = (&waf_http, &detector);
+                async move { detector.fingerprint_host(waf_http, &url, fingerprint_timeout).await }
+            })
+            .buffer_unordered(5)
+            .filter_map(|f| async move { f })
+            .collect()
+            .await;
+        let confident: Vec<&api_hunter::waf::HostFingerprint> = fingerprints.iter()
+            .filter(|f| f.waf_type != api_hunter::waf::WafType::None && f.confidence >= api_hunter::waf::fingerprint::MIN_CONFIDENCE)
+            .collect();
+        for f in &confident {
+            println!("   [*] WAF fingerprint: {} -> {} ({:.0}% confidence{})", f.host, f.waf, f.confidence * 100.0, if f.blocks_attacks { ", blocks attacks" } else { "" });
+            // Endpoints the passive signatures missed get the host's WAF
+            for ev in results.iter_mut().filter(|e| e.waf().is_none()) {
+                if url::Url::parse(&ev.final_url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)).as_deref() == Some(f.host.as_str()) {
+                    ev.annotate(Annotation::WafDetected { waf: f.waf.clone() });
+                    *waf_detections.lock().entry(f.waf.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+        let _ = std::fs::write(paths::artifact(&out_dir, "waf_fingerprints.json"), serde_json::to_string_pretty(&fingerprints).unwrap_or_default());
+    }
+
+    // One representative per response template in the sorted outputs
+    let clusters = api_hunter::enrich::simhash::cluster(&mut results, api_hunter::enrich::simhash::DEFAULT_MAX_DISTANCE);
+    if !clusters.is_empty() {
+        let clustered: usize = clusters.iter().map(|c| c.size).sum();
+        println!("   [*] {} near-identical responses grouped into {} clusters", clustered, clusters.len());
+        let _ = std::fs::write(paths::artifact(&out_dir, "clusters.json"), serde_json::to_string_pretty(&clusters).unwrap_or_default());
+    }
+
+    let refs: Vec<&RawEvent> = api_hunter::enrich::simhash::representatives(&results, &clusters);
+    let success_count = results.len();
     tracing::info!("Generating final reports for {} successful probes...", success_count);
     
-    let csv_path = out_dir.join("target_apis_sorted.csv");
-    let top_path = out_dir.join("target_top.txt");
+    let csv_path = paths::artifact(&out_dir, "target_apis_sorted.csv");
+    let top_path = paths::artifact(&out_dir, "target_top.txt");
     write_csv(&csv_path, &refs)?;
     write_top_txt(&top_path, &refs)?;
 
@@ -562,8 +1537,12 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
     if scan_vulns && success_count > 0 {
         println!("[*] Vulnerability scanning...");
         
+        let http = api_hunter::http_client::HttpClient::new(client.clone())
+            .with(api_hunter::http_client::Logging("runner::analysis"))
+            .with(api_hunter::http_client::Throttled(throttle.clone()))
+            .with(api_hunter::http_client::Retry::new(retries as usize));
         let analysis_timeout = tokio::time::Duration::from_secs(120);
-        match tokio::time::timeout(analysis_timeout, run_deep_analysis(&client, &results, scan_admin, aggressive, &out_dir, &domain)).await {
+        match tokio::time::timeout(analysis_timeout, run_deep_analysis(&http, &results, scan_admin, aggressive, &out_dir, &domain)).await {
             Ok(Ok(())) => {
                 // Silently completed
             }
@@ -576,7 +1555,7 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         };
         
         // Read findings from analysis summary
-        if let Ok(summary_content) = std::fs::read_to_string(out_dir.join("analysis_summary.txt")) {
+        if let Ok(summary_content) = std::fs::read_to_string(paths::artifact(&out_dir, "analysis_summary.txt")) {
             for line in summary_content.lines() {
                 if line.contains("Critical Issues:") {
                     critical_findings = line.split(':').nth(1).and_then(|s| s.trim().parse().ok()).unwrap_or(0);
@@ -597,19 +1576,708 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         // Admin scanning is handled in run_deep_analysis - no additional output
     }
 
+    // Origins the probe reached and the in-scope check for URLs found along the way, shared by the active phases
+    let mut origins: Vec<String> = results.iter().filter_map(|e| api_hunter::probe::soft404::origin_of(&e.final_url)).collect();
+    origins.sort();
+    origins.dedup();
+    let on_target = |u: &str| api_hunter::filter::third_party::on_target(u, &domain);
+
     // Phase 6: Aggressive Testing (Parameter Fuzzing, IDOR)
+    // With --bypass-waf, injection payloads a host's WAF blocks are resent through the transforms recommended for it
+    let waf_mutators = if bypass_waf {
+        api_hunter::waf::WafMutators::from_events(&results)
+    } else {
+        api_hunter::waf::WafMutators::new()
+    };
+
+    let mut pp_findings: Vec<api_hunter::fuzz::prototype_pollution::PrototypePollutionFinding> = Vec::new();
+    let mut hpp_findings: Vec<api_hunter::fuzz::hpp::HppFinding> = Vec::new();
+    // Upload test results, for the report
+    let mut upload_reports: Vec<api_hunter::fuzz::upload::UploadReport> = Vec::new();
+    let mut rate_limit_reports: Vec<api_hunter::probe::rate_limit_bypass::RateLimitReport> = Vec::new();
+    let mut oob_findings: Vec<api_hunter::oob::OobFinding> = Vec::new();
+    let mut json_findings: Vec<api_hunter::fuzz::json_fuzzer::JsonFuzzFinding> = Vec::new();
+    // Brute-forced parameters also go into the generated OpenAPI spec
+    let mut discovered_params: Vec<api_hunter::fuzz::param_bruteforce::DiscoveredParam> = Vec::new();
     if aggressive && success_count > 0 {
         println!("[~] Aggressive testing...");
-        
+        if !waf_mutators.is_empty() {
+            let hosts: Vec<String> = waf_mutators.hosts().into_iter().map(|(host, waf)| format!("{} ({})", host, waf)).collect();
+            println!("   [*] WAF-aware payload mutation on {}", hosts.join(", "));
+        }
+
+        // Brute force runs under the throttle the probe learned
+        let fuzz_http = api_hunter::http_client::HttpClient::new(client.clone())
+            .with(api_hunter::http_client::Logging("runner::fuzz"))
+            .with(api_hunter::http_client::Throttled(throttle.clone()))
+            .with(api_hunter::http_client::Retry::new(retries as usize));
+
+        // Path brute force below every probed host
+        {
+            use api_hunter::fuzz::path_bruteforce::{self, BruteforceConfig, PathBruteforcer};
+            let mut words: Vec<String> = path_bruteforce::DEFAULT_WORDS.iter().map(|w| w.to_string()).collect();
+            if let Some(path) = &path_wordlist {
+                words.extend(path_bruteforce::load_wordlist(std::path::Path::new(path))?);
+            }
+            let config = BruteforceConfig {
+                words,
+                extensions: path_extensions.clone(),
+                depth: path_depth,
+                concurrency: (per_host as usize).max(1),
+                timeout: Duration::from_secs(timeout.clamp(2, 10)),
+                ..BruteforceConfig::default()
+            };
+            println!("   [*] Path brute force: {} paths on {} host(s), depth {}", path_bruteforce::paths(&config.words, &config.extensions).len(), origins.len(), config.depth);
+            let bruteforcer = PathBruteforcer::new(&fuzz_http, config);
+            let known: std::collections::HashSet<&str> = results.iter().map(|e| e.final_url.trim_end_matches('/')).collect();
+            let mut path_hits = Vec::new();
+            for origin in &origins {
+                path_hits.extend(bruteforcer.run(origin).await);
+            }
+            let new_hits: Vec<_> = path_hits.iter().filter(|h| !known.contains(h.url.trim_end_matches('/'))).collect();
+            println!("      [+] {} paths found, {} not seen before", path_hits.len(), new_hits.len());
+            for hit in new_hits.iter().take(15) {
+                println!("      [+] {} -> {}{}", hit.url, hit.status, if hit.directory { " (dir)" } else { "" });
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "path_bruteforce.json"), serde_json::to_string_pretty(&path_hits).unwrap_or_default());
+        }
+
+        // Parameter names the endpoints accept without advertising them
+        {
+            use api_hunter::fuzz::param_bruteforce::{self, ParamBruteforceConfig, ParamBruteforcer, ParamLocation};
+            let mut words = param_bruteforce::default_words();
+            if let Some(path) = &param_wordlist {
+                words.extend(param_bruteforce::load_wordlist(std::path::Path::new(path))?);
+            }
+            let config = ParamBruteforceConfig { words, timeout: Duration::from_secs(timeout.clamp(2, 10)), ..ParamBruteforceConfig::default() };
+            let bruteforcer = ParamBruteforcer::new(&fuzz_http, config);
+            let endpoints: Vec<&RawEvent> = results.iter()
+                .filter(|e| (200..300).contains(&e.status) && !e.final_url.contains('?'))
+                .take(20)
+                .collect();
+            println!("   [*] Parameter brute force on {} endpoints...", endpoints.len());
+            for ev in endpoints {
+                discovered_params.extend(bruteforcer.run(&ev.final_url, ParamLocation::Query).await);
+                let accepts_post = ev.allowed_methods.iter().any(|m| m == "POST") || ev.methods.get("POST").is_some_and(|s| !matches!(s, 404 | 405 | 501));
+                if accepts_post {
+                    discovered_params.extend(bruteforcer.run(&ev.final_url, ParamLocation::Json).await);
+                }
+            }
+            println!("      [+] {} parameters found", discovered_params.len());
+            for p in discovered_params.iter().take(15) {
+                println!("      [+] {} {:?} on {} ({:?})", p.name, p.location, p.url, p.reason);
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "param_bruteforce.json"), serde_json::to_string_pretty(&discovered_params).unwrap_or_default());
+
+            // Body parameters go to the mass-assignment tester
+            let tester = api_hunter::fuzz::mass_assignment::MassAssignmentTester::new(fuzz_http.clone());
+            let mut by_url: std::collections::BTreeMap<&str, Vec<String>> = std::collections::BTreeMap::new();
+            for p in discovered_params.iter().filter(|p| p.location == ParamLocation::Json) {
+                by_url.entry(p.url.as_str()).or_default().push(p.name.clone());
+            }
+            let mut mass_assignment_results = Vec::new();
+            for (url, names) in by_url {
+                match tester.test_discovered(url, "POST", &names).await {
+                    Ok(result) if !result.vulnerabilities.is_empty() => {
+                        println!("      [!] {} discovered body parameters writable on {}", result.vulnerabilities.len(), url);
+                        mass_assignment_results.push(result);
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Mass assignment test failed for {}: {}", url, e),
+                }
+            }
+            if !mass_assignment_results.is_empty() {
+                let _ = std::fs::write(paths::artifact(&out_dir, "param_mass_assignment.json"), serde_json::to_string_pretty(&mass_assignment_results).unwrap_or_default());
+            }
+        }
+
+        // JSON request bodies: captured bodies plus bodies built from the OpenAPI specs the probe found
+        let mut seeds = body_seeds.clone();
+        for ev in results.iter().filter(|e| e.status == 200 && api_hunter::fuzz::json_fuzzer::is_spec_url(&e.final_url)) {
+            let Ok(resp) = fuzz_http.get(&ev.final_url).await else { continue };
+            let Ok(text) = resp.text().await else { continue };
+            let spec: Option<serde_json::Value> = serde_json::from_str(&text).ok().or_else(|| serde_yaml::from_str(&text).ok());
+            if let Some(spec) = spec {
+                seeds.extend(api_hunter::fuzz::json_fuzzer::seeds_from_openapi(&spec, &ev.final_url));
+            }
+        }
+        // Specs may list servers of other parties
+        seeds.retain(|s| url::Url::parse(&s.url).ok().and_then(|u| u.host_str().map(|h| h == domain || h.ends_with(&format!(".{}", domain)))).unwrap_or(false));
+        seeds.dedup_by(|a, b| a.method == b.method && a.url == b.url);
+        seeds.truncate(30);
+
+        // JSON body fuzzing
+        {
+            use api_hunter::fuzz::json_fuzzer::JsonFuzzer;
+            if !seeds.is_empty() {
+                println!("   [*] JSON body fuzzing: {} request bodies...", seeds.len());
+                let fuzzer = JsonFuzzer::new(&fuzz_http, Duration::from_secs(timeout.clamp(2, 10))).with_waf(&waf_mutators);
+                for seed in &seeds {
+                    json_findings.extend(fuzzer.run(seed).await);
+                }
+                if json_findings.is_empty() {
+                    println!("      [-] No injection or type confusion indicators");
+                }
+                for f in json_findings.iter().take(15) {
+                    println!("      [!] {} {} {} ({:?}, {}): {}", f.method, f.url, f.field, f.indicator, f.severity.label(), f.evidence.chars().take(80).collect::<String>());
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "json_fuzz_findings.json"), serde_json::to_string_pretty(&json_findings).unwrap_or_default());
+            }
+        }
+
+        // Prototype pollution: the JSON bodies above, plus JSON endpoints that take POST with an empty object
+        {
+            use api_hunter::fuzz::prototype_pollution::{framework_hint, PrototypePollutionTester};
+            let mut targets: Vec<(String, String, serde_json::Value)> = seeds.iter()
+                .filter(|s| s.body.is_object())
+                .map(|s| (s.method.clone(), s.url.clone(), s.body.clone()))
+                .collect();
+            for ev in results.iter().filter(|e| (200..300).contains(&e.status) && e.content_type.as_deref().is_some_and(|c| c.contains("json"))) {
+                let posts = ev.allowed_methods.iter().any(|m| m == "POST") || ev.methods.get("POST").is_some_and(|s| !matches!(s, 404 | 405 | 501));
+                if posts && !targets.iter().any(|(_, u, _)| u == &ev.final_url) {
+                    targets.push(("POST".to_string(), ev.final_url.clone(), serde_json::json!({})));
+                }
+            }
+            targets.truncate(30);
+            if !targets.is_empty() {
+                println!("   [*] Prototype pollution: {} JSON endpoints...", targets.len());
+                let tester = PrototypePollutionTester::new(&fuzz_http, Duration::from_secs(timeout.clamp(2, 10)));
+                for (method, url, body) in &targets {
+                    // Fingerprint of the probed endpoint, else of another one on the same host
+                    let host = url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string));
+                    let framework = results.iter().find(|e| &e.final_url == url).and_then(|e| framework_hint(&e.headers))
+                        .or_else(|| results.iter()
+                            .filter(|e| url::Url::parse(&e.final_url).ok().and_then(|u| u.host_str().map(str::to_string)) == host)
+                            .find_map(|e| framework_hint(&e.headers)));
+                    if let Some(f) = tester.test(method, url, body, framework.as_deref()).await {
+                        println!("      [!] {} {} ({:?} via {:?}, {}{}): {}", f.method, f.url, f.technique, f.vector, f.severity.label(),
+                            f.framework.as_deref().map(|fw| format!(", {}", fw)).unwrap_or_default(), f.evidence);
+                        pp_findings.push(f);
+                    }
+                }
+                if pp_findings.is_empty() {
+                    println!("      [-] No pollution indicators");
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "prototype_pollution.json"), serde_json::to_string_pretty(&pp_findings).unwrap_or_default());
+            }
+        }
+
+        // Injection points: query parameters seen in the results and the parameters the brute force found
+        let mut points: Vec<(String, String, api_hunter::fuzz::param_bruteforce::ParamLocation)> = Vec::new();
+        for ev in results.iter().filter(|e| (200..300).contains(&e.status)) {
+            let Ok(parsed) = url::Url::parse(&ev.final_url) else { continue };
+            for (name, _) in parsed.query_pairs() {
+                points.push((ev.final_url.clone(), name.into_owned(), api_hunter::fuzz::param_bruteforce::ParamLocation::Query));
+            }
+        }
+        points.extend(discovered_params.iter().map(|p| (p.url.clone(), p.name.clone(), p.location)));
+        let mut seen = std::collections::HashSet::new();
+        points.retain(|p| seen.insert(p.clone()));
+        points.truncate(40);
+
+        // Template injection
+        if !points.is_empty() {
+            use api_hunter::fuzz::ssti::SstiTester;
+            println!("   [*] Template injection: {} parameters...", points.len());
+            let tester = SstiTester::new(&fuzz_http, Duration::from_secs(timeout.clamp(2, 10))).with_waf(&waf_mutators);
+            let mut ssti_findings = Vec::new();
+            for (url, name, location) in &points {
+                if let Some(finding) = tester.test(url, name, *location).await {
+                    println!("      [!] {} in {} ({} engine, {:?}): {}", finding.parameter, finding.url, finding.engine, finding.classification.final_severity, finding.evidence);
+                    ssti_findings.push(finding);
+                }
+            }
+            if ssti_findings.is_empty() {
+                println!("      [-] No template expressions evaluated");
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "ssti_findings.json"), serde_json::to_string_pretty(&ssti_findings).unwrap_or_default());
+        }
+
+        // Parameter pollution: which duplicate wins, and whether that gets a tampered id or price through
+        if !points.is_empty() {
+            use api_hunter::fuzz::hpp::{HppTester, Precedence};
+            println!("   [*] Parameter pollution: {} parameters...", points.len());
+            let tester = HppTester::new(&fuzz_http, Duration::from_secs(timeout.clamp(2, 10)));
+            for (url, name, location) in &points {
+                let Some(finding) = tester.test(url, name, *location).await else { continue };
+                if let (Some(impact), Some(evidence)) = (finding.impact, &finding.evidence) {
+                    println!("      [!] {:?} bypass via {} in {}: {}", impact, finding.parameter, finding.url, evidence);
+                }
+                hpp_findings.push(finding);
+            }
+            let count = |p: Precedence| hpp_findings.iter().flat_map(|f| &f.behaviors).filter(|b| b.precedence == p).count();
+            println!("      [+] Duplicates: {} first wins, {} last wins, {} concatenated, {} rejected",
+                count(Precedence::First), count(Precedence::Last), count(Precedence::Concatenated), count(Precedence::Rejected));
+            let _ = std::fs::write(paths::artifact(&out_dir, "hpp_findings.json"), serde_json::to_string_pretty(&hpp_findings).unwrap_or_default());
+        }
+
+        // Blind SSRF/XXE/injections: payloads with callback hosts, hits on the listener are correlated back
+        if let Some(config) = oob {
+            use api_hunter::oob::{OobServer, OobTester};
+            match OobServer::start(config).await {
+                Ok(server) => {
+                    let xml_targets: Vec<&str> = results.iter()
+                        .filter(|e| (200..300).contains(&e.status))
+                        .filter(|e| e.allowed_methods.iter().any(|m| m == "POST") || e.methods.get("POST").is_some_and(|s| !matches!(s, 404 | 405 | 501)))
+                        .map(|e| e.final_url.as_str())
+                        .take(20)
+                        .collect();
+                    println!("   [*] Out-of-band: {} parameters, {} XML endpoints, callbacks on *.{} (HTTP {})", points.len(), xml_targets.len(), server.domain(), server.http_addr());
+                    let tester = OobTester::new(&fuzz_http, &server, Duration::from_secs(timeout.clamp(2, 10)));
+                    let mut sent = 0;
+                    for (url, name, location) in &points {
+                        sent += tester.inject(url, name, *location).await;
+                    }
+                    for url in xml_targets {
+                        sent += tester.xxe(url).await;
+                    }
+                    println!("      [*] {} payloads sent, waiting {}s for callbacks...", sent, oob_wait.as_secs());
+                    tokio::time::sleep(oob_wait).await;
+                    oob_findings = server.correlate();
+                    if oob_findings.is_empty() {
+                        println!("      [-] No callbacks received");
+                    }
+                    for f in &oob_findings {
+                        let protocols: Vec<String> = f.interactions.iter().map(|i| format!("{:?}", i.protocol)).collect::<std::collections::BTreeSet<_>>().into_iter().collect();
+                        println!("      [!] {:?} in {}{} ({}, {} from {})", f.probe.kind, f.probe.url, f.probe.parameter.as_deref().map(|p| format!(" ({})", p)).unwrap_or_default(), f.severity.label(), protocols.join("+"), f.interactions[0].remote);
+                    }
+                    let _ = std::fs::write(paths::artifact(&out_dir, "oob_findings.json"), serde_json::to_string_pretty(&oob_findings).unwrap_or_default());
+                }
+                Err(e) => println!("   [!] Out-of-band tests skipped: {}", e),
+            }
+        }
+
+        // Endpoints that rate-limited the probe: IP headers, path variants and other API versions
+        {
+            use api_hunter::probe::rate_limit_bypass::RateLimitBypassTester;
+            let mut limited: Vec<&str> = results.iter().filter(|e| e.status == 429).map(|e| e.final_url.as_str()).collect();
+            limited.sort_unstable();
+            limited.dedup();
+            limited.truncate(10);
+            if !limited.is_empty() {
+                println!("   [*] Rate-limit bypass: {} endpoints answered 429...", limited.len());
+                // No throttle layer: it would back off on exactly the 429s being tested
+                let bypass_http = api_hunter::http_client::HttpClient::new(client.clone())
+                    .with(api_hunter::http_client::Logging("runner::rate_limit_bypass"));
+                let tester = RateLimitBypassTester::new(&bypass_http);
+                for url in limited {
+                    let report = tester.test(url).await;
+                    for b in &report.bypasses {
+                        println!("      [!] {} on {} -> {} ({})", b.variant.technique, url, b.status, b.variant.url);
+                    }
+                    rate_limit_reports.push(report);
+                }
+                if rate_limit_reports.iter().all(|r| r.bypasses.is_empty()) {
+                    println!("      [-] No bypass restored a 2xx");
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "rate_limit_bypass.json"), serde_json::to_string_pretty(&rate_limit_reports).unwrap_or_default());
+            }
+        }
+
+        // File uploads (opt-in): forms with a file input on the HTML pages, and upload-like paths
+        if upload_tests {
+            use api_hunter::fuzz::upload::{upload_forms, UploadTarget, UploadTester};
+            let mut targets: Vec<UploadTarget> = Vec::new();
+            for ev in results.iter().filter(|e| e.status == 200 && e.content_type.as_deref().is_some_and(|c| c.contains("html"))).take(30) {
+                let html = match ev.body.as_ref().and_then(|b| b.text()) {
+                    Some(text) => text,
+                    None => match fuzz_http.get(&ev.final_url).await {
+                        Ok(resp) => resp.text().await.unwrap_or_default(),
+                        Err(_) => continue,
+                    },
+                };
+                targets.extend(upload_forms(&html, &ev.final_url));
+            }
+            for ev in results.iter().filter(|e| !matches!(e.status, 404 | 410) && !e.methods.get("POST").is_some_and(|s| matches!(s, 404 | 405 | 501))) {
+                targets.extend(UploadTarget::from_path(&ev.final_url));
+            }
+            // Forms may post to other parties
+            targets.retain(|t| url::Url::parse(&t.url).ok().and_then(|u| u.host_str().map(|h| h == domain || h.ends_with(&format!(".{}", domain)))).unwrap_or(false));
+            let mut seen = std::collections::HashSet::new();
+            targets.retain(|t| seen.insert((t.url.clone(), t.field.clone())));
+            targets.truncate(10);
+            if !targets.is_empty() {
+                println!("   [*] Upload tests: {} endpoints (test files stay on the server)...", targets.len());
+                let tester = UploadTester::new(&fuzz_http, Duration::from_secs(timeout.clamp(5, 15)));
+                for target in &targets {
+                    let report = tester.test(target).await;
+                    if report.baseline.is_none() {
+                        println!("      [-] {}: no test file accepted", target.url);
+                    }
+                    for f in &report.findings {
+                        println!("      [!] {} {:?} ({}): {} - {}", f.url, f.test, f.severity.label(), f.filename, f.evidence);
+                    }
+                    upload_reports.push(report);
+                }
+                let _ = std::fs::write(paths::artifact(&out_dir, "upload_findings.json"), serde_json::to_string_pretty(&upload_reports).unwrap_or_default());
+            }
+        }
+
         // Set a longer timeout for intensive fuzzing
         let fuzz_timeout = tokio::time::Duration::from_secs(60);
-        let _ = tokio::time::timeout(fuzz_timeout, run_param_fuzzing(&client, &results, true, &out_dir)).await;
+        let _ = tokio::time::timeout(fuzz_timeout, run_param_fuzzing(&client, &results, &discovered_params, true, &out_dir)).await;
+    }
+
+    // Drift between the provided spec and the probe results
+    let mut spec_drift: Option<api_hunter::analyze::spec_drift::DriftReport> = None;
+    if let Some(ref documented) = api_spec {
+        println!("[~] API spec drift...");
+        let drift = api_hunter::analyze::spec_drift::compare(documented, &results);
+        if drift.is_empty() {
+            println!("   [-] Every live endpoint is documented and every documented one answers");
+        }
+        for s in drift.shadow.iter().take(15) {
+            match &s.documented_path {
+                Some(_) => println!("   [!] Undocumented {} on {} ({})", s.methods.join(", "), s.path, s.status),
+                None => println!("   [!] Shadow endpoint: {} ({})", s.url, s.status),
+            }
+        }
+        for z in drift.zombies.iter().take(15) {
+            println!("   [i] Zombie endpoint: {} {} ({})", z.methods.join(", "), z.path, z.reason);
+        }
+        let _ = std::fs::write(paths::artifact(&out_dir, "spec_drift.json"), serde_json::to_string_pretty(&drift).unwrap_or_default());
+        spec_drift = Some(drift);
+    }
+
+    // Reverse-engineered API spec of everything observed so far
+    if success_count > 0 {
+        let spec = api_hunter::output::openapi::generate(&domain, &results, &discovered_params);
+        match spec.to_yaml() {
+            Ok(yaml) => {
+                let path = paths::artifact(&out_dir, "openapi.yaml");
+                match std::fs::write(&path, yaml) {
+                    Ok(()) => println!("[=] OpenAPI spec: {} ({} paths, {} operations)", path.display(), spec.paths.len(), spec.operation_count()),
+                    Err(e) => eprintln!("   [!] Failed to write OpenAPI spec: {}", e),
+                }
+            }
+            Err(e) => eprintln!("   [!] Failed to build OpenAPI spec: {}", e),
+        }
+    }
+
+    // Phase 6b: Two-identity BOLA/IDOR test (only GETs, so not tied to --aggressive)
+    let mut bola_findings: Vec<api_hunter::fuzz::bola::BolaFinding> = Vec::new();
+    if let Some((user_a, user_b)) = bola {
+        if success_count > 0 {
+            use api_hunter::fuzz::bola::BolaTester;
+            println!("[~] BOLA test with two identities...");
+            let bola_client = api_hunter::http_client::builder_unauthenticated("fuzz::bola").user_agent(api_hunter::tls_profile::user_agent_or("api-hunter/0.1")).build()?;
+            let tester = BolaTester::new(bola_client, user_a, user_b, Duration::from_secs(timeout.max(5)));
+            let urls: Vec<String> = results.iter().map(|e| e.orig_url.clone()).collect();
+            bola_findings = tester.run(&urls, (concurrency as usize).min(10)).await;
+            if bola_findings.is_empty() {
+                println!("   [-] B could not read any of A's objects");
+            } else {
+                println!("   [!] {} objects of user A readable by user B", bola_findings.len());
+                for f in bola_findings.iter().take(10) {
+                    println!("      [!] {} ({}, {} of A's values)", f.url, f.severity, f.leaked.len());
+                }
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "bola_findings.json"), serde_json::to_string_pretty(&bola_findings).unwrap_or_default());
+        }
+    }
+
+    // JWTs of the scan: its own bearer token first, then tokens seen in responses (--jwt)
+    let mut scan_tokens: Vec<String> = api_hunter::identify::headers().get(reqwest::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|t| api_hunter::security::jwt_forge::decode(t).is_some())
+        .map(|t| vec![t.trim().to_string()])
+        .unwrap_or_default();
+    for analysis in jwt_results.lock().iter() {
+        if !scan_tokens.contains(&analysis.token.raw) {
+            scan_tokens.push(analysis.token.raw.clone());
+        }
+    }
+
+    // JWT forgery (algorithm confusion, kid injection) against endpoints that verify the token
+    let mut forge_findings: Vec<api_hunter::security::ForgeFinding> = Vec::new();
+    if jwt && success_count > 0 {
+        use api_hunter::security::jwt_forge::{self, JwtForger};
+        let tokens: Vec<&String> = scan_tokens.iter().take(jwt_forge::MAX_TOKENS).collect();
+        if !tokens.is_empty() {
+            println!("[~] JWT forgery: {} token(s), algorithm confusion and kid injection...", tokens.len());
+            let forge_client = api_hunter::http_client::builder_unauthenticated("security::jwt_forge").user_agent(api_hunter::tls_profile::user_agent_or("api-hunter/0.1")).build()?;
+            let forger = JwtForger::new(forge_client, Duration::from_secs(timeout.max(5)), &domain).with_exclusion(host_exclusion.clone());
+            let candidates: Vec<String> = results.iter()
+                .filter(|e| (200..300).contains(&e.status) || matches!(e.status, 401 | 403))
+                .map(|e| e.orig_url.clone())
+                .take(jwt_forge::MAX_CANDIDATES)
+                .collect();
+            let forge_origins = &origins[..origins.len().min(3)];
+            for token in &tokens {
+                forge_findings.extend(forger.test_token(token, &candidates, forge_origins).await);
+            }
+            if forge_findings.is_empty() {
+                println!("   [-] No forged token accepted");
+            } else {
+                let mut analyses = jwt_results.lock();
+                for f in &forge_findings {
+                    println!("      [!] {} accepts a forged {} token: {:?} ({})", f.url, f.forged_alg, f.attack, f.variant);
+                    let vulnerability = f.attack.vulnerability();
+                    for a in analyses.iter_mut().filter(|a| f.forged_token.split('.').nth(1) == a.token.raw.split('.').nth(1)) {
+                        if !a.vulnerabilities.contains(&vulnerability) {
+                            a.vulnerabilities.push(vulnerability.clone());
+                        }
+                    }
+                }
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "jwt_forgery.json"), serde_json::to_string_pretty(&forge_findings).unwrap_or_default());
+        }
     }
 
-    // Phase 7: WAF Bypass Techniques
-    if bypass_waf && success_count > 0 {
-        println!("[#] WAF bypass testing...");
-        // WAF bypass techniques would be implemented here
+    // Content-Security-Policy grades of the HTML pages, from the captured headers
+    let mut csp_reports: Vec<api_hunter::analyze::csp::CspReport> = Vec::new();
+    if !lite && success_count > 0 {
+        csp_reports = api_hunter::analyze::csp::analyze_events(&results);
+        if !csp_reports.is_empty() {
+            let weak = csp_reports.iter().filter(|r| matches!(r.analysis.grade, 'D' | 'F')).count();
+            println!("[*] Content-Security-Policy: {} policies graded, {} weak (D/F)", csp_reports.len(), weak);
+            let _ = std::fs::write(paths::artifact(&out_dir, "csp_analysis.json"), serde_json::to_string_pretty(&csp_reports).unwrap_or_default());
+        }
+    }
+
+    // Cookies set anywhere in the probe results: flags, scope, prefixes, lifetime
+    let mut cookie_reports: Vec<api_hunter::analyze::cookies::CookieReport> = Vec::new();
+    if !lite && success_count > 0 {
+        cookie_reports = api_hunter::analyze::cookies::analyze_events(&results, std::time::SystemTime::now());
+        if !cookie_reports.is_empty() {
+            let weak = cookie_reports.iter().filter(|r| matches!(r.severity(), "HIGH" | "MEDIUM")).count();
+            println!("[*] Cookies: {} analyzed ({} session), {} with Medium or worse issues", cookie_reports.len(), cookie_reports.iter().filter(|r| r.session).count(), weak);
+            let _ = std::fs::write(paths::artifact(&out_dir, "cookie_analysis.json"), serde_json::to_string_pretty(&cookie_reports).unwrap_or_default());
+        }
+    }
+
+    // OpenID Connect discovery: metadata, key set, PKCE, redirect_uri validation, token lifetimes
+    let mut oidc_reports: Vec<api_hunter::analyze::oidc::OidcReport> = Vec::new();
+    if !lite && success_count > 0 {
+        use api_hunter::analyze::oidc::{self, OidcAnalyzer};
+        let client_ids = oidc::client_ids(results.iter().flat_map(|e| [e.orig_url.as_str(), e.final_url.as_str()]));
+        let analyzer = OidcAnalyzer::new(Duration::from_secs(timeout.clamp(5, 15)), &domain)?;
+        for origin in origins.iter().take(10) {
+            if let Some(report) = analyzer.analyze(origin, &client_ids, &scan_tokens).await {
+                println!("[*] OpenID Connect: {} ({} keys)", report.config_url, report.keys);
+                for f in &report.findings {
+                    println!("   [!] {}: {}", f.severity, f.detail);
+                }
+                oidc_reports.push(report);
+            }
+        }
+        if !oidc_reports.is_empty() {
+            let _ = std::fs::write(paths::artifact(&out_dir, "oidc_analysis.json"), serde_json::to_string_pretty(&oidc_reports).unwrap_or_default());
+        }
+    }
+
+    // OAuth flows from the JS bundles and scanned authorization URLs. Requests go to endpoints on the
+    // target; third-party identity providers only with --aggressive.
+    let mut oauth_findings: Vec<api_hunter::analyze::oauth::OAuthFinding> = Vec::new();
+    {
+        use api_hunter::analyze::oauth::OAuthTester;
+        use api_hunter::gather::js_deep_analyzer::OAuthFlow;
+        let mut flows = std::mem::take(&mut js_oauth_flows);
+        flows.extend(results.iter().filter_map(|e| OAuthFlow::from_url(&e.orig_url, "scan")));
+        let mut seen = std::collections::HashSet::new();
+        flows.retain(|f| seen.insert((f.authorization_endpoint.clone(), f.client_id.clone(), f.redirect_uri.clone())));
+        flows.truncate(10);
+        if !flows.is_empty() {
+            println!("[~] OAuth flows: {} authorization request(s)...", flows.len());
+            let tester = OAuthTester::new(Duration::from_secs(timeout.clamp(5, 15)), &domain)?;
+            for flow in &flows {
+                for f in tester.test(flow, aggressive || on_target(&flow.authorization_endpoint)).await {
+                    println!("   [!] {}: {:?} {} - {}", f.severity, f.check, f.authorization_endpoint, f.detail);
+                    oauth_findings.push(f);
+                }
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "oauth_findings.json"), serde_json::to_string_pretty(&oauth_findings).unwrap_or_default());
+        }
+    }
+
+    // WebSocket endpoints from the JS bundles; third-party sockets only with --aggressive
+    let mut websocket_reports = Vec::new();
+    {
+        let sockets: Vec<String> = std::mem::take(&mut js_websockets).into_iter()
+            .filter(|u| aggressive || on_target(u)).take(10).collect();
+        if !sockets.is_empty() {
+            println!("[~] WebSocket endpoints: {}...", sockets.len());
+            let tester = api_hunter::probe::websocket::WebSocketTester::with_timeout(Duration::from_secs(timeout.clamp(5, 15)));
+            for socket in &sockets {
+                match tester.analyze(socket).await {
+                    Some(report) => {
+                        for v in &report.vulnerabilities {
+                            println!("   [!] {}: {} {} - {}", v.severity, v.name, report.url, v.description);
+                        }
+                        websocket_reports.push(report);
+                    }
+                    None => println!("   [-] {}: no WebSocket handshake", socket),
+                }
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "websocket_analysis.json"), serde_json::to_string_pretty(&websocket_reports).unwrap_or_default());
+        }
+    }
+
+    // gRPC services: reflection calls without credentials on gRPC and HTTP/2 hosts
+    let mut grpc_reports: Vec<api_hunter::probe::grpc::GrpcReport> = Vec::new();
+    if !lite && success_count > 0 {
+        use api_hunter::probe::grpc::{self, GrpcProber};
+        let origins = grpc::targets(&results, 10);
+        if !origins.is_empty() {
+            println!("[~] gRPC detection on {} HTTP/2 hosts...", origins.len());
+            let prober = GrpcProber::new(Duration::from_secs(timeout.clamp(5, 15)));
+            for origin in &origins {
+                if let Some(report) = prober.probe(origin).await {
+                    match &report.reflection {
+                        Some(_) => println!("   [!] {}: gRPC reflection enabled ({:?}, {} services, {} methods)", report.origin, report.transport, report.services.len(), report.methods()),
+                        None => println!("   [+] {}: gRPC service ({:?}), reflection off", report.origin, report.transport),
+                    }
+                    grpc_reports.push(report);
+                }
+            }
+            if !grpc_reports.is_empty() {
+                let _ = std::fs::write(paths::artifact(&out_dir, "grpc_analysis.json"), serde_json::to_string_pretty(&grpc_reports).unwrap_or_default());
+            }
+        }
+    }
+
+    // Web cache poisoning (unkeyed headers) and cache deception (opt-in), under fresh cache busters
+    let mut cache_reports: Vec<api_hunter::probe::cache_poisoning::CacheReport> = Vec::new();
+    if cache_poisoning && success_count > 0 {
+        use api_hunter::probe::cache_poisoning::{self, CachePoisoner};
+        let targets = cache_poisoning::targets(&results, 10);
+        if !targets.is_empty() {
+            println!("[~] Cache poisoning and deception on {} endpoints...", targets.len());
+            let poisoner = CachePoisoner::new(Duration::from_secs(timeout.clamp(5, 15)))?;
+            for url in &targets {
+                let Some(report) = poisoner.test(url).await else { continue };
+                if !report.buster_keyed {
+                    println!("   [-] {}: query string not in the cache key, header tests skipped", url);
+                }
+                for f in &report.findings {
+                    println!("   [!] {} ({}{}): {} - {}", url, f.severity, if f.confirmed { ", confirmed" } else { "" }, f.vector, f.effect);
+                }
+                cache_reports.push(report);
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "cache_poisoning.json"), serde_json::to_string_pretty(&cache_reports).unwrap_or_default());
+        }
+    }
+
+    // security.txt: where to report what this scan found
+    let mut disclosure_policies: Vec<api_hunter::discover::security_txt::SecurityTxt> = Vec::new();
+    if success_count > 0 {
+        use api_hunter::discover::security_txt::SecurityTxtFetcher;
+        let fetcher = SecurityTxtFetcher::new(Duration::from_secs(timeout.clamp(5, 15)))?;
+        for origin in origins.iter().take(10) {
+            let Some(txt) = fetcher.fetch(origin).await else { continue };
+            match txt.report_to() {
+                Some(report_to) => println!("[*] security.txt: {} - report to {}", txt.url, report_to),
+                None => println!("[*] security.txt: {} (no contact)", txt.url),
+            }
+            for issue in &txt.issues {
+                println!("   [-] {}", issue);
+            }
+            disclosure_policies.push(txt);
+        }
+        if !disclosure_policies.is_empty() {
+            let _ = std::fs::write(paths::artifact(&out_dir, "security_txt.json"), serde_json::to_string_pretty(&disclosure_policies).unwrap_or_default());
+        }
+    }
+
+    // API gateways (Kong, Apigee, AWS API Gateway, Tyk) from headers and error bodies, then their admin
+    // APIs, default routes and stages
+    let mut gateway_reports: Vec<api_hunter::analyze::gateway::GatewayReport> = Vec::new();
+    if !lite && success_count > 0 {
+        use api_hunter::analyze::gateway::{self, GatewayAnalyzer};
+        gateway_reports = gateway::fingerprint(&results, 10);
+        if !gateway_reports.is_empty() {
+            println!("[~] API gateways on {} origins...", gateway_reports.len());
+            let analyzer = GatewayAnalyzer::new(Duration::from_secs(timeout.clamp(5, 15)))?;
+            for report in gateway_reports.iter_mut() {
+                analyzer.check(report).await;
+                println!("   [+] {}: {}{}", report.origin, report.gateway.name(), report.version.as_deref().map(|v| format!(" {}", v)).unwrap_or_default());
+                for f in &report.findings {
+                    println!("   [!] {}: {} - {}", f.severity, f.url, f.detail);
+                }
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "gateway_analysis.json"), serde_json::to_string_pretty(&gateway_reports).unwrap_or_default());
+        }
+    }
+
+    // Phase 6c: HTTP/2 vs HTTP/3 (opt-in). QUIC can't go through the proxy.
+    let mut h3_findings: Vec<api_hunter::probe::http3::H3Finding> = Vec::new();
+    if http3 && success_count > 0 {
+        use api_hunter::probe::http3;
+        if proxied {
+            println!("[!] HTTP/3 comparison skipped: QUIC can't be sent through the proxy");
+        } else {
+            println!("[*] HTTP/3 comparison...");
+            let report = http3::run(&results, Duration::from_secs(timeout.clamp(5, 15)), concurrency as usize).await;
+            if report.hosts.is_empty() {
+                println!("   [-] No host advertises HTTP/3");
+            } else if !report.probed {
+                println!("   [!] {} hosts advertise HTTP/3, but curl with HTTP/3 support is not installed", report.hosts.len());
+            } else {
+                let answered: usize = report.hosts.iter().map(|h| h.answered).sum();
+                println!("   [+] {} hosts advertise HTTP/3, {} endpoints answered over QUIC", report.hosts.len(), answered);
+                for f in report.findings.iter().take(10) {
+                    println!("      [!] {} ({}): TCP {} / HTTP/3 {}", f.url, f.severity.label(), f.tcp_status, f.h3_status);
+                }
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "http3.json"), serde_json::to_string_pretty(&report).unwrap_or_default());
+            h3_findings = report.findings;
+        }
+    }
+
+    // Phase 6d: Request smuggling (opt-in). Raw sockets can't go through the proxy either.
+    let mut smuggling_findings: Vec<api_hunter::probe::smuggling::SmugglingFinding> = Vec::new();
+    if smuggling && success_count > 0 {
+        use api_hunter::probe::smuggling::{self, SmugglingProber};
+        if proxied {
+            println!("[!] Smuggling probes skipped: raw requests can't be sent through the proxy");
+        } else {
+            let targets = smuggling::targets(&results, 10);
+            println!("[*] Request smuggling probes on {} hosts...", targets.len());
+            let prober = SmugglingProber::new(Duration::from_secs(timeout.clamp(5, 10)));
+            for target in &targets {
+                if let Some(f) = prober.test(target).await {
+                    println!("   [!] {:?} desync on {} ({}, {}{})", f.desync, f.url, f.variant, f.severity, if f.confirmed { ", 404 poisoning confirmed" } else { "" });
+                    smuggling_findings.push(f);
+                }
+            }
+            if smuggling_findings.is_empty() {
+                println!("   [-] No desync indicators");
+            }
+            let _ = std::fs::write(paths::artifact(&out_dir, "smuggling_findings.json"), serde_json::to_string_pretty(&smuggling_findings).unwrap_or_default());
+        }
+    }
+
+    // Phase 7: WAF Bypass Techniques - which payload transforms got past the WAFs during fuzzing
+    let waf_mutations = waf_mutators.records();
+    if bypass_waf && !waf_mutations.is_empty() {
+        println!("[#] WAF bypass: {} blocked payloads", waf_mutations.len());
+        let mut by_technique: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+        for m in &waf_mutations {
+            let technique = m.technique.as_ref().map(|t| format!("{:?}", t)).unwrap_or_else(|| "still blocked".to_string());
+            *by_technique.entry(format!("{} / {}", m.waf, technique)).or_default() += 1;
+        }
+        for (key, n) in &by_technique {
+            println!("   [+] {}: {}", key, n);
+        }
+        let _ = std::fs::write(paths::artifact(&out_dir, "waf_mutations.json"), serde_json::to_string_pretty(&waf_mutations).unwrap_or_default());
+    }
+
+    let (reused, stored) = api_hunter::probe::response_cache::shared().stats();
+    if reused > 0 {
+        println!("[~] {} requests answered from the response cache ({} responses stored)", reused, stored);
+    }
+
+    if let Some(path) = &cookie_path {
+        match api_hunter::cookie_jar::shared().save(path) {
+            Ok(n) => println!("[~] Saved {} cookie(s) to {}", n, path.display()),
+            Err(e) => eprintln!("[!] Warning: {}", e),
+        }
+    }
+    if let Some(anon) = &anonymizer {
+        if let Err(e) = anon.save_session(&api_hunter::anonymizer::session_path(&workspace, &domain)) {
+            eprintln!("[!] Warning: {}", e);
+        }
     }
 
     // Print scan summary
@@ -630,6 +2298,18 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
     } else {
         println!("\n[*] No WAF detected");
     }
+
+    // Proxy pool summary
+    if let Some(proxy_pool) = api_hunter::anonymizer::pool::shared() {
+        use api_hunter::anonymizer::pool::ProxyState;
+        let stats = proxy_pool.stats();
+        let requests: usize = stats.iter().map(|s| s.requests).sum();
+        println!("\n[*] Proxy pool: {}/{} healthy at the end, {} requests", proxy_pool.healthy(), stats.len(), requests);
+        for s in stats.iter().filter(|s| s.state != ProxyState::Healthy) {
+            println!("    [-] {} {:?}: {}", s.proxy, s.state, s.reason.as_deref().unwrap_or("-"));
+        }
+        let _ = std::fs::write(paths::artifact(&out_dir, "proxy_pool.json"), serde_json::to_string_pretty(&stats).unwrap_or_default());
+    }
     
     // JWT Analysis Summary
     if jwt {
@@ -643,7 +2323,7 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
                 // Save JWT report
                 if let Some(ref analyzer) = jwt_analyzer {
                     let report = analyzer.generate_report(&jwt_analysis_results);
-                    let _ = std::fs::write(out_dir.join("jwt_analysis.txt"), &report);
+                    let _ = std::fs::write(paths::artifact(&out_dir, "jwt_analysis.txt"), &report);
                 }
             }
         }
@@ -663,46 +2343,500 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
         if critical_findings > 0 {
             println!("   [!!] CRITICAL: {}", critical_findings);
         }
-        if high_findings > 0 {
-            println!("   [!] HIGH: {}", high_findings);
+        if high_findings > 0 {
+            println!("   [!] HIGH: {}", high_findings);
+        }
+        if medium_findings > 0 {
+            println!("   [i] MEDIUM: {}", medium_findings);
+        }
+    } else {
+        println!("\n[v] No critical/high/medium vulnerabilities detected");
+    }
+    
+    // Only show output location if user explicitly specified -o flag
+    if out != "./results" {
+        println!("\n[=] Results: {}", out_dir.display());
+    }
+    
+    // Save structured report if requested
+    if let Some(report_path) = report {
+        use api_hunter::output::clean_reporter::{ScanReport, Finding, Severity, JsAnalysisSummary};
+        use std::path::Path;
+        
+        let mut scan_report = ScanReport::new(domain.clone());
+        scan_report.scan_duration_seconds = scan_duration;
+        scan_report.total_endpoints = success_count;
+        scan_report.third_party_references = third_party_refs;
+        scan_report.hosts = host_summaries(&host_status, &results);
+        scan_report.disclosure_policies = disclosure_policies;
+        
+        // Try to read and parse existing findings
+        if let Ok(summary_content) = std::fs::read_to_string(paths::artifact(&out_dir, "analysis_summary.txt")) {
+            // Parse findings from summary (simplified - in production would parse properly)
+            for _ in 0..critical_findings {
+                scan_report.add_finding(Finding {
+                    severity: Severity::Critical,
+                    category: "Security".to_string(),
+                    title: "Critical Issue".to_string(),
+                    description: "See analysis_summary.txt for details".to_string(),
+                    url: target.clone(),
+                    evidence: vec![],
+                    remediation: None,
+                });
+            }
+        }
+        
+        // Mass assignment: the deep-analysis tests and the parameters found by brute force
+        for artifact in ["mass_assignment_findings.json", "param_mass_assignment.json"] {
+            let Ok(content) = std::fs::read_to_string(paths::artifact(&out_dir, artifact)) else { continue };
+            let results: Vec<api_hunter::fuzz::mass_assignment::MassAssignmentResult> = serde_json::from_str(&content).unwrap_or_default();
+            for result in &results {
+                for vuln in &result.vulnerabilities {
+                    scan_report.add_finding(Finding {
+                        severity: match vuln.severity.as_str() {
+                            "CRITICAL" => Severity::Critical,
+                            "HIGH" => Severity::High,
+                            _ => Severity::Medium,
+                        },
+                        category: "Mass Assignment".to_string(),
+                        title: format!("{}: {}", vuln.vuln_type, vuln.parameter),
+                        description: vuln.description.clone(),
+                        url: result.url.clone(),
+                        evidence: vuln.payload.iter().cloned().collect(),
+                        remediation: Some("Bind request bodies to an allow-list of client-writable fields".to_string()),
+                    });
+                }
+            }
+        }
+
+        for leak in &github_leaks {
+            let mut evidence: Vec<String> = leak.urls.iter().chain(leak.api_paths.iter()).take(10).cloned().collect();
+            evidence.extend(leak.secrets.iter().map(|sec| format!("{:?}: {}", sec.secret_type, sec.line_context)));
+            scan_report.add_finding(Finding {
+                severity: Severity::Info,
+                category: "Public Code Reference".to_string(),
+                title: format!("{}: {}", leak.repository, leak.path),
+                description: "Target references found in public GitHub code".to_string(),
+                url: leak.html_url.clone(),
+                evidence,
+                remediation: None,
+            });
+        }
+
+        for f in &bola_findings {
+            scan_report.add_finding(Finding {
+                severity: if f.severity == "high" { Severity::High } else { Severity::Medium },
+                category: "BOLA/IDOR".to_string(),
+                title: format!("User B can read user A's object: {}", f.url),
+                description: format!("Requested with user B's credentials, the object returned user A's data (A: {}, B: {}, anonymous: {})",
+                    f.status_a, f.status_b, f.status_anonymous.map(|s| s.to_string()).unwrap_or_else(|| "no response".to_string())),
+                url: f.url.clone(),
+                evidence: f.leaked.clone(),
+                remediation: Some("Check object ownership on every request, not only authentication".to_string()),
+            });
+        }
+
+        for report in &websocket_reports {
+            for v in &report.vulnerabilities {
+                scan_report.add_finding(Finding {
+                    severity: match v.severity.as_str() { "Critical" => Severity::Critical, "High" => Severity::High, "Medium" => Severity::Medium, _ => Severity::Low },
+                    category: "WebSocket".to_string(),
+                    title: format!("{}: {}", v.name, report.url),
+                    description: format!("{}. {}", v.description, v.details),
+                    url: report.url.clone(),
+                    evidence: report.protocol.iter().map(|p| format!("Sec-WebSocket-Protocol: {}", p)).collect(),
+                    remediation: None,
+                });
+            }
+        }
+
+        for r in &pii_reports {
+            let mut kinds: Vec<String> = r.matches.iter().map(|m| format!("{:?}", m.kind)).collect();
+            kinds.sort();
+            kinds.dedup();
+            scan_report.add_finding(Finding {
+                severity: Severity::High,
+                category: "PII Exposure".to_string(),
+                title: format!("Personal data in response: {}", r.url),
+                description: format!("The {} response carries {} PII values ({})", r.status, r.matches.len(), kinds.join(", ")),
+                url: r.url.clone(),
+                evidence: r.matches.iter().take(10).map(|m| format!("{:?} at {}: {}", m.kind, m.path, m.sample)).collect(),
+                remediation: Some("Return personal fields only to the data subject or authorized staff, and mask them otherwise".to_string()),
+            });
+        }
+
+        for r in &error_reports {
+            use api_hunter::analyze::error_disclosure::DisclosureKind;
+            let d = &r.disclosure;
+            let kind = match d.kind {
+                DisclosureKind::DebugConsole => "interactive debugger",
+                DisclosureKind::DebugPage => "debug page",
+                DisclosureKind::StackTrace => "stack trace",
+                DisclosureKind::ErrorPage => "error page",
+            };
+            scan_report.add_finding(Finding {
+                severity: match d.severity.as_str() { "CRITICAL" => Severity::Critical, "HIGH" => Severity::High, "MEDIUM" => Severity::Medium, _ => Severity::Low },
+                category: "Error Disclosure".to_string(),
+                title: format!("{} {}: {}", d.framework.name(), kind, r.url),
+                description: if d.credentials.is_empty() {
+                    format!("The {} response is a {} {}", r.status, d.framework.name(), kind)
+                } else {
+                    format!("The {} response is a {} {} showing {}", r.status, d.framework.name(), kind, d.credentials.join(", "))
+                },
+                url: r.url.clone(),
+                evidence: std::iter::once(d.evidence.clone()).chain(d.versions.iter().cloned()).chain(d.paths.iter().cloned()).collect(),
+                remediation: Some("Turn off debug mode in production and return generic error bodies; log the details server-side".to_string()),
+            });
+        }
+
+        for c in sse_captures.iter().filter(|c| !c.secrets.is_empty() || !c.pii.is_empty()) {
+            scan_report.add_finding(Finding {
+                severity: if c.secrets.is_empty() { Severity::Medium } else { Severity::High },
+                category: "Server-Sent Events".to_string(),
+                title: format!("Sensitive data in event stream: {}", c.url),
+                description: format!("The first {} events of the stream carry {} secrets and {} PII values", c.events, c.secrets.len(), c.pii.len()),
+                url: c.url.clone(),
+                evidence: c.pii.iter().map(|p| format!("{:?} at {}: {}", p.kind, p.path, p.sample))
+                    .chain(c.secrets.iter().map(|s| format!("{:?}: {}", s.secret_type, s.line_context)))
+                    .take(10)
+                    .collect(),
+                remediation: Some("Scope the stream to the subscriber and leave credentials and personal fields out of broadcast events".to_string()),
+            });
+        }
+
+        for report in grpc_reports.iter().filter(|r| r.reflection.is_some()) {
+            scan_report.add_finding(Finding {
+                severity: if report.severity.as_deref() == Some("MEDIUM") { Severity::Medium } else { Severity::Low },
+                category: "gRPC".to_string(),
+                title: format!("Public gRPC server reflection: {}", report.origin),
+                description: format!("{} lists {} services and {} methods to unauthenticated clients", report.reflection.as_deref().unwrap_or_default(), report.services.len(), report.methods()),
+                url: report.origin.clone(),
+                evidence: report.services.iter().map(|s| s.name.clone()).collect(),
+                remediation: Some("Register the reflection service only in development builds or put it behind authentication".to_string()),
+            });
+        }
+
+        for report in &cache_reports {
+            use api_hunter::probe::cache_poisoning::CacheAttack;
+            for f in &report.findings {
+                let kind = match f.attack {
+                    CacheAttack::UnkeyedHeader => "Web cache poisoning",
+                    CacheAttack::CacheDeception => "Web cache deception",
+                };
+                scan_report.add_finding(Finding {
+                    severity: match f.severity.as_str() { "high" => Severity::High, "medium" => Severity::Medium, _ => Severity::Low },
+                    category: "Cache".to_string(),
+                    title: format!("{}{}: {}", kind, if f.confirmed { "" } else { " (unconfirmed)" }, report.url),
+                    description: format!("{}: {}", f.vector, f.effect),
+                    url: report.url.clone(),
+                    evidence: vec![format!("Cache key: {}", f.cache_key)].into_iter().chain(report.cache.cdn.clone()).collect(),
+                    remediation: Some(match f.attack {
+                        CacheAttack::UnkeyedHeader => "Strip forwarding headers at the edge or add them to the cache key".to_string(),
+                        CacheAttack::CacheDeception => "Cache by Cache-Control only and reject unknown path suffixes on dynamic routes".to_string(),
+                    }),
+                });
+            }
+        }
+
+        if let Some(drift) = &spec_drift {
+            for s in &drift.shadow {
+                let (title, description) = match &s.documented_path {
+                    Some(path) => (
+                        format!("Undocumented methods {} on {}", s.methods.join(", "), path),
+                        format!("{} answers methods {} doesn't document", path, drift.spec),
+                    ),
+                    None => (
+                        format!("Shadow endpoint: {}", s.path),
+                        format!("Live endpoint ({}) missing from {}", s.status, drift.spec),
+                    ),
+                };
+                scan_report.add_finding(Finding {
+                    severity: if s.severity == "MEDIUM" { Severity::Medium } else { Severity::Low },
+                    category: "API Spec Drift".to_string(),
+                    title,
+                    description,
+                    url: s.url.clone(),
+                    evidence: s.methods.iter().map(|m| format!("{} is live", m)).collect(),
+                    remediation: Some("Document the endpoint and review it, or remove it".to_string()),
+                });
+            }
+            for z in &drift.zombies {
+                scan_report.add_finding(Finding {
+                    severity: if z.placeholder { Severity::Info } else { Severity::Low },
+                    category: "API Spec Drift".to_string(),
+                    title: format!("Zombie endpoint: {} {}", z.methods.join(", "), z.path),
+                    description: format!("Documented in {} but {}{}", drift.spec, z.reason, if z.placeholder { " (probed with placeholder path parameters)" } else { "" }),
+                    url: z.urls.first().cloned().unwrap_or_default(),
+                    evidence: z.urls.clone(),
+                    remediation: Some("Remove the operation from the spec, or restore the endpoint".to_string()),
+                });
+            }
+        }
+
+        for report in &gateway_reports {
+            for f in &report.findings {
+                scan_report.add_finding(Finding {
+                    severity: match f.severity.as_str() { "CRITICAL" => Severity::Critical, "HIGH" => Severity::High, "MEDIUM" => Severity::Medium, _ => Severity::Low },
+                    category: "Gateway".to_string(),
+                    title: format!("{}: {:?} at {}", report.gateway.name(), f.check, f.url),
+                    description: f.detail.clone(),
+                    url: f.url.clone(),
+                    evidence: report.evidence.clone(),
+                    remediation: None,
+                });
+            }
+        }
+
+        for f in &oauth_findings {
+            scan_report.add_finding(Finding {
+                severity: match f.severity.as_str() { "HIGH" => Severity::High, "MEDIUM" => Severity::Medium, _ => Severity::Low },
+                category: "OAuth".to_string(),
+                title: format!("{:?}: {}", f.check, f.authorization_endpoint),
+                description: f.detail.clone(),
+                url: f.evidence_url.clone(),
+                evidence: f.client_id.iter().map(|c| format!("client_id: {}", c)).collect(),
+                remediation: None,
+            });
         }
-        if medium_findings > 0 {
-            println!("   [i] MEDIUM: {}", medium_findings);
+
+        for r in csp_reports.iter().filter(|r| matches!(r.analysis.grade, 'C' | 'D' | 'F')) {
+            let a = &r.analysis;
+            scan_report.add_finding(Finding {
+                severity: match a.severity() { "HIGH" => Severity::High, "MEDIUM" => Severity::Medium, _ => Severity::Low },
+                category: "Content-Security-Policy".to_string(),
+                title: format!("CSP grade {}: {}", a.grade, r.urls[0]),
+                description: a.issues.iter().filter(|i| i.severity != "LOW").map(|i| i.detail.as_str()).collect::<Vec<_>>().join("; "),
+                url: r.urls[0].clone(),
+                evidence: a.policy.iter().map(|p| format!("{}: {}", if a.report_only { "Content-Security-Policy-Report-Only" } else { "Content-Security-Policy" }, p))
+                    .chain(r.urls.iter().skip(1).cloned())
+                    .collect(),
+                remediation: Some("Use a nonce- or hash-based script-src with 'strict-dynamic', object-src 'none' and base-uri 'none'".to_string()),
+            });
         }
-    } else {
-        println!("\n[v] No critical/high/medium vulnerabilities detected");
-    }
-    
-    // Only show output location if user explicitly specified -o flag
-    if out != "./results" {
-        println!("\n[=] Results: {}", out_dir.display());
-    }
-    
-    // Save structured report if requested
-    if let Some(report_path) = report {
-        use api_hunter::output::clean_reporter::{ScanReport, Finding, Severity, JsAnalysisSummary};
-        use std::path::Path;
-        
-        let mut scan_report = ScanReport::new(domain.clone());
-        scan_report.scan_duration_seconds = scan_duration;
-        scan_report.total_endpoints = success_count;
-        
-        // Try to read and parse existing findings
-        if let Ok(summary_content) = std::fs::read_to_string(out_dir.join("analysis_summary.txt")) {
-            // Parse findings from summary (simplified - in production would parse properly)
-            for _ in 0..critical_findings {
+
+        for r in cookie_reports.iter().filter(|r| matches!(r.severity(), "HIGH" | "MEDIUM")) {
+            scan_report.add_finding(Finding {
+                severity: match r.severity() { "HIGH" => Severity::High, _ => Severity::Medium },
+                category: "Cookies".to_string(),
+                title: format!("Cookie {}: {}", r.cookie.name, r.origin),
+                description: r.issues.iter().filter(|i| i.severity != "INFO").map(|i| i.detail.as_str()).collect::<Vec<_>>().join("; "),
+                url: r.urls[0].clone(),
+                evidence: r.urls.clone(),
+                remediation: Some("Set session cookies as __Host- with Secure, HttpOnly, Path=/ and SameSite=Lax or Strict".to_string()),
+            });
+        }
+
+        for report in &oidc_reports {
+            for f in &report.findings {
                 scan_report.add_finding(Finding {
-                    severity: Severity::Critical,
-                    category: "Security".to_string(),
-                    title: "Critical Issue".to_string(),
-                    description: "See analysis_summary.txt for details".to_string(),
-                    url: target.clone(),
-                    evidence: vec![],
+                    severity: match f.severity.as_str() { "HIGH" => Severity::High, "MEDIUM" => Severity::Medium, _ => Severity::Low },
+                    category: "OpenID Connect".to_string(),
+                    title: format!("{:?}: {}", f.check, report.origin),
+                    description: f.detail.clone(),
+                    url: report.config_url.clone(),
+                    evidence: report.jwks_uri.iter().cloned().collect(),
                     remediation: None,
                 });
             }
         }
-        
+
+        for f in &forge_findings {
+            scan_report.add_finding(Finding {
+                severity: Severity::Critical,
+                category: "JWT Forgery".to_string(),
+                title: format!("Forged {} token accepted: {}", f.forged_alg, f.url),
+                description: format!("The endpoint verifies {} tokens but accepted one forged by {:?} ({})", f.original_alg, f.attack, f.variant),
+                url: f.url.clone(),
+                evidence: vec![f.forged_token.clone()],
+                remediation: Some("Pin the expected algorithm and key per issuer; never take the algorithm or a key path from the token".to_string()),
+            });
+        }
+
+        for f in &smuggling_findings {
+            scan_report.add_finding(Finding {
+                severity: if f.confirmed { Severity::Critical } else { Severity::High },
+                category: "Request Smuggling".to_string(),
+                title: format!("{} request smuggling ({}): {}", f.desync.label(), f.variant, f.url),
+                description: format!("Front end and back end disagree on the request length with `{}`{}",
+                    f.te_header, if f.confirmed { "; a smuggled request poisoned the next response with a 404" } else { " (timing only, not confirmed by 404 poisoning)" }),
+                url: f.url.clone(),
+                evidence: vec![f.evidence.clone(), f.request.clone()],
+                remediation: Some("Normalize or reject ambiguous Content-Length/Transfer-Encoding at the front end and use HTTP/2 end to end".to_string()),
+            });
+        }
+
+        for f in &hpp_findings {
+            let (Some(impact), Some(severity), Some(evidence)) = (f.impact, f.severity, &f.evidence) else { continue };
+            let (what, remediation) = match impact {
+                api_hunter::fuzz::hpp::Impact::Authorization => ("authorization", "Reject duplicate parameters, and check authorization on the value the handler actually uses"),
+                api_hunter::fuzz::hpp::Impact::Pricing => ("pricing", "Reject duplicate parameters, and compute prices server-side"),
+            };
+            scan_report.add_finding(Finding {
+                severity,
+                category: "Parameter Pollution".to_string(),
+                title: format!("Duplicate {} changes {} logic: {}", f.parameter, what, f.url),
+                description: evidence.clone(),
+                url: f.url.clone(),
+                evidence: f.behaviors.iter().map(|b| format!("{:?}: {:?} wins ({})", b.channel, b.precedence, b.evidence)).collect(),
+                remediation: Some(remediation.to_string()),
+            });
+        }
+
+        for f in &pp_findings {
+            use api_hunter::fuzz::prototype_pollution::Vector;
+            scan_report.add_finding(Finding {
+                severity: f.severity,
+                category: "Prototype Pollution".to_string(),
+                title: format!("Prototype pollution via {}: {} {}", match f.vector { Vector::Proto => "__proto__", Vector::ConstructorPrototype => "constructor.prototype" }, f.method, f.url),
+                description: format!("{:?}: {}", f.technique, f.evidence),
+                url: f.url.clone(),
+                evidence: std::iter::once(f.payload.to_string()).chain(f.framework.as_ref().map(|fw| format!("Framework: {}", fw))).collect(),
+                remediation: Some("Merge request JSON into objects without a prototype, or drop `__proto__` and `constructor` keys".to_string()),
+            });
+        }
+
+        for f in upload_reports.iter().flat_map(|r| &r.findings) {
+            use api_hunter::fuzz::upload::UploadTest;
+            let kind = match f.test {
+                UploadTest::DangerousExtension => "Dangerous extension accepted",
+                UploadTest::ContentTypeSpoofing => "Content-Type spoofing accepted",
+                UploadTest::SvgScript => "SVG with script accepted",
+                UploadTest::XmlEntity => "XML external entity resolved",
+                UploadTest::PathTraversal => "Path traversal in file name",
+            };
+            scan_report.add_finding(Finding {
+                severity: f.severity,
+                category: "File Upload".to_string(),
+                title: format!("{}: {}", kind, f.url),
+                description: f.evidence.clone(),
+                url: f.url.clone(),
+                evidence: std::iter::once(format!("{} as {} ({})", f.filename, f.content_type, f.status)).chain(f.stored_url.clone()).collect(),
+                remediation: Some("Allow-list extensions and content types server-side, rename stored files and serve them from another origin as downloads".to_string()),
+            });
+        }
+
+        for f in &h3_findings {
+            use api_hunter::probe::http3::Difference;
+            let kind = match f.difference {
+                Difference::BlockedOverTcpOnly => "Blocked over TCP, answered over HTTP/3",
+                Difference::BlockedOverH3Only => "Blocked over HTTP/3 only",
+                Difference::StatusDiffers => "Status differs over HTTP/3",
+                Difference::ContentDiffers => "Content differs over HTTP/3",
+            };
+            scan_report.add_finding(Finding {
+                severity: f.severity,
+                category: "HTTP/3".to_string(),
+                title: format!("{}: {}", kind, f.url),
+                description: format!("{} answered {}, HTTP/3 answered {}", f.tcp_version.as_deref().unwrap_or("TCP"), f.tcp_status, f.h3_status),
+                url: f.url.clone(),
+                evidence: [f.tcp_server.as_ref().map(|s| format!("TCP server: {}", s)), f.h3_server.as_ref().map(|s| format!("HTTP/3 server: {}", s))].into_iter().flatten().collect(),
+                remediation: Some("Apply the same WAF and access rules to the QUIC listener as to TCP, or stop advertising HTTP/3".to_string()),
+            });
+        }
+
+        for f in &json_findings {
+            use api_hunter::fuzz::json_fuzzer::Indicator;
+            let kind = match f.indicator {
+                Indicator::SqlError => "SQL error",
+                Indicator::NoSqlInjection => "NoSQL injection",
+                Indicator::TemplateInjection => "Template injection",
+                Indicator::CommandOrFileInjection => "Command or file injection",
+                Indicator::TypeConfusion => "Type confusion",
+                Indicator::StackTrace => "Stack trace",
+                Indicator::ServerError => "Server error",
+            };
+            scan_report.add_finding(Finding {
+                severity: f.severity,
+                category: "JSON Body Injection".to_string(),
+                title: format!("{} in `{}`: {} {}", kind, f.field, f.method, f.url),
+                description: format!("{:?} mutation of `{}` changed the response from {} to {}", f.mutation, f.field, f.baseline_status, f.status),
+                url: f.url.clone(),
+                evidence: std::iter::once(f.evidence.clone()).chain(f.waf_bypass.as_ref().map(|t| format!("WAF bypass: {:?}", t))).collect(),
+                remediation: Some("Validate request bodies against a schema and keep user input out of queries, templates and shell commands".to_string()),
+            });
+        }
+
+        for f in &oob_findings {
+            use api_hunter::oob::{OobKind, Protocol};
+            let kind = match f.probe.kind {
+                OobKind::Ssrf => "Blind SSRF",
+                OobKind::Xxe => "Blind XXE",
+                OobKind::CommandInjection => "Blind command injection",
+                OobKind::SqlInjection => "Blind SQL injection",
+                OobKind::Jndi => "JNDI lookup (Log4Shell)",
+            };
+            let over = if f.interactions.iter().any(|i| i.protocol == Protocol::Http) { "an HTTP request" } else { "a DNS lookup" };
+            scan_report.add_finding(Finding {
+                severity: f.severity,
+                category: "Out-of-Band".to_string(),
+                title: format!("{}: {}", kind, f.probe.url),
+                description: format!("The server made {} to the callback host sent{}", over, f.probe.parameter.as_deref().map(|p| format!(" in `{}`", p)).unwrap_or_default()),
+                url: f.probe.url.clone(),
+                evidence: f.interactions.iter().map(|i| format!("{} from {}: {}", if i.protocol == Protocol::Http { "HTTP" } else { "DNS" }, i.remote, i.data.lines().next().unwrap_or_default())).collect(),
+                remediation: Some("Don't pass user input to URL fetchers, XML parsers, shells, queries or log lookups; restrict egress from the server".to_string()),
+            });
+        }
+
+        for r in &rate_limit_reports {
+            let Some(severity) = r.severity else { continue };
+            scan_report.add_finding(Finding {
+                severity,
+                category: "Rate Limit Bypass".to_string(),
+                title: format!("Rate limit bypassed: {}", r.url),
+                description: format!("{} of {} variants got past the 429", r.bypasses.len(), r.tried),
+                url: r.url.clone(),
+                evidence: r.bypasses.iter().map(|b| format!("{} -> {} ({})", b.variant.technique, b.status, b.variant.url)).collect(),
+                remediation: Some("Key the rate limit on the authenticated identity or the real client address, and normalise paths before counting".to_string()),
+            });
+        }
+
+        for hit in &vhost_hits {
+            scan_report.add_finding(Finding {
+                severity: if hit.resolves { Severity::Info } else { Severity::Medium },
+                category: "Virtual Host".to_string(),
+                title: format!("{} virtual host {}", if hit.resolves { "Virtual" } else { "Hidden" }, hit.host),
+                description: format!("{} answers with its own application ({}{}) when requested with this Host header",
+                    hit.target, hit.fingerprint.status, hit.fingerprint.title.as_ref().map(|t| format!(", \"{}\"", t)).unwrap_or_default()),
+                url: format!("{}/", hit.target),
+                evidence: vec![format!("Host: {}", hit.host)],
+                remediation: (!hit.resolves).then(|| "Don't serve internal applications on internet-facing servers, or restrict them by source address".to_string()),
+            });
+        }
+
+        for cert in &tls_certs {
+            for issue in &cert.issues {
+                scan_report.add_finding(Finding {
+                    severity: issue.severity(),
+                    category: "Weak TLS".to_string(),
+                    title: format!("{}: {}", cert.host, issue.title()),
+                    description: format!("Certificate issued by {} to {}, valid until {}", cert.issuer_name, cert.subject, cert.not_after),
+                    url: format!("https://{}:{}/", cert.host, cert.port),
+                    evidence: vec![
+                        format!("Protocol: {}", cert.tls_version.as_deref().unwrap_or("unknown")),
+                        format!("Signature: {}", cert.signature_algorithm),
+                        format!("SANs: {}", cert.sans.join(", ")),
+                    ],
+                    remediation: Some(match issue {
+                        api_hunter::probe::tls::TlsIssue::LegacyProtocol { .. } => "Disable TLS 1.0 and 1.1".to_string(),
+                        api_hunter::probe::tls::TlsIssue::WeakSignature { .. } => "Reissue the certificate with a SHA-256 (or stronger) signature".to_string(),
+                        _ => "Install a valid certificate from a trusted CA that covers this host".to_string(),
+                    }),
+                });
+            }
+        }
+
+        for lib in &js_vulnerable_libs {
+            scan_report.add_finding(Finding {
+                severity: lib.severity,
+                category: "Vulnerable JS Library".to_string(),
+                title: format!("{} {} ({})", lib.name, lib.version, lib.identifiers.join(", ")),
+                description: lib.summary.clone(),
+                url: lib.source_file.clone(),
+                evidence: vec![format!("{}@{} bundled in {}", lib.name, lib.version, lib.source_file)],
+                remediation: Some(format!("Upgrade {} to a patched release", lib.name)),
+            });
+        }
+
         // Save report
         if let Err(e) = scan_report.save_to_file(Path::new(&report_path)) {
             eprintln!("   [!] Failed to save report: {}", e);
@@ -710,13 +2844,113 @@ async fn run_scan(target: String, out: String, concurrency: u16, per_host: u16,
             println!("[=] Report: {}", report_path);
         }
     }
+
+    if graph {
+        let previous = registry.latest_artifact("asset_graph.graphml");
+        match write_asset_graph(&out_dir, previous.as_deref(), &domain, &results) {
+            Ok(path) => println!("[=] Asset graph: {}", path.display()),
+            Err(e) => eprintln!("   [!] Failed to write asset graph: {}", e),
+        }
+    }
+
+    write_identify_audit(&out_dir)?;
+    registry.finish_scan(&scan_run)?;
     
     Ok(())
 }
 
+/// Per-host outcome for the report: pre-flight state plus the number of endpoints found on each host
+fn host_summaries(host_status: &[api_hunter::probe::preflight::HostStatus], results: &[RawEvent]) -> Vec<api_hunter::output::clean_reporter::HostSummary> {
+    use api_hunter::output::clean_reporter::HostSummary;
+    use api_hunter::probe::preflight::HostState;
+
+    let mut endpoints: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    for ev in results {
+        if let Some(host) = url::Url::parse(&ev.orig_url).ok().and_then(|u| u.host_str().map(str::to_lowercase)) {
+            *endpoints.entry(host).or_default() += 1;
+        }
+    }
+    let mut summaries: Vec<HostSummary> = host_status.iter()
+        .map(|h| HostSummary { host: h.host.clone(), state: h.state, endpoints: endpoints.remove(&h.host).unwrap_or(0) })
+        .collect();
+    // Hosts that were not pre-flighted but answered
+    summaries.extend(endpoints.into_iter().map(|(host, endpoints)| HostSummary { host, state: HostState::Alive, endpoints }));
+    summaries
+}
+
+/// Build the asset graph from probe results and analysis findings, merging into the graph of an
+/// earlier run (`previous`), and write it as GraphML and DOT
+fn write_asset_graph(out_dir: &std::path::Path, previous: Option<&std::path::Path>, domain: &str, results: &[RawEvent]) -> anyhow::Result<PathBuf> {
+    use api_hunter::output::graph::AssetGraph;
+
+    let graphml_path = paths::artifact(out_dir, "asset_graph.graphml");
+    let mut graph = match previous.map(std::fs::read_to_string) {
+        Some(Ok(existing)) => AssetGraph::from_graphml(&existing)?,
+        _ => AssetGraph::new(),
+    };
+
+    for ev in results {
+        graph.add_endpoint(&ev.orig_url, domain, Some(ev.status));
+    }
+
+    // Findings from the --sV/--sA analysis, if it ran
+    if let Ok(analysis) = std::fs::read_to_string(paths::artifact(out_dir, "analysis_results.json")) {
+        let json: serde_json::Value = serde_json::from_str(&analysis).unwrap_or_default();
+        let items = |key: &str| json.get(key).and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        for a in items("analyses") {
+            let url = a["url"].as_str().unwrap_or_default();
+            // Same severity mapping as analysis_summary.txt
+            for f in a["findings"].as_array().into_iter().flatten().filter_map(|f| f.as_str()) {
+                let severity = if f.contains("password") || f.contains("secret") || f.contains("token") {
+                    "CRITICAL"
+                } else if f.contains("PUBLIC") || f.contains("CORS") {
+                    "HIGH"
+                } else {
+                    "MEDIUM"
+                };
+                graph.add_finding(url, domain, severity, f);
+            }
+        }
+        for a in items("admin_findings").iter().filter(|a| a["accessible"].as_bool() == Some(true)) {
+            graph.add_finding(a["url"].as_str().unwrap_or_default(), domain, a["risk_level"].as_str().unwrap_or("MEDIUM"), "Exposed admin/debug endpoint");
+        }
+        for f in items("exposed_files") {
+            graph.add_finding(f["url"].as_str().unwrap_or_default(), domain, f["risk_level"].as_str().unwrap_or("HIGH"), f["kind"].as_str().unwrap_or("Exposed file"));
+        }
+        for e in items("observability_endpoints") {
+            graph.add_finding(e["url"].as_str().unwrap_or_default(), domain, e["risk_level"].as_str().unwrap_or("HIGH"), e["summary"].as_str().unwrap_or("Exposed observability endpoint"));
+        }
+        for i in items("idor_findings").iter().filter(|i| i["is_vulnerable"].as_bool() == Some(true)) {
+            let title = format!("IDOR via {}", i["parameter"].as_str().unwrap_or("parameter"));
+            graph.add_finding(i["url"].as_str().unwrap_or_default(), domain, i["risk_level"].as_str().unwrap_or("HIGH"), &title);
+        }
+    }
+
+    std::fs::write(&graphml_path, graph.to_graphml())?;
+    std::fs::write(paths::artifact(out_dir, "asset_graph.dot"), graph.to_dot())?;
+    Ok(graphml_path)
+}
+
+/// Write `identify_audit.json` when identification headers are configured
+fn write_identify_audit(out_dir: &std::path::Path) -> anyhow::Result<()> {
+    if !api_hunter::identify::is_configured() {
+        return Ok(());
+    }
+    let audit = api_hunter::identify::audit();
+    let path = paths::artifact(out_dir, "identify_audit.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&audit)?)?;
+    if audit.verified {
+        println!("[=] Identification headers verified on {} clients: {}", audit.clients.len(), path.display());
+    } else {
+        println!("[!] Identification headers missing on: {} ({})", audit.missing.join(", "), path.display());
+    }
+    Ok(())
+}
+
 async fn run_param_fuzzing(
     client: &reqwest::Client,
     results: &[RawEvent],
+    discovered: &[api_hunter::fuzz::param_bruteforce::DiscoveredParam],
     test_idor: bool,
     out_dir: &PathBuf,
 ) -> anyhow::Result<()> {
@@ -726,7 +2960,7 @@ async fn run_param_fuzzing(
     use std::io::Write;
     
     // Open file for immediate writing
-    let fuzz_path = out_dir.join("fuzz_results.txt");
+    let fuzz_path = paths::artifact(out_dir, "fuzz_results.txt");
     let mut fuzz_file = std::fs::File::create(&fuzz_path)?;
     writeln!(fuzz_file, "=== Parameter Fuzzing Results ===")?;
     writeln!(fuzz_file, "")?;
@@ -758,6 +2992,22 @@ async fn run_param_fuzzing(
     
     tracing::info!("Discovered {} unique parameters from responses ", discovered_params.len());
     
+    // Phase 2a: Fuzz the query parameters the brute force found
+    for param in discovered.iter().filter(|p| p.location == api_hunter::fuzz::param_bruteforce::ParamLocation::Query) {
+        match smart_fuzz_parameter(client, &param.url, &param.name, None).await {
+            Ok(fuzz_results) => {
+                for result in fuzz_results.into_iter().filter(|r| r.different_response) {
+                    let ct = result.content_type.as_deref().unwrap_or("unknown");
+                    writeln!(fuzz_file, "PARAM (brute-forced): {} = {} | URL: {} | Status: {} | Size: {} | Content-Type: {}",
+                        param.name, result.test_value, param.url, result.status, result.response_size, ct)?;
+                    fuzz_file.flush()?;
+                    finding_count += 1;
+                }
+            }
+            Err(e) => tracing::debug!("Failed to fuzz parameter {}: {}", param.name, e),
+        }
+    }
+
     // Phase 2: Test common parameters on endpoints without query strings
     tracing::info!("Phase 2: Testing common parameters on endpoints...");
     let common = common_params();
@@ -892,7 +3142,7 @@ async fn run_param_fuzzing(
 }
 
 async fn run_deep_analysis(
-    client: &reqwest::Client,
+    http: &api_hunter::http_client::HttpClient,
     results: &[RawEvent],
     scan_admin: bool,
     aggressive: bool,
@@ -901,25 +3151,29 @@ async fn run_deep_analysis(
 ) -> anyhow::Result<()> {
     use api_hunter::analyze::api_analyzer::ApiAnalysis;
     use api_hunter::analyze::admin_scanner::{scan_admin_paths, RiskLevel};
+    use api_hunter::analyze::exposed_files::scan_exposed_files;
+    use api_hunter::analyze::observability::scan_observability;
     use api_hunter::fuzz::idor_tester::{test_idor_advanced, IdorRiskLevel};
     use api_hunter::fuzz::param_discovery::extract_params_from_url;
-    use api_hunter::http_client::HttpClient;
     
-    let analysis_path = out_dir.join("analysis_results.json");
-    let summary_path = out_dir.join("analysis_summary.txt");
+    let analysis_path = paths::artifact(out_dir, "analysis_results.json");
+    let summary_path = paths::artifact(out_dir, "analysis_summary.txt");
     
     let mut all_analyses = Vec::new();
     let mut admin_findings = Vec::new();
+    let mut exposed_files = Vec::new();
+    let mut observability = Vec::new();
     let mut idor_findings = Vec::new();
     
     // === PHASE 1: NEW API SECURITY FEATURES ===
-    let http_client = HttpClient::new(client.clone());
+    let http_client = http.clone();
+    let client = http.client();
     
     // Phase 1.1: GraphQL Discovery & Testing
     println!("   [*] GraphQL discovery & testing...");
     tracing::info!("Phase 1.1: GraphQL endpoint discovery and security testing");
     
-    let graphql_tester = api_hunter::graphql::GraphQLTester::new(http_client.clone());
+    let graphql_tester = api_hunter::graphql::GraphQLTester::new(http_client.clone()).with_fuzzing(aggressive);
     let graphql_endpoints = graphql_tester.discover_endpoints(&format!("https://{}", domain)).await;
     
     if !graphql_endpoints.is_empty() {
@@ -931,6 +3185,8 @@ async fn run_deep_analysis(
                 Ok(result) => {
                     if result.has_introspection {
                         println!("         [!] {} has introspection enabled", endpoint);
+                    } else if result.schema_recovered {
+                        println!("         [!] {} schema partially recovered without introspection", endpoint);
                     }
                     if !result.vulnerabilities.is_empty() {
                         println!("         [!] {} GraphQL vulnerabilities on {}", result.vulnerabilities.len(), endpoint);
@@ -944,7 +3200,7 @@ async fn run_deep_analysis(
         }
         
         if !all_graphql_results.is_empty() {
-            let graphql_path = out_dir.join("graphql_findings.json");
+            let graphql_path = paths::artifact(out_dir, "graphql_findings.json");
             std::fs::write(&graphql_path, serde_json::to_string_pretty(&all_graphql_results)?)?;
             tracing::info!("GraphQL findings saved to: {}", graphql_path.display());
         }
@@ -982,7 +3238,7 @@ async fn run_deep_analysis(
     }
     
     if !auth_results.is_empty() {
-        let auth_path = out_dir.join("auth_findings.json");
+        let auth_path = paths::artifact(out_dir, "auth_findings.json");
         std::fs::write(&auth_path, serde_json::to_string_pretty(&auth_results)?)?;
         tracing::info!("Auth findings saved to: {}", auth_path.display());
     }
@@ -1009,7 +3265,7 @@ async fn run_deep_analysis(
                     println!("      [!] {} version-specific vulnerabilities", version_result.vulnerabilities.len());
                 }
                 
-                let version_path = out_dir.join("version_findings.json");
+                let version_path = paths::artifact(out_dir, "version_findings.json");
                 std::fs::write(&version_path, serde_json::to_string_pretty(&version_result)?)?;
                 tracing::info!("Version findings saved to: {}", version_path.display());
             } else {
@@ -1021,46 +3277,86 @@ async fn run_deep_analysis(
         }
     }
     
-    // Phase 1.4: Mass Assignment Testing
-    println!("   [*] Mass assignment testing...");
-    tracing::info!("Phase 1.4: Mass assignment and hidden parameter discovery");
-    
-    let mass_assignment_tester = api_hunter::fuzz::mass_assignment::MassAssignmentTester::new(http_client.clone());
+    // Phase 1.4: Mass Assignment Testing (aggressive: the payloads write data)
     let mut mass_assignment_results = Vec::new();
+    if aggressive {
+        println!("   [*] Mass assignment testing...");
+        tracing::info!("Phase 1.4: Mass assignment and hidden parameter discovery");
+
+        let mass_assignment_tester = api_hunter::fuzz::mass_assignment::MassAssignmentTester::new(http_client.clone());
+
+        // Only the write methods method probing saw accepted
+        let write_endpoints: Vec<(&str, Vec<&str>)> = results.iter()
+            .map(|r| {
+                let methods: Vec<&str> = ["POST", "PUT", "PATCH"].into_iter()
+                    .filter(|m| r.allowed_methods.iter().any(|a| a == m) || r.methods.get(*m).is_some_and(|s| !matches!(s, 404 | 405 | 501)))
+                    .collect();
+                (r.orig_url.as_str(), methods)
+            })
+            .filter(|(_, methods)| !methods.is_empty())
+            .take(15)
+            .collect();
+
+        for (url, methods) in &write_endpoints {
+            for method in methods {
+                match mass_assignment_tester.test_endpoint(url, method).await {
+                    Ok(result) => {
+                        if !result.vulnerabilities.is_empty() {
+                            println!("      [!] {} {} mass assignment vulns on {}", result.vulnerabilities.len(), method, url);
+                            mass_assignment_results.push(result);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Mass assignment test failed for {} {}: {}", method, url, e);
+                    }
+                }
+            }
+        }
+
+        if !mass_assignment_results.is_empty() {
+            let mass_assignment_path = paths::artifact(out_dir, "mass_assignment_findings.json");
+            std::fs::write(&mass_assignment_path, serde_json::to_string_pretty(&mass_assignment_results)?)?;
+            tracing::info!("Mass assignment findings saved to: {}", mass_assignment_path.display());
+        } else if write_endpoints.is_empty() {
+            println!("      [-] No endpoints accepted POST/PUT/PATCH");
+        } else {
+            println!("      [-] No mass assignment vulnerabilities found");
+        }
+    }
+
+    // Phase 1.5: Field Selection & Sort Key Enumeration
+    println!("   [*] Field selection testing...");
+    tracing::info!("Phase 1.5: Field selector and sort key enumeration on search endpoints");
     
-    // Test POST/PUT endpoints
-    let post_endpoints: Vec<String> = results.iter()
-        .filter(|r| {
-            let url_lower = r.orig_url.to_lowercase();
-            url_lower.contains("/api") || url_lower.contains("/user") || 
-            url_lower.contains("/account") || url_lower.contains("/profile")
-        })
+    let field_tester = api_hunter::fuzz::field_selection::FieldSelectionTester::new(http_client.clone());
+    let mut field_selection_results = Vec::new();
+    
+    let search_endpoints: Vec<&String> = results.iter()
+        .map(|r| &r.orig_url)
+        .filter(|u| api_hunter::fuzz::field_selection::is_search_endpoint(u))
         .take(15)
-        .map(|r| r.orig_url.clone())
         .collect();
     
-    for url in &post_endpoints {
-        for method in &["POST", "PUT", "PATCH"] {
-            match mass_assignment_tester.test_endpoint(url, method).await {
-                Ok(result) => {
-                    if !result.vulnerabilities.is_empty() {
-                        println!("      [!] {} {} mass assignment vulns on {}", result.vulnerabilities.len(), method, url);
-                        mass_assignment_results.push(result);
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Mass assignment test failed for {} {}: {}", method, url, e);
+    for url in search_endpoints {
+        match field_tester.test_endpoint(url).await {
+            Ok(result) => {
+                if !result.vulnerabilities.is_empty() {
+                    println!("      [!] {} field selection issues on {}", result.vulnerabilities.len(), url);
+                    field_selection_results.push(result);
                 }
             }
+            Err(e) => {
+                tracing::warn!("Field selection test failed for {}: {}", url, e);
+            }
         }
     }
     
-    if !mass_assignment_results.is_empty() {
-        let mass_assignment_path = out_dir.join("mass_assignment_findings.json");
-        std::fs::write(&mass_assignment_path, serde_json::to_string_pretty(&mass_assignment_results)?)?;
-        tracing::info!("Mass assignment findings saved to: {}", mass_assignment_path.display());
+    if !field_selection_results.is_empty() {
+        let field_selection_path = paths::artifact(out_dir, "field_selection_findings.json");
+        std::fs::write(&field_selection_path, serde_json::to_string_pretty(&field_selection_results)?)?;
+        tracing::info!("Field selection findings saved to: {}", field_selection_path.display());
     } else {
-        println!("      [-] No mass assignment vulnerabilities found");
+        println!("      [-] No field selection issues found");
     }
     
     // === END PHASE 1 ===
@@ -1104,9 +3400,10 @@ async fn run_deep_analysis(
         "analyses": all_analyses,
         "admin_findings": admin_findings,
         "idor_findings": idor_findings,
+        "mass_assignment_findings": mass_assignment_results,
     });
     std::fs::write(&analysis_path, serde_json::to_string_pretty(&json_data)?)?;
-    write_analysis_summary(&summary_path, &all_analyses, &admin_findings, &idor_findings)?;
+    write_analysis_summary(&summary_path, &all_analyses, &admin_findings, &exposed_files, &observability, &idor_findings, &mass_assignment_results)?;
     tracing::info!("Wrote partial results to: {}", analysis_path.display());
     
     // Phase 2.5: ULTRA-FAST PARALLEL XSS testing - Only on target domain
@@ -1186,12 +3483,46 @@ async fn run_deep_analysis(
         
         if !xss_findings.is_empty() {
             tracing::info!("XSS testing complete: {} vulnerabilities found", xss_findings.len());
-            let xss_path = out_dir.join("xss_findings.json");
+            let xss_path = paths::artifact(out_dir, "xss_findings.json");
             std::fs::write(&xss_path, serde_json::to_string_pretty(&xss_findings)?)?;
             println!("   [=] XSS findings saved to: {}", xss_path.display());
         }
     }
 
+    // Phase 2.6: Open redirects - auth endpoints and URLs that already carry a redirect parameter first
+    {
+        use api_hunter::analyze::open_redirect::{self, OpenRedirectTester};
+        let on_target = |u: &url::Url| target_domain.as_deref().is_some_and(|t| u.host_str().is_some_and(|h| h == t || h.ends_with(&format!(".{}", t))));
+        let mut candidates: Vec<(bool, String)> = results.iter()
+            .filter(|e| (200..400).contains(&e.status))
+            .filter_map(|e| url::Url::parse(&e.final_url).ok().filter(on_target))
+            .map(|u| {
+                let has_param = u.query_pairs().any(|(k, _)| open_redirect::REDIRECT_PARAMS.iter().any(|p| p.eq_ignore_ascii_case(&k)));
+                (has_param || open_redirect::is_auth_flow(u.as_str(), ""), u.to_string())
+            })
+            .collect();
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+        let mut seen = std::collections::HashSet::new();
+        candidates.retain(|(_, u)| seen.insert(u.clone()));
+        candidates.truncate(10);
+        if !candidates.is_empty() {
+            println!("   [*] Open redirect testing {} endpoints...", candidates.len());
+            let tester = OpenRedirectTester::new(Duration::from_secs(10))?;
+            let mut redirect_findings = Vec::new();
+            for (_, url) in &candidates {
+                for f in tester.test(url).await {
+                    println!("   [!] {:?}: {} on {}", f.severity, f.title, url);
+                    redirect_findings.push(f);
+                }
+            }
+            if !redirect_findings.is_empty() {
+                let redirect_path = paths::artifact(out_dir, "open_redirect_findings.json");
+                std::fs::write(&redirect_path, serde_json::to_string_pretty(&redirect_findings)?)?;
+                println!("   [=] Open redirect findings saved to: {}", redirect_path.display());
+            }
+        }
+    }
+
     
     // Phase 3: Admin endpoint scanning IN PARALLEL (if enabled)
     if scan_admin {
@@ -1210,7 +3541,7 @@ async fn run_deep_analysis(
         }
         
         // Run admin scans in parallel
-        let admin_tasks: Vec<_> = base_urls.into_iter().map(|base_url| {
+        let admin_tasks: Vec<_> = base_urls.iter().cloned().map(|base_url| {
             let client = client.clone();
             tokio::spawn(async move {
                 tracing::info!("Scanning admin paths on: {}", base_url);
@@ -1229,22 +3560,74 @@ async fn run_deep_analysis(
             })
         }).collect();
         
+        // Exposed VCS metadata / env files / backups on the same hosts
+        let exposed_tasks: Vec<_> = base_urls.iter().cloned().map(|base_url| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                match scan_exposed_files(&client, &base_url).await {
+                    Ok(found) => Some(found),
+                    Err(e) => {
+                        tracing::warn!("Exposed file scan failed for {}: {}", base_url, e);
+                        None
+                    }
+                }
+            })
+        }).collect();
+        
+        // Metrics, actuators, pprof and server-status pages on the same hosts
+        let observability_tasks: Vec<_> = base_urls.iter().cloned().map(|base_url| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                match scan_observability(&client, &base_url).await {
+                    Ok(found) => Some(found),
+                    Err(e) => {
+                        tracing::warn!("Observability scan failed for {}: {}", base_url, e);
+                        None
+                    }
+                }
+            })
+        }).collect();
+        
         // Collect admin findings
         for task in admin_tasks {
             if let Ok(Some(findings)) = task.await {
                 admin_findings.extend(findings);
             }
         }
+        for task in exposed_tasks {
+            if let Ok(Some(found)) = task.await {
+                for f in &found {
+                    println!("      [!] Exposed {}: {}", f.kind, f.url);
+                }
+                exposed_files.extend(found);
+            }
+        }
+        for task in observability_tasks {
+            if let Ok(Some(found)) = task.await {
+                for e in &found {
+                    println!("      [!] {}: {} ({})", e.kind.label(), e.url, e.summary);
+                }
+                observability.extend(found);
+            }
+        }
+        if !observability.is_empty() {
+            let observability_path = paths::artifact(out_dir, "observability.json");
+            std::fs::write(&observability_path, serde_json::to_string_pretty(&observability)?)?;
+            println!("   [=] Observability endpoints saved to: {}", observability_path.display());
+        }
         
         // Write admin results immediately
         tracing::info!("Updating analysis results with admin findings...");
         let json_data = serde_json::json!({
             "analyses": all_analyses,
             "admin_findings": admin_findings,
+            "exposed_files": exposed_files,
+            "observability_endpoints": observability,
             "idor_findings": idor_findings,
+            "mass_assignment_findings": mass_assignment_results,
         });
         std::fs::write(&analysis_path, serde_json::to_string_pretty(&json_data)?)?;
-        write_analysis_summary(&summary_path, &all_analyses, &admin_findings, &idor_findings)?;
+        write_analysis_summary(&summary_path, &all_analyses, &admin_findings, &exposed_files, &observability, &idor_findings, &mass_assignment_results)?;
     }
     
     // Phase 4: Advanced IDOR testing (if enabled via aggressive mode)
@@ -1299,12 +3682,15 @@ async fn run_deep_analysis(
     let json_data = serde_json::json!({
         "analyses": all_analyses,
         "admin_findings": admin_findings,
+        "exposed_files": exposed_files,
+        "observability_endpoints": observability,
         "idor_findings": idor_findings,
+        "mass_assignment_findings": mass_assignment_results,
     });
     std::fs::write(&analysis_path, serde_json::to_string_pretty(&json_data)?)?;
     
     // Write summary
-    let (critical, high, medium) = write_analysis_summary(&summary_path, &all_analyses, &admin_findings, &idor_findings)?;
+    let (critical, high, medium) = write_analysis_summary(&summary_path, &all_analyses, &admin_findings, &exposed_files, &observability, &idor_findings, &mass_assignment_results)?;
     
     // Only show if findings exist
     if critical > 0 || high > 0 || medium > 0 {
@@ -1318,7 +3704,10 @@ fn write_analysis_summary(
     summary_path: &PathBuf,
     all_analyses: &[api_hunter::analyze::api_analyzer::ApiAnalysis],
     admin_findings: &[api_hunter::analyze::admin_scanner::AdminScanResult],
+    exposed_files: &[api_hunter::analyze::exposed_files::ExposedFile],
+    observability: &[api_hunter::analyze::observability::ObservabilityEndpoint],
     idor_findings: &[api_hunter::fuzz::idor_tester::IdorTestResult],
+    mass_assignment: &[api_hunter::fuzz::mass_assignment::MassAssignmentResult],
 ) -> anyhow::Result<(usize, usize, usize)> {
     use api_hunter::analyze::admin_scanner::RiskLevel;
     use api_hunter::fuzz::idor_tester::IdorRiskLevel;
@@ -1403,6 +3792,62 @@ fn write_analysis_summary(
         }
     }
     
+    // Exposed files
+    if !exposed_files.is_empty() {
+        writeln!(summary_file)?;
+        writeln!(summary_file, "=== Exposed Files ===")?;
+        for file in exposed_files {
+            let risk_emoji = match file.risk_level {
+                RiskLevel::Critical => "🔴 CRITICAL",
+                RiskLevel::High => "🟠 HIGH",
+                RiskLevel::Medium => "🟡 MEDIUM",
+                RiskLevel::Low => "🔵 LOW",
+            };
+            writeln!(summary_file, "{} - {} ({})", risk_emoji, file.url, file.kind)?;
+            writeln!(summary_file, "  Evidence: {}", file.evidence)?;
+            writeln!(summary_file, "  Extraction: {}", file.extraction_hint)?;
+            
+            match file.risk_level {
+                RiskLevel::Critical => critical_findings += 1,
+                RiskLevel::High => high_findings += 1,
+                RiskLevel::Medium => medium_findings += 1,
+                _ => {}
+            }
+        }
+    }
+    
+    // Observability endpoints
+    if !observability.is_empty() {
+        writeln!(summary_file)?;
+        writeln!(summary_file, "=== Observability Endpoints ===")?;
+        for endpoint in observability {
+            let risk_emoji = match endpoint.risk_level {
+                RiskLevel::Critical => "🔴 CRITICAL",
+                RiskLevel::High => "🟠 HIGH",
+                RiskLevel::Medium => "🟡 MEDIUM",
+                RiskLevel::Low => "🔵 LOW",
+            };
+            writeln!(summary_file, "{} - {} ({})", risk_emoji, endpoint.url, endpoint.kind.label())?;
+            writeln!(summary_file, "  {}", endpoint.summary)?;
+            if !endpoint.routes.is_empty() {
+                writeln!(summary_file, "  Routes: {}", endpoint.routes.join(", "))?;
+            }
+            if !endpoint.secrets.is_empty() {
+                writeln!(summary_file, "  Unmasked secrets: {}", endpoint.secrets.join(", "))?;
+            }
+            if let Some(ref heap) = endpoint.heap {
+                writeln!(summary_file, "  Heap: {}", heap)?;
+            }
+            
+            match endpoint.risk_level {
+                RiskLevel::Critical => critical_findings += 1,
+                RiskLevel::High => high_findings += 1,
+                RiskLevel::Medium => medium_findings += 1,
+                _ => {}
+            }
+        }
+    }
+    
     // IDOR Findings
     if !idor_findings.is_empty() {
         writeln!(summary_file, "")?;
@@ -1428,6 +3873,32 @@ fn write_analysis_summary(
         }
     }
     
+    // Mass assignment
+    if mass_assignment.iter().any(|r| !r.vulnerabilities.is_empty()) {
+        writeln!(summary_file)?;
+        writeln!(summary_file, "=== Mass Assignment ===")?;
+        for result in mass_assignment {
+            for vuln in &result.vulnerabilities {
+                let risk_emoji = match vuln.severity.as_str() {
+                    "CRITICAL" => "🔴 CRITICAL",
+                    "HIGH" => "🟠 HIGH",
+                    _ => "🟡 MEDIUM",
+                };
+                writeln!(summary_file, "{} - {} ({})", risk_emoji, result.url, vuln.vuln_type)?;
+                writeln!(summary_file, "  Parameter: {}", vuln.parameter)?;
+                if let Some(ref payload) = vuln.payload {
+                    writeln!(summary_file, "  Payload: {}", payload)?;
+                }
+
+                match vuln.severity.as_str() {
+                    "CRITICAL" => critical_findings += 1,
+                    "HIGH" => high_findings += 1,
+                    _ => medium_findings += 1,
+                }
+            }
+        }
+    }
+
     // Overall Summary
     writeln!(summary_file, "")?;
     writeln!(summary_file, "=== Overall Summary ===")?;
@@ -1457,6 +3928,171 @@ async fn print_final_statistics(
     Ok(())
 }
 
+async fn handle_flow_command(files: Vec<String>, out: Option<String>) -> anyhow::Result<()> {
+    use api_hunter::fuzz::flows::{Flow, FlowRunner};
+
+    let client = api_hunter::http_client::builder("runner::flow").user_agent(api_hunter::tls_profile::user_agent_or("api-hunter/0.1")).build()?;
+    let runner = FlowRunner::new(client);
+    let mut results = Vec::new();
+
+    for file in &files {
+        let flow = Flow::load(std::path::Path::new(file))?;
+        println!("   [*] Flow '{}' ({} steps)", flow.name, flow.steps.len());
+
+        let result = runner.run(&flow).await;
+        for step in &result.steps {
+            println!("      [+] {} {} -> {}", step.method, step.url, step.status);
+        }
+        if let Some(reason) = &result.aborted {
+            println!("      [-] Aborted: {}", reason);
+        }
+        for finding in &result.findings {
+            println!("      [!] {} - {}: {}", finding.severity, finding.step, finding.description);
+        }
+        results.push(result);
+    }
+
+    let total: usize = results.iter().map(|r| r.findings.len()).sum();
+    println!("\n[*] Flows: {} run, {} findings", results.len(), total);
+
+    if let Some(out) = out {
+        std::fs::write(&out, serde_json::to_string_pretty(&results)?)?;
+        println!("[=] Results saved to: {}", out);
+        let dir = std::path::Path::new(&out).parent().unwrap_or(std::path::Path::new("."));
+        write_identify_audit(dir)?;
+    }
+
+    Ok(())
+}
+
+async fn handle_race_command(
+    url: String,
+    method: String,
+    data: Option<String>,
+    config: api_hunter::fuzz::race::RaceConfig,
+    out: Option<String>,
+    report: Option<String>,
+) -> anyhow::Result<()> {
+    use api_hunter::fuzz::race::{self, RaceRequest};
+
+    // Headers come from the global -H, --identify and --auth-* options
+    let request = RaceRequest { method, url, headers: Vec::new(), body: data };
+    println!("   [*] Race: {} x {} {} (last-byte sync)", config.count, request.method, request.url);
+
+    let result = race::run(&request, &config).await?;
+    let statuses: Vec<String> = result.status_counts.iter().map(|(s, n)| format!("{}x{}", n, s)).collect();
+    println!("      [+] Responses: {} ({} without answer), final bytes sent within {:.1} ms", statuses.join(", "), result.failed, result.sync_spread_ms);
+    if result.vulnerable {
+        println!("      [!] {} requests succeeded, {} expected - race condition", result.successes, result.expected);
+    } else {
+        println!("      [-] {} requests succeeded ({} expected)", result.successes, result.expected);
+    }
+
+    if let Some(out) = out {
+        std::fs::write(&out, serde_json::to_string_pretty(&result)?)?;
+        println!("[=] Results saved to: {}", out);
+    }
+
+    if let Some(report_path) = report {
+        use api_hunter::output::clean_reporter::{Finding, ScanReport, Severity};
+        let host = url::Url::parse(&result.url).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_else(|| result.url.clone());
+        let mut scan_report = ScanReport::new(host);
+        scan_report.total_endpoints = 1;
+        if result.vulnerable {
+            let statuses: Vec<String> = result.status_counts.iter().map(|(s, n)| format!("{}x{}", n, s)).collect();
+            scan_report.add_finding(Finding {
+                severity: Severity::High,
+                category: "Race Condition".to_string(),
+                title: format!("{} of {} parallel requests succeeded: {} {}", result.successes, result.sent, result.method, result.url),
+                description: format!("The endpoint should allow {} success(es), but {} identical requests sent within {:.1} ms succeeded", result.expected, result.successes, result.sync_spread_ms),
+                url: result.url.clone(),
+                evidence: vec![format!("Responses: {}", statuses.join(", "))],
+                remediation: Some("Make the check and the state change one atomic operation (row lock, unique constraint, idempotency key)".to_string()),
+            });
+        }
+        scan_report.save_to_file(std::path::Path::new(&report_path))?;
+        println!("[=] Report: {}", report_path);
+    }
+    Ok(())
+}
+
+fn handle_schema_command(out: &str) -> anyhow::Result<()> {
+    use api_hunter::output::schema::{self, SCHEMA_VERSION};
+
+    let written = schema::write_all(std::path::Path::new(out))?;
+    println!("[*] Output schema v{}", SCHEMA_VERSION);
+    for path in &written {
+        println!("      [+] {}", path.display());
+    }
+    Ok(())
+}
+
+fn handle_jwt_command(command: JwtCommand) -> anyhow::Result<()> {
+    use api_hunter::security::jwt_cracker::CrackTarget;
+
+    match command {
+        JwtCommand::Crack { token, wordlist, threads } => {
+            let target = CrackTarget::parse(&token).map_err(|e| anyhow::anyhow!(e))?;
+            let file = std::fs::File::open(&wordlist).map_err(|e| anyhow::anyhow!("failed to open {}: {}", wordlist, e))?;
+            let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            println!("[*] Cracking {} secret with {} ({:.1} MB, {} threads)", target.alg.name(), wordlist, size as f64 / 1_048_576.0,
+                if threads == 0 { "all".to_string() } else { threads.to_string() });
+            let start = std::time::Instant::now();
+            let last = parking_lot::Mutex::new(start);
+            let found = target.crack(std::io::BufReader::with_capacity(1 << 20, file), threads, |tried| {
+                let mut last = last.lock();
+                if last.elapsed() >= Duration::from_secs(1) {
+                    *last = std::time::Instant::now();
+                    let rate = tried as f64 / start.elapsed().as_secs_f64().max(0.001);
+                    print!("\r   [-] {} tried ({:.0}/s)   ", tried, rate);
+                    let _ = std::io::stdout().flush();
+                }
+            })?;
+            println!();
+            match found {
+                Some(secret) => println!("[+] Secret found after {:.1}s: {}", start.elapsed().as_secs_f64(), String::from_utf8_lossy(&secret)),
+                None => println!("[-] Secret not in the wordlist ({:.1}s)", start.elapsed().as_secs_f64()),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_signatures_command(command: SignaturesCommand) -> anyhow::Result<()> {
+    use api_hunter::waf::signatures::{self, SignatureFile};
+
+    match command {
+        SignaturesCommand::Update { url, out } => {
+            let path = match out {
+                Some(out) => PathBuf::from(out),
+                None => signatures::user_path().ok_or_else(|| anyhow::anyhow!("HOME is not set, pass --out"))?,
+            };
+            let client = api_hunter::http_client::third_party().user_agent("api-hunter/0.1").build()?;
+            println!("[*] Fetching WAF signatures from {}", url);
+            let file = signatures::update(&client, &url, &path).await?;
+            println!("[+] {} signatures (version {}) written to {}", file.signatures.len(), file.version, path.display());
+        }
+        SignaturesCommand::Show => {
+            let user = signatures::user_path().filter(|p| p.exists());
+            let (source, file) = match &user {
+                Some(path) => match std::fs::read_to_string(path).map_err(anyhow::Error::from).and_then(|d| SignatureFile::parse(&d)) {
+                    Ok(file) => (path.display().to_string(), file),
+                    Err(e) => {
+                        println!("[!] {}: {:#}", path.display(), e);
+                        ("built-in".to_string(), SignatureFile::parse(signatures::EMBEDDED)?)
+                    }
+                },
+                None => ("built-in".to_string(), SignatureFile::parse(signatures::EMBEDDED)?),
+            };
+            println!("[*] WAF signatures: {} (version {})", source, file.version);
+            for sig in &file.signatures {
+                println!("      [-] {}: {} header patterns, {} cookies, {} block page patterns", sig.waf, sig.headers.len() + sig.server_patterns.len(), sig.cookies.len(), sig.body_patterns.len());
+            }
+        }
+    }
+    Ok(())
+}
+
 async fn handle_test_endpoint_command(
     url: String,
     fuzz: bool,
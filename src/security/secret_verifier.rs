@@ -0,0 +1,239 @@
+//! Live validation of discovered secrets (`--verify-secrets`, opt-in)
+//! Each recognized key type gets one harmless, read-only call against its provider:
+//! AWS STS `GetCallerIdentity`, Stripe `GET /v1/balance` and Slack `auth.test`. The result is stored on
+//! the secret, so `js_critical_info.json` shows which leaks are still live.
+
+use crate::gather::js_deep_analyzer::{Secret, SecretType};
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+static AWS_KEY_ID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:AKIA|ASIA)[0-9A-Z]{16}$").unwrap());
+static AWS_SECRET_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9/+]{40}$").unwrap());
+static AWS_ARN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<Arn>([^<]+)</Arn>").unwrap());
+
+/// Upper bound on provider calls per scan
+const MAX_VERIFICATIONS: usize = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    /// The provider accepted the credential
+    Active,
+    /// The provider rejected it (revoked, expired or never valid)
+    Revoked,
+    /// Not checked or inconclusive (network error, missing key half, rate limit)
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Verification {
+    pub provider: String,
+    pub status: VerificationStatus,
+    /// Account/identity for active keys, or why the check was inconclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Verification {
+    fn new(provider: &str, status: VerificationStatus, detail: Option<String>) -> Self {
+        Self { provider: provider.to_string(), status, detail }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    AwsKeyId,
+    Stripe,
+    Slack,
+}
+
+/// Key types that can be validated, by value format
+pub fn classify(value: &str) -> Option<Provider> {
+    if AWS_KEY_ID_RE.is_match(value) {
+        Some(Provider::AwsKeyId)
+    } else if ["sk_live_", "sk_test_", "rk_live_", "rk_test_"].iter().any(|p| value.starts_with(p)) {
+        Some(Provider::Stripe)
+    } else if ["xoxb-", "xoxp-", "xoxa-", "xoxs-", "xoxe."].iter().any(|p| value.starts_with(p)) {
+        Some(Provider::Slack)
+    } else {
+        None
+    }
+}
+
+/// STS answers 200 with the caller ARN for valid keys and 403 `InvalidClientTokenId` for unknown ones
+pub fn interpret_aws(status: u16, body: &str) -> Verification {
+    match status {
+        200 => Verification::new("aws", VerificationStatus::Active, AWS_ARN_RE.captures(body).map(|c| c[1].to_string())),
+        403 if body.contains("InvalidClientTokenId") || body.contains("SignatureDoesNotMatch") => {
+            Verification::new("aws", VerificationStatus::Revoked, None)
+        }
+        s => Verification::new("aws", VerificationStatus::Unknown, Some(format!("HTTP {}", s))),
+    }
+}
+
+/// Stripe: 200 valid, 401 invalid, 403 valid restricted key without balance access
+pub fn interpret_stripe(status: u16) -> Verification {
+    match status {
+        200 => Verification::new("stripe", VerificationStatus::Active, None),
+        403 => Verification::new("stripe", VerificationStatus::Active, Some("restricted key".to_string())),
+        401 => Verification::new("stripe", VerificationStatus::Revoked, None),
+        s => Verification::new("stripe", VerificationStatus::Unknown, Some(format!("HTTP {}", s))),
+    }
+}
+
+/// Slack always answers 200; `ok` and `error` in the body carry the result
+pub fn interpret_slack(body: &str) -> Verification {
+    let json: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+    if json["ok"].as_bool() == Some(true) {
+        let detail = match (json["team"].as_str(), json["user"].as_str()) {
+            (Some(team), Some(user)) => Some(format!("{} / {}", team, user)),
+            (Some(team), None) => Some(team.to_string()),
+            _ => None,
+        };
+        return Verification::new("slack", VerificationStatus::Active, detail);
+    }
+    match json["error"].as_str() {
+        Some(e @ ("invalid_auth" | "token_revoked" | "token_expired" | "account_inactive" | "not_authed")) => {
+            Verification::new("slack", VerificationStatus::Revoked, Some(e.to_string()))
+        }
+        other => Verification::new("slack", VerificationStatus::Unknown, other.map(str::to_string)),
+    }
+}
+
+/// AWS Signature Version 4 for a request signing `content-type`, `host` and `x-amz-date`
+#[allow(clippy::too_many_arguments)]
+pub fn sigv4_signature(method: &str, host: &str, query: &str, body: &str, amz_date: &str, region: &str, service: &str, secret_key: &str) -> String {
+    let date = &amz_date[..8];
+    let canonical_request = format!(
+        "{}\n/\n{}\ncontent-type:application/x-www-form-urlencoded; charset=utf-8\nhost:{}\nx-amz-date:{}\n\ncontent-type;host;x-amz-date\n{}",
+        method, query, host, amz_date, hex(&Sha256::digest(body.as_bytes())),
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex(&Sha256::digest(canonical_request.as_bytes())));
+
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub struct SecretVerifier {
+    client: Client,
+}
+
+impl SecretVerifier {
+    pub fn new() -> Result<Self> {
+        // Provider APIs, not the target: no identification headers
+        let client = crate::http_client::third_party()
+            .timeout(Duration::from_secs(10))
+            .user_agent("api-hunter/0.1")
+            .build()?;
+        Ok(Self { client })
+    }
+
+    /// Verify every recognized secret in place. AWS key IDs are paired with secret access keys found
+    /// in the same file first, then anywhere.
+    pub async fn verify_all(&self, secrets: &mut [Secret]) {
+        let aws_secrets: Vec<(String, String)> = secrets.iter()
+            .filter(|s| matches!(s.secret_type, SecretType::AwsKey | SecretType::Generic) && AWS_SECRET_RE.is_match(&s.value))
+            .map(|s| (s.source_file.clone(), s.value.clone()))
+            .collect();
+
+        let mut calls = 0;
+        for secret in secrets.iter_mut() {
+            let Some(provider) = classify(&secret.value) else { continue };
+            if calls >= MAX_VERIFICATIONS {
+                secret.verification = Some(Verification::new(provider_name(provider), VerificationStatus::Unknown, Some("verification limit reached".to_string())));
+                continue;
+            }
+            let result = match provider {
+                Provider::Stripe => self.verify_stripe(&secret.value).await,
+                Provider::Slack => self.verify_slack(&secret.value).await,
+                Provider::AwsKeyId if secret.value.starts_with("ASIA") => {
+                    Verification::new("aws", VerificationStatus::Unknown, Some("temporary key, needs a session token".to_string()))
+                }
+                Provider::AwsKeyId => {
+                    let mut candidates: Vec<&(String, String)> = aws_secrets.iter().filter(|(f, _)| *f == secret.source_file).collect();
+                    candidates.extend(aws_secrets.iter().filter(|(f, _)| *f != secret.source_file));
+                    let mut result = Verification::new("aws", VerificationStatus::Unknown, Some("no secret access key found".to_string()));
+                    for (_, secret_key) in candidates.into_iter().take(3) {
+                        result = self.verify_aws(&secret.value, secret_key).await;
+                        if result.status == VerificationStatus::Active {
+                            break;
+                        }
+                    }
+                    result
+                }
+            };
+            calls += 1;
+            tracing::info!("Secret verification ({}): {:?}", result.provider, result.status);
+            secret.verification = Some(result);
+        }
+    }
+
+    async fn verify_aws(&self, key_id: &str, secret_key: &str) -> Verification {
+        let host = "sts.amazonaws.com";
+        let body = "Action=GetCallerIdentity&Version=2011-06-15";
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let amz_date = crate::output::results_manager::format_timestamp(now);
+        let signature = sigv4_signature("POST", host, "", body, &amz_date, "us-east-1", "sts", secret_key);
+        let auth = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}/us-east-1/sts/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature={}",
+            key_id, &amz_date[..8], signature,
+        );
+        let resp = self.client.post(format!("https://{}/", host))
+            .header("content-type", "application/x-www-form-urlencoded; charset=utf-8")
+            .header("x-amz-date", &amz_date)
+            .header("authorization", auth)
+            .body(body)
+            .send()
+            .await;
+        match resp {
+            Ok(r) => {
+                let status = r.status().as_u16();
+                interpret_aws(status, &r.text().await.unwrap_or_default())
+            }
+            Err(e) => Verification::new("aws", VerificationStatus::Unknown, Some(e.to_string())),
+        }
+    }
+
+    async fn verify_stripe(&self, key: &str) -> Verification {
+        match self.client.get("https://api.stripe.com/v1/balance").bearer_auth(key).send().await {
+            Ok(r) => interpret_stripe(r.status().as_u16()),
+            Err(e) => Verification::new("stripe", VerificationStatus::Unknown, Some(e.to_string())),
+        }
+    }
+
+    async fn verify_slack(&self, token: &str) -> Verification {
+        match self.client.post("https://slack.com/api/auth.test").bearer_auth(token).send().await {
+            Ok(r) => interpret_slack(&r.text().await.unwrap_or_default()),
+            Err(e) => Verification::new("slack", VerificationStatus::Unknown, Some(e.to_string())),
+        }
+    }
+}
+
+fn provider_name(provider: Provider) -> &'static str {
+    match provider {
+        Provider::AwsKeyId => "aws",
+        Provider::Stripe => "stripe",
+        Provider::Slack => "slack",
+    }
+}
@@ -0,0 +1,131 @@
+//! API token classification by format
+//! Most providers put the token kind into a fixed prefix (`sk_live_`, `ghp_`, `xoxb-`, `AKIA`), which
+//! tells the provider, whether it's a production or test credential and how much it can do without
+//! calling anyone. Each known format carries a severity and a read-only call to confirm it by hand
+//! (`--verify-secrets` does this automatically for AWS, Stripe and Slack).
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Privilege {
+    /// Full account access
+    Admin,
+    /// Scoped to what the token was granted (PATs, restricted keys, bot tokens)
+    Scoped,
+    /// Short-lived access or refresh token
+    Session,
+    /// Meant to be public (publishable keys, identifiers)
+    Public,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct TokenClass {
+    pub provider: String,
+    /// e.g. "live secret key"
+    pub kind: String,
+    pub privilege: Privilege,
+    /// Production credential; `None` where the format doesn't tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live: Option<bool>,
+    /// HIGH, MEDIUM or LOW
+    pub severity: String,
+    /// Read-only request that confirms the token (`<token>` stands for the value)
+    pub validation: String,
+}
+
+struct Format {
+    pattern: &'static str,
+    provider: &'static str,
+    kind: &'static str,
+    privilege: Privilege,
+    live: Option<bool>,
+    severity: &'static str,
+    validation: &'static str,
+}
+
+/// Known formats; the first match wins, so specific prefixes come before general ones
+const FORMATS: &[Format] = &[
+    // Stripe
+    Format { pattern: r"sk_live_[0-9A-Za-z]{24,}", provider: "stripe", kind: "live secret key", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl https://api.stripe.com/v1/balance -u <token>:" },
+    Format { pattern: r"rk_live_[0-9A-Za-z]{24,}", provider: "stripe", kind: "live restricted key", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl https://api.stripe.com/v1/balance -u <token>:" },
+    Format { pattern: r"sk_test_[0-9A-Za-z]{24,}", provider: "stripe", kind: "test secret key", privilege: Privilege::Admin, live: Some(false), severity: "MEDIUM", validation: "curl https://api.stripe.com/v1/balance -u <token>:" },
+    Format { pattern: r"rk_test_[0-9A-Za-z]{24,}", provider: "stripe", kind: "test restricted key", privilege: Privilege::Scoped, live: Some(false), severity: "LOW", validation: "curl https://api.stripe.com/v1/balance -u <token>:" },
+    Format { pattern: r"whsec_[0-9A-Za-z]{24,}", provider: "stripe", kind: "webhook signing secret", privilege: Privilege::Scoped, live: None, severity: "HIGH", validation: "None without the endpoint: lets anyone forge webhook events" },
+    Format { pattern: r"pk_live_[0-9A-Za-z]{24,}", provider: "stripe", kind: "live publishable key", privilege: Privilege::Public, live: Some(true), severity: "LOW", validation: "Publishable by design; check it isn't paired with a secret key" },
+    Format { pattern: r"pk_test_[0-9A-Za-z]{24,}", provider: "stripe", kind: "test publishable key", privilege: Privilege::Public, live: Some(false), severity: "LOW", validation: "Publishable by design; test mode in production code" },
+    // GitHub
+    Format { pattern: r"github_pat_[0-9A-Za-z_]{60,}", provider: "github", kind: "fine-grained personal access token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://api.github.com/user" },
+    Format { pattern: r"ghp_[0-9A-Za-z]{36}", provider: "github", kind: "personal access token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -I -H 'Authorization: Bearer <token>' https://api.github.com/user (scopes in X-OAuth-Scopes)" },
+    Format { pattern: r"gho_[0-9A-Za-z]{36}", provider: "github", kind: "OAuth access token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -I -H 'Authorization: Bearer <token>' https://api.github.com/user (scopes in X-OAuth-Scopes)" },
+    Format { pattern: r"ghu_[0-9A-Za-z]{36}", provider: "github", kind: "GitHub App user token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://api.github.com/user" },
+    Format { pattern: r"ghr_[0-9A-Za-z]{36,}", provider: "github", kind: "GitHub App refresh token", privilege: Privilege::Session, live: Some(true), severity: "HIGH", validation: "Needs the app's client secret to exchange; rotate the app credentials" },
+    Format { pattern: r"ghs_[0-9A-Za-z]{36}", provider: "github", kind: "GitHub App installation token", privilege: Privilege::Session, live: Some(true), severity: "MEDIUM", validation: "curl -H 'Authorization: Bearer <token>' https://api.github.com/installation/repositories (valid for 1h)" },
+    // GitLab
+    Format { pattern: r"glpat-[0-9A-Za-z_\-]{20,}", provider: "gitlab", kind: "personal access token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -H 'PRIVATE-TOKEN: <token>' https://gitlab.com/api/v4/personal_access_tokens/self" },
+    // AWS
+    Format { pattern: r"AKIA[0-9A-Z]{16}", provider: "aws", kind: "long-term access key ID", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "aws sts get-caller-identity (needs the secret access key)" },
+    Format { pattern: r"ASIA[0-9A-Z]{16}", provider: "aws", kind: "temporary access key ID", privilege: Privilege::Session, live: Some(true), severity: "MEDIUM", validation: "aws sts get-caller-identity (needs the secret key and session token)" },
+    // Slack
+    Format { pattern: r"xoxb-[0-9A-Za-z\-]{10,}", provider: "slack", kind: "bot token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://slack.com/api/auth.test" },
+    Format { pattern: r"xoxp-[0-9A-Za-z\-]{10,}", provider: "slack", kind: "user token", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://slack.com/api/auth.test" },
+    Format { pattern: r"xox[aors]-[0-9A-Za-z\-]{10,}", provider: "slack", kind: "workspace or session token", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://slack.com/api/auth.test" },
+    Format { pattern: r"xoxe\.xox[bp]-[0-9A-Za-z\-]{10,}", provider: "slack", kind: "rotating token", privilege: Privilege::Session, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://slack.com/api/auth.test" },
+    Format { pattern: r"xapp-[0-9A-Za-z\-]{10,}", provider: "slack", kind: "app-level token", privilege: Privilege::Scoped, live: Some(true), severity: "MEDIUM", validation: "curl -X POST -H 'Authorization: Bearer <token>' https://slack.com/api/apps.connections.open" },
+    // Google
+    Format { pattern: r"AIza[0-9A-Za-z_\-]{35}", provider: "google", kind: "API key", privilege: Privilege::Scoped, live: Some(true), severity: "MEDIUM", validation: "curl 'https://maps.googleapis.com/maps/api/geocode/json?address=x&key=<token>' (check which APIs it's restricted to)" },
+    Format { pattern: r"GOCSPX-[0-9A-Za-z_\-]{28}", provider: "google", kind: "OAuth client secret", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "Exchange a code at https://oauth2.googleapis.com/token with the client id" },
+    Format { pattern: r"ya29\.[0-9A-Za-z_\-]{20,}", provider: "google", kind: "OAuth access token", privilege: Privilege::Session, live: Some(true), severity: "HIGH", validation: "curl 'https://oauth2.googleapis.com/tokeninfo?access_token=<token>'" },
+    // Other providers with prefixed tokens
+    Format { pattern: r"SG\.[0-9A-Za-z_\-]{22}\.[0-9A-Za-z_\-]{43}", provider: "sendgrid", kind: "API key", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://api.sendgrid.com/v3/scopes" },
+    Format { pattern: r"sk-ant-[0-9A-Za-z_\-]{20,}", provider: "anthropic", kind: "API key", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl -H 'x-api-key: <token>' -H 'anthropic-version: 2023-06-01' https://api.anthropic.com/v1/models" },
+    Format { pattern: r"sk-(?:proj|svcacct|admin)-[0-9A-Za-z_\-]{20,}", provider: "openai", kind: "API key", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://api.openai.com/v1/models" },
+    Format { pattern: r"npm_[0-9A-Za-z]{36}", provider: "npm", kind: "access token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://registry.npmjs.org/-/whoami" },
+    Format { pattern: r"pypi-AgEIcHlwaS5vcmc[0-9A-Za-z_\-]{50,}", provider: "pypi", kind: "API token", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "Upload-only; confirm the scope in the PyPI account and revoke" },
+    Format { pattern: r"shpat_[0-9a-fA-F]{32}", provider: "shopify", kind: "admin API access token", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl -H 'X-Shopify-Access-Token: <token>' https://<shop>.myshopify.com/admin/api/2024-01/shop.json" },
+    Format { pattern: r"shpss_[0-9a-fA-F]{32}", provider: "shopify", kind: "app shared secret", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "None: lets anyone sign webhooks and app requests" },
+    Format { pattern: r"sq0atp-[0-9A-Za-z_\-]{22}", provider: "square", kind: "access token", privilege: Privilege::Admin, live: Some(true), severity: "HIGH", validation: "curl -H 'Authorization: Bearer <token>' https://connect.squareup.com/v2/locations" },
+    Format { pattern: r"sq0csp-[0-9A-Za-z_\-]{43}", provider: "square", kind: "application secret", privilege: Privilege::Scoped, live: Some(true), severity: "HIGH", validation: "None without the application id; rotate in the developer dashboard" },
+    Format { pattern: r"SK[0-9a-fA-F]{32}", provider: "twilio", kind: "API key SID", privilege: Privilege::Scoped, live: None, severity: "MEDIUM", validation: "curl -u <token>:<secret> https://api.twilio.com/2010-04-01/Accounts.json (needs the key secret)" },
+    Format { pattern: r"AC[0-9a-fA-F]{32}", provider: "twilio", kind: "account SID", privilege: Privilege::Public, live: None, severity: "LOW", validation: "Identifier only; look for the auth token nearby" },
+    Format { pattern: r"https://hooks\.slack\.com/services/T[0-9A-Z]+/B[0-9A-Z]+/[0-9A-Za-z]+", provider: "slack", kind: "incoming webhook", privilege: Privilege::Scoped, live: Some(true), severity: "MEDIUM", validation: "POST {} to the URL: 400 invalid_payload means it's live (no message is sent)" },
+    Format { pattern: r"https://discord(?:app)?\.com/api/webhooks/[0-9]+/[0-9A-Za-z_\-]+", provider: "discord", kind: "webhook", privilege: Privilege::Scoped, live: Some(true), severity: "MEDIUM", validation: "curl <token> (GET returns the webhook's channel and guild)" },
+    Format { pattern: r"-----BEGIN (?:RSA |EC |OPENSSH |DSA |ENCRYPTED )?PRIVATE KEY-----", provider: "generic", kind: "private key", privilege: Privilege::Admin, live: None, severity: "HIGH", validation: "Match against the target's public keys (TLS certificate, JWKS, authorized_keys)" },
+];
+
+/// One anchored regex per format, in `FORMATS` order
+static ANCHORED: Lazy<Vec<Regex>> = Lazy::new(|| FORMATS.iter().map(|f| Regex::new(&format!("^(?:{})$", f.pattern)).unwrap()).collect());
+
+/// All formats, for finding tokens in text
+static ANY: Lazy<Regex> = Lazy::new(|| {
+    let alternatives: Vec<String> = FORMATS.iter().map(|f| format!("(?:{})", f.pattern)).collect();
+    Regex::new(&format!(r"(?:^|[^0-9A-Za-z_\-])({})", alternatives.join("|"))).unwrap()
+});
+
+/// Provider, privilege and severity of `value`, if it's a known token format
+pub fn classify(value: &str) -> Option<TokenClass> {
+    let value = value.trim();
+    let i = ANCHORED.iter().position(|re| re.is_match(value))?;
+    let f = &FORMATS[i];
+    Some(TokenClass {
+        provider: f.provider.to_string(),
+        kind: f.kind.to_string(),
+        privilege: f.privilege,
+        live: f.live,
+        severity: f.severity.to_string(),
+        validation: f.validation.to_string(),
+    })
+}
+
+/// Tokens of a known format in `content`, with their offsets
+pub fn find_tokens(content: &str) -> Vec<(usize, &str)> {
+    ANY.captures_iter(content)
+        .filter_map(|c| c.get(1))
+        // A fixed-length format that runs on is part of a longer string
+        .filter(|m| !content[m.end()..].starts_with(|c: char| c.is_ascii_alphanumeric()))
+        .map(|m| (m.start(), m.as_str()))
+        .collect()
+}
@@ -1,4 +1,5 @@
 use crate::probe::advanced_tests::{AdvancedTester, print_results};
+use crate::probe::rate_limit_bypass::RateLimitBypassTester;
 use crate::probe::graphql::{GraphQLTester, print_graphql_results};
 use crate::probe::websocket::{WebSocketTester, print_websocket_results};
 use crate::discover::api_docs::{ApiDocsDiscovery, print_api_docs_results};
@@ -56,6 +57,20 @@ pub async fn run_endpoint_tests(url: &str, include_fuzzing: bool, num_rate_limit
     println!("[*] Phase 3: Rate Limiting Testing");
     let rate_results = tester.test_rate_limiting(num_rate_limit_requests).await;
     print_results(&rate_results);
+    if rate_results.iter().any(|r| r.status == 429) {
+        println!("[*] Phase 3.5: Rate Limit Bypass Testing");
+        let http = crate::http_client::HttpClient::new(crate::http_client::builder("probe::rate_limit_bypass").build()?);
+        let report = RateLimitBypassTester::new(&http).test(url).await;
+        if !report.limited {
+            println!("    [-] Limit already expired, bypasses not tested");
+        } else if report.bypasses.is_empty() {
+            println!("    [+] None of {} bypass variants got through", report.tried);
+        }
+        for b in &report.bypasses {
+            println!("    [!] {} -> {} ({})", b.variant.technique, b.status, b.variant.url);
+        }
+        println!();
+    }
 
     // Phase 4: Deep Response Analysis
     println!("[*] Phase 4: Deep Response Analysis");
@@ -0,0 +1,250 @@
+//! Browser TLS profiles (`--tls-profile`)
+//! Target clients send a ClientHello shaped like the selected browser's - its cipher suite, key exchange
+//! group and signature algorithm order and its ALPN list (unless `--http-version` narrows it) - together
+//! with the browser's User-Agent and client hints, instead of rustls' defaults, which WAFs fingerprint
+//! (JA3/JA4) as a non-browser client. Only what rustls supports can be offered: the CBC and legacy suites
+//! browsers still list, GREASE values and the extension order stay rustls', so the profile passes coarse
+//! fingerprint checks but not an exact JA3 comparison. Certificates aren't verified under a profile (like
+//! the anonymizer always did).
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::ClientBuilder;
+use rustls::cipher_suite::*;
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::kx_group::{SECP256R1, SECP384R1, X25519};
+use rustls::{Certificate, ClientConfig, ServerName, SignatureScheme, SupportedCipherSuite, SupportedKxGroup};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+const CHROME_CH_UA: &str = "\"Not_A Brand\";v=\"8\", \"Chromium\";v=\"120\", \"Google Chrome\";v=\"120\"";
+
+/// `sec-ch-ua-platform` matching a Chrome User-Agent
+fn platform(user_agent: &str) -> &'static str {
+    if user_agent.contains("Android") {
+        "\"Android\""
+    } else if user_agent.contains("Macintosh") {
+        "\"macOS\""
+    } else if user_agent.contains("Linux") {
+        "\"Linux\""
+    } else {
+        "\"Windows\""
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsProfile {
+    Chrome120,
+    Firefox121,
+    Safari17,
+}
+
+impl TlsProfile {
+    pub const ALL: [TlsProfile; 3] = [TlsProfile::Chrome120, TlsProfile::Firefox121, TlsProfile::Safari17];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name().eq_ignore_ascii_case(s.trim()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Chrome120 => "chrome120",
+            Self::Firefox121 => "firefox121",
+            Self::Safari17 => "safari17",
+        }
+    }
+
+    pub fn user_agent(&self) -> &'static str {
+        match self {
+            Self::Chrome120 => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+            Self::Firefox121 => "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+            Self::Safari17 => "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15",
+        }
+    }
+
+    /// Headers the browser sends on every request besides the User-Agent. `Accept` is left to the
+    /// clients, since it changes what APIs answer.
+    pub fn headers(&self) -> HeaderMap {
+        let pairs: &[(&str, &str)] = match self {
+            Self::Chrome120 => &[
+                ("sec-ch-ua", CHROME_CH_UA),
+                ("sec-ch-ua-mobile", "?0"),
+                ("sec-ch-ua-platform", "\"Windows\""),
+                ("accept-language", "en-US,en;q=0.9"),
+            ],
+            Self::Firefox121 => &[("accept-language", "en-US,en;q=0.5")],
+            Self::Safari17 => &[("accept-language", "en-US,en;q=0.9")],
+        };
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(HeaderName::from_static(name), HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    /// The profile a User-Agent claims to be (Chrome for anything unrecognized)
+    pub fn from_user_agent(user_agent: &str) -> Self {
+        if user_agent.contains("Firefox/") {
+            Self::Firefox121
+        } else if user_agent.contains("Chrome/") || user_agent.contains("Chromium/") {
+            Self::Chrome120
+        } else if user_agent.contains("Safari/") {
+            Self::Safari17
+        } else {
+            Self::Chrome120
+        }
+    }
+
+    /// Headers of a `fetch()` from a script on the target's own pages, in the order the browser sends
+    /// them. `Accept-Encoding` is left to reqwest (which only decodes gzip and brotli) and ends up last;
+    /// reqwest's own `Accept` default keeps the first position.
+    pub fn browser_headers(&self, user_agent: &str) -> HeaderMap {
+        const ACCEPT: &str = "application/json, text/plain, */*";
+        let ordered: Vec<(&str, &str)> = match self {
+            Self::Chrome120 => vec![
+                ("sec-ch-ua", CHROME_CH_UA),
+                ("sec-ch-ua-mobile", if user_agent.contains("Mobile") { "?1" } else { "?0" }),
+                ("user-agent", user_agent),
+                ("accept", ACCEPT),
+                ("sec-ch-ua-platform", platform(user_agent)),
+                ("sec-fetch-site", "same-origin"),
+                ("sec-fetch-mode", "cors"),
+                ("sec-fetch-dest", "empty"),
+                ("accept-language", "en-US,en;q=0.9"),
+            ],
+            Self::Firefox121 => vec![
+                ("user-agent", user_agent),
+                ("accept", ACCEPT),
+                ("accept-language", "en-US,en;q=0.5"),
+                ("sec-fetch-dest", "empty"),
+                ("sec-fetch-mode", "cors"),
+                ("sec-fetch-site", "same-origin"),
+            ],
+            Self::Safari17 => vec![
+                ("accept", ACCEPT),
+                ("sec-fetch-site", "same-origin"),
+                ("sec-fetch-mode", "cors"),
+                ("accept-language", "en-US,en;q=0.9"),
+                ("user-agent", user_agent),
+                ("sec-fetch-dest", "empty"),
+            ],
+        };
+        let mut headers = HeaderMap::new();
+        for (name, value) in ordered {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        }
+        headers
+    }
+
+    /// Cipher suites in the browser's order (those rustls implements)
+    pub fn cipher_suites(&self) -> Vec<SupportedCipherSuite> {
+        match self {
+            Self::Chrome120 => vec![
+                TLS13_AES_128_GCM_SHA256, TLS13_AES_256_GCM_SHA384, TLS13_CHACHA20_POLY1305_SHA256,
+                TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256, TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384, TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256, TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            ],
+            Self::Firefox121 => vec![
+                TLS13_AES_128_GCM_SHA256, TLS13_CHACHA20_POLY1305_SHA256, TLS13_AES_256_GCM_SHA384,
+                TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256, TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256, TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+                TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384, TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+            ],
+            Self::Safari17 => vec![
+                TLS13_AES_128_GCM_SHA256, TLS13_AES_256_GCM_SHA384, TLS13_CHACHA20_POLY1305_SHA256,
+                TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384, TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+                TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256, TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256, TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            ],
+        }
+    }
+
+    /// Key exchange groups in the browser's order; all three browsers lead with X25519
+    pub fn kx_groups(&self) -> Vec<&'static SupportedKxGroup> {
+        vec![&X25519, &SECP256R1, &SECP384R1]
+    }
+
+    /// Signature algorithms in the browser's order (those rustls can verify)
+    pub fn signature_schemes(&self) -> Vec<SignatureScheme> {
+        use SignatureScheme::*;
+        match self {
+            Self::Chrome120 | Self::Safari17 => vec![
+                ECDSA_NISTP256_SHA256, RSA_PSS_SHA256, RSA_PKCS1_SHA256, ECDSA_NISTP384_SHA384,
+                RSA_PSS_SHA384, RSA_PKCS1_SHA384, RSA_PSS_SHA512, RSA_PKCS1_SHA512,
+            ],
+            Self::Firefox121 => vec![
+                ECDSA_NISTP256_SHA256, ECDSA_NISTP384_SHA384, RSA_PSS_SHA256, RSA_PSS_SHA384,
+                RSA_PSS_SHA512, RSA_PKCS1_SHA256, RSA_PKCS1_SHA384, RSA_PKCS1_SHA512,
+            ],
+        }
+    }
+
+    pub fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::builder()
+            .with_cipher_suites(&self.cipher_suites())
+            .with_kx_groups(&self.kx_groups())
+            .with_protocol_versions(&[&rustls::version::TLS13, &rustls::version::TLS12])
+            .expect("browser profiles only use TLS 1.2/1.3 suites")
+            .with_custom_certificate_verifier(Arc::new(ProfileVerifier { schemes: self.signature_schemes() }))
+            .with_no_client_auth();
+        config.alpn_protocols = crate::http_client::http_version().alpn();
+        config
+    }
+}
+
+/// Accepts any certificate and advertises the profile's signature algorithms
+struct ProfileVerifier {
+    schemes: Vec<SignatureScheme>,
+}
+
+impl ServerCertVerifier for ProfileVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.schemes.clone()
+    }
+}
+
+static PROFILE: Lazy<RwLock<Option<TlsProfile>>> = Lazy::new(|| RwLock::new(None));
+
+/// Use `profile` for all target clients built afterwards
+pub fn set_profile(profile: Option<TlsProfile>) {
+    *PROFILE.write() = profile;
+}
+
+pub fn profile() -> Option<TlsProfile> {
+    *PROFILE.read()
+}
+
+/// Install the profile's TLS configuration and headers (nothing to do without `--tls-profile`). A
+/// User-Agent set later on the builder still wins.
+pub fn apply(builder: ClientBuilder) -> ClientBuilder {
+    match profile() {
+        Some(p) => builder
+            .use_preconfigured_tls(p.client_config())
+            .user_agent(p.user_agent())
+            .default_headers(p.headers()),
+        None => builder,
+    }
+}
+
+/// User-Agent for a target client: the profile's, so it matches the ClientHello, or `default`
+pub fn user_agent_or(default: &'static str) -> &'static str {
+    profile().map(|p| p.user_agent()).unwrap_or(default)
+}
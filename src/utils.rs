@@ -8,13 +8,33 @@ pub fn ensure_dir(path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Load a `target_raw.jsonl`. Lines from older and newer releases both load (missing fields take their
+/// defaults, unknown fields are ignored); a line that still doesn't parse is skipped with a warning
+/// instead of failing the whole file.
 pub fn read_jsonl(path: std::path::PathBuf) -> anyhow::Result<Vec<crate::output::writer_jsonl::RawEvent>> {
+    use crate::output::schema::SCHEMA_VERSION;
     let mut out = Vec::new();
-    let data = std::fs::read_to_string(path)?;
-    for line in data.lines() {
+    let data = std::fs::read_to_string(&path)?;
+    let mut skipped = 0;
+    let mut newest = 0;
+    for (i, line) in data.lines().enumerate() {
         if line.trim().is_empty() { continue; }
-        let v: crate::output::writer_jsonl::RawEvent = serde_json::from_str(line)?;
-        out.push(v);
+        match serde_json::from_str::<crate::output::writer_jsonl::RawEvent>(line) {
+            Ok(v) => {
+                newest = newest.max(v.schema_version);
+                out.push(v);
+            }
+            Err(e) => {
+                skipped += 1;
+                tracing::warn!("{}:{}: skipping unreadable event: {}", path.display(), i + 1, e);
+            }
+        }
+    }
+    if newest > SCHEMA_VERSION {
+        tracing::warn!("{} uses schema v{} (this build writes v{}); newer fields are ignored", path.display(), newest, SCHEMA_VERSION);
+    }
+    if out.is_empty() && skipped > 0 {
+        anyhow::bail!("no readable events in {} ({} lines skipped)", path.display(), skipped);
     }
     Ok(out)
 }
@@ -1,3 +1,4 @@
+use crate::http_client::Paced;
 use super::detector::WafType;
 use reqwest::{Client, Method};
 use serde::{Deserialize, Serialize};
@@ -9,6 +10,8 @@ pub enum BypassTechnique {
     DoubleUrlEncoding,
     UnicodeEncoding,
     MixedCaseEncoding,
+    /// SQL inline comments (`/**/`) instead of spaces
+    InlineComments,
     
     // HTTP manipulation
     VerbTampering(Vec<String>), // GET, POST, HEAD, OPTIONS, PUT, PATCH
@@ -61,6 +64,9 @@ impl WafBypass {
                 ]),
                 BypassTechnique::VerbTampering(vec!["HEAD".to_string(), "OPTIONS".to_string()]),
                 BypassTechnique::PathObfuscation,
+                BypassTechnique::UnicodeEncoding,
+                BypassTechnique::MixedCaseEncoding,
+                BypassTechnique::InlineComments,
             ],
             WafType::Imperva => vec![
                 BypassTechnique::IpRotation,
@@ -70,6 +76,9 @@ impl WafBypass {
                 ]),
                 BypassTechnique::PathTraversal,
                 BypassTechnique::VerbTampering(vec!["PUT".to_string(), "PATCH".to_string()]),
+                BypassTechnique::DoubleUrlEncoding,
+                BypassTechnique::UnicodeEncoding,
+                BypassTechnique::InlineComments,
             ],
             WafType::Akamai => vec![
                 BypassTechnique::IpRotation,
@@ -79,12 +88,15 @@ impl WafBypass {
                 ]),
                 BypassTechnique::PathObfuscation,
                 BypassTechnique::DoubleUrlEncoding,
+                BypassTechnique::UnicodeEncoding,
+                BypassTechnique::MixedCaseEncoding,
             ],
             WafType::ModSecurity => vec![
                 BypassTechnique::MixedCaseEncoding,
                 BypassTechnique::UrlEncoding,
                 BypassTechnique::PathObfuscation,
                 BypassTechnique::NullByteInjection,
+                BypassTechnique::InlineComments,
             ],
             WafType::AwsWaf => vec![
                 BypassTechnique::IpRotation,
@@ -92,6 +104,9 @@ impl WafBypass {
                 BypassTechnique::HeaderInjection(vec![
                     ("X-Forwarded-For".to_string(), "10.0.0.1".to_string()),
                 ]),
+                BypassTechnique::UnicodeEncoding,
+                BypassTechnique::MixedCaseEncoding,
+                BypassTechnique::InlineComments,
             ],
             _ => vec![
                 // Generic bypass techniques
@@ -106,10 +121,18 @@ impl WafBypass {
                 BypassTechnique::HeaderInjection(vec![
                     ("X-Forwarded-For".to_string(), "127.0.0.1".to_string()),
                 ]),
+                BypassTechnique::UnicodeEncoding,
+                BypassTechnique::DoubleUrlEncoding,
+                BypassTechnique::MixedCaseEncoding,
             ],
         }
     }
 
+    /// The recommended strategies that transform injection payloads (see `waf::mutation`)
+    pub fn payload_transforms(&self) -> Vec<BypassTechnique> {
+        self.get_strategies().into_iter().filter(BypassTechnique::is_payload_transform).collect()
+    }
+
     /// Apply path obfuscation to URL
     pub fn obfuscate_path(url: &str) -> Vec<String> {
         let mut variations = Vec::new();
@@ -200,7 +223,7 @@ impl WafBypass {
             BypassTechnique::PathObfuscation => {
                 let variations = Self::obfuscate_path(url);
                 for variant in variations {
-                    if let Ok(response) = client.get(&variant).send().await {
+                    if let Ok(response) = client.get(&variant).send_paced().await {
                         let status = response.status().as_u16();
                         let body = response.text().await.unwrap_or_default();
                         
@@ -227,7 +250,7 @@ impl WafBypass {
                         _ => Method::GET,
                     };
                     
-                    if let Ok(response) = client.request(method.clone(), url).send().await {
+                    if let Ok(response) = client.request(method.clone(), url).send_paced().await {
                         let status = response.status().as_u16();
                         let body = response.text().await.unwrap_or_default();
                         
@@ -249,7 +272,7 @@ impl WafBypass {
                     request = request.header(key, value);
                 }
                 
-                if let Ok(response) = request.send().await {
+                if let Ok(response) = request.send_paced().await {
                     let status = response.status().as_u16();
                     let body = response.text().await.unwrap_or_default();
                     
@@ -294,6 +317,9 @@ impl WafBypass {
         match technique {
             BypassTechnique::UrlEncoding => "URL encode special characters to bypass signature matching",
             BypassTechnique::DoubleUrlEncoding => "Double URL encoding to evade decoders",
+            BypassTechnique::UnicodeEncoding => "Escape special characters as \\uXXXX (JSON) or %uXXXX (query) so signatures don't match",
+            BypassTechnique::MixedCaseEncoding => "Alternate letter case in keywords (SeLeCt, <ScRiPt>) against case-sensitive rules",
+            BypassTechnique::InlineComments => "Replace spaces with SQL inline comments (/**/)",
             BypassTechnique::VerbTampering(_) => "Use alternative HTTP methods (HEAD, OPTIONS, PUT) that may not be filtered",
             BypassTechnique::PathObfuscation => "Add //, /./, trailing slashes to obfuscate paths",
             BypassTechnique::HeaderInjection(_) => "Inject headers like X-Forwarded-For to appear as internal request",
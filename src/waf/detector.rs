@@ -1,3 +1,4 @@
+use crate::output::writer_jsonl::RawEvent;
 use reqwest::Response;
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +39,30 @@ impl WafType {
             WafType::None => "None",
         }
     }
+
+    /// The type for a WAF name as annotated on probe results (`name()`, or the Server header names)
+    pub fn from_name(name: &str) -> Self {
+        let lower = name.to_ascii_lowercase();
+        let known = [
+            ("cloudflare", WafType::Cloudflare),
+            ("imperva", WafType::Imperva),
+            ("incapsula", WafType::Imperva),
+            ("akamai", WafType::Akamai),
+            ("big-ip", WafType::F5BigIP),
+            ("modsecurity", WafType::ModSecurity),
+            ("aws", WafType::AwsWaf),
+            ("azure", WafType::AzureWaf),
+            ("sucuri", WafType::Sucuri),
+            ("wordfence", WafType::Wordfence),
+            ("barracuda", WafType::Barracuda),
+            ("fortiweb", WafType::Fortiweb),
+            ("wallarm", WafType::Wallarm),
+        ];
+        known.into_iter()
+            .find(|(needle, _)| lower.contains(needle))
+            .map(|(_, waf)| waf)
+            .unwrap_or_else(|| if lower.is_empty() || lower == "none" { WafType::None } else { WafType::Unknown(name.to_string()) })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,10 +85,45 @@ impl WafDetector {
         }
     }
 
+    /// Detector over a given signature set instead of the configured one
+    pub fn with_signatures(signatures: Vec<super::signatures::WafSignature>) -> Self {
+        Self { signatures }
+    }
+
     /// Detect WAF from response headers, cookies, and body
     pub async fn detect(&self, response: &Response, body: &str) -> WafDetection {
         let headers = response.headers();
-        let mut detected_wafs: Vec<(WafType, f32, Vec<String>)> = Vec::new();
+        self.detect_with(|name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string), body)
+    }
+
+    /// Detect WAF from the headers a probe already captured, without another request
+    pub fn detect_event(&self, event: &RawEvent) -> WafDetection {
+        let body = event.json_sample.as_ref().map(|j| j.to_string()).unwrap_or_default();
+        self.detect_with(|name| event.header(name).map(str::to_string), &body)
+    }
+
+    fn detect_with(&self, header: impl Fn(&str) -> Option<String>, body: &str) -> WafDetection {
+        // The signature with the highest confidence
+        self.scores(header, body)
+            .into_iter()
+            .filter(|d| d.confidence > 0.3)
+            .max_by(|a, b| a.confidence.partial_cmp(&b.confidence).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or_else(|| WafDetection {
+                waf_type: WafType::None,
+                confidence: 0.0,
+                evidence: Vec::new(),
+                headers_found: Vec::new(),
+                cookies_found: Vec::new(),
+            })
+    }
+
+    /// Confidence of every signature for one response (headers, cookie names, body)
+    pub(crate) fn scores(&self, header: impl Fn(&str) -> Option<String>, body: &str) -> Vec<WafDetection> {
+        let cookie_names: Vec<String> = header("set-cookie")
+            .map(|v| v.lines().filter_map(|c| c.split('=').next()).map(|n| n.trim().to_ascii_lowercase()).collect())
+            .unwrap_or_default();
+        let body_lower = body.to_lowercase();
+        let mut out = Vec::new();
 
         // Check each signature
         for sig in &self.signatures {
@@ -74,81 +134,66 @@ impl WafDetector {
 
             // Check headers
             for (header_name, header_pattern) in &sig.headers {
-                if let Some(header_value) = headers.get(header_name) {
-                    if let Ok(value_str) = header_value.to_str() {
-                        if value_str.to_lowercase().contains(&header_pattern.to_lowercase()) {
-                            confidence += 0.3;
-                            evidence.push(format!("Header: {} = {}", header_name, value_str));
-                            headers_found.push(format!("{}: {}", header_name, value_str));
-                        }
+                if let Some(value_str) = header(header_name) {
+                    if value_str.to_lowercase().contains(&header_pattern.to_lowercase()) {
+                        confidence += 0.3;
+                        evidence.push(format!("Header: {} = {}", header_name, value_str));
+                        headers_found.push(format!("{}: {}", header_name, value_str));
                     }
                 }
             }
 
             // Check server header specifically
-            if let Some(server) = headers.get("server") {
-                if let Ok(server_str) = server.to_str() {
-                    for pattern in &sig.server_patterns {
-                        if server_str.to_lowercase().contains(&pattern.to_lowercase()) {
-                            confidence += 0.4;
-                            evidence.push(format!("Server: {}", server_str));
-                            headers_found.push(format!("server: {}", server_str));
-                        }
+            if let Some(server_str) = header("server") {
+                for pattern in &sig.server_patterns {
+                    if server_str.to_lowercase().contains(&pattern.to_lowercase()) {
+                        confidence += 0.4;
+                        evidence.push(format!("Server: {}", server_str));
+                        headers_found.push(format!("server: {}", server_str));
                     }
                 }
             }
 
-            // Check cookies
-            if let Some(cookie_header) = headers.get("set-cookie") {
-                if let Ok(cookie_str) = cookie_header.to_str() {
-                    for cookie_pattern in &sig.cookies {
-                        if cookie_str.contains(cookie_pattern) {
-                            confidence += 0.25;
-                            evidence.push(format!("Cookie: {}", cookie_pattern));
-                            cookies_found.push(cookie_pattern.clone());
-                        }
-                    }
+            // Check cookies (by name: `ts` must not match every cookie containing those letters)
+            for cookie_pattern in &sig.cookies {
+                if cookie_names.iter().any(|name| name.starts_with(&cookie_pattern.to_ascii_lowercase())) {
+                    confidence += 0.25;
+                    evidence.push(format!("Cookie: {}", cookie_pattern));
+                    cookies_found.push(cookie_pattern.clone());
                 }
             }
 
             // Check body patterns (only if we have some confidence already)
             if confidence > 0.0 {
                 for body_pattern in &sig.body_patterns {
-                    if body.contains(body_pattern) {
+                    if body_lower.contains(body_pattern) {
                         confidence += 0.15;
                         evidence.push(format!("Body pattern: {}", body_pattern));
                     }
                 }
             }
 
-            // Cap confidence at 1.0
-            confidence = confidence.min(1.0);
-
-            if confidence > 0.3 {
-                detected_wafs.push((sig.waf_type.clone(), confidence, evidence));
-            }
+            out.push(WafDetection {
+                waf_type: sig.waf_type.clone(),
+                // Cap confidence at 1.0
+                confidence: confidence.min(1.0),
+                evidence,
+                headers_found,
+                cookies_found,
+            });
         }
+        out
+    }
 
-        // Return the WAF with highest confidence
-        if let Some((waf_type, confidence, evidence)) = detected_wafs.iter().max_by(|a, b| {
-            a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)
-        }) {
-            WafDetection {
-                waf_type: waf_type.clone(),
-                confidence: *confidence,
-                evidence: evidence.clone(),
-                headers_found: Vec::new(), // Populated separately
-                cookies_found: Vec::new(),
-            }
-        } else {
-            WafDetection {
-                waf_type: WafType::None,
-                confidence: 0.0,
-                evidence: Vec::new(),
-                headers_found: Vec::new(),
-                cookies_found: Vec::new(),
-            }
-        }
+    /// Body patterns of a signature found in a block page
+    pub(crate) fn block_page_matches(&self, waf_type: &WafType, body: &str) -> Vec<String> {
+        let body_lower = body.to_lowercase();
+        self.signatures.iter()
+            .filter(|sig| &sig.waf_type == waf_type)
+            .flat_map(|sig| sig.body_patterns.iter())
+            .filter(|p| body_lower.contains(p.as_str()))
+            .cloned()
+            .collect()
     }
 
     /// Quick check if response indicates WAF blocking
@@ -182,39 +227,6 @@ impl WafDetector {
 
         false
     }
-
-    /// Detect WAF by sending a harmless test payload
-    /// Only used when --detect-waf flag is explicitly set
-    pub async fn active_detection(
-        &self,
-        client: &reqwest::Client,
-        url: &str,
-    ) -> Result<WafDetection, Box<dyn std::error::Error>> {
-        // Send a harmless but "suspicious-looking" request
-        // This won't actually exploit anything
-        let test_url = format!("{}?test=<script>", url);
-        
-        let response = client
-            .get(&test_url)
-            .send()
-            .await?;
-
-        let status = response.status().as_u16();
-        let headers = response.headers().clone();
-        let body = response.text().await?;
-        
-        // Create a fake response for detection (since we consumed the original)
-        let detection = WafDetection {
-            waf_type: WafType::None,
-            confidence: 0.0,
-            evidence: Vec::new(),
-            headers_found: Vec::new(),
-            cookies_found: Vec::new(),
-        };
-        // TODO: Implement proper detection without consuming response
-
-        Ok(detection)
-    }
 }
 
 impl Default for WafDetector {
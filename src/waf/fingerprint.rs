@@ -0,0 +1,137 @@
+//! Active WAF fingerprinting
+//! One benign and one attack-looking request per host (XSS, SQL and traversal in the query, nothing
+//! that changes state). The signatures are scored on both answers; a refusal of the attack request
+//! that the benign one didn't get, and a block page of a known WAF, add to the confidence.
+
+use super::detector::{WafDetector, WafType};
+use crate::http_client::HttpClient;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Query of the benign request
+pub const BENIGN_QUERY: &str = "q=apihunter";
+/// Query of the attack request: `<script>alert(1)</script>`, `1' OR '1'='1`, `../../../../etc/passwd`
+pub const ATTACK_QUERY: &str = "q=%3Cscript%3Ealert(1)%3C%2Fscript%3E&id=1%27%20OR%20%271%27%3D%271&file=..%2F..%2F..%2F..%2Fetc%2Fpasswd";
+
+/// Confidence from which a fingerprint is taken over for the host's endpoints
+pub const MIN_CONFIDENCE: f32 = 0.5;
+
+/// One answer to a fingerprint request
+#[derive(Debug, Clone, Default)]
+pub struct Observed {
+    pub status: u16,
+    /// Lower-case names, `set-cookie` joined with newlines (`RawEvent::headers` form)
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl Observed {
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers.get(&name.to_ascii_lowercase()).cloned()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HostFingerprint {
+    pub host: String,
+    pub url: String,
+    pub waf: String,
+    pub waf_type: WafType,
+    pub confidence: f32,
+    /// The attack request was refused (or the connection dropped) while the benign one wasn't
+    pub blocks_attacks: bool,
+    pub benign_status: u16,
+    /// `None` when the attack request got no answer
+    pub attack_status: Option<u16>,
+    pub evidence: Vec<String>,
+}
+
+/// A refusal the benign request didn't get
+fn refused(benign: &Observed, attack: &Observed) -> bool {
+    attack.status != benign.status
+        && (WafDetector::is_blocked_response(attack.status, &attack.body) || matches!(attack.status, 403 | 406 | 501 | 999))
+}
+
+impl WafDetector {
+    /// Fingerprint from the answers to the benign and the attack request
+    pub fn fingerprint(&self, host: &str, url: &str, benign: &Observed, attack: Option<&Observed>) -> HostFingerprint {
+        let blocks_attacks = attack.is_none_or(|a| refused(benign, a));
+        let block_page = attack.filter(|_| blocks_attacks);
+
+        let mut best: Option<(WafType, f32, Vec<String>)> = None;
+        let benign_scores = self.scores(|n| benign.header(n), &benign.body);
+        let attack_scores = attack.map(|a| self.scores(|n| a.header(n), &a.body)).unwrap_or_default();
+        for (i, passive) in benign_scores.into_iter().enumerate() {
+            let mut evidence = passive.evidence;
+            let mut confidence = passive.confidence;
+            if let Some(other) = attack_scores.get(i).filter(|o| o.confidence > confidence) {
+                confidence = other.confidence;
+                evidence = other.evidence.clone();
+            }
+            if let Some(page) = block_page {
+                let matches = self.block_page_matches(&passive.waf_type, &page.body);
+                if !matches.is_empty() {
+                    confidence += 0.25;
+                    evidence.push(format!("Block page: {}", matches.join(", ")));
+                }
+            }
+            if confidence > 0.0 && blocks_attacks {
+                confidence += 0.2;
+            }
+            let confidence = confidence.min(1.0);
+            if confidence > 0.3 && best.as_ref().is_none_or(|b| confidence > b.1) {
+                best = Some((passive.waf_type, confidence, evidence));
+            }
+        }
+
+        let behavior = match attack {
+            None => "attack request dropped, benign request answered".to_string(),
+            Some(a) if blocks_attacks => format!("attack request refused with {} (benign: {})", a.status, benign.status),
+            Some(_) => String::new(),
+        };
+        let (waf_type, confidence, mut evidence) = match best {
+            Some(found) => found,
+            None if blocks_attacks => (WafType::Unknown("Unidentified WAF".to_string()), 0.5, Vec::new()),
+            None => (WafType::None, 0.0, Vec::new()),
+        };
+        if blocks_attacks {
+            evidence.insert(0, behavior);
+        }
+        HostFingerprint {
+            host: host.to_string(),
+            url: url.to_string(),
+            waf: waf_type.name().to_string(),
+            waf_type,
+            confidence,
+            blocks_attacks,
+            benign_status: benign.status,
+            attack_status: attack.map(|a| a.status),
+            evidence,
+        }
+    }
+
+    /// Send the benign and the attack request to `url`. `None` if the benign one gets no answer.
+    pub async fn fingerprint_host(&self, http: &HttpClient, url: &str, timeout: Duration) -> Option<HostFingerprint> {
+        let parsed = url::Url::parse(url).ok()?;
+        let host = parsed.host_str()?.to_ascii_lowercase();
+        let with_query = |query: &str| {
+            let mut u = parsed.clone();
+            u.set_query(Some(query));
+            u.to_string()
+        };
+        let benign = fetch(http, &with_query(BENIGN_QUERY), timeout).await?;
+        let attack = fetch(http, &with_query(ATTACK_QUERY), timeout).await;
+        Some(self.fingerprint(&host, url, &benign, attack.as_ref()))
+    }
+}
+
+async fn fetch(http: &HttpClient, url: &str, timeout: Duration) -> Option<Observed> {
+    let resp = http.send(http.client().get(url).timeout(timeout)).await.ok()?;
+    let status = resp.status().as_u16();
+    let headers = crate::output::writer_jsonl::collect_headers(resp.headers());
+    let (bytes, _) = tokio::time::timeout(timeout, crate::probe::http_probe::read_limited(resp, BODY_LIMIT)).await.ok()?.ok()?;
+    Some(Observed { status, headers, body: String::from_utf8_lossy(&bytes).into_owned() })
+}
@@ -2,9 +2,15 @@
 // Only activates with explicit flags - no automatic exploitation
 
 pub mod detector;
+pub mod fingerprint;
 pub mod bypass;
 pub mod signatures;
+pub mod origin;
+pub mod mutation;
 
 pub use detector::{WafDetector, WafType, WafDetection};
+pub use fingerprint::HostFingerprint;
 pub use bypass::{WafBypass, BypassTechnique, BypassResult};
 pub use signatures::WafSignature;
+pub use origin::{OriginFinder, OriginReport, OriginCandidate};
+pub use mutation::WafMutators;
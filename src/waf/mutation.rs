@@ -0,0 +1,205 @@
+//! WAF-aware payload mutation
+//! When probing saw a WAF in front of a host, an injection payload the WAF blocks is sent again
+//! through the encoding and tampering transforms `WafBypass` recommends for that WAF, one at a time,
+//! until one gets through. The response that got through is what the fuzzer classifies, and which
+//! transform it was is recorded per host and parameter.
+
+use super::bypass::{BypassTechnique, WafBypass};
+use super::detector::{WafDetector, WafType};
+use crate::output::writer_jsonl::RawEvent;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Where a payload goes: raw query values are percent-encoded, raw JSON values are string literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Query,
+    Json,
+}
+
+fn mixed_case(payload: &str) -> String {
+    let mut upper = false;
+    payload.chars().map(|c| {
+        if c.is_ascii_alphabetic() {
+            upper = !upper;
+            if upper { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() }
+        } else {
+            c
+        }
+    }).collect()
+}
+
+fn percent_all(payload: &str) -> String {
+    payload.bytes().map(|b| format!("%{:02X}", b)).collect()
+}
+
+fn json_literal(payload: &str) -> String {
+    serde_json::to_string(payload).unwrap_or_default()
+}
+
+impl BypassTechnique {
+    /// Techniques `tamper` implements
+    pub fn is_payload_transform(&self) -> bool {
+        matches!(self,
+            Self::UrlEncoding | Self::DoubleUrlEncoding | Self::UnicodeEncoding | Self::MixedCaseEncoding
+                | Self::InlineComments | Self::NullByteInjection)
+    }
+
+    /// `payload` transformed for `context`, ready to be put into the request as is; `None` if the
+    /// technique doesn't apply there or leaves the payload unchanged
+    pub fn tamper(&self, payload: &str, context: Context) -> Option<String> {
+        let changed = |p: String| (p != payload).then_some(p);
+        let encode = |p: &str| match context {
+            Context::Query => urlencoding::encode(p).into_owned(),
+            Context::Json => json_literal(p),
+        };
+        match (self, context) {
+            (Self::MixedCaseEncoding, _) => changed(mixed_case(payload)).map(|p| encode(&p)),
+            (Self::InlineComments, _) => changed(payload.replace(' ', "/**/")).map(|p| encode(&p)),
+            (Self::NullByteInjection, Context::Query) => Some(format!("%00{}", encode(payload))),
+            (Self::NullByteInjection, Context::Json) => Some(format!("\"\\u0000{}", &json_literal(payload)[1..])),
+            (Self::UrlEncoding, Context::Query) => Some(percent_all(payload)),
+            (Self::DoubleUrlEncoding, Context::Query) => Some(percent_all(payload).replace('%', "%25")),
+            (Self::UnicodeEncoding, Context::Query) => Some(payload.chars().map(|c| {
+                if c.is_ascii_alphanumeric() { c.to_string() } else { format!("%u{:04X}", c as u32) }
+            }).collect()),
+            (Self::UnicodeEncoding, Context::Json) => {
+                let escaped: String = payload.encode_utf16().map(|u| {
+                    let c = char::from_u32(u as u32).filter(char::is_ascii_alphanumeric);
+                    c.map(String::from).unwrap_or_else(|| format!("\\u{:04x}", u))
+                }).collect();
+                Some(format!("\"{}\"", escaped))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `url` with the query parameter `name` set to an already encoded value
+pub fn with_raw_param(url: &str, name: &str, raw: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else { return url.to_string() };
+    let pairs: Vec<(String, String)> = parsed.query_pairs()
+        .filter(|(k, _)| k != name)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    let base = parsed.to_string();
+    let sep = if parsed.query().is_some_and(|q| !q.is_empty()) { "&" } else if base.ends_with('?') { "" } else { "?" };
+    format!("{}{}{}={}", base, sep, urlencoding::encode(name), raw)
+}
+
+/// A WAF block in place of the answer the endpoint gave without the payload
+pub fn blocked(baseline_status: u16, status: u16, body: &str) -> bool {
+    status != baseline_status && WafDetector::is_blocked_response(status, body)
+}
+
+/// One blocked payload and what happened when it was transformed
+#[derive(Debug, Clone, Serialize)]
+pub struct MutationRecord {
+    pub host: String,
+    pub waf: String,
+    pub url: String,
+    /// Field or parameter the payload went into
+    pub target: String,
+    pub payload: String,
+    pub blocked_status: u16,
+    /// First transform that got through; `None` if every one was blocked too
+    pub technique: Option<BypassTechnique>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+/// The WAF seen per host and the transforms recommended for it
+#[derive(Default)]
+pub struct WafMutators {
+    by_host: HashMap<String, (WafType, Vec<BypassTechnique>)>,
+    log: Mutex<Vec<MutationRecord>>,
+}
+
+impl WafMutators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The WAF probing annotated most often on each host
+    pub fn from_events(events: &[RawEvent]) -> Self {
+        let mut counts: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+        for ev in events {
+            let (Some(waf), Some(host)) = (ev.waf(), url::Url::parse(&ev.final_url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase))) else { continue };
+            *counts.entry(host).or_default().entry(waf).or_default() += 1;
+        }
+        let mut mutators = Self::new();
+        for (host, wafs) in counts {
+            if let Some((waf, _)) = wafs.into_iter().max_by_key(|(name, n)| (*n, *name)) {
+                mutators.insert(&host, WafType::from_name(waf));
+            }
+        }
+        mutators
+    }
+
+    pub fn insert(&mut self, host: &str, waf: WafType) {
+        let techniques = WafBypass::new(waf.clone()).payload_transforms();
+        self.by_host.insert(host.to_ascii_lowercase(), (waf, techniques));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_host.is_empty()
+    }
+
+    /// Hosts and their WAF
+    pub fn hosts(&self) -> Vec<(String, String)> {
+        let mut out: Vec<_> = self.by_host.iter().map(|(h, (w, _))| (h.clone(), w.name().to_string())).collect();
+        out.sort();
+        out
+    }
+
+    fn for_url(&self, url: &str) -> Option<(String, &WafType, &[BypassTechnique])> {
+        let host = url::Url::parse(url).ok()?.host_str()?.to_ascii_lowercase();
+        let (waf, techniques) = self.by_host.get(&host)?;
+        Some((host, waf, techniques))
+    }
+
+    /// Send `payload` with `send` (`None` sends it as is, `Some` a transformed raw value). If the WAF of
+    /// the host blocks it, each recommended transform is tried until one isn't blocked. The response
+    /// that got through (or the block) and the transform used.
+    pub async fn send<F, Fut>(&self, url: &str, target: &str, payload: &str, context: Context, baseline_status: u16, send: F)
+        -> Option<(u16, String, Option<BypassTechnique>)>
+    where
+        F: Fn(Option<String>) -> Fut,
+        Fut: Future<Output = Option<(u16, String)>>,
+    {
+        let (status, body) = send(None).await?;
+        let Some((host, waf, techniques)) = self.for_url(url) else { return Some((status, body, None)) };
+        if !blocked(baseline_status, status, &body) {
+            return Some((status, body, None));
+        }
+        let mut record = MutationRecord {
+            host,
+            waf: waf.name().to_string(),
+            url: url.to_string(),
+            target: target.to_string(),
+            payload: payload.to_string(),
+            blocked_status: status,
+            technique: None,
+            status: None,
+        };
+        for technique in techniques {
+            let Some(raw) = technique.tamper(payload, context) else { continue };
+            let Some((t_status, t_body)) = send(Some(raw)).await else { continue };
+            if !blocked(baseline_status, t_status, &t_body) {
+                record.technique = Some(technique.clone());
+                record.status = Some(t_status);
+                self.log.lock().push(record);
+                return Some((t_status, t_body, Some(technique.clone())));
+            }
+        }
+        self.log.lock().push(record);
+        Some((status, body, None))
+    }
+
+    pub fn records(&self) -> Vec<MutationRecord> {
+        self.log.lock().clone()
+    }
+}
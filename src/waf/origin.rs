@@ -0,0 +1,251 @@
+// Origin IP Discovery
+// Looks for the real server behind a CDN/WAF edge. Opt-in only (--find-origin).
+// Candidates are reported for manual verification - nothing is sent to them.
+
+use crate::http_client::Paced;
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+/// Published Cloudflare ranges and the major Akamai allocations
+const CDN_RANGES: &[(&str, &str)] = &[
+    ("Cloudflare", "173.245.48.0/20"), ("Cloudflare", "103.21.244.0/22"), ("Cloudflare", "103.22.200.0/22"),
+    ("Cloudflare", "103.31.4.0/22"), ("Cloudflare", "141.101.64.0/18"), ("Cloudflare", "108.162.192.0/18"),
+    ("Cloudflare", "190.93.240.0/20"), ("Cloudflare", "188.114.96.0/20"), ("Cloudflare", "197.234.240.0/22"),
+    ("Cloudflare", "198.41.128.0/17"), ("Cloudflare", "162.158.0.0/15"), ("Cloudflare", "104.16.0.0/13"),
+    ("Cloudflare", "104.24.0.0/14"), ("Cloudflare", "172.64.0.0/13"), ("Cloudflare", "131.0.72.0/22"),
+    ("Cloudflare", "2400:cb00::/32"), ("Cloudflare", "2606:4700::/32"), ("Cloudflare", "2803:f800::/32"),
+    ("Cloudflare", "2405:b500::/32"), ("Cloudflare", "2405:8100::/32"), ("Cloudflare", "2a06:98c0::/29"),
+    ("Cloudflare", "2c0f:f248::/32"),
+    ("Akamai", "23.32.0.0/11"), ("Akamai", "23.192.0.0/11"), ("Akamai", "2.16.0.0/13"),
+    ("Akamai", "104.64.0.0/10"), ("Akamai", "184.24.0.0/13"), ("Akamai", "184.50.0.0/15"),
+    ("Akamai", "96.16.0.0/15"), ("Akamai", "95.100.0.0/15"), ("Akamai", "72.246.0.0/15"),
+    ("Akamai", "88.221.0.0/16"), ("Akamai", "2600:1400::/24"), ("Akamai", "2a02:26f0::/29"),
+];
+
+/// Subdomains that commonly bypass the CDN and point straight at the origin
+const LEAK_PREFIXES: &[&str] = &[
+    "direct", "direct-connect", "origin", "origin-www", "www-origin", "backend", "server",
+    "mail", "webmail", "smtp", "mx", "ftp", "cpanel", "whm", "dev", "staging", "old", "vpn",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OriginCandidate {
+    pub ip: String,
+    /// Where the IP was found (e.g. "subdomain:mail.example.com", "securitytrails", "shodan-cert")
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OriginReport {
+    pub domain: String,
+    pub edge_ips: Vec<String>,
+    /// CDN provider the domain currently resolves to, if recognized
+    pub cdn: Option<String>,
+    pub candidates: Vec<OriginCandidate>,
+    /// Sources that were skipped or failed (missing API key, HTTP error)
+    pub skipped_sources: Vec<String>,
+}
+
+pub struct OriginFinder {
+    client: reqwest::Client,
+    securitytrails_key: Option<String>,
+    shodan_key: Option<String>,
+    censys_credentials: Option<(String, String)>,
+}
+
+impl OriginFinder {
+    /// Uses the SecurityTrails, Shodan and Censys keys from the config file / environment
+    pub fn new(keys: &crate::config::ApiKeys) -> Self {
+        let client = crate::http_client::third_party()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+
+        let censys_credentials = match (&keys.censys_id, &keys.censys_secret) {
+            (Some(id), Some(secret)) => Some((id.clone(), secret.clone())),
+            _ => None,
+        };
+
+        Self {
+            client,
+            securitytrails_key: keys.securitytrails.clone(),
+            shodan_key: keys.shodan.clone(),
+            censys_credentials,
+        }
+    }
+
+    /// Collect candidate origin IPs for `domain`. `known_subdomains` (e.g. from enumeration) are resolved as well.
+    pub async fn discover(&self, domain: &str, known_subdomains: &[String]) -> OriginReport {
+        let edge: Vec<IpAddr> = resolve(domain).await;
+        let cdn = edge.iter().find_map(cdn_provider).map(|s| s.to_string());
+
+        let mut found: BTreeMap<IpAddr, BTreeSet<String>> = BTreeMap::new();
+        let mut skipped = Vec::new();
+
+        // Subdomain leaks: hosts on the same domain that resolve outside the CDN
+        let mut hosts: Vec<String> = LEAK_PREFIXES.iter().map(|p| format!("{}.{}", p, domain)).collect();
+        hosts.extend(known_subdomains.iter().take(200).cloned());
+        hosts.sort();
+        hosts.dedup();
+
+        let resolved: Vec<(String, Vec<IpAddr>)> = stream::iter(hosts)
+            .map(|h| async move {
+                let ips = resolve(&h).await;
+                (h, ips)
+            })
+            .buffer_unordered(20)
+            .collect()
+            .await;
+
+        for (host, ips) in resolved {
+            for ip in ips {
+                found.entry(ip).or_default().insert(format!("subdomain:{}", host));
+            }
+        }
+
+        // Historical DNS
+        match &self.securitytrails_key {
+            Some(key) => match self.securitytrails_history(domain, key).await {
+                Ok(ips) => for ip in ips { found.entry(ip).or_default().insert("securitytrails".to_string()); },
+                Err(e) => skipped.push(format!("securitytrails: {}", e)),
+            },
+            None => skipped.push("securitytrails: no API key configured".to_string()),
+        }
+
+        // Hosts presenting a certificate for the domain
+        match &self.shodan_key {
+            Some(key) => match self.shodan_cert_search(domain, key).await {
+                Ok(ips) => for ip in ips { found.entry(ip).or_default().insert("shodan-cert".to_string()); },
+                Err(e) => skipped.push(format!("shodan: {}", e)),
+            },
+            None => skipped.push("shodan: no API key configured".to_string()),
+        }
+
+        match &self.censys_credentials {
+            Some((id, secret)) => match self.censys_cert_search(domain, id, secret).await {
+                Ok(ips) => for ip in ips { found.entry(ip).or_default().insert("censys-cert".to_string()); },
+                Err(e) => skipped.push(format!("censys: {}", e)),
+            },
+            None => skipped.push("censys: no API credentials configured".to_string()),
+        }
+
+        let candidates = found.into_iter()
+            .filter(|(ip, _)| !edge.contains(ip) && cdn_provider(ip).is_none() && is_public(ip))
+            .map(|(ip, sources)| OriginCandidate {
+                ip: ip.to_string(),
+                sources: sources.into_iter().collect(),
+            })
+            .collect();
+
+        OriginReport {
+            domain: domain.to_string(),
+            edge_ips: edge.iter().map(|ip| ip.to_string()).collect(),
+            cdn,
+            candidates,
+            skipped_sources: skipped,
+        }
+    }
+
+    async fn securitytrails_history(&self, domain: &str, key: &str) -> Result<Vec<IpAddr>> {
+        let url = format!("https://api.securitytrails.com/v1/history/{}/dns/a", domain);
+        let v: serde_json::Value = self.client.get(&url)
+            .header("APIKEY", key)
+            .send_paced().await?
+            .error_for_status()?
+            .json().await
+            .context("invalid SecurityTrails response")?;
+
+        let mut ips = Vec::new();
+        for record in v["records"].as_array().into_iter().flatten() {
+            // Records already attributed to a CDN are not interesting
+            let orgs = record["organizations"].to_string().to_lowercase();
+            if orgs.contains("cloudflare") || orgs.contains("akamai") {
+                continue;
+            }
+            for value in record["values"].as_array().into_iter().flatten() {
+                if let Some(ip) = value["ip"].as_str().and_then(|s| s.parse().ok()) {
+                    ips.push(ip);
+                }
+            }
+        }
+        Ok(ips)
+    }
+
+    async fn shodan_cert_search(&self, domain: &str, key: &str) -> Result<Vec<IpAddr>> {
+        let query = format!("ssl.cert.subject.cn:\"{}\"", domain);
+        let url = format!(
+            "https://api.shodan.io/shodan/host/search?key={}&query={}",
+            key,
+            urlencoding::encode(&query)
+        );
+        let v: serde_json::Value = self.client.get(&url)
+            .send_paced().await?
+            .error_for_status()?
+            .json().await
+            .context("invalid Shodan response")?;
+
+        Ok(v["matches"].as_array().into_iter().flatten()
+            .filter_map(|m| m["ip_str"].as_str().and_then(|s| s.parse().ok()))
+            .collect())
+    }
+
+    async fn censys_cert_search(&self, domain: &str, id: &str, secret: &str) -> Result<Vec<IpAddr>> {
+        let query = format!("services.tls.certificates.leaf_data.names: {}", domain);
+        let url = format!(
+            "https://search.censys.io/api/v2/hosts/search?q={}&per_page=100",
+            urlencoding::encode(&query)
+        );
+        let v: serde_json::Value = self.client.get(&url)
+            .basic_auth(id, Some(secret))
+            .send_paced().await?
+            .error_for_status()?
+            .json().await
+            .context("invalid Censys response")?;
+
+        Ok(v["result"]["hits"].as_array().into_iter().flatten()
+            .filter_map(|h| h["ip"].as_str().and_then(|s| s.parse().ok()))
+            .collect())
+    }
+}
+
+async fn resolve(host: &str) -> Vec<IpAddr> {
+    let mut ips: Vec<IpAddr> = crate::net::resolve(host, 443).await.into_iter().map(|a| a.ip()).collect();
+    ips.sort();
+    ips.dedup();
+    ips
+}
+
+/// Name of the CDN owning `ip`, if it falls in a known edge range
+pub fn cdn_provider(ip: &IpAddr) -> Option<&'static str> {
+    CDN_RANGES.iter()
+        .find(|(_, cidr)| cidr_contains(cidr, ip))
+        .map(|(name, _)| *name)
+}
+
+fn cidr_contains(cidr: &str, ip: &IpAddr) -> bool {
+    let Some((net, bits)) = cidr.split_once('/') else { return false };
+    let Ok(bits) = bits.parse::<u32>() else { return false };
+
+    match (net.parse::<IpAddr>(), ip) {
+        (Ok(IpAddr::V4(net)), IpAddr::V4(ip)) => {
+            let mask = if bits == 0 { 0 } else { u32::MAX << (32 - bits) };
+            (u32::from(net) & mask) == (u32::from(*ip) & mask)
+        }
+        (Ok(IpAddr::V6(net)), IpAddr::V6(ip)) => {
+            let mask = if bits == 0 { 0 } else { u128::MAX << (128 - bits) };
+            (u128::from(net) & mask) == (u128::from(*ip) & mask)
+        }
+        _ => false,
+    }
+}
+
+fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified()),
+        IpAddr::V6(v6) => !(v6.is_loopback() || v6.is_unspecified() || (v6.segments()[0] & 0xfe00) == 0xfc00),
+    }
+}
@@ -1,4 +1,19 @@
+// WAF signature database
+// The signatures live in `signatures.json`, compiled in as the fallback. A copy in the config
+// directory (`api_hunter signatures update`, or edited by hand) takes precedence, so new block pages
+// and cookies don't need a rebuild.
+
 use super::detector::WafType;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The set shipped with this build
+pub const EMBEDDED: &str = include_str!("signatures.json");
+
+/// Where `signatures update` fetches the latest set from
+pub const UPDATE_URL: &str = "https://raw.githubusercontent.com/mmadersbacher/API_Hunter/main/src/waf/signatures.json";
 
 pub struct WafSignature {
     pub waf_type: WafType,
@@ -8,183 +23,104 @@ pub struct WafSignature {
     pub body_patterns: Vec<String>,
 }
 
+/// `signatures.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureFile {
+    #[serde(default)]
+    pub version: u32,
+    pub signatures: Vec<SignatureEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureEntry {
+    /// WAF name; names of known WAFs map to their type (`WafType::from_name`), others are kept as is
+    pub waf: String,
+    /// Header name -> substring of the value (empty: presence is enough)
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub server_patterns: Vec<String>,
+    /// Cookie name prefixes
+    #[serde(default)]
+    pub cookies: Vec<String>,
+    /// Lower-case block page text
+    #[serde(default)]
+    pub body_patterns: Vec<String>,
+}
+
+impl From<SignatureEntry> for WafSignature {
+    fn from(entry: SignatureEntry) -> Self {
+        Self {
+            waf_type: WafType::from_name(&entry.waf),
+            headers: entry.headers.into_iter().map(|(name, pattern)| (name.to_ascii_lowercase(), pattern)).collect(),
+            server_patterns: entry.server_patterns,
+            cookies: entry.cookies,
+            body_patterns: entry.body_patterns.into_iter().map(|p| p.to_lowercase()).collect(),
+        }
+    }
+}
+
+impl SignatureFile {
+    /// Parse and check a signature file: at least one signature, each with a name and something to match
+    pub fn parse(data: &str) -> Result<Self> {
+        let file: SignatureFile = serde_json::from_str(data).context("invalid WAF signature file")?;
+        if file.signatures.is_empty() {
+            bail!("WAF signature file has no signatures");
+        }
+        for (i, sig) in file.signatures.iter().enumerate() {
+            if sig.waf.trim().is_empty() {
+                bail!("WAF signature {} has no name", i + 1);
+            }
+            if sig.headers.is_empty() && sig.server_patterns.is_empty() && sig.cookies.is_empty() && sig.body_patterns.is_empty() {
+                bail!("WAF signature {} ({}) matches nothing", i + 1, sig.waf);
+            }
+        }
+        Ok(file)
+    }
+
+    pub fn into_signatures(self) -> Vec<WafSignature> {
+        self.signatures.into_iter().map(WafSignature::from).collect()
+    }
+}
+
+/// `~/.config/apihunter/waf_signatures.json`
+pub fn user_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config/apihunter/waf_signatures.json"))
+}
+
+/// Signatures from `path`, or the embedded set if it is missing or broken
+pub fn load_from(path: Option<&Path>) -> Vec<WafSignature> {
+    if let Some(path) = path.filter(|p| p.exists()) {
+        match std::fs::read_to_string(path).map_err(anyhow::Error::from).and_then(|data| SignatureFile::parse(&data)) {
+            Ok(file) => return file.into_signatures(),
+            Err(e) => tracing::warn!("{}: {:#}, using the built-in WAF signatures", path.display(), e),
+        }
+    }
+    embedded()
+}
+
+pub fn embedded() -> Vec<WafSignature> {
+    SignatureFile::parse(EMBEDDED).map(SignatureFile::into_signatures).unwrap_or_default()
+}
+
 pub fn load_signatures() -> Vec<WafSignature> {
-    vec![
-        // Cloudflare
-        WafSignature {
-            waf_type: WafType::Cloudflare,
-            headers: vec![
-                ("server".to_string(), "cloudflare".to_string()),
-                ("cf-ray".to_string(), "".to_string()),
-                ("cf-cache-status".to_string(), "".to_string()),
-            ],
-            server_patterns: vec!["cloudflare".to_string()],
-            cookies: vec!["__cfduid".to_string(), "__cf_bm".to_string()],
-            body_patterns: vec![
-                "cloudflare".to_string(),
-                "ray id".to_string(),
-                "cloudflare-nginx".to_string(),
-                "attention required".to_string(),
-            ],
-        },
-        // Imperva Incapsula
-        WafSignature {
-            waf_type: WafType::Imperva,
-            headers: vec![
-                ("x-cdn".to_string(), "incapsula".to_string()),
-                ("x-iinfo".to_string(), "".to_string()),
-            ],
-            server_patterns: vec![],
-            cookies: vec![
-                "incap_ses_".to_string(),
-                "visid_incap_".to_string(),
-                "nlbi_".to_string(),
-            ],
-            body_patterns: vec![
-                "incapsula".to_string(),
-                "incident id".to_string(),
-                "_incapsula_resource".to_string(),
-            ],
-        },
-        // Akamai
-        WafSignature {
-            waf_type: WafType::Akamai,
-            headers: vec![
-                ("server".to_string(), "akamaighost".to_string()),
-                ("x-akamai-request-id".to_string(), "".to_string()),
-                ("akamai-origin-hop".to_string(), "".to_string()),
-            ],
-            server_patterns: vec!["akamaighost".to_string()],
-            cookies: vec!["ak_bmsc".to_string(), "bm_sz".to_string()],
-            body_patterns: vec![
-                "akamai".to_string(),
-                "reference #".to_string(),
-                "access denied".to_string(),
-            ],
-        },
-        // F5 BIG-IP
-        WafSignature {
-            waf_type: WafType::F5BigIP,
-            headers: vec![
-                ("server".to_string(), "big-ip".to_string()),
-                ("x-wa-info".to_string(), "".to_string()),
-            ],
-            server_patterns: vec!["big-ip".to_string(), "bigip".to_string()],
-            cookies: vec![
-                "bigipserver".to_string(),
-                "f5-".to_string(),
-                "ts".to_string(),
-            ],
-            body_patterns: vec![
-                "the requested url was rejected".to_string(),
-                "f5 networks".to_string(),
-                "bigip".to_string(),
-            ],
-        },
-        // ModSecurity
-        WafSignature {
-            waf_type: WafType::ModSecurity,
-            headers: vec![("server".to_string(), "mod_security".to_string())],
-            server_patterns: vec!["mod_security".to_string(), "modsecurity".to_string()],
-            cookies: vec![],
-            body_patterns: vec![
-                "mod_security".to_string(),
-                "this error was generated by mod_security".to_string(),
-                "modsecurity".to_string(),
-            ],
-        },
-        // AWS WAF
-        WafSignature {
-            waf_type: WafType::AwsWaf,
-            headers: vec![
-                ("x-amzn-requestid".to_string(), "".to_string()),
-                ("x-amzn-errortype".to_string(), "".to_string()),
-                ("x-amz-cf-id".to_string(), "".to_string()),
-            ],
-            server_patterns: vec!["awselb".to_string(), "amazon".to_string()],
-            cookies: vec!["awsalb".to_string(), "awsalbcors".to_string()],
-            body_patterns: vec![
-                "aws".to_string(),
-                "forbidden".to_string(),
-                "request blocked".to_string(),
-            ],
-        },
-        // Azure WAF
-        WafSignature {
-            waf_type: WafType::AzureWaf,
-            headers: vec![
-                ("x-azure-ref".to_string(), "".to_string()),
-                ("x-msedge-ref".to_string(), "".to_string()),
-            ],
-            server_patterns: vec!["azure".to_string()],
-            cookies: vec!["arr".to_string()],
-            body_patterns: vec![
-                "azure".to_string(),
-                "microsoft".to_string(),
-                "this request has been blocked".to_string(),
-            ],
-        },
-        // Sucuri
-        WafSignature {
-            waf_type: WafType::Sucuri,
-            headers: vec![
-                ("server".to_string(), "sucuri".to_string()),
-                ("x-sucuri-id".to_string(), "".to_string()),
-                ("x-sucuri-cache".to_string(), "".to_string()),
-            ],
-            server_patterns: vec!["sucuri".to_string()],
-            cookies: vec!["sucuri-".to_string()],
-            body_patterns: vec![
-                "sucuri".to_string(),
-                "cloudproxy".to_string(),
-                "access denied - sucuri website firewall".to_string(),
-            ],
-        },
-        // Wordfence
-        WafSignature {
-            waf_type: WafType::Wordfence,
-            headers: vec![],
-            server_patterns: vec![],
-            cookies: vec!["wfvt_".to_string(), "wordfence".to_string()],
-            body_patterns: vec![
-                "wordfence".to_string(),
-                "generated by wordfence".to_string(),
-                "this response was generated by wordfence".to_string(),
-            ],
-        },
-        // Barracuda
-        WafSignature {
-            waf_type: WafType::Barracuda,
-            headers: vec![("server".to_string(), "barracuda".to_string())],
-            server_patterns: vec!["barracuda".to_string()],
-            cookies: vec!["barra_counter".to_string()],
-            body_patterns: vec![
-                "barracuda".to_string(),
-                "you have been blocked".to_string(),
-            ],
-        },
-        // Fortinet FortiWeb
-        WafSignature {
-            waf_type: WafType::Fortiweb,
-            headers: vec![],
-            server_patterns: vec!["fortiweb".to_string()],
-            cookies: vec!["fortiweb".to_string()],
-            body_patterns: vec![
-                "fortinet".to_string(),
-                "fortiweb".to_string(),
-                "request denied by fortiweb".to_string(),
-            ],
-        },
-        // Wallarm
-        WafSignature {
-            waf_type: WafType::Wallarm,
-            headers: vec![("server".to_string(), "wallarm".to_string())],
-            server_patterns: vec!["wallarm".to_string()],
-            cookies: vec!["wallarm".to_string()],
-            body_patterns: vec![
-                "wallarm".to_string(),
-                "blocked by wallarm".to_string(),
-            ],
-        },
-    ]
+    load_from(user_path().as_deref())
+}
+
+/// Fetch the signature set from `url`, check it and write it to `path`. Returns the file written.
+pub async fn update(client: &reqwest::Client, url: &str, path: &Path) -> Result<SignatureFile> {
+    let resp = client.get(url).send().await.with_context(|| format!("failed to fetch {}", url))?;
+    if !resp.status().is_success() {
+        bail!("{} answered {}", url, resp.status());
+    }
+    let data = resp.text().await.with_context(|| format!("failed to read {}", url))?;
+    let file = SignatureFile::parse(&data)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    // Written next to the target first, so a failed write leaves the old set in place
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, &data).with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(file)
 }
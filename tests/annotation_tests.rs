@@ -0,0 +1,34 @@
+use api_hunter::output::{Annotation, RawEvent};
+
+const LEGACY: &str = r#"{"orig_url":"https://example.com/api","final_url":"https://example.com/api","status":403,"content_type":null,"server":"cloudflare","content_length":null,"response_ms":12,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":3,"notes":["waf:cloudflare","WAF:UnknownBlock","key:id","JWT:2","something else"]}"#;
+
+#[test]
+fn test_legacy_notes_load_as_annotations() {
+    let ev: RawEvent = serde_json::from_str(LEGACY).unwrap();
+    assert_eq!(ev.annotations, vec![
+        Annotation::WafDetected { waf: "cloudflare".to_string() },
+        Annotation::WafBlock,
+        Annotation::JsonKey { key: "id".to_string() },
+        Annotation::JwtFound { count: 2 },
+        Annotation::Note { text: "something else".to_string() },
+    ]);
+    assert_eq!(ev.waf(), Some("cloudflare"));
+}
+
+#[test]
+fn test_tagged_serialization_roundtrip() {
+    let mut ev: RawEvent = serde_json::from_str(LEGACY).unwrap();
+    ev.annotations.clear();
+    assert!(ev.annotate(Annotation::RateLimited { retry_after: Some(30) }));
+    assert!(!ev.annotate(Annotation::RateLimited { retry_after: Some(30) }));
+    ev.annotate(Annotation::Cluster { id: "c3".to_string() });
+
+    let json = serde_json::to_value(&ev).unwrap();
+    assert_eq!(json["annotations"][0], serde_json::json!({"type": "rate_limited", "retry_after": 30}));
+    assert_eq!(json["annotations"][1], serde_json::json!({"type": "cluster", "id": "c3"}));
+    assert!(json.get("notes").is_none());
+
+    let back: RawEvent = serde_json::from_value(json).unwrap();
+    assert_eq!(back.annotations, ev.annotations);
+    assert_eq!(api_hunter::output::annotation::join(&back.annotations, ";"), "rate-limited:30s;cluster:c3");
+}
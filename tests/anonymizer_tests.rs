@@ -0,0 +1,66 @@
+use api_hunter::anonymizer::Anonymizer;
+use api_hunter::tls_profile::TlsProfile;
+use common::Response;
+
+mod common;
+
+
+
+#[test]
+fn test_profile_from_user_agent() {
+    assert_eq!(TlsProfile::from_user_agent("Mozilla/5.0 (X11; Ubuntu; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0"), TlsProfile::Firefox121);
+    assert_eq!(TlsProfile::from_user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15"), TlsProfile::Safari17);
+    assert_eq!(TlsProfile::from_user_agent("Mozilla/5.0 (Linux; Android 14) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36"), TlsProfile::Chrome120);
+
+    let android = TlsProfile::Chrome120.browser_headers("Mozilla/5.0 (Linux; Android 14) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36");
+    assert_eq!(android["sec-ch-ua-platform"], "\"Android\"");
+    assert_eq!(android["sec-ch-ua-mobile"], "?1");
+    assert_eq!(android["sec-fetch-mode"], "cors");
+}
+
+#[tokio::test]
+async fn test_anonymous_client_sends_browser_headers_in_order() {
+    let anon = Anonymizer::new(true);
+    let user_agent = anon.get_current_user_agent();
+    let expected: Vec<String> = TlsProfile::from_user_agent(&user_agent).browser_headers(&user_agent)
+        .keys().map(|k| k.to_string()).collect();
+
+    let (base, seen) = common::serve_recording(|_| Response::new(204)).await;
+    let client = anon.create_anonymous_client(5).unwrap();
+    let resp = client.get(format!("{}/api/users", base)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+
+    let names: Vec<String> = seen.lock()[0].headers.iter().map(|(k, _)| k.to_ascii_lowercase()).collect();
+    let sent: Vec<&String> = names.iter().filter(|n| expected.contains(n)).collect();
+    // All of the profile's headers, in its order (reqwest's own Accept default keeps its slot)
+    let mut expected_order: Vec<&String> = expected.iter().collect();
+    if let Some(pos) = expected_order.iter().position(|n| *n == "accept") {
+        let accept = expected_order.remove(pos);
+        expected_order.insert(0, accept);
+    }
+    assert_eq!(sent, expected_order, "sent {:?}", names);
+}
+
+#[test]
+fn test_session_persists_across_runs() {
+    let dir = std::env::temp_dir().join(format!("api_hunter_anon_{}", std::process::id()));
+    let path = api_hunter::anonymizer::session_path(&dir, "example.com");
+    assert!(path.ends_with(".anon/example.com.json"));
+
+    let first = Anonymizer::new(true);
+    first.save_session(&path).unwrap();
+    let saved = first.session();
+
+    let mut second = Anonymizer::new(true);
+    assert!(second.load_session(&path).unwrap());
+    assert_eq!(second.get_current_user_agent(), saved.user_agent);
+    let restored = second.session();
+    assert_eq!((restored.session_id, restored.started_at, restored.rotations), (saved.session_id.clone(), saved.started_at, saved.rotations));
+    assert!(restored.last_rotation.abs_diff(saved.last_rotation) <= 1);
+
+    // A user agent the current profile can't produce starts a new identity
+    let mut foreign = saved.clone();
+    foreign.user_agent = "curl/8.0".to_string();
+    assert!(!second.restore(&foreign));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,58 @@
+use api_hunter::auth::session::{AuthConfig, SessionMonitor};
+
+#[test]
+fn test_auth_headers() {
+    let auth = AuthConfig {
+        token: Some("Bearer abc.def".to_string()),
+        cookie: Some(" session=1; csrf=2 ".to_string()),
+        header: Some("X-Api-Key: k3y".to_string()),
+        basic: Some("user:pass".to_string()),
+        login: None,
+    };
+    let headers = auth.headers().unwrap();
+    // The token wins over basic credentials
+    assert_eq!(headers["authorization"], "Bearer abc.def");
+    assert!(headers["authorization"].is_sensitive());
+    assert_eq!(headers["cookie"], "session=1; csrf=2");
+    assert_eq!(headers["x-api-key"], "k3y");
+
+    let basic = AuthConfig { basic: Some("user:pass".to_string()), ..Default::default() };
+    assert_eq!(basic.headers().unwrap()["authorization"], "Basic dXNlcjpwYXNz");
+
+    assert!(AuthConfig { basic: Some("user".to_string()), ..Default::default() }.headers().is_err());
+    assert!(AuthConfig { header: Some("X-Api-Key".to_string()), ..Default::default() }.headers().is_err());
+    assert!(AuthConfig::default().headers().unwrap().is_empty());
+}
+
+#[test]
+fn test_flags_override_config() {
+    let file: AuthConfig = serde_json::from_str(r#"{"token": "old", "cookie": "a=b"}"#).unwrap();
+    let merged = file.merge(AuthConfig { token: Some("new".to_string()), ..Default::default() });
+    assert_eq!(merged.token.as_deref(), Some("new"));
+    assert_eq!(merged.cookie.as_deref(), Some("a=b"));
+    assert_eq!(merged.methods(), vec!["bearer token", "cookie"]);
+}
+
+#[test]
+fn test_401_wave_detected_once() {
+    let monitor = SessionMonitor::new();
+    let mut alerts = 0;
+    for i in 0..100 {
+        // Occasional 401s on protected admin routes are normal
+        let status = if i % 10 == 0 { 401 } else { 200 };
+        alerts += monitor.record(status) as usize;
+    }
+    assert_eq!(alerts, 0);
+    for _ in 0..100 {
+        alerts += monitor.record(401) as usize;
+    }
+    assert_eq!(alerts, 1);
+    assert_eq!(monitor.expired_at(), Some(100));
+}
+
+#[test]
+fn test_rejected_from_start_is_no_expiry() {
+    let monitor = SessionMonitor::new();
+    assert!(!(0..200).any(|_| monitor.record(401)));
+    assert_eq!(monitor.expired_at(), None);
+}
@@ -0,0 +1,44 @@
+use api_hunter::probe::body::{BodyCapture, BodyEncoding, StoredBody};
+use api_hunter::probe::http_probe::{probe_url, ProbeOptions};
+use common::Response;
+
+mod common;
+
+#[test]
+fn test_capture_roundtrip() {
+    let text = StoredBody::capture(b"<html>token=abc</html>", false, false).unwrap();
+    assert_eq!(text.encoding, BodyEncoding::Text);
+    assert_eq!(text.text().unwrap(), "<html>token=abc</html>");
+
+    let body = "x".repeat(5000);
+    let gz = StoredBody::capture(body.as_bytes(), true, true).unwrap();
+    assert_eq!(gz.encoding, BodyEncoding::GzipBase64);
+    assert!(gz.data.len() < 1000);
+    assert!(gz.truncated);
+    assert_eq!(gz.text().unwrap(), body);
+
+    // Binary bodies are only kept compressed
+    assert!(StoredBody::capture(b"\x89PNG\r\n\x1a\n\0\0", false, false).is_none());
+    assert_eq!(StoredBody::capture(b"\x89PNG\r\n\x1a\n\0\0", false, true).unwrap().bytes().unwrap(), b"\x89PNG\r\n\x1a\n\0\0");
+}
+
+#[tokio::test]
+async fn test_probe_stores_truncated_body() {
+    // Ignores Range on purpose
+    let base = common::serve(|_| {
+        Response::html(200, format!("<html><script>var t = 'secret';</script>{}</html>", "a".repeat(20_000)))
+    }).await;
+
+    let client = reqwest::Client::new();
+    let url = format!("{}/page", base);
+    let opts = ProbeOptions { store_body: Some(BodyCapture { max_bytes: 4096, compress: false }), ..ProbeOptions::new(5, 1) };
+    let ev = probe_url(&client, &url, None, &opts).await.unwrap();
+    let body = ev.body.expect("body stored");
+    assert_eq!(body.size, 4096);
+    assert!(body.truncated);
+    assert!(body.text().unwrap().starts_with("<html><script>"));
+
+    // Off by default
+    let ev = probe_url(&client, &url, None, &ProbeOptions::new(5, 1)).await.unwrap();
+    assert!(ev.body.is_none());
+}
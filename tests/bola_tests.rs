@@ -0,0 +1,77 @@
+use api_hunter::fuzz::bola::{compare, is_object_url, object_urls, BolaTester, Fetched, Identity, Verdict};
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+const ORDER_A: &str = r#"{"id":17,"owner":"alice@example.com","total":129.5,"address":"Main Street 1"}"#;
+const ORDER_B: &str = r#"{"id":17,"owner":"bob@example.com","total":12.0,"address":"Side Road 9"}"#;
+
+#[test]
+fn test_compare_verdicts() {
+    let a = Fetched::new(200, ORDER_A.to_string());
+    let denied = Fetched::new(401, r#"{"error":"unauthorized"}"#.to_string());
+
+    assert!(matches!(compare(&a, &Fetched::new(200, ORDER_A.to_string()), Some(&denied)), Verdict::Vulnerable { identical: true, .. }));
+    assert_eq!(compare(&a, &Fetched::new(403, String::new()), Some(&denied)), Verdict::Denied);
+    assert_eq!(compare(&a, &Fetched::new(200, ORDER_A.to_string()), Some(&a)), Verdict::Public);
+    // B sees its own version of /me-style resources
+    assert_eq!(compare(&a, &Fetched::new(200, ORDER_B.to_string()), Some(&denied)), Verdict::Different);
+    assert_eq!(compare(&denied, &a, None), Verdict::Unusable);
+
+    // Same data with a per-request field still counts
+    let reordered = Fetched::new(200, r#"{"request_id":"r-99123","id":17,"owner":"alice@example.com","total":129.5,"address":"Main Street 1"}"#.to_string());
+    match compare(&a, &reordered, Some(&denied)) {
+        Verdict::Vulnerable { identical, leaked } => {
+            assert!(!identical);
+            assert!(leaked.contains(&"owner=alice@example.com".to_string()));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_object_urls() {
+    assert!(is_object_url("https://api.example.com/orders/17"));
+    assert!(is_object_url("https://api.example.com/orders?order_id=5"));
+    assert!(!is_object_url("https://api.example.com/orders"));
+
+    let list = serde_json::json!({"data": [{"id": 17}, {"id": "a-b"}, {"name": "x"}, {"id": 20}, {"id": 21}]});
+    assert_eq!(object_urls("https://api.example.com/orders/?page=1", &list), vec![
+        "https://api.example.com/orders/17",
+        "https://api.example.com/orders/a-b",
+        "https://api.example.com/orders/20",
+    ]);
+}
+
+#[tokio::test]
+async fn test_bola_end_to_end() {
+    let base = common::serve(|req| {
+        let user = match req.header("authorization") {
+            Some("Bearer AAA") => "a",
+            Some("Bearer BBB") => "b",
+            _ => "",
+        };
+        let body = match (req.path(), user) {
+            (_, "") => None,
+            ("/api/orders", _) => Some(r#"[{"id":17}]"#),
+            // Vulnerable: anyone logged in gets order 17
+            ("/api/orders/17", _) => Some(ORDER_A),
+            // Own profile per user
+            ("/api/users/5", "a") => Some(r#"{"id":5,"email":"alice@example.com"}"#),
+            ("/api/users/5", "b") => Some(r#"{"id":6,"email":"bob@example.com"}"#),
+            _ => None,
+        };
+        body.map_or_else(|| Response::new(401), |b| Response::json(200, b))
+    }).await;
+
+    let a = Identity::from_specs("A", &["Authorization: Bearer AAA".to_string()]).unwrap();
+    let b = Identity::from_specs("B", &["Authorization: Bearer BBB".to_string()]).unwrap();
+    let tester = BolaTester::new(reqwest::Client::new(), a, b, Duration::from_secs(5));
+    let findings = tester.run(&[format!("{}/api/orders", base), format!("{}/api/users/5", base)], 4).await;
+
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].url, format!("{}/api/orders/17", base));
+    assert_eq!(findings[0].severity, "high");
+    assert_eq!(findings[0].status_anonymous, Some(401));
+}
@@ -0,0 +1,39 @@
+use api_hunter::gather::bundle_reader::{binary_kind, WindowedReader};
+
+#[test]
+fn test_match_straddling_window_edge_is_found() {
+    let mut content = "x".repeat(60);
+    content.push_str("fetch('/api/v2/users/profile')");
+    content.push_str(&"y".repeat(100));
+
+    let mut reader = WindowedReader::new(64, 32);
+    let mut windows = Vec::new();
+    for piece in content.as_bytes().chunks(17) {
+        windows.extend(reader.push(piece));
+    }
+    windows.extend(reader.finish());
+
+    assert!(windows.len() > 1);
+    assert!(windows.iter().any(|w| w.contains("/api/v2/users/profile")));
+    // Every byte is covered
+    assert!(windows[0].starts_with("xxx"));
+    assert!(windows.last().unwrap().ends_with("yyy"));
+}
+
+#[test]
+fn test_windows_do_not_split_utf8() {
+    let content = "ä".repeat(100);
+    let mut reader = WindowedReader::new(33, 8);
+    let mut windows = reader.push(content.as_bytes());
+    windows.extend(reader.finish());
+    assert!(windows.iter().all(|w| !w.contains('\u{FFFD}')));
+}
+
+#[test]
+fn test_binary_kind() {
+    assert_eq!(binary_kind(b"\0asm\x01\0\0\0", None), Some("wasm"));
+    assert_eq!(binary_kind(b"anything", Some("application/wasm")), Some("wasm"));
+    assert_eq!(binary_kind(b"\x89PNG\r\n\x1a\n", Some("application/javascript")), Some("image"));
+    assert_eq!(binary_kind(b"var a = 1;\0\0\0", None), Some("binary"));
+    assert_eq!(binary_kind(b"!function(e){var t={};}();\n", Some("application/javascript")), None);
+}
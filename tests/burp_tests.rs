@@ -0,0 +1,28 @@
+use api_hunter::gather::burp::parse_burp;
+
+#[test]
+fn parse_burp_extracts_requests() {
+    let xml = r#"<?xml version="1.0"?>
+<items burpVersion="2023.10">
+  <item>
+    <url><![CDATA[https://example.com/api/v1/orders]]></url>
+    <method><![CDATA[POST]]></method>
+    <request base64="true"><![CDATA[UE9TVCAvYXBpL3YxL29yZGVycyBIVFRQLzEuMQ0KSG9zdDogZXhhbXBsZS5jb20NCkF1dGhvcml6YXRpb246IEJlYXJlciBhYmMNCkNvbnRlbnQtVHlwZTogYXBwbGljYXRpb24vanNvbg0KDQp7ImlkIjoxfQ==]]></request>
+  </item>
+  <item>
+    <url><![CDATA[https://example.com/api/v1/orders]]></url>
+    <method><![CDATA[POST]]></method>
+    <request base64="false"><![CDATA[POST /api/v1/orders HTTP/1.1]]></request>
+  </item>
+  <item>
+    <url>https://example.com/search?q=1&amp;page=2</url>
+    <method>GET</method>
+  </item>
+</items>"#;
+    let reqs = parse_burp(xml).unwrap();
+    assert_eq!(reqs.len(), 2);
+    assert_eq!(reqs[0].method, "POST");
+    assert_eq!(reqs[0].headers.get("authorization").map(|s| s.as_str()), Some("Bearer abc"));
+    assert!(reqs[0].json_body.is_some());
+    assert_eq!(reqs[1].url, "https://example.com/search?q=1&page=2");
+}
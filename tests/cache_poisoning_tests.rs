@@ -0,0 +1,108 @@
+use api_hunter::output::RawEvent;
+use api_hunter::probe::cache_poisoning::{self, CacheAttack, CacheInfo, CachePoisoner};
+use common::Response;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+mod common;
+
+fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_cache_info() {
+    let cf = CacheInfo::from_headers(&headers(&[("cf-cache-status", "HIT"), ("cf-ray", "8a1"), ("age", "12"), ("cache-control", "public, max-age=300")]));
+    assert_eq!((cf.cdn.as_deref(), cf.hit, cf.age), (Some("Cloudflare"), true, Some(12)));
+    assert!(cf.cacheable());
+
+    let cloudfront = CacheInfo::from_headers(&headers(&[("x-cache", "Miss from cloudfront"), ("via", "1.1 abc.cloudfront.net (CloudFront)")]));
+    assert_eq!((cloudfront.cdn.as_deref(), cloudfront.hit), (Some("CloudFront"), false));
+    assert!(cloudfront.is_cached());
+    assert!(!cloudfront.cacheable());
+
+    assert!(!CacheInfo::from_headers(&headers(&[("cache-control", "private, max-age=600")])).cacheable());
+    assert!(!CacheInfo::from_headers(&headers(&[("cache-control", "max-age=0")])).cacheable());
+    assert!(CacheInfo::from_headers(&headers(&[("cache-control", "s-maxage=60")])).cacheable());
+    assert!(!CacheInfo::from_headers(&headers(&[("content-type", "text/html")])).is_cached());
+}
+
+#[test]
+fn test_urls() {
+    assert_eq!(cache_poisoning::with_buster("https://a.example/x?q=1#top", "c1"), "https://a.example/x?q=1&apihunter_cb=c1");
+    assert_eq!(cache_poisoning::with_buster("https://a.example/x", "c1"), "https://a.example/x?apihunter_cb=c1");
+    assert_eq!(cache_poisoning::deception_url("https://a.example/api/me/?x=1", "/{}.css", "n").as_deref(), Some("https://a.example/api/me/n.css"));
+    assert_eq!(cache_poisoning::deception_url("https://a.example/api/me", ";{}.css", "n").as_deref(), Some("https://a.example/api/me;n.css"));
+}
+
+#[test]
+fn test_targets_prefer_cached_then_json() {
+    let event = |url: &str, ct: &str, cached: bool| {
+        let mut ev: RawEvent = serde_json::from_str(&format!(
+            r#"{{"orig_url":"{0}","final_url":"{0}","status":200,"content_type":"{1}","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1}}"#,
+            url, ct
+        )).unwrap();
+        if cached {
+            ev.headers.insert("x-cache".to_string(), "HIT".to_string());
+        }
+        ev
+    };
+    let results = vec![
+        event("https://a.example/", "text/html", false),
+        event("https://a.example/api/me", "application/json", false),
+        event("https://a.example/static/page", "text/html", true),
+        event("https://a.example/api/orders", "application/json", false),
+        event("https://b.example/api/v1", "application/json", false),
+    ];
+    assert_eq!(cache_poisoning::targets(&results, 10), vec!["https://a.example/static/page", "https://a.example/api/me", "https://b.example/api/v1"]);
+}
+
+/// A caching proxy keyed on the request target, in front of an app that trusts `X-Forwarded-Host` and
+/// routes `/api/me/<anything>` to `/api/me`. Paths ending in `.css` are cached whatever the app says.
+async fn caching_server() -> String {
+    let cache: Arc<Mutex<HashMap<String, Response>>> = Arc::default();
+    common::serve(move |req| {
+        if let Some(stored) = cache.lock().unwrap().get(&req.target) {
+            let mut hit = stored.clone().header("Age", "3");
+            hit.headers.iter_mut().filter(|(k, _)| k == "X-Cache").for_each(|(_, v)| *v = "HIT".to_string());
+            return hit;
+        }
+        let response = if req.path().starts_with("/api/me") {
+            Response::json(200, r#"{"user":"alice","email":"alice@example.com","api_key":"k-19f2"}"#).header("Cache-Control", "no-store")
+        } else {
+            let host = req.header("x-forwarded-host").or(req.header("host")).unwrap_or_default();
+            Response::html(200, format!(r#"<html><script src="//{}/static/app.js"></script></html>"#, host))
+                .header("Cache-Control", "public, max-age=60")
+        }.header("X-Cache", "MISS");
+        if response.headers.iter().any(|(_, v)| v.starts_with("public")) || req.path().ends_with(".css") {
+            cache.lock().unwrap().insert(req.target.clone(), response.clone());
+        }
+        response
+    }).await
+}
+
+#[tokio::test]
+async fn test_unkeyed_header_and_deception() {
+    let base = caching_server().await;
+    let poisoner = CachePoisoner::new(Duration::from_secs(5)).unwrap();
+
+    let page = poisoner.test(&format!("{}/page", base)).await.unwrap();
+    assert!(page.buster_keyed);
+    assert_eq!(page.cache.status.as_deref(), Some("MISS"));
+    assert_eq!(page.findings.len(), 1, "{:?}", page.findings);
+    let f = &page.findings[0];
+    assert_eq!(f.attack, CacheAttack::UnkeyedHeader);
+    assert!(f.vector.starts_with("X-Forwarded-Host: apihunter"));
+    assert!(f.cache_key.contains("apihunter_cb="));
+    assert!(f.confirmed);
+    assert_eq!(f.severity, "high");
+
+    let me = poisoner.test(&format!("{}/api/me", base)).await.unwrap();
+    assert_eq!(me.findings.len(), 1, "{:?}", me.findings);
+    let f = &me.findings[0];
+    assert_eq!(f.attack, CacheAttack::CacheDeception);
+    assert!(f.vector.starts_with(&format!("{}/api/me/apihunter", base)) && f.vector.ends_with(".css"));
+    assert!(f.confirmed);
+    assert_eq!(f.severity, "high");
+}
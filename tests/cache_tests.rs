@@ -0,0 +1,71 @@
+use api_hunter::gather::cache::SourceCache;
+use std::time::Duration;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("apihunter-cache-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn stores_and_expires_entries() {
+    let dir = temp_dir("ttl");
+    let cache = SourceCache::new(dir.clone(), Duration::from_secs(3600));
+    cache.put("crtsh", "example.com", &vec!["api.example.com".to_string()]);
+
+    assert_eq!(cache.get::<Vec<String>>("crtsh", "example.com"), Some(vec!["api.example.com".to_string()]));
+    assert_eq!(cache.get::<Vec<String>>("crtsh", "other.com"), None);
+    assert_eq!(cache.get::<Vec<String>>("otx", "example.com"), None);
+
+    let expired = SourceCache::new(dir.clone(), Duration::ZERO);
+    std::thread::sleep(Duration::from_millis(1100));
+    assert_eq!(expired.get::<Vec<String>>("crtsh", "example.com"), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn replays_streamed_urls() {
+    let dir = temp_dir("stream");
+    let cache = SourceCache::new(dir.clone(), Duration::from_secs(3600));
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let sent = cache.stream("otx", "example.com", tx, |t| async move {
+        t.send("https://example.com/api/a".to_string()).await.ok();
+        t.send("https://example.com/api/b".to_string()).await.ok();
+        Ok(2)
+    }).await.unwrap();
+    assert_eq!(sent, 2);
+    let mut first = Vec::new();
+    while let Some(u) = rx.recv().await { first.push(u); }
+
+    // Second run must not call the gatherer
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let sent = cache.stream("otx", "example.com", tx, |_t| async move {
+        panic!("gatherer should not run on a cache hit");
+    }).await.unwrap();
+    assert_eq!(sent, 2);
+    let mut second = Vec::new();
+    while let Some(u) = rx.recv().await { second.push(u); }
+    assert_eq!(first, second);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn truncated_streams_are_not_cached() {
+    let dir = temp_dir("truncated");
+    let cache = SourceCache::new(dir.clone(), Duration::from_secs(3600));
+
+    // A gatherer that hit a rate limit after one page: its URLs are forwarded, but not stored
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let res = cache.stream("urlscan", "example.com", tx, |t| async move {
+        t.send("https://example.com/api/a".to_string()).await.ok();
+        anyhow::bail!("search returned 429 Too Many Requests after 1 URLs")
+    }).await;
+    assert!(res.is_err());
+    assert_eq!(rx.recv().await.as_deref(), Some("https://example.com/api/a"));
+    assert_eq!(cache.get::<Vec<String>>("urlscan", "example.com"), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
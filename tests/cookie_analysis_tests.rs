@@ -0,0 +1,79 @@
+use api_hunter::analyze::cookies::{self, CookieCheck, SetCookie};
+use api_hunter::output::RawEvent;
+use std::time::SystemTime;
+
+fn checks(header: &str, url: &str) -> Vec<CookieCheck> {
+    let now = httpdate::parse_http_date("Sat, 17 Oct 2026 12:00:00 GMT").unwrap();
+    cookies::check_cookie(&SetCookie::parse(header).unwrap(), url, now).iter().map(|i| i.check).collect()
+}
+
+#[test]
+fn test_parse_set_cookie() {
+    let c = SetCookie::parse("SID=abc=def; Domain=.Example.com; Path=/; expires=Wed, 21-Oct-2026 07:28:00 GMT; secure; HttpOnly; SameSite=Lax").unwrap();
+    assert_eq!((c.name.as_str(), c.value.as_str()), ("SID", "abc=def"));
+    assert_eq!(c.domain.as_deref(), Some("example.com"));
+    assert_eq!((c.secure, c.http_only, c.same_site.as_deref()), (true, true, Some("Lax")));
+    let now = httpdate::parse_http_date("Sat, 17 Oct 2026 07:28:00 GMT").unwrap();
+    assert_eq!(c.lifetime(now), Some(4 * 86400));
+    // Max-Age wins over Expires
+    assert_eq!(SetCookie::parse("a=1; Expires=Wed, 21 Oct 2026 07:28:00 GMT; Max-Age=60").unwrap().lifetime(now), Some(60));
+    assert_eq!(SetCookie::parse("a=1").unwrap().lifetime(now), None);
+    assert!(SetCookie::parse("no-equals-sign").is_none());
+    assert_eq!(SetCookie::parse_all("a=1; Secure\nb=2").len(), 2);
+
+    assert!(SetCookie::parse("PHPSESSID=x").unwrap().is_session());
+    assert!(!SetCookie::parse("csrftoken=x").unwrap().is_session());
+    assert!(!SetCookie::parse("theme=dark").unwrap().is_session());
+}
+
+#[test]
+fn test_session_cookie_checks() {
+    let url = "https://app.example.com/login";
+    assert_eq!(checks("__Host-session=x; Path=/; Secure; HttpOnly; SameSite=Lax", url), vec![]);
+    assert_eq!(
+        checks("session=x; Domain=example.com; Max-Age=31536000", url),
+        vec![CookieCheck::NoPrefix, CookieCheck::MissingSecure, CookieCheck::MissingHttpOnly, CookieCheck::MissingSameSite, CookieCheck::BroadDomain, CookieCheck::LongLived],
+    );
+    assert_eq!(checks("auth_token=x; Secure; HttpOnly; SameSite=None", url), vec![CookieCheck::NoPrefix, CookieCheck::SameSiteNone]);
+    assert_eq!(checks("sessionid=x; HttpOnly; SameSite=Strict", "http://app.example.com/"), vec![CookieCheck::SetOverHttp]);
+}
+
+#[test]
+fn test_prefix_and_non_session_checks() {
+    let url = "https://app.example.com/";
+    assert_eq!(checks("__Host-id=x; Domain=example.com; Path=/app; Secure", url), vec![CookieCheck::HostPrefixViolation]);
+    assert_eq!(checks("__Secure-pref=x; Path=/", url), vec![CookieCheck::SecurePrefixViolation]);
+    // Preference and CSRF cookies only need Secure
+    assert_eq!(checks("theme=dark; Max-Age=99999999", url), vec![CookieCheck::MissingSecure]);
+    assert_eq!(checks("XSRF-TOKEN=x; Secure; SameSite=None", url), vec![]);
+
+    let issues = cookies::check_cookie(&SetCookie::parse("theme=dark").unwrap(), url, SystemTime::now());
+    assert_eq!(cookies::severity(&issues), "LOW");
+}
+
+#[test]
+fn test_events_grouped_by_origin_and_name() {
+    let event = |url: &str, set_cookie: &str| {
+        let mut ev: RawEvent = serde_json::from_str(&format!(
+            r#"{{"orig_url":"{0}","final_url":"{0}","status":200,"content_type":"text/html","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1}}"#,
+            url
+        )).unwrap();
+        ev.headers.insert("set-cookie".to_string(), set_cookie.to_string());
+        ev
+    };
+    let results = vec![
+        event("https://app.example.com/", "sid=1; Path=/\ntheme=dark; Secure"),
+        event("https://app.example.com/login", "sid=2; Path=/"),
+        event("https://api.example.com/", "sid=3; Secure; HttpOnly; SameSite=Lax; Max-Age=3600"),
+    ];
+    let reports = cookies::analyze_events(&results, SystemTime::now());
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].urls, vec!["https://app.example.com/", "https://app.example.com/login"]);
+    assert!(reports[0].session);
+    assert_eq!(reports[0].severity(), "MEDIUM");
+    assert_eq!(reports[1].severity(), "INFO");
+    assert_eq!((reports[2].origin.as_str(), reports[2].lifetime), ("https://api.example.com", Some(3600)));
+    assert!(reports[2].issues.iter().all(|i| i.check == CookieCheck::NoPrefix));
+    // The cookie value is never written out
+    assert!(serde_json::to_value(&reports[0]).unwrap()["cookie"].get("value").is_none());
+}
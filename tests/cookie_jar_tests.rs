@@ -0,0 +1,47 @@
+use api_hunter::cookie_jar::Jar;
+use reqwest::cookie::CookieStore;
+use reqwest::header::HeaderValue;
+use common::Response;
+use std::sync::Arc;
+
+mod common;
+
+#[tokio::test]
+async fn test_cookies_sent_back() {
+    let (base, seen) = common::serve_recording(|req| match req.path() {
+        "/login" => Response::new(200).header("Set-Cookie", "sid=abc; Path=/; HttpOnly"),
+        _ => Response::new(200),
+    }).await;
+
+    let jar = Arc::new(Jar::new());
+    let client = reqwest::Client::builder().cookie_provider(jar.clone()).build().unwrap();
+    client.get(format!("{}/login", base)).send().await.unwrap();
+    client.get(format!("{}/api/me", base)).send().await.unwrap();
+
+    let requests = seen.lock();
+    assert!(requests[0].header("cookie").is_none());
+    assert_eq!(requests[1].header("cookie"), Some("sid=abc"));
+    assert_eq!(jar.len(), 1);
+}
+
+#[test]
+fn test_save_and_load_keep_session_cookies() {
+    let url = url::Url::parse("https://api.example.com/v1/users").unwrap();
+    let jar = Jar::new();
+    let set = [HeaderValue::from_static("sid=abc; Path=/"), HeaderValue::from_static("pref=dark; Max-Age=3600")];
+    jar.set_cookies(&mut set.iter(), &url);
+
+    let dir = std::env::temp_dir().join(format!("apihunter_cookie_jar_{}", std::process::id()));
+    let path = api_hunter::cookie_jar::path(&dir, "api.example.com");
+    assert!(path.ends_with(".cookies/api.example.com.json"));
+    assert_eq!(jar.save(&path).unwrap(), 2);
+
+    let loaded = Jar::new();
+    assert_eq!(loaded.load(&path).unwrap(), 2);
+    let mut cookies = loaded.cookies_for(&url);
+    cookies.sort();
+    assert_eq!(cookies, vec!["pref=dark", "sid=abc"]);
+    // Not sent to other hosts
+    assert!(loaded.cookies(&url::Url::parse("https://other.example.com/").unwrap()).is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,99 @@
+use api_hunter::analyze::cors_checker::{self, CorsAnalysis, Preflight, PreflightAnalysis, PROBE_HEADER, PROBE_ORIGIN};
+use api_hunter::analyze::risk_classifier::RiskClassifier;
+use api_hunter::analyze::vulnerability_scanner::VulnerabilitySeverity;
+use common::Response;
+use std::collections::HashMap;
+
+mod common;
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_evaluate_preflight() {
+    let reflected = headers(&[
+        ("access-control-allow-origin", PROBE_ORIGIN),
+        ("access-control-allow-credentials", "true"),
+        ("access-control-allow-methods", "GET, PUT, DELETE"),
+        ("access-control-allow-headers", "Content-Type"),
+    ]);
+    let p = Preflight::evaluate("PUT", &["content-type"], PROBE_ORIGIN, 204, &reflected);
+    assert!(p.accepted && p.allow_credentials);
+    // Method or header not allowed
+    assert!(!Preflight::evaluate("PATCH", &[], PROBE_ORIGIN, 204, &reflected).accepted);
+    assert!(!Preflight::evaluate("DELETE", &["authorization"], PROBE_ORIGIN, 204, &reflected).accepted);
+    // Another origin, or an error status
+    assert!(!Preflight::evaluate("PUT", &[], "null", 204, &reflected).accepted);
+    assert!(!Preflight::evaluate("PUT", &[], PROBE_ORIGIN, 403, &reflected).accepted);
+
+    // `*` is no wildcard with credentials, and never covers Authorization
+    let wildcard = headers(&[("access-control-allow-origin", "*"), ("access-control-allow-methods", "*"), ("access-control-allow-headers", "*")]);
+    assert!(Preflight::evaluate("DELETE", &[PROBE_HEADER], PROBE_ORIGIN, 200, &wildcard).accepted);
+    assert!(!Preflight::evaluate("DELETE", &["authorization"], PROBE_ORIGIN, 200, &wildcard).accepted);
+    let mut with_credentials = wildcard.clone();
+    with_credentials.insert("access-control-allow-credentials".to_string(), "true".to_string());
+    assert!(!Preflight::evaluate("DELETE", &[], PROBE_ORIGIN, 200, &with_credentials).accepted);
+}
+
+#[test]
+fn test_preflight_findings_and_scoring() {
+    let reflected = |origin: &str| headers(&[
+        ("access-control-allow-origin", origin),
+        ("access-control-allow-credentials", "true"),
+        ("access-control-allow-methods", "PUT, DELETE, PATCH"),
+        ("access-control-allow-headers", &format!("content-type, authorization, {}", PROBE_HEADER)),
+        ("access-control-max-age", "604800"),
+    ]);
+    let analysis = PreflightAnalysis::from_preflights(vec![
+        Preflight::evaluate("PUT", &["content-type"], PROBE_ORIGIN, 204, &reflected(PROBE_ORIGIN)),
+        Preflight::evaluate("DELETE", &["authorization"], PROBE_ORIGIN, 204, &reflected(PROBE_ORIGIN)),
+        Preflight::evaluate("PATCH", &[PROBE_HEADER], PROBE_ORIGIN, 204, &reflected(PROBE_ORIGIN)),
+        Preflight::evaluate("PUT", &["content-type"], "null", 204, &reflected("null")),
+    ]);
+    assert!(analysis.arbitrary_origin && analysis.null_origin && analysis.credentials && analysis.any_header);
+    assert_eq!(analysis.dangerous_methods, vec!["PUT", "DELETE", "PATCH"]);
+    assert_eq!(analysis.findings.len(), 4);
+    assert!(analysis.findings[0].starts_with("CRITICAL: Preflight accepts any origin with credentials"));
+
+    // No CORS headers on the simple request: only the preflight shows the problem
+    let cors = CorsAnalysis::analyze(&HashMap::new()).with_preflight(analysis, true);
+    assert!(cors.is_misconfigured);
+    assert_eq!(cors.risk.unwrap().final_severity, VulnerabilitySeverity::Critical);
+
+    // Null origin with state-changing methods, no credentials
+    let null_only = PreflightAnalysis::from_preflights(vec![
+        Preflight::evaluate("PUT", &["content-type"], PROBE_ORIGIN, 403, &HashMap::new()),
+        Preflight::evaluate("PUT", &["content-type"], "null", 204, &headers(&[("access-control-allow-origin", "null"), ("access-control-allow-methods", "PUT"), ("access-control-allow-headers", "content-type")])),
+    ]);
+    let risk = RiskClassifier::classify_cors_issue(false, false, false, false, false, Some(&null_only));
+    assert_eq!(risk.final_severity, VulnerabilitySeverity::Critical);
+    assert_eq!(risk.factors.iter().map(|f| f.factor.as_str()).collect::<Vec<_>>(), vec!["Null Origin Accepted", "Sensitive Operations", "Dangerous Methods"]);
+
+    // A strict preflight adds nothing
+    let strict = PreflightAnalysis::from_preflights(vec![Preflight::evaluate("PUT", &[], PROBE_ORIGIN, 403, &HashMap::new())]);
+    assert!(strict.findings.is_empty());
+    assert_eq!(RiskClassifier::classify_cors_issue(false, false, false, false, false, Some(&strict)).final_severity, VulnerabilitySeverity::Low);
+}
+
+/// Reflects the Origin and the requested method and headers of every preflight
+async fn reflecting_server() -> String {
+    common::serve(|req| {
+        let header = |name| req.header(name).unwrap_or_default().to_string();
+        Response::new(204)
+            .header("Access-Control-Allow-Origin", header("origin"))
+            .header("Access-Control-Allow-Credentials", "true")
+            .header("Access-Control-Allow-Methods", header("access-control-request-method"))
+            .header("Access-Control-Allow-Headers", header("access-control-request-headers"))
+    }).await
+}
+
+#[tokio::test]
+async fn test_check_preflight() {
+    let base = reflecting_server().await;
+    let analysis = cors_checker::check_preflight(&reqwest::Client::new(), &format!("{}/api/users/1", base)).await;
+    assert_eq!(analysis.preflights.len(), cors_checker::PREFLIGHTS.len());
+    assert!(analysis.preflights.iter().all(|p| p.accepted));
+    assert!(analysis.arbitrary_origin && analysis.null_origin && analysis.credentials && analysis.any_header);
+    assert_eq!(analysis.dangerous_methods, vec!["PUT", "DELETE", "PATCH"]);
+}
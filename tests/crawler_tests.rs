@@ -0,0 +1,49 @@
+use api_hunter::gather::crawler::{extract, same_domain, CrawlResult, FormTarget};
+use url::Url;
+
+#[test]
+fn extracts_links_forms_and_inline_calls() {
+    let page = Url::parse("https://example.com/account/").unwrap();
+    let html = r#"
+        <html><body>
+          <a href="/about#team">About</a>
+          <a href="settings?tab=2">Settings</a>
+          <a href="javascript:void(0)">x</a>
+          <a href="mailto:a@example.com">mail</a>
+          <form action="/api/login" method="post"><input name="user"><input name="pass" type="password"></form>
+          <form><input name="q"></form>
+          <script>
+            fetch("/api/v1/profile").then(r => r.json());
+            axios.post('/api/v1/orders', {});
+            $.getJSON("/api/legacy/stats");
+            xhr.open("GET", "/api/v1/notifications");
+          </script>
+          <script src="/static/app.js"></script>
+        </body></html>"#;
+
+    let found = extract(html, &page);
+    assert_eq!(found.links, vec![
+        "https://example.com/about".to_string(),
+        "https://example.com/account/settings?tab=2".to_string(),
+    ]);
+    assert_eq!(found.forms[0], FormTarget {
+        action: "https://example.com/api/login".to_string(),
+        method: "POST".to_string(),
+        inputs: vec!["user".to_string(), "pass".to_string()],
+        page: page.to_string(),
+    });
+    assert_eq!(found.forms[1].action, "https://example.com/account/");
+    assert_eq!(found.fetch_targets.len(), 4);
+    assert!(found.fetch_targets.contains(&"https://example.com/api/legacy/stats".to_string()));
+
+    let result = CrawlResult { forms: found.forms.clone(), ..Default::default() };
+    assert!(result.candidate_urls().contains(&"https://example.com/api/login?user=&pass=".to_string()));
+}
+
+#[test]
+fn stays_on_domain() {
+    assert!(same_domain(&Url::parse("https://www.example.com/x").unwrap(), "example.com"));
+    assert!(same_domain(&Url::parse("https://api.example.com/x").unwrap(), "example.com"));
+    assert!(!same_domain(&Url::parse("https://example.com.evil.io/x").unwrap(), "example.com"));
+    assert!(!same_domain(&Url::parse("https://cdn.other.com/x").unwrap(), "example.com"));
+}
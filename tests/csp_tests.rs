@@ -0,0 +1,99 @@
+use api_hunter::analyze::csp::{self, CspAnalysis, CspCheck, CspPolicy};
+use api_hunter::analyze::security_headers::SecurityHeaderAnalysis;
+use api_hunter::output::RawEvent;
+use std::collections::HashMap;
+
+fn checks(policy: &str) -> Vec<CspCheck> {
+    csp::check_policy(&CspPolicy::parse(policy), false).iter().map(|i| i.check).collect()
+}
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_parse_and_fallback() {
+    let policy = CspPolicy::parse("Default-Src 'self'; script-src 'self' https://cdn.example.com;; img-src *; script-src 'unsafe-inline'");
+    assert_eq!(policy.get("default-src"), Some(&["'self'".to_string()][..]));
+    // The first occurrence wins
+    assert_eq!(policy.effective("script-src").map(|(d, s)| (d, s.len())), Some(("script-src", 2)));
+    assert_eq!(policy.effective("object-src").map(|(d, _)| d), Some("default-src"));
+    assert_eq!(policy.effective("script-src-elem").map(|(d, _)| d), Some("script-src"));
+}
+
+#[test]
+fn test_script_source_checks() {
+    let strict = "script-src 'nonce-r4nd0m' 'strict-dynamic'; object-src 'none'; base-uri 'none'; frame-ancestors 'self'";
+    assert!(checks(strict).is_empty());
+    assert_eq!(csp::grade(&csp::check_policy(&CspPolicy::parse(strict), false)), 'A');
+
+    // A nonce disables 'unsafe-inline', 'strict-dynamic' the host and scheme sources
+    assert!(checks("script-src 'nonce-abc' 'unsafe-inline' 'strict-dynamic' https: *; object-src 'none'; base-uri 'self'; frame-ancestors 'none'").is_empty());
+
+    assert_eq!(
+        checks("default-src 'self' 'unsafe-inline' 'unsafe-eval' * data: http://cdn.example.com; base-uri 'self'; frame-ancestors 'none'"),
+        vec![CspCheck::UnsafeInline, CspCheck::UnsafeEval, CspCheck::WildcardSource, CspCheck::SchemeSource, CspCheck::InsecureSource, CspCheck::MissingObjectSrc],
+    );
+    assert_eq!(checks("img-src 'self'"), vec![CspCheck::NoScriptRestriction, CspCheck::MissingObjectSrc, CspCheck::MissingBaseUri, CspCheck::MissingFrameAncestors]);
+}
+
+#[test]
+fn test_bypassable_hosts() {
+    assert_eq!(csp::bypassable("ajax.googleapis.com"), Some("ajax.googleapis.com"));
+    assert_eq!(csp::bypassable("maps.googleapis.com"), Some("*.googleapis.com"));
+    // A wildcard in the policy covering a bypassable host
+    assert_eq!(csp::bypassable("*.google.com"), Some("www.google.com"));
+    assert!(csp::bypassable("cdn.example.com").is_none());
+    assert!(csp::bypassable("evilgoogle.com").is_none());
+
+    let found = csp::check_policy(&CspPolicy::parse("script-src 'self' https://www.google.com/recaptcha/ cdn.example.com; object-src 'none'; base-uri 'none'; frame-ancestors 'none'"), false);
+    assert_eq!(found.iter().map(|i| i.check).collect::<Vec<_>>(), vec![CspCheck::BypassableHost]);
+    assert_eq!(csp::grade(&found), 'D');
+}
+
+#[test]
+fn test_graded_analysis() {
+    let missing = CspAnalysis::analyze(&headers(&[]));
+    assert_eq!((missing.grade, missing.issues[0].check), ('F', CspCheck::Missing));
+
+    let report_only = CspAnalysis::analyze(&headers(&[("content-security-policy-report-only", "script-src 'self'; object-src 'none'; base-uri 'none'; frame-ancestors 'none'")]));
+    assert!(report_only.report_only);
+    assert_eq!(report_only.grade, 'D');
+
+    let eval = CspAnalysis::analyze(&headers(&[("content-security-policy", "script-src 'self' 'unsafe-eval'; object-src 'none'")]));
+    assert_eq!((eval.grade, eval.severity()), ('C', "MEDIUM"));
+    let lows = CspAnalysis::analyze(&headers(&[("content-security-policy", "script-src 'self'; object-src 'none'")]));
+    assert_eq!((lows.grade, lows.severity()), ('B', "LOW"));
+
+    // The header analysis reports a weak policy instead of "present"
+    let sec = SecurityHeaderAnalysis::analyze(&headers(&[("content-security-policy", "default-src * 'unsafe-inline'")]));
+    assert!(sec.has_csp);
+    assert_eq!(sec.csp.grade, 'D');
+    assert!(sec.findings.iter().any(|f| f.starts_with("Weak CSP (grade D)")));
+}
+
+#[test]
+fn test_events_grouped_by_policy() {
+    let event = |url: &str, ct: &str, csp: Option<&str>| {
+        let mut ev: RawEvent = serde_json::from_str(&format!(
+            r#"{{"orig_url":"{0}","final_url":"{0}","status":200,"content_type":"{1}","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1}}"#,
+            url, ct
+        )).unwrap();
+        if let Some(p) = csp {
+            ev.headers.insert("content-security-policy".to_string(), p.to_string());
+        }
+        ev
+    };
+    let results = vec![
+        event("https://www.example.com/", "text/html", Some("script-src 'self' 'unsafe-inline'")),
+        event("https://www.example.com/about", "text/html; charset=utf-8", Some("script-src 'self' 'unsafe-inline'")),
+        event("https://www.example.com/api/users", "application/json", None),
+        event("https://shop.example.com/", "text/html", None),
+        event("https://shop.example.com/cart", "text/html", None),
+    ];
+    let reports = csp::analyze_events(&results);
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].urls, vec!["https://www.example.com/", "https://www.example.com/about"]);
+    assert_eq!(reports[0].analysis.grade, 'D');
+    assert_eq!((reports[1].analysis.grade, reports[1].urls.len()), ('F', 2));
+}
@@ -0,0 +1,39 @@
+use api_hunter::identify;
+use common::Response;
+
+mod common;
+
+#[test]
+fn config_file_headers() {
+    let path = std::env::temp_dir().join(format!("apihunter-headers-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{"headers": ["Cookie: session=abc"], "identify": ["X-Bug-Bounty: hunter"]}"#).unwrap();
+    let config = api_hunter::config::Config::load(Some(&path)).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(config.headers, vec!["Cookie: session=abc".to_string()]);
+    assert_eq!(config.identify, vec!["X-Bug-Bounty: hunter".to_string()]);
+}
+
+// Header state is process-global, so everything that configures it lives in one test
+#[tokio::test]
+async fn custom_headers_are_sent_but_not_audited() {
+    identify::configure_custom(&["Cookie: session=abc".to_string(), "X-Bug-Bounty: other".to_string()]).unwrap();
+    assert!(identify::has_headers());
+    assert!(!identify::is_configured());
+    identify::configure(&["X-Bug-Bounty:hunter".to_string()]).unwrap();
+    assert!(identify::configure_custom(&["no-colon".to_string()]).is_err());
+
+    let (base, seen) = common::serve_recording(|_| Response::new(204)).await;
+
+    let client = identify::apply(reqwest::Client::builder(), "probe").build().unwrap();
+    client.get(format!("{}/", base)).send().await.unwrap();
+    let request = seen.lock()[0].clone();
+    assert_eq!(request.header("cookie"), Some("session=abc"));
+    // Identification headers win over custom ones of the same name
+    let bounty: Vec<&str> = request.headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case("x-bug-bounty")).map(|(_, v)| v.as_str()).collect();
+    assert_eq!(bounty, ["hunter"]);
+
+    let audit = identify::audit();
+    assert_eq!(audit.headers, vec!["x-bug-bounty: hunter".to_string()]);
+    assert!(audit.verified);
+    assert!(audit.clients.iter().any(|c| c.component == "probe" && c.headers.contains(&"cookie".to_string())));
+}
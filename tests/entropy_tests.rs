@@ -0,0 +1,41 @@
+use api_hunter::gather::entropy::{find_secrets, shannon_entropy, DEFAULT_THRESHOLD};
+use api_hunter::gather::js_deep_analyzer::{JsDeepAnalyzer, SecretType};
+
+#[test]
+fn entropy_of_known_strings() {
+    assert_eq!(shannon_entropy(""), 0.0);
+    assert_eq!(shannon_entropy("aaaa"), 0.0);
+    assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn finds_unknown_provider_tokens_with_context() {
+    let js = r#"const cfg={acmeApiKey:"q8Zt3LmX0vN7pRk2Wy5HcJ9dFs4GbA1e",theme:"dark-mode-2024",buildHash:"Xq9Lm2Zt8Vn4Rk7Wy1HcJ5dFs3GbA0eP"};
+        window.sentryDsn = "9f3c2a7be1d84f06a5c39e2b7d10f4a8";
+        const routeName = "userProfileSettingsPage12";"#;
+    let hits = find_secrets(js, DEFAULT_THRESHOLD);
+    let values: Vec<&str> = hits.iter().map(|h| h.value.as_str()).collect();
+    // Random literal next to a credential name; hex gets the lower threshold
+    assert!(values.contains(&"q8Zt3LmX0vN7pRk2Wy5HcJ9dFs4GbA1e"));
+    assert!(values.contains(&"9f3c2a7be1d84f06a5c39e2b7d10f4a8"));
+    // Random but not named like a credential
+    assert!(!values.contains(&"Xq9Lm2Zt8Vn4Rk7Wy1HcJ5dFs3GbA0eP"));
+    assert!(!values.iter().any(|v| v.contains("userProfile")));
+
+    // Raising the threshold filters everything
+    assert!(find_secrets(js, 8.0).is_empty());
+}
+
+#[test]
+fn entropy_hits_reported_as_generic_secrets() {
+    let js = r#"var t={partnerToken:"Rk2Wy5HcJ9dFs4GbA1eq8Zt3LmX0vN7p",apiKey:"Qw7ErT9yUi3OpAs5DfGh2J"};"#;
+    let secrets = JsDeepAnalyzer::extract_secrets(js, "app.js");
+    let generic: Vec<_> = secrets.iter().filter(|s| s.secret_type == SecretType::Generic).collect();
+    assert_eq!(generic.len(), 1);
+    assert_eq!(generic[0].value, "Rk2Wy5HcJ9dFs4GbA1eq8Zt3LmX0vN7p");
+    assert!(generic[0].entropy.is_some());
+    // The apiKey literal keeps its specific type
+    assert!(secrets.iter().filter(|s| s.value == "Qw7ErT9yUi3OpAs5DfGh2J").all(|s| s.secret_type == SecretType::ApiKey));
+
+    assert!(JsDeepAnalyzer::extract_secrets_with_entropy(js, "app.js", None).iter().all(|s| s.secret_type != SecretType::Generic));
+}
@@ -0,0 +1,83 @@
+use api_hunter::analyze::error_disclosure::{self, DisclosureKind, Framework};
+use api_hunter::output::RawEvent;
+use serde_json::json;
+
+#[test]
+fn test_debug_pages() {
+    let werkzeug = r#"<title>ZeroDivisionError // Werkzeug Debugger</title><div class="footer">Brought to you by DON'T PANIC, your friendly Werkzeug powered traceback interpreter.</div>
+<h4>File <cite class="filename">"/home/deploy/app/views.py"</cite></h4>"#;
+    let d = error_disclosure::classify(werkzeug).unwrap();
+    assert_eq!((d.framework, d.kind, d.severity.as_str()), (Framework::WerkzeugDebugger, DisclosureKind::DebugConsole, "CRITICAL"));
+    assert_eq!(d.paths, vec!["/home/deploy/app/views.py"]);
+
+    let django = r#"<h1>OperationalError at /api/users/</h1><table><tr><th>Django Version:</th><td>4.2.1</td></tr>
+<tr><th>Python Version:</th><td>3.11.4</td></tr><tr><th>Exception Location:</th><td>/usr/lib/python3/dist-packages/django/db/backends/utils.py, line 89</td></tr></table>
+<tr><td>SECRET_KEY</td><td class="code"><pre>'********************'</pre></td></tr>
+<p>You're seeing this error because you have <code>DEBUG = True</code> in your Django settings file.</p>"#;
+    let d = error_disclosure::classify(django).unwrap();
+    assert_eq!((d.framework, d.kind, d.severity.as_str()), (Framework::Django, DisclosureKind::DebugPage, "HIGH"));
+    assert_eq!(d.versions, vec!["Django 4.2.1", "Python 3.11.4"]);
+    assert_eq!(d.paths, vec!["/usr/lib/python3/dist-packages/django/db/backends/utils.py"]);
+    // Django masks the secret key
+    assert!(d.credentials.is_empty());
+
+    let whoops = "<h1>Whoops! There was an error.</h1><td>DB_PASSWORD</td><td>hunter2secret</td><td>APP_KEY</td><td>base64:Zm9vYmFyYmF6</td> Laravel v10.13.5 PHP 8.2.7";
+    let d = error_disclosure::classify(whoops).unwrap();
+    assert_eq!((d.framework, d.severity.as_str()), (Framework::Laravel, "CRITICAL"));
+    assert_eq!(d.credentials, vec!["DB_PASSWORD", "APP_KEY"]);
+    assert_eq!(d.versions, vec!["Laravel 10.13.5", "PHP 8.2.7"]);
+}
+
+#[test]
+fn test_stack_traces_and_error_pages() {
+    let spring = "<html><body><h1>Whitelabel Error Page</h1><p>This application has no explicit mapping for /error</p></body></html>";
+    let d = error_disclosure::classify(spring).unwrap();
+    assert_eq!((d.framework, d.kind, d.severity.as_str()), (Framework::Spring, DisclosureKind::ErrorPage, "LOW"));
+
+    let express = "<pre>TypeError: Cannot read properties of undefined (reading 'id')<br> &nbsp; &nbsp;at getUser (/srv/api/routes/users.js:14:22)<br> &nbsp; &nbsp;at Layer.handle [as handle_request] (/srv/api/node_modules/express/lib/router/layer.js:95:5)</pre>";
+    let d = error_disclosure::classify(express).unwrap();
+    assert_eq!((d.framework, d.kind, d.severity.as_str()), (Framework::Express, DisclosureKind::StackTrace, "MEDIUM"));
+    assert_eq!(d.paths[0], "/srv/api/routes/users.js");
+
+    let python = "Traceback (most recent call last):\n  File \"<stdin>\", line 1, in <module>\nKeyError: 'id'";
+    let d = error_disclosure::classify(python).unwrap();
+    assert_eq!((d.framework, d.severity.as_str()), (Framework::Python, "LOW"));
+
+    let php = "<br />\n<b>Fatal error</b>:  Uncaught Error: Call to undefined function foo() in /var/www/html/index.php:3 in <b>/var/www/html/index.php</b> on line <b>3</b><br />";
+    assert_eq!(error_disclosure::classify(php).unwrap().framework, Framework::Php);
+
+    let go = "panic: runtime error: invalid memory address or nil pointer dereference\ngoroutine 1 [running]:\nmain.main()\n\t/app/main.go:12 +0x1d";
+    assert_eq!(error_disclosure::classify(go).unwrap().framework, Framework::Go);
+
+    assert!(error_disclosure::classify(r#"{"error":"not found"}"#).is_none());
+    // "at " in prose is not a trace
+    assert!(error_disclosure::classify("Meet us at the conference at 10am").is_none());
+}
+
+#[test]
+fn test_json_errors() {
+    let laravel = json!({
+        "message": "SQLSTATE[HY000] [2002] Connection refused",
+        "exception": "Illuminate\\Database\\QueryException",
+        "file": "/var/www/app/vendor/laravel/framework/src/Illuminate/Database/Connection.php",
+        "line": 760,
+        "trace": [{"file": "/var/www/app/app/Http/Controllers/UserController.php", "line": 21}]
+    });
+    let d = error_disclosure::classify_json(&laravel).unwrap();
+    assert_eq!((d.framework, d.kind, d.severity.as_str()), (Framework::Laravel, DisclosureKind::DebugPage, "HIGH"));
+    assert_eq!(d.paths.len(), 2);
+
+    let spring = json!({
+        "timestamp": "2026-10-17T10:00:00.000+00:00", "status": 500, "error": "Internal Server Error", "path": "/api/orders",
+        "trace": "java.lang.NullPointerException\n\tat com.shop.OrderService.find(OrderService.java:42)\n"
+    });
+    let d = error_disclosure::classify_json(&spring).unwrap();
+    assert_eq!((d.framework, d.kind), (Framework::Spring, DisclosureKind::StackTrace));
+
+    let mut ev: RawEvent = serde_json::from_str(
+        r#"{"orig_url":"https://a.example/x","final_url":"https://a.example/x","status":500,"content_type":"text/html","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":{"_sample":"<h1>Whitelabel Error Page</h1>"},"score":1}"#,
+    ).unwrap();
+    assert_eq!(error_disclosure::classify_event(&ev).unwrap().framework, Framework::Spring);
+    ev.json_sample = Some(json!({"error": "bad request"}));
+    assert!(error_disclosure::classify_event(&ev).is_none());
+}
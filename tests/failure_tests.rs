@@ -0,0 +1,48 @@
+use api_hunter::probe::failure::{classify, classify_message, count_by_kind, FailureKind, ProbeError, ProbeFailure};
+
+#[test]
+fn test_classify_messages() {
+    assert_eq!(classify_message("dns error: failed to lookup address information: Name or service not known"), FailureKind::Dns);
+    assert_eq!(classify_message("tcp connect error: Connection refused (os error 111)"), FailureKind::ConnectionRefused);
+    assert_eq!(classify_message("connection closed before message completed"), FailureKind::ConnectionReset);
+    assert_eq!(classify_message("invalid peer certificate: UnknownIssuer"), FailureKind::Tls);
+    assert_eq!(classify_message("something else"), FailureKind::Other);
+}
+
+#[test]
+fn test_classify_io_errors() {
+    let refused = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "nope");
+    assert_eq!(classify(&refused), FailureKind::ConnectionRefused);
+    let reset = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset by peer");
+    assert_eq!(classify(&reset), FailureKind::ConnectionReset);
+}
+
+#[test]
+fn test_transient_kinds() {
+    assert!(FailureKind::Timeout.is_transient());
+    assert!(FailureKind::ConnectionReset.is_transient());
+    assert!(!FailureKind::Dns.is_transient());
+    assert!(!FailureKind::Tls.is_transient());
+    assert!(FailureKind::ConnectionReset.suggests_blocking());
+    assert!(!FailureKind::ConnectionRefused.suggests_blocking());
+}
+
+#[tokio::test]
+async fn test_refused_port_is_not_retried() {
+    // Bind and drop to get a port nothing listens on
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let url = format!("http://127.0.0.1:{}/api/users", port);
+    let client = reqwest::Client::new();
+
+    let opts = api_hunter::probe::http_probe::ProbeOptions { backoff_initial_ms: 10, backoff_max_ms: 50, ..api_hunter::probe::http_probe::ProbeOptions::new(2, 3) };
+    let err = api_hunter::probe::http_probe::probe_url(&client, &url, None, &opts).await.unwrap_err();
+    let failure = ProbeFailure::from_error(&url, &err);
+    assert_eq!(failure.kind, FailureKind::ConnectionRefused);
+    assert_eq!(failure.attempts, 1);
+    assert_eq!(failure.host, "127.0.0.1");
+    assert!(err.downcast_ref::<ProbeError>().is_some());
+
+    let line = serde_json::to_string(&failure).unwrap();
+    assert!(line.contains(r#""kind":"connection_refused""#));
+    assert_eq!(count_by_kind(&[failure])[&FailureKind::ConnectionRefused], 1);
+}
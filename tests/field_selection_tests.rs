@@ -0,0 +1,39 @@
+use api_hunter::fuzz::field_selection::{is_search_endpoint, new_sensitive_keys, schema_leak, with_param};
+use serde_json::json;
+
+#[test]
+fn detects_search_endpoints() {
+    assert!(is_search_endpoint("https://api.example.com/users?q=bob"));
+    assert!(is_search_endpoint("https://api.example.com/users?sort=name&limit=10"));
+    assert!(is_search_endpoint("https://api.example.com/v1/products/search"));
+    assert!(!is_search_endpoint("https://api.example.com/users/42"));
+    assert!(!is_search_endpoint("https://api.example.com/login?next=/home"));
+
+    assert_eq!(
+        with_param("https://api.example.com/users?q=bob&fields=id", "fields", "*,password"),
+        "https://api.example.com/users?q=bob&fields=*%2Cpassword"
+    );
+}
+
+#[test]
+fn finds_newly_exposed_sensitive_fields() {
+    let baseline = json!({"data": [{"id": 1, "name": "bob"}]});
+    let response = json!({"data": [{"id": 1, "name": "bob", "password_hash": "$2b$10$abc", "email": null, "nickname": "b"}]});
+    assert_eq!(new_sensitive_keys(&baseline, &response), vec!["password_hash".to_string()]);
+
+    // Fields the default projection already returns are not an exposure
+    let baseline = json!({"data": [{"id": 1, "email": "bob@example.com"}]});
+    let response = json!({"data": [{"id": 1, "email": "bob@example.com"}]});
+    assert!(new_sensitive_keys(&baseline, &response).is_empty());
+}
+
+#[test]
+fn extracts_schema_from_errors() {
+    let leak = schema_leak("SQLSTATE[42S22]: Column not found: 1054 Unknown column 'users.password_hash' in 'order clause'").unwrap();
+    assert_eq!(leak.fields, vec!["users.password_hash".to_string()]);
+
+    let leak = schema_leak(r#"{"error":"invalid sort key. Allowed fields: id, name, created_at"}"#).unwrap();
+    assert!(leak.fields.contains(&"created_at".to_string()));
+
+    assert!(schema_leak(r#"{"data":[{"id":1}]}"#).is_none());
+}
@@ -0,0 +1,43 @@
+use api_hunter::fuzz::flows::{json_path, render, Condition, Flow};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn parses_flow_and_validates_identities() {
+    let yaml = r#"
+name: order-idor
+base_url: https://api.example.com
+identities:
+  alice: { headers: { Authorization: "Bearer AAA" } }
+steps:
+  - name: create
+    as: alice
+    method: POST
+    path: /orders
+    json: { item: 42 }
+    capture: { order_id: "$.id" }
+    expect: { status: [201] }
+"#;
+    let flow = Flow::parse(yaml).unwrap();
+    assert_eq!(flow.steps.len(), 1);
+    assert_eq!(flow.steps[0].identity.as_deref(), Some("alice"));
+    assert_eq!(flow.steps[0].capture["order_id"], "$.id");
+
+    let bad = yaml.replace("as: alice", "as: mallory");
+    assert!(Flow::parse(&bad).is_err());
+}
+
+#[test]
+fn renders_variables_and_json_paths() {
+    let mut vars = HashMap::new();
+    vars.insert("order_id".to_string(), "17".to_string());
+    assert_eq!(render("/orders/{{order_id}}/{{ missing }}", &vars), "/orders/17/{{ missing }}");
+
+    let body = json!({"data": {"items": [{"id": 5}, {"id": 9}]}});
+    assert_eq!(json_path(&body, "$.data.items[1].id"), Some(&json!(9)));
+    assert_eq!(json_path(&body, "$.data.nope"), None);
+
+    let cond = Condition { status: vec![200], body_contains: vec!["paid".into()], body_not_contains: vec![] };
+    assert!(cond.matches(200, "{\"state\":\"paid\"}"));
+    assert!(!cond.matches(403, "paid"));
+}
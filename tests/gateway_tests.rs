@@ -0,0 +1,129 @@
+use api_hunter::analyze::gateway::{self, Gateway, GatewayAnalyzer, GatewayCheck, TYK_DEFAULT_SECRET};
+use api_hunter::output::RawEvent;
+use common::Response;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+mod common;
+
+fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_detect() {
+    let (g, version, evidence) = gateway::detect("https://api.example/x", &headers(&[("via", "kong/3.4.2"), ("x-kong-proxy-latency", "1")]), "").unwrap();
+    assert_eq!((g, version.as_deref()), (Gateway::Kong, Some("3.4.2")));
+    assert_eq!(evidence, vec!["header x-kong-proxy-latency", "via: kong/3.4.2"]);
+    let (g, ..) = gateway::detect("https://api.example/", &headers(&[]), r#"{"message":"no Route matched with those values"}"#).unwrap();
+    assert_eq!(g, Gateway::Kong);
+
+    let (g, _, evidence) = gateway::detect("https://abc123.execute-api.eu-west-1.amazonaws.com/prod/users", &headers(&[("x-amzn-requestid", "r1")]), "").unwrap();
+    assert_eq!(g, Gateway::AwsApiGateway);
+    assert_eq!(evidence, vec!["host abc123.execute-api.eu-west-1.amazonaws.com"]);
+    let (g, ..) = gateway::detect("https://api.example/", &headers(&[("x-amzn-requestid", "r1")]), r#"{"message":"Missing Authentication Token"}"#).unwrap();
+    assert_eq!(g, Gateway::AwsApiGateway);
+    // Other AWS services send a request id too
+    assert!(gateway::detect("https://api.example/", &headers(&[("x-amzn-requestid", "r1")]), "{}").is_none());
+
+    let fault = r#"{"fault":{"faultstring":"Failed to resolve API Key variable request.queryparam.apikey","detail":{"errorcode":"steps.oauth.v2.FailedToResolveAPIKey"}}}"#;
+    assert_eq!(gateway::detect("https://api.example/", &headers(&[]), fault).unwrap().0, Gateway::Apigee);
+    assert_eq!(gateway::detect("https://api.example/", &headers(&[]), r#"{"error": "Authorization field missing"}"#).unwrap().0, Gateway::Tyk);
+    assert!(gateway::detect("https://api.example/", &headers(&[("server", "nginx")]), r#"{"error":"unauthorized"}"#).is_none());
+}
+
+#[test]
+fn test_fingerprint_and_kong_routes() {
+    let event = |url: &str, sample: serde_json::Value, pairs: &[(&str, &str)]| {
+        let mut ev: RawEvent = serde_json::from_str(&format!(
+            r#"{{"orig_url":"{0}","final_url":"{0}","status":401,"content_type":"application/json","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1}}"#,
+            url
+        )).unwrap();
+        ev.json_sample = Some(sample);
+        ev.headers = headers(pairs);
+        ev
+    };
+    let fault = json!({"fault": {"faultstring": "Invalid access token", "detail": {"errorcode": "steps.oauth.v2.InvalidAccessToken"}}});
+    let results = vec![
+        event("https://abc.execute-api.us-east-1.amazonaws.com/prod/users", json!({"message": "Forbidden"}), &[("x-amzn-errortype", "ForbiddenException")]),
+        event("https://abc.execute-api.us-east-1.amazonaws.com/prod/orders", json!({}), &[("x-amz-apigw-id", "A1")]),
+        event("https://edge.example/v1/orders", fault, &[]),
+        event("https://plain.example/", json!({"ok": true}), &[]),
+    ];
+    let reports = gateway::fingerprint(&results, 10);
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].gateway, Gateway::AwsApiGateway);
+    assert_eq!(reports[0].stages, vec!["prod"]);
+    assert_eq!(reports[0].evidence, vec!["header x-amzn-errortype", "host abc.execute-api.us-east-1.amazonaws.com", "header x-amz-apigw-id"]);
+    assert_eq!(reports[1].gateway, Gateway::Apigee);
+    assert_eq!(reports[1].findings[0].check, GatewayCheck::FaultDisclosure);
+    assert!(reports[1].findings[0].detail.contains("steps.oauth.v2.InvalidAccessToken"));
+
+    assert_eq!(gateway::kong_route(&json!({"name": "users", "paths": ["/users"], "methods": ["GET", "POST"]})).as_deref(), Some("users GET,POST /users"));
+    assert_eq!(gateway::kong_route(&json!({"name": "catchall", "paths": null, "hosts": null, "methods": null})), None);
+    assert_eq!(gateway::kong_admin_version(&json!({"tagline": "Welcome to kong", "version": "3.4.2"})).as_deref(), Some("3.4.2"));
+    assert!(gateway::kong_admin_version(&json!({"message": "ok"})).is_none());
+}
+
+/// Answers like a gateway: Tyk with its control API and default secret on port, Kong's Admin API behind
+/// `/admin-api`, or AWS API Gateway with `prod` and `dev` stages
+async fn gateway_server(kind: Gateway) -> String {
+    common::serve(move |req| {
+        let secret = req.header("x-tyk-authorization") == Some(TYK_DEFAULT_SECRET);
+        match (kind, req.path()) {
+            (Gateway::Tyk, "/hello") => Response::json(200, r#"{"status":"pass","version":"v5.2.1","description":"Tyk GW"}"#),
+            (Gateway::Tyk, "/tyk/apis") if secret => Response::json(200, r#"[{"name":"Billing","proxy":{"listen_path":"/billing/","target_url":"http://billing.internal:8080"}}]"#),
+            (Gateway::Tyk, "/tyk/apis") => Response::json(403, r#"{"status":"error","message":"Attempted administrative access with invalid or missing key!"}"#),
+            (Gateway::Kong, "/admin-api/") => Response::json(200, r#"{"tagline":"Welcome to kong","version":"3.6.0"}"#),
+            (Gateway::Kong, "/admin-api/routes") => Response::json(200, r#"{"data":[{"name":"users","paths":["/users"]},{"name":"legacy","id":"r2"}],"next":null}"#),
+            (Gateway::AwsApiGateway, "/prod/" | "/dev/") => Response::json(403, r#"{"message":"Missing Authentication Token"}"#)
+                .header("x-amzn-errortype", "MissingAuthenticationTokenException"),
+            (Gateway::AwsApiGateway, _) => Response::json(403, r#"{"message":"Forbidden"}"#).header("x-amzn-errortype", "ForbiddenException"),
+            _ => Response::json(404, r#"{"message":"no Route matched with those values"}"#),
+        }
+    }).await
+}
+
+fn report(gateway: Gateway, origin: String, stages: &[&str]) -> gateway::GatewayReport {
+    gateway::GatewayReport {
+        origin,
+        gateway,
+        version: None,
+        evidence: Vec::new(),
+        admin_url: None,
+        routes: Vec::new(),
+        stages: stages.iter().map(|s| s.to_string()).collect(),
+        findings: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_gateway_checks() {
+    let analyzer = GatewayAnalyzer::new(Duration::from_secs(5)).unwrap();
+
+    let base = gateway_server(Gateway::Tyk).await;
+    let mut tyk = report(Gateway::Tyk, base.clone(), &[]);
+    analyzer.check(&mut tyk).await;
+    assert_eq!(tyk.version.as_deref(), Some("v5.2.1"));
+    let checks: Vec<_> = tyk.findings.iter().map(|f| (f.check, f.severity.as_str())).collect();
+    assert_eq!(checks, vec![(GatewayCheck::HealthEndpoint, "LOW"), (GatewayCheck::AdminApiExposed, "CRITICAL")]);
+    assert_eq!(tyk.routes, vec!["Billing /billing/ -> http://billing.internal:8080"]);
+
+    let base = gateway_server(Gateway::Kong).await;
+    let mut kong = report(Gateway::Kong, base.clone(), &[]);
+    analyzer.check(&mut kong).await;
+    assert_eq!(kong.admin_url, Some(format!("{}/admin-api", base)));
+    assert_eq!(kong.version.as_deref(), Some("3.6.0"));
+    assert_eq!(kong.routes, vec!["users * /users"]);
+    let checks: Vec<_> = kong.findings.iter().map(|f| (f.check, f.severity.as_str())).collect();
+    assert_eq!(checks, vec![(GatewayCheck::AdminApiExposed, "CRITICAL"), (GatewayCheck::CatchAllRoute, "MEDIUM")]);
+    assert_eq!(kong.severity(), Some("CRITICAL"));
+
+    let base = gateway_server(Gateway::AwsApiGateway).await;
+    let mut aws = report(Gateway::AwsApiGateway, base.clone(), &["prod"]);
+    analyzer.check(&mut aws).await;
+    assert_eq!(aws.stages, vec!["prod", "dev"]);
+    assert_eq!(aws.findings.len(), 1);
+    assert_eq!((aws.findings[0].check, aws.findings[0].severity.as_str()), (GatewayCheck::StageExposed, "MEDIUM"));
+}
@@ -0,0 +1,44 @@
+use api_hunter::output::graph::{AssetGraph, NodeKind};
+
+#[test]
+fn builds_domain_host_endpoint_hierarchy() {
+    let mut graph = AssetGraph::new();
+    graph.add_endpoint("https://api.example.com/v1/users?id=1&sort=name", "example.com", Some(200));
+    graph.add_endpoint("https://api.example.com/v1/users?id=2", "example.com", Some(200));
+    graph.add_endpoint("https://cdn.thirdparty.co.uk/app.js", "example.com", None);
+    graph.add_finding("https://api.example.com/v1/users", "example.com", "high", "Missing auth");
+
+    let count = |kind| graph.nodes().iter().filter(|n| n.kind == kind).count();
+    assert_eq!(count(NodeKind::Domain), 2);
+    assert_eq!(count(NodeKind::Host), 2);
+    assert_eq!(count(NodeKind::Endpoint), 2);
+    assert_eq!(count(NodeKind::Parameter), 2);
+    assert_eq!(count(NodeKind::Finding), 1);
+    assert!(graph.nodes().iter().any(|n| n.id == "domain:thirdparty.co.uk"));
+    assert!(graph.edges().contains(&("domain:example.com".to_string(), "host:api.example.com".to_string())));
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph assets {"));
+    assert!(dot.contains("\"host:api.example.com\" -> \"endpoint:https://api.example.com/v1/users\""));
+}
+
+#[test]
+fn graphml_round_trips() {
+    let mut graph = AssetGraph::new();
+    graph.add_endpoint("https://api.example.com/search?q=<x>&a=\"b\"", "example.com", Some(200));
+    graph.add_finding("https://api.example.com/search", "example.com", "critical", "Leaks password & token");
+
+    let xml = graph.to_graphml();
+    let loaded = AssetGraph::from_graphml(&xml).unwrap();
+    assert_eq!(loaded.nodes().len(), graph.nodes().len());
+    assert_eq!(loaded.edges().len(), graph.edges().len());
+    let finding = loaded.nodes().iter().find(|n| n.kind == NodeKind::Finding).unwrap();
+    assert_eq!(finding.label, "Leaks password & token");
+    assert_eq!(finding.attrs.get("severity").map(String::as_str), Some("CRITICAL"));
+
+    // Reloaded graphs keep deduplicating
+    let mut merged = loaded;
+    merged.add_endpoint("https://api.example.com/search?q=1", "example.com", Some(200));
+    assert_eq!(merged.nodes().len(), graph.nodes().len());
+    assert!(AssetGraph::from_graphml("<html></html>").is_err());
+}
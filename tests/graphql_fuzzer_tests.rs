@@ -0,0 +1,159 @@
+use api_hunter::graphql::fuzzer::{classify_access, field_query, inconsistent_authorization, literal, Access, FieldAccess, SchemaFuzzer};
+use api_hunter::graphql::tester::GraphQLSchema;
+use api_hunter::http_client::HttpClient;
+use common::Response;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+mod common;
+
+fn named(kind: &str, name: &str) -> Value {
+    json!({ "kind": kind, "name": name, "ofType": null })
+}
+
+fn non_null(inner: Value) -> Value {
+    json!({ "kind": "NON_NULL", "name": null, "ofType": inner })
+}
+
+fn arg(name: &str, ty: Value) -> Value {
+    json!({ "name": name, "type": ty })
+}
+
+fn field(name: &str, ty: Value, args: Vec<Value>) -> Value {
+    json!({ "name": name, "type": ty, "args": args })
+}
+
+/// Query { user(id: ID!), account(id: ID!), search(q: String!), orders(filter: OrderFilter!) } over User
+fn introspection() -> Value {
+    json!({ "data": { "__schema": {
+        "queryType": { "name": "Query" },
+        "mutationType": null,
+        "types": [
+            { "kind": "OBJECT", "name": "Query", "fields": [
+                field("user", named("OBJECT", "User"), vec![arg("id", non_null(named("SCALAR", "ID")))]),
+                field("account", named("OBJECT", "User"), vec![arg("id", non_null(named("SCALAR", "ID")))]),
+                field("search", json!({ "kind": "LIST", "name": null, "ofType": named("OBJECT", "User") }), vec![arg("q", non_null(named("SCALAR", "String")))]),
+                field("orders", named("SCALAR", "Int"), vec![arg("filter", non_null(named("INPUT_OBJECT", "OrderFilter")))]),
+            ]},
+            { "kind": "OBJECT", "name": "User", "fields": [
+                field("id", named("SCALAR", "ID"), vec![]),
+                field("name", named("SCALAR", "String"), vec![]),
+                field("email", named("SCALAR", "String"), vec![]),
+            ]},
+            { "kind": "INPUT_OBJECT", "name": "OrderFilter", "inputFields": [
+                arg("status", non_null(named("ENUM", "OrderStatus"))),
+                arg("limit", named("SCALAR", "Int")),
+            ]},
+            { "kind": "ENUM", "name": "OrderStatus", "enumValues": [{ "name": "OPEN" }, { "name": "CLOSED" }] },
+        ]
+    }}})
+}
+
+#[test]
+fn test_query_generation() {
+    let schema = GraphQLSchema::from_introspection(&introspection());
+    assert_eq!(schema.query_type.as_deref(), Some("Query"));
+    assert_eq!(schema.enum_values["OrderStatus"], vec!["OPEN", "CLOSED"]);
+
+    let fields = &schema.type_fields["Query"];
+    let by_name = |n: &str| fields.iter().find(|f| f.name == n).unwrap();
+    assert_eq!(field_query(&schema, by_name("user"), &[]).unwrap(), "query Fuzz { user(id: \"1\") { id name email } }");
+    assert_eq!(field_query(&schema, by_name("user"), &[("id", "\"7\"".to_string())]).unwrap(), "query Fuzz { user(id: \"7\") { id name email } }");
+    // Input objects get their required fields, enums their first value; scalars have no selection
+    assert_eq!(field_query(&schema, by_name("orders"), &[]).unwrap(), "query Fuzz { orders(filter: {status: OPEN}) }");
+
+    assert_eq!(literal(&schema, "contactEmail", "String", "SCALAR", false, 0).unwrap(), "\"user@example.com\"");
+    assert_eq!(literal(&schema, "ids", "Int", "SCALAR", true, 0).unwrap(), "[1]");
+    assert!(literal(&schema, "x", "Unknown", "ENUM", false, 0).is_none());
+}
+
+#[test]
+fn test_access_classification() {
+    assert_eq!(classify_access(200, &json!({ "data": { "user": { "id": "1" } } }), "user"), Access::Allowed);
+    assert_eq!(classify_access(403, &json!({}), "user"), Access::Denied);
+    let denied = json!({ "data": { "user": null }, "errors": [{ "message": "Not authorized", "path": ["user"] }] });
+    assert_eq!(classify_access(200, &denied, "user"), Access::Denied);
+    let coded = json!({ "data": null, "errors": [{ "message": "nope", "extensions": { "code": "FORBIDDEN" } }] });
+    assert_eq!(classify_access(200, &coded, "user"), Access::Denied);
+    assert_eq!(classify_access(200, &json!({ "data": { "user": null } }), "user"), Access::Null);
+    let invalid = json!({ "errors": [{ "message": "Cannot query field \"x\"" }] });
+    assert_eq!(classify_access(400, &invalid, "user"), Access::Error);
+    // Refused subfield only: the field itself answered
+    let partial = json!({ "data": { "user": { "id": "1", "email": null } }, "errors": [{ "message": "Forbidden", "path": ["user", "email"] }] });
+    assert_eq!(classify_access(200, &partial, "user"), Access::Allowed);
+}
+
+fn access(field: &str, access: Access, by_id: bool, denied: &[&str], allowed: &[&str]) -> FieldAccess {
+    FieldAccess {
+        field: field.to_string(),
+        return_type: "User".to_string(),
+        query: format!("query Fuzz {{ {} }}", field),
+        access,
+        by_id,
+        denied_subfields: denied.iter().map(|s| s.to_string()).collect(),
+        allowed_subfields: allowed.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_inconsistent_authorization() {
+    // A list refused next to the caller's own object is normal
+    assert!(inconsistent_authorization(&[access("users", Access::Denied, false, &[], &[]), access("me", Access::Allowed, false, &[], &["id"])]).is_empty());
+
+    let lookups = inconsistent_authorization(&[access("account", Access::Denied, true, &[], &[]), access("user", Access::Allowed, true, &[], &["id"])]);
+    assert_eq!(lookups.len(), 1);
+    assert!(lookups[0].description.contains("refused through account but returned through user"));
+
+    let subfield = inconsistent_authorization(&[
+        access("me", Access::Allowed, false, &["email"], &["id"]),
+        access("node", Access::Allowed, true, &[], &["id", "email"]),
+    ]);
+    assert_eq!(subfield.len(), 1);
+    assert!(subfield[0].description.contains("User.email is refused through me but returned through node"));
+}
+
+/// `user(id)` answers any ID, `account(id)` refuses, `search(q)` breaks on quotes
+async fn graphql_app() -> String {
+    let base = common::serve(|req| {
+        let body: Value = serde_json::from_slice(&req.body).unwrap_or(Value::Null);
+        let query = body["query"].as_str().unwrap_or_default();
+        let (status, answer) = if let Some(rest) = query.strip_prefix("query Fuzz { user(id: \"") {
+            let id = rest.split('"').next().unwrap_or_default();
+            (200, json!({ "data": { "user": { "id": id, "name": format!("User {}", id), "email": format!("user{}@corp-mail.com", id) } } }))
+        } else if query.contains("account(") {
+            (200, json!({ "data": { "account": null }, "errors": [{ "message": "Not authorized", "path": ["account"] }] }))
+        } else if query.contains("search(") && query.contains('\'') {
+            (500, json!({ "errors": [{ "message": "You have an error in your SQL syntax near ''' at line 1" }] }))
+        } else if query.contains("search(") {
+            (200, json!({ "data": { "search": [] } }))
+        } else {
+            (400, json!({ "errors": [{ "message": "Validation error" }] }))
+        };
+        Response::json(status, answer.to_string())
+    }).await;
+    format!("{}/graphql", base)
+}
+
+#[tokio::test]
+async fn test_schema_fuzzing() {
+    let url = graphql_app().await;
+    let schema = GraphQLSchema::from_introspection(&introspection());
+    let http = HttpClient::new(reqwest::Client::new());
+    let (accesses, vulns) = SchemaFuzzer::new(&http, Duration::from_secs(5)).run(&url, &schema).await;
+
+    let of = |f: &str| accesses.iter().find(|a| a.field == f).unwrap().access;
+    assert_eq!(of("user"), Access::Allowed);
+    assert_eq!(of("account"), Access::Denied);
+    assert_eq!(of("search"), Access::Allowed);
+    assert_eq!(of("orders"), Access::Error);
+
+    let kinds: Vec<&str> = vulns.iter().map(|v| v.vuln_type.as_str()).collect();
+    let idor = vulns.iter().find(|v| v.vuln_type == "GraphQL IDOR Enumeration").expect("IDOR finding");
+    assert_eq!(idor.severity, "HIGH");
+    assert!(idor.description.contains("user(id)"));
+    let injection = vulns.iter().find(|v| v.vuln_type == "GraphQL Argument Injection").expect("injection finding");
+    assert!(injection.description.contains("search(q)"));
+    assert!(kinds.contains(&"Inconsistent Field Authorization"));
+    // The refused lookup gets no payloads
+    assert!(!vulns.iter().any(|v| v.description.starts_with("account(")));
+}
@@ -0,0 +1,211 @@
+use api_hunter::graphql::recovery::{document, parse_hint, parse_type_ref, Hint, SchemaRecovery, SENTINEL};
+use api_hunter::http_client::HttpClient;
+use common::Response;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_error_parsing() {
+    assert_eq!(
+        parse_hint(r#"Cannot query field "usr" on type "Query". Did you mean "user", "users", or "userByEmail"?"#),
+        Some(Hint::UnknownField { name: "usr".into(), on_type: "Query".into(), suggestions: vec!["user".into(), "users".into(), "userByEmail".into()] })
+    );
+    assert_eq!(
+        parse_hint(r#"Cannot query field "x" on type "Node". Did you mean to use an inline fragment on "User"?"#),
+        Some(Hint::UnknownField { name: "x".into(), on_type: "Node".into(), suggestions: vec![] })
+    );
+    assert_eq!(
+        parse_hint(r#"Field "user" of type "User" must have a selection of subfields. Did you mean "user { ... }"?"#),
+        Some(Hint::NeedsSelection { field: "user".into(), type_ref: "User".into() })
+    );
+    assert_eq!(
+        parse_hint(r#"Field "id" must not have a selection since type "ID!" has no subfields."#),
+        Some(Hint::Leaf { field: "id".into(), type_ref: "ID!".into() })
+    );
+    assert_eq!(
+        parse_hint(r#"Field "user" argument "id" of type "ID!" is required, but it was not provided."#),
+        Some(Hint::RequiredArg { field: "user".into(), arg: "id".into(), type_ref: "ID!".into() })
+    );
+    assert_eq!(
+        parse_hint(r#"Unknown argument "ordr" on field "Query.users". Did you mean "order"?"#),
+        Some(Hint::UnknownArg { arg: "ordr".into(), field: "users".into(), suggestions: vec!["order".into()] })
+    );
+    assert_eq!(parse_hint("Internal server error"), None);
+
+    assert_eq!(parse_type_ref("[User!]!"), ("User".to_string(), true));
+    assert_eq!(parse_type_ref("ID!"), ("ID".to_string(), false));
+    assert_eq!(
+        document("query", &["user".to_string()], &["id".to_string()]),
+        format!("query {{ user {{ id {} }} }}", SENTINEL)
+    );
+}
+
+/// (field, return type, arguments) per type
+type Schema = Vec<(&'static str, Vec<(&'static str, &'static str, Vec<(&'static str, &'static str)>)>)>;
+
+fn schema() -> Schema {
+    vec![
+        ("Query", vec![
+            ("user", "User", vec![("id", "ID!")]),
+            ("users", "[User!]!", vec![("limit", "Int"), ("orderDirection", "String")]),
+            ("userByEmail", "User", vec![("email", "String!")]),
+            ("health", "String", vec![]),
+        ]),
+        ("Mutation", vec![("deleteUser", "Boolean", vec![("id", "ID!")])]),
+        ("User", vec![("id", "ID!", vec![]), ("email", "String", vec![]), ("profile", "Profile", vec![])]),
+        ("Profile", vec![("bio", "String", vec![]), ("phoneNumber", "String", vec![])]),
+    ]
+}
+
+struct Selection {
+    name: String,
+    args: Vec<String>,
+    children: Option<Vec<Selection>>,
+}
+
+fn tokens(doc: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut word = String::new();
+    for c in doc.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            out.push(std::mem::take(&mut word));
+        }
+        if "{}():".contains(c) {
+            out.push(c.to_string());
+        }
+    }
+    if !word.is_empty() {
+        out.push(word);
+    }
+    out
+}
+
+fn selections(tokens: &[String], pos: &mut usize) -> Vec<Selection> {
+    let mut out = Vec::new();
+    *pos += 1; // {
+    while *pos < tokens.len() && tokens[*pos] != "}" {
+        let name = tokens[*pos].clone();
+        *pos += 1;
+        let mut args = Vec::new();
+        if tokens.get(*pos).map(String::as_str) == Some("(") {
+            while tokens[*pos] != ")" {
+                if tokens[*pos + 1] == ":" {
+                    args.push(tokens[*pos].clone());
+                }
+                *pos += 1;
+            }
+            *pos += 1;
+        }
+        let children = (tokens.get(*pos).map(String::as_str) == Some("{")).then(|| selections(tokens, pos));
+        out.push(Selection { name, args, children });
+    }
+    *pos += 1; // }
+    out
+}
+
+/// Names sharing the first three letters, the way suggestion lists look
+fn did_you_mean(name: &str, candidates: &[&str]) -> String {
+    let prefix: String = name.to_ascii_lowercase().chars().take(3).collect();
+    let similar: Vec<String> = candidates.iter().filter(|c| c.to_ascii_lowercase().starts_with(&prefix)).map(|c| format!("\"{}\"", c)).collect();
+    if similar.is_empty() { String::new() } else { format!(" Did you mean {}?", similar.join(" or ")) }
+}
+
+fn validate(schema: &Schema, type_name: &str, set: &[Selection], errors: &mut Vec<String>) {
+    let fields = &schema.iter().find(|(t, _)| *t == type_name).unwrap().1;
+    let names: Vec<&str> = fields.iter().map(|f| f.0).collect();
+    for sel in set {
+        let Some((name, ty, args)) = fields.iter().find(|f| f.0 == sel.name) else {
+            errors.push(format!("Cannot query field \"{}\" on type \"{}\".{}", sel.name, type_name, did_you_mean(&sel.name, &names)));
+            continue;
+        };
+        let arg_names: Vec<&str> = args.iter().map(|a| a.0).collect();
+        for arg in &sel.args {
+            if !arg_names.contains(&arg.as_str()) {
+                errors.push(format!("Unknown argument \"{}\" on field \"{}.{}\".{}", arg, type_name, name, did_you_mean(arg, &arg_names)));
+            }
+        }
+        for (arg, arg_ty) in args.iter().filter(|a| a.1.ends_with('!')) {
+            if !sel.args.iter().any(|a| a == arg) {
+                errors.push(format!("Field \"{}\" argument \"{}\" of type \"{}\" is required, but it was not provided.", name, arg, arg_ty));
+            }
+        }
+        let named = ty.trim_matches(|c| matches!(c, '[' | ']' | '!'));
+        let object = schema.iter().any(|(t, _)| *t == named);
+        match (&sel.children, object) {
+            (None, true) => errors.push(format!("Field \"{}\" of type \"{}\" must have a selection of subfields. Did you mean \"{} {{ ... }}\"?", name, ty, name)),
+            (Some(_), false) => errors.push(format!("Field \"{}\" must not have a selection since type \"{}\" has no subfields.", name, ty)),
+            (Some(children), true) => validate(schema, named, children, errors),
+            (None, false) => {}
+        }
+    }
+}
+
+/// graphql-js style validation over `schema()`, introspection off; records whether anything ran
+async fn graphql_app(executed: Arc<AtomicBool>) -> String {
+    let base = common::serve(move |req| {
+        let body: Value = serde_json::from_slice(&req.body).unwrap_or(Value::Null);
+        let toks = tokens(body["query"].as_str().unwrap_or_default());
+        let root = if toks.first().map(String::as_str) == Some("mutation") { "Mutation" } else { "Query" };
+        let mut pos = toks.iter().position(|t| t == "{").unwrap_or(0);
+        let set = selections(&toks, &mut pos);
+        let mut errors = Vec::new();
+        validate(&schema(), root, &set, &mut errors);
+        let answer = if errors.is_empty() {
+            executed.store(true, Ordering::SeqCst);
+            json!({ "data": {} })
+        } else {
+            json!({ "errors": errors.iter().map(|m| json!({ "message": m })).collect::<Vec<_>>() })
+        };
+        Response::json(200, answer.to_string())
+    }).await;
+    format!("{}/graphql", base)
+}
+
+#[tokio::test]
+async fn test_schema_recovery() {
+    let executed = Arc::new(AtomicBool::new(false));
+    let url = graphql_app(executed.clone()).await;
+    let http = HttpClient::new(reqwest::Client::new());
+    let recovered = SchemaRecovery::new(&http, Duration::from_secs(5)).recover(&url).await.expect("partial schema");
+    let schema = &recovered.schema;
+
+    assert!(recovered.suggestions);
+    assert!(!executed.load(Ordering::SeqCst), "a probe passed validation");
+    assert_eq!(schema.query_type.as_deref(), Some("Query"));
+    // `userByEmail` and `phoneNumber` aren't in the wordlist: suggestions found them
+    assert_eq!(schema.queries, vec!["health", "user", "userByEmail", "users"]);
+    assert_eq!(schema.mutations, vec!["deleteUser"]);
+
+    let field = |t: &str, f: &str| schema.type_fields[t].iter().find(|d| d.name == f).unwrap().clone();
+    let user = field("Query", "user");
+    assert_eq!((user.type_name.as_str(), user.kind.as_str()), ("User", "OBJECT"));
+    assert_eq!(user.args.len(), 1);
+    assert!(user.args[0].required && user.args[0].name == "id" && user.args[0].type_name == "ID");
+    let users = field("Query", "users");
+    assert_eq!(users.type_name, "User");
+    let optional: Vec<&str> = users.args.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(optional, vec!["limit", "orderDirection"]);
+    assert_eq!(field("Query", "health").type_name, "String");
+    assert_eq!(field("Profile", "phoneNumber").kind, "SCALAR");
+    assert_eq!(field("User", "profile").type_name, "Profile");
+
+    assert!(schema.sensitive_types.contains(&"User".to_string()));
+    assert!(schema.sensitive_types.contains(&"Profile".to_string()));
+}
+
+#[tokio::test]
+async fn test_masked_errors() {
+    // Errors without the validation messages: nothing to recover
+    let base = common::serve(|_| Response::json(400, r#"{"errors":[{"message":"GraphQL validation failed"}]}"#)).await;
+    let url = format!("{}/graphql", base);
+    let http = HttpClient::new(reqwest::Client::new());
+    assert!(SchemaRecovery::new(&http, Duration::from_secs(5)).recover(&url).await.is_none());
+}
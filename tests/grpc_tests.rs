@@ -0,0 +1,129 @@
+use api_hunter::output::writer_jsonl::RawEvent;
+use api_hunter::probe::grpc::{self, encode_bytes_field as field, GrpcProber, Transport, TRAILER_FLAG};
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+fn event(url: &str, content_type: Option<&str>, http_version: &str) -> RawEvent {
+    let mut ev: RawEvent = serde_json::from_str(&format!(
+        r#"{{"orig_url":"{0}","final_url":"{0}","status":200,"content_type":null,"server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1,"http_version":"{1}"}}"#,
+        url, http_version
+    )).unwrap();
+    ev.content_type = content_type.map(str::to_string);
+    ev
+}
+
+/// `ServerReflectionResponse` listing `services`
+fn list_response(services: &[&str]) -> Vec<u8> {
+    let list: Vec<u8> = services.iter().flat_map(|s| field(1, &field(1, s.as_bytes()))).collect();
+    field(6, &list)
+}
+
+/// `FileDescriptorProto` of package `shop.v1` with an order service
+fn order_file() -> Vec<u8> {
+    let mut get = field(1, b"GetOrder");
+    get.extend(field(2, b".shop.v1.GetOrderRequest"));
+    get.extend(field(3, b".shop.v1.Order"));
+    let mut watch = field(1, b"WatchOrders");
+    watch.extend(field(2, b".shop.v1.WatchRequest"));
+    watch.extend(field(3, b".shop.v1.Order"));
+    watch.extend([0x30, 0x01]); // server_streaming = true
+    let mut service = field(1, b"OrderService");
+    service.extend(field(2, &get));
+    service.extend(field(2, &watch));
+    let mut file = field(1, b"shop/v1/order.proto");
+    file.extend(field(2, b"shop.v1"));
+    file.extend(field(6, &service));
+    file
+}
+
+#[test]
+fn test_reflection_messages() {
+    assert_eq!(grpc::list_services_request(), vec![0x3a, 0x01, b'*']);
+    assert_eq!(grpc::frame(0, b"ab"), vec![0, 0, 0, 0, 2, b'a', b'b']);
+    let mut body = grpc::frame(0, b"ab");
+    body.extend(grpc::frame(TRAILER_FLAG, b"grpc-status:0"));
+    body.extend([0, 0, 0]); // truncated frame
+    assert_eq!(grpc::parse_frames(&body), vec![(0, b"ab".to_vec()), (TRAILER_FLAG, b"grpc-status:0".to_vec())]);
+
+    let parsed = grpc::parse_reflection_response(&list_response(&["shop.v1.OrderService", "grpc.health.v1.Health"])).unwrap();
+    assert_eq!(parsed.services, vec!["shop.v1.OrderService", "grpc.health.v1.Health"]);
+    let mut error = vec![0x08, 0x05]; // error_code = NOT_FOUND
+    error.extend(field(2, b"symbol not found"));
+    assert_eq!(grpc::parse_reflection_response(&field(7, &error)).unwrap().error, Some((5, "symbol not found".to_string())));
+    assert!(grpc::parse_reflection_response(&[0x3a, 0x10, b'x']).is_none());
+
+    let services = grpc::parse_file_descriptor(&order_file());
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0].name, "shop.v1.OrderService");
+    let methods: Vec<_> = services[0].methods.iter().map(|m| (m.name.as_str(), m.input_type.as_str(), m.server_streaming)).collect();
+    assert_eq!(methods, vec![("GetOrder", "shop.v1.GetOrderRequest", false), ("WatchOrders", "shop.v1.WatchRequest", true)]);
+}
+
+#[test]
+fn test_targets_prefer_grpc_content_types() {
+    let results = vec![
+        event("https://www.example.com/", Some("text/html"), "HTTP/2"),
+        event("https://old.example.com/", Some("text/html"), "HTTP/1.1"),
+        event("https://rpc.example.com/shop.v1.OrderService/GetOrder", Some("application/grpc"), "HTTP/2"),
+        event("https://www.example.com/app.js", None, "HTTP/2"),
+    ];
+    assert_eq!(grpc::targets(&results, 10), vec!["https://rpc.example.com", "https://www.example.com"]);
+    assert_eq!(grpc::targets(&results, 1), vec!["https://rpc.example.com"]);
+}
+
+/// gRPC-web over HTTP/1.1. `reflection` answers the v1alpha reflection service; otherwise every call
+/// gets `UNIMPLEMENTED` in the trailer frame.
+async fn serve(reflection: bool) -> String {
+    common::serve(move |req| {
+        // h2 prior-knowledge preface and anything but gRPC-web
+        if req.method != "POST" || req.header("content-type") != Some("application/grpc-web+proto") {
+            return Response::new(400);
+        }
+        let request = grpc::parse_frames(&req.body).into_iter().next().map(|(_, m)| m).unwrap_or_default();
+        let mut out = Vec::new();
+        let trailer = if reflection && req.path() == grpc::REFLECTION_METHODS[1] {
+            let answer = if request == grpc::list_services_request() {
+                list_response(&["shop.v1.OrderService", "grpc.reflection.v1alpha.ServerReflection"])
+            } else if request == grpc::file_containing_symbol_request("shop.v1.OrderService") {
+                field(4, &field(1, &order_file()))
+            } else {
+                let mut error = vec![0x08, 0x05];
+                error.extend(field(2, b"not found"));
+                field(7, &error)
+            };
+            out.extend(grpc::frame(0, &answer));
+            "grpc-status:0\r\n".to_string()
+        } else {
+            "grpc-status:12\r\ngrpc-message:unknown service\r\n".to_string()
+        };
+        out.extend(grpc::frame(TRAILER_FLAG, trailer.as_bytes()));
+        Response::new(200).header("Content-Type", "application/grpc-web+proto").body(out)
+    }).await
+}
+
+#[tokio::test]
+async fn test_grpc_web_reflection() {
+    let prober = GrpcProber::new(Duration::from_secs(3));
+
+    let base = serve(true).await;
+    let report = prober.probe(&base).await.unwrap();
+    assert_eq!(report.transport, Transport::GrpcWeb);
+    assert_eq!(report.reflection.as_deref(), Some("grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo"));
+    let names: Vec<_> = report.services.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["shop.v1.OrderService", "grpc.reflection.v1alpha.ServerReflection"]);
+    assert_eq!(report.methods(), 2);
+    assert_eq!(report.severity.as_deref(), Some("MEDIUM"));
+
+    // A gRPC service without reflection is detected, with the refusal kept
+    let closed = prober.probe(&serve(false).await).await.unwrap();
+    assert!(closed.reflection.is_none() && closed.services.is_empty() && closed.severity.is_none());
+    assert_eq!(closed.status.as_deref(), Some("12 unknown service"));
+}
+
+#[tokio::test]
+async fn test_plain_http_is_not_grpc() {
+    let base = common::serve(|_| Response::html(404, "not found")).await;
+    assert!(GrpcProber::new(Duration::from_secs(3)).probe(&base).await.is_none());
+}
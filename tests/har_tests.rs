@@ -0,0 +1,16 @@
+use api_hunter::gather::har::parse_har;
+
+#[test]
+fn parse_har_extracts_requests() {
+    let har = r#"{"log":{"entries":[
+        {"request":{"method":"post","url":"https://example.com/api/v1/users","headers":[{"name":":authority","value":"example.com"},{"name":"Authorization","value":"Bearer abc"}],"postData":{"mimeType":"application/json","text":"{\"name\":\"x\"}"}}},
+        {"request":{"method":"POST","url":"https://example.com/api/v1/users","headers":[]}},
+        {"request":{"method":"GET","url":"data:image/png;base64,AAAA","headers":[]}}
+    ]}}"#;
+    let reqs = parse_har(har).unwrap();
+    assert_eq!(reqs.len(), 1);
+    assert_eq!(reqs[0].method, "POST");
+    assert_eq!(reqs[0].headers.get("authorization").map(|s| s.as_str()), Some("Bearer abc"));
+    assert!(!reqs[0].headers.contains_key(":authority"));
+    assert!(reqs[0].json_body.is_some());
+}
@@ -0,0 +1,90 @@
+use api_hunter::fuzz::hpp::{classify, duplicate_key_json, polluted_query, tampered_value, Channel, HppTester, Impact, Precedence, Snapshot};
+use api_hunter::fuzz::param_bruteforce::ParamLocation;
+use api_hunter::http_client::HttpClient;
+use api_hunter::output::clean_reporter::Severity;
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+fn snap(status: u16, body: &str) -> Snapshot {
+    Snapshot { status, body: body.to_string() }
+}
+
+#[test]
+fn test_request_construction() {
+    assert_eq!(polluted_query("http://a.test/x?id=1&b=2", "id", &["1", "2"]), "http://a.test/x?b=2&id=1&id=2");
+    assert_eq!(polluted_query("http://a.test/x?id=1", "id", &[]), "http://a.test/x");
+    assert_eq!(duplicate_key_json("role", &["user", "7"]), r#"{"role":"user","role":7}"#);
+
+    assert_eq!(tampered_value("user_id", "41"), Some((Impact::Authorization, "42".to_string())));
+    assert_eq!(tampered_value("isAdmin", "false"), Some((Impact::Authorization, "true".to_string())));
+    assert_eq!(tampered_value("price", "19"), Some((Impact::Pricing, "0".to_string())));
+    assert_eq!(tampered_value("q", "shoes"), None);
+}
+
+#[test]
+fn test_classification() {
+    let (a, b) = ("hppaaa", "hppbbb");
+    let only_a = snap(200, "<p>hppaaa</p>");
+    let only_b = snap(200, "<p>hppbbb</p>");
+    assert_eq!(classify(a, b, &snap(200, "<p>hppbbb</p>"), &only_a, &only_b).0, Precedence::Last);
+    assert_eq!(classify(a, b, &snap(200, "<p>hppaaa</p>"), &only_a, &only_b).0, Precedence::First);
+    let (precedence, evidence) = classify(a, b, &snap(200, "<p>hppaaa,hppbbb</p>"), &only_a, &only_b);
+    assert_eq!(precedence, Precedence::Concatenated);
+    assert!(evidence.contains("hppaaa,hppbbb"));
+
+    // Not reflected: compared with the single responses
+    let user = snap(200, "{\"id\":5}");
+    let missing = snap(404, "not found");
+    assert_eq!(classify("5", b, &snap(404, "not found"), &user, &missing).0, Precedence::Last);
+    assert_eq!(classify("5", b, &snap(200, "{\"id\":5}"), &user, &missing).0, Precedence::First);
+    assert_eq!(classify("5", b, &snap(400, "duplicate"), &user, &snap(200, "{}")).0, Precedence::Rejected);
+    assert_eq!(classify("5", b, &missing, &missing, &missing).0, Precedence::Unknown);
+}
+
+/// `/account?id=` behind a gateway that lets only id 5 through. With `split`, the gateway checks the first
+/// id and the application serves the last one.
+async fn account_app(split: bool) -> String {
+    let base = common::serve(move |req| {
+        if req.method != "GET" {
+            return Response::new(405).header("Content-Type", "application/json");
+        }
+        let ids: Vec<String> = req.query().into_iter().filter(|(k, _)| k == "id").map(|(_, v)| v).collect();
+        let checked = ids.first();
+        let served = if split { ids.last() } else { ids.first() };
+        match (checked, served) {
+            (Some(c), Some(s)) if c == "5" => match s.parse::<u32>() {
+                Ok(id) => Response::json(200, format!("{{\"id\":{},\"email\":\"user{}@shop.test\",\"orders\":[1,2,3]}}", id, id)),
+                Err(_) => Response::json(404, "{\"error\":\"no such account\"}"),
+            },
+            (Some(_), _) => Response::json(403, "{\"error\":\"forbidden\"}"),
+            _ => Response::json(400, "{\"error\":\"id required\"}"),
+        }
+    }).await;
+    format!("{}/account", base)
+}
+
+#[tokio::test]
+async fn test_split_precedence_is_flagged() {
+    let base = account_app(true).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::hpp").build().unwrap());
+    let tester = HppTester::new(&http, Duration::from_secs(5));
+    let finding = tester.test(&format!("{}?id=5", base), "id", ParamLocation::Query).await.expect("endpoint answers");
+    assert_eq!(finding.impact, Some(Impact::Authorization));
+    assert_eq!(finding.channel, Some(Channel::Query));
+    assert_eq!(finding.severity, Some(Severity::High));
+    assert!(finding.evidence.unwrap().contains("id=6 alone: 403"));
+    // POST is not handled, so only the query channel was classified
+    assert_eq!(finding.behaviors.len(), 1);
+}
+
+#[tokio::test]
+async fn test_consistent_precedence_is_clean() {
+    let base = account_app(false).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::hpp").build().unwrap());
+    let tester = HppTester::new(&http, Duration::from_secs(5));
+    let finding = tester.test(&format!("{}?id=5", base), "id", ParamLocation::Query).await.expect("endpoint answers");
+    assert_eq!(finding.impact, None);
+    assert_eq!(finding.behaviors[0].precedence, Precedence::First);
+}
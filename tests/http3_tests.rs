@@ -0,0 +1,59 @@
+use api_hunter::output::clean_reporter::Severity;
+use api_hunter::output::writer_jsonl::RawEvent;
+use api_hunter::probe::http3::{self, compare, parse_alt_svc, parse_curl_output, Difference};
+
+fn event(status: u16, alt_svc: Option<&str>) -> RawEvent {
+    let mut ev: RawEvent = serde_json::from_str(&format!(
+        r#"{{"orig_url":"https://api.example.com/admin","final_url":"https://api.example.com/admin","status":{},"content_type":null,"server":"cloudflare","content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":3,"http_version":"HTTP/2"}}"#,
+        status
+    )).unwrap();
+    if let Some(v) = alt_svc {
+        ev.headers.insert("alt-svc".to_string(), v.to_string());
+    }
+    ev
+}
+
+#[test]
+fn test_parse_alt_svc() {
+    let alts = parse_alt_svc(r#"h3=":443"; ma=86400, h3-29="quic.example.com:8443", h2=":443""#);
+    assert_eq!(alts.len(), 3);
+    assert_eq!((alts[0].protocol.as_str(), alts[0].host.as_deref(), alts[0].port, alts[0].max_age), ("h3", None, 443, Some(86400)));
+    assert_eq!((alts[1].host.as_deref(), alts[1].port), (Some("quic.example.com"), 8443));
+    assert!(alts[1].is_h3());
+    assert!(!alts[2].is_h3());
+    assert!(parse_alt_svc("clear").is_empty());
+
+    assert_eq!(http3::advertised(&event(200, Some(r#"h2=":443", h3=":443""#))).map(|a| a.protocol), Some("h3".to_string()));
+    assert!(http3::advertised(&event(200, Some(r#"h2=":443""#))).is_none());
+    assert!(http3::advertised(&event(200, None)).is_none());
+}
+
+#[test]
+fn test_parse_curl_output() {
+    let raw = b"HTTP/3 200\r\nserver: nginx\r\ncontent-type: application/json\r\nvary: Origin\r\nvary: Accept\r\n\r\n{\"ok\":true}";
+    let resp = parse_curl_output(raw).unwrap();
+    assert_eq!(resp.status, 200);
+    assert_eq!(resp.headers.get("server").map(String::as_str), Some("nginx"));
+    assert_eq!(resp.headers.get("vary").map(String::as_str), Some("Origin, Accept"));
+    assert_eq!(resp.length, 11);
+    assert!(parse_curl_output(b"garbage").is_none());
+}
+
+#[test]
+fn test_compare() {
+    let h3 = |status: u16| parse_curl_output(format!("HTTP/3 {}\r\nserver: nginx\r\n\r\n", status).as_bytes()).unwrap();
+
+    let bypass = compare(&event(403, None), &h3(200)).unwrap();
+    assert_eq!(bypass.difference, Difference::BlockedOverTcpOnly);
+    assert_eq!(bypass.severity, Severity::High);
+    assert_eq!(bypass.tcp_version.as_deref(), Some("HTTP/2"));
+    assert_eq!(bypass.h3_server.as_deref(), Some("nginx"));
+
+    assert_eq!(compare(&event(200, None), &h3(403)).unwrap().difference, Difference::BlockedOverH3Only);
+    assert_eq!(compare(&event(200, None), &h3(404)).unwrap().difference, Difference::StatusDiffers);
+    // A blocked request that fails over QUIC too is no bypass
+    assert_eq!(compare(&event(403, None), &h3(502)).unwrap().difference, Difference::StatusDiffers);
+    assert!(compare(&event(403, None), &h3(403)).is_none());
+    // Same status without bodies to compare
+    assert!(compare(&event(200, None), &h3(200)).is_none());
+}
@@ -0,0 +1,160 @@
+use api_hunter::http_client::{HttpClient, Middleware, Next, Retry, Throttled};
+use api_hunter::probe::throttle::Throttle;
+use common::Response;
+use parking_lot::Mutex;
+use reqwest::Request;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+mod common;
+
+/// Answers with `responses` in order, then 204s
+async fn serve(responses: Vec<Response>) -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let base = common::serve(move |_| {
+        let n = counter.fetch_add(1, Ordering::SeqCst);
+        responses.get(n).cloned().unwrap_or_else(|| Response::new(204))
+    }).await;
+    (base, hits)
+}
+
+struct Tag(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+#[async_trait::async_trait]
+impl Middleware for Tag {
+    async fn handle(&self, req: Request, next: Next<'_>) -> anyhow::Result<reqwest::Response> {
+        self.1.lock().push(self.0);
+        next.run(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_layers_run_in_order() {
+    let (base, hits) = serve(vec![]).await;
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let http = HttpClient::new(reqwest::Client::new())
+        .with(Tag("outer", seen.clone()))
+        .with(Tag("inner", seen.clone()));
+    let resp = http.get(&format!("{}/", base)).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    assert_eq!(*seen.lock(), vec!["outer", "inner"]);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_retry_transient_failure() {
+    let (base, hits) = serve(vec![Response::hang_up(), Response::new(200).body("ok")]).await;
+    let client = reqwest::Client::builder().pool_max_idle_per_host(0).build().unwrap();
+    let http = HttpClient::new(client).with(Retry::new(3));
+    let resp = http.get(&format!("{}/", base)).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_throttle_records_rate_limits() {
+    let (base, _) = serve(vec![Response::new(429)]).await;
+    let throttle = Arc::new(Throttle::new(8, 4));
+    let http = HttpClient::new(reqwest::Client::new()).with(Throttled(throttle.clone()));
+    let resp = http.get(&format!("{}/", base)).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 429);
+    let throttled = throttle.throttled_hosts();
+    assert_eq!(throttled.len(), 1);
+    assert_eq!(throttled[0].host, "127.0.0.1");
+    assert!(throttle.host_limit("127.0.0.1") < 4);
+}
+
+#[test]
+fn test_proxy_configuration() {
+    assert_eq!(api_hunter::http_client::proxy(), None);
+    api_hunter::http_client::configure_proxy(Some(api_hunter::http_client::ProxyConfig::new("http://127.0.0.1:8080")));
+    assert!(api_hunter::http_client::builder("tests::http_client").build().is_ok());
+    assert!(api_hunter::http_client::third_party().build().is_ok());
+    api_hunter::http_client::configure_proxy(None);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_spacing() {
+    let limiter = Arc::new(api_hunter::http_client::RateLimiter::new(20));
+    let start = std::time::Instant::now();
+    let tasks: Vec<_> = (0..5).map(|_| {
+        let limiter = limiter.clone();
+        tokio::spawn(async move { limiter.acquire().await })
+    }).collect();
+    for t in tasks {
+        t.await.unwrap();
+    }
+    // First slot is immediate, the other four are 50ms apart
+    assert!(start.elapsed() >= std::time::Duration::from_millis(190));
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+}
+
+/// Minimal SOCKS5 server with username/password auth that answers every request with a 204 itself;
+/// records the (username, requested host) of each connection
+async fn socks5_server() -> (u16, Arc<Mutex<Vec<(String, String)>>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    tokio::spawn(async move {
+        while let Ok((mut sock, _)) = listener.accept().await {
+            let log = log.clone();
+            tokio::spawn(async move {
+                let mut head = [0u8; 2];
+                sock.read_exact(&mut head).await.unwrap();
+                let mut methods = vec![0u8; head[1] as usize];
+                sock.read_exact(&mut methods).await.unwrap();
+                assert!(methods.contains(&2));
+                sock.write_all(&[5, 2]).await.unwrap();
+                let mut ver_len = [0u8; 2];
+                sock.read_exact(&mut ver_len).await.unwrap();
+                let mut user = vec![0u8; ver_len[1] as usize];
+                sock.read_exact(&mut user).await.unwrap();
+                let mut plen = [0u8; 1];
+                sock.read_exact(&mut plen).await.unwrap();
+                let mut pass = vec![0u8; plen[0] as usize];
+                sock.read_exact(&mut pass).await.unwrap();
+                sock.write_all(&[1, 0]).await.unwrap();
+                let mut req = [0u8; 4];
+                sock.read_exact(&mut req).await.unwrap();
+                // socks5h sends the hostname (address type 3), not a locally resolved IP
+                assert_eq!(req[3], 3);
+                let mut hlen = [0u8; 1];
+                sock.read_exact(&mut hlen).await.unwrap();
+                let mut host = vec![0u8; hlen[0] as usize + 2];
+                sock.read_exact(&mut host).await.unwrap();
+                host.truncate(hlen[0] as usize);
+                log.lock().push((String::from_utf8(user).unwrap(), String::from_utf8(host).unwrap()));
+                sock.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 80]).await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = sock.read(&mut buf).await;
+                let _ = sock.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").await;
+            });
+        }
+    });
+    (port, seen)
+}
+
+#[tokio::test]
+async fn test_socks_circuit_per_host() {
+    use api_hunter::http_client::ProxyConfig;
+    let (port, seen) = socks5_server().await;
+    let config = ProxyConfig { url: format!("socks5h://127.0.0.1:{}", port), isolate_per_host: true };
+    let client = reqwest::Client::builder().proxy(config.to_proxy().unwrap()).build().unwrap();
+    for host in ["a.example.invalid", "b.example.invalid"] {
+        let resp = client.get(format!("http://{}/", host)).send().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 204);
+    }
+    let seen = seen.lock().clone();
+    assert_eq!(seen, vec![
+        ("a.example.invalid".to_string(), "a.example.invalid".to_string()),
+        ("b.example.invalid".to_string(), "b.example.invalid".to_string()),
+    ]);
+
+    let tor = ProxyConfig::tor();
+    assert_eq!(tor.url, api_hunter::http_client::TOR_PROXY);
+    assert_ne!(tor.url_for("a.example.com"), tor.url_for("b.example.com"));
+    assert_eq!(ProxyConfig::new("socks5://127.0.0.1:1080").url_for("a.example.com"), "socks5://127.0.0.1:1080");
+}
@@ -0,0 +1,35 @@
+use api_hunter::identify;
+use common::Response;
+
+mod common;
+
+#[test]
+fn rejects_malformed_headers() {
+    assert!(identify::parse("X-Bug-Bounty").is_err());
+    assert!(identify::parse("Bad Header:value").is_err());
+    let (name, value) = identify::parse("X-Bug-Bounty: hunter").unwrap();
+    assert_eq!(name.as_str(), "x-bug-bounty");
+    assert_eq!(value, "hunter");
+}
+
+// Identification state is process-global, so configuration and audit are checked in one test
+#[tokio::test]
+async fn headers_are_sent_and_audited() {
+    // Built before configuration: must show up as missing
+    let _early = identify::apply(reqwest::Client::builder(), "early").build().unwrap();
+
+    identify::configure(&["X-Bug-Bounty:hunter".to_string()]).unwrap();
+    assert!(identify::is_configured());
+
+    let (base, seen) = common::serve_recording(|_| Response::new(204)).await;
+
+    let client = identify::apply(reqwest::Client::builder(), "test").build().unwrap();
+    client.get(format!("{}/", base)).send().await.unwrap();
+    assert_eq!(seen.lock()[0].header("x-bug-bounty"), Some("hunter"));
+
+    let audit = identify::audit();
+    assert_eq!(audit.headers, vec!["x-bug-bounty: hunter".to_string()]);
+    assert!(!audit.verified);
+    assert_eq!(audit.missing, vec!["early".to_string()]);
+    assert!(audit.clients.iter().any(|c| c.component == "test" && c.headers == ["x-bug-bounty"]));
+}
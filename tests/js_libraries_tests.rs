@@ -0,0 +1,28 @@
+use api_hunter::gather::js_libraries::{compare_versions, detect_libraries, find_vulnerable};
+use api_hunter::output::clean_reporter::Severity;
+use std::cmp::Ordering;
+
+#[test]
+fn detects_and_flags_vulnerable_libraries() {
+    let js = r#"/*! jQuery v3.3.1 | (c) JS Foundation and other contributors | jquery.org/license */
+!function(e,t){}(this);
+/*!*** ./node_modules/axios/lib/axios.js ***!*/
+{"name":"lodash","version":"4.17.21","license":"MIT"}"#;
+    let libs = detect_libraries(js, "https://example.com/app.js");
+
+    let find = |n: &str| libs.iter().find(|l| l.name == n);
+    assert_eq!(find("jquery").and_then(|l| l.version.as_deref()), Some("3.3.1"));
+    assert_eq!(find("lodash").and_then(|l| l.version.as_deref()), Some("4.17.21"));
+    assert!(find("axios").is_some_and(|l| l.version.is_none()));
+
+    let vulns = find_vulnerable(&libs);
+    assert!(vulns.iter().any(|v| v.name == "jquery" && v.identifiers.contains(&"CVE-2019-11358".to_string()) && v.severity == Severity::Medium));
+    assert!(!vulns.iter().any(|v| v.name == "lodash"));
+}
+
+#[test]
+fn compares_versions() {
+    assert_eq!(compare_versions("3.4.0", "3.4.0"), Ordering::Equal);
+    assert_eq!(compare_versions("3.10.0", "3.4.1"), Ordering::Greater);
+    assert_eq!(compare_versions("3.0.0-beta.1", "3.0.0"), Ordering::Less);
+}
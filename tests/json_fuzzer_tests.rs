@@ -0,0 +1,89 @@
+use api_hunter::fuzz::json_fuzzer::{classify, leaves, mutations, seeds_from_openapi, BodySeed, Indicator, JsonFuzzer, MutationKind, SeedSource};
+use api_hunter::http_client::HttpClient;
+use api_hunter::output::clean_reporter::Severity;
+use common::Response;
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_seeds_from_openapi() {
+    let spec = json!({
+        "openapi": "3.0.0",
+        "servers": [{ "url": "/api/v1" }],
+        "paths": {
+            "/users/{id}": {
+                "put": { "requestBody": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } } } },
+                "get": {}
+            }
+        },
+        "components": { "schemas": { "User": {
+            "type": "object",
+            "properties": {
+                "email": { "type": "string", "format": "email" },
+                "age": { "type": "integer" },
+                "role": { "type": "string", "enum": ["user", "admin"] },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        } } }
+    });
+    let seeds = seeds_from_openapi(&spec, "https://api.example.com/docs/openapi.json");
+    assert_eq!(seeds, vec![BodySeed {
+        method: "PUT".to_string(),
+        url: "https://api.example.com/api/v1/users/1".to_string(),
+        body: json!({ "email": "user@example.com", "age": 1, "role": "user", "tags": ["test"] }),
+        source: SeedSource::Spec,
+    }]);
+}
+
+#[test]
+fn test_mutations_and_classification() {
+    let body = json!({ "name": "a", "qty": 2, "items": [{ "sku": "x" }] });
+    assert_eq!(leaves(&body), vec!["/items/0/sku", "/name", "/qty"]);
+    let m = mutations(&body);
+    assert!(m.iter().any(|(p, k, b)| p == "/qty" && *k == MutationKind::SwapType && b["qty"] == json!("2")));
+    assert!(m.iter().any(|(p, k, b)| p == "/name" && *k == MutationKind::ToArray && b["name"] == json!(["a"])));
+    assert!(!m.iter().any(|(p, k, _)| p == "/qty" && *k == MutationKind::SqlQuote));
+
+    let base = (200, "{\"ok\":true}");
+    let sql = classify(MutationKind::SqlQuote, base, 500, "ERROR: unterminated quoted string at or near \"'\"");
+    assert_eq!(sql.map(|s| s.0), Some(Indicator::SqlError));
+    assert_eq!(classify(MutationKind::ToArray, base, 500, "Internal Server Error").map(|s| s.0), Some(Indicator::TypeConfusion));
+    assert_eq!(classify(MutationKind::LongString, base, 500, "Internal Server Error").map(|s| s.0), Some(Indicator::ServerError));
+    assert_eq!(classify(MutationKind::Template, base, 200, "{\"name\":\"a9359\"}").map(|s| s.0), Some(Indicator::TemplateInjection));
+    assert_eq!(classify(MutationKind::NoSqlOperator, (401, "denied"), 200, "{\"token\":\"x\"}").map(|s| s.0), Some(Indicator::NoSqlInjection));
+    assert_eq!(classify(MutationKind::EmptyString, base, 400, "{\"error\":\"name required\"}"), None);
+}
+
+/// POST /orders: a quote in `note` produces a SQL error, an array for `qty` a TypeError
+async fn order_api() -> String {
+    let base = common::serve(|req| {
+        let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap_or_default();
+        let (status, text) = if body["note"].as_str().is_some_and(|n| n.contains('\'')) {
+            (500, "You have an error in your SQL syntax near ''\"' at line 1")
+        } else if body["qty"].is_array() {
+            (500, "TypeError: qty.toFixed is not a function")
+        } else if body["qty"].is_number() {
+            (201, "{\"id\":7}")
+        } else {
+            (400, "{\"error\":\"invalid order\"}")
+        };
+        Response::new(status).body(text)
+    }).await;
+    format!("{}/orders", base)
+}
+
+#[tokio::test]
+async fn test_fuzzer_classifies_responses() {
+    let url = order_api().await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::json_fuzzer").build().unwrap());
+    let seed = BodySeed { method: "POST".to_string(), url, body: json!({ "note": "gift", "qty": 1 }), source: SeedSource::Observed };
+    let findings = JsonFuzzer::new(&http, Duration::from_secs(5)).run(&seed).await;
+    let summary: Vec<(&str, Indicator)> = findings.iter().map(|f| (f.field.as_str(), f.indicator)).collect();
+    assert!(summary.contains(&("/note", Indicator::SqlError)), "{:?}", summary);
+    assert!(summary.contains(&("/qty", Indicator::TypeConfusion)), "{:?}", summary);
+    assert!(findings.iter().all(|f| f.baseline_status == 201));
+    assert!(findings.iter().all(|f| f.severity == f.indicator.severity()));
+    assert_eq!(Indicator::SqlError.severity(), Severity::High);
+}
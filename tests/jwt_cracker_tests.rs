@@ -0,0 +1,57 @@
+use api_hunter::security::jwt_cracker::{CrackTarget, HmacAlg, BATCH_SIZE};
+use api_hunter::security::{JwtAnalyzer, JwtVulnerability};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+fn token(alg: HmacAlg, secret: &[u8]) -> String {
+    let header = URL_SAFE_NO_PAD.encode(format!(r#"{{"alg":"{}","typ":"JWT"}}"#, alg.name()));
+    let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"42","role":"user"}"#);
+    let message = format!("{}.{}", header, payload);
+    format!("{}.{}", message, URL_SAFE_NO_PAD.encode(alg.sign(secret, message.as_bytes())))
+}
+
+#[test]
+fn test_parse_and_verify() {
+    for alg in [HmacAlg::Hs256, HmacAlg::Hs384, HmacAlg::Hs512] {
+        let target = CrackTarget::parse(&token(alg, b"hunter2")).unwrap();
+        assert_eq!(target.alg, alg);
+        assert!(target.verify(b"hunter2"));
+        assert!(!target.verify(b"hunter3"));
+    }
+    let rs = format!("{}.e30.c2ln", URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#));
+    assert!(CrackTarget::parse(&rs).unwrap_err().contains("not an HMAC"));
+    assert!(CrackTarget::parse("a.b").is_err());
+}
+
+#[test]
+fn test_crack_wordlist() {
+    // Past the first batch, CRLF line endings and a line that isn't UTF-8
+    let mut wordlist: Vec<u8> = Vec::new();
+    for i in 0..BATCH_SIZE + 10 {
+        wordlist.extend_from_slice(format!("word{}\r\n", i).as_bytes());
+    }
+    wordlist.extend_from_slice(b"\xffbinary\r\n");
+    wordlist.extend_from_slice("pässwörd\n".as_bytes());
+
+    let progress = AtomicU64::new(0);
+    let target = CrackTarget::parse(&token(HmacAlg::Hs512, "pässwörd".as_bytes())).unwrap();
+    let found = target.crack(Cursor::new(&wordlist), 2, |n| progress.store(n, Ordering::SeqCst)).unwrap();
+    assert_eq!(found.as_deref(), Some("pässwörd".as_bytes()));
+    assert_eq!(progress.load(Ordering::SeqCst), BATCH_SIZE as u64);
+
+    let target = CrackTarget::parse(&token(HmacAlg::Hs256, b"\xffbinary")).unwrap();
+    assert_eq!(target.crack(Cursor::new(&wordlist), 0, |_| {}).unwrap().as_deref(), Some(&b"\xffbinary"[..]));
+
+    let target = CrackTarget::parse(&token(HmacAlg::Hs384, b"not-in-the-list")).unwrap();
+    assert!(target.crack(Cursor::new(&wordlist), 0, |_| {}).unwrap().is_none());
+}
+
+#[test]
+fn test_analyzer_weak_secret_all_hmac() {
+    let analyzer = JwtAnalyzer::new();
+    for alg in [HmacAlg::Hs256, HmacAlg::Hs384, HmacAlg::Hs512] {
+        let result = analyzer.analyze_token(&token(alg, b"changeme")).unwrap();
+        assert!(result.vulnerabilities.contains(&JwtVulnerability::WeakSecret("changeme".into())), "{}", alg.name());
+    }
+}
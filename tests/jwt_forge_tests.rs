@@ -0,0 +1,177 @@
+use api_hunter::filter::third_party::HostExclusion;
+use api_hunter::security::jwt_cracker::CrackTarget;
+use api_hunter::security::jwt_forge::{self, ForgeAttack, Jwk, JwtForger};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use common::Response;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+// 1024-bit RSA and P-256 test keys; the PEMs are `openssl rsa/ec -pubout` of the same keys
+const RSA_N: &str = "v6L77WqekmcQXZt5XOa7WUGOVbyCI3ns6eG8xvmzwsS0tEOqogq33da4gfCnVFtJvR84qYeRVNnbSjoavEcwl__OtZOL3sJIy5oabKRnbKXcl5-4C0LWmejwM6Il35WWWElcKuGbCN9ZWFq8JosxbNUYzmt8TzHeKT7x1ujQgpE";
+const RSA_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC/ovvtap6SZxBdm3lc5rtZQY5V
+vIIjeezp4bzG+bPCxLS0Q6qiCrfd1riB8KdUW0m9Hziph5FU2dtKOhq8RzCX/861
+k4vewkjLmhpspGdspdyXn7gLQtaZ6PAzoiXflZZYSVwq4ZsI31lYWrwmizFs1RjO
+a3xPMd4pPvHW6NCCkQIDAQAB
+-----END PUBLIC KEY-----
+";
+const RSA_PKCS1_PEM: &str = "-----BEGIN RSA PUBLIC KEY-----
+MIGJAoGBAL+i++1qnpJnEF2beVzmu1lBjlW8giN57OnhvMb5s8LEtLRDqqIKt93W
+uIHwp1RbSb0fOKmHkVTZ20o6GrxHMJf/zrWTi97CSMuaGmykZ2yl3JefuAtC1pno
+8DOiJd+VllhJXCrhmwjfWVhavCaLMWzVGM5rfE8x3ik+8dbo0IKRAgMBAAE=
+-----END RSA PUBLIC KEY-----
+";
+const EC_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE5cWXK0C/aSkK17U+rpF0Jd5MIyXP
+DVyHEXpO9LIAk24n4pvIf8kROj+6qjqaCJX5k4XZqUxtqnoAGReoEr4Dxg==
+-----END PUBLIC KEY-----
+";
+
+fn rsa_jwks() -> String {
+    format!(r#"{{"keys":[{{"kty":"RSA","kid":"k1","use":"sig","alg":"RS256","n":"{}","e":"AQAB"}}]}}"#, RSA_N)
+}
+
+/// An RS256 token; the signature is never checked by the mock, only compared
+fn rs256_token() -> String {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256","typ":"JWT","kid":"k1"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"42","role":"user"}"#);
+    format!("{}.{}.{}", header, payload, URL_SAFE_NO_PAD.encode([7u8; 128]))
+}
+
+#[test]
+fn test_jwk_encodings() {
+    let rsa = jwt_forge::parse_jwks(&rsa_jwks());
+    assert_eq!(rsa.len(), 1);
+    assert_eq!(jwt_forge::pem("PUBLIC KEY", &rsa[0].spki_der().unwrap()), RSA_PEM);
+    assert_eq!(jwt_forge::pem("RSA PUBLIC KEY", &rsa[0].pkcs1_der().unwrap()), RSA_PKCS1_PEM);
+
+    let ec = jwt_forge::parse_jwks(r#"{"kty":"EC","crv":"P-256","x":"5cWXK0C_aSkK17U-rpF0Jd5MIyXPDVyHEXpO9LIAk24","y":"J-KbyH_JETo_uqo6mgiV-ZOF2alMbap6ABkXqBK-A8Y"}"#);
+    assert_eq!(jwt_forge::pem("PUBLIC KEY", &ec[0].spki_der().unwrap()), EC_PEM);
+    assert!(ec[0].pkcs1_der().is_none());
+
+    let secrets = jwt_forge::key_secrets(&rsa[0]);
+    assert!(secrets.iter().any(|(_, s)| s == RSA_PEM.as_bytes()));
+    assert!(secrets.iter().any(|(_, s)| s == RSA_PEM.trim_end().as_bytes()));
+    assert!(secrets.iter().any(|(_, s)| s == RSA_PKCS1_PEM.as_bytes()));
+    assert!(jwt_forge::key_secrets(&Jwk { kty: "oct".into(), ..Default::default() }).is_empty());
+    assert!(jwt_forge::parse_jwks("<html>").is_empty());
+}
+
+#[test]
+fn test_forged_tokens() {
+    let token = rs256_token();
+    let forged = jwt_forge::confusion_tokens(&token, &[("pem".into(), RSA_PEM.as_bytes().to_vec())]);
+    assert_eq!(forged.len(), 1);
+    assert_eq!(forged[0].alg, "HS256");
+    let (header, payload) = jwt_forge::decode(&forged[0].token).unwrap();
+    assert_eq!((header["alg"].as_str(), header["kid"].as_str()), (Some("HS256"), Some("k1")));
+    assert_eq!(payload, jwt_forge::decode(&token).unwrap().1);
+    assert!(CrackTarget::parse(&forged[0].token).unwrap().verify(RSA_PEM.as_bytes()));
+
+    // Only asymmetric tokens are confused; RS512 maps to HS512
+    assert_eq!(jwt_forge::confusion_alg("RS512").map(|a| a.name()), Some("HS512"));
+    assert_eq!(jwt_forge::confusion_alg("ES256").map(|a| a.name()), Some("HS256"));
+    assert!(jwt_forge::confusion_alg("HS256").is_none());
+
+    let kids = jwt_forge::kid_tokens(&token);
+    assert!(kids.iter().all(|k| k.attack == ForgeAttack::KidInjection));
+    let dev_null = kids.iter().find(|k| jwt_forge::decode(&k.token).unwrap().0["kid"] == "/dev/null").unwrap();
+    assert!(CrackTarget::parse(&dev_null.token).unwrap().verify(b""));
+
+    let tampered = jwt_forge::tamper(&token);
+    assert_ne!(tampered, token);
+    assert_eq!(tampered.rsplit_once('.').unwrap().0, token.rsplit_once('.').unwrap().0);
+
+    let urls = jwt_forge::key_urls(&token, &["https://api.test".to_string()]);
+    assert_eq!(urls, vec!["https://api.test/.well-known/jwks.json".to_string()]);
+}
+
+type Verifier = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// `/.well-known/jwks.json` serves the RSA key, every other path needs a bearer token `accepts`
+async fn serve(accepts: Verifier) -> String {
+    common::serve(move |req| {
+        let token = req.header("authorization").and_then(|v| v.strip_prefix("Bearer "));
+        if req.path() == "/.well-known/jwks.json" {
+            Response::json(200, rsa_jwks())
+        } else if token.is_some_and(|t| accepts(t)) {
+            Response::json(200, r#"{"id":42}"#)
+        } else {
+            Response::json(401, r#"{"error":"invalid token"}"#)
+        }
+    }).await
+}
+
+fn forger() -> JwtForger {
+    JwtForger::new(reqwest::Client::new(), Duration::from_secs(5), "127.0.0.1")
+}
+
+#[tokio::test]
+async fn test_algorithm_confusion_accepted() {
+    let original = rs256_token();
+    let expected = original.clone();
+    // Verifies HS tokens with the PEM as served, the RS256 token by comparison
+    let base = serve(Arc::new(move |t: &str| {
+        t == expected || CrackTarget::parse(t).is_ok_and(|c| c.verify(RSA_PEM.as_bytes()))
+    })).await;
+    let url = format!("{}/api/me", base);
+
+    let findings = forger().test_token(&original, std::slice::from_ref(&url), std::slice::from_ref(&base)).await;
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].attack, ForgeAttack::AlgorithmConfusion);
+    assert_eq!((findings[0].original_alg.as_str(), findings[0].forged_alg.as_str()), ("RS256", "HS256"));
+    assert_eq!(findings[0].url, url);
+    assert!(findings[0].variant.contains("PEM"));
+}
+
+#[tokio::test]
+async fn test_kid_injection_accepted() {
+    let original = rs256_token();
+    let expected = original.clone();
+    // Reads the key from the file `kid` names
+    let base = serve(Arc::new(move |t: &str| {
+        if t == expected {
+            return true;
+        }
+        let Some((header, _)) = jwt_forge::decode(t) else { return false };
+        header["kid"].as_str().is_some_and(|k| k.ends_with("/dev/null")) && CrackTarget::parse(t).is_ok_and(|c| c.verify(b""))
+    })).await;
+
+    let findings = forger().test_token(&original, &[format!("{}/api/me", base)], std::slice::from_ref(&base)).await;
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].attack, ForgeAttack::KidInjection);
+    assert_eq!(findings[0].severity, "HIGH");
+}
+
+#[tokio::test]
+async fn test_strict_and_unverified_endpoints() {
+    let original = rs256_token();
+    let expected = original.clone();
+    let strict = serve(Arc::new(move |t: &str| t == expected)).await;
+    assert!(forger().verifies(&format!("{}/api/me", strict), &original).await);
+    assert!(forger().test_token(&original, &[format!("{}/api/me", strict)], std::slice::from_ref(&strict)).await.is_empty());
+
+    // Accepts anything: doesn't verify signatures, so forgeries prove nothing
+    let open = serve(Arc::new(|_: &str| true)).await;
+    assert!(!forger().verifies(&format!("{}/api/me", open), &original).await);
+    assert!(forger().test_token(&original, &[format!("{}/api/me", open)], std::slice::from_ref(&open)).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_key_urls_in_scope() {
+    let (base, requests) = common::serve_recording(|_| Response::json(200, rsa_jwks())).await;
+    let port = base.rsplit(':').next().unwrap();
+    // `jku` on another host than the target (127.0.0.1)
+    let header = URL_SAFE_NO_PAD.encode(format!(r#"{{"alg":"RS256","jku":"http://localhost:{}/keys.json"}}"#, port));
+    let token = format!("{}.{}.{}", header, URL_SAFE_NO_PAD.encode(r#"{"sub":"42"}"#), URL_SAFE_NO_PAD.encode([7u8; 128]));
+
+    let excluded = forger().with_exclusion(HostExclusion::new(true, &["localhost".to_string()], &[]));
+    assert!(excluded.fetch_secrets(&token, &[]).await.is_empty());
+    assert!(requests.lock().is_empty());
+
+    let secrets = forger().fetch_secrets(&token, &[]).await;
+    assert!(secrets.iter().any(|(_, s)| s == RSA_PEM.as_bytes()));
+    assert_eq!(requests.lock().len(), 1);
+}
@@ -0,0 +1,54 @@
+use api_hunter::auth::login_flow::{login, LoginRecipe};
+use common::Response;
+
+mod common;
+
+
+
+#[test]
+fn test_recipe_validation() {
+    assert!(LoginRecipe::parse("url: https://a.example/login\n").is_err());
+    assert!(LoginRecipe::parse("url: https://a.example/login\nset: 'X-Token: {{token}}'\nextract: { cookies: ['*'] }\n").is_err());
+    assert!(LoginRecipe::parse("url: https://a.example/login\njson: {}\nbody: x\nextract: { token: '$.t' }\n").is_err());
+    let r = LoginRecipe::parse("url: https://a.example/login\nextract: { token: '$.t' }\n").unwrap();
+    assert_eq!(r.method, "POST");
+}
+
+#[tokio::test]
+async fn test_login_extracts_token_and_cookies() {
+    std::env::set_var("APIHUNTER_TEST_PASSWORD", "s3cret");
+    let (base, seen) = common::serve_recording(|_| {
+        Response::json(200, "{\"data\":{\"access_token\":\"tok123\"}}\n")
+            .header("Set-Cookie", "sid=xyz; Path=/; HttpOnly")
+            .header("Set-Cookie", "tracking=1")
+    }).await;
+    let recipe = LoginRecipe::parse(&format!(
+        "url: {}/api/login\njson: {{ username: alice, password: \"{{{{env.APIHUNTER_TEST_PASSWORD}}}}\" }}\nextract:\n  token: \"$.data.access_token\"\n  cookies: [sid]\nexpect: {{ status: [200] }}\n",
+        base
+    )).unwrap();
+
+    let auth = login(&reqwest::Client::new(), &recipe).await.unwrap();
+    assert_eq!(auth.token.as_deref(), Some("tok123"));
+    assert_eq!(auth.cookie.as_deref(), Some("sid=xyz"));
+
+    let request = seen.lock()[0].clone();
+    assert_eq!((request.method.as_str(), request.path()), ("POST", "/api/login"));
+    assert!(request.text().contains(r#""password":"s3cret""#));
+}
+
+#[tokio::test]
+async fn test_login_custom_header_and_failure() {
+    let base = common::serve(|_| Response::new(200).header("X-Auth-Token", "abc")).await;
+    let recipe = LoginRecipe::parse(&format!(
+        "url: {}/login\nform: {{ user: alice }}\nextract: {{ token: \"header:X-Auth-Token\" }}\nset: \"X-Session: {{{{token}}}}\"\n",
+        base
+    )).unwrap();
+    let auth = login(&reqwest::Client::new(), &recipe).await.unwrap();
+    assert_eq!(auth.header.as_deref(), Some("X-Session: abc"));
+    assert!(auth.token.is_none());
+
+    let base = common::serve(|_| Response::new(401)).await;
+    let recipe = LoginRecipe::parse(&format!("url: {}/login\nextract: {{ token: \"$.token\" }}\nexpect: {{ status: [200] }}\n", base)).unwrap();
+    let err = login(&reqwest::Client::new(), &recipe).await.unwrap_err();
+    assert!(err.to_string().contains("status 401"));
+}
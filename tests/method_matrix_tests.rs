@@ -0,0 +1,62 @@
+use api_hunter::output::Annotation;
+use api_hunter::probe::http_probe::{non_get_routes, probe_url, ProbeOptions};
+use common::Response;
+use std::collections::BTreeMap;
+
+mod common;
+
+async fn serve() -> String {
+    common::serve(|req| {
+        // /api/orders is a POST-only route; everything else is missing
+        match (req.method.as_str(), req.path() == "/api/orders") {
+            ("OPTIONS", true) => Response::json(204, "").header("Allow", "POST, OPTIONS"),
+            ("POST", true) => Response::json(201, "{\"id\":1}"),
+            (_, true) => Response::json(405, "").header("Allow", "POST, OPTIONS"),
+            _ => Response::json(404, "not found"),
+        }
+    }).await
+}
+
+#[tokio::test]
+async fn test_method_matrix() {
+    let base = serve().await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/orders", base);
+
+    let ev = probe_url(&client, &url, None, &ProbeOptions::new(5, 1)).await.unwrap();
+    assert_eq!(ev.methods.get("HEAD"), Some(&405));
+    assert_eq!(ev.methods.get("GET"), Some(&405));
+    assert_eq!(ev.methods.get("OPTIONS"), Some(&204));
+    // POST only with --aggressive
+    assert!(!ev.methods.contains_key("POST"));
+    assert_eq!(ev.allowed_methods, vec!["OPTIONS", "POST"]);
+    assert_eq!(ev.http_version.as_deref(), Some("HTTP/1.1"));
+    assert!(ev.annotations.contains(&Annotation::AcceptsMethod { method: "POST".to_string() }));
+    assert!(api_hunter::scoring::score::score_event(&ev) <= 3);
+
+    let aggressive = ProbeOptions { aggressive: true, ..ProbeOptions::new(5, 1) };
+    let ev = probe_url(&client, &url, None, &aggressive).await.unwrap();
+    assert_eq!(ev.methods.get("POST"), Some(&201));
+
+    let missing = probe_url(&client, &format!("{}/api/nothing", base), None, &aggressive).await.unwrap();
+    assert_eq!(missing.methods.get("POST"), Some(&404));
+    assert!(!missing.annotations.iter().any(|a| matches!(a, Annotation::AcceptsMethod { .. })));
+
+    // Off: HEAD only
+    let single = ProbeOptions { method_matrix: false, ..ProbeOptions::new(5, 1) };
+    let ev = probe_url(&client, &format!("{}/api/nothing", base), None, &single).await.unwrap();
+    assert_eq!(ev.methods.keys().collect::<Vec<_>>(), vec!["HEAD"]);
+    assert!(ev.allowed_methods.is_empty());
+}
+
+#[test]
+fn test_non_get_routes() {
+    let matrix = |pairs: &[(&str, u16)]| pairs.iter().map(|(m, s)| (m.to_string(), *s)).collect::<BTreeMap<_, _>>();
+    // GET works: nothing to report
+    assert!(non_get_routes(&matrix(&[("GET", 200), ("POST", 201)]), &[]).is_empty());
+    // GET 404, POST validates input
+    assert_eq!(non_get_routes(&matrix(&[("GET", 404), ("POST", 422)]), &[]), vec!["POST"]);
+    // Allow is only trusted on a 405
+    assert_eq!(non_get_routes(&matrix(&[("GET", 405)]), &["PUT".to_string(), "GET".to_string()]), vec!["PUT"]);
+    assert!(non_get_routes(&matrix(&[("GET", 404)]), &["PUT".to_string()]).is_empty());
+}
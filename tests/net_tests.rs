@@ -0,0 +1,101 @@
+use api_hunter::net::{self, AddressFamily};
+use common::Response;
+use std::net::IpAddr;
+
+mod common;
+
+#[test]
+fn test_family_flags() {
+    assert_eq!(AddressFamily::from_flags(true, false), AddressFamily::V4);
+    assert_eq!(AddressFamily::from_flags(false, true), AddressFamily::V6);
+    assert_eq!(AddressFamily::from_flags(false, false), AddressFamily::Any);
+    let v6: IpAddr = "2001:db8::1".parse().unwrap();
+    assert!(!AddressFamily::V4.allows(&v6));
+    assert!(AddressFamily::V6.allows(&v6));
+    assert!(AddressFamily::Any.allows(&v6));
+}
+
+// One test for everything that depends on the process-wide family
+#[tokio::test]
+async fn test_family_selection() {
+    net::set_family(AddressFamily::V6);
+    assert!(net::resolve("127.0.0.1", 443).await.is_empty());
+    assert_eq!(net::resolve("[::1]", 443).await.len(), 1);
+
+    net::set_family(AddressFamily::V4);
+    assert!(net::resolve("::1", 443).await.is_empty());
+    let addrs = net::resolve("localhost", 80).await;
+    assert!(!addrs.is_empty());
+    assert!(addrs.iter().all(|a| a.is_ipv4() && a.port() == 80));
+
+    // Target clients only connect to addresses of the selected family
+    let base = common::serve(|_| Response::new(204)).await.replace("127.0.0.1", "localhost");
+    let client = api_hunter::http_client::builder("tests::net").build().unwrap();
+    let resp = client.get(format!("{}/", base)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+
+    net::set_family(AddressFamily::V6);
+    let client = api_hunter::http_client::builder("tests::net").build().unwrap();
+    let err = client.get(format!("{}/", base)).send().await;
+    assert!(err.is_err());
+    net::set_family(AddressFamily::Any);
+}
+
+#[test]
+fn test_doh_messages() {
+    use api_hunter::net::doh::{encode_query, parse_response, DohServer, RecordType};
+    let query = encode_query("api.example.com", RecordType::A).unwrap();
+    assert_eq!(&query[12..], b"\x03api\x07example\x03com\x00\x00\x01\x00\x01");
+    assert!(encode_query("bad..name", RecordType::A).is_err());
+
+    // Answer: a CNAME, then an A record behind a compression pointer
+    let mut resp = vec![0, 0, 0x81, 0x80, 0, 1, 0, 2, 0, 0, 0, 0];
+    resp.extend_from_slice(&query[12..]);
+    resp.extend_from_slice(&[0xC0, 12, 0, 5, 0, 1, 0, 0, 0x0E, 0x10, 0, 4, 1, b'x', 0xC0, 16]);
+    resp.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 30, 0, 4, 93, 184, 216, 34]);
+    let (ips, ttl) = parse_response(&resp, RecordType::A).unwrap();
+    assert_eq!(ips, vec!["93.184.216.34".parse::<IpAddr>().unwrap()]);
+    assert_eq!(ttl.as_secs(), 30);
+    assert!(parse_response(&resp, RecordType::Aaaa).unwrap().0.is_empty());
+
+    // NXDOMAIN is an empty answer, SERVFAIL an error
+    let mut nxdomain = resp[..query.len()].to_vec();
+    nxdomain[3] = 0x83;
+    nxdomain[7] = 0;
+    assert!(parse_response(&nxdomain, RecordType::A).unwrap().0.is_empty());
+    resp[3] = 0x82;
+    assert!(parse_response(&resp, RecordType::A).is_err());
+
+    assert_eq!(DohServer::parse("Cloudflare").unwrap(), DohServer::Cloudflare);
+    assert_eq!(DohServer::parse("https://doh.example.net/dns-query").unwrap().url(), "https://doh.example.net/dns-query");
+    assert!(DohServer::parse("8.8.8.8").is_err());
+}
+
+#[tokio::test]
+async fn test_doh_resolver() {
+    use api_hunter::net::doh::{DohResolver, DohServer, RecordType};
+    use base64::Engine;
+    // DoH server stand-in: answers every A query with 10.1.2.3 and counts requests
+    let hits = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = hits.clone();
+    let base = common::serve(move |req| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let query = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(req.param("dns").unwrap()).unwrap();
+        let mut answer = query.clone();
+        answer[2] = 0x81;
+        answer[3] = 0x80;
+        if query[query.len() - 3] == 1 {
+            answer[7] = 1;
+            answer.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 1, 0, 0, 4, 10, 1, 2, 3]);
+        }
+        Response::new(200).header("Content-Type", "application/dns-message").body(answer)
+    }).await;
+    let url = format!("{}/dns-query", base);
+
+    let resolver = DohResolver::new(DohServer::Custom(url)).unwrap();
+    let ips = resolver.lookup_ip("internal.example.com", AddressFamily::Any).await.unwrap();
+    assert_eq!(ips, vec!["10.1.2.3".parse::<IpAddr>().unwrap()]);
+    // Cached for the TTL (at least a minute)
+    assert_eq!(resolver.lookup("internal.example.com", RecordType::A).await.unwrap().len(), 1);
+    assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
@@ -0,0 +1,124 @@
+use api_hunter::analyze::oauth::{self, OAuthCheck, OAuthTester};
+use api_hunter::gather::js_deep_analyzer::{JsDeepAnalyzer, OAuthFlow};
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+fn checks(findings: &[oauth::OAuthFinding]) -> Vec<OAuthCheck> {
+    findings.iter().map(|f| f.check).collect()
+}
+
+#[test]
+fn test_extract_flows() {
+    let js = r#"
+        var login=function(){window.location="https://id.example.com/oauth2/authorize?client_id=spa-web&response_type=token&redirect_uri=https%3A%2F%2Fapp.example.com%2Fcb"};
+        const authConfig = {
+            authorizationEndpoint: "https://sso.example.com/connect/authorize",
+            clientId: "mobile-app",
+            redirectUri: "https://app.example.com/callback",
+            responseType: "code",
+        };
+        fetch("https://app.example.com/api/authorized-users");
+    "#;
+    let flows = JsDeepAnalyzer::extract_oauth_flows(js, "main.js");
+    assert_eq!(flows.len(), 2);
+    assert_eq!(flows[0].authorization_endpoint, "https://id.example.com/oauth2/authorize");
+    assert_eq!(flows[0].client_id.as_deref(), Some("spa-web"));
+    assert_eq!(flows[0].redirect_uri.as_deref(), Some("https://app.example.com/cb"));
+    assert_eq!(flows[0].has_state(), Some(false));
+    assert_eq!(flows[1].authorization_endpoint, "https://sso.example.com/connect/authorize");
+    assert_eq!((flows[1].client_id.as_deref(), flows[1].response_type.as_deref()), (Some("mobile-app"), Some("code")));
+    assert_eq!(flows[1].has_state(), None);
+
+    assert_eq!(checks(&oauth::static_findings(&flows[0])), vec![OAuthCheck::MissingState, OAuthCheck::ImplicitFlow]);
+    assert!(oauth::static_findings(&flows[1]).is_empty());
+    assert!(OAuthFlow::from_url("https://a.test/authorize?x=1", "scan").is_none());
+}
+
+#[test]
+fn test_request_building() {
+    let flow = OAuthFlow::from_url("https://id.test/authorize?audience=api&client_id=web&redirect_uri=https://app.test/cb&response_type=code&state=xyz", "scan").unwrap();
+    assert_eq!(flow.has_state(), Some(true));
+    let url = oauth::authorization_url(&flow, "token", Some("https://evil.example/callback")).unwrap();
+    assert_eq!(url, "https://id.test/authorize?audience=api&response_type=token&client_id=web&redirect_uri=https%3A%2F%2Fevil.example%2Fcallback&state=apihunter");
+
+    let payloads = oauth::redirect_payloads(flow.redirect_uri.as_deref());
+    assert_eq!(payloads.iter().map(|p| p.1.as_str()).collect::<Vec<_>>(), vec![
+        "https://evil.example/callback", "https://app.test.evil.example/callback", "https://app.test@evil.example/callback",
+    ]);
+    assert_eq!(oauth::redirect_payloads(None).len(), 1);
+
+    assert!(oauth::is_error(400, None));
+    assert!(oauth::is_error(302, Some("https://app.test/cb?error=unsupported_response_type")));
+    assert!(!oauth::is_error(302, Some("/login?next=%2Fauthorize")));
+
+    assert!(oauth::leaks_full_url("unsafe-url"));
+    assert!(oauth::leaks_full_url("strict-origin, no-referrer-when-downgrade"));
+    assert!(!oauth::leaks_full_url("no-referrer-when-downgrade, strict-origin-when-cross-origin"));
+
+    let (third_party, meta) = oauth::page_referrers("https://app.test/cb", r#"<meta name="referrer" content="unsafe-url"><script src="/app.js"></script><img src="https://tracker.test/p.gif"><link rel="stylesheet" href="//cdn.test/s.css">"#);
+    assert_eq!(third_party, vec!["https://cdn.test/s.css".to_string(), "https://tracker.test/p.gif".to_string()]);
+    assert_eq!(meta.as_deref(), Some("unsafe-url"));
+}
+
+/// An authorization server that redirects `web` to any redirect_uri, accepts `token` and `code` (with a
+/// login redirect) and refuses other response types; its callback page embeds a tracker
+async fn serve(lenient: bool) -> String {
+    common::serve(move |req| {
+        let param = |k: &str| req.param(k).unwrap_or_default();
+        let registered = format!("http://{}/cb", req.header("host").unwrap_or_default());
+        match req.path() {
+            "/authorize" => {
+                let redirect = param("redirect_uri");
+                if !matches!(param("response_type").as_str(), "code" | "token") {
+                    Response::new(302).header("Location", format!("{}?error=unsupported_response_type", registered))
+                } else if !lenient && param("response_type") == "token" {
+                    Response::new(400).body("unauthorized_client")
+                } else if lenient || redirect == registered {
+                    Response::new(302).header("Location", format!("{}?code=abc&state={}", redirect, param("state")))
+                } else {
+                    Response::new(400).body("invalid redirect_uri")
+                }
+            }
+            "/cb" => {
+                let policy = if lenient { "unsafe-url" } else { "strict-origin-when-cross-origin" };
+                Response::new(200).header("Referrer-Policy", policy).body(r#"<html><img src="https://tracker.test/pixel.gif"></html>"#)
+            }
+            _ => Response::new(404),
+        }
+    }).await
+}
+
+fn flow(base: &str) -> OAuthFlow {
+    OAuthFlow {
+        authorization_endpoint: format!("{}/authorize", base),
+        client_id: Some("web".into()),
+        redirect_uri: Some(format!("{}/cb", base)),
+        response_type: Some("code".into()),
+        url: None,
+        source_file: "main.js".into(),
+    }
+}
+
+#[tokio::test]
+async fn test_lenient_server() {
+    let base = serve(true).await;
+    let tester = OAuthTester::new(Duration::from_secs(5), "127.0.0.1").unwrap();
+    let findings = tester.test(&flow(&base), true).await;
+    assert_eq!(checks(&findings), vec![OAuthCheck::OpenRedirectUri, OAuthCheck::ImplicitFlow, OAuthCheck::ReferrerLeak]);
+    assert_eq!(findings[0].severity, "HIGH");
+    assert!(findings[0].evidence_url.contains("redirect_uri=https%3A%2F%2Fevil.example%2Fcallback"));
+    assert!(findings[2].evidence_url.contains(oauth::TEST_CODE));
+    assert!(findings[2].detail.contains("tracker.test"));
+
+    // Without active tests only the code is judged
+    assert!(tester.test(&flow(&base), false).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_strict_server() {
+    let base = serve(false).await;
+    let tester = OAuthTester::new(Duration::from_secs(5), "127.0.0.1").unwrap();
+    assert!(tester.test(&flow(&base), true).await.is_empty());
+}
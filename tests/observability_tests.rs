@@ -0,0 +1,114 @@
+use api_hunter::analyze::admin_scanner::RiskLevel;
+use api_hunter::analyze::observability::{self, EndpointKind};
+use common::Response;
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn test_prometheus_and_server_status() {
+    let metrics = r#"# HELP http_server_requests_seconds Duration of HTTP server request handling
+# TYPE http_server_requests_seconds summary
+http_server_requests_seconds_count{method="GET",status="200",uri="/api/internal/users"} 12.0
+http_server_requests_seconds_count{method="POST",status="200",uri="/admin/reindex"} 1.0
+http_server_requests_seconds_count{method="GET",status="404",uri="/api/internal/users"} 3.0
+# TYPE jvm_memory_used_bytes gauge
+jvm_memory_used_bytes{area="heap",id="G1 Eden Space"} 1.2E7
+"#;
+    let (names, routes) = observability::parse_prometheus(metrics).unwrap();
+    assert_eq!(names, vec!["http_server_requests_seconds", "jvm_memory_used_bytes"]);
+    assert_eq!(routes, vec!["/api/internal/users", "/admin/reindex"]);
+    assert!(observability::parse_prometheus("<html><h1>Not Found</h1></html>").is_none());
+
+    let status = "<h1>Apache Server Status for internal.example (via 10.0.0.5)</h1><table><tr><td>10.0.0.9</td><td nowrap>api.internal</td><td nowrap>GET /billing/export?token=abc HTTP/1.1</td></tr>\
+<tr><td nowrap>POST /api/v2/login HTTP/1.1</td></tr></table>";
+    assert_eq!(observability::parse_server_status(status).unwrap(), vec!["/billing/export?token=abc", "/api/v2/login"]);
+    assert!(observability::parse_server_status("<html>Welcome</html>").is_none());
+
+    let nginx = observability::analyze("https://a.example/nginx_status", EndpointKind::NginxStatus, b"Active connections: 2 \nserver accepts handled requests\n", None).unwrap();
+    assert_eq!(nginx.risk_level, RiskLevel::Low);
+}
+
+#[test]
+fn test_actuator_parsers() {
+    let boot2 = json!({
+        "activeProfiles": ["prod"],
+        "propertySources": [
+            {"name": "systemEnvironment", "properties": {
+                "DB_PASSWORD": {"value": "******"},
+                "AWS_SECRET_ACCESS_KEY": {"value": "wJalrXUtnFEMI/K7MDENG"},
+                "PATH": {"value": "/usr/bin"}
+            }},
+            {"name": "applicationConfig", "properties": {"spring.datasource.url": {"value": "jdbc:postgresql://db:5432/app"}}}
+        ]
+    });
+    let (names, secrets) = observability::parse_actuator_env(&boot2).unwrap();
+    assert_eq!(names.len(), 4);
+    assert!(names.contains(&"spring.datasource.url".to_string()));
+    assert_eq!(secrets, vec!["AWS_SECRET_ACCESS_KEY"]);
+
+    let boot1 = json!({"profiles": [], "systemProperties": {"java.version": "1.8.0"}, "applicationConfig: [classpath:/application.properties]": {"jwt.secret": "s3cr3t-value"}});
+    let (_, secrets) = observability::parse_actuator_env(&boot1).unwrap();
+    assert_eq!(secrets, vec!["jwt.secret"]);
+    assert!(observability::parse_actuator_env(&json!({"status": "UP"})).is_none());
+
+    let mappings = json!({"contexts": {"app": {"mappings": {"dispatcherServlets": {"dispatcherServlet": [
+        {"handler": "com.shop.AdminController#purge()", "predicate": "{POST [/internal/purge]}"},
+        {"handler": "com.shop.UserController#get(Long)", "predicate": "{GET [/api/users/{id}], produces [application/json]}"}
+    ]}}}}});
+    assert_eq!(observability::parse_actuator_mappings(&mappings), vec!["/internal/purge", "/api/users/{id}"]);
+    let boot1_mappings = json!({"{[/api/orders],methods=[GET]}": {"bean": "requestMappingHandlerMapping"}});
+    assert_eq!(observability::parse_actuator_mappings(&boot1_mappings), vec!["/api/orders"]);
+
+    let index = json!({"_links": {"self": {"href": "/actuator"}, "health": {"href": "/actuator/health"}, "env": {"href": "/actuator/env"}}});
+    assert_eq!(observability::parse_actuator_links(&index).unwrap(), vec!["env", "health"]);
+}
+
+#[test]
+fn test_heap_data() {
+    let mut hprof = b"JAVA PROFILE 1.0.2\0".to_vec();
+    hprof.extend_from_slice(&[0, 0, 0, 8, 0, 0, 1, 0x8a]);
+    assert_eq!(observability::hprof_version(&hprof).as_deref(), Some("JAVA PROFILE 1.0.2"));
+    assert!(observability::hprof_version(b"<html>heapdump</html>").is_none());
+    let e = observability::analyze("https://a.example/actuator/heapdump", EndpointKind::ActuatorHeapdump, &hprof, Some(256 * 1024 * 1024)).unwrap();
+    assert_eq!(e.risk_level, RiskLevel::Critical);
+    assert_eq!(e.heap.as_deref(), Some("JAVA PROFILE 1.0.2, 256 MB"));
+
+    let heap = "heap profile: 142: 5832704 [3068: 67289088] @ heap/1048576\n1: 4194304 [1: 4194304] @ 0x4a2b1c\n";
+    assert_eq!(observability::parse_pprof_heap(heap).as_deref(), Some("Go heap profile: 142 objects in use, 5832704 bytes"));
+
+    let cmdline = observability::analyze("https://a.example/debug/pprof/cmdline", EndpointKind::PprofCmdline, b"/usr/local/bin/api\0-listen=:8080\0-db-password=hunter22\0", None).unwrap();
+    assert_eq!(cmdline.risk_level, RiskLevel::Critical);
+    assert_eq!(cmdline.secrets, vec!["db-password"]);
+    assert!(observability::analyze("https://a.example/debug/pprof/cmdline", EndpointKind::PprofCmdline, b"<!doctype html><p>Not found</p>", None).is_none());
+}
+
+/// A Spring Boot app exposing every actuator, and answering 200 with a soft-404 page everywhere else
+async fn actuator_server() -> String {
+    common::serve(|req| match req.path() {
+        "/actuator" => Response::json(200, r#"{"_links":{"self":{"href":"/actuator"},"env":{"href":"/actuator/env"},"heapdump":{"href":"/actuator/heapdump"}}}"#),
+        "/actuator/env" => Response::json(200, r#"{"propertySources":[{"name":"systemEnvironment","properties":{"SPRING_DATASOURCE_PASSWORD":{"value":"prod-db-pass"}}}]}"#),
+        "/actuator/heapdump" => Response::new(200).header("Content-Type", "application/octet-stream").body([b"JAVA PROFILE 1.0.2\0".as_slice(), &[7u8; 4096]].concat()),
+        "/actuator/prometheus" => Response::new(200).header("Content-Type", "text/plain")
+            .body("# TYPE http_server_requests_seconds summary\nhttp_server_requests_seconds_count{uri=\"/internal/jobs\"} 1.0\n"),
+        _ => Response::html(200, "<html><body>Welcome to the shop</body></html>"),
+    }).await
+}
+
+#[tokio::test]
+async fn test_scan_observability() {
+    let base = actuator_server().await;
+    let client = reqwest::Client::new();
+    let found = observability::scan_observability(&client, &format!("{}/api", base)).await.unwrap();
+
+    let kinds: Vec<EndpointKind> = found.iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, vec![EndpointKind::Prometheus, EndpointKind::ActuatorIndex, EndpointKind::ActuatorEnv, EndpointKind::ActuatorHeapdump]);
+
+    let prometheus = &found[0];
+    assert!(prometheus.url.ends_with("/actuator/prometheus"));
+    assert_eq!(prometheus.routes, vec!["/internal/jobs"]);
+    assert_eq!(found[1].risk_level, RiskLevel::High);
+    assert_eq!(found[2].risk_level, RiskLevel::Critical);
+    assert_eq!(found[2].secrets, vec!["SPRING_DATASOURCE_PASSWORD"]);
+    assert_eq!(found[3].heap.as_deref(), Some("JAVA PROFILE 1.0.2"));
+}
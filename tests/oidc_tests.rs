@@ -0,0 +1,130 @@
+use api_hunter::analyze::oidc::{self, OidcAnalyzer, OidcCheck};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use common::Response;
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+fn checks(findings: &[oidc::OidcFinding]) -> Vec<OidcCheck> {
+    findings.iter().map(|f| f.check).collect()
+}
+
+fn token(claims: serde_json::Value) -> String {
+    format!("{}.{}.c2ln", URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#), URL_SAFE_NO_PAD.encode(claims.to_string()))
+}
+
+/// Modulus of `bits` bits
+fn modulus(bits: usize) -> String {
+    let mut n = vec![0xffu8; bits / 8];
+    n[0] = 0x80;
+    URL_SAFE_NO_PAD.encode(n)
+}
+
+#[test]
+fn test_config_checks() {
+    let config = json!({
+        "issuer": "https://id.test",
+        "authorization_endpoint": "https://id.test/authorize",
+        "response_types_supported": ["code", "id_token"],
+        "id_token_signing_alg_values_supported": ["RS256", "none"],
+    });
+    assert_eq!(checks(&oidc::check_config(&config)), vec![OidcCheck::NoneAlgorithm, OidcCheck::MissingPkce]);
+
+    let plain = json!({"authorization_endpoint": "https://id.test/authorize", "code_challenge_methods_supported": ["plain"]});
+    assert_eq!(checks(&oidc::check_pkce(&plain)), vec![OidcCheck::PlainPkceOnly]);
+    let s256 = json!({"authorization_endpoint": "https://id.test/authorize", "code_challenge_methods_supported": ["plain", "S256"]});
+    assert!(oidc::check_pkce(&s256).is_empty());
+    // Implicit-only providers and bare key servers have no code flow to protect
+    assert!(oidc::check_pkce(&json!({"authorization_endpoint": "https://id.test/a", "response_types_supported": ["id_token token"]})).is_empty());
+    assert!(oidc::check_pkce(&json!({"jwks_uri": "https://id.test/jwks"})).is_empty());
+}
+
+#[test]
+fn test_jwks_checks() {
+    let jwks = json!({"keys": [
+        {"kty": "RSA", "kid": "old", "n": modulus(1024), "e": "AQAB"},
+        {"kty": "RSA", "kid": "mid", "n": modulus(1536), "e": "AQAB"},
+        {"kty": "RSA", "kid": "ok", "n": modulus(2048), "e": "AQAB"},
+        {"kty": "oct", "kid": "hmac", "k": "c2VjcmV0"},
+        {"kty": "EC", "crv": "P-256", "x": "AA", "y": "AA", "d": "AA"},
+    ]});
+    let findings = oidc::check_jwks(&jwks);
+    assert_eq!(checks(&findings), vec![OidcCheck::WeakKey, OidcCheck::WeakKey, OidcCheck::SymmetricKeyExposed, OidcCheck::PrivateKeyExposed]);
+    assert_eq!((findings[0].severity.as_str(), findings[1].severity.as_str()), ("HIGH", "MEDIUM"));
+    assert!(findings[0].detail.contains("1024-bit"));
+    assert!(findings[3].detail.contains("key #5"));
+    assert!(oidc::check_jwks(&json!({"issuer": "x"})).is_empty());
+}
+
+#[test]
+fn test_token_lifetimes() {
+    let tokens = vec![
+        token(json!({"iss": "https://id.test/", "sub": "a", "iat": 1_700_000_000, "exp": 1_700_000_000 + 3600})),
+        token(json!({"iss": "https://id.test", "sub": "b", "iat": 1_700_000_000, "exp": 1_700_000_000 + 30 * 86400})),
+        token(json!({"iss": "https://id.test", "sub": "c"})),
+        token(json!({"iss": "https://other.test", "sub": "d"})),
+        token(json!({"sub": "e"})),
+    ];
+    let findings = oidc::check_token_lifetimes(&tokens, Some("https://id.test"));
+    assert_eq!(checks(&findings), vec![OidcCheck::LongLivedToken, OidcCheck::NonExpiringToken]);
+    assert!(findings[0].detail.contains("'b'") && findings[0].detail.contains("720h"));
+    // Unknown issuer: every token counts
+    assert_eq!(oidc::check_token_lifetimes(&tokens, None).len(), 4);
+}
+
+#[test]
+fn test_client_ids() {
+    let urls = ["https://a.test/login?client_id=web-app&x=1", "https://a.test/authorize?client_id=web-app", "https://a.test/?client_id=", "https://a.test/cb?client_id=mobile"];
+    assert_eq!(oidc::client_ids(urls), vec!["mobile".to_string(), "web-app".to_string()]);
+    let probe = oidc::redirect_probe_url("https://a.test/authorize?prompt=none", "web-app").unwrap();
+    assert!(probe.starts_with("https://a.test/authorize?prompt=none&response_type=code&client_id=web-app&redirect_uri=https%3A%2F%2Fevil.example%2Fcallback"));
+}
+
+/// An identity provider whose authorization endpoint redirects to any `redirect_uri` of `web-app`
+async fn serve() -> String {
+    common::serve(|req| {
+        let issuer = format!("http://{}", req.header("host").unwrap_or_default());
+        let param = |k: &str| req.param(k).unwrap_or_default();
+        match req.path() {
+            "/.well-known/openid-configuration" => Response::json(200, json!({
+                "issuer": issuer,
+                "authorization_endpoint": format!("{}/authorize", issuer),
+                "jwks_uri": format!("{}/keys", issuer),
+                "response_types_supported": ["code"],
+                "code_challenge_methods_supported": ["S256"],
+                "id_token_signing_alg_values_supported": ["RS256"],
+            }).to_string()),
+            "/keys" => Response::json(200, json!({"keys": [{"kty": "RSA", "kid": "k1", "n": modulus(1024), "e": "AQAB"}]}).to_string()),
+            "/authorize" if param("client_id") == "web-app" => {
+                Response::new(302).header("Location", format!("{}?code=abc&state={}", param("redirect_uri"), param("state")))
+            }
+            "/authorize" => Response::json(400, r#"{"error":"invalid_client"}"#),
+            _ => Response::new(404),
+        }
+    }).await
+}
+
+#[tokio::test]
+async fn test_analyze_provider() {
+    let base = serve().await;
+    let analyzer = OidcAnalyzer::new(Duration::from_secs(5), "127.0.0.1").unwrap();
+    let tokens = vec![token(json!({"iss": base, "sub": "u1"}))];
+
+    let report = analyzer.analyze(&base, &["unknown".to_string(), "web-app".to_string()], &tokens).await.unwrap();
+    assert_eq!(report.config_url, format!("{}/.well-known/openid-configuration", base));
+    assert_eq!(report.jwks_uri, Some(format!("{}/keys", base)));
+    assert_eq!(report.keys, 1);
+    assert_eq!(checks(&report.findings), vec![OidcCheck::WeakKey, OidcCheck::OpenRedirectUri, OidcCheck::NonExpiringToken]);
+
+    // Without a known client id there is nothing to test the redirect with
+    let report = analyzer.analyze(&base, &[], &[]).await.unwrap();
+    assert_eq!(checks(&report.findings), vec![OidcCheck::WeakKey]);
+}
+
+#[tokio::test]
+async fn test_no_discovery_document() {
+    let base = common::serve(|_| Response::new(200).body("<html>app</html>")).await;
+    let analyzer = OidcAnalyzer::new(Duration::from_secs(5), "127.0.0.1").unwrap();
+    assert!(analyzer.analyze(&base, &[], &[]).await.is_none());
+}
@@ -0,0 +1,139 @@
+use api_hunter::fuzz::param_bruteforce::ParamLocation;
+use api_hunter::http_client::HttpClient;
+use api_hunter::oob::{dns, id_from_host, payloads, OobConfig, OobKind, OobServer, OobTester, Protocol};
+use api_hunter::output::clean_reporter::Severity;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn config(dns: bool) -> OobConfig {
+    OobConfig {
+        domain: "oob.test".to_string(),
+        http_listen: "127.0.0.1:0".parse().unwrap(),
+        dns_listen: dns.then(|| "127.0.0.1:0".parse().unwrap()),
+        answer_ip: Some(Ipv4Addr::new(203, 0, 113, 7)),
+    }
+}
+
+fn query(name: &str, qtype: u16) -> Vec<u8> {
+    let mut packet = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    packet
+}
+
+#[test]
+fn test_id_from_host() {
+    assert_eq!(id_from_host("abc123.oob.test", "oob.test").as_deref(), Some("abc123"));
+    assert_eq!(id_from_host("x.ABC123.oob.test.:80", "oob.test").as_deref(), Some("abc123"));
+    assert_eq!(id_from_host("oob.test", "oob.test"), None);
+    assert_eq!(id_from_host("abc123.evil-oob.test", "oob.test"), None);
+}
+
+#[test]
+fn test_dns_wire_format() {
+    let packet = query("Abc.OOB.test", dns::TYPE_A);
+    let question = dns::parse_query(&packet).unwrap();
+    assert_eq!(question.name, "abc.oob.test");
+    assert_eq!(question.id, 0x1234);
+
+    let answer = dns::answer(&packet, &question, Some(Ipv4Addr::new(203, 0, 113, 7)));
+    assert_eq!(&answer[..2], &[0x12, 0x34]);
+    assert_eq!(answer[2] & 0x84, 0x84);
+    assert_eq!(u16::from_be_bytes([answer[6], answer[7]]), 1);
+    assert_eq!(&answer[answer.len() - 4..], &[203, 0, 113, 7]);
+
+    // AAAA: empty answer
+    let packet = query("abc.oob.test", 28);
+    let answer = dns::answer(&packet, &dns::parse_query(&packet).unwrap(), Some(Ipv4Addr::LOCALHOST));
+    assert_eq!(u16::from_be_bytes([answer[6], answer[7]]), 0);
+    // Responses aren't queries
+    let mut response = query("abc.oob.test", dns::TYPE_A);
+    response[2] |= 0x80;
+    assert!(dns::parse_query(&response).is_none());
+}
+
+#[test]
+fn test_payloads_carry_callback() {
+    let host = "abc123.oob.test";
+    for p in payloads::command(host).iter().chain(&payloads::sql(host)).chain(&payloads::jndi(host)) {
+        assert!(p.contains(host), "{}", p);
+    }
+    assert!(payloads::xxe("http://abc123.oob.test/").iter().all(|b| b.contains("SYSTEM \"http://abc123.oob.test/")));
+    assert_eq!(OobKind::Ssrf.severity(false), Severity::Medium);
+    assert_eq!(OobKind::Ssrf.severity(true), Severity::High);
+    assert_eq!(OobKind::Jndi.severity(false), Severity::Critical);
+}
+
+#[tokio::test]
+async fn test_dns_server_records_lookups() {
+    let server = OobServer::start(config(true)).await.unwrap();
+    let dns_addr = server.dns_addr().unwrap();
+    let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket.send_to(&query("abc123.oob.test", dns::TYPE_A), dns_addr).await.unwrap();
+    let mut buf = [0u8; 512];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(5), socket.recv_from(&mut buf)).await.unwrap().unwrap();
+    assert_eq!(&buf[n - 4..n], &[203, 0, 113, 7]);
+
+    let interactions = server.interactions();
+    assert_eq!(interactions.len(), 1);
+    assert_eq!(interactions[0].id, "abc123");
+    assert_eq!(interactions[0].protocol, Protocol::Dns);
+    // Not registered: no finding
+    assert!(server.correlate().is_empty());
+}
+
+/// Fetches the `url` query parameter server-side (blind SSRF); the callback domain doesn't resolve here, so
+/// the request goes to the listener with the callback host in the Host header
+async fn ssrf_app(listener_addr: SocketAddr) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://127.0.0.1:{}/preview", listener.local_addr().unwrap().port());
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut sock, _)) = listener.accept().await else { break };
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 16384];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]).to_string();
+                let target = req.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let parsed = url::Url::parse(&format!("http://x{}", target)).unwrap();
+                let fetch = parsed.query_pairs().find(|(k, _)| k == "url").map(|(_, v)| v.into_owned());
+                if let Some(host) = fetch.as_deref().and_then(|u| url::Url::parse(u).ok()).and_then(|u| u.host_str().map(str::to_string)) {
+                    if let Ok(mut out) = tokio::net::TcpStream::connect(listener_addr).await {
+                        let _ = out.write_all(format!("GET / HTTP/1.1\r\nHost: {}\r\n\r\n", host).as_bytes()).await;
+                        let mut sink = Vec::new();
+                        let _ = out.read_to_end(&mut sink).await;
+                    }
+                }
+                let _ = sock.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok").await;
+            });
+        }
+    });
+    base
+}
+
+#[tokio::test]
+async fn test_ssrf_callback_is_correlated() {
+    let server = OobServer::start(config(false)).await.unwrap();
+    let base = ssrf_app(server.http_addr()).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::oob").build().unwrap());
+    let tester = OobTester::new(&http, &server, Duration::from_secs(5));
+
+    assert!(tester.inject(&format!("{}?url=x", base), "url", ParamLocation::Query).await > 0);
+    // Ignored parameter: nothing fetched
+    tester.inject(&base, "q", ParamLocation::Query).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let findings = server.correlate();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].probe.kind, OobKind::Ssrf);
+    assert_eq!(findings[0].probe.parameter.as_deref(), Some("url"));
+    assert_eq!(findings[0].severity, Severity::High);
+    assert_eq!(findings[0].interactions[0].protocol, Protocol::Http);
+    assert!(findings[0].interactions[0].data.contains(&format!("{}.oob.test", findings[0].probe.id)));
+}
@@ -0,0 +1,56 @@
+use api_hunter::analyze::open_redirect::{is_auth_flow, is_external, OpenRedirectTester};
+use api_hunter::analyze::vulnerability_scanner::VulnerabilitySeverity;
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_location_resolution() {
+    let base = "https://shop.test/login?next=x";
+    assert!(is_external(base, "https://evil.example/"));
+    assert!(is_external(base, "//evil.example/"));
+    assert!(is_external(base, "/\\evil.example/"));
+    assert!(is_external(base, "https://shop.test@evil.example/"));
+    assert!(is_external(base, "https://shop.test.evil.example/"));
+    assert!(!is_external(base, "/evil.example/"));
+    assert!(!is_external(base, "https://evil.example.shop.test/"));
+    assert!(!is_external(base, "https://notevil.example/"));
+
+    assert!(is_auth_flow("https://shop.test/oauth/authorize", "state"));
+    assert!(is_auth_flow("https://shop.test/api/x", "redirect_uri"));
+    assert!(!is_auth_flow("https://shop.test/go", "url"));
+}
+
+/// `/login?next=` redirects to anything not starting with `http` (prefix filter, so `//host` passes);
+/// `/go?url=` only to same-site paths; everything else is a plain page
+async fn redirect_app() -> String {
+    common::serve(|req| {
+        let location = match req.path() {
+            "/login" => req.param("next").filter(|v| !v.starts_with("http")),
+            "/go" => req.param("url").filter(|v| v.starts_with('/') && !v.starts_with("//") && !v.starts_with("/\\") && !v.contains('\t')),
+            _ => None,
+        };
+        match location {
+            Some(l) => Response::new(302).header("Location", l),
+            None => Response::new(200).body("ok"),
+        }
+    }).await
+}
+
+#[tokio::test]
+async fn test_redirect_parameters() {
+    let base = redirect_app().await;
+    let tester = OpenRedirectTester::new(Duration::from_secs(5)).unwrap();
+
+    let findings = tester.test(&format!("{}/login?next=/home", base)).await;
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, VulnerabilitySeverity::High);
+    assert_eq!(findings[0].category, "Open Redirect");
+    assert!(findings[0].evidence.contains(&"Payload: //evil.example/".to_string()));
+
+    // Same-site only
+    assert!(tester.test(&format!("{}/go", base)).await.is_empty());
+    // No redirect at all
+    assert!(tester.test(&format!("{}/profile", base)).await.is_empty());
+}
@@ -0,0 +1,108 @@
+use api_hunter::fuzz::param_bruteforce::{DiscoveredParam, ParamLocation, Reason};
+use api_hunter::output::openapi::{self, OpenApi};
+use api_hunter::output::RawEvent;
+use serde_json::json;
+
+fn event(url: &str, status: u16, sample: serde_json::Value) -> RawEvent {
+    let mut ev: RawEvent = serde_json::from_str(&format!(
+        r#"{{"orig_url":"{0}","final_url":"{0}","status":{1},"content_type":"application/json; charset=utf-8","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1}}"#,
+        url, status
+    )).unwrap();
+    ev.json_sample = Some(sample);
+    ev
+}
+
+#[test]
+fn test_infer_and_merge_schemas() {
+    let schema = openapi::infer_schema(&json!({
+        "id": 7, "price": 9.5, "email": "a@example.com", "created": "2026-10-17T10:00:00Z",
+        "tags": ["a"], "owner": null, "nested": {"uuid": "123e4567-e89b-12d3-a456-426614174000"}
+    }));
+    assert_eq!(schema["properties"]["id"], json!({"type": "integer"}));
+    assert_eq!(schema["properties"]["price"], json!({"type": "number"}));
+    assert_eq!(schema["properties"]["email"], json!({"type": "string", "format": "email"}));
+    assert_eq!(schema["properties"]["created"]["format"], "date-time");
+    assert_eq!(schema["properties"]["tags"], json!({"type": "array", "items": {"type": "string"}}));
+    assert_eq!(schema["properties"]["owner"], json!({"nullable": true}));
+    assert_eq!(schema["properties"]["nested"]["properties"]["uuid"]["format"], "uuid");
+    // No values end up in the spec
+    assert!(!schema.to_string().contains("a@example.com"));
+
+    let merged = openapi::merge_schemas(
+        &openapi::infer_schema(&json!([{"id": 1, "owner": null}, {"id": 2.5, "name": "x", "owner": {"id": 3}}])),
+        &openapi::infer_schema(&json!([])),
+    );
+    let item = &merged["items"];
+    assert_eq!(item["properties"]["id"], json!({"type": "number"}));
+    assert_eq!(item["properties"]["name"], json!({"type": "string"}));
+    assert_eq!(item["properties"]["owner"], json!({"type": "object", "properties": {"id": {"type": "integer"}}, "nullable": true}));
+    assert_eq!(openapi::merge_schemas(&json!({"type": "string"}), &json!({"type": "boolean"})), json!({"oneOf": [{"type": "string"}, {"type": "boolean"}]}));
+}
+
+#[test]
+fn test_path_template() {
+    let (origin, path, params) = openapi::path_template("https://api.example:8443/v1/users/42/orders/123e4567-e89b-12d3-a456-426614174000?x=1").unwrap();
+    assert_eq!(origin, "https://api.example:8443");
+    assert_eq!(path, "/v1/users/{userId}/orders/{orderId}");
+    assert_eq!(params.iter().map(|p| (p.name.as_str(), p.schema["type"].as_str().unwrap())).collect::<Vec<_>>(), vec![("userId", "integer"), ("orderId", "string")]);
+    assert_eq!(params[1].schema["format"], "uuid");
+
+    let (_, path, _) = openapi::path_template("https://api.example/categories/5/42").unwrap();
+    assert_eq!(path, "/categories/{categoryId}/{id}");
+    let (_, path, params) = openapi::path_template("https://api.example/").unwrap();
+    assert_eq!((path.as_str(), params.len()), ("/", 0));
+    assert!(openapi::path_template("ftp://api.example/x").is_none());
+}
+
+#[test]
+fn test_generate() {
+    let mut user = event("https://api.example/v1/users/1", 200, json!({"id": 1, "email": "a@example.com"}));
+    user.methods.insert("DELETE".to_string(), 204);
+    user.methods.insert("PUT".to_string(), 405);
+    user.allowed_methods = vec!["GET".to_string(), "PATCH".to_string(), "OPTIONS".to_string()];
+    let other_user = event("https://api.example/v1/users/2", 200, json!({"id": 2, "email": null, "name": "b"}));
+    let mut admin = event("https://admin.example/admin/stats?from=2026-01-01&limit=10", 401, json!({"error": "unauthorized"}));
+    admin.headers.insert("www-authenticate".to_string(), "Bearer realm=\"admin\"".to_string());
+    let mut html = event("https://api.example/", 200, json!({"_sample": "<html>"}));
+    html.content_type = Some("text/html".to_string());
+    let missing = event("https://api.example/v1/nothing", 404, json!({}));
+
+    let params = vec![
+        DiscoveredParam { url: "https://api.example/v1/users/3".to_string(), name: "debug".to_string(), location: ParamLocation::Query, reason: Reason::Reflected },
+        DiscoveredParam { url: "https://api.example/v1/users/3".to_string(), name: "role".to_string(), location: ParamLocation::Json, reason: Reason::Reflected },
+    ];
+    let spec = openapi::generate("api.example", &[user, other_user, admin, html, missing], &params);
+    // Round trip through YAML, as other tools would read it
+    let yaml = spec.to_yaml().unwrap();
+    assert!(yaml.starts_with("openapi: 3.0.3\n"), "{}", yaml);
+    assert!(!yaml.contains("a@example.com"));
+    let spec: OpenApi = serde_yaml::from_str(&yaml).unwrap();
+
+    assert_eq!(spec.servers.iter().map(|s| s.url.as_str()).collect::<Vec<_>>(), vec!["https://admin.example", "https://api.example"]);
+    assert_eq!(spec.paths.keys().collect::<Vec<_>>(), vec!["/", "/admin/stats", "/v1/users/{userId}"]);
+    assert_eq!(spec.operation_count(), 6);
+
+    let users = &spec.paths["/v1/users/{userId}"];
+    assert_eq!(users.servers.len(), 1);
+    assert_eq!(users.parameters[0].name, "userId");
+    assert_eq!(users.operations.keys().collect::<Vec<_>>(), vec!["delete", "get", "patch", "post"]);
+    let get = &users.operations["get"];
+    assert_eq!(get.operation_id, "getV1UsersByUserId");
+    assert_eq!(get.parameters[0].name, "debug");
+    let schema = get.responses["200"].content["application/json"].schema.as_ref().unwrap();
+    assert_eq!(schema["properties"]["email"], json!({"type": "string", "format": "email", "nullable": true}));
+    assert_eq!(schema["properties"]["name"], json!({"type": "string"}));
+    assert!(users.operations["delete"].responses["204"].content.is_empty());
+    assert_eq!(users.operations["patch"].responses.keys().collect::<Vec<_>>(), vec!["default"]);
+    let body = users.operations["post"].request_body.as_ref().unwrap();
+    assert!(body.content["application/json"].schema.as_ref().unwrap()["properties"].get("role").is_some());
+
+    let stats = &spec.paths["/admin/stats"].operations["get"];
+    assert_eq!(stats.parameters.iter().map(|p| (p.name.as_str(), p.schema["type"].as_str().unwrap())).collect::<Vec<_>>(), vec![("from", "string"), ("limit", "integer")]);
+    assert!(stats.security[0].contains_key("bearerAuth"));
+    assert_eq!(spec.components.unwrap().security_schemes["bearerAuth"], json!({"type": "http", "scheme": "bearer"}));
+
+    let root = &spec.paths["/"].operations["get"];
+    assert_eq!(root.operation_id, "getRoot");
+    assert!(root.responses["200"].content["text/html"].schema.is_none());
+}
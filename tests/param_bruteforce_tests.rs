@@ -0,0 +1,61 @@
+use api_hunter::fuzz::param_bruteforce::{Baseline, ParamBruteforceConfig, ParamBruteforcer, ParamLocation, Reason, Snapshot};
+use api_hunter::http_client::HttpClient;
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+fn sent(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_baseline_ignores_echoed_request() {
+    // The page echoes the query string; the names sent must not make the body look different
+    let page = |q: &str| Snapshot { status: 200, body: format!("{{\"self\":\"/api/items?{}\",\"items\":[1,2,3]}}", q) };
+    let junk_a = sent(&[("ah01", "ah11111111")]);
+    let junk_b = sent(&[("ah02", "ah22222222")]);
+    let base = Baseline::new((&page("ah01=ah11111111"), &junk_a), (&page("ah02=ah22222222"), &junk_b)).unwrap();
+    assert!(base.reflects);
+
+    let chunk = sent(&[("page", "ah33333333"), ("q", "ah44444444")]);
+    assert_eq!(base.compare(&page("page=ah33333333&q=ah44444444"), &chunk), None);
+    assert_eq!(base.compare(&Snapshot { status: 400, body: String::new() }, &chunk), Some(Reason::Status));
+}
+
+/// `/items` honours `debug` (different body) and echoes `callback`; POST accepts `role`
+async fn api_server() -> String {
+    let base = common::serve(|req| {
+        let body = if req.method == "POST" {
+            let v: serde_json::Value = serde_json::from_slice(&req.body).unwrap_or_default();
+            match v.get("role") {
+                Some(_) => "{\"error\":\"role is read-only\"}".to_string(),
+                None => "{\"created\":true}".to_string(),
+            }
+        } else if let Some(cb) = req.param("callback") {
+            format!("{}([1,2,3])", cb)
+        } else if req.param("debug").is_some() {
+            "{\"items\":[1,2,3],\"debug\":{\"sql\":\"SELECT * FROM items\"}}".to_string()
+        } else {
+            "{\"items\":[1,2,3]}".to_string()
+        };
+        Response::json(200, body)
+    }).await;
+    format!("{}/items", base)
+}
+
+#[tokio::test]
+async fn test_bruteforce_finds_accepted_names() {
+    let url = api_server().await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::param_bruteforce").build().unwrap());
+    let mut words: Vec<String> = (0..60).map(|i| format!("unused{}", i)).collect();
+    words.extend(["debug", "callback", "role"].map(String::from));
+    let config = ParamBruteforceConfig { words, chunk_size: 32, timeout: Duration::from_secs(5) };
+    let bruteforcer = ParamBruteforcer::new(&http, config);
+
+    let query: Vec<(String, Reason)> = bruteforcer.run(&url, ParamLocation::Query).await.into_iter().map(|p| (p.name, p.reason)).collect();
+    assert_eq!(query, vec![("callback".to_string(), Reason::Reflected), ("debug".to_string(), Reason::Length)]);
+
+    let body: Vec<String> = bruteforcer.run(&url, ParamLocation::Json).await.into_iter().map(|p| p.name).collect();
+    assert_eq!(body, vec!["role".to_string()]);
+}
@@ -0,0 +1,56 @@
+use api_hunter::fuzz::path_bruteforce::{paths, BruteforceConfig, PathBruteforcer};
+use api_hunter::http_client::HttpClient;
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+fn words(list: &[&str]) -> Vec<String> {
+    list.iter().map(|w| w.to_string()).collect()
+}
+
+#[test]
+fn test_paths_with_extensions() {
+    let p = paths(&words(&["admin", "/backup", "swagger.json", "admin", "# comment x"]), &words(&["json", ".bak"]));
+    assert_eq!(p, words(&["admin", "admin.json", "admin.bak", "backup", "backup.json", "backup.bak", "swagger.json"]));
+}
+
+/// Catch-all SPA: everything unknown is the index page. `/api/` is a directory holding `users`,
+/// `/admin` redirects to `/admin/` (empty apart from the index page), and `/config.bak` exists.
+async fn spa_server() -> String {
+    common::serve(|req| match req.path() {
+        "/api" | "/api/" => Response::new(200).body("{\"version\":1}"),
+        "/api/users" => Response::new(401).body("{\"error\":\"login required\"}"),
+        "/admin" => Response::new(301).header("Location", "/admin/"),
+        "/admin/" => Response::new(403).body("forbidden"),
+        "/config.bak" => Response::new(200).body("db_password=secret"),
+        p if p.starts_with("/api/") => Response::new(404).body("{\"error\":\"not found\"}"),
+        _ => Response::new(200).body("<html><div id=\"root\"></div><script src=\"/main.js\"></script></html>"),
+    }).await
+}
+
+#[tokio::test]
+async fn test_bruteforce_recurses_and_skips_catch_all() {
+    let base = spa_server().await;
+    let client = api_hunter::http_client::builder("tests::path_bruteforce")
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let http = HttpClient::new(client);
+    let config = BruteforceConfig {
+        words: words(&["api", "admin", "users", "config", "login"]),
+        extensions: words(&["bak"]),
+        depth: 2,
+        timeout: Duration::from_secs(5),
+        ..BruteforceConfig::default()
+    };
+    let hits = PathBruteforcer::new(&http, config).run(&base).await;
+    let found: Vec<(String, u16, bool)> = hits.iter().map(|h| (h.url.trim_start_matches(&base).to_string(), h.status, h.directory)).collect();
+    assert_eq!(found, vec![
+        ("/admin".to_string(), 301, true),
+        ("/api".to_string(), 200, true),
+        ("/api/users".to_string(), 401, true),
+        ("/config.bak".to_string(), 200, false),
+    ]);
+    assert_eq!(hits.iter().find(|h| h.url.ends_with("/api/users")).unwrap().depth, 2);
+}
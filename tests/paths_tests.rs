@@ -0,0 +1,49 @@
+use api_hunter::output::paths::{artifact, endpoint_file, host_dir, relative_path, sanitize_component};
+use std::path::{Component, Path};
+
+#[test]
+fn sanitizes_windows_unsafe_names() {
+    assert_eq!(sanitize_component("api.example.com:8443"), "api.example.com_8443");
+    assert_eq!(sanitize_component("a<b>c|d?e*f\"g"), "a_b_c_d_e_f_g");
+    assert_eq!(sanitize_component("name. "), "name");
+    assert_eq!(sanitize_component(".."), "_");
+    assert_eq!(sanitize_component("CON"), "_CON");
+    assert_eq!(sanitize_component("nul.json"), "_nul.json");
+    assert_eq!(sanitize_component("console.json"), "console.json");
+}
+
+#[test]
+fn long_names_are_truncated_but_distinct() {
+    let a = sanitize_component(&format!("{}a", "x".repeat(300)));
+    let b = sanitize_component(&format!("{}b", "x".repeat(300)));
+    assert!(a.len() <= 100);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn artifacts_and_hosts_stay_in_out_dir() {
+    let out = Path::new("results");
+    assert_eq!(artifact(out, "analysis_results.json"), out.join("analysis_results.json"));
+    assert_eq!(host_dir(out, "API.Example.com:8080"), out.join("api.example.com_8080"));
+
+    let p = relative_path("../../etc/./passwd");
+    assert!(p.components().all(|c| matches!(c, Component::Normal(_))));
+    assert_eq!(p, Path::new("etc").join("passwd"));
+}
+
+#[test]
+fn endpoint_files_do_not_collide() {
+    let dir = Path::new("bodies");
+    let a = endpoint_file(dir, "https://api.example.com/a/b", "json");
+    let b = endpoint_file(dir, "https://api.example.com/a_b", "json");
+    let c = endpoint_file(dir, "https://api.example.com/a/b?x=1", "json");
+    assert_ne!(a, b);
+    assert_ne!(a, c);
+    assert!(a.starts_with(dir.join("api.example.com")));
+    assert!(a.file_name().unwrap().to_str().unwrap().starts_with("a_b-"));
+    assert_eq!(a, endpoint_file(dir, "https://api.example.com/a/b", ".json"));
+
+    let root = endpoint_file(dir, "https://api.example.com:8443/", "txt");
+    assert!(root.starts_with(dir.join("api.example.com_8443")));
+    assert!(root.to_str().unwrap().contains("root-"));
+}
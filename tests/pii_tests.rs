@@ -0,0 +1,55 @@
+use api_hunter::enrich::pii::{classify_value, luhn_valid, scan_event, scan_json, scan_text, PiiKind};
+use api_hunter::output::RawEvent;
+use api_hunter::probe::body::StoredBody;
+use serde_json::json;
+
+#[test]
+fn classifies_pii_values() {
+    assert_eq!(classify_value("email", "jane.doe@example.com"), Some(PiiKind::Email));
+    assert_eq!(classify_value("card", "4111 1111 1111 1111"), Some(PiiKind::CreditCard));
+    assert_eq!(classify_value("iban", "DE89370400440532013000"), Some(PiiKind::Iban));
+    assert_eq!(classify_value("ssn", "123-45-6789"), Some(PiiKind::Ssn));
+    assert_eq!(classify_value("mobile", "0664 1234567"), Some(PiiKind::Phone));
+    // Numeric IDs can pass Luhn but are not cards
+    assert_eq!(classify_value("orderId", "4111111111111111"), None);
+    assert!(!luhn_valid("4111111111111112"));
+}
+
+#[test]
+fn scans_nested_json() {
+    let v = json!({"data": {"users": [{"id": 1, "email": "a.b@example.org", "phone": "+43 660 1234567"}]}});
+    let found = scan_json(&v);
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().any(|m| m.path == "data.users[0].email" && !m.sample.contains("a.b@")));
+}
+
+#[test]
+fn scans_free_text() {
+    let html = "<p>Contact: jane.doe@example.com, +43 660 1234567</p><img src=\"logo@2x.png\">\
+                <td>DE89 3704 0044 0532 0130 00</td><td>123-45-6789</td><td>4111-1111-1111-1111</td>\
+                <span>order 4111111111111112</span><span>id 1234567890123456</span>";
+    let found = scan_text(html);
+    let kinds: Vec<PiiKind> = found.iter().map(|m| m.kind).collect();
+    assert_eq!(kinds, vec![PiiKind::Email, PiiKind::Phone, PiiKind::Iban, PiiKind::Ssn, PiiKind::CreditCard]);
+    assert!(found[0].path.starts_with('@') && !found[0].sample.contains("jane.doe"));
+    assert!(scan_text("<div>No personal data here, version 1.2.3</div>").is_empty());
+}
+
+#[test]
+fn scans_probe_results() {
+    let mut ev: RawEvent = serde_json::from_value(json!({
+        "orig_url": "https://api.example.com/users", "final_url": "https://api.example.com/users", "status": 200,
+        "content_type": "application/json", "server": null, "content_length": null, "response_ms": null,
+        "tls_issuer": null, "is_graphql": false, "score": 1,
+        "json_sample": {"users": [{"email": "a@example.org"}, {"email": "a@example.org"}, {"ssn": "078-05-1120"}]},
+    })).unwrap();
+    // The same value twice counts once
+    let found = scan_event(&ev);
+    assert_eq!(found.iter().map(|m| m.kind).collect::<Vec<_>>(), vec![PiiKind::Email, PiiKind::Ssn]);
+
+    // Non-JSON responses: the text sample, or the stored body when there is one
+    ev.json_sample = Some(json!({"_sample": "Call +1 415 555 0100"}));
+    assert_eq!(scan_event(&ev).iter().map(|m| m.kind).collect::<Vec<_>>(), vec![PiiKind::Phone]);
+    ev.body = StoredBody::capture(b"<html>mail admin@example.net</html>", false, false);
+    assert_eq!(scan_event(&ev).iter().map(|m| m.kind).collect::<Vec<_>>(), vec![PiiKind::Email]);
+}
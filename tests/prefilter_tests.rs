@@ -0,0 +1,21 @@
+use api_hunter::probe::prefilter::{verdict, Verdict};
+
+#[test]
+fn test_not_found_is_dropped() {
+    assert_eq!(verdict("https://example.com/api/users", 404, "https://example.com/api/users", None), Verdict::NotFound);
+    assert_eq!(verdict("https://example.com/api/users", 410, "https://example.com/api/users", None), Verdict::NotFound);
+    assert_eq!(verdict("https://example.com/api/users", 401, "https://example.com/api/users", None), Verdict::Keep);
+}
+
+#[test]
+fn test_redirect_to_home() {
+    // Unfollowed redirect
+    assert_eq!(verdict("https://example.com/api/old", 302, "https://example.com/api/old", Some("/")), Verdict::RedirectHome);
+    // Followed redirect
+    assert_eq!(verdict("https://example.com/api/old", 200, "https://example.com/index.html", None), Verdict::RedirectHome);
+    // Redirects elsewhere are kept: login pages, other hosts, the home page itself
+    assert_eq!(verdict("https://example.com/admin", 302, "https://example.com/admin", Some("/login?next=/admin")), Verdict::Keep);
+    assert_eq!(verdict("https://example.com/api/old", 301, "https://example.com/api/old", Some("https://other.com/")), Verdict::Keep);
+    assert_eq!(verdict("https://example.com/", 200, "https://example.com/", None), Verdict::Keep);
+    assert_eq!(verdict("https://example.com/api/v1", 301, "https://example.com/api/v1", Some("/api/v1/")), Verdict::Keep);
+}
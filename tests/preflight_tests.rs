@@ -0,0 +1,93 @@
+use api_hunter::output::clean_reporter::HostSummary;
+use api_hunter::probe::preflight::{host_ports, retain_reachable, HostState, HostStatus, Preflight};
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn status(host: &str, state: HostState) -> HostStatus {
+    HostStatus { host: host.to_string(), state, addresses: vec![], ports: vec![], tls: None, tls_error: None, banner: None }
+}
+
+#[test]
+fn collects_default_and_explicit_ports() {
+    let urls = vec![
+        "https://api.example.com/v1/users".to_string(),
+        "http://API.example.com:8080/health".to_string(),
+        "https://old.example.com/api".to_string(),
+        "ws://ws.example.com/socket".to_string(),
+    ];
+    let targets = host_ports(&urls);
+    assert_eq!(targets.len(), 2);
+    let api: Vec<(String, u16)> = targets["api.example.com"].iter().cloned().collect();
+    assert_eq!(api, vec![("http".to_string(), 80), ("http".to_string(), 8080), ("https".to_string(), 443)]);
+}
+
+#[test]
+fn skips_only_unreachable_hosts() {
+    let urls = vec![
+        "https://api.example.com/v1".to_string(),
+        "https://old.example.com/api".to_string(),
+        "https://gone.example.com/api".to_string(),
+        "https://tls.example.com/api".to_string(),
+        "https://unchecked.example.com/api".to_string(),
+    ];
+    let statuses = vec![
+        status("api.example.com", HostState::Alive),
+        status("old.example.com", HostState::Dead),
+        status("gone.example.com", HostState::Unresolved),
+        status("tls.example.com", HostState::NoHttp),
+    ];
+    let (kept, skipped) = retain_reachable(urls, &statuses);
+    assert_eq!(skipped, 2);
+    assert_eq!(kept, vec![
+        "https://api.example.com/v1".to_string(),
+        "https://tls.example.com/api".to_string(),
+        "https://unchecked.example.com/api".to_string(),
+    ]);
+}
+
+#[test]
+fn report_distinguishes_dead_hosts_from_empty_ones() {
+    let describe = |state, endpoints| HostSummary { host: "h".to_string(), state, endpoints }.describe();
+    assert_eq!(describe(HostState::Dead, 0), "dead host (no port open)");
+    assert_eq!(describe(HostState::Alive, 0), "no API endpoints found");
+    assert_eq!(describe(HostState::Alive, 3), "3 endpoints");
+    assert!(describe(HostState::Unresolved, 0).starts_with("unresolved"));
+}
+
+#[tokio::test]
+async fn checks_local_ports() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let open_port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut sock, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                if let Ok(n) = sock.read(&mut buf).await {
+                    if n > 0 {
+                        let _ = sock.write_all(b"HTTP/1.1 404 Not Found\r\nServer: test-banner\r\nContent-Length: 0\r\n\r\n").await;
+                    }
+                }
+            });
+        }
+    });
+    let closed_port = {
+        let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        l.local_addr().unwrap().port()
+    };
+
+    let checker = Preflight::new(Duration::from_secs(2), 4).unwrap();
+    let alive = checker.check_host("127.0.0.1", &BTreeSet::from([("http".to_string(), open_port)])).await;
+    assert_eq!(alive.state, HostState::Alive);
+    assert_eq!(alive.banner.as_deref(), Some("test-banner"));
+    assert_eq!(alive.ports[0].status, Some(404));
+
+    let dead = checker.check_host("127.0.0.1", &BTreeSet::from([("http".to_string(), closed_port)])).await;
+    assert_eq!(dead.state, HostState::Dead);
+    assert!(!dead.is_reachable());
+
+    // Open port that doesn't speak TLS
+    let no_tls = checker.check_host("127.0.0.1", &BTreeSet::from([("https".to_string(), open_port)])).await;
+    assert_eq!(no_tls.state, HostState::NoHttp);
+    assert_eq!(no_tls.tls, Some(false));
+}
@@ -0,0 +1,38 @@
+use api_hunter::scoring::priority::{score_candidate, ProbeQueue};
+
+#[test]
+fn test_candidate_scores() {
+    let admin = score_candidate("https://example.com/internal/admin/users");
+    let versioned = score_candidate("https://example.com/api/v1/products?page=2");
+    let plain = score_candidate("https://example.com/about");
+    assert!(admin > versioned);
+    assert!(versioned > plain);
+    assert!(score_candidate("https://example.com/api/users/42") > score_candidate("https://example.com/api/users"));
+    assert!(score_candidate("https://example.com/img/logo.png") < 0);
+    // Substrings don't count: "environment" is not "env"
+    assert_eq!(score_candidate("https://example.com/environment"), 0);
+}
+
+#[test]
+fn test_queue_order() {
+    let mut queue: ProbeQueue = [
+        "https://example.com/about",
+        "https://example.com/static/app.css",
+        "https://example.com/actuator/env",
+        "https://example.com/contact",
+        "https://example.com/api/v2/orders",
+    ].iter().map(|s| s.to_string()).collect();
+    queue.push("https://example.com/imported".to_string(), 1000);
+    assert_eq!(queue.len(), 6);
+
+    let order: Vec<String> = queue.collect();
+    assert_eq!(order, vec![
+        "https://example.com/imported",
+        "https://example.com/actuator/env",
+        "https://example.com/api/v2/orders",
+        // Equal scores keep the discovery order
+        "https://example.com/about",
+        "https://example.com/contact",
+        "https://example.com/static/app.css",
+    ]);
+}
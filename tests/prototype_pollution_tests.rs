@@ -0,0 +1,87 @@
+use api_hunter::fuzz::prototype_pollution::{framework_hint, indented, reflects, PrototypePollutionTester, Technique, Vector};
+use api_hunter::http_client::HttpClient;
+use api_hunter::output::clean_reporter::Severity;
+use common::Response;
+use parking_lot::Mutex;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_payloads_and_signals() {
+    let body = json!({"name": "bob"});
+    assert_eq!(Vector::Proto.inject(&body, json!({"a": 1})).unwrap(), json!({"name": "bob", "__proto__": {"a": 1}}));
+    assert_eq!(Vector::ConstructorPrototype.inject(&body, json!({"a": 1})).unwrap(), json!({"name": "bob", "constructor": {"prototype": {"a": 1}}}));
+    assert!(Vector::Proto.inject(&json!([1]), json!({})).is_none());
+
+    assert!(indented("{\n          \"a\": 1\n}", 10));
+    assert!(!indented("{\n  \"a\": {\n            \"b\": 1\n  }\n}", 10));
+    assert!(reflects(r#"{"user":{"name":"bob","ahx":"p1"}}"#, Vector::Proto, "ahx", "p1"));
+    assert!(!reflects(r#"{"user":{"__proto__":{"ahx":"p1"}}}"#, Vector::Proto, "ahx", "p1"));
+
+    let mut headers = BTreeMap::new();
+    headers.insert("X-Powered-By".to_string(), "Express".to_string());
+    assert_eq!(framework_hint(&headers).as_deref(), Some("Express.js"));
+    let mut headers = BTreeMap::new();
+    headers.insert("etag".to_string(), "W/\"1c-abcdef\"".to_string());
+    assert_eq!(framework_hint(&headers).as_deref(), Some("Node.js"));
+    assert_eq!(framework_hint(&BTreeMap::new()), None);
+}
+
+/// Profile endpoint echoing the merged body. When `vulnerable`, `__proto__` is merged into a process-wide
+/// prototype that every echoed object inherits from, and `json spaces` on it indents the output.
+async fn profile_app(vulnerable: bool) -> String {
+    let proto: Arc<Mutex<Map<String, Value>>> = Arc::new(Mutex::new(Map::new()));
+    let base = common::serve(move |req| {
+        let mut input: Map<String, Value> = serde_json::from_slice(&req.body).unwrap_or_default();
+        if vulnerable {
+            if let Some(Value::Object(props)) = input.remove("__proto__") {
+                proto.lock().extend(props);
+            }
+        }
+        let mut profile = proto.lock().clone();
+        profile.retain(|k, _| k != "json spaces");
+        profile.extend(input);
+        let out = json!({"ok": true, "profile": profile});
+        let spaces = proto.lock().get("json spaces").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let rendered = if spaces > 0 {
+            let mut w = Vec::new();
+            let indent = " ".repeat(spaces);
+            let fmt = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            serde::Serialize::serialize(&out, &mut serde_json::Serializer::with_formatter(&mut w, fmt)).unwrap();
+            String::from_utf8(w).unwrap()
+        } else {
+            out.to_string()
+        };
+        Response::new(200).header("X-Powered-By", "Express").header("Content-Type", "application/json").body(rendered)
+    }).await;
+    format!("{}/api/profile", base)
+}
+
+#[tokio::test]
+async fn test_json_spaces_pollution_is_found_and_reset() {
+    let url = profile_app(true).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::prototype_pollution").build().unwrap());
+    let tester = PrototypePollutionTester::new(&http, Duration::from_secs(5));
+    let body = json!({"name": "bob"});
+    let finding = tester.test("POST", &url, &body, Some("Express.js")).await.expect("polluted");
+    assert_eq!(finding.technique, Technique::JsonSpaces);
+    assert_eq!(finding.vector, Vector::Proto);
+    assert_eq!(finding.severity, Severity::High);
+    assert_eq!(finding.framework.as_deref(), Some("Express.js"));
+
+    // Output is compact again
+    let text = reqwest::Client::new().post(&url).json(&body).send().await.unwrap().text().await.unwrap();
+    assert!(!text.contains('\n'));
+}
+
+#[tokio::test]
+async fn test_plain_keys_are_clean() {
+    let url = profile_app(false).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::prototype_pollution").build().unwrap());
+    let tester = PrototypePollutionTester::new(&http, Duration::from_secs(5));
+    assert!(tester.test("POST", &url, &json!({"name": "bob"}), None).await.is_none());
+}
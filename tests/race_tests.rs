@@ -0,0 +1,103 @@
+use api_hunter::fuzz::race::{self, parse_response, RaceConfig, RaceRequest};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn test_request_bytes() {
+    let request = RaceRequest {
+        method: "post".to_string(),
+        url: "http://shop.test:8080/api/redeem?x=1".to_string(),
+        headers: vec![("Authorization".to_string(), "Bearer AAA".to_string()), ("Content-Length".to_string(), "999".to_string())],
+        body: Some("{\"code\":\"A\"}".to_string()),
+    };
+    api_hunter::identify::configure(&["X-Bug-Bounty: hunter".to_string()]).unwrap();
+    let text = String::from_utf8(request.to_bytes().unwrap()).unwrap();
+    assert!(text.starts_with("POST /api/redeem?x=1 HTTP/1.1\r\nHost: shop.test:8080\r\nx-bug-bounty: hunter\r\nAuthorization: Bearer AAA\r\n"));
+    assert!(api_hunter::identify::audit().clients.iter().any(|c| c.component == "fuzz::race" && c.headers == ["x-bug-bounty"]));
+    assert!(text.contains("Content-Length: 12\r\n") && !text.contains("999"));
+    assert!(text.ends_with("Connection: close\r\n\r\n{\"code\":\"A\"}"));
+
+    let config = RaceConfig { success_match: Some("ok".to_string()), ..RaceConfig::default() };
+    assert!(config.is_success(201, "ok"));
+    assert!(!config.is_success(200, "already used"));
+    assert!(!config.is_success(409, "ok"));
+    assert_eq!(parse_response(b"HTTP/1.1 409 Conflict\r\nA: b\r\n\r\nused"), Some((409, "used".to_string())));
+}
+
+/// Coupon that can be redeemed once. `atomic` checks and sets in one step; otherwise the check and the
+/// update are 50 ms apart, like a read, a remote call and a write.
+async fn coupon_app(atomic: bool) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://127.0.0.1:{}/redeem", listener.local_addr().unwrap().port());
+    let redeemed = Arc::new(AtomicBool::new(false));
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut sock, _)) = listener.accept().await else { break };
+            let redeemed = redeemed.clone();
+            tokio::spawn(async move {
+                let mut data = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Whole request: head and the Content-Length body
+                loop {
+                    let text = String::from_utf8_lossy(&data).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let len: usize = head.lines()
+                            .find_map(|l| l.strip_prefix("Content-Length: "))
+                            .and_then(|v| v.trim().parse().ok())
+                            .unwrap_or(0);
+                        if body.len() >= len {
+                            break;
+                        }
+                    }
+                    match sock.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let won = if atomic {
+                    !redeemed.swap(true, Ordering::SeqCst)
+                } else if !redeemed.load(Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    redeemed.store(true, Ordering::SeqCst);
+                    true
+                } else {
+                    false
+                };
+                let (status, body) = if won { ("200 OK", "{\"redeemed\":true}") } else { ("409 Conflict", "{\"error\":\"used\"}") };
+                let resp = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+                let _ = sock.write_all(resp.as_bytes()).await;
+            });
+        }
+    });
+    base
+}
+
+fn redeem(url: String) -> RaceRequest {
+    RaceRequest {
+        method: "POST".to_string(),
+        url,
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body: Some("{\"code\":\"WELCOME\"}".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_non_atomic_redemption_races() {
+    let config = RaceConfig { count: 10, timeout: Duration::from_secs(5), success_match: Some("redeemed".to_string()), ..RaceConfig::default() };
+    let result = race::run(&redeem(coupon_app(false).await), &config).await.unwrap();
+    assert_eq!(result.failed, 0);
+    assert!(result.successes > 1, "{:?}", result.status_counts);
+    assert!(result.vulnerable);
+    assert_eq!(result.severity.as_deref(), Some("high"));
+}
+
+#[tokio::test]
+async fn test_atomic_redemption_is_clean() {
+    let config = RaceConfig { count: 10, timeout: Duration::from_secs(5), ..RaceConfig::default() };
+    let result = race::run(&redeem(coupon_app(true).await), &config).await.unwrap();
+    assert_eq!(result.successes, 1);
+    assert_eq!(result.status_counts.get(&409), Some(&9));
+    assert!(!result.vulnerable);
+}
@@ -0,0 +1,68 @@
+use api_hunter::http_client::HttpClient;
+use api_hunter::probe::rate_limit_bypass::{ip_header_variants, url_variants, BypassKind, RateLimitBypassTester, IP_HEADERS};
+use api_hunter::output::clean_reporter::Severity;
+use common::Response;
+
+mod common;
+
+#[test]
+fn test_variants() {
+    let headers = ip_header_variants("https://api.test/v2/login");
+    assert_eq!(headers.len(), IP_HEADERS.len());
+    assert!(headers.iter().all(|v| v.kind == BypassKind::IpHeader && v.url == "https://api.test/v2/login"));
+    let forwarded = headers.iter().find(|v| v.headers[0].0 == "Forwarded").unwrap();
+    assert!(forwarded.headers[0].1.starts_with("for="));
+
+    let urls: Vec<_> = url_variants("https://api.test/v2/login?a=1").into_iter().map(|v| (v.technique, v.url)).collect();
+    let has = |t: &str, u: &str| urls.iter().any(|(tech, url)| tech == t && url == u);
+    assert!(has("upper-case path", "https://api.test/V2/LOGIN?a=1"));
+    assert!(has("capitalized last segment", "https://api.test/v2/Login?a=1"));
+    assert!(has("trailing slash", "https://api.test/v2/login/?a=1"));
+    assert!(has("double slash", "https://api.test//v2/login?a=1"));
+    assert!(has("version v1", "https://api.test/v1/login?a=1"));
+    assert!(has("version v3", "https://api.test/v3/login?a=1"));
+    assert!(has("no version", "https://api.test/login?a=1"));
+    assert!(urls.iter().any(|(t, u)| t == "padding parameter" && u.starts_with("https://api.test/v2/login?a=1&_")));
+
+    let unversioned: Vec<_> = url_variants("http://api.test/api/items/").into_iter().map(|v| v.url).collect();
+    assert!(unversioned.contains(&"http://api.test/api/v1/items/".to_string()));
+    assert!(unversioned.contains(&"http://api.test/api/items".to_string()));
+}
+
+/// Limits every request on `/api/v1/login` by exact path, except those carrying `X-Forwarded-For`
+/// when `trust_xff` is set
+async fn limited_app(trust_xff: bool) -> String {
+    let base = common::serve(move |req| {
+        let xff = req.header("x-forwarded-for").is_some();
+        if req.path() == "/api/v1/login" && !(trust_xff && xff) {
+            Response::new(429)
+        } else if req.path().eq_ignore_ascii_case("/api/v1/login") {
+            Response::new(200)
+        } else {
+            Response::new(404)
+        }
+    }).await;
+    format!("{}/api/v1/login", base)
+}
+
+#[tokio::test]
+async fn test_bypasses_are_reported() {
+    let url = limited_app(true).await;
+    let http = HttpClient::new(reqwest::Client::new());
+    let report = RateLimitBypassTester::new(&http).test(&url).await;
+    assert!(report.limited);
+    let techniques: Vec<_> = report.bypasses.iter().map(|b| b.variant.technique.as_str()).collect();
+    assert!(techniques.contains(&"X-Forwarded-For rotation"));
+    assert!(techniques.contains(&"upper-case path"));
+    assert!(!techniques.contains(&"X-Real-IP rotation"));
+    assert!(!techniques.contains(&"version v2"));
+    assert_eq!(report.severity, Some(Severity::High));
+}
+
+#[tokio::test]
+async fn test_header_not_trusted() {
+    let url = limited_app(false).await;
+    let http = HttpClient::new(reqwest::Client::new());
+    let report = RateLimitBypassTester::new(&http).test(&url).await;
+    assert!(report.bypasses.iter().all(|b| b.variant.kind != BypassKind::IpHeader));
+}
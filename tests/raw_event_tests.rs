@@ -0,0 +1,45 @@
+use api_hunter::analyze::security_headers::SecurityHeaderAnalysis;
+use api_hunter::output::writer_jsonl::{collect_headers, RawEvent};
+use api_hunter::waf::{WafDetector, WafType};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+fn event(headers: &[(&str, &str)]) -> RawEvent {
+    let mut map = HeaderMap::new();
+    for (k, v) in headers {
+        map.append(HeaderName::from_bytes(k.as_bytes()).unwrap(), HeaderValue::from_str(v).unwrap());
+    }
+    let line = r#"{"orig_url":"https://example.com/api","final_url":"https://example.com/api","status":200,"content_type":null,"server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":0,"notes":[]}"#;
+    // Lines written before headers/attempts existed still load
+    let mut ev: RawEvent = serde_json::from_str(line).unwrap();
+    assert_eq!(ev.attempts, 1);
+    ev.headers = collect_headers(&map);
+    ev
+}
+
+#[test]
+fn test_headers_are_captured_and_joined() {
+    let ev = event(&[
+        ("Cache-Control", "no-store"),
+        ("Vary", "Origin"),
+        ("Vary", "Accept-Encoding"),
+        ("Set-Cookie", "a=1; Expires=Wed, 21 Oct 2026 07:28:00 GMT"),
+        ("Set-Cookie", "b=2"),
+    ]);
+    assert_eq!(ev.header("cache-control"), Some("no-store"));
+    assert_eq!(ev.header("VARY"), Some("Origin, Accept-Encoding"));
+    assert_eq!(ev.header("set-cookie"), Some("a=1; Expires=Wed, 21 Oct 2026 07:28:00 GMT\nb=2"));
+
+    let json = serde_json::to_string(&ev).unwrap();
+    let back: RawEvent = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.headers, ev.headers);
+}
+
+#[test]
+fn test_downstream_modules_use_probe_headers() {
+    let ev = event(&[("cf-ray", "8a1b2c3d4e5f-FRA"), ("server", "cloudflare"), ("strict-transport-security", "max-age=31536000")]);
+    assert_eq!(WafDetector::new().detect_event(&ev).waf_type, WafType::Cloudflare);
+
+    let security = SecurityHeaderAnalysis::from_event(&ev);
+    assert!(security.has_hsts);
+    assert!(!security.has_csp);
+}
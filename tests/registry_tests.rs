@@ -0,0 +1,107 @@
+use api_hunter::output::results_manager::{format_timestamp, RUNS_DIR};
+use api_hunter::output::{ArtifactRegistry, RetentionPolicy};
+use std::fs;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("apihunter-registry-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn formats_utc_timestamps() {
+    assert_eq!(format_timestamp(0), "19700101T000000Z");
+    assert_eq!(format_timestamp(1_700_000_000), "20231114T221320Z");
+    assert_eq!(format_timestamp(951_782_400), "20000229T000000Z");
+}
+
+#[test]
+fn archives_previous_runs_instead_of_deleting() {
+    let dir = temp_dir("archive");
+    // Results from before the registry existed
+    fs::write(dir.join("analysis_results.json"), "legacy").unwrap();
+    fs::create_dir_all(dir.join(".cache")).unwrap();
+    fs::write(dir.join(".cache").join("crtsh.json"), "{}").unwrap();
+
+    let registry = ArtifactRegistry::new(&dir);
+    let first = registry.begin_scan("example.com", false).unwrap();
+    assert_eq!(first.dir, dir);
+    assert!(!dir.join("analysis_results.json").exists());
+    assert!(dir.join(".cache").join("crtsh.json").exists());
+    fs::write(dir.join("analysis_results.json"), "first").unwrap();
+    registry.finish_scan(&first).unwrap();
+
+    let second = registry.begin_scan("example.com", false).unwrap();
+    let archived = dir.join(RUNS_DIR).join(&first.scan_id).join("analysis_results.json");
+    assert_eq!(fs::read_to_string(&archived).unwrap(), "first");
+    assert_eq!(registry.latest_artifact("analysis_results.json"), Some(archived));
+    registry.finish_scan(&second).unwrap();
+
+    let runs = registry.load().unwrap().runs;
+    assert_eq!(runs.len(), 3);
+    assert!(runs[0].scan_id.ends_with("-legacy"));
+    assert_eq!(runs[1].location, format!("{}/{}", RUNS_DIR, first.scan_id));
+    assert_eq!(runs[1].files, vec!["analysis_results.json".to_string()]);
+    assert_eq!(runs[2].location, ".");
+    assert!(runs[2].finished_at.is_some());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn overwrite_removes_only_the_latest_run() {
+    let dir = temp_dir("overwrite");
+    let registry = ArtifactRegistry::new(&dir);
+    let first = registry.begin_scan("example.com", false).unwrap();
+    fs::write(dir.join("target_top.txt"), "first").unwrap();
+    registry.finish_scan(&first).unwrap();
+
+    let second = registry.begin_scan("example.com", true).unwrap();
+    assert!(!dir.join("target_top.txt").exists());
+    assert!(!dir.join(RUNS_DIR).join(&first.scan_id).exists());
+    registry.finish_scan(&second).unwrap();
+    assert_eq!(registry.load().unwrap().runs.len(), 1);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn concurrent_scans_get_separate_directories() {
+    let dir = temp_dir("concurrent");
+    let registry = ArtifactRegistry::new(&dir);
+    let running = registry.begin_scan("a.example.com", false).unwrap();
+    fs::write(dir.join("target_raw.jsonl"), "in progress").unwrap();
+
+    let other = registry.begin_scan("b.example.com", false).unwrap();
+    assert_ne!(other.dir, dir);
+    assert!(other.dir.starts_with(dir.join(RUNS_DIR)));
+    // The running scan's files were left alone
+    assert_eq!(fs::read_to_string(dir.join("target_raw.jsonl")).unwrap(), "in progress");
+
+    registry.finish_scan(&other).unwrap();
+    registry.finish_scan(&running).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn retention_keeps_newest_runs() {
+    let dir = temp_dir("retention");
+    let registry = ArtifactRegistry::new(&dir);
+    let mut ids = Vec::new();
+    for i in 0..3 {
+        let run = registry.begin_scan("example.com", false).unwrap();
+        fs::write(dir.join("target_top.txt"), i.to_string()).unwrap();
+        registry.finish_scan(&run).unwrap();
+        ids.push(run.scan_id);
+    }
+    // Two archived runs, one current
+    assert_eq!(registry.apply_retention(RetentionPolicy::default()).unwrap(), Vec::<String>::new());
+    let removed = registry.apply_retention(RetentionPolicy { keep_runs: Some(1), max_age: None }).unwrap();
+    assert_eq!(removed, vec![ids[0].clone()]);
+    assert!(!dir.join(RUNS_DIR).join(&ids[0]).exists());
+    assert!(dir.join(RUNS_DIR).join(&ids[1]).exists());
+    assert!(dir.join("target_top.txt").exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,83 @@
+use api_hunter::probe::response_cache::ResponseCache;
+use common::Response;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+/// Counts requests; `/big` answers with a body larger than the probe sample
+async fn server(hits: Arc<AtomicUsize>) -> String {
+    common::serve(move |req| {
+        hits.fetch_add(1, Ordering::SeqCst);
+        let body = if req.method == "GET" && req.path() == "/big" { "x".repeat(1000) } else { r#"{"id":1}"#.to_string() };
+        Response::json(200, body)
+    }).await
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("apihunter_{}_{}", name, std::process::id()))
+}
+
+#[tokio::test]
+async fn test_responses_reused() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base = server(hits.clone()).await;
+    let dir = temp_dir("response_cache");
+    let cache = ResponseCache::new(dir.clone(), Duration::from_secs(60));
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/users/1", base);
+
+    let first = cache.fetch(&client, client.get(&url), 4096).await.unwrap();
+    assert!(!first.cached);
+    // A ranged request for the same URL is answered from the complete copy
+    let second = cache.fetch(&client, client.get(&url).header("Range", "bytes=0-99"), 100).await.unwrap();
+    assert!(second.cached);
+    assert_eq!(second.status, 200);
+    assert_eq!(second.body, br#"{"id":1}"#);
+    assert_eq!(second.headers["content-type"], "application/json");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // Different request headers are a different entry
+    cache.fetch(&client, client.get(&url).header("Authorization", "Bearer other"), 4096).await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    // POST is never cached
+    cache.fetch(&client, client.post(&url), 4096).await.unwrap();
+    cache.fetch(&client, client.post(&url), 4096).await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+
+    // A repeated scan (new cache instance on the same directory) reuses the stored response
+    let again = ResponseCache::new(dir.clone(), Duration::from_secs(60));
+    assert!(again.fetch(&client, client.get(&url), 4096).await.unwrap().cached);
+    assert_eq!(again.stats(), (1, 0));
+    // Expired entries are fetched again
+    let expired = ResponseCache::new(dir.clone(), Duration::ZERO);
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(!expired.fetch(&client, client.get(&url), 4096).await.unwrap().cached);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_truncated_copy_not_served_for_larger_reads() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base = server(hits.clone()).await;
+    let dir = temp_dir("response_cache_limit");
+    let cache = ResponseCache::new(dir.clone(), Duration::from_secs(60));
+    let client = reqwest::Client::new();
+    let url = format!("{}/big", base);
+
+    let sample = cache.fetch(&client, client.get(&url), 100).await.unwrap();
+    assert!(sample.truncated);
+    assert_eq!(sample.body.len(), 100);
+    // Smaller reads can use the stored prefix, larger ones can't
+    assert!(cache.fetch(&client, client.get(&url), 50).await.unwrap().cached);
+    let full = cache.fetch(&client, client.get(&url), 4096).await.unwrap();
+    assert!(!full.cached);
+    assert_eq!(full.body.len(), 1000);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // Identical bodies are stored once
+    let bodies = std::fs::read_dir(dir.join("bodies")).unwrap().count();
+    assert_eq!(bodies, 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,70 @@
+use api_hunter::gather::js_deep_analyzer::JsCriticalInfo;
+use api_hunter::output::annotation::Annotation;
+use api_hunter::output::clean_reporter::ScanReport;
+use api_hunter::output::schema::{self, Artifact, SCHEMA_VERSION};
+use api_hunter::output::RawEvent;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("apihunter-schema-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn test_schemas_are_written_and_versioned() {
+    let dir = temp_dir("write");
+    let written = schema::write_all(&dir).unwrap();
+    assert_eq!(written.len(), Artifact::ALL.len());
+
+    let raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("raw_event.v1.schema.json")).unwrap()).unwrap();
+    assert_eq!(raw["x-schema-version"], SCHEMA_VERSION);
+    assert_eq!(raw["title"], "RawEvent");
+    let props = raw["properties"].as_object().unwrap();
+    for field in ["schema_version", "orig_url", "status", "annotations", "headers", "body"] {
+        assert!(props.contains_key(field), "missing {}", field);
+    }
+    // Typed annotations are documented, not just "array"
+    assert!(raw["definitions"]["Annotation"].is_object());
+
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("scan_report.v1.schema.json")).unwrap()).unwrap();
+    assert!(report["definitions"]["Finding"].is_object());
+    assert!(dir.join("js_critical_info.v1.schema.json").exists());
+    assert!(dir.join("finding.v1.schema.json").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_new_outputs_carry_schema_version() {
+    assert_eq!(ScanReport::new("example.com".into()).schema_version, SCHEMA_VERSION);
+    assert_eq!(JsCriticalInfo::default().schema_version, SCHEMA_VERSION);
+
+    // Files written before versioning load as version 0
+    let info: JsCriticalInfo = serde_json::from_str(r#"{"endpoints":[],"secrets":[],"domains":[],"parameters":[],"websockets":[],"graphql":[],"routes":[],"cloud_storage":[],"emails":[],"comments":[],"integrations":[],"source_maps":[],"versions":{}}"#).unwrap();
+    assert_eq!(info.schema_version, 0);
+}
+
+#[test]
+fn test_read_jsonl_tolerates_old_new_and_broken_lines() {
+    let dir = temp_dir("jsonl");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("target_raw.jsonl");
+    let legacy = r#"{"orig_url":"https://a.example/api","final_url":"https://a.example/api","status":200,"content_type":null,"server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":3,"notes":["WAF:Cloudflare"]}"#;
+    let newer = r#"{"schema_version":7,"orig_url":"https://b.example/api","final_url":"https://b.example/api","status":401,"content_type":null,"server":null,"content_length":null,"response_ms":12,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":5,"annotations":[{"type":"jwt_found","count":1},{"type":"future_thing","x":1}],"some_new_field":{"a":1}}"#;
+    std::fs::write(&path, format!("{}\n{{not json\n\n{}\n", legacy, newer)).unwrap();
+
+    let events = api_hunter::utils::read_jsonl(path.clone()).unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].schema_version, 0);
+    assert_eq!(events[0].waf(), Some("Cloudflare"));
+    assert_eq!(events[1].schema_version, 7);
+    assert_eq!(events[1].annotations[0], Annotation::JwtFound { count: 1 });
+    assert!(matches!(&events[1].annotations[1], Annotation::Note { text } if text.contains("future_thing")));
+
+    // Round trip keeps the current version
+    let mut ev: RawEvent = events[0].clone();
+    ev.schema_version = SCHEMA_VERSION;
+    let back: RawEvent = serde_json::from_str(&serde_json::to_string(&ev).unwrap()).unwrap();
+    assert_eq!(back.schema_version, SCHEMA_VERSION);
+
+    std::fs::write(&path, "garbage\n").unwrap();
+    assert!(api_hunter::utils::read_jsonl(path).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,62 @@
+use api_hunter::gather::js_deep_analyzer::{JsDeepAnalyzer, SecretRules, SecretType};
+use api_hunter::gather::secret_patterns::{load, parse};
+
+const YAML: &str = r#"
+patterns:
+  - name: acme_api_key
+    provider: Acme
+    regex: 'acme_live_[A-Za-z0-9]{24}'
+  - name: internal_token
+    regex: 'internalToken\s*:\s*"([^"]{16,})"'
+"#;
+
+const TOML: &str = r#"
+[[patterns]]
+name = "acme_api_key"
+provider = "Acme"
+regex = 'acme_live_[A-Za-z0-9]{24}'
+"#;
+
+#[test]
+fn parses_yaml_and_toml() {
+    let yaml = parse(YAML, false).unwrap();
+    assert_eq!(yaml.len(), 2);
+    assert_eq!(yaml[0].label(), "Acme: acme_api_key");
+    assert_eq!(yaml[0].group, 0);
+    assert_eq!(yaml[1].label(), "internal_token");
+    assert_eq!(yaml[1].group, 1);
+
+    let toml = parse(TOML, true).unwrap();
+    assert_eq!(toml.len(), 1);
+    assert_eq!(toml[0].name, "acme_api_key");
+
+    // JSON goes through the YAML parser
+    let json = parse(r#"{"patterns": [{"name": "x", "regex": "x_[0-9]{8}"}]}"#, false).unwrap();
+    assert_eq!(json.len(), 1);
+}
+
+#[test]
+fn rejects_bad_patterns() {
+    assert!(parse("patterns:\n  - name: broken\n    regex: '(unclosed'\n", false).is_err());
+    assert!(parse("patterns:\n  - name: nogroup\n    regex: 'abc'\n    group: 1\n", false).is_err());
+    assert!(load(std::path::Path::new("/nonexistent/patterns.yaml")).is_err());
+}
+
+#[test]
+fn custom_patterns_are_merged_with_builtins() {
+    let js = r#"const a="acme_live_Zr3vN1xK7pL2mW9sT4yB6cQd";window.cfg={internalToken: "k2Jd8sLq0Pz7Xw4Rv"};const s="AKIAIOSFODNN7QWERTY9";"#;
+    let rules = SecretRules { entropy_threshold: None, custom: parse(YAML, false).unwrap() };
+    let secrets = JsDeepAnalyzer::extract_secrets_with_rules(js, "app.js", &rules);
+
+    let acme = secrets.iter().find(|s| s.value == "acme_live_Zr3vN1xK7pL2mW9sT4yB6cQd").unwrap();
+    assert_eq!(acme.secret_type, SecretType::Custom("Acme: acme_api_key".to_string()));
+    assert!(acme.line_context.contains("acme_live_"));
+    let internal = secrets.iter().find(|s| s.value == "k2Jd8sLq0Pz7Xw4Rv").unwrap();
+    assert_eq!(internal.secret_type, SecretType::Custom("internal_token".to_string()));
+    // Built-in patterns still apply
+    assert!(secrets.iter().any(|s| s.secret_type == SecretType::AwsKey));
+
+    // Without custom patterns the provider token is not reported
+    let default = JsDeepAnalyzer::extract_secrets_with_entropy(js, "app.js", None);
+    assert!(!default.iter().any(|s| s.value.starts_with("acme_live_")));
+}
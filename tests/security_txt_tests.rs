@@ -0,0 +1,80 @@
+use api_hunter::discover::security_txt::{self, SecurityTxtFetcher};
+use api_hunter::output::clean_reporter::ScanReport;
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+/// 2026-10-17T00:00:00Z
+const NOW: i64 = 1_792_195_200;
+
+#[test]
+fn test_parse_rfc3339() {
+    assert_eq!(security_txt::parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+    assert_eq!(security_txt::parse_rfc3339("2026-10-17T00:00:00Z"), Some(NOW));
+    assert_eq!(security_txt::parse_rfc3339("2026-10-17T02:00:00.000+02:00"), Some(NOW));
+    assert_eq!(security_txt::parse_rfc3339("2024-02-29T12:30:15-01:00"), Some(1_709_213_415));
+    assert_eq!(security_txt::parse_rfc3339("next year"), None);
+    assert_eq!(security_txt::parse_rfc3339("2026-13-01T00:00:00Z"), None);
+}
+
+#[test]
+fn test_parse() {
+    let text = "# Our policy\nContact: mailto:security@example.com\nContact: https://example.com/report\nExpires: 2027-06-30T23:00:00.000Z\nEncryption: https://example.com/pgp-key.txt\nPolicy: https://hackerone.com/example\nPreferred-Languages: en, de\nCanonical: https://example.com/.well-known/security.txt\n";
+    let txt = security_txt::parse(text, "https://example.com/.well-known/security.txt", NOW).unwrap();
+    assert_eq!(txt.contacts, vec!["mailto:security@example.com", "https://example.com/report"]);
+    assert_eq!(txt.policy, vec!["https://hackerone.com/example"]);
+    assert_eq!(txt.preferred_languages.as_deref(), Some("en, de"));
+    assert!(!txt.expired && !txt.signed);
+    assert!(txt.issues.is_empty(), "{:?}", txt.issues);
+    assert_eq!(txt.report_to().as_deref(), Some("mailto:security@example.com (policy: https://hackerone.com/example)"));
+
+    let stale = security_txt::parse("Contact: security@example.com\nExpires: 2025-01-01T00:00:00Z\nCanonical: https://www.example.com/.well-known/security.txt", "https://example.com/.well-known/security.txt", NOW).unwrap();
+    assert!(stale.expired);
+    assert_eq!(stale.issues.len(), 3, "{:?}", stale.issues);
+    assert!(stale.issues[0].starts_with("expired on 2025-01-01"));
+    assert!(stale.issues[2].contains("mailto:"));
+
+    assert!(security_txt::parse("<!doctype html><html><title>Shop</title></html>", "https://example.com/security.txt", NOW).is_none());
+}
+
+#[test]
+fn test_parse_signed() {
+    let signed = "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA512\n\nContact: mailto:psirt@example.com\nExpires: 2027-01-01T00:00:00Z\n- -----\n-----BEGIN PGP SIGNATURE-----\n\niQIzBAEBCgAdFiEE\n-----END PGP SIGNATURE-----\n";
+    let txt = security_txt::parse(signed, "https://example.com/.well-known/security.txt", NOW).unwrap();
+    assert!(txt.signed);
+    assert_eq!(txt.contacts, vec!["mailto:psirt@example.com"]);
+    assert!(txt.issues.is_empty(), "{:?}", txt.issues);
+}
+
+/// A site with an HTML catch-all at the well-known path and the file at the legacy location
+async fn legacy_server() -> String {
+    common::serve(|req| match req.path() {
+        "/security.txt" => Response::new(200).header("Content-Type", "text/plain").body("Contact: mailto:sec@example.test\nExpires: 2099-01-01T00:00:00Z\n"),
+        _ => Response::html(200, "<html><body>Welcome</body></html>"),
+    }).await
+}
+
+#[tokio::test]
+async fn test_fetch_and_report() {
+    let base = legacy_server().await;
+    let fetcher = SecurityTxtFetcher::new(Duration::from_secs(5)).unwrap();
+    let txt = fetcher.fetch(&base).await.unwrap();
+    assert_eq!(txt.url, format!("{}/security.txt", base));
+    assert_eq!(txt.contacts, vec!["mailto:sec@example.test"]);
+    for issue in ["Expires is more than a year ahead", "served over plain HTTP", "only at the legacy /security.txt location"] {
+        assert!(txt.issues.iter().any(|i| i.starts_with(issue)), "{:?}", txt.issues);
+    }
+
+    let mut report = ScanReport::new("example.test".into());
+    report.disclosure_policies.push(txt);
+    let path = std::env::temp_dir().join(format!("apihunter-security-txt-{}.txt", std::process::id()));
+    report.save_to_file(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("Disclosure Policy (security.txt):"));
+    assert!(text.contains("  - Contact: mailto:sec@example.test"));
+    let _ = std::fs::remove_file(&path);
+
+    let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+    assert_eq!(json["disclosure_policies"][0]["contacts"][0], "mailto:sec@example.test");
+}
@@ -0,0 +1,57 @@
+use api_hunter::gather::service_worker::{
+    imported_scripts, manifest_link, manifest_urls, ngsw_entries, precache_entries, registrations, runtime_routes,
+};
+
+#[test]
+fn parses_web_manifest() {
+    let html = r#"<head><link rel="manifest" href="/app.webmanifest"><script src="/main.js"></script></head>"#;
+    assert_eq!(manifest_link(html), Some("/app.webmanifest".to_string()));
+
+    let manifest = r#"{
+        "name": "Shop",
+        "start_url": "/app/?source=pwa",
+        "scope": "/app/",
+        "shortcuts": [{"name": "Orders", "url": "/app/orders"}],
+        "share_target": {"action": "/api/share", "method": "POST"}
+    }"#;
+    let urls = manifest_urls(manifest, "https://example.com/app.webmanifest");
+    assert!(urls.contains(&"https://example.com/app/?source=pwa".to_string()));
+    assert!(urls.contains(&"https://example.com/app/orders".to_string()));
+    assert!(urls.contains(&"https://example.com/api/share".to_string()));
+}
+
+#[test]
+fn extracts_worker_registrations_and_precache() {
+    let js = r#"if("serviceWorker" in navigator){navigator.serviceWorker.register("/service-worker.js",{scope:"/"})}"#;
+    assert_eq!(registrations(js), vec!["/service-worker.js".to_string()]);
+
+    let sw = r#"
+        importScripts("https://storage.googleapis.com/workbox-cdn/releases/4.3.1/workbox-sw.js", "/precache-manifest.4f2a.js");
+        workbox.precaching.precacheAndRoute([{url:"/index.html",revision:"abc"},{"revision":null,"url":"/static/js/main.1a2b.js"}]);
+        workbox.routing.registerRoute(/\/api\/v2\/.*/, new workbox.strategies.NetworkFirst());
+        workbox.routing.registerRoute("/api/config", new workbox.strategies.CacheFirst());
+        self.addEventListener("fetch", e => { if (url.pathname.startsWith("/graphql")) return; });
+    "#;
+    assert_eq!(imported_scripts(sw).len(), 2);
+    assert_eq!(precache_entries(sw), vec!["/index.html".to_string(), "/static/js/main.1a2b.js".to_string()]);
+    let routes = runtime_routes(sw);
+    assert!(routes.contains(&"/api/config".to_string()));
+    assert!(routes.contains(&"/graphql".to_string()));
+    assert!(routes.contains(&"/api/v2/.*".to_string()));
+
+    let legacy = r#"var precacheConfig=[["index.html","0123456789abcdef0123456789abcdef"]];"#;
+    assert_eq!(precache_entries(legacy), vec!["index.html".to_string()]);
+}
+
+#[test]
+fn reads_angular_ngsw() {
+    let ngsw = r#"{
+        "assetGroups": [{"name": "app", "urls": ["/index.html", "/main.js"]}],
+        "dataGroups": [{"name": "api", "patterns": ["/api/users/.*"]}],
+        "hashTable": {"/assets/config.json": "abc"}
+    }"#;
+    let (assets, data) = ngsw_entries(ngsw);
+    assert!(assets.contains(&"/main.js".to_string()));
+    assert!(assets.contains(&"/assets/config.json".to_string()));
+    assert_eq!(data, vec!["/api/users/.*".to_string()]);
+}
@@ -0,0 +1,62 @@
+use api_hunter::enrich::simhash::{self, distance, simhash, DEFAULT_MAX_DISTANCE};
+use api_hunter::output::{Annotation, RawEvent};
+
+fn product_page(id: u32, name: &str) -> String {
+    format!(
+        "<html><head><title>{name} | Shop</title></head><body><nav><a href=\"/\">Home</a><a href=\"/cart\">Cart</a>\
+         <a href=\"/account\">Account</a></nav><main><h1>{name}</h1><p class=\"price\">EUR {id}.99</p>\
+         <button class=\"add-to-cart\" data-id=\"{id}\">Add to cart</button><section class=\"reviews\">No reviews yet\
+         </section></main><footer>Imprint Privacy Terms Contact Shipping Returns</footer></body></html>"
+    )
+}
+
+fn event(url: &str, score: i32, body: &str) -> RawEvent {
+    let mut ev: RawEvent = serde_json::from_str(&format!(
+        r#"{{"orig_url":"{url}","final_url":"{url}","status":200,"content_type":"text/html; charset=utf-8","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":{score}}}"#
+    )).unwrap();
+    ev.body_simhash = simhash(body.as_bytes()).map(simhash::to_hex);
+    ev
+}
+
+#[test]
+fn test_simhash_distance() {
+    let a = simhash(product_page(1, "Red Shoe").as_bytes()).unwrap();
+    let b = simhash(product_page(2, "Blue Shoe").as_bytes()).unwrap();
+    assert!(distance(a, b) <= DEFAULT_MAX_DISTANCE, "distance {}", distance(a, b));
+
+    let api = simhash(br#"{"users":[{"id":1,"email":"a@example.com","role":"admin","created_at":"2024-01-01","active":true}]}"#).unwrap();
+    assert!(distance(a, api) > DEFAULT_MAX_DISTANCE);
+    assert_eq!(simhash::from_hex(&simhash::to_hex(a)), Some(a));
+    assert!(simhash(b"tiny body").is_none());
+}
+
+#[test]
+fn test_cluster_keeps_one_representative() {
+    let mut events: Vec<RawEvent> = (1..=20)
+        .map(|i| event(&format!("https://shop.example/product/{}", i), if i == 7 { 5 } else { 1 }, &product_page(i, &format!("Product number {}", i))))
+        .collect();
+    events.push(event("https://shop.example/api/users", 9, r#"{"users":[{"id":1,"email":"a@example.com","role":"admin","created_at":"2024-01-01","active":true}]}"#));
+    // Same template on another host is another cluster
+    events.push(event("https://blog.example/product/1", 1, &product_page(1, "Product number 1")));
+    events.push(event("https://blog.example/product/2", 1, &product_page(2, "Product number 2")));
+
+    let clusters = simhash::cluster(&mut events, DEFAULT_MAX_DISTANCE);
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters[0].id, "c1");
+    assert_eq!(clusters[0].size, 20);
+    assert_eq!(clusters[0].representative, "https://shop.example/product/7");
+    assert_eq!(clusters[1].size, 2);
+    assert!(events[0].annotations.contains(&Annotation::Cluster { id: "c1".to_string() }));
+    assert!(events[20].annotations.is_empty());
+
+    let reps = simhash::representatives(&events, &clusters);
+    let urls: Vec<&str> = reps.iter().map(|e| e.orig_url.as_str()).collect();
+    assert_eq!(urls, vec!["https://shop.example/product/7", "https://shop.example/api/users", "https://blog.example/product/1"]);
+}
+
+#[test]
+fn test_events_without_body_stay_unclustered() {
+    let mut events = vec![event("https://a.example/x", 1, ""), event("https://a.example/y", 1, "")];
+    assert!(simhash::cluster(&mut events, DEFAULT_MAX_DISTANCE).is_empty());
+    assert_eq!(simhash::representatives(&events, &[]).len(), 2);
+}
@@ -0,0 +1,144 @@
+use api_hunter::probe::smuggling::{parse_status, Desync, SmugglingProber, Target, POISON_PATH, TE_HEADERS};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn test_probe_construction() {
+    api_hunter::identify::configure(&["X-Bug-Bounty: hunter".to_string()]).unwrap();
+    let target = Target::parse("https://shop.test/api/cart?x=1").unwrap();
+    assert!(target.https);
+    assert_eq!((target.host.as_str(), target.port, target.path.as_str()), ("shop.test", 443, "/api/cart?x=1"));
+
+    let cl_te = target.timing_probe(Desync::ClTe, TE_HEADERS[0].1);
+    assert!(cl_te.starts_with("POST /api/cart?x=1 HTTP/1.1\r\nHost: shop.test\r\n"));
+    assert!(cl_te.contains("\r\nx-bug-bounty: hunter\r\n"));
+    assert!(api_hunter::identify::audit().clients.iter().any(|c| c.component == "probe::smuggling"));
+    assert!(cl_te.ends_with("Transfer-Encoding: chunked\r\nContent-Length: 4\r\n\r\n1\r\nA\r\nX"));
+    let te_cl = target.timing_probe(Desync::TeCl, TE_HEADERS[1].1);
+    assert!(te_cl.ends_with("Transfer-Encoding : chunked\r\nContent-Length: 6\r\n\r\n0\r\n\r\nX"));
+
+    // TE.CL poison: Content-Length covers exactly the chunk size line
+    let poison = target.poison_probe(Desync::TeCl, TE_HEADERS[0].1);
+    let body = poison.split_once("\r\n\r\n").unwrap().1;
+    let (size, rest) = body.split_once("\r\n").unwrap();
+    assert!(poison.contains(&format!("Content-Length: {}\r\n", size.len() + 2)));
+    let chunk = usize::from_str_radix(size, 16).unwrap();
+    assert!(rest[..chunk].starts_with(&format!("GET {} HTTP/1.1", POISON_PATH)));
+    assert_eq!(&rest[chunk..], "\r\n0\r\n\r\n");
+
+    assert_eq!(parse_status(b"HTTP/1.1 404 Not Found\r\n"), Some(404));
+    assert_eq!(parse_status(b"garbage"), None);
+    assert_eq!(Target::parse("http://127.0.0.1:8080/").unwrap().port, 8080);
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// End of a chunked body in `data`, `None` while incomplete
+fn chunked_end(data: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let line_end = pos + find(&data[pos..], b"\r\n")?;
+        let size = usize::from_str_radix(std::str::from_utf8(&data[pos..line_end]).ok()?.trim(), 16).ok()?;
+        pos = line_end + 2;
+        if size == 0 {
+            return (data.len() >= pos + 2).then_some(pos + 2);
+        }
+        if data.len() < pos + size + 2 {
+            return None;
+        }
+        pos += size + 2;
+    }
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().find_map(|l| l.split_once(':').filter(|(k, _)| k.trim().eq_ignore_ascii_case(name)).map(|(_, v)| v.trim()))
+}
+
+/// Front end honouring Content-Length in front of a back end honouring Transfer-Encoding, sharing one
+/// back-end connection (bytes left over prefix the next request). With `strict`, requests carrying both
+/// headers are rejected, like a patched front end.
+async fn proxy_chain(strict: bool) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://127.0.0.1:{}/api", listener.local_addr().unwrap().port());
+    let leftover: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut sock, _)) = listener.accept().await else { break };
+            let leftover = leftover.clone();
+            tokio::spawn(async move {
+                // Front end: head, then Content-Length bytes
+                let mut data = Vec::new();
+                let mut buf = [0u8; 4096];
+                let head_end = loop {
+                    if let Some(i) = find(&data, b"\r\n\r\n") {
+                        break i + 4;
+                    }
+                    match sock.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                    }
+                };
+                let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+                let te = header(&head, "transfer-encoding").is_some();
+                let cl: usize = header(&head, "content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+                if strict && te && cl > 0 {
+                    let _ = sock.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n").await;
+                    return;
+                }
+                while data.len() < head_end + cl {
+                    match sock.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                    }
+                }
+                data.truncate(head_end + cl);
+
+                // Back end: chunked when announced, with what is left from the previous request in front
+                let mut back = std::mem::take(&mut *leftover.lock());
+                back.extend_from_slice(&data);
+                let back_head_end = find(&back, b"\r\n\r\n").unwrap() + 4;
+                let back_head = String::from_utf8_lossy(&back[..back_head_end]).to_string();
+                let body_end = if header(&back_head, "transfer-encoding").is_some_and(|v| v.contains("chunked")) {
+                    match chunked_end(&back[back_head_end..]) {
+                        Some(end) => back_head_end + end,
+                        None => {
+                            // Waits for the rest of the chunk
+                            tokio::time::sleep(Duration::from_secs(30)).await;
+                            return;
+                        }
+                    }
+                } else {
+                    back_head_end
+                };
+                *leftover.lock() = back[body_end..].to_vec();
+                let path = back_head.split_whitespace().nth(1).unwrap_or("/");
+                let status = if path == POISON_PATH { "404 Not Found" } else { "200 OK" };
+                let _ = sock.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).as_bytes()).await;
+            });
+        }
+    });
+    base
+}
+
+#[tokio::test]
+async fn test_cl_te_chain_is_confirmed() {
+    let target = Target::parse(&proxy_chain(false).await).unwrap();
+    let prober = SmugglingProber::new(Duration::from_millis(500));
+    let finding = prober.test(&target).await.expect("CL.TE desync");
+    assert_eq!(finding.desync, Desync::ClTe);
+    assert_eq!(finding.variant, "plain");
+    assert!(finding.confirmed);
+    assert_eq!(finding.severity, "critical");
+    assert!(finding.request.contains("Content-Length: 4"));
+}
+
+#[tokio::test]
+async fn test_strict_front_end_is_clean() {
+    let target = Target::parse(&proxy_chain(true).await).unwrap();
+    let prober = SmugglingProber::new(Duration::from_millis(500));
+    assert!(prober.test(&target).await.is_none());
+}
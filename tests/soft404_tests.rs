@@ -0,0 +1,88 @@
+use api_hunter::output::annotation::Annotation;
+use api_hunter::probe::http_probe::{probe_url, ProbeOptions};
+use api_hunter::probe::soft404::{self, body_hash, Baseline, Baselines, Response};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+fn baseline(status: u16, body: &str, truncated: bool) -> Baseline {
+    Baseline {
+        origin: "https://shop.example".into(),
+        probe_url: "https://shop.example/0123456789abcdef".into(),
+        status,
+        content_type: Some("text/html".into()),
+        content_length: Some(body.len() as u64),
+        size: body.len(),
+        truncated,
+        body_hash: body_hash(body.as_bytes(), "/0123456789abcdef"),
+    }
+}
+
+fn html<'a>(status: u16, body: &'a str) -> Response<'a> {
+    Response { status, content_type: Some("text/html; charset=utf-8"), content_length: None, body: Some((body.as_bytes(), false)) }
+}
+
+#[test]
+fn test_matching() {
+    let spa = "<html><div id=\"root\"></div><script src=\"/main.js\"></script></html>";
+    let b = baseline(200, spa, false);
+    assert!(b.matches("https://shop.example/api/v1/users", &html(200, spa)));
+    // Other status or type
+    assert!(!b.matches("https://shop.example/api/v1/users", &html(201, spa)));
+    let json = Response { content_type: Some("application/json"), ..html(200, spa) };
+    assert!(!b.matches("https://shop.example/api/v1/users", &json));
+    // A different page
+    assert!(!b.matches("https://shop.example/api/v1/users", &html(200, "{\"users\":[{\"id\":1,\"name\":\"a\"}],\"total\":1,\"page\":1,\"more\":false}")));
+
+    // Not-found pages that echo the path
+    let echo = baseline(200, "<h1>Page /0123456789abcdef not found</h1>", false);
+    assert!(echo.matches("https://shop.example/admin/config", &html(200, "<h1>Page /admin/config not found</h1>")));
+
+    // A nonce changes the hash; the size still matches
+    let nonce = baseline(200, "<html>csrf=aaaaaaaaaaaa</html>", false);
+    assert!(nonce.matches("https://shop.example/x", &html(200, "<html>csrf=bbbbbbbbbbbb</html>")));
+
+    // Hosts with real 404s never produce soft-404s
+    let strict = baseline(404, "not found", false);
+    assert!(strict.is_strict());
+    assert!(!strict.matches("https://shop.example/x", &html(404, "not found")));
+
+    // HEAD only: length decides
+    let head = Response { status: 200, content_type: Some("text/html"), content_length: Some(spa.len() as u64), body: None };
+    assert!(b.matches("https://shop.example/x", &head));
+    assert!(!b.matches("https://shop.example/x", &Response { content_length: None, ..head }));
+}
+
+#[test]
+fn test_baselines_by_origin() {
+    let mut baselines = Baselines::default();
+    baselines.insert(baseline(200, "x", false));
+    assert!(baselines.for_url("https://shop.example/api/users?id=1").is_some());
+    assert!(baselines.for_url("http://shop.example/api/users").is_none());
+    assert!(baselines.for_url("https://api.shop.example/users").is_none());
+    assert_eq!(baselines.catch_all().count(), 1);
+}
+
+#[tokio::test]
+async fn test_probe_flags_catch_all_pages() {
+    // SPA: every path returns the index, except a real API endpoint
+    let base = common::serve(|req| match req.path() {
+        "/api/orders" => common::Response::json(200, "{\"orders\":[]}"),
+        _ => common::Response::html(200, "<html><div id=\"app\"></div><script src=\"/app.js\"></script></html>"),
+    }).await;
+
+    let client = reqwest::Client::new();
+    let urls = vec![format!("{}/api/orders", base), format!("{}/api/v2/secret", base)];
+    let baselines = soft404::learn_all(&client, &urls, 4, Duration::from_secs(5)).await;
+    assert_eq!(baselines.len(), 1);
+    let b = baselines.for_url(&urls[0]).unwrap();
+    assert_eq!(b.status, 200);
+    assert!(!b.is_strict());
+
+    let opts = ProbeOptions { soft404: Some(Arc::new(baselines)), ..ProbeOptions::new(5, 1) };
+    let real = probe_url(&client, &urls[0], None, &opts).await.unwrap();
+    assert!(!real.annotations.contains(&Annotation::Soft404));
+    let fake = probe_url(&client, &urls[1], None, &opts).await.unwrap();
+    assert!(fake.annotations.contains(&Annotation::Soft404));
+}
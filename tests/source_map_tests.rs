@@ -0,0 +1,25 @@
+use api_hunter::gather::source_map::{decode_data_uri, is_first_party, parse, relative_path};
+use std::path::PathBuf;
+
+#[test]
+fn rebuilds_original_sources() {
+    let map = r#")]}'
+{"version":3,"sourceRoot":"","sources":["webpack://app/./src/api/client.ts","webpack://app/./node_modules/axios/index.js","webpack://app/webpack/bootstrap"],
+"sourcesContent":["export const API = '/api/v2/internal';","module.exports = {}",null],"mappings":""}"#;
+    let sources = parse(map).unwrap();
+    assert_eq!(sources.len(), 2);
+
+    let first_party: Vec<_> = sources.iter().filter(|s| is_first_party(&s.path)).collect();
+    assert_eq!(first_party.len(), 1);
+    assert!(first_party[0].content.contains("/api/v2/internal"));
+
+    assert_eq!(relative_path("webpack://app/./src/../api/client.ts"), PathBuf::from("app/src/api/client.ts"));
+    assert!(parse("{\"foo\":1}").is_err());
+}
+
+#[test]
+fn decodes_inline_maps() {
+    let uri = "data:application/json;charset=utf-8;base64,eyJzb3VyY2VzIjpbImEuanMiXSwic291cmNlc0NvbnRlbnQiOlsieCJdfQ==";
+    let json = decode_data_uri(uri).unwrap();
+    assert_eq!(parse(&json).unwrap()[0].path, "a.js");
+}
@@ -0,0 +1,103 @@
+use api_hunter::analyze::spec_drift::{self, ApiSpec};
+use api_hunter::output::{Annotation, RawEvent};
+use serde_json::json;
+
+fn event(url: &str, status: u16) -> RawEvent {
+    serde_json::from_str(&format!(
+        r#"{{"orig_url":"{0}","final_url":"{0}","status":{1},"content_type":"application/json","server":null,"content_length":null,"response_ms":null,"tls_issuer":null,"is_graphql":false,"json_sample":null,"score":1}}"#,
+        url, status
+    )).unwrap()
+}
+
+#[test]
+fn test_load_spec() {
+    let openapi = json!({
+        "openapi": "3.0.3",
+        "info": {"title": "Shop", "version": "2.1"},
+        "servers": [
+            {"url": "https://{env}.example.com/api/{version}", "variables": {"env": {"default": "api"}, "version": {"default": "v2"}}},
+            {"url": "https://prod.other-company.com/api/v2/"}
+        ],
+        "paths": {
+            "/users/{id}/": {"get": {}, "delete": {}, "parameters": []},
+            "/health": {"get": {}},
+            "/x-only": {"x-internal": true}
+        }
+    });
+    let spec = ApiSpec::from_value(&openapi, "shop.yaml", "example.com").unwrap();
+    assert_eq!(spec.title.as_deref(), Some("Shop"));
+    // The other company's server is rebased onto the target
+    assert_eq!(spec.bases, vec!["https://api.example.com/api/v2", "https://example.com/api/v2"]);
+    assert_eq!(spec.paths.keys().collect::<Vec<_>>(), vec!["/health", "/users/{id}"]);
+    assert_eq!(spec.operation_count(), 3);
+    assert_eq!(spec.probe_urls()[..2], ["https://api.example.com/api/v2/health", "https://api.example.com/api/v2/users/1"]);
+
+    let path = std::env::temp_dir().join(format!("apihunter-swagger-{}.yaml", std::process::id()));
+    std::fs::write(&path, "swagger: '2.0'\nhost: example.com\nbasePath: /v1\nschemes: [http]\npaths:\n  /orders:\n    post: {}\n").unwrap();
+    let swagger = ApiSpec::load(&path, "example.com").unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(swagger.bases, vec!["http://example.com/v1"]);
+    assert_eq!(swagger.probe_urls(), vec!["http://example.com/v1/orders"]);
+
+    assert!(ApiSpec::from_value(&json!({"openapi": "3.0.0"}), "empty.json", "example.com").is_err());
+}
+
+#[test]
+fn test_compare() {
+    let openapi = json!({
+        "openapi": "3.0.3",
+        "servers": [{"url": "/api"}],
+        "paths": {
+            "/users": {"get": {}},
+            "/users/me": {"get": {}, "post": {}},
+            "/users/{id}": {"get": {}},
+            "/legacy/export": {"get": {}},
+            "/orders/{orderId}": {"get": {}}
+        }
+    });
+    let spec = ApiSpec::from_value(&openapi, "api.yaml", "example.com").unwrap();
+
+    let mut users = event("https://example.com/api/users", 200);
+    users.methods.insert("POST".to_string(), 201);
+    let mut me = event("https://example.com/api/users/me", 200);
+    me.methods.insert("POST".to_string(), 405);
+    let mut legacy = event("https://example.com/api/legacy/export", 200);
+    legacy.annotations.push(Annotation::Soft404);
+    let mut blocked = event("https://example.com/api/debug", 403);
+    blocked.annotations.push(Annotation::WafBlock);
+    let events = vec![
+        users,
+        me,
+        event("https://example.com/api/users/1", 200),
+        legacy,
+        event("https://example.com/api/orders/1", 404),
+        event("https://example.com/api/internal/accounts/42", 200),
+        event("https://example.com/api/internal/accounts/43", 200),
+        event("https://example.com/api/admin", 401),
+        event("https://example.com/api/gone", 404),
+        blocked,
+        // Not under the spec's base path
+        event("https://example.com/static/app.js", 200),
+    ];
+    let drift = spec_drift::compare(&spec, &events);
+    assert_eq!((drift.documented_paths, drift.documented_operations), (5, 6));
+
+    let shadow: Vec<_> = drift.shadow.iter().map(|s| (s.path.as_str(), s.methods.join(","), s.severity.as_str())).collect();
+    assert_eq!(shadow, vec![
+        ("/api/admin", "GET".to_string(), "LOW"),
+        ("/api/internal/accounts/{accountId}", "GET".to_string(), "MEDIUM"),
+        ("/users", "POST".to_string(), "MEDIUM"),
+    ]);
+    assert_eq!(drift.shadow[1].url, "https://example.com/api/internal/accounts/42");
+    assert_eq!(drift.shadow[2].documented_path.as_deref(), Some("/users"));
+
+    let zombies: Vec<_> = drift.zombies.iter().map(|z| (z.path.as_str(), z.methods.join(","), z.placeholder, z.severity.as_str())).collect();
+    assert_eq!(zombies, vec![
+        ("/legacy/export", "GET".to_string(), false, "LOW"),
+        ("/orders/{orderId}", "GET".to_string(), true, "INFO"),
+        ("/users/me", "POST".to_string(), false, "LOW"),
+    ]);
+    assert_eq!(drift.zombies[1].reason, "answered 404");
+    assert_eq!(drift.zombies[0].urls, vec!["https://example.com/api/legacy/export"]);
+    assert!(!drift.is_empty());
+}
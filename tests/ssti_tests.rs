@@ -0,0 +1,70 @@
+use api_hunter::analyze::risk_classifier::RiskClassifier;
+use api_hunter::analyze::vulnerability_scanner::VulnerabilitySeverity;
+use api_hunter::fuzz::param_bruteforce::ParamLocation;
+use api_hunter::fuzz::ssti::{evaluated, polyglot, SstiTester, Syntax, TemplateEngine};
+use api_hunter::http_client::HttpClient;
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_polyglot_and_detection() {
+    let (payload, expected) = polyglot();
+    assert_eq!(expected.len(), 4);
+    assert!(payload.starts_with("{{") && payload.contains("${") && payload.contains("<%= ") && payload.contains("#{"));
+    let (curly, erb) = (&expected[0].1, &expected[2].1);
+    let body = format!("<p>Hello {}</p>", erb);
+    assert_eq!(evaluated(&expected, "<p>Hello hunter</p>", &body), vec![Syntax::Erb]);
+    // Already on the page without the payload
+    assert!(evaluated(&expected, curly, curly).is_empty());
+    assert_eq!(Syntax::Curly.wrap("7*7"), "{{7*7}}");
+    assert_eq!(Syntax::Hash.wrap("7*7"), "#{7*7}");
+}
+
+#[test]
+fn test_classification() {
+    let known = RiskClassifier::classify_ssti("Jinja2", true, 2);
+    assert_eq!(known.final_severity, VulnerabilitySeverity::Critical);
+    assert_eq!(known.category, "Server-Side Template Injection");
+    let unknown = RiskClassifier::classify_ssti("unknown", false, 2);
+    assert_eq!(unknown.final_severity, VulnerabilitySeverity::High);
+}
+
+/// Renders `name` through a Jinja2-like engine: `{{a*b}}` with integers, `{{7*'7'}}` repeats the string.
+/// `raw` is echoed without rendering.
+async fn jinja_app() -> String {
+    let mul = regex::Regex::new(r"\{\{(\d+)\*(\d+)\}\}").unwrap();
+    let base = common::serve(move |req| {
+        let mut text = String::from("<h1>Hi</h1>");
+        for (k, v) in req.query() {
+            let rendered = match k.as_str() {
+                "name" => mul
+                    .replace_all(&v.replace("{{7*'7'}}", "7777777"), |c: &regex::Captures| {
+                        (c[1].parse::<u64>().unwrap() * c[2].parse::<u64>().unwrap()).to_string()
+                    })
+                    .into_owned(),
+                _ => v,
+            };
+            text.push_str(&format!("<p>{}</p>", rendered));
+        }
+        Response::html(200, text)
+    }).await;
+    format!("{}/greet", base)
+}
+
+#[tokio::test]
+async fn test_tester_identifies_jinja2() {
+    let base = jinja_app().await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::ssti").build().unwrap());
+    let tester = SstiTester::new(&http, Duration::from_secs(5));
+
+    let finding = tester.test(&format!("{}?name=bob", base), "name", ParamLocation::Query).await.expect("name is rendered");
+    assert_eq!(finding.syntax, Syntax::Curly);
+    assert_eq!(finding.engine, TemplateEngine::Jinja2);
+    assert_eq!(finding.classification.final_severity, VulnerabilitySeverity::Critical);
+    assert!(finding.payload.starts_with("{{"));
+
+    // Reflected as-is: no product in the page
+    assert!(tester.test(&base, "raw", ParamLocation::Query).await.is_none());
+}
@@ -0,0 +1,170 @@
+use api_hunter::probe::http_probe::{probe_url, ProbeOptions};
+use api_hunter::probe::throttle::{parse_retry_after, Throttle};
+use common::Response;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+mod common;
+
+#[test]
+fn test_parse_retry_after() {
+    assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+    // Dates in the past mean "now"
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+    let future = httpdate::fmt_http_date(std::time::SystemTime::now() + Duration::from_secs(30));
+    let d = parse_retry_after(&future).unwrap();
+    assert!(d > Duration::from_secs(25) && d <= Duration::from_secs(30));
+    assert_eq!(parse_retry_after("soon"), None);
+}
+
+#[tokio::test]
+async fn test_aimd_decrease_and_recovery() {
+    let t = Throttle::new(100, 8);
+    assert_eq!(t.host_limit("api.example"), 8);
+
+    t.record("api.example", 429, Some(Duration::ZERO));
+    assert_eq!(t.host_limit("api.example"), 4);
+    t.record("api.example", 503, Some(Duration::ZERO));
+    assert_eq!(t.host_limit("api.example"), 2);
+    // Other hosts are unaffected
+    assert_eq!(t.host_limit("cdn.example"), 8);
+
+    // One step up per window of `limit` successes
+    t.record("api.example", 200, None);
+    assert_eq!(t.host_limit("api.example"), 2);
+    t.record("api.example", 200, None);
+    assert_eq!(t.host_limit("api.example"), 3);
+    for _ in 0..100 {
+        t.record("api.example", 404, None);
+    }
+    assert_eq!(t.host_limit("api.example"), 8);
+
+    let hosts = t.throttled_hosts();
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].throttled, 2);
+}
+
+#[tokio::test]
+async fn test_retry_after_pauses_host() {
+    let t = Throttle::new(10, 2);
+    t.record("api.example", 429, Some(Duration::from_millis(300)));
+    let start = Instant::now();
+    let _p = t.acquire("api.example").await;
+    assert!(start.elapsed() >= Duration::from_millis(250));
+    // Other hosts don't wait
+    let start = Instant::now();
+    let _q = t.acquire("cdn.example").await;
+    assert!(start.elapsed() < Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_limit_caps_concurrency() {
+    let t = Arc::new(Throttle::new(10, 4));
+    t.record("api.example", 429, Some(Duration::ZERO));
+    t.record("api.example", 429, Some(Duration::ZERO));
+    assert_eq!(t.host_limit("api.example"), 1);
+
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let tasks: Vec<_> = (0..4).map(|_| {
+        let (t, active, peak) = (t.clone(), active.clone(), peak.clone());
+        tokio::spawn(async move {
+            let _p = t.acquire("api.example").await;
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+        })
+    }).collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(peak.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_probe_retries_after_429() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    // The first attempt (HEAD, then GET) is throttled; everything after succeeds
+    let base = common::serve(move |_| {
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+            Response::new(429).header("Retry-After", "0")
+        } else {
+            Response::json(200, "{}")
+        }
+    }).await;
+
+    let client = reqwest::Client::new();
+    let throttle = Throttle::new(10, 4);
+    let opts = ProbeOptions { method_matrix: false, ..ProbeOptions::new(5, 3) };
+    let ev = probe_url(&client, &format!("{}/api", base), Some(&throttle), &opts).await.unwrap();
+    assert_eq!(ev.status, 200);
+    assert_eq!(ev.attempts, 2);
+    assert_eq!(throttle.host_limit("127.0.0.1"), 2);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+    assert_eq!(throttle.throttled_hosts()[0].throttled, 1);
+}
+
+#[tokio::test]
+async fn test_waf_blocks_lower_timing() {
+    let t = Throttle::new(100, 8);
+    // Below the threshold: nothing changes
+    for i in 0..20 {
+        assert!(t.record_block("api.example", i % 5 == 0).is_none());
+    }
+    assert_eq!(t.host_limit("api.example"), 8);
+
+    let mut steps = Vec::new();
+    for _ in 0..40 {
+        if let Some(s) = t.record_block("api.example", true) {
+            steps.push(s);
+        }
+    }
+    // One step per window of blocks, three at most
+    assert_eq!(steps.iter().map(|s| (s.level, s.per_host)).collect::<Vec<_>>(), vec![(1, 4), (2, 2), (3, 1)]);
+    assert_eq!(steps[0].delay, Duration::from_millis(250));
+    assert!(steps[0].block_rate > 0.3);
+    assert_eq!(t.host_limit("api.example"), 1);
+    assert!(t.record_block("cdn.example", false).is_none());
+    assert_eq!(t.host_limit("cdn.example"), 8);
+
+    // AIMD recovers up to the lowered ceiling only
+    let t = Throttle::new(100, 8);
+    for _ in 0..10 {
+        t.record_block("api.example", true);
+    }
+    for _ in 0..100 {
+        t.record("api.example", 200, None);
+    }
+    assert_eq!(t.host_limit("api.example"), 4);
+    assert_eq!(t.slowdowns().len(), 1);
+    assert!(t.throttled_hosts().is_empty());
+
+    // Requests are spaced out
+    let start = Instant::now();
+    drop(t.acquire("api.example").await);
+    drop(t.acquire("api.example").await);
+    assert!(start.elapsed() >= Duration::from_millis(240));
+}
+
+#[tokio::test]
+async fn test_probe_slows_down_on_block_pages() {
+    let base = common::serve(|_| {
+        Response::html(403, "<html><title>Access denied</title>Blocked by the web application firewall</html>")
+    }).await;
+
+    let client = reqwest::Client::new();
+    let throttle = Throttle::new(10, 4);
+    // Aggressive mode ignores 429s, not blocks
+    let opts = ProbeOptions { aggressive: true, ..ProbeOptions::new(5, 1) };
+    for i in 0..10 {
+        let ev = probe_url(&client, &format!("{}/api/{}", base, i), Some(&throttle), &opts).await.unwrap();
+        assert!(ev.annotations.contains(&api_hunter::output::Annotation::WafBlock));
+    }
+    let slowdowns = throttle.slowdowns();
+    assert_eq!(slowdowns.len(), 1);
+    assert_eq!((slowdowns[0].host.as_str(), slowdowns[0].per_host), ("127.0.0.1", 2));
+}
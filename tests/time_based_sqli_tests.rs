@@ -0,0 +1,53 @@
+use api_hunter::probe::advanced_tests::{time_based_payloads, AdvancedTester, Severity, TimingBaseline};
+use common::Response;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_baseline_and_payloads() {
+    let baseline = TimingBaseline::from_samples(&[100, 120, 80, 100, 100]);
+    assert_eq!(baseline.mean_ms, 100.0);
+    assert!(baseline.stddev_ms > 10.0 && baseline.stddev_ms < 15.0);
+    assert!(baseline.is_delayed(5200, 5));
+    assert!(!baseline.is_delayed(3000, 5));
+
+    // Jittery endpoint: a 1 s delay inside the normal spread isn't a signal
+    let jittery = TimingBaseline::from_samples(&[200, 1800, 300, 1500, 250]);
+    assert!(!jittery.is_delayed(1900, 1));
+
+    let payloads = time_based_payloads(7);
+    for dbms in ["MySQL", "PostgreSQL", "MSSQL"] {
+        assert!(payloads.iter().any(|(d, _)| *d == dbms));
+    }
+    assert!(payloads.iter().all(|(_, p)| p.contains('7') && !p.contains('#')));
+}
+
+/// Sleeps for `SLEEP(n)` in a quoted `id` (MySQL behind a string concatenation); `slow` sleeps on every request
+async fn mysql_app() -> String {
+    let sleep = regex::Regex::new(r"^1' AND SLEEP\((\d+)\)").unwrap();
+    common::serve(move |req| {
+        let response = Response::json(200, "{\"id\":1,\"name\":\"alice\"}");
+        let id = req.param("id").unwrap_or_default();
+        match sleep.captures(&id) {
+            Some(c) if req.path() == "/users" => response.delay(Duration::from_secs(c[1].parse().unwrap())),
+            _ => response,
+        }
+    }).await
+}
+
+#[tokio::test]
+async fn test_time_based_detection() {
+    let base = mysql_app().await;
+
+    let tester = AdvancedTester::new(&format!("{}/users", base)).unwrap().with_sql_delay(1);
+    let results = tester.test_time_based_sql_injection("id").await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].severity, Severity::Critical);
+    assert!(results[0].vulnerability.as_deref().unwrap().contains("MySQL"));
+    assert!(results[0].details.contains("SLEEP(1)"));
+
+    // Same app without the injectable query
+    let tester = AdvancedTester::new(&format!("{}/items", base)).unwrap().with_sql_delay(1);
+    assert!(tester.test_time_based_sql_injection("id").await.is_empty());
+}
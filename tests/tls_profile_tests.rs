@@ -0,0 +1,116 @@
+use api_hunter::http_client::HttpVersion;
+use api_hunter::tls_profile::{self, TlsProfile};
+use parking_lot::Mutex;
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::{CipherSuite, SignatureScheme};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[derive(Debug, Default, Clone)]
+struct Hello {
+    cipher_suites: Vec<CipherSuite>,
+    signature_schemes: Vec<SignatureScheme>,
+    alpn: Vec<Vec<u8>>,
+    request: String,
+}
+
+/// Records what the client offered, then hands out the self-signed certificate
+struct Capture {
+    key: Arc<CertifiedKey>,
+    seen: Arc<Mutex<Hello>>,
+}
+
+impl ResolvesServerCert for Capture {
+    fn resolve(&self, hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        let mut seen = self.seen.lock();
+        seen.cipher_suites = hello.cipher_suites().to_vec();
+        seen.signature_schemes = hello.signature_schemes().to_vec();
+        seen.alpn = hello.alpn().map(|a| a.map(|p| p.to_vec()).collect()).unwrap_or_default();
+        Some(self.key.clone())
+    }
+}
+
+/// HTTPS server that answers one request with a 204 over HTTP/1.1
+async fn tls_server() -> (String, Arc<Mutex<Hello>>) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let key = rustls::sign::any_supported_type(&rustls::PrivateKey(cert.serialize_private_key_der())).unwrap();
+    let seen = Arc::new(Mutex::new(Hello::default()));
+    let mut config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(Arc::new(Capture {
+            key: Arc::new(CertifiedKey::new(vec![rustls::Certificate(cert.serialize_der().unwrap())], key)),
+            seen: seen.clone(),
+        }));
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("https://localhost:{}/", listener.local_addr().unwrap().port());
+    let log = seen.clone();
+    tokio::spawn(async move {
+        let (sock, _) = listener.accept().await.unwrap();
+        let mut tls = acceptor.accept(sock).await.unwrap();
+        let mut buf = [0u8; 4096];
+        let n = tls.read(&mut buf).await.unwrap_or(0);
+        log.lock().request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+        let _ = tls.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").await;
+        let _ = tls.shutdown().await;
+    });
+    (url, seen)
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(TlsProfile::parse("Chrome120"), Some(TlsProfile::Chrome120));
+    assert_eq!(TlsProfile::parse("safari17"), Some(TlsProfile::Safari17));
+    assert_eq!(TlsProfile::parse("edge"), None);
+    for profile in TlsProfile::ALL {
+        assert_eq!(TlsProfile::parse(profile.name()), Some(profile));
+    }
+}
+
+#[tokio::test]
+async fn test_client_hello_follows_profile() {
+    let (url, seen) = tls_server().await;
+    tls_profile::set_profile(Some(TlsProfile::Firefox121));
+    let client = api_hunter::http_client::builder("tests::tls_profile").build().unwrap();
+    tls_profile::set_profile(None);
+    let resp = client.get(&url).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+
+    let hello = seen.lock().clone();
+    assert_eq!(&hello.cipher_suites[..3], &[
+        CipherSuite::TLS13_AES_128_GCM_SHA256,
+        CipherSuite::TLS13_CHACHA20_POLY1305_SHA256,
+        CipherSuite::TLS13_AES_256_GCM_SHA384,
+    ]);
+    assert_eq!(&hello.signature_schemes[..2], &[SignatureScheme::ECDSA_NISTP256_SHA256, SignatureScheme::ECDSA_NISTP384_SHA384]);
+    assert_eq!(hello.alpn, vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+    assert!(hello.request.contains("user-agent: mozilla/5.0 (windows nt 10.0; win64; x64; rv:121.0)"));
+    assert!(hello.request.contains("accept-language: en-us,en;q=0.5"));
+
+    // --http-version 1 narrows the ALPN list
+    let (url, seen) = tls_server().await;
+    api_hunter::http_client::configure_http_version(HttpVersion::Http1);
+    tls_profile::set_profile(Some(TlsProfile::Chrome120));
+    let client = api_hunter::http_client::builder("tests::tls_profile").build().unwrap();
+    tls_profile::set_profile(None);
+    api_hunter::http_client::configure_http_version(HttpVersion::Auto);
+    let resp = client.get(&url).send().await.unwrap();
+    assert_eq!(resp.version(), reqwest::Version::HTTP_11);
+    let hello = seen.lock().clone();
+    assert_eq!(hello.alpn, vec![b"http/1.1".to_vec()]);
+    assert_eq!(hello.cipher_suites[1], CipherSuite::TLS13_AES_256_GCM_SHA384);
+    assert!(hello.request.contains("sec-ch-ua-platform: \"windows\""));
+}
+
+#[test]
+fn test_http_version_parse() {
+    assert_eq!(HttpVersion::parse("auto"), Some(HttpVersion::Auto));
+    assert_eq!(HttpVersion::parse("1"), Some(HttpVersion::Http1));
+    assert_eq!(HttpVersion::parse("2"), Some(HttpVersion::Http2));
+    assert_eq!(HttpVersion::parse("3"), None);
+    assert_eq!(HttpVersion::Http2.alpn(), vec![b"h2".to_vec()]);
+    assert_eq!(api_hunter::http_client::version_label(reqwest::Version::HTTP_2), "HTTP/2");
+}
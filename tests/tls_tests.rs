@@ -0,0 +1,101 @@
+use api_hunter::output::clean_reporter::Severity;
+use api_hunter::probe::tls::{self, name_matches, CertInfo, TlsIssue};
+use rcgen::{date_time_ymd, Certificate, CertificateParams};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+fn cert_der(sans: &[&str], not_after: (i32, u8, u8)) -> Vec<u8> {
+    let mut params = CertificateParams::new(sans.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+    params.not_before = date_time_ymd(2019, 1, 1);
+    params.not_after = date_time_ymd(not_after.0, not_after.1, not_after.2);
+    Certificate::from_params(params).unwrap().serialize_der().unwrap()
+}
+
+#[test]
+fn test_name_matches() {
+    assert!(name_matches("api.example.com", "API.example.com"));
+    assert!(name_matches("*.example.com", "api.example.com"));
+    assert!(!name_matches("*.example.com", "example.com"));
+    assert!(!name_matches("*.example.com", "a.b.example.com"));
+    assert!(!name_matches("api.example.com", "www.example.com"));
+}
+
+#[test]
+fn test_expired_self_signed_cert() {
+    let der = cert_der(&["api.example.com", "*.cdn.example.com", "other.org"], (2020, 1, 1));
+    let info = CertInfo::from_der("api.example.com", 443, &der, SystemTime::now()).unwrap();
+    assert!(info.self_signed);
+    assert!(info.days_left < 0);
+    assert_eq!(info.signature_algorithm, "ecdsaWithSHA256");
+    assert!(matches!(info.issues[0], TlsIssue::Expired { days } if days > 365));
+    assert!(info.issues.contains(&TlsIssue::SelfSigned));
+    assert!(!info.issues.contains(&TlsIssue::HostnameMismatch));
+    assert!(!info.issues.iter().any(|i| matches!(i, TlsIssue::WeakSignature { .. })));
+    assert_eq!(info.in_scope_sans("example.com"), vec!["api.example.com", "cdn.example.com"]);
+
+    let other = CertInfo::from_der("www.example.com", 443, &der, SystemTime::now()).unwrap();
+    assert!(other.issues.contains(&TlsIssue::HostnameMismatch));
+}
+
+#[test]
+fn test_expiring_soon() {
+    let der = cert_der(&["api.example.com"], (2030, 1, 10));
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_893_456_000); // 2030-01-01
+    let info = CertInfo::from_der("api.example.com", 443, &der, now).unwrap();
+    assert_eq!(info.issues[0], TlsIssue::ExpiresSoon { days: 9 });
+    assert_eq!(info.issues[0].severity(), Severity::Low);
+}
+
+#[test]
+fn test_client_hello_lengths() {
+    let hello = tls::client_hello("api.example.com", 1);
+    assert_eq!(&hello[..3], &[0x16, 3, 1]);
+    assert_eq!(u16::from_be_bytes([hello[3], hello[4]]) as usize, hello.len() - 5);
+    assert_eq!(hello[5], 0x01);
+    assert_eq!(&hello[9..11], &[3, 1]);
+    assert!(hello.windows(15).any(|w| w == b"api.example.com"));
+    // No SNI for addresses
+    assert!(tls::client_hello("127.0.0.1", 2).len() < hello.len());
+}
+
+#[test]
+fn test_targets() {
+    let urls = vec![
+        "https://api.example.com/v1/users".to_string(),
+        "https://api.example.com:8443/admin".to_string(),
+        "https://www.example.com/api".to_string(),
+        "http://plain.example.com/api".to_string(),
+    ];
+    let targets: Vec<(String, u16)> = tls::targets(&urls).into_iter().collect();
+    assert_eq!(targets, vec![("api.example.com".to_string(), 8443), ("www.example.com".to_string(), 443)]);
+}
+
+#[tokio::test]
+async fn test_inspect_local_server() {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(vec![rustls::Certificate(cert.serialize_der().unwrap())], rustls::PrivateKey(cert.serialize_private_key_der()))
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let Ok((sock, _)) = listener.accept().await else { break };
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let _ = acceptor.accept(sock).await;
+            });
+        }
+    });
+
+    let info = tls::inspect("127.0.0.1", port, Duration::from_secs(5)).await.unwrap();
+    assert_eq!(info.tls_version.as_deref(), Some("TLS 1.3"));
+    assert_eq!(info.sans, vec!["localhost"]);
+    assert!(info.issues.contains(&TlsIssue::SelfSigned));
+    assert!(info.issues.contains(&TlsIssue::HostnameMismatch));
+    // rustls refuses anything below TLS 1.2
+    assert!(tls::legacy_protocols("127.0.0.1", port, Duration::from_secs(5)).await.is_empty());
+}
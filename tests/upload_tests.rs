@@ -0,0 +1,114 @@
+use api_hunter::fuzz::upload::{accepted, cases, multipart, stored_location, upload_forms, Marker, UploadTarget, UploadTest, UploadTester};
+use api_hunter::http_client::HttpClient;
+use api_hunter::output::clean_reporter::Severity;
+use common::Response;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_discovery_and_bodies() {
+    let html = r#"<form action="/profile/avatar" method="post" enctype="multipart/form-data">
+        <input type="hidden" name="csrf" value="t0k3n"><input type="file" name="picture"><input type="submit" value="Go">
+        </form><form action="/search"><input name="q"></form>"#;
+    let forms = upload_forms(html, "https://shop.test/profile");
+    assert_eq!(forms, vec![UploadTarget {
+        url: "https://shop.test/profile/avatar".to_string(),
+        field: "picture".to_string(),
+        fields: vec![("csrf".to_string(), "t0k3n".to_string())],
+    }]);
+    assert!(UploadTarget::from_path("https://shop.test/api/v1/Upload").is_some());
+    assert!(UploadTarget::from_path("https://shop.test/api/v1/users").is_none());
+
+    let body = String::from_utf8(multipart("XX", &[("a".to_string(), "1".to_string())], "file", "../x\".txt", "text/plain", b"hi")).unwrap();
+    assert_eq!(body, "--XX\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n--XX\r\nContent-Disposition: form-data; name=\"file\"; filename=\"../x%22.txt\"\r\nContent-Type: text/plain\r\n\r\nhi\r\n--XX--\r\n");
+
+    let marker = Marker::new();
+    let all = cases(&marker);
+    assert!(all.iter().all(|c| c.filename.contains(&marker.stem)));
+    // The evaluated output never appears in a payload's source
+    assert!(all.iter().filter(|c| c.test != UploadTest::PathTraversal && !c.filename.ends_with(".html") && c.test != UploadTest::SvgScript)
+        .all(|c| !String::from_utf8_lossy(&c.content).contains(&marker.joined())));
+
+    assert!(accepted(201, "{\"ok\":true}"));
+    assert!(!accepted(200, "File type not allowed"));
+    assert!(!accepted(413, ""));
+    assert_eq!(
+        stored_location("https://shop.test/api/upload", None, r#"{"url":"\/files\/apihunter-1.php"}"#, "apihunter-1").as_deref(),
+        Some("https://shop.test/files/apihunter-1.php")
+    );
+    assert_eq!(stored_location("https://shop.test/api/upload", Some("/f/apihunter-1.txt"), "", "apihunter-1").as_deref(), Some("https://shop.test/f/apihunter-1.txt"));
+    assert_eq!(stored_location("https://shop.test/api/upload", None, "{\"ok\":true}", "apihunter-1"), None);
+}
+
+/// Stored files: name -> (content type, content)
+type Files = Arc<Mutex<HashMap<String, (String, Vec<u8>)>>>;
+
+/// Upload endpoint storing files under `/files/<name>` and serving them back; `.php` files are
+/// "executed" (string concatenation evaluated). With `allow` set, other extensions are rejected.
+async fn upload_app(allow: Option<&'static [&'static str]>) -> String {
+    let files: Files = Arc::new(Mutex::new(HashMap::new()));
+    let php = regex::Regex::new(r"<\?php echo '([^']*)'\.'([^']*)'; \?>").unwrap();
+    let base = common::serve(move |req| {
+        let not_found = || Response::new(404).header("Content-Type", "text/plain");
+        if let Some(name) = req.path().strip_prefix("/files/") {
+            match files.lock().get(name) {
+                Some((ctype, content)) if name.ends_with(".php") => {
+                    let out = php.replace_all(&String::from_utf8_lossy(content), "$1$2").into_owned();
+                    Response::new(200).header("Content-Type", ctype.as_str()).body(out)
+                }
+                Some((ctype, content)) => Response::new(200).header("Content-Type", ctype.as_str()).body(content.clone()),
+                None => not_found(),
+            }
+        } else if req.path() == "/upload" {
+            let text = req.text();
+            let part = text.split("filename=\"").nth(1).unwrap_or_default();
+            let filename = part.split('"').next().unwrap_or_default();
+            let ctype = part.split("Content-Type: ").nth(1).and_then(|c| c.split("\r\n").next()).unwrap_or_default().to_string();
+            let content = part.split_once("\r\n\r\n").map(|(_, c)| c.rsplit_once("\r\n--").map(|(c, _)| c).unwrap_or(c)).unwrap_or_default();
+            let name = filename.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+            let ext = name.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+            if allow.is_some_and(|a| !a.contains(&ext.as_str())) {
+                Response::json(400, "{\"error\":\"file type not allowed\"}")
+            } else {
+                files.lock().insert(name.clone(), (ctype, content.as_bytes().to_vec()));
+                Response::json(201, format!("{{\"url\":\"/files/{}\"}}", name))
+            }
+        } else {
+            not_found()
+        }
+    }).await;
+    format!("{}/upload", base)
+}
+
+#[tokio::test]
+async fn test_unfiltered_upload_executes() {
+    let url = upload_app(None).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::upload").build().unwrap());
+    let tester = UploadTester::new(&http, Duration::from_secs(5));
+    let report = tester.test(&UploadTarget::from_path(&url).unwrap()).await;
+    assert!(report.baseline.as_deref().is_some_and(|b| b.ends_with(".txt")));
+    assert_eq!(report.rejected, 0);
+    let php = report.findings.iter().find(|f| f.test == UploadTest::DangerousExtension && f.filename.ends_with(".php")).expect("php accepted");
+    assert_eq!(php.severity, Severity::Critical);
+    assert!(php.stored_url.as_deref().is_some_and(|u| u.contains("/files/")));
+    let svg = report.findings.iter().find(|f| f.test == UploadTest::SvgScript).unwrap();
+    assert_eq!(svg.severity, Severity::High);
+    // Traversal stripped to the base name, no entity resolution
+    assert!(!report.findings.iter().any(|f| matches!(f.test, UploadTest::PathTraversal | UploadTest::XmlEntity)));
+}
+
+#[tokio::test]
+async fn test_extension_allowlist_is_clean() {
+    let url = upload_app(Some(&["txt", "png"])).await;
+    let http = HttpClient::new(api_hunter::http_client::builder("tests::upload").build().unwrap());
+    let tester = UploadTester::new(&http, Duration::from_secs(5));
+    let report = tester.test(&UploadTarget::from_path(&url).unwrap()).await;
+    assert!(report.baseline.is_some());
+    assert!(report.findings.is_empty(), "{:?}", report.findings);
+    // Only the traversal files (.txt) got through
+    assert_eq!(report.accepted, 4);
+}
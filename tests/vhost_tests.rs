@@ -0,0 +1,56 @@
+use api_hunter::discover::vhost::{candidates, Fingerprint, VhostScanner};
+use common::Response;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+mod common;
+
+fn page(title: &str, extra: &str) -> Vec<u8> {
+    format!("<html><head><title>{}</title></head><body>{}</body></html>", title, extra).into_bytes()
+}
+
+#[test]
+fn test_fingerprint_matching() {
+    let h = HeaderMap::new();
+    let default = Fingerprint::new(200, &h, &page("Welcome to nginx!", "If you see this page, the nginx web server is successfully installed."));
+    // Default pages echoing the requested name are still the default vhost
+    let echoed = Fingerprint::new(200, &h, &page("Welcome to nginx!", "If you see this page, the nginx web server is successfully installed. admin.example.com"));
+    assert!(default.matches(&echoed));
+    assert_eq!(default.title.as_deref(), Some("Welcome to nginx!"));
+
+    assert!(!default.matches(&Fingerprint::new(200, &h, &page("Jenkins", "Dashboard"))));
+    assert!(!default.matches(&Fingerprint::new(404, &h, &page("Welcome to nginx!", "If you see this page, the nginx web server is successfully installed."))));
+}
+
+#[test]
+fn test_candidates() {
+    let subs = vec!["api.example.com".to_string(), "example.com".to_string(), "*.example.com".to_string()];
+    let words = vec!["admin".to_string(), "# comment".to_string(), "api".to_string()];
+    let c = candidates("Example.com", &subs, &words);
+    assert_eq!(c, vec![
+        ("api.example.com".to_string(), "subdomains".to_string()),
+        ("admin.example.com".to_string(), "wordlist".to_string()),
+    ]);
+}
+
+#[tokio::test]
+async fn test_hidden_vhost_found() {
+    let base = common::serve(|req| {
+        let body = match req.header("host").unwrap_or_default().to_ascii_lowercase().as_str() {
+            "internal-admin.apihunter.invalid" => page("Admin Console", "login"),
+            "apihunter.invalid" => page("Example Shop", "products"),
+            _ => page("Default", "no site configured"),
+        };
+        Response::new(200).body(body)
+    }).await;
+
+    let words: Vec<String> = ["www", "internal-admin", "dev"].iter().map(|s| s.to_string()).collect();
+    let cands = candidates("apihunter.invalid", &[], &words);
+    let scanner = VhostScanner::new(Duration::from_secs(5), 4).unwrap();
+    let hits = scanner.scan(&base, "apihunter.invalid", &cands).await;
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].host, "internal-admin.apihunter.invalid");
+    assert_eq!(hits[0].fingerprint.title.as_deref(), Some("Admin Console"));
+    assert!(!hits[0].resolves);
+}
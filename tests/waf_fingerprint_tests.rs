@@ -0,0 +1,72 @@
+use api_hunter::http_client::HttpClient;
+use api_hunter::waf::fingerprint::{Observed, ATTACK_QUERY};
+use api_hunter::waf::{WafDetector, WafType};
+use common::Response;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+mod common;
+
+fn observed(status: u16, headers: &[(&str, &str)], body: &str) -> Observed {
+    let headers: BTreeMap<String, String> = headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Observed { status, headers, body: body.to_string() }
+}
+
+#[test]
+fn test_fingerprint_scoring() {
+    let detector = WafDetector::new();
+
+    // Cookie name alone is weak; the block page of the attack request confirms it
+    let benign = observed(200, &[("set-cookie", "__cf_bm=abc; path=/\nsession=1")], "{}");
+    let attack = observed(403, &[("set-cookie", "__cf_bm=abc; path=/")], "<title>Attention Required! | Cloudflare</title> Ray ID: 8f2a");
+    let passive = detector.fingerprint("a.test", "https://a.test/", &benign, Some(&benign.clone()));
+    assert_eq!(passive.waf_type, WafType::None);
+    let active = detector.fingerprint("a.test", "https://a.test/", &benign, Some(&attack));
+    assert_eq!(active.waf_type, WafType::Cloudflare);
+    assert!(active.blocks_attacks);
+    assert!(active.confidence >= 0.7);
+    assert!(active.evidence[0].contains("refused with 403"));
+    assert!(active.evidence.iter().any(|e| e.starts_with("Block page:")));
+
+    // Refusal without any signature
+    let unknown = detector.fingerprint("b.test", "https://b.test/", &observed(200, &[], "ok"), Some(&observed(406, &[], "Not Acceptable")));
+    assert_eq!(unknown.waf_type, WafType::Unknown("Unidentified WAF".into()));
+    assert_eq!(unknown.confidence, 0.5);
+
+    // A dropped attack request counts as a refusal
+    assert!(detector.fingerprint("b.test", "https://b.test/", &observed(200, &[], "ok"), None).blocks_attacks);
+
+    // Application errors are not WAF blocks
+    let app = detector.fingerprint("c.test", "https://c.test/", &observed(200, &[], "ok"), Some(&observed(400, &[], "bad id")));
+    assert_eq!(app.waf_type, WafType::None);
+    assert!(!app.blocks_attacks);
+
+    // Cookie patterns match names: `ts` (F5) must not match `status=` or `pts=`
+    let cookies = observed(200, &[("set-cookie", "status=1\npts=2"), ("server", "nginx")], "");
+    assert_eq!(detector.fingerprint("d.test", "https://d.test/", &cookies, Some(&cookies.clone())).waf_type, WafType::None);
+}
+
+/// Imperva-style edge: the benign request passes, the attack request gets the incident page
+async fn imperva_app() -> String {
+    let base = common::serve(|req| {
+        if req.target.contains(ATTACK_QUERY) {
+            Response::new(403).header("Set-Cookie", "visid_incap_1234=abc; path=/").body("<html>Request unsuccessful. Incapsula incident ID: 123-456</html>")
+        } else {
+            Response::new(200).header("Set-Cookie", "incap_ses_99_1234=xyz; path=/").body("{\"status\":\"ok\"}")
+        }
+    }).await;
+    format!("{}/", base)
+}
+
+#[tokio::test]
+async fn test_fingerprint_host() {
+    let base = imperva_app().await;
+    let http = HttpClient::new(reqwest::Client::new());
+    let fp = WafDetector::new().fingerprint_host(&http, &base, Duration::from_secs(5)).await.expect("fingerprint");
+    assert_eq!(fp.host, "127.0.0.1");
+    assert_eq!(fp.waf_type, WafType::Imperva);
+    assert_eq!((fp.benign_status, fp.attack_status), (200, Some(403)));
+    assert!(fp.blocks_attacks);
+    assert!(fp.confidence > 0.6);
+    assert!(fp.evidence.iter().any(|e| e.contains("incap")));
+}
@@ -0,0 +1,113 @@
+use api_hunter::fuzz::json_fuzzer::{BodySeed, Indicator, JsonFuzzer, SeedSource};
+use api_hunter::fuzz::param_bruteforce::ParamLocation;
+use api_hunter::fuzz::ssti::{Syntax, SstiTester};
+use api_hunter::http_client::HttpClient;
+use api_hunter::waf::bypass::{BypassTechnique, WafBypass};
+use api_hunter::waf::mutation::{blocked, with_raw_param, Context, WafMutators};
+use api_hunter::waf::WafType;
+use common::Response;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_transforms() {
+    let sql = "' OR 1=1";
+    assert_eq!(BypassTechnique::UrlEncoding.tamper("'a", Context::Query).unwrap(), "%27%61");
+    assert_eq!(BypassTechnique::DoubleUrlEncoding.tamper("'a", Context::Query).unwrap(), "%2527%2561");
+    assert_eq!(BypassTechnique::UnicodeEncoding.tamper("'a", Context::Query).unwrap(), "%u0027a");
+    assert_eq!(BypassTechnique::UnicodeEncoding.tamper("'a", Context::Json).unwrap(), r#""\u0027a""#);
+    assert_eq!(BypassTechnique::MixedCaseEncoding.tamper("union select", Context::Json).unwrap(), r#""UnIoN sElEcT""#);
+    assert_eq!(BypassTechnique::InlineComments.tamper(sql, Context::Query).unwrap(), "%27%2F%2A%2A%2FOR%2F%2A%2A%2F1%3D1");
+    assert_eq!(BypassTechnique::NullByteInjection.tamper("'", Context::Json).unwrap(), r#""\u0000'""#);
+    // Unchanged or meaningless in the context: nothing to send
+    assert!(BypassTechnique::InlineComments.tamper("'\"", Context::Json).is_none());
+    assert!(BypassTechnique::MixedCaseEncoding.tamper("'\"", Context::Query).is_none());
+    assert!(BypassTechnique::UrlEncoding.tamper(sql, Context::Json).is_none());
+    assert!(BypassTechnique::PathObfuscation.tamper(sql, Context::Query).is_none());
+
+    // Raw JSON literals still parse to the payload (the null byte aside)
+    for t in [BypassTechnique::UnicodeEncoding, BypassTechnique::MixedCaseEncoding] {
+        let literal = t.tamper("{{7*7}} ../x", Context::Json).unwrap();
+        assert_eq!(serde_json::from_str::<String>(&literal).unwrap().to_ascii_lowercase(), "{{7*7}} ../x");
+    }
+
+    assert_eq!(with_raw_param("http://a.test/p?x=1&q=old", "q", "%u0027"), "http://a.test/p?x=1&q=%u0027");
+    assert_eq!(with_raw_param("http://a.test/p", "q", "%27"), "http://a.test/p?q=%27");
+}
+
+#[test]
+fn test_strategies() {
+    assert_eq!(WafType::from_name("Cloudflare"), WafType::Cloudflare);
+    assert_eq!(WafType::from_name("Imperva Incapsula"), WafType::Imperva);
+    assert_eq!(WafType::from_name("F5 BIG-IP"), WafType::F5BigIP);
+    assert_eq!(WafType::from_name("Acme Shield"), WafType::Unknown("Acme Shield".to_string()));
+
+    let transforms = WafBypass::new(WafType::Cloudflare).payload_transforms();
+    assert!(!transforms.is_empty());
+    assert!(transforms.iter().all(BypassTechnique::is_payload_transform));
+    assert!(transforms.iter().any(|t| matches!(t, BypassTechnique::UnicodeEncoding)));
+    assert!(!WafBypass::new(WafType::Unknown("x".into())).payload_transforms().is_empty());
+
+    assert!(blocked(200, 403, "<h1>Access denied</h1> Ray ID: 1234"));
+    assert!(!blocked(403, 403, "Access denied"));
+    assert!(!blocked(200, 500, "You have an error in your SQL syntax"));
+}
+
+/// A WAF that blocks raw `'`, `{{` and `../` in the request, in front of an app that decodes JSON (and
+/// `\u` escapes with it): SQL errors on quotes, `{{a*b}}` evaluated
+async fn waf_app() -> String {
+    let curly = Regex::new(r"\{\{(\d+)\*(\d+)\}\}").unwrap();
+    common::serve(move |req| {
+        let raw_body = req.text();
+        let inspected = format!("{} {}", req.target, raw_body);
+        if ["'", "%27", "{{", "%7B%7B", "../"].iter().any(|p| inspected.contains(p)) {
+            return Response::json(403, "<html><h1>Access denied</h1><p>Blocked by security policy. Ray ID: 8f2a</p></html>");
+        }
+        let body: Value = serde_json::from_str(&raw_body).unwrap_or(Value::Null);
+        let value = body.as_object().and_then(|o| o.values().next()).and_then(Value::as_str).unwrap_or_default();
+        if value.contains('\'') {
+            Response::json(500, "{\"error\":\"You have an error in your SQL syntax near ''\"}")
+        } else {
+            let rendered = curly.replace_all(value, |c: &regex::Captures| (c[1].parse::<u64>().unwrap() * c[2].parse::<u64>().unwrap()).to_string());
+            Response::json(200, json!({ "greeting": format!("Hello {}", rendered) }).to_string())
+        }
+    }).await
+}
+
+#[tokio::test]
+async fn test_json_fuzzer_through_waf() {
+    let base = waf_app().await;
+    let http = HttpClient::new(reqwest::Client::new());
+    let seed = BodySeed { method: "POST".into(), url: format!("{}/api/search", base), body: json!({ "q": "shoes" }), source: SeedSource::Spec };
+
+    // Without mutation the WAF answers every payload
+    let plain = JsonFuzzer::new(&http, Duration::from_secs(5)).run(&seed).await;
+    assert!(!plain.iter().any(|f| f.indicator == Indicator::SqlError));
+
+    let mut waf = WafMutators::new();
+    waf.insert("127.0.0.1", WafType::Cloudflare);
+    let findings = JsonFuzzer::new(&http, Duration::from_secs(5)).with_waf(&waf).run(&seed).await;
+    let sql = findings.iter().find(|f| f.indicator == Indicator::SqlError).expect("SQL error behind the WAF");
+    assert!(matches!(sql.waf_bypass, Some(BypassTechnique::UnicodeEncoding)));
+
+    let records = waf.records();
+    assert!(records.iter().any(|r| r.target == "/q" && r.blocked_status == 403 && r.waf == "Cloudflare"
+        && matches!(r.technique, Some(BypassTechnique::UnicodeEncoding)) && r.status == Some(500)));
+}
+
+#[tokio::test]
+async fn test_ssti_through_waf() {
+    let base = waf_app().await;
+    let http = HttpClient::new(reqwest::Client::new());
+    let url = format!("{}/api/greet", base);
+    assert!(SstiTester::new(&http, Duration::from_secs(5)).test(&url, "name", ParamLocation::Json).await.is_none());
+
+    let mut waf = WafMutators::new();
+    waf.insert("127.0.0.1", WafType::AwsWaf);
+    let finding = SstiTester::new(&http, Duration::from_secs(5)).with_waf(&waf).test(&url, "name", ParamLocation::Json).await.expect("SSTI behind the WAF");
+    assert_eq!(finding.syntax, Syntax::Curly);
+    assert!(matches!(finding.waf_bypass, Some(BypassTechnique::UnicodeEncoding)));
+}
@@ -0,0 +1,85 @@
+use api_hunter::waf::fingerprint::Observed;
+use api_hunter::waf::signatures::{embedded, load_from, update, SignatureFile, EMBEDDED};
+use api_hunter::waf::{WafDetector, WafType};
+use common::Response;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+mod common;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("api_hunter_sigs_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const CUSTOM: &str = r#"{
+    "version": 7,
+    "signatures": [
+        { "waf": "Radware AppWall", "headers": { "X-SL-CompState": "" }, "cookies": ["rdwr"], "body_patterns": ["Unauthorized Activity Has Been Detected"] }
+    ]
+}"#;
+
+#[test]
+fn test_embedded_signatures() {
+    let file = SignatureFile::parse(EMBEDDED).unwrap();
+    assert!(file.version >= 1);
+    let signatures = embedded();
+    assert_eq!(signatures.len(), file.signatures.len());
+    // Every built-in name maps to its type
+    assert!(signatures.iter().all(|s| !matches!(s.waf_type, WafType::Unknown(_) | WafType::None)));
+    assert!(signatures.iter().any(|s| s.waf_type == WafType::Cloudflare && s.cookies.contains(&"__cfduid".to_string())));
+
+    assert!(SignatureFile::parse(r#"{"signatures": []}"#).is_err());
+    assert!(SignatureFile::parse(r#"{"signatures": [{"waf": "X"}]}"#).is_err());
+    assert!(SignatureFile::parse(r#"{"signatures": [{"waf": "", "cookies": ["a"]}]}"#).is_err());
+    assert!(SignatureFile::parse("<html>").is_err());
+}
+
+#[test]
+fn test_user_signatures() {
+    let dir = temp_dir("user");
+    let path = dir.join("waf_signatures.json");
+    std::fs::write(&path, CUSTOM).unwrap();
+
+    let signatures = load_from(Some(&path));
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].waf_type, WafType::Unknown("Radware AppWall".into()));
+    // Header names and body patterns are normalized for matching
+    assert_eq!(signatures[0].headers, vec![("x-sl-compstate".to_string(), String::new())]);
+
+    let detector = WafDetector::with_signatures(signatures);
+    let headers: BTreeMap<String, String> = [("x-sl-compstate".to_string(), "1".to_string()), ("set-cookie".to_string(), "rdwr_sess=1".to_string())].into();
+    let benign = Observed { status: 200, headers: headers.clone(), body: "ok".into() };
+    let attack = Observed { status: 403, headers, body: "<h1>Unauthorized Activity Has Been Detected</h1>".into() };
+    let fp = detector.fingerprint("a.test", "https://a.test/", &benign, Some(&attack));
+    assert_eq!(fp.waf, "Radware AppWall");
+    assert!(fp.blocks_attacks);
+
+    // Broken or missing files fall back to the built-in set
+    std::fs::write(&path, "{ not json").unwrap();
+    assert_eq!(load_from(Some(&path)).len(), embedded().len());
+    assert_eq!(load_from(Some(&dir.join("missing.json"))).len(), embedded().len());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+async fn serve(body: &'static str) -> String {
+    format!("{}/signatures.json", common::serve(move |_| Response::json(200, body)).await)
+}
+
+#[tokio::test]
+async fn test_update() {
+    let dir = temp_dir("update");
+    let path = dir.join("nested").join("waf_signatures.json");
+    let client = reqwest::Client::new();
+
+    let file = update(&client, &serve(CUSTOM).await, &path).await.unwrap();
+    assert_eq!((file.version, file.signatures.len()), (7, 1));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CUSTOM);
+
+    // An invalid download leaves the installed set alone
+    assert!(update(&client, &serve(r#"{"signatures": []}"#).await, &path).await.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CUSTOM);
+    let _ = std::fs::remove_dir_all(&dir);
+}
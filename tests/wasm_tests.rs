@@ -0,0 +1,50 @@
+use api_hunter::gather::wasm::{data_segments, endpoint_candidates, extract_strings, wasm_urls};
+
+/// Minimal module with one active data segment at offset 1024
+fn module(data: &[u8]) -> Vec<u8> {
+    let mut segment = vec![0x01, 0x00, 0x41, 0x80, 0x08, 0x0B, data.len() as u8];
+    segment.extend_from_slice(data);
+    let mut out = b"\0asm\x01\0\0\0".to_vec();
+    // An empty custom section before the data section
+    out.extend_from_slice(&[0x00, 0x01, 0x00]);
+    out.push(11);
+    out.push(segment.len() as u8);
+    out.extend_from_slice(&segment);
+    out
+}
+
+#[test]
+fn test_data_segment_strings() {
+    let data = b"\0\0https://api.example.com/v1/users/api/v2/orders/{id}\0\x01\x02short\0sk_live_51Habcdefghijklmnopqrstuv\0";
+    let bytes = module(data);
+
+    let segments = data_segments(&bytes).unwrap();
+    assert_eq!(segments, vec![&data[..]]);
+
+    let strings = extract_strings(&bytes);
+    assert!(strings.iter().any(|s| s.starts_with("https://api.example.com")));
+    assert!(!strings.iter().any(|s| s == "short"));
+
+    let endpoints = endpoint_candidates(&strings);
+    assert!(endpoints.contains(&"https://api.example.com/v1/users/api/v2/orders/{id}".to_string()));
+}
+
+#[test]
+fn test_unparseable_module_falls_back_to_raw_scan() {
+    let mut bytes = module(b"/api/internal/flags");
+    bytes.truncate(bytes.len() - 2);
+    assert!(data_segments(&bytes).is_err());
+    let strings = extract_strings(&bytes);
+    assert!(endpoint_candidates(&strings).contains(&"/api/internal/fla".to_string()));
+}
+
+#[test]
+fn test_wasm_references() {
+    let js = r#"const u = new URL("pkg/app_bg.wasm", import.meta.url); fetch("/static/main.wasm?v=3"); "https://cdn.example.com/x.wasm""#;
+    let urls = wasm_urls(js, "https://example.com/assets/index.js");
+    assert_eq!(urls, vec![
+        "https://cdn.example.com/x.wasm",
+        "https://example.com/assets/pkg/app_bg.wasm",
+        "https://example.com/static/main.wasm",
+    ]);
+}
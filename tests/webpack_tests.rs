@@ -0,0 +1,43 @@
+use api_hunter::gather::webpack::chunk_urls;
+
+#[test]
+fn builds_chunk_urls_from_runtime_maps() {
+    // Webpack 5 / CRA style runtime
+    let cra = r#"r.p="/";r.u=e=>"static/js/"+e+"."+{12:"a1b2c3d4",34:"e5f6a7b8"}[e]+".chunk.js";r.miniCssF=e=>"static/css/"+e+"."+{12:"aaaa1111"}[e]+".chunk.css";"#;
+    let urls = chunk_urls(cra, "https://example.com/static/js/runtime-main.js");
+    assert_eq!(urls, vec![
+        "https://example.com/static/js/12.a1b2c3d4.chunk.js",
+        "https://example.com/static/js/34.e5f6a7b8.chunk.js",
+    ]);
+
+    // Named chunks with "auto" public path
+    let named = r#"o.p="auto";o.u=function(e){return"js/"+({5:"admin"}[e]||e)+"-"+{5:"0f0f0f0f",7:"1e1e1e1e"}[e]+".js"}"#;
+    let urls = chunk_urls(named, "https://example.com/app/js/runtime.js");
+    assert_eq!(urls, vec![
+        "https://example.com/app/js/7-1e1e1e1e.js",
+        "https://example.com/app/js/admin-0f0f0f0f.js",
+    ]);
+}
+
+#[test]
+fn finds_dynamic_imports_and_manifest_chunks() {
+    use api_hunter::gather::webpack::{dynamic_chunk_urls, manifest_js_urls, next_build_id};
+
+    let vite = r#"const A=()=>__vitePreload(()=>import("./Admin-3f2a.js"),["assets/Admin-3f2a.js","assets/Admin.css","assets/api-9c9c.js"]);"#;
+    assert_eq!(dynamic_chunk_urls(vite, "https://example.com/assets/index-1111.js"), vec![
+        "https://example.com/assets/Admin-3f2a.js",
+        "https://example.com/assets/api-9c9c.js",
+    ]);
+
+    let next = r#"self.__BUILD_MANIFEST={"/admin":["static/chunks/pages/admin-1a2b.js"],sortedPages:["/admin"]};"#;
+    assert_eq!(dynamic_chunk_urls(next, "https://example.com/_next/static/abc/_buildManifest.js"), vec![
+        "https://example.com/_next/static/chunks/pages/admin-1a2b.js",
+    ]);
+    assert_eq!(next_build_id(r#"<script id="__NEXT_DATA__">{"page":"/","buildId":"abc"}</script>"#).as_deref(), Some("abc"));
+
+    let cra = r#"{"files":{"main.js":"/static/js/main.1a.js","static/js/787.2b.chunk.js":"/static/js/787.2b.chunk.js","main.css":"/static/css/main.css"},"entrypoints":["static/js/main.1a.js"]}"#;
+    assert_eq!(manifest_js_urls(cra, "https://example.com/asset-manifest.json"), vec![
+        "https://example.com/static/js/787.2b.chunk.js",
+        "https://example.com/static/js/main.1a.js",
+    ]);
+}
@@ -0,0 +1,103 @@
+use api_hunter::probe::websocket::{self, WebSocketTester, OP_CLOSE, OP_TEXT};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn test_accept_key_and_frames() {
+    // Example from RFC 6455 section 1.3
+    assert_eq!(websocket::accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+    let frame = websocket::encode_frame(OP_TEXT, b"Hello", [0x37, 0xfa, 0x21, 0x3d]);
+    assert_eq!(frame, vec![0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]);
+    assert_eq!(websocket::decode_frame(&frame), Some((OP_TEXT, b"Hello".to_vec(), 11)));
+    // Unmasked server frame, extended length
+    let mut long = vec![0x82, 126, 0x01, 0x00];
+    long.extend(vec![b'a'; 256]);
+    assert_eq!(websocket::decode_frame(&long).map(|(op, p, n)| (op, p.len(), n)), Some((0x2, 256, 260)));
+    assert!(websocket::decode_frame(&long[..100]).is_none());
+}
+
+/// Server frame with `text`
+fn server_frame(text: &str) -> Vec<u8> {
+    let mut frame = vec![0x81, text.len() as u8];
+    frame.extend(text.as_bytes());
+    frame
+}
+
+/// A socket server on `/ws`. `lenient` accepts any Origin, greets clients with account data and echoes
+/// messages unchanged; otherwise only its own origin is accepted and messages are answered with an ack.
+async fn serve(lenient: bool) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+    let own_origin = format!("http://{}", base);
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut sock, _)) = listener.accept().await else { break };
+            let own_origin = own_origin.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 4096];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]).to_string();
+                let header = |name: &str| req.lines()
+                    .find(|l| l.to_ascii_lowercase().starts_with(&format!("{}:", name)))
+                    .map(|l| l.split_once(':').unwrap().1.trim().to_string());
+                let path = req.split_whitespace().nth(1).unwrap_or("/");
+                let origin_ok = lenient || header("origin").as_deref() == Some(own_origin.as_str());
+                let (Some(key), "/ws", true) = (header("sec-websocket-key"), path, origin_ok) else {
+                    let _ = sock.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+                    return;
+                };
+                let resp = format!("HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\nSec-WebSocket-Protocol: chat\r\n\r\n", websocket::accept_key(&key));
+                if sock.write_all(resp.as_bytes()).await.is_err() {
+                    return;
+                }
+                if lenient {
+                    let _ = sock.write_all(&server_frame(r#"{"user":"admin","balance":1200}"#)).await;
+                }
+                let mut pending = Vec::new();
+                loop {
+                    let n = sock.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        return;
+                    }
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some((opcode, payload, used)) = websocket::decode_frame(&pending) {
+                        pending.drain(..used);
+                        if opcode == OP_CLOSE {
+                            return;
+                        }
+                        let reply = if lenient { String::from_utf8_lossy(&payload).into_owned() } else { r#"{"ack":true}"#.to_string() };
+                        let _ = sock.write_all(&server_frame(&reply)).await;
+                    }
+                }
+            });
+        }
+    });
+    base
+}
+
+#[tokio::test]
+async fn test_lenient_socket() {
+    let base = serve(true).await;
+    let tester = WebSocketTester::with_timeout(Duration::from_secs(3));
+    let report = tester.analyze(&format!("ws://{}/ws", base)).await.unwrap();
+    assert_eq!(report.protocol.as_deref(), Some("chat"));
+    assert!(!report.origin_validated && report.unauthenticated && report.echoes_messages);
+    let names: Vec<&str> = report.vulnerabilities.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["Unencoded Message Reflection", "Missing Origin Validation", "Unauthenticated Data Stream"]);
+    assert!(report.vulnerabilities[1].description.contains("evil.example"));
+    assert!(report.vulnerabilities[2].details.contains("admin"));
+}
+
+#[tokio::test]
+async fn test_strict_socket() {
+    let base = serve(false).await;
+    let tester = WebSocketTester::with_timeout(Duration::from_secs(3));
+    let report = tester.analyze(&format!("ws://{}/ws", base)).await.unwrap();
+    assert!(report.origin_validated && !report.echoes_messages);
+    assert!(report.vulnerabilities.is_empty());
+
+    // Not a socket endpoint
+    assert!(tester.analyze(&format!("ws://{}/other", base)).await.is_none());
+    assert_eq!(tester.discover_websocket(&format!("http://{}", base)).await, vec![format!("ws://{}/ws", base)]);
+}